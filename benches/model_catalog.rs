@@ -108,7 +108,7 @@ fn bench_capabilities_contains_all(c: &mut Criterion) {
     let caps = ModelCapabilities::from_bits(0b1111_1111_1111_1111);
     let required = ModelCapabilities::from_bits(0b0000_0101_0101_0101);
     c.bench_function("capabilities/contains_all", |b| {
-        b.iter(|| black_box(caps.contains_all(black_box(required))))
+        b.iter(|| black_box(caps.contains_all(black_box(&required))))
     });
 }
 
@@ -117,10 +117,10 @@ fn bench_capabilities_intersects(c: &mut Criterion) {
     let b_caps = ModelCapabilities::from_bits(0b0101_0101);
     let c_caps = ModelCapabilities::from_bits(0b1000_0000);
     c.bench_function("capabilities/intersects_disjoint", |b| {
-        b.iter(|| black_box(a.intersects(black_box(b_caps))))
+        b.iter(|| black_box(a.intersects(black_box(&b_caps))))
     });
     c.bench_function("capabilities/intersects_overlap", |b| {
-        b.iter(|| black_box(a.intersects(black_box(c_caps))))
+        b.iter(|| black_box(a.intersects(black_box(&c_caps))))
     });
 }
 
@@ -128,15 +128,15 @@ fn bench_capabilities_merge(c: &mut Criterion) {
     let a = ModelCapabilities::from_bits(0b1010_1010);
     let b = ModelCapabilities::from_bits(0b0101_0101);
     c.bench_function("capabilities/merge", |b_iter| {
-        b_iter.iter(|| black_box(a.merge(black_box(b))))
+        b_iter.iter(|| black_box(a.merge(black_box(&b))))
     });
 }
 
 fn bench_capabilities_disable_mask(c: &mut Criterion) {
     c.bench_function("capabilities/disable_mask", |b| {
         b.iter(|| {
-            let mut caps = ModelCapabilities::all();
-            caps.disable_mask(black_box(0b1111_0000));
+            let mut caps = ModelCapabilities::all(64);
+            caps.disable_mask(black_box(&ModelCapabilities::from_bits(0b1111_0000)));
             black_box(caps)
         })
     });
@@ -149,17 +149,15 @@ fn bench_capabilities_disable_mask(c: &mut Criterion) {
 fn bench_mask_to_names(c: &mut Criterion) {
     let names = sample_model_names(16);
     let registry = ModelRegistry::new(&names);
-    let model_mask = 0b1010_0101_0011_1100u64;
+    let model_mask = ModelCapabilities::from_bits(0b1010_0101_0011_1100);
 
     c.bench_function("registry/mask_to_names", |b| {
         b.iter(|| {
-            let mut result = Vec::new();
-            for idx in 0..registry.len() {
-                let bit = 1u64 << idx;
-                if (black_box(model_mask) & bit) != 0 {
-                    result.push(registry.get_name(idx).to_string());
-                }
-            }
+            let result: Vec<String> = black_box(&model_mask)
+                .iter()
+                .filter(|&idx| idx < registry.len())
+                .map(|idx| registry.get_name(idx).to_string())
+                .collect();
             black_box(result)
         })
     });
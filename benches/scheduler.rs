@@ -17,6 +17,7 @@ use std::time::Duration;
 use chrono::Utc;
 use serde_json::json;
 
+use pollux::config::Priority;
 use pollux::model_catalog::ModelCapabilities;
 use pollux::providers::geminicli::resource::GeminiCliResource;
 use pollux::providers::traits::scheduler::ResourceScheduler;
@@ -25,8 +26,8 @@ use pollux::providers::traits::scheduler::ResourceScheduler;
 // Helpers
 // ---------------------------------------------------------------------------
 
-fn mask(index: usize) -> u64 {
-    1u64 << index
+fn mask(index: usize) -> ModelCapabilities {
+    ModelCapabilities::single(index)
 }
 
 fn make_credential(project_id: &str) -> GeminiCliResource {
@@ -53,9 +54,9 @@ fn make_expired_credential(project_id: &str) -> GeminiCliResource {
 
 fn setup_manager(model_count: usize, cred_count: u64) -> ResourceScheduler<GeminiCliResource> {
     let mut manager = ResourceScheduler::<GeminiCliResource>::new(model_count);
-    let all_caps = ModelCapabilities::all().bits();
+    let all_caps = ModelCapabilities::all(model_count);
     for id in 1..=cred_count {
-        manager.add_credential(id, make_credential(&format!("proj-{id}")), all_caps);
+        manager.add_credential(id, make_credential(&format!("proj-{id}")), all_caps.clone());
     }
     manager
 }
@@ -68,7 +69,7 @@ fn bench_get_assigned_single_cred(c: &mut Criterion) {
     let mut manager = setup_manager(4, 1);
 
     c.bench_function("scheduler/get_assigned_1_cred", |b| {
-        b.iter(|| black_box(manager.get_assigned(mask(0), None)))
+        b.iter(|| black_box(manager.get_assigned(&mask(0), None, None, Priority::Normal)))
     });
 }
 
@@ -76,7 +77,7 @@ fn bench_get_assigned_10_creds(c: &mut Criterion) {
     let mut manager = setup_manager(4, 10);
 
     c.bench_function("scheduler/get_assigned_10_creds", |b| {
-        b.iter(|| black_box(manager.get_assigned(mask(0), None)))
+        b.iter(|| black_box(manager.get_assigned(&mask(0), None, None, Priority::Normal)))
     });
 }
 
@@ -84,7 +85,7 @@ fn bench_get_assigned_100_creds(c: &mut Criterion) {
     let mut manager = setup_manager(8, 100);
 
     c.bench_function("scheduler/get_assigned_100_creds", |b| {
-        b.iter(|| black_box(manager.get_assigned(mask(0), None)))
+        b.iter(|| black_box(manager.get_assigned(&mask(0), None, None, Priority::Normal)))
     });
 }
 
@@ -95,7 +96,7 @@ fn bench_get_assigned_round_robin(c: &mut Criterion) {
         b.iter(|| {
             // Simulate 10 sequential assignments (full rotation)
             for _ in 0..10 {
-                black_box(manager.get_assigned(mask(0), None));
+                black_box(manager.get_assigned(&mask(0), None, None, Priority::Normal));
             }
         })
     });
@@ -109,7 +110,7 @@ fn bench_get_assigned_different_models(c: &mut Criterion) {
         b.iter(|| {
             let m = mask(model_idx % 8);
             model_idx += 1;
-            black_box(manager.get_assigned(m, None))
+            black_box(manager.get_assigned(&m, None, None, Priority::Normal))
         })
     });
 }
@@ -120,22 +121,26 @@ fn bench_get_assigned_different_models(c: &mut Criterion) {
 
 fn bench_get_assigned_with_expired(c: &mut Criterion) {
     let mut manager = ResourceScheduler::<GeminiCliResource>::new(4);
-    let all_caps = ModelCapabilities::all().bits();
+    let all_caps = ModelCapabilities::all(4);
 
     // Add 5 expired + 5 valid credentials
     for id in 1..=5 {
         manager.add_credential(
             id,
             make_expired_credential(&format!("proj-expired-{id}")),
-            all_caps,
+            all_caps.clone(),
         );
     }
     for id in 6..=10 {
-        manager.add_credential(id, make_credential(&format!("proj-valid-{id}")), all_caps);
+        manager.add_credential(
+            id,
+            make_credential(&format!("proj-valid-{id}")),
+            all_caps.clone(),
+        );
     }
 
     c.bench_function("scheduler/get_assigned_skip_expired", |b| {
-        b.iter(|| black_box(manager.get_assigned(mask(0), None)))
+        b.iter(|| black_box(manager.get_assigned(&mask(0), None, None, Priority::Normal)))
     });
 }
 
@@ -147,7 +152,7 @@ fn bench_get_assigned_with_refreshing(c: &mut Criterion) {
     }
 
     c.bench_function("scheduler/get_assigned_skip_refreshing", |b| {
-        b.iter(|| black_box(manager.get_assigned(mask(0), None)))
+        b.iter(|| black_box(manager.get_assigned(&mask(0), None, None, Priority::Normal)))
     });
 }
 
@@ -155,11 +160,11 @@ fn bench_get_assigned_with_unsupported(c: &mut Criterion) {
     let mut manager = setup_manager(4, 10);
     // Mark half as unsupported for model 0
     for id in 1..=5 {
-        manager.mark_model_unsupported(id, mask(0));
+        manager.mark_model_unsupported(id, &mask(0));
     }
 
     c.bench_function("scheduler/get_assigned_skip_unsupported", |b| {
-        b.iter(|| black_box(manager.get_assigned(mask(0), None)))
+        b.iter(|| black_box(manager.get_assigned(&mask(0), None, None, Priority::Normal)))
     });
 }
 
@@ -174,7 +179,7 @@ fn bench_cooldown_report_and_drain(c: &mut Criterion) {
         b.iter(|| {
             let id = (counter % 10) + 1;
             counter += 1;
-            manager.report_rate_limit(id, mask(0), Duration::from_secs(60));
+            manager.report_rate_limit(id, &mask(0), Duration::from_secs(60));
         })
     });
 }
@@ -183,7 +188,7 @@ fn bench_process_waiting_room_empty(c: &mut Criterion) {
     let mut manager = setup_manager(4, 10);
 
     c.bench_function("scheduler/get_assigned_empty_waitroom", |b| {
-        b.iter(|| black_box(manager.get_assigned(mask(0), None)))
+        b.iter(|| black_box(manager.get_assigned(&mask(0), None, None, Priority::Normal)))
     });
 }
 
@@ -195,13 +200,13 @@ fn bench_process_waiting_room_with_expired_cooldowns(c: &mut Criterion) {
                 let mut manager = setup_manager(4, 20);
                 // Add cooldowns in the past (will be drained immediately)
                 for id in 1..=20 {
-                    manager.report_rate_limit(id, mask(0), Duration::from_nanos(1));
+                    manager.report_rate_limit(id, &mask(0), Duration::from_nanos(1));
                 }
                 // Give the cooldowns time to expire
                 std::thread::sleep(Duration::from_micros(10));
 
                 let start = std::time::Instant::now();
-                black_box(manager.get_assigned(mask(0), None));
+                black_box(manager.get_assigned(&mask(0), None, None, Priority::Normal));
                 total += start.elapsed();
             }
             total
@@ -216,14 +221,14 @@ fn bench_process_waiting_room_with_expired_cooldowns(c: &mut Criterion) {
 fn bench_add_credential(c: &mut Criterion) {
     c.bench_function("scheduler/add_credential_4_models", |b| {
         let mut manager = ResourceScheduler::<GeminiCliResource>::new(4);
-        let all_caps = ModelCapabilities::all().bits();
+        let all_caps = ModelCapabilities::all(4);
         let mut counter = 0u64;
         b.iter(|| {
             counter += 1;
             manager.add_credential(
                 counter,
                 make_credential(&format!("proj-{counter}")),
-                all_caps,
+                all_caps.clone(),
             );
         })
     });
@@ -232,14 +237,14 @@ fn bench_add_credential(c: &mut Criterion) {
 fn bench_add_credential_16_models(c: &mut Criterion) {
     c.bench_function("scheduler/add_credential_16_models", |b| {
         let mut manager = ResourceScheduler::<GeminiCliResource>::new(16);
-        let all_caps = ModelCapabilities::all().bits();
+        let all_caps = ModelCapabilities::all(16);
         let mut counter = 0u64;
         b.iter(|| {
             counter += 1;
             manager.add_credential(
                 counter,
                 make_credential(&format!("proj-{counter}")),
-                all_caps,
+                all_caps.clone(),
             );
         })
     });
@@ -268,7 +273,7 @@ fn bench_get_assigned_1000_creds(c: &mut Criterion) {
     let mut manager = setup_manager(8, 1000);
 
     c.bench_function("scheduler/get_assigned_1000_creds", |b| {
-        b.iter(|| black_box(manager.get_assigned(mask(0), None)))
+        b.iter(|| black_box(manager.get_assigned(&mask(0), None, None, Priority::Normal)))
     });
 }
 
@@ -278,7 +283,7 @@ fn bench_get_assigned_round_robin_1000(c: &mut Criterion) {
     c.bench_function("scheduler/round_robin_1000_creds", |b| {
         b.iter(|| {
             for _ in 0..1000 {
-                black_box(manager.get_assigned(mask(0), None));
+                black_box(manager.get_assigned(&mask(0), None, None, Priority::Normal));
             }
         })
     });
@@ -288,27 +293,27 @@ fn bench_get_assigned_all_exhausted(c: &mut Criterion) {
     let mut manager = ResourceScheduler::<GeminiCliResource>::new(4);
     // No credentials at all
     c.bench_function("scheduler/get_assigned_empty", |b| {
-        b.iter(|| black_box(manager.get_assigned(mask(0), None)))
+        b.iter(|| black_box(manager.get_assigned(&mask(0), None, None, Priority::Normal)))
     });
 }
 
 fn bench_get_assigned_all_cooling(c: &mut Criterion) {
     let mut manager = setup_manager(4, 10);
     for id in 1..=10 {
-        manager.report_rate_limit(id, mask(0), Duration::from_secs(3600));
+        manager.report_rate_limit(id, &mask(0), Duration::from_secs(3600));
     }
     c.bench_function("scheduler/get_assigned_all_cooling_10", |b| {
-        b.iter(|| black_box(manager.get_assigned(mask(0), None)))
+        b.iter(|| black_box(manager.get_assigned(&mask(0), None, None, Priority::Normal)))
     });
 }
 
 fn bench_get_assigned_all_cooling_1000(c: &mut Criterion) {
     let mut manager = setup_manager(8, 1000);
     for id in 1..=1000 {
-        manager.report_rate_limit(id, mask(0), Duration::from_secs(3600));
+        manager.report_rate_limit(id, &mask(0), Duration::from_secs(3600));
     }
     c.bench_function("scheduler/get_assigned_all_cooling_1000", |b| {
-        b.iter(|| black_box(manager.get_assigned(mask(0), None)))
+        b.iter(|| black_box(manager.get_assigned(&mask(0), None, None, Priority::Normal)))
     });
 }
 
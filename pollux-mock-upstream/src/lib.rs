@@ -0,0 +1,239 @@
+//! In-process stand-in for the upstreams Pollux talks to: the Google token
+//! endpoint, cloudcode-pa (`loadCodeAssist`, `onboardUser`,
+//! `generateContent`, `streamGenerateContent`) and the Codex responses
+//! endpoint.
+//!
+//! Every endpoint answers with a canned success unless a [`Fault`] was queued
+//! for it, in which case the next request gets that provider's native error
+//! body instead. Requests are recorded so tests can assert what Pollux sent.
+
+mod responses;
+
+use axum::extract::{Request, State};
+use axum::http::header::AUTHORIZATION;
+use axum::response::{IntoResponse, Response};
+use axum::routing::post;
+use axum::{Json, Router};
+use serde::Deserialize;
+use std::collections::{HashMap, VecDeque};
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex, PoisonError};
+use tokio::net::TcpListener;
+
+/// Companion project handed out by `onboardUser`.
+pub const PROJECT_ID: &str = "mock-project";
+
+/// Text of every successful generation.
+pub const REPLY_TEXT: &str = "Hello from the mock upstream.";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Endpoint {
+    /// `POST /token`, the OAuth refresh grant.
+    Token,
+    LoadCodeAssist,
+    OnboardUser,
+    /// Lists [`MockUpstream::set_available_models`] plus one exhausted model.
+    FetchAvailableModels,
+    GenerateContent,
+    StreamGenerateContent,
+    /// `POST /backend-api/codex/responses`.
+    CodexResponses,
+}
+
+/// Error the next request to an endpoint answers with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Fault {
+    /// 401 with an expired-token body.
+    Unauthorized,
+    /// 403 with a permission-denied body.
+    Forbidden,
+    /// 429 whose body (`RetryInfo` on cloudcode-pa, `resets_in_seconds` on
+    /// Codex) and `retry-after` header name the delay.
+    RateLimited { retry_after_secs: u64 },
+}
+
+/// One request as the mock received it.
+#[derive(Debug, Clone)]
+pub struct Recorded {
+    /// Bearer token, without the `Bearer ` prefix.
+    pub bearer: Option<String>,
+    pub body: String,
+}
+
+#[derive(Debug, Default)]
+struct Inner {
+    faults: HashMap<Endpoint, VecDeque<Fault>>,
+    requests: HashMap<Endpoint, Vec<Recorded>>,
+    tokens_issued: u64,
+    available_models: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default)]
+struct Shared(Arc<Mutex<Inner>>);
+
+impl Shared {
+    fn with<T>(&self, f: impl FnOnce(&mut Inner) -> T) -> T {
+        f(&mut self.0.lock().unwrap_or_else(PoisonError::into_inner))
+    }
+}
+
+/// A running mock upstream, serving until the runtime shuts down.
+#[derive(Debug, Clone)]
+pub struct MockUpstream {
+    addr: SocketAddr,
+    shared: Shared,
+}
+
+impl MockUpstream {
+    /// Serve on an ephemeral localhost port.
+    pub async fn spawn() -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("bind mock upstream listener");
+        Self::serve(listener)
+    }
+
+    /// Serve on `listener` in a background task.
+    pub fn serve(listener: TcpListener) -> Self {
+        let addr = listener.local_addr().expect("mock upstream local addr");
+        let shared = Shared::default();
+        let app = router(shared.clone());
+        tokio::spawn(async move {
+            axum::serve(listener, app)
+                .await
+                .expect("mock upstream server");
+        });
+        Self { addr, shared }
+    }
+
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// Base URL, usable as both the cloudcode-pa and the Codex `custom_api_url`.
+    pub fn url(&self) -> String {
+        format!("http://{}/", self.addr)
+    }
+
+    /// OAuth token endpoint URL.
+    pub fn token_url(&self) -> String {
+        format!("http://{}/token", self.addr)
+    }
+
+    /// Answer the next request to `endpoint` with `fault`. Faults queue up
+    /// and are consumed in order, one per request.
+    pub fn inject(&self, endpoint: Endpoint, fault: Fault) {
+        self.shared
+            .with(|inner| inner.faults.entry(endpoint).or_default().push_back(fault));
+    }
+
+    /// Models `fetchAvailableModels` reports with quota left. Default: none.
+    pub fn set_available_models(&self, models: &[&str]) {
+        let models = models.iter().map(ToString::to_string).collect();
+        self.shared.with(|inner| inner.available_models = models);
+    }
+
+    /// Requests received by `endpoint` so far, oldest first.
+    pub fn requests(&self, endpoint: Endpoint) -> Vec<Recorded> {
+        self.shared
+            .with(|inner| inner.requests.get(&endpoint).cloned().unwrap_or_default())
+    }
+
+    pub fn hits(&self, endpoint: Endpoint) -> usize {
+        self.shared
+            .with(|inner| inner.requests.get(&endpoint).map_or(0, Vec::len))
+    }
+}
+
+fn router(shared: Shared) -> Router {
+    let route = |endpoint| {
+        post(move |State(shared): State<Shared>, req: Request| handle(shared, endpoint, req))
+    };
+    Router::new()
+        .route("/token", route(Endpoint::Token))
+        .route(
+            "/v1internal:loadCodeAssist",
+            route(Endpoint::LoadCodeAssist),
+        )
+        .route("/v1internal:onboardUser", route(Endpoint::OnboardUser))
+        .route(
+            "/v1internal:fetchAvailableModels",
+            route(Endpoint::FetchAvailableModels),
+        )
+        .route(
+            "/v1internal:generateContent",
+            route(Endpoint::GenerateContent),
+        )
+        .route(
+            "/v1internal:streamGenerateContent",
+            route(Endpoint::StreamGenerateContent),
+        )
+        .route(
+            "/backend-api/codex/responses",
+            route(Endpoint::CodexResponses),
+        )
+        .route("/mock/faults", post(queue_fault))
+        .with_state(shared)
+}
+
+#[derive(Debug, Deserialize)]
+struct FaultRequest {
+    endpoint: Endpoint,
+    #[serde(flatten)]
+    fault: Fault,
+}
+
+/// `POST /mock/faults`: queue a fault over HTTP, for the standalone binary.
+async fn queue_fault(State(shared): State<Shared>, Json(req): Json<FaultRequest>) -> Response {
+    shared.with(|inner| {
+        inner
+            .faults
+            .entry(req.endpoint)
+            .or_default()
+            .push_back(req.fault);
+    });
+    axum::http::StatusCode::NO_CONTENT.into_response()
+}
+
+async fn handle(shared: Shared, endpoint: Endpoint, req: Request) -> Response {
+    let bearer = req
+        .headers()
+        .get(AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .map(str::to_string);
+    let body = axum::body::to_bytes(req.into_body(), usize::MAX)
+        .await
+        .map(|bytes| String::from_utf8_lossy(&bytes).into_owned())
+        .unwrap_or_default();
+
+    let (fault, token_seq, available_models) = shared.with(|inner| {
+        inner.requests.entry(endpoint).or_default().push(Recorded {
+            bearer,
+            body: body.clone(),
+        });
+        let fault = inner
+            .faults
+            .get_mut(&endpoint)
+            .and_then(VecDeque::pop_front);
+        if fault.is_none() && endpoint == Endpoint::Token {
+            inner.tokens_issued += 1;
+        }
+        (fault, inner.tokens_issued, inner.available_models.clone())
+    });
+
+    if let Some(fault) = fault {
+        return responses::fault(endpoint, fault);
+    }
+    match endpoint {
+        Endpoint::Token => responses::token(token_seq),
+        Endpoint::LoadCodeAssist => responses::load_code_assist(),
+        Endpoint::OnboardUser => responses::onboard_user(),
+        Endpoint::FetchAvailableModels => responses::fetch_available_models(&available_models),
+        Endpoint::GenerateContent => responses::generate_content(&body),
+        Endpoint::StreamGenerateContent => responses::stream_generate_content(&body),
+        Endpoint::CodexResponses => responses::codex_responses(&body),
+    }
+}
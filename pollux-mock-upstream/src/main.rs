@@ -0,0 +1,27 @@
+//! `pollux-mock-upstream [ADDR]`: serve the mock upstream on `ADDR`
+//! (default `127.0.0.1:8788`) for manual runs against a local Pollux.
+//!
+//! Point `providers.*.custom_api_url` / `api_url` at the printed URL and the
+//! token URLs at its `/token`. Queue faults with
+//! `curl -d '{"endpoint":"stream_generate_content","kind":"rate_limited","retry_after_secs":30}'
+//! -H 'content-type: application/json' <URL>mock/faults`.
+
+use pollux_mock_upstream::MockUpstream;
+use tokio::net::TcpListener;
+
+#[tokio::main]
+async fn main() {
+    let addr = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| "127.0.0.1:8788".to_string());
+    let listener = match TcpListener::bind(&addr).await {
+        Ok(listener) => listener,
+        Err(e) => {
+            eprintln!("cannot bind {addr}: {e}");
+            std::process::exit(1);
+        }
+    };
+    let mock = MockUpstream::serve(listener);
+    println!("mock upstream listening on {}", mock.url());
+    let _ = tokio::signal::ctrl_c().await;
+}
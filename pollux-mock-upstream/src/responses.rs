@@ -0,0 +1,238 @@
+//! Canned upstream bodies, success and error, in each provider's wire shape.
+
+use super::{Endpoint, Fault, PROJECT_ID, REPLY_TEXT};
+use axum::Json;
+use axum::http::StatusCode;
+use axum::http::header::{CONTENT_TYPE, RETRY_AFTER};
+use axum::response::{IntoResponse, Response};
+use chrono::{SecondsFormat, Utc};
+use serde_json::{Value, json};
+
+/// Unsigned JWT with `sub` = `mock-sub` and `email` = `mock@example.com`;
+/// Pollux only decodes the claims.
+const ID_TOKEN: &str = "eyJhbGciOiJub25lIiwidHlwIjoiSldUIn0.\
+    eyJzdWIiOiJtb2NrLXN1YiIsImVtYWlsIjoibW9ja0BleGFtcGxlLmNvbSIsImVtYWlsX3ZlcmlmaWVkIjp0cnVlfQ.";
+
+pub(super) fn token(seq: u64) -> Response {
+    Json(json!({
+        "access_token": format!("mock-access-{seq}"),
+        "token_type": "Bearer",
+        "expires_in": 3600,
+        "scope": "openid",
+        "id_token": ID_TOKEN,
+    }))
+    .into_response()
+}
+
+/// No companion project yet, so callers go on to `onboardUser`.
+pub(super) fn load_code_assist() -> Response {
+    Json(json!({
+        "currentTier": { "id": "free-tier", "quotaTier": "free-tier" },
+        "allowedTiers": [{ "id": "free-tier", "isDefault": true }],
+    }))
+    .into_response()
+}
+
+pub(super) fn onboard_user() -> Response {
+    Json(json!({
+        "name": "operations/mock-onboard",
+        "done": true,
+        "response": {
+            "cloudaicompanionProject": { "id": PROJECT_ID, "name": "Mock Project" }
+        },
+    }))
+    .into_response()
+}
+
+/// Model ids a credential may call; upstream drops `remainingFraction` once
+/// it reaches zero, as for `mock-exhausted`.
+pub(super) fn fetch_available_models(available: &[String]) -> Response {
+    let mut models = serde_json::Map::new();
+    for model in available {
+        models.insert(
+            model.clone(),
+            json!({ "displayName": model, "quotaInfo": { "remainingFraction": 1.0 } }),
+        );
+    }
+    models.insert(
+        "mock-exhausted".to_string(),
+        json!({ "quotaInfo": { "resetTime": rfc3339_after(3600) } }),
+    );
+    Json(json!({ "models": models })).into_response()
+}
+
+fn requested_model(body: &str) -> String {
+    serde_json::from_str::<Value>(body)
+        .ok()
+        .and_then(|v| v.get("model")?.as_str().map(str::to_string))
+        .unwrap_or_default()
+}
+
+fn gemini_chunk(model: &str, text: &str, last: bool) -> Value {
+    let mut candidate = json!({ "content": { "role": "model", "parts": [{ "text": text }] } });
+    let mut response = json!({ "modelVersion": model, "responseId": "mock-response" });
+    if last {
+        candidate["finishReason"] = json!("STOP");
+        response["usageMetadata"] = json!({
+            "promptTokenCount": 3,
+            "candidatesTokenCount": 6,
+            "totalTokenCount": 9,
+        });
+    }
+    response["candidates"] = json!([candidate]);
+    json!({ "response": response, "traceId": "mock-trace" })
+}
+
+pub(super) fn generate_content(body: &str) -> Response {
+    Json(gemini_chunk(&requested_model(body), REPLY_TEXT, true)).into_response()
+}
+
+pub(super) fn stream_generate_content(body: &str) -> Response {
+    let model = requested_model(body);
+    let (head, tail) = REPLY_TEXT.split_at(REPLY_TEXT.len() / 2);
+    let events = [
+        gemini_chunk(&model, head, false),
+        gemini_chunk(&model, tail, true),
+    ];
+    sse(events.iter().map(|event| format!("data: {event}\n\n")))
+}
+
+pub(super) fn codex_responses(body: &str) -> Response {
+    let model = requested_model(body);
+    let response = |status: &str, output: Value| {
+        json!({
+            "id": "resp_mock",
+            "object": "response",
+            "model": model,
+            "status": status,
+            "output": output,
+        })
+    };
+    let message = json!({
+        "type": "message",
+        "id": "msg_mock",
+        "role": "assistant",
+        "status": "completed",
+        "content": [{ "type": "output_text", "text": REPLY_TEXT, "annotations": [] }],
+    });
+    let mut completed = response("completed", json!([message]));
+    completed["usage"] = json!({ "input_tokens": 3, "output_tokens": 6, "total_tokens": 9 });
+
+    let events = [
+        json!({ "type": "response.created", "response": response("in_progress", json!([])) }),
+        json!({
+            "type": "response.output_text.delta",
+            "item_id": "msg_mock",
+            "output_index": 0,
+            "content_index": 0,
+            "delta": REPLY_TEXT,
+        }),
+        json!({ "type": "response.output_item.done", "output_index": 0, "item": message }),
+        json!({ "type": "response.completed", "response": completed }),
+    ];
+    sse(events.iter().map(|event| {
+        let kind = event["type"].as_str().unwrap_or_default();
+        format!("event: {kind}\ndata: {event}\n\n")
+    }))
+}
+
+fn sse(events: impl Iterator<Item = String>) -> Response {
+    (
+        [(CONTENT_TYPE, "text/event-stream")],
+        events.collect::<String>(),
+    )
+        .into_response()
+}
+
+pub(super) fn fault(endpoint: Endpoint, fault: Fault) -> Response {
+    let status = match fault {
+        Fault::Unauthorized => StatusCode::UNAUTHORIZED,
+        Fault::Forbidden => StatusCode::FORBIDDEN,
+        Fault::RateLimited { .. } => StatusCode::TOO_MANY_REQUESTS,
+    };
+    let body = match endpoint {
+        Endpoint::Token => oauth_error(fault),
+        Endpoint::CodexResponses => codex_error(fault),
+        _ => google_rpc_error(fault),
+    };
+    let mut resp = (status, Json(body)).into_response();
+    if let Fault::RateLimited { retry_after_secs } = fault {
+        resp.headers_mut()
+            .insert(RETRY_AFTER, retry_after_secs.into());
+    }
+    resp
+}
+
+fn oauth_error(fault: Fault) -> Value {
+    let (error, description) = match fault {
+        Fault::Unauthorized => ("invalid_client", "Unauthorized"),
+        Fault::Forbidden => ("unauthorized_client", "Forbidden"),
+        Fault::RateLimited { .. } => ("rate_limit_exceeded", "Too Many Requests"),
+    };
+    json!({ "error": error, "error_description": description })
+}
+
+fn google_rpc_error(fault: Fault) -> Value {
+    let error = match fault {
+        Fault::Unauthorized => json!({
+            "code": 401,
+            "message": "Request had invalid authentication credentials.",
+            "status": "UNAUTHENTICATED",
+        }),
+        Fault::Forbidden => json!({
+            "code": 403,
+            "message": "The caller does not have permission",
+            "status": "PERMISSION_DENIED",
+        }),
+        Fault::RateLimited { retry_after_secs } => json!({
+            "code": 429,
+            "message": format!(
+                "You have exhausted your capacity on this model. \
+                 Your quota will reset after {retry_after_secs}s."
+            ),
+            "status": "RESOURCE_EXHAUSTED",
+            "details": [
+                {
+                    "@type": "type.googleapis.com/google.rpc.ErrorInfo",
+                    "reason": "QUOTA_EXHAUSTED",
+                    "domain": "cloudcode-pa.googleapis.com",
+                    "metadata": {
+                        "quotaResetDelay": format!("{retry_after_secs}s"),
+                        "quotaResetTimeStamp": rfc3339_after(retry_after_secs),
+                    },
+                },
+                {
+                    "@type": "type.googleapis.com/google.rpc.RetryInfo",
+                    "retryDelay": format!("{retry_after_secs}s"),
+                },
+            ],
+        }),
+    };
+    json!({ "error": error })
+}
+
+fn codex_error(fault: Fault) -> Value {
+    let error = match fault {
+        Fault::Unauthorized => json!({
+            "message": "Provided authentication token is expired. Please try signing in again.",
+            "type": "invalid_request_error",
+            "code": "token_expired",
+        }),
+        Fault::Forbidden => json!({
+            "message": "You do not have access to this resource.",
+            "type": "permission_error",
+        }),
+        Fault::RateLimited { retry_after_secs } => json!({
+            "message": "The usage limit has been reached",
+            "type": "usage_limit_reached",
+            "resets_in_seconds": retry_after_secs,
+        }),
+    };
+    json!({ "error": error })
+}
+
+/// `now + secs` as an RFC 3339 UTC timestamp.
+fn rfc3339_after(secs: u64) -> String {
+    let after = chrono::Duration::seconds(i64::try_from(secs).unwrap_or(i64::MAX));
+    (Utc::now() + after).to_rfc3339_opts(SecondsFormat::Secs, true)
+}
@@ -0,0 +1,80 @@
+//! Anthropic Messages `count_tokens` request/response schema.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+/// Anthropic request body for `POST /v1/messages/count_tokens`.
+///
+/// Schema reference:
+/// https://docs.anthropic.com/en/api/messages-count-tokens
+///
+/// Content blocks are kept as raw JSON; only the fields Pollux counts are
+/// modeled, everything else lands in `extra`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnthropicCountTokensRequest {
+    /// Anthropic docs: `string`, required.
+    #[serde(default)]
+    pub model: String,
+
+    /// Anthropic docs: `array`, required.
+    #[serde(default)]
+    pub messages: Vec<AnthropicMessage>,
+
+    /// Anthropic docs: `string | array` of text blocks, optional.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub system: Option<Value>,
+
+    /// Anthropic docs: `array`, optional.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tools: Option<Vec<Value>>,
+
+    #[serde(default, flatten)]
+    pub extra: BTreeMap<String, Value>,
+}
+
+/// One entry of `messages`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnthropicMessage {
+    /// `user` or `assistant`.
+    pub role: String,
+
+    /// A string, or an array of `text` / `thinking` / `tool_use` /
+    /// `tool_result` / `image` / ... blocks.
+    #[serde(default)]
+    pub content: Value,
+
+    #[serde(default, flatten)]
+    pub extra: BTreeMap<String, Value>,
+}
+
+/// Anthropic response body for `POST /v1/messages/count_tokens`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AnthropicCountTokensResponse {
+    pub input_tokens: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn claude_code_request_parses() {
+        let request: AnthropicCountTokensRequest = serde_json::from_value(json!({
+            "model": "claude-sonnet-4-5",
+            "system": [{"type": "text", "text": "You are Claude Code."}],
+            "messages": [
+                {"role": "user", "content": "hi"},
+                {"role": "assistant", "content": [
+                    {"type": "tool_use", "id": "toolu_1", "name": "Read", "input": {"path": "a"}}
+                ]}
+            ],
+            "tools": [{"name": "Read", "input_schema": {"type": "object"}}]
+        }))
+        .unwrap();
+        assert_eq!(request.messages.len(), 2);
+        assert_eq!(request.messages[1].content[0]["name"], "Read");
+        assert_eq!(request.tools.as_ref().map(Vec::len), Some(1));
+    }
+}
@@ -0,0 +1,80 @@
+//! Anthropic error response schema.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+/// Anthropic error body, e.g.
+/// `{"type":"error","error":{"type":"rate_limit_error","message":"..."}}`.
+///
+/// Schema reference:
+/// https://docs.anthropic.com/en/api/errors
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnthropicErrorBody {
+    /// Always `error`.
+    #[serde(default = "error_type")]
+    pub r#type: String,
+
+    #[serde(default)]
+    pub error: AnthropicErrorObject,
+
+    #[serde(flatten)]
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub extra: BTreeMap<String, Value>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AnthropicErrorObject {
+    /// `invalid_request_error`, `authentication_error`, `permission_error`,
+    /// `not_found_error`, `rate_limit_error`, `api_error`, `overloaded_error`, ...
+    #[serde(default)]
+    pub r#type: String,
+
+    #[serde(default)]
+    pub message: String,
+
+    #[serde(flatten)]
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub extra: BTreeMap<String, Value>,
+}
+
+fn error_type() -> String {
+    "error".to_string()
+}
+
+impl AnthropicErrorBody {
+    pub fn new(r#type: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            r#type: error_type(),
+            error: AnthropicErrorObject {
+                r#type: r#type.into(),
+                message: message.into(),
+                extra: BTreeMap::new(),
+            },
+            extra: BTreeMap::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn new_serializes_the_error_envelope() {
+        let body = AnthropicErrorBody::new("invalid_request_error", "bad");
+        assert_eq!(
+            serde_json::to_value(&body).unwrap(),
+            json!({"type": "error", "error": {"type": "invalid_request_error", "message": "bad"}})
+        );
+    }
+
+    #[test]
+    fn parses_upstream_body_with_request_id() {
+        let raw = r#"{"type":"error","error":{"type":"overloaded_error","message":"Overloaded"},"request_id":"req_1"}"#;
+        let body: AnthropicErrorBody = serde_json::from_str(raw).unwrap();
+        assert_eq!(body.error.r#type, "overloaded_error");
+        assert_eq!(body.extra.get("request_id"), Some(&json!("req_1")));
+    }
+}
@@ -0,0 +1,56 @@
+//! Anthropic Messages request schema.
+
+use super::AnthropicMessage;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+/// Anthropic request body for `POST /v1/messages`.
+///
+/// Schema reference:
+/// https://docs.anthropic.com/en/api/messages
+///
+/// Only the fields Pollux routes on are modeled; everything else
+/// (`system`, `tools`, `thinking`, ...) is forwarded untouched via `extra`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnthropicMessagesRequest {
+    /// Anthropic docs: `string`, required.
+    #[serde(default)]
+    pub model: String,
+
+    /// Anthropic docs: `array`, required.
+    #[serde(default)]
+    pub messages: Vec<AnthropicMessage>,
+
+    /// Anthropic docs: `integer`, required.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_tokens: Option<u32>,
+
+    /// Anthropic docs: `boolean`, optional; `true` answers with SSE events.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub stream: bool,
+
+    #[serde(default, flatten)]
+    pub extra: BTreeMap<String, Value>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn roundtrip_preserves_unknown_fields() {
+        let input = json!({
+            "model": "claude-sonnet-4-5",
+            "max_tokens": 1024,
+            "system": "be brief",
+            "messages": [{"role": "user", "content": "hi"}],
+            "thinking": {"type": "enabled", "budget_tokens": 2048},
+        });
+        let parsed: AnthropicMessagesRequest = serde_json::from_value(input.clone()).unwrap();
+        assert_eq!(parsed.model, "claude-sonnet-4-5");
+        assert!(!parsed.stream);
+        assert_eq!(serde_json::to_value(&parsed).unwrap(), input);
+    }
+}
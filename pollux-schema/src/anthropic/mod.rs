@@ -0,0 +1,9 @@
+mod count_tokens;
+mod error;
+mod messages;
+
+pub use count_tokens::{
+    AnthropicCountTokensRequest, AnthropicCountTokensResponse, AnthropicMessage,
+};
+pub use error::{AnthropicErrorBody, AnthropicErrorObject};
+pub use messages::AnthropicMessagesRequest;
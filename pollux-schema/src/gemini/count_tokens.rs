@@ -0,0 +1,73 @@
+use super::{Content, GeminiGenerateContentRequest};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+/// Gemini `countTokens` request body.
+///
+/// Clients send either bare `contents` or a full `generateContentRequest`;
+/// both are normalized by [`Self::into_generate_request`].
+///
+/// Reference: <https://ai.google.dev/api/tokens#method:-models.counttokens>
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GeminiCountTokensRequest {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub contents: Vec<Content>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub generate_content_request: Option<GeminiGenerateContentRequest>,
+}
+
+impl GeminiCountTokensRequest {
+    /// The request whose turns are counted; `generateContentRequest` wins
+    /// when both are set.
+    #[must_use]
+    pub fn into_generate_request(self) -> GeminiGenerateContentRequest {
+        self.generate_content_request
+            .unwrap_or_else(|| GeminiGenerateContentRequest {
+                contents: self.contents,
+                system_instruction: None,
+                generation_config: None,
+                tools: None,
+                tool_config: None,
+                extra: BTreeMap::new(),
+            })
+    }
+}
+
+/// Gemini `countTokens` response body; Cloud Code returns it unwrapped.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GeminiCountTokensResponse {
+    pub total_tokens: u64,
+
+    /// `cachedContentTokenCount`, `promptTokensDetails`, ...
+    #[serde(default, flatten)]
+    pub extra: BTreeMap<String, Value>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn generate_content_request_takes_precedence() {
+        let request: GeminiCountTokensRequest = serde_json::from_value(json!({
+            "contents": [{"role": "user", "parts": [{"text": "bare"}]}],
+            "generateContentRequest": {
+                "model": "models/gemini-2.5-pro",
+                "contents": [{"role": "user", "parts": [{"text": "wrapped"}]}]
+            }
+        }))
+        .unwrap();
+
+        let contents = request.into_generate_request().contents;
+        assert_eq!(contents.len(), 1);
+        assert_eq!(
+            serde_json::to_value(&contents[0]).unwrap()["parts"][0]["text"],
+            "wrapped"
+        );
+    }
+}
@@ -0,0 +1,81 @@
+use super::Content;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+/// Gemini `embedContent` request body.
+///
+/// Reference: <https://ai.google.dev/api/embeddings#method:-models.embedcontent>
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GeminiEmbedContentRequest {
+    /// `models/<id>`; required per entry of a batch, optional otherwise.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+
+    pub content: Content,
+
+    /// `taskType`, `title`, `outputDimensionality`, ...
+    #[serde(default, flatten)]
+    pub extra: BTreeMap<String, Value>,
+}
+
+/// Gemini `batchEmbedContents` request body.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GeminiBatchEmbedContentsRequest {
+    pub requests: Vec<GeminiEmbedContentRequest>,
+}
+
+/// Either embedding RPC, as forwarded upstream.
+#[derive(Debug, Clone, Serialize)]
+#[serde(untagged)]
+pub enum GeminiEmbedRequest {
+    Single(GeminiEmbedContentRequest),
+    Batch(GeminiBatchEmbedContentsRequest),
+}
+
+impl GeminiEmbedRequest {
+    /// Point every entry at `models/{model}` so a batch cannot mix models
+    /// (the credential was leased for one).
+    pub fn set_model(&mut self, model: &str) {
+        let name = format!("models/{model}");
+        match self {
+            Self::Single(request) => request.model = Some(name),
+            Self::Batch(batch) => {
+                for request in &mut batch.requests {
+                    request.model = Some(name.clone());
+                }
+            }
+        }
+    }
+
+    #[must_use]
+    pub fn is_batch(&self) -> bool {
+        matches!(self, Self::Batch(_))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn batch_entries_are_pinned_to_the_route_model() {
+        let batch: GeminiBatchEmbedContentsRequest = serde_json::from_value(json!({
+            "requests": [
+                {"model": "models/other", "content": {"parts": [{"text": "a"}]}},
+                {"content": {"parts": [{"text": "b"}]}, "taskType": "RETRIEVAL_QUERY"}
+            ]
+        }))
+        .unwrap();
+
+        let mut request = GeminiEmbedRequest::Batch(batch);
+        request.set_model("gemini-embedding-001");
+
+        let value = serde_json::to_value(&request).unwrap();
+        assert_eq!(value["requests"][0]["model"], "models/gemini-embedding-001");
+        assert_eq!(value["requests"][1]["model"], "models/gemini-embedding-001");
+        assert_eq!(value["requests"][1]["taskType"], "RETRIEVAL_QUERY");
+    }
+}
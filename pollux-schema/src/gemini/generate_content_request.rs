@@ -8,20 +8,26 @@
 //! - Validation-friendly request shape (e.g. required `contents`).
 
 mod content;
+mod function_call;
 mod generation;
+mod media;
 mod system_instruction;
 mod tool;
 mod tool_config;
+mod tool_validation;
 
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::BTreeMap;
 
 pub use content::{Content, Part};
+pub use function_call::{FunctionCall, FunctionResponse};
 pub use generation::GenerationConfig;
+pub use media::{Blob, FileData};
 use system_instruction::deserialize_system_instruction;
-pub use tool::Tool;
-pub use tool_config::ToolConfig;
+pub use tool::{FunctionDeclaration, Tool};
+pub use tool_config::{FunctionCallingConfig, ToolConfig};
+pub use tool_validation::ToolValidationError;
 
 /// Gemini `generateContent` / `streamGenerateContent` request body.
 ///
@@ -67,6 +73,17 @@ impl GeminiGenerateContentRequest {
     pub fn system_instruction_mut(&mut self) -> &mut Option<Content> {
         &mut self.system_instruction
     }
+
+    /// Total decoded size of every `inlineData` part in `contents`.
+    #[must_use]
+    pub fn inline_data_len(&self) -> usize {
+        self.contents
+            .iter()
+            .flat_map(|content| &content.parts)
+            .filter_map(|part| part.inline_data.as_ref())
+            .map(Blob::decoded_len)
+            .sum()
+    }
 }
 
 #[cfg(test)]
@@ -200,6 +217,7 @@ mod tests {
         );
         assert!(req.contents[0].parts[1].text.is_none());
         assert!(req.contents[0].parts[1].inline_data.is_some());
+        assert_eq!(req.inline_data_len(), 7);
     }
 
     /// Mirrors the real Antigravity IDE request captured in antiREV/.
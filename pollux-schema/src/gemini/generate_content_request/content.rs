@@ -2,6 +2,9 @@ use serde::{Deserialize, Serialize, de::Error};
 use serde_json::Value;
 use std::collections::BTreeMap;
 
+use super::function_call::{FunctionCall, FunctionResponse};
+use super::media::{Blob, FileData};
+
 /// A single conversation turn or system instruction.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Content {
@@ -45,19 +48,19 @@ pub struct Part {
 
     /// Inline media bytes.
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub inline_data: Option<Value>,
+    pub inline_data: Option<Blob>,
 
     /// Function call produced by model.
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub function_call: Option<Value>,
+    pub function_call: Option<FunctionCall>,
 
     /// Function response used as context.
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub function_response: Option<Value>,
+    pub function_response: Option<FunctionResponse>,
 
     /// URI-based file data.
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub file_data: Option<Value>,
+    pub file_data: Option<FileData>,
 
     /// Executable code block.
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -83,6 +86,12 @@ impl Part {
     pub fn thought_signature_mut(&mut self) -> &mut Option<String> {
         &mut self.thought_signature
     }
+
+    /// Whether this part carries media (`inlineData` or `fileData`).
+    #[must_use]
+    pub fn is_media(&self) -> bool {
+        self.inline_data.is_some() || self.file_data.is_some()
+    }
 }
 
 fn deserialize_parts<'de, D>(deserializer: D) -> Result<Vec<Part>, D::Error>
@@ -166,7 +175,10 @@ mod tests {
 
         let part = &content.parts[0];
         assert!(part.text.is_none());
-        assert!(part.inline_data.is_some());
+        assert!(part.is_media());
+        let blob = part.inline_data.as_ref().unwrap();
+        assert_eq!(blob.mime_type.as_deref(), Some("image/png"));
+        assert_eq!(blob.data, "abc123");
     }
 
     #[test]
@@ -193,6 +205,12 @@ mod tests {
         ]);
 
         let contents: Vec<Content> = serde_json::from_value(input.clone()).unwrap();
+        let call = contents[0].parts[0].function_call.as_ref().unwrap();
+        assert_eq!(call.name.as_deref(), Some("get_weather"));
+        assert_eq!(call.args, Some(json!({"city": "London"})));
+        let response = contents[1].parts[0].function_response.as_ref().unwrap();
+        assert_eq!(response.response, Some(json!({"temperature": 15})));
+
         let output = serde_json::to_value(&contents).unwrap();
         assert_eq!(output, input);
     }
@@ -215,7 +233,7 @@ mod tests {
         assert_eq!(part.thought, Some(true));
         assert_eq!(part.thought_signature.as_deref(), Some("c2ln"));
         assert!(part.part_metadata.is_some());
-        assert!(part.file_data.is_some());
+        assert_eq!(part.file_data.as_ref().unwrap().file_uri, "gs://a/b");
         assert!(part.video_metadata.is_some());
 
         let output = serde_json::to_value(&content).unwrap();
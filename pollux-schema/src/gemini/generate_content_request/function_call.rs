@@ -0,0 +1,74 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+/// `functionCall` part data: a call the model asked for.
+///
+/// `name` is optional because streamed responses may split one call across
+/// several parts, with only the first carrying the name.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FunctionCall {
+    /// Call id, echoed back on the matching `functionResponse`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+
+    /// Declared function name.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+
+    /// Arguments: usually a JSON object, or a raw text fragment when streamed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub args: Option<Value>,
+
+    /// Streamed argument updates (`{"jsonPath": ..., "value": ...}`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub partial_args: Option<Vec<Value>>,
+
+    /// Set on streamed fragments when more of the same call follows.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub will_continue: Option<bool>,
+
+    #[serde(default, flatten)]
+    pub extra: BTreeMap<String, Value>,
+}
+
+/// `functionResponse` part data: the result of a call, sent as context.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FunctionResponse {
+    /// Id of the `functionCall` this answers.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+
+    /// Name of the called function.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+
+    /// Function output as a JSON object.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub response: Option<Value>,
+
+    #[serde(default, flatten)]
+    pub extra: BTreeMap<String, Value>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn streamed_fragment_roundtrips() {
+        let input = json!({
+            "partialArgs": [{"jsonPath": "$.city", "stringValue": "Par"}],
+            "willContinue": true,
+            "someFutureField": 1
+        });
+        let call: FunctionCall = serde_json::from_value(input.clone()).unwrap();
+        assert!(call.name.is_none());
+        assert_eq!(call.will_continue, Some(true));
+        assert_eq!(call.partial_args.as_ref().map(Vec::len), Some(1));
+        assert_eq!(serde_json::to_value(&call).unwrap(), input);
+    }
+}
@@ -0,0 +1,85 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+/// `inlineData` part data: media bytes sent inline as base64.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Blob {
+    /// IANA media type, e.g. `image/png`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mime_type: Option<String>,
+
+    /// Base64-encoded payload.
+    pub data: String,
+
+    #[serde(default, flatten)]
+    pub extra: BTreeMap<String, Value>,
+}
+
+impl Blob {
+    /// Size of the decoded payload in bytes, computed from the base64 length
+    /// without decoding. Whitespace is not expected and counts as data.
+    #[must_use]
+    pub fn decoded_len(&self) -> usize {
+        let data = self.data.as_bytes();
+        let padding = data.iter().rev().take(2).filter(|&&b| b == b'=').count();
+        (data.len() / 4 * 3 + (data.len() % 4) * 3 / 4).saturating_sub(padding)
+    }
+}
+
+/// `fileData` part data: media referenced by URI (uploaded file or GCS object).
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileData {
+    /// IANA media type; optional when the URI already implies it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mime_type: Option<String>,
+
+    /// File API or `gs://` URI.
+    pub file_uri: String,
+
+    #[serde(default, flatten)]
+    pub extra: BTreeMap<String, Value>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn blob(data: &str) -> Blob {
+        Blob {
+            data: data.to_string(),
+            ..Blob::default()
+        }
+    }
+
+    #[test]
+    fn decoded_len_accounts_for_padding() {
+        assert_eq!(blob("").decoded_len(), 0);
+        assert_eq!(blob("aA==").decoded_len(), 1);
+        assert_eq!(blob("aGk=").decoded_len(), 2);
+        assert_eq!(blob("aGVsbG8=").decoded_len(), 5);
+        assert_eq!(blob("aGVsbG8h").decoded_len(), 6);
+        // Unpadded input is accepted by upstream as well.
+        assert_eq!(blob("aGVsbG8").decoded_len(), 5);
+    }
+
+    #[test]
+    fn media_roundtrip_preserves_unknown_fields() {
+        let input = json!({
+            "mimeType": "image/png",
+            "data": "iVBORw0KGgo=",
+            "displayName": "cat.png"
+        });
+        let parsed: Blob = serde_json::from_value(input.clone()).unwrap();
+        assert_eq!(parsed.mime_type.as_deref(), Some("image/png"));
+        assert_eq!(serde_json::to_value(&parsed).unwrap(), input);
+
+        let input = json!({"fileUri": "gs://bucket/cat.png"});
+        let parsed: FileData = serde_json::from_value(input.clone()).unwrap();
+        assert_eq!(parsed.mime_type, None);
+        assert_eq!(serde_json::to_value(&parsed).unwrap(), input);
+    }
+}
@@ -8,7 +8,7 @@ use std::collections::BTreeMap;
 pub struct ToolConfig {
     /// Function-calling configuration.
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub function_calling_config: Option<Value>,
+    pub function_calling_config: Option<FunctionCallingConfig>,
 
     /// Retrieval configuration.
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -18,6 +18,34 @@ pub struct ToolConfig {
     pub extra: BTreeMap<String, Value>,
 }
 
+/// `toolConfig.functionCallingConfig` object.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FunctionCallingConfig {
+    /// `AUTO`, `ANY`, `NONE` or `VALIDATED`.
+    ///
+    /// Kept as raw string for transparent pass-through.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mode: Option<String>,
+
+    /// Functions the model may call; only meaningful with `ANY`/`VALIDATED`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub allowed_function_names: Option<Vec<String>>,
+
+    #[serde(default, flatten)]
+    pub extra: BTreeMap<String, Value>,
+}
+
+impl FunctionCallingConfig {
+    /// Whether `mode` restricts calls to `allowed_function_names`.
+    #[must_use]
+    pub fn forces_call(&self) -> bool {
+        self.mode
+            .as_deref()
+            .is_some_and(|m| m.eq_ignore_ascii_case("ANY") || m.eq_ignore_ascii_case("VALIDATED"))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -31,10 +59,9 @@ mod tests {
         });
         let tool_cfg: ToolConfig = serde_json::from_value(input.clone()).unwrap();
 
-        assert_eq!(
-            tool_cfg.function_calling_config,
-            Some(json!({"mode": "AUTO"}))
-        );
+        let calling = tool_cfg.function_calling_config.as_ref().unwrap();
+        assert_eq!(calling.mode.as_deref(), Some("AUTO"));
+        assert!(!calling.forces_call());
         assert_eq!(
             tool_cfg.retrieval_config,
             Some(json!({"latencyBudgetMs": 300}))
@@ -0,0 +1,194 @@
+use std::collections::HashSet;
+use std::fmt;
+
+use super::GeminiGenerateContentRequest;
+
+/// A request whose tool declarations, tool config or call parts disagree.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ToolValidationError {
+    /// `tools[tool].functionDeclarations[declaration]` has an empty name.
+    EmptyFunctionName { tool: usize, declaration: usize },
+    /// The same function is declared more than once.
+    DuplicateFunctionName(String),
+    /// `allowedFunctionNames` is set but `mode` is not `ANY`/`VALIDATED`.
+    AllowedNamesWithoutForcedMode,
+    /// `allowedFunctionNames` names a function that is not declared.
+    UndeclaredAllowedFunction(String),
+    /// `contents[content].parts[part]` is a `functionCall` without a name.
+    UnnamedFunctionCall { content: usize, part: usize },
+}
+
+impl fmt::Display for ToolValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::EmptyFunctionName { tool, declaration } => write!(
+                f,
+                "tools[{tool}].functionDeclarations[{declaration}] must have a name"
+            ),
+            Self::DuplicateFunctionName(name) => {
+                write!(f, "function '{name}' is declared more than once")
+            }
+            Self::AllowedNamesWithoutForcedMode => f.write_str(
+                "toolConfig.functionCallingConfig.allowedFunctionNames requires mode ANY or VALIDATED",
+            ),
+            Self::UndeclaredAllowedFunction(name) => write!(
+                f,
+                "toolConfig.functionCallingConfig.allowedFunctionNames names undeclared function '{name}'"
+            ),
+            Self::UnnamedFunctionCall { content, part } => write!(
+                f,
+                "contents[{content}].parts[{part}].functionCall must have a name"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ToolValidationError {}
+
+impl GeminiGenerateContentRequest {
+    /// Names of all declared functions, in declaration order.
+    pub fn function_names(&self) -> impl Iterator<Item = &str> {
+        self.tools
+            .iter()
+            .flatten()
+            .filter_map(|tool| tool.function_declarations.as_deref())
+            .flatten()
+            .map(|declaration| declaration.name.as_str())
+    }
+
+    /// Check that tool declarations, `toolConfig` and `functionCall` parts
+    /// are consistent with each other.
+    ///
+    /// Deserialization stays lenient so the gateway can pass requests through
+    /// for upstream to judge; callers that build or rewrite tool requests use
+    /// this to fail early with a precise message.
+    pub fn validate_tools(&self) -> Result<(), ToolValidationError> {
+        let mut declared = HashSet::new();
+        for (tool_index, tool) in self.tools.iter().flatten().enumerate() {
+            for (index, declaration) in tool.function_declarations.iter().flatten().enumerate() {
+                if declaration.name.is_empty() {
+                    return Err(ToolValidationError::EmptyFunctionName {
+                        tool: tool_index,
+                        declaration: index,
+                    });
+                }
+                if !declared.insert(declaration.name.as_str()) {
+                    return Err(ToolValidationError::DuplicateFunctionName(
+                        declaration.name.clone(),
+                    ));
+                }
+            }
+        }
+
+        let calling = self
+            .tool_config
+            .as_ref()
+            .and_then(|cfg| cfg.function_calling_config.as_ref());
+        if let Some(allowed) = calling.and_then(|c| c.allowed_function_names.as_ref()) {
+            if !calling.is_some_and(|c| c.forces_call()) {
+                return Err(ToolValidationError::AllowedNamesWithoutForcedMode);
+            }
+            if let Some(name) = allowed.iter().find(|n| !declared.contains(n.as_str())) {
+                return Err(ToolValidationError::UndeclaredAllowedFunction(name.clone()));
+            }
+        }
+
+        for (content_index, content) in self.contents.iter().enumerate() {
+            for (part_index, part) in content.parts.iter().enumerate() {
+                if part
+                    .function_call
+                    .as_ref()
+                    .is_some_and(|call| call.name.as_deref().is_none_or(str::is_empty))
+                {
+                    return Err(ToolValidationError::UnnamedFunctionCall {
+                        content: content_index,
+                        part: part_index,
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{Value, json};
+
+    fn request(tools: Value, tool_config: Value) -> GeminiGenerateContentRequest {
+        serde_json::from_value(json!({
+            "contents": [
+                {"role": "model", "parts": [{"functionCall": {"name": "get_weather", "args": {}}}]}
+            ],
+            "tools": tools,
+            "toolConfig": tool_config
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn consistent_tool_request_passes() {
+        let req = request(
+            json!([{"functionDeclarations": [
+                {"name": "get_weather", "description": "weather"},
+                {"name": "get_time", "description": "time"}
+            ]}]),
+            json!({"functionCallingConfig": {"mode": "ANY", "allowedFunctionNames": ["get_time"]}}),
+        );
+        assert_eq!(req.validate_tools(), Ok(()));
+        assert_eq!(
+            req.function_names().collect::<Vec<_>>(),
+            ["get_weather", "get_time"]
+        );
+    }
+
+    #[test]
+    fn inconsistent_tool_requests_are_rejected() {
+        let declared = json!([
+            {"functionDeclarations": [{"name": "get_weather", "description": ""}]},
+            {"functionDeclarations": [{"name": "get_weather", "description": ""}]}
+        ]);
+        assert_eq!(
+            request(declared, json!({})).validate_tools(),
+            Err(ToolValidationError::DuplicateFunctionName(
+                "get_weather".to_string()
+            ))
+        );
+
+        let declared =
+            json!([{"functionDeclarations": [{"name": "get_weather", "description": ""}]}]);
+        assert_eq!(
+            request(
+                declared.clone(),
+                json!({"functionCallingConfig": {"mode": "AUTO", "allowedFunctionNames": ["get_weather"]}})
+            )
+            .validate_tools(),
+            Err(ToolValidationError::AllowedNamesWithoutForcedMode)
+        );
+        assert_eq!(
+            request(
+                declared,
+                json!({"functionCallingConfig": {"mode": "ANY", "allowedFunctionNames": ["nope"]}})
+            )
+            .validate_tools(),
+            Err(ToolValidationError::UndeclaredAllowedFunction(
+                "nope".to_string()
+            ))
+        );
+
+        let mut req = request(json!([]), json!({}));
+        req.contents[0].parts[0]
+            .function_call
+            .as_mut()
+            .unwrap()
+            .name = None;
+        assert_eq!(
+            req.validate_tools(),
+            Err(ToolValidationError::UnnamedFunctionCall {
+                content: 0,
+                part: 0
+            })
+        );
+    }
+}
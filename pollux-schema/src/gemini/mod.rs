@@ -1,9 +1,19 @@
+mod count_tokens;
+mod embed_content;
 mod generate_content_request;
 mod model_list;
+mod predict;
 mod v1beta_response;
 
+pub use count_tokens::{GeminiCountTokensRequest, GeminiCountTokensResponse};
+pub use embed_content::{
+    GeminiBatchEmbedContentsRequest, GeminiEmbedContentRequest, GeminiEmbedRequest,
+};
 pub use generate_content_request::GeminiGenerateContentRequest;
-pub use generate_content_request::{Content, GenerationConfig, Part};
+pub use generate_content_request::{
+    Blob, Content, FileData, FunctionCall, FunctionCallingConfig, FunctionDeclaration,
+    FunctionResponse, GenerationConfig, Part, Tool, ToolConfig, ToolValidationError,
+};
 pub use model_list::{GeminiModel, GeminiModelList};
-pub(crate) use v1beta_response::Candidate;
-pub use v1beta_response::GeminiResponseBody;
+pub use predict::GeminiPredictRequest;
+pub use v1beta_response::{Candidate, GeminiResponseBody};
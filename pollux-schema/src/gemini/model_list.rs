@@ -43,4 +43,49 @@ impl GeminiModelList {
             models: models_list,
         }
     }
+
+    /// Append embedding models, advertised with the embedding RPCs only.
+    #[must_use]
+    pub fn with_embedding_models<I, S>(mut self, model_names: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.models.extend(model_names.into_iter().map(|model| {
+            let name = model.into();
+            GeminiModel {
+                name: name.clone(),
+                display_name: name,
+                supported_generation_methods: Some(vec![
+                    "embedContent".to_string(),
+                    "batchEmbedContents".to_string(),
+                ]),
+                ..Default::default()
+            }
+        }));
+        self
+    }
+
+    /// Append image generation models: Imagen on `predict`, Gemini image
+    /// models on `generateContent`.
+    #[must_use]
+    pub fn with_image_models<I, S>(mut self, model_names: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.models.extend(model_names.into_iter().map(|model| {
+            let name = model.into();
+            GeminiModel {
+                name: name.clone(),
+                display_name: name,
+                supported_generation_methods: Some(vec![
+                    "predict".to_string(),
+                    "generateContent".to_string(),
+                ]),
+                ..Default::default()
+            }
+        }));
+        self
+    }
 }
@@ -0,0 +1,37 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+/// Imagen `predict` request body.
+///
+/// Reference: <https://ai.google.dev/api/generate-images>
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GeminiPredictRequest {
+    /// One entry per prompt, e.g. `{"prompt": "..."}`.
+    pub instances: Vec<Value>,
+
+    /// `sampleCount`, `aspectRatio`, `personGeneration`, ...
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parameters: Option<Value>,
+
+    #[serde(default, flatten)]
+    pub extra: BTreeMap<String, Value>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn predict_request_roundtrips() {
+        let input = json!({
+            "instances": [{"prompt": "a lighthouse at dusk"}],
+            "parameters": {"sampleCount": 2, "aspectRatio": "16:9"}
+        });
+        let request: GeminiPredictRequest = serde_json::from_value(input.clone()).unwrap();
+        assert_eq!(request.instances.len(), 1);
+        assert_eq!(serde_json::to_value(&request).unwrap(), input);
+    }
+}
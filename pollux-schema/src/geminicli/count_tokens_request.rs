@@ -0,0 +1,27 @@
+use crate::gemini::Content;
+use serde::Serialize;
+
+/// Cloud Code `countTokens` envelope. Unlike `generateContent` it carries no
+/// project, and the model is a resource name (`models/<id>`).
+#[derive(Debug, Serialize)]
+pub struct VertexCountTokensRequest<'a> {
+    pub request: VertexCountTokensBody<'a>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct VertexCountTokensBody<'a> {
+    pub model: String,
+    pub contents: &'a [Content],
+}
+
+impl<'a> VertexCountTokensRequest<'a> {
+    #[must_use]
+    pub fn new(model: &str, contents: &'a [Content]) -> Self {
+        Self {
+            request: VertexCountTokensBody {
+                model: format!("models/{model}"),
+                contents,
+            },
+        }
+    }
+}
@@ -0,0 +1,11 @@
+use crate::gemini::GeminiEmbedRequest;
+use serde::Serialize;
+
+/// Cloud Code `embedContent` / `batchEmbedContents` envelope, shaped like
+/// [`VertexGenerateContentRequest`](super::VertexGenerateContentRequest).
+#[derive(Debug, Serialize)]
+pub struct VertexEmbedContentRequest<'a> {
+    pub model: &'a str,
+    pub project: &'a str,
+    pub request: &'a GeminiEmbedRequest,
+}
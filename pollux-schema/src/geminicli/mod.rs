@@ -2,6 +2,12 @@
 
 mod cli_request;
 mod cli_response;
+mod count_tokens_request;
+mod embed_request;
+mod predict_request;
 
 pub use cli_request::VertexGenerateContentRequest;
 pub use cli_response::GeminiCliResponseBody;
+pub use count_tokens_request::{VertexCountTokensBody, VertexCountTokensRequest};
+pub use embed_request::VertexEmbedContentRequest;
+pub use predict_request::VertexPredictRequest;
@@ -0,0 +1,11 @@
+use crate::gemini::GeminiPredictRequest;
+use serde::Serialize;
+
+/// Cloud Code `predict` envelope, shaped like
+/// [`VertexGenerateContentRequest`](super::VertexGenerateContentRequest).
+#[derive(Debug, Serialize)]
+pub struct VertexPredictRequest<'a> {
+    pub model: &'a str,
+    pub project: &'a str,
+    pub request: &'a GeminiPredictRequest,
+}
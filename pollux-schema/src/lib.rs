@@ -1,9 +1,11 @@
+pub mod anthropic;
 pub mod antigravity;
 pub mod codex;
 pub mod gemini;
 pub mod geminicli;
 mod macros;
 pub mod openai;
+pub mod qwen;
 
 pub use antigravity::{AntigravityRequestBody, AntigravityRequestMeta};
 pub use codex::{CodexErrorBody, CodexRequestBody};
@@ -11,3 +13,4 @@ pub use geminicli::{GeminiCliResponseBody, VertexGenerateContentRequest};
 pub use openai::{
     OpenaiRequestBody, OpenaiResponsesErrorBody, OpenaiResponsesErrorObject, OpenaiRole,
 };
+pub use qwen::QwenErrorBody;
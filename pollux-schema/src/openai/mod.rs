@@ -1,7 +1,13 @@
+mod chat_completions;
 mod model_list;
 mod responses_error;
 mod responses_request;
 
+pub use chat_completions::{
+    ChatChoice, ChatChunkChoice, ChatCompletion, ChatCompletionChunk, ChatCompletionRequest,
+    ChatDelta, ChatFunctionCall, ChatFunctionCallDelta, ChatFunctionDefinition, ChatMessage,
+    ChatResponseMessage, ChatStreamOptions, ChatTool, ChatToolCall, ChatToolCallDelta, ChatUsage,
+};
 pub use model_list::{OpenaiModel, OpenaiModelList};
 pub use responses_error::{OpenaiResponsesErrorBody, OpenaiResponsesErrorObject};
 pub use responses_request::{
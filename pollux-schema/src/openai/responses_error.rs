@@ -4,6 +4,7 @@ use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
 use crate::codex::{CodexErrorBody, CodexErrorObject};
+use crate::qwen::{QwenErrorBody, QwenErrorObject};
 
 /// OpenAI-compatible error response schema.
 ///
@@ -48,6 +49,33 @@ impl From<CodexErrorBody> for OpenaiResponsesErrorBody {
     }
 }
 
+impl From<QwenErrorBody> for OpenaiResponsesErrorBody {
+    /// DashScope-style bodies carry `code` / `message` at the top level;
+    /// those fill in whatever the `error` envelope lacks.
+    fn from(upstream_err: QwenErrorBody) -> Self {
+        let QwenErrorBody { inner, extra } = upstream_err;
+        let QwenErrorObject {
+            code,
+            message,
+            r#type,
+            param,
+            ..
+        } = inner;
+        let top_level = |key: &str| extra.get(key).and_then(Value::as_str).map(str::to_string);
+
+        OpenaiResponsesErrorBody {
+            inner: OpenaiResponsesErrorObject {
+                code: code.or_else(|| top_level("code")),
+                message: message
+                    .or_else(|| top_level("message"))
+                    .unwrap_or("Upstream error (check server logs for details).".to_string()),
+                r#type: r#type.unwrap_or("UNKNOWN".to_string()),
+                param,
+            },
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,3 @@
+mod qwen_error;
+
+pub use qwen_error::{QwenErrorBody, QwenErrorObject};
@@ -0,0 +1,77 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+/// Qwen (DashScope compatible-mode) upstream error response schema.
+///
+/// Errors are OpenAI-style `{"error": {...}}` envelopes; some gateway errors
+/// come back DashScope-style with top-level `code` / `message`, kept in `extra`.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct QwenErrorBody {
+    #[serde(rename = "error")]
+    #[serde(default)]
+    pub inner: QwenErrorObject,
+
+    #[serde(flatten)]
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub extra: BTreeMap<String, Value>,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct QwenErrorObject {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+
+    /// OpenAI-style `type` field. Named `r#type` because `type` is a Rust keyword.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub r#type: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub param: Option<Value>,
+
+    #[serde(flatten)]
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub extra: BTreeMap<String, Value>,
+}
+
+impl QwenErrorBody {
+    /// Error code from the OpenAI-style envelope, falling back to a
+    /// DashScope-style top-level `code`.
+    pub fn code(&self) -> Option<&str> {
+        self.inner
+            .code
+            .as_deref()
+            .or(self.inner.r#type.as_deref())
+            .or_else(|| self.extra.get("code").and_then(Value::as_str))
+    }
+
+    /// True when the account's free daily quota is used up.
+    pub fn is_quota_exhausted(&self) -> bool {
+        self.code()
+            .is_some_and(|code| code.eq_ignore_ascii_case("insufficient_quota"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn code_reads_openai_envelope_then_top_level() {
+        let openai: QwenErrorBody = serde_json::from_str(
+            r#"{"error":{"code":"insufficient_quota","message":"Free allocated quota exceeded.","type":"insufficient_quota"}}"#,
+        )
+        .expect("parse openai-style body");
+        assert!(openai.is_quota_exhausted());
+
+        let dashscope: QwenErrorBody = serde_json::from_str(
+            r#"{"code":"InvalidApiKey","message":"Invalid API-key provided."}"#,
+        )
+        .expect("parse dashscope-style body");
+        assert_eq!(dashscope.code(), Some("InvalidApiKey"));
+        assert!(!dashscope.is_quota_exhausted());
+    }
+}
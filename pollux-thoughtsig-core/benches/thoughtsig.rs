@@ -8,10 +8,11 @@
 )]
 use criterion::{Criterion, criterion_group, criterion_main};
 use pollux_thoughtsig_core::{
-    CacheKeyGenerator, CacheMissPolicy, PatchEvent, Patchable, SignaturePatcher, SignatureSniffer,
-    SniffEvent, Sniffable, ThoughtSignatureEngine,
+    CacheKey, CacheKeyGenerator, CacheMissPolicy, FingerprintVersion, PatchEvent, Patchable,
+    SignaturePatcher, SignatureSniffer, SniffEvent, Sniffable, ThoughtSignatureEngine,
 };
 use serde_json::{Value, json};
+use std::borrow::Cow;
 use std::hint::black_box;
 use std::sync::Arc;
 
@@ -62,28 +63,28 @@ fn sample_json_large() -> Value {
 fn bench_generate_text_short(c: &mut Criterion) {
     let text = sample_text_short();
     c.bench_function("keygen/text_short", |b| {
-        b.iter(|| CacheKeyGenerator::generate_text(black_box(text)))
+        b.iter(|| CacheKeyGenerator::default().generate_text(black_box(text)))
     });
 }
 
 fn bench_generate_text_long(c: &mut Criterion) {
     let text = sample_text_long();
     c.bench_function("keygen/text_long", |b| {
-        b.iter(|| CacheKeyGenerator::generate_text(black_box(&text)))
+        b.iter(|| CacheKeyGenerator::default().generate_text(black_box(&text)))
     });
 }
 
 fn bench_generate_json_small(c: &mut Criterion) {
     let val = sample_json_small();
     c.bench_function("keygen/json_small", |b| {
-        b.iter(|| CacheKeyGenerator::generate_json(black_box(&val)))
+        b.iter(|| CacheKeyGenerator::default().generate_json(black_box(&val)))
     });
 }
 
 fn bench_generate_json_large(c: &mut Criterion) {
     let val = sample_json_large();
     c.bench_function("keygen/json_large", |b| {
-        b.iter(|| CacheKeyGenerator::generate_json(black_box(&val)))
+        b.iter(|| CacheKeyGenerator::default().generate_json(black_box(&val)))
     });
 }
 
@@ -93,9 +94,10 @@ fn bench_generate_json_large(c: &mut Criterion) {
 
 fn bench_engine_put_get(c: &mut Criterion) {
     let engine = ThoughtSignatureEngine::new(3600, 4096);
-    let keys: Vec<u64> = (0..1000).collect();
+    let key = |hash| CacheKey::new(FingerprintVersion::V1, hash);
+    let keys: Vec<CacheKey> = (0..1000).map(key).collect();
     for &k in &keys {
-        engine.put_signature(k, Arc::from(format!("sig_{k}")));
+        engine.put_signature(k, Arc::from(format!("sig_{}", k.hash)));
     }
 
     c.bench_function("engine/cache_hit", |b| {
@@ -108,14 +110,14 @@ fn bench_engine_put_get(c: &mut Criterion) {
     });
 
     c.bench_function("engine/cache_miss", |b| {
-        b.iter(|| black_box(engine.get_signature(&999_999)))
+        b.iter(|| black_box(engine.get_signature(&key(999_999))))
     });
 
     c.bench_function("engine/put", |b| {
         let mut counter = 100_000u64;
         b.iter(|| {
             counter += 1;
-            engine.put_signature(counter, Arc::from("bench_sig"));
+            engine.put_signature(key(counter), Arc::from("bench_sig"));
         })
     });
 }
@@ -142,7 +144,7 @@ impl Sniffable for FakeSniffable {
     fn data(&self) -> SniffEvent<'_> {
         match &self.data_kind {
             DataKind::Text(t) => SniffEvent::ThoughtText(t),
-            DataKind::FunctionCall(v) => SniffEvent::FunctionCall(v),
+            DataKind::FunctionCall(v) => SniffEvent::FunctionCall(Cow::Borrowed(v)),
             DataKind::None => SniffEvent::None,
         }
     }
@@ -222,7 +224,7 @@ impl Patchable for FakePatchable {
     fn data(&self) -> PatchEvent<'_> {
         match &self.data {
             FakeData::Text(t) => PatchEvent::ThoughtText(t),
-            FakeData::FunctionCall(v) => PatchEvent::FunctionCall(v),
+            FakeData::FunctionCall(v) => PatchEvent::FunctionCall(Cow::Borrowed(v)),
             FakeData::None => PatchEvent::None,
         }
     }
@@ -237,7 +239,7 @@ impl Patchable for FakePatchable {
 fn bench_patch_cache_hit(c: &mut Criterion) {
     let engine = Arc::new(ThoughtSignatureEngine::new(3600, 4096));
     let text = "alpha beta gamma";
-    let key = CacheKeyGenerator::generate_text(text).unwrap();
+    let key = CacheKeyGenerator::default().generate_text(text).unwrap();
     engine.put_signature(key, Arc::from("cached_sig"));
     let patcher = SignaturePatcher::new(engine, CacheMissPolicy::Fallback);
 
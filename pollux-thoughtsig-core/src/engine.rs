@@ -1,35 +1,226 @@
+use crate::fingerprint::{CacheKey, CacheKeyGenerator, FingerprintVersion};
+use moka::notification::RemovalCause;
 use moka::sync::Cache;
+use serde::Serialize;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::{sync::Arc, time::Duration};
 
-pub type CacheKey = u64;
 pub type ThoughtSignature = Arc<str>;
-pub type SignatureCacheStore = Cache<CacheKey, ThoughtSignature>;
 
-pub struct ThoughtSignatureEngine {
-    cache: SignatureCacheStore,
-    dummy_signature: ThoughtSignature,
+/// Which entries a [`SignatureCacheStore::invalidate`] call drops.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Invalidation {
+    /// One fingerprint, as logged by the patcher.
+    Key(CacheKey),
+    /// Everything captured from responses of this model.
+    Model(String),
+    All,
 }
 
-impl ThoughtSignatureEngine {
+/// Point-in-time view of a store's counters.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct SignatureCacheStats {
+    pub entries: u64,
+    pub hits: u64,
+    pub misses: u64,
+    pub inserts: u64,
+    /// Entries dropped for age or size, not by invalidation.
+    pub evictions: u64,
+}
+
+/// Counters shared by store implementations.
+#[derive(Debug, Default)]
+pub struct CacheCounters {
+    hits: AtomicU64,
+    misses: AtomicU64,
+    inserts: AtomicU64,
+    evictions: AtomicU64,
+}
+
+impl CacheCounters {
+    /// Count a lookup by whether it found a signature.
+    pub fn record_lookup(&self, hit: bool) {
+        let counter = if hit { &self.hits } else { &self.misses };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn record_insert(&self) {
+        self.inserts.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Count a removal if the cache dropped the entry on its own.
+    pub fn record_removal(&self, cause: RemovalCause) {
+        if matches!(cause, RemovalCause::Expired | RemovalCause::Size) {
+            self.evictions.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    pub fn snapshot(&self, entries: u64) -> SignatureCacheStats {
+        SignatureCacheStats {
+            entries,
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            inserts: self.inserts.load(Ordering::Relaxed),
+            evictions: self.evictions.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Storage behind a [`ThoughtSignatureEngine`].
+///
+/// Lookups sit on the request path, so implementations must answer from
+/// memory; slower backends belong behind an in-memory front.
+pub trait SignatureCacheStore: Send + Sync {
+    fn get(&self, key: &CacheKey) -> Option<ThoughtSignature>;
+    /// `model` tags the entry for [`Invalidation::Model`].
+    fn insert(&self, key: CacheKey, signature: ThoughtSignature, model: Option<&str>);
+    fn stats(&self) -> SignatureCacheStats;
+    fn invalidate(&self, scope: &Invalidation);
+
+    /// Move the entry under `from` to `to`, for keys of a retired
+    /// [`FingerprintVersion`]. The default copies the signature without its
+    /// model tag and leaves `from` to expire.
+    fn rekey(&self, from: &CacheKey, to: CacheKey) -> Option<ThoughtSignature> {
+        let signature = self.get(from)?;
+        self.insert(to, signature.clone(), None);
+        Some(signature)
+    }
+}
+
+/// In-process store with a TTL and an entry bound; the default.
+#[derive(Clone)]
+pub struct MemorySignatureStore {
+    cache: Cache<CacheKey, (ThoughtSignature, Option<Arc<str>>)>,
+    counters: Arc<CacheCounters>,
+}
+
+impl MemorySignatureStore {
     pub fn new(ttl_secs: u64, max_capacity: u64) -> Self {
-        let cache = SignatureCacheStore::builder()
+        let counters = Arc::new(CacheCounters::default());
+        let listener = counters.clone();
+        let cache = Cache::builder()
             .time_to_live(Duration::from_secs(ttl_secs.max(1)))
             .max_capacity(max_capacity.max(1))
+            .support_invalidation_closures()
+            .eviction_listener(move |_, _, cause| listener.record_removal(cause))
             .build();
+        Self { cache, counters }
+    }
+}
+
+impl SignatureCacheStore for MemorySignatureStore {
+    fn get(&self, key: &CacheKey) -> Option<ThoughtSignature> {
+        let signature = self.cache.get(key).map(|(signature, _)| signature);
+        self.counters.record_lookup(signature.is_some());
+        signature
+    }
+
+    fn insert(&self, key: CacheKey, signature: ThoughtSignature, model: Option<&str>) {
+        self.counters.record_insert();
+        self.cache.insert(key, (signature, model.map(Arc::from)));
+    }
+
+    fn stats(&self) -> SignatureCacheStats {
+        self.cache.run_pending_tasks();
+        self.counters.snapshot(self.cache.entry_count())
+    }
+
+    fn invalidate(&self, scope: &Invalidation) {
+        match scope {
+            Invalidation::Key(key) => self.cache.invalidate(key),
+            Invalidation::Model(model) => {
+                let model: Arc<str> = Arc::from(model.as_str());
+                // Only fails when closures are unsupported, which they are not.
+                let _ = self
+                    .cache
+                    .invalidate_entries_if(move |_, (_, tag)| tag.as_ref() == Some(&model));
+            }
+            Invalidation::All => self.cache.invalidate_all(),
+        }
+        self.cache.run_pending_tasks();
+    }
+
+    fn rekey(&self, from: &CacheKey, to: CacheKey) -> Option<ThoughtSignature> {
+        let entry = self.cache.remove(from);
+        self.counters.record_lookup(entry.is_some());
+        let (signature, model) = entry?;
+        self.cache.insert(to, (signature.clone(), model));
+        Some(signature)
+    }
+}
+
+pub struct ThoughtSignatureEngine {
+    store: Arc<dyn SignatureCacheStore>,
+    dummy_signature: ThoughtSignature,
+    keys: CacheKeyGenerator,
+    legacy_keys: Option<CacheKeyGenerator>,
+}
+
+impl ThoughtSignatureEngine {
+    pub fn new(ttl_secs: u64, max_capacity: u64) -> Self {
+        Self::with_store(Arc::new(MemorySignatureStore::new(ttl_secs, max_capacity)))
+    }
+
+    pub fn with_store(store: Arc<dyn SignatureCacheStore>) -> Self {
         let dummy_signature: ThoughtSignature = Arc::from("skip_thought_signature_validator");
 
         Self {
-            cache,
+            store,
             dummy_signature,
+            keys: CacheKeyGenerator::default(),
+            legacy_keys: None,
         }
     }
 
+    /// Key signatures with `version`. With `migrate_from`, a miss also
+    /// probes the key that version would give and moves a hit over, so a
+    /// cache filled before an algorithm change keeps serving.
+    #[must_use]
+    pub fn with_fingerprint(
+        mut self,
+        version: FingerprintVersion,
+        migrate_from: Option<FingerprintVersion>,
+    ) -> Self {
+        self.keys = CacheKeyGenerator::new(version);
+        self.legacy_keys = migrate_from
+            .filter(|&legacy| legacy != version)
+            .map(CacheKeyGenerator::new);
+        self
+    }
+
+    pub fn keys(&self) -> CacheKeyGenerator {
+        self.keys
+    }
+
+    /// Generator of the version being migrated from, if any.
+    pub fn legacy_keys(&self) -> Option<CacheKeyGenerator> {
+        self.legacy_keys
+    }
+
     pub fn get_signature(&self, key: &CacheKey) -> Option<ThoughtSignature> {
-        self.cache.get(key)
+        self.store.get(key)
+    }
+
+    /// Re-key a signature found under a [`Self::legacy_keys`] key.
+    pub fn migrate_signature(&self, from: &CacheKey, to: CacheKey) -> Option<ThoughtSignature> {
+        self.store.rekey(from, to)
     }
 
     pub fn put_signature(&self, key: CacheKey, signature: ThoughtSignature) {
-        self.cache.insert(key, signature);
+        self.store.insert(key, signature, None);
+    }
+
+    /// [`Self::put_signature`], tagged with the model that produced it.
+    pub fn put_model_signature(&self, key: CacheKey, signature: ThoughtSignature, model: &str) {
+        self.store.insert(key, signature, Some(model));
+    }
+
+    pub fn stats(&self) -> SignatureCacheStats {
+        self.store.stats()
+    }
+
+    pub fn invalidate(&self, scope: &Invalidation) {
+        self.store.invalidate(scope);
     }
 
     pub fn fallback_signature(&self) -> ThoughtSignature {
@@ -41,10 +232,14 @@ impl ThoughtSignatureEngine {
 mod tests {
     use super::*;
 
+    fn key(hash: u64) -> CacheKey {
+        CacheKey::new(FingerprintVersion::V1, hash)
+    }
+
     #[test]
     fn get_signature_returns_none_when_no_cache() {
         let engine = ThoughtSignatureEngine::new(3600, 1024);
-        let key = 42_u64;
+        let key = CacheKey::new(FingerprintVersion::V1, 42);
 
         let signature = engine.get_signature(&key);
         assert!(signature.is_none());
@@ -53,10 +248,58 @@ mod tests {
     #[test]
     fn get_signature_hits_cache_when_present() {
         let engine = ThoughtSignatureEngine::new(3600, 1024);
-        let key = 7_u64;
+        let key = CacheKey::new(FingerprintVersion::V1, 7);
         engine.put_signature(key, Arc::from("sig_007"));
 
         let signature = engine.get_signature(&key);
         assert_eq!(signature.as_deref(), Some("sig_007"));
     }
+
+    #[test]
+    fn stats_count_lookups_and_invalidation_by_model() {
+        let engine = ThoughtSignatureEngine::new(3600, 1024);
+        engine.put_model_signature(key(1), Arc::from("sig_a"), "gemini-2.5-pro");
+        engine.put_model_signature(key(2), Arc::from("sig_b"), "gemini-2.5-flash");
+        engine.put_signature(key(3), Arc::from("sig_c"));
+
+        assert!(engine.get_signature(&key(1)).is_some());
+        assert!(engine.get_signature(&key(9)).is_none());
+
+        engine.invalidate(&Invalidation::Model("gemini-2.5-pro".to_string()));
+        assert!(engine.get_signature(&key(1)).is_none());
+        assert!(engine.get_signature(&key(2)).is_some());
+
+        engine.invalidate(&Invalidation::Key(key(2)));
+        assert!(engine.get_signature(&key(2)).is_none());
+
+        let stats = engine.stats();
+        assert_eq!(stats.entries, 1);
+        assert_eq!(stats.inserts, 3);
+        assert_eq!((stats.hits, stats.misses), (2, 3));
+        assert_eq!(stats.evictions, 0);
+    }
+
+    #[test]
+    fn migration_moves_legacy_entries_to_the_current_version() {
+        let engine = ThoughtSignatureEngine::new(3600, 1024)
+            .with_fingerprint(FingerprintVersion::V2, Some(FingerprintVersion::V1));
+        let legacy = engine
+            .legacy_keys()
+            .unwrap()
+            .generate_text("alpha")
+            .unwrap();
+        let current = engine.keys().generate_text("alpha").unwrap();
+        engine.put_model_signature(legacy, Arc::from("sig_a"), "gemini-2.5-pro");
+
+        assert_eq!(
+            engine.migrate_signature(&legacy, current).as_deref(),
+            Some("sig_a")
+        );
+        assert!(engine.get_signature(&legacy).is_none());
+        assert_eq!(engine.get_signature(&current).as_deref(), Some("sig_a"));
+
+        // The model tag moves with the entry.
+        engine.invalidate(&Invalidation::Model("gemini-2.5-pro".to_string()));
+        assert!(engine.get_signature(&current).is_none());
+    }
 }
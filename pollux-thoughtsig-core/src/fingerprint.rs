@@ -1,39 +1,113 @@
-use crate::CacheKey;
-
 use ahash::AHasher;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use std::hash::Hasher;
 
 const DOMAIN_TEXT: u8 = 1;
 const DOMAIN_JSON: u8 = 2;
 
+/// Algorithm behind a [`CacheKey`].
+///
+/// The version is part of every key, so a cache filled by one algorithm is
+/// never probed with keys of another and stores can tell stale rows apart.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FingerprintVersion {
+    /// aHash with its built-in keys. Only stable within one build: the output
+    /// changes with the `ahash` release and the host's AES support. Text is
+    /// hashed as given, surrounding whitespace included.
+    #[default]
+    V1,
+    /// First 8 bytes of SHA-256; stable across builds and hosts. Text is
+    /// trimmed before hashing.
+    V2,
+}
+
+impl FingerprintVersion {
+    pub const ALL: [Self; 2] = [Self::V1, Self::V2];
+
+    /// Version byte, as persisted next to the key.
+    pub fn byte(self) -> u8 {
+        match self {
+            Self::V1 => 1,
+            Self::V2 => 2,
+        }
+    }
+
+    pub fn from_byte(byte: u8) -> Option<Self> {
+        Self::ALL.into_iter().find(|version| version.byte() == byte)
+    }
+}
+
+/// Fingerprint of a thought or function call.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CacheKey {
+    pub version: FingerprintVersion,
+    pub hash: u64,
+}
+
+impl CacheKey {
+    pub fn new(version: FingerprintVersion, hash: u64) -> Self {
+        Self { version, hash }
+    }
+}
+
 #[derive(Debug, Default, Clone, Copy)]
-pub struct CacheKeyGenerator;
+pub struct CacheKeyGenerator {
+    version: FingerprintVersion,
+}
 
 impl CacheKeyGenerator {
-    pub fn generate_text(text: impl AsRef<str>) -> Option<CacheKey> {
-        Some(text.as_ref())
-            .filter(|&t| !t.trim().is_empty())
-            .map(|t| {
-                let mut hasher = AHasher::default();
-                hasher.write_u8(DOMAIN_TEXT);
-                hasher.write(t.as_bytes());
-                hasher.finish()
-            })
+    pub fn new(version: FingerprintVersion) -> Self {
+        Self { version }
+    }
+
+    pub fn version(self) -> FingerprintVersion {
+        self.version
+    }
+
+    pub fn generate_text(self, text: impl AsRef<str>) -> Option<CacheKey> {
+        let text = text.as_ref();
+        let trimmed = text.trim();
+        if trimmed.is_empty() {
+            return None;
+        }
+        let text = match self.version {
+            FingerprintVersion::V1 => text,
+            FingerprintVersion::V2 => trimmed,
+        };
+        Some(self.key(DOMAIN_TEXT, text.as_bytes()))
     }
 
-    pub fn generate_json(value: &impl Serialize) -> Option<CacheKey> {
+    pub fn generate_json(self, value: &impl Serialize) -> Option<CacheKey> {
         let mut normalized = serde_json::to_value(value).ok()?;
         if normalized.is_null() {
             return None;
         }
         normalized.sort_all_objects();
         let bytes = serde_json::to_vec(&normalized).ok()?;
+        Some(self.key(DOMAIN_JSON, &bytes))
+    }
 
-        let mut hasher = AHasher::default();
-        hasher.write_u8(DOMAIN_JSON);
-        hasher.write(&bytes);
-        Some(hasher.finish())
+    fn key(self, domain: u8, bytes: &[u8]) -> CacheKey {
+        let hash = match self.version {
+            FingerprintVersion::V1 => {
+                let mut hasher = AHasher::default();
+                hasher.write_u8(domain);
+                hasher.write(bytes);
+                hasher.finish()
+            }
+            FingerprintVersion::V2 => {
+                let digest = Sha256::new()
+                    .chain_update([domain])
+                    .chain_update(bytes)
+                    .finalize();
+                let mut prefix = [0u8; 8];
+                prefix.copy_from_slice(&digest[..8]);
+                u64::from_be_bytes(prefix)
+            }
+        };
+        CacheKey::new(self.version, hash)
     }
 }
 
@@ -54,8 +128,8 @@ mod tests {
         });
 
         assert_eq!(
-            CacheKeyGenerator::generate_json(&lhs),
-            CacheKeyGenerator::generate_json(&rhs)
+            CacheKeyGenerator::default().generate_json(&lhs),
+            CacheKeyGenerator::default().generate_json(&rhs)
         );
     }
 
@@ -65,8 +139,8 @@ mod tests {
         let rhs = json!(["b", "a"]);
 
         assert_ne!(
-            CacheKeyGenerator::generate_json(&lhs),
-            CacheKeyGenerator::generate_json(&rhs)
+            CacheKeyGenerator::default().generate_json(&lhs),
+            CacheKeyGenerator::default().generate_json(&rhs)
         );
     }
 
@@ -75,14 +149,37 @@ mod tests {
         let lhs = "  alpha  ";
         let rhs = "alpha";
 
-        assert_eq!(
-            CacheKeyGenerator::generate_text(lhs),
-            CacheKeyGenerator::generate_text(rhs)
-        );
+        let v2 = CacheKeyGenerator::new(FingerprintVersion::V2);
+        assert_eq!(v2.generate_text(lhs), v2.generate_text(rhs));
+
+        // V1 keeps hashing the untrimmed text its persisted keys were made of.
+        let v1 = CacheKeyGenerator::new(FingerprintVersion::V1);
+        assert_ne!(v1.generate_text(lhs), v1.generate_text(rhs));
     }
 
     #[test]
     fn empty_string_returns_none() {
-        assert_eq!(CacheKeyGenerator::generate_text("   "), None);
+        assert_eq!(CacheKeyGenerator::default().generate_text("   "), None);
+    }
+
+    #[test]
+    fn versions_key_the_same_input_apart() {
+        let v1 = CacheKeyGenerator::new(FingerprintVersion::V1);
+        let v2 = CacheKeyGenerator::new(FingerprintVersion::V2);
+        let text = v2.generate_text("alpha").unwrap();
+        assert_eq!(text.version, FingerprintVersion::V2);
+        assert_ne!(v1.generate_text("alpha"), Some(text));
+
+        // V2 is pinned: persisted keys must survive rebuilds.
+        assert_eq!(text.hash, 0xe1fd_e697_30d0_3058);
+        assert_ne!(v2.generate_json(&"alpha"), Some(text));
+    }
+
+    #[test]
+    fn version_bytes_round_trip() {
+        for version in FingerprintVersion::ALL {
+            assert_eq!(FingerprintVersion::from_byte(version.byte()), Some(version));
+        }
+        assert_eq!(FingerprintVersion::from_byte(0), None);
     }
 }
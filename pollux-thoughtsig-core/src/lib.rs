@@ -4,9 +4,13 @@ pub mod patch;
 mod sniffer;
 
 pub use engine::ThoughtSignatureEngine;
-pub use engine::{CacheKey, SignatureCacheStore, ThoughtSignature};
-pub use fingerprint::CacheKeyGenerator;
+pub use engine::{
+    CacheCounters, Invalidation, MemorySignatureStore, SignatureCacheStats, SignatureCacheStore,
+    ThoughtSignature,
+};
+pub use fingerprint::{CacheKey, CacheKeyGenerator, FingerprintVersion};
 pub use patch::{
-    CacheMissPolicy, PatchEvent, PatchOutcome, Patchable, SignaturePatcher, SignaturePreview,
+    CacheMissPolicy, PatchEvent, PatchOutcome, PatchSummary, Patchable, SignaturePatcher,
+    SignaturePreview,
 };
-pub use sniffer::{SignatureSniffer, SniffEvent, Sniffable};
+pub use sniffer::{SignatureSniffer, SniffEvent, SniffLimits, Sniffable, SniffableChunk};
@@ -1,5 +1,6 @@
 use crate::{CacheKey, CacheKeyGenerator, ThoughtSignatureEngine};
 use serde_json::Value;
+use std::borrow::Cow;
 use std::fmt;
 use std::sync::Arc;
 
@@ -21,7 +22,8 @@ impl fmt::Display for SignaturePreview<'_> {
 
 pub enum PatchEvent<'a> {
     ThoughtText(&'a str),
-    FunctionCall(&'a Value),
+    /// The `functionCall` object as JSON; typed parts serialize into it.
+    FunctionCall(Cow<'a, Value>),
     None,
 }
 
@@ -36,8 +38,48 @@ pub enum CacheMissPolicy {
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum PatchOutcome {
     Skipped,
-    Patched { cache_key: Option<CacheKey> },
-    Dropped { cache_key: Option<CacheKey> },
+    /// Cache hit: the cached signature was filled in.
+    Patched {
+        cache_key: Option<CacheKey>,
+    },
+    /// Cache miss under [`CacheMissPolicy::Fallback`]: the dummy was filled in.
+    Fallback {
+        cache_key: Option<CacheKey>,
+    },
+    /// Cache miss under [`CacheMissPolicy::Drop`]: the part should be removed.
+    Dropped {
+        cache_key: Option<CacheKey>,
+    },
+}
+
+/// Tally of the [`PatchOutcome`]s produced for one request.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PatchSummary {
+    pub hits: u32,
+    /// Parts that got the dummy signature.
+    pub misses: u32,
+    pub dropped: u32,
+}
+
+impl PatchSummary {
+    pub fn record(&mut self, outcome: &PatchOutcome) {
+        match outcome {
+            PatchOutcome::Skipped => {}
+            PatchOutcome::Patched { .. } => self.hits += 1,
+            PatchOutcome::Fallback { .. } => self.misses += 1,
+            PatchOutcome::Dropped { .. } => self.dropped += 1,
+        }
+    }
+}
+
+impl fmt::Display for PatchSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "hits={}; misses={}; dropped={}",
+            self.hits, self.misses, self.dropped
+        )
+    }
 }
 
 /// Pure data-access trait for types that carry a thought signature slot.
@@ -70,16 +112,24 @@ impl SignaturePatcher {
             return PatchOutcome::Skipped;
         }
 
-        let cache_key = match item.data() {
-            PatchEvent::ThoughtText(text) => CacheKeyGenerator::generate_text(text),
-            PatchEvent::FunctionCall(function_call) => {
-                CacheKeyGenerator::generate_json(function_call)
+        let (cache_key, signature) = {
+            let event = item.data();
+            if matches!(event, PatchEvent::None) {
+                return PatchOutcome::Skipped;
             }
-            PatchEvent::None => return PatchOutcome::Skipped,
+            let cache_key = fingerprint(self.engine.keys(), &event);
+            // A key of the previous fingerprint version is migrated on a miss.
+            let signature = cache_key.and_then(|key| {
+                self.engine.get_signature(&key).or_else(|| {
+                    let legacy = fingerprint(self.engine.legacy_keys()?, &event)?;
+                    self.engine.migrate_signature(&legacy, key)
+                })
+            });
+            (cache_key, signature)
         };
 
         // Cache hit — use the cached signature.
-        if let Some(signature) = cache_key.and_then(|k| self.engine.get_signature(&k)) {
+        if let Some(signature) = signature {
             *item.thought_signature_mut() = Some(signature.to_string());
             return PatchOutcome::Patched { cache_key };
         }
@@ -88,16 +138,25 @@ impl SignaturePatcher {
         match self.policy {
             CacheMissPolicy::Fallback => {
                 *item.thought_signature_mut() = Some(self.engine.fallback_signature().to_string());
-                PatchOutcome::Patched { cache_key }
+                PatchOutcome::Fallback { cache_key }
             }
             CacheMissPolicy::Drop => PatchOutcome::Dropped { cache_key },
         }
     }
 }
 
+fn fingerprint(keys: CacheKeyGenerator, event: &PatchEvent<'_>) -> Option<CacheKey> {
+    match event {
+        PatchEvent::ThoughtText(text) => keys.generate_text(text),
+        PatchEvent::FunctionCall(function_call) => keys.generate_json(function_call),
+        PatchEvent::None => None,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::FingerprintVersion;
     use serde_json::{Value, json};
 
     enum FakeData {
@@ -129,7 +188,9 @@ mod tests {
         fn data(&self) -> PatchEvent<'_> {
             match &self.data {
                 FakeData::Text(text) => PatchEvent::ThoughtText(text),
-                FakeData::FunctionCall(function_call) => PatchEvent::FunctionCall(function_call),
+                FakeData::FunctionCall(function_call) => {
+                    PatchEvent::FunctionCall(Cow::Borrowed(function_call))
+                }
                 FakeData::None => PatchEvent::None,
             }
         }
@@ -159,7 +220,9 @@ mod tests {
     #[test]
     fn patch_text_with_cache_hit_uses_cached_signature() {
         let (patcher, engine) = patcher_with_engine(CacheMissPolicy::Fallback);
-        let key = CacheKeyGenerator::generate_text("alpha").expect("text key must exist");
+        let key = CacheKeyGenerator::default()
+            .generate_text("alpha")
+            .expect("text key must exist");
         engine.put_signature(key, Arc::from("sig_alpha"));
 
         let mut item = FakePatchable::new(FakeData::Text("alpha"));
@@ -185,8 +248,8 @@ mod tests {
         let outcome = patcher.patch(&mut item);
         assert_eq!(
             outcome,
-            PatchOutcome::Patched {
-                cache_key: CacheKeyGenerator::generate_json(&function_call),
+            PatchOutcome::Fallback {
+                cache_key: CacheKeyGenerator::default().generate_json(&function_call),
             }
         );
         assert_eq!(
@@ -203,7 +266,7 @@ mod tests {
         assert_eq!(
             outcome,
             PatchOutcome::Dropped {
-                cache_key: CacheKeyGenerator::generate_text("uncached"),
+                cache_key: CacheKeyGenerator::default().generate_text("uncached"),
             }
         );
         assert!(item.signature.is_none());
@@ -233,7 +296,7 @@ mod tests {
         let patcher = patcher(CacheMissPolicy::Fallback);
         let mut item = FakePatchable::new(FakeData::Text("   "));
         let outcome = patcher.patch(&mut item);
-        assert_eq!(outcome, PatchOutcome::Patched { cache_key: None });
+        assert_eq!(outcome, PatchOutcome::Fallback { cache_key: None });
         assert_eq!(
             item.signature.as_deref(),
             Some("skip_thought_signature_validator")
@@ -248,4 +311,59 @@ mod tests {
         assert_eq!(outcome, PatchOutcome::Dropped { cache_key: None });
         assert!(item.signature.is_none());
     }
+
+    #[test]
+    fn legacy_key_hit_is_migrated_to_the_current_version() {
+        let engine = Arc::new(
+            ThoughtSignatureEngine::new(3600, 1024)
+                .with_fingerprint(FingerprintVersion::V2, Some(FingerprintVersion::V1)),
+        );
+        let legacy = CacheKeyGenerator::default().generate_text("alpha").unwrap();
+        engine.put_signature(legacy, Arc::from("sig_alpha"));
+        let patcher = SignaturePatcher::new(engine.clone(), CacheMissPolicy::Drop);
+
+        let mut item = FakePatchable::new(FakeData::Text("alpha"));
+        let current = engine.keys().generate_text("alpha");
+        assert_eq!(
+            patcher.patch(&mut item),
+            PatchOutcome::Patched { cache_key: current }
+        );
+        assert_eq!(item.signature.as_deref(), Some("sig_alpha"));
+        assert!(engine.get_signature(&legacy).is_none());
+
+        // Without `migrate_from` the legacy entry is not consulted.
+        let engine = Arc::new(
+            ThoughtSignatureEngine::new(3600, 1024).with_fingerprint(FingerprintVersion::V2, None),
+        );
+        engine.put_signature(legacy, Arc::from("sig_alpha"));
+        let patcher = SignaturePatcher::new(engine, CacheMissPolicy::Drop);
+        let mut item = FakePatchable::new(FakeData::Text("alpha"));
+        assert!(matches!(
+            patcher.patch(&mut item),
+            PatchOutcome::Dropped { .. }
+        ));
+    }
+
+    #[test]
+    fn summary_counts_each_outcome_kind() {
+        let mut summary = PatchSummary::default();
+        for outcome in [
+            PatchOutcome::Skipped,
+            PatchOutcome::Patched { cache_key: None },
+            PatchOutcome::Patched { cache_key: None },
+            PatchOutcome::Fallback { cache_key: None },
+            PatchOutcome::Dropped { cache_key: None },
+        ] {
+            summary.record(&outcome);
+        }
+        assert_eq!(
+            summary,
+            PatchSummary {
+                hits: 2,
+                misses: 1,
+                dropped: 1
+            }
+        );
+        assert_eq!(summary.to_string(), "hits=2; misses=1; dropped=1");
+    }
 }
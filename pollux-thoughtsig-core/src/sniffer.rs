@@ -1,11 +1,13 @@
 use crate::ThoughtSignatureEngine;
-use crate::fingerprint::CacheKeyGenerator;
+use crate::fingerprint::CacheKey;
 use serde_json::Value;
+use std::borrow::Cow;
 use std::sync::Arc;
 
 pub enum SniffEvent<'a> {
     ThoughtText(&'a str),
-    FunctionCall(&'a Value),
+    /// The `functionCall` object as JSON; typed parts serialize into it.
+    FunctionCall(Cow<'a, Value>),
     None,
 }
 
@@ -16,10 +18,38 @@ pub trait Sniffable {
     fn is_finished(&self) -> bool;
 }
 
+/// One streamed response chunk, which may carry several parts.
+///
+/// Implementors hand each part to `visit` in order; only the last part of a
+/// chunk that ends its candidate should report [`Sniffable::is_finished`].
+pub trait SniffableChunk {
+    fn visit_parts(&self, visit: &mut dyn FnMut(&dyn Sniffable));
+}
+
+/// Bounds on what a [`SignatureSniffer`] holds between chunks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SniffLimits {
+    /// Thought text buffered per candidate. A longer thought is not cached;
+    /// the patcher's miss policy covers it on the way back in.
+    pub max_thought_bytes: usize,
+}
+
+impl Default for SniffLimits {
+    fn default() -> Self {
+        Self {
+            max_thought_bytes: 1024 * 1024,
+        }
+    }
+}
+
 #[derive(Debug, Default)]
 pub struct SessionState {
     thought_buffer: String,
-    function_buffer: Option<Value>,
+    /// Set once the thought outgrew [`SniffLimits::max_thought_bytes`].
+    thought_overflowed: bool,
+    /// Key of the latest function call, hashed as it arrives so the call
+    /// itself is not kept.
+    function_key: Option<CacheKey>,
     pending_signature: Option<String>,
     current_index: Option<u32>,
 }
@@ -27,7 +57,8 @@ pub struct SessionState {
 impl SessionState {
     fn reset(&mut self, new_index: u32) {
         self.thought_buffer.clear();
-        self.function_buffer = None;
+        self.thought_overflowed = false;
+        self.function_key = None;
         self.pending_signature = None;
         self.current_index = Some(new_index);
     }
@@ -35,18 +66,39 @@ impl SessionState {
 
 pub struct SignatureSniffer {
     engine: Arc<ThoughtSignatureEngine>,
+    limits: SniffLimits,
+    /// Tag for captured signatures, so they can be flushed per model.
+    model: Option<Arc<str>>,
     state: SessionState,
 }
 
 impl SignatureSniffer {
     pub fn new(engine: Arc<ThoughtSignatureEngine>) -> Self {
+        Self::with_limits(engine, SniffLimits::default())
+    }
+
+    pub fn with_limits(engine: Arc<ThoughtSignatureEngine>, limits: SniffLimits) -> Self {
         Self {
             engine,
+            limits,
+            model: None,
             state: SessionState::default(),
         }
     }
 
-    pub fn inspect<T: Sniffable>(&mut self, item: &T) {
+    /// Tag signatures captured by this sniffer with `model`.
+    #[must_use]
+    pub fn with_model(mut self, model: &str) -> Self {
+        self.model = Some(Arc::from(model));
+        self
+    }
+
+    /// Feed every part of one streamed chunk, as it arrives.
+    pub fn inspect_chunk<C: SniffableChunk + ?Sized>(&mut self, chunk: &C) {
+        chunk.visit_parts(&mut |part| self.inspect(part));
+    }
+
+    pub fn inspect<T: Sniffable + ?Sized>(&mut self, item: &T) {
         if let Some(next_index) = item.index()
             && self.state.current_index != Some(next_index)
         {
@@ -55,9 +107,9 @@ impl SignatureSniffer {
         }
 
         match item.data() {
-            SniffEvent::ThoughtText(thought) => self.state.thought_buffer.push_str(thought),
+            SniffEvent::ThoughtText(thought) => self.buffer_thought(thought),
             SniffEvent::FunctionCall(function) => {
-                self.state.function_buffer = Some(function.clone())
+                self.state.function_key = self.engine.keys().generate_json(&function);
             }
             SniffEvent::None => {}
         }
@@ -71,8 +123,20 @@ impl SignatureSniffer {
         }
     }
 
+    fn buffer_thought(&mut self, thought: &str) {
+        if self.state.thought_overflowed {
+            return;
+        }
+        if self.state.thought_buffer.len() + thought.len() > self.limits.max_thought_bytes {
+            self.state.thought_overflowed = true;
+            self.state.thought_buffer = String::new();
+            return;
+        }
+        self.state.thought_buffer.push_str(thought);
+    }
+
     fn flush(&mut self) {
-        if self.state.thought_buffer.is_empty() && self.state.function_buffer.is_none() {
+        if self.state.thought_buffer.is_empty() && self.state.function_key.is_none() {
             // No data, so we skip flushing to avoid storing empty keys
             return;
         }
@@ -89,17 +153,19 @@ impl SignatureSniffer {
 
         let signature: crate::ThoughtSignature = Arc::from(signature);
 
-        if let Some(text_key) = CacheKeyGenerator::generate_text(&self.state.thought_buffer) {
-            self.engine.put_signature(text_key, signature.clone());
+        if let Some(text_key) = self.engine.keys().generate_text(&self.state.thought_buffer) {
+            self.store(text_key, signature.clone());
         }
 
-        if let Some(function_key) = self
-            .state
-            .function_buffer
-            .as_ref()
-            .and_then(CacheKeyGenerator::generate_json)
-        {
-            self.engine.put_signature(function_key, signature);
+        if let Some(function_key) = self.state.function_key {
+            self.store(function_key, signature);
+        }
+    }
+
+    fn store(&self, key: CacheKey, signature: crate::ThoughtSignature) {
+        match self.model.as_deref() {
+            Some(model) => self.engine.put_model_signature(key, signature, model),
+            None => self.engine.put_signature(key, signature),
         }
     }
 }
@@ -107,6 +173,7 @@ impl SignatureSniffer {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::fingerprint::CacheKeyGenerator;
 
     enum DataKind {
         Text(&'static str),
@@ -125,7 +192,9 @@ mod tests {
         fn data(&self) -> SniffEvent<'_> {
             match &self.data_kind {
                 DataKind::Text(text) => SniffEvent::ThoughtText(text),
-                DataKind::FunctionCall(function_call) => SniffEvent::FunctionCall(function_call),
+                DataKind::FunctionCall(function_call) => {
+                    SniffEvent::FunctionCall(Cow::Borrowed(function_call))
+                }
                 DataKind::None => SniffEvent::None,
             }
         }
@@ -172,8 +241,9 @@ mod tests {
         };
         sniffer.inspect(&third);
 
-        let key =
-            CacheKeyGenerator::generate_text("alpha beta").expect("text key must be generated");
+        let key = CacheKeyGenerator::default()
+            .generate_text("alpha beta")
+            .expect("text key must be generated");
         let cached = engine.get_signature(&key).expect("text key must be stored");
         assert_eq!(cached, Arc::from("sig_001"));
     }
@@ -197,7 +267,8 @@ mod tests {
 
         sniffer.inspect(&item);
 
-        let key = CacheKeyGenerator::generate_json(&function_call)
+        let key = CacheKeyGenerator::default()
+            .generate_json(&function_call)
             .expect("function hash key must be generated");
         let cached = engine
             .get_signature(&key)
@@ -218,7 +289,85 @@ mod tests {
         };
 
         sniffer.inspect(&item);
-        let key = CacheKeyGenerator::generate_text("alpha").expect("text key must be generated");
+        let key = CacheKeyGenerator::default()
+            .generate_text("alpha")
+            .expect("text key must be generated");
         assert!(engine.get_signature(&key).is_none());
     }
+
+    struct FakeChunk(Vec<FakeSniffable>);
+
+    impl SniffableChunk for FakeChunk {
+        fn visit_parts(&self, visit: &mut dyn FnMut(&dyn Sniffable)) {
+            for part in &self.0 {
+                visit(part);
+            }
+        }
+    }
+
+    #[test]
+    fn chunk_parts_are_sniffed_in_order() {
+        let engine = Arc::new(ThoughtSignatureEngine::new(3600, 128));
+        let mut sniffer = SignatureSniffer::new(engine.clone());
+        let function_call = serde_json::json!({"name": "f", "args": {}});
+
+        sniffer.inspect_chunk(&FakeChunk(vec![
+            FakeSniffable {
+                data_kind: DataKind::Text("thinking"),
+                signature: None,
+                index: Some(0),
+                finished: false,
+            },
+            FakeSniffable {
+                data_kind: DataKind::FunctionCall(function_call.clone()),
+                signature: Some("sig_chunk"),
+                index: Some(0),
+                finished: true,
+            },
+        ]));
+
+        let text_key = CacheKeyGenerator::default()
+            .generate_text("thinking")
+            .unwrap();
+        let function_key = CacheKeyGenerator::default()
+            .generate_json(&function_call)
+            .unwrap();
+        assert_eq!(
+            engine.get_signature(&text_key),
+            Some(Arc::from("sig_chunk"))
+        );
+        assert_eq!(
+            engine.get_signature(&function_key),
+            Some(Arc::from("sig_chunk"))
+        );
+    }
+
+    #[test]
+    fn thought_over_limit_is_not_cached() {
+        let engine = Arc::new(ThoughtSignatureEngine::new(3600, 128));
+        let mut sniffer = SignatureSniffer::with_limits(
+            engine.clone(),
+            SniffLimits {
+                max_thought_bytes: 8,
+            },
+        );
+
+        for (text, signature, finished) in [
+            ("alpha ", None, false),
+            ("beta", Some("sig_long"), false),
+            ("", None, true),
+        ] {
+            sniffer.inspect(&FakeSniffable {
+                data_kind: DataKind::Text(text),
+                signature,
+                index: Some(0),
+                finished,
+            });
+        }
+
+        for text in ["alpha beta", "alpha ", "beta"] {
+            let key = CacheKeyGenerator::default().generate_text(text).unwrap();
+            assert!(engine.get_signature(&key).is_none());
+        }
+    }
 }
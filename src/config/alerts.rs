@@ -0,0 +1,119 @@
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+/// Body shape posted to `alerts.webhook_url`.
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertFormat {
+    /// `{"event": ..., "text": ..., "suppressed": ..., "at": ...}` plus the
+    /// event's own fields.
+    #[default]
+    Json,
+    /// Slack incoming webhook: `{"text": ...}`.
+    Slack,
+    /// Telegram Bot API `sendMessage`: `{"chat_id": ..., "text": ...}`.
+    Telegram,
+}
+
+/// Webhook alerts on pool events: credential bans, permanent refresh
+/// failures, requests finding no credential for their model and database
+/// errors.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct AlertsConfig {
+    /// Where alerts are posted; unset turns alerts off. For Telegram,
+    /// `https://api.telegram.org/bot<token>/sendMessage`.
+    /// TOML: `alerts.webhook_url`. Default: unset.
+    #[serde(default)]
+    pub webhook_url: Option<Url>,
+
+    /// TOML: `alerts.format`. Default: `json`.
+    #[serde(default)]
+    pub format: AlertFormat,
+
+    /// Chat that receives Telegram alerts; required for `telegram`.
+    /// TOML: `alerts.telegram_chat_id`.
+    #[serde(default)]
+    pub telegram_chat_id: Option<String>,
+
+    /// Repeats of the same alert (same event about the same credential,
+    /// model or query) within this many seconds are counted, not sent; the
+    /// next one sent reports how many were held back.
+    /// TOML: `alerts.min_interval_secs`. Default: `300`.
+    #[serde(default = "default_min_interval_secs")]
+    pub min_interval_secs: u64,
+
+    /// Alerts sent per minute across all events; the rest are dropped.
+    /// TOML: `alerts.max_per_minute`. Default: `20`.
+    #[serde(default = "default_max_per_minute")]
+    pub max_per_minute: u32,
+}
+
+impl Default for AlertsConfig {
+    fn default() -> Self {
+        Self {
+            webhook_url: None,
+            format: AlertFormat::default(),
+            telegram_chat_id: None,
+            min_interval_secs: default_min_interval_secs(),
+            max_per_minute: default_max_per_minute(),
+        }
+    }
+}
+
+impl AlertsConfig {
+    /// Problems that make these alerts unusable.
+    pub(crate) fn validate(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        if self.webhook_url.is_none() {
+            return problems;
+        }
+        if self.format == AlertFormat::Telegram
+            && self
+                .telegram_chat_id
+                .as_deref()
+                .is_none_or(|id| id.trim().is_empty())
+        {
+            problems
+                .push("alerts.telegram_chat_id must be set for the telegram format".to_string());
+        }
+        if self.max_per_minute == 0 {
+            problems.push("alerts.max_per_minute must be at least 1".to_string());
+        }
+        problems
+    }
+}
+
+fn default_min_interval_secs() -> u64 {
+    300
+}
+
+fn default_max_per_minute() -> u32 {
+    20
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn telegram_alerts_need_a_chat() {
+        let alerts =
+            |value: serde_json::Value| -> AlertsConfig { serde_json::from_value(value).unwrap() };
+        assert!(
+            alerts(serde_json::json!({"format": "telegram"}))
+                .validate()
+                .is_empty()
+        );
+
+        let url = "https://api.telegram.org/botTOKEN/sendMessage";
+        let no_chat = alerts(serde_json::json!({"webhook_url": url, "format": "telegram"}));
+        assert_eq!(no_chat.validate().len(), 1);
+
+        let ok = alerts(serde_json::json!({
+            "webhook_url": url, "format": "telegram", "telegram_chat_id": "-100"
+        }));
+        assert!(ok.validate().is_empty());
+        assert_eq!(ok.min_interval_secs, 300);
+    }
+}
@@ -1,6 +1,8 @@
+use super::providers::Priority;
 use serde::{Deserialize, Deserializer, Serialize};
 use serde_json::Value;
-use std::net::{IpAddr, Ipv4Addr};
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::path::PathBuf;
 
 /// Basic (core) configuration managed by Figment.
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -15,11 +17,33 @@ pub struct BasicConfig {
     #[serde(default = "default_listen_port")]
     pub listen_port: u16,
 
-    /// Database URL for `SQLite`.
+    /// Listener overriding `listen_addr`/`listen_port`: `unix:/run/pollux.sock`
+    /// for a Unix domain socket, or a socket address such as `127.0.0.1:8188`.
+    /// Sockets passed in by systemd socket activation take precedence over both.
+    /// TOML: `basic.listen`. Default: unset.
+    #[serde(default)]
+    pub listen: Option<String>,
+
+    /// Database URL. `sqlite://...` (default), or `postgres://...` or
+    /// `mysql://...` (built with the `mysql` feature) for a store shared
+    /// across multiple Pollux instances.
     /// TOML: `basic.database_url`. Default: `sqlite://data.db`.
     #[serde(default)]
     pub database_url: String,
 
+    /// Encrypt stored refresh and access tokens with AES-256-GCM under the
+    /// SHA-256 of this secret. Existing plaintext rows are encrypted at
+    /// startup; losing the secret means re-adding every credential.
+    /// TOML: `basic.db_encryption_key`. Default: unset (tokens stored as-is).
+    #[serde(default)]
+    pub db_encryption_key: Option<String>,
+
+    /// File holding `db_encryption_key`, read once at startup; a trailing
+    /// newline is ignored. Mutually exclusive with `db_encryption_key`.
+    /// TOML: `basic.db_encryption_key_file`. Default: unset.
+    #[serde(default)]
+    pub db_encryption_key_file: Option<PathBuf>,
+
     /// Log level for tracing subscriber initialization (e.g., "error", "warn", "info", "debug", "trace").
     /// TOML: `basic.loglevel`. Default: `info`.
     #[serde(default)]
@@ -31,12 +55,250 @@ pub struct BasicConfig {
     #[serde(deserialize_with = "deserialize_string_lax")]
     pub pollux_key: String,
 
+    /// File holding `pollux_key`, e.g. a mounted container secret; read once
+    /// at startup, a trailing newline is ignored. Mutually exclusive with
+    /// `pollux_key`.
+    /// TOML: `basic.pollux_key_file`. Default: unset.
+    #[serde(default)]
+    pub pollux_key_file: Option<PathBuf>,
+
     /// Whether OAuth CSRF/PKCE cookies are marked insecure (`Secure=false`).
     /// TOML: `basic.insecure_cookie`. Default: `false`.
     ///
     /// Keep `false` in production/HTTPS. Set `true` only for local plain-HTTP testing.
     #[serde(default)]
     pub insecure_cookie: bool,
+
+    /// Additional API keys restricted to a subset of routes.
+    /// TOML: `[[basic.api_keys]]`. Default: none.
+    ///
+    /// `pollux_key` keeps access to every route; these keys are for lending out
+    /// a narrow slice of the pool. Admin routes (`/admin/*`) take only
+    /// `pollux_key`, even for a key whose patterns would match them.
+    #[serde(default)]
+    pub api_keys: Vec<ApiKeyConfig>,
+
+    /// Never log request/response payloads; only metadata (model, status,
+    /// latency, sizes) is kept.
+    /// TOML: `basic.compliance_mode`. Default: `false`.
+    ///
+    /// Builds with the `compliance` cargo feature always behave as if this is `true`.
+    #[serde(default)]
+    pub compliance_mode: bool,
+
+    /// Seconds to let in-flight requests (including SSE streams) finish after
+    /// a shutdown signal. Streams still open afterwards get a final SSE
+    /// `error` event and are closed.
+    /// TOML: `basic.shutdown_drain_secs`. Default: `30`.
+    #[serde(default = "default_shutdown_drain_secs")]
+    pub shutdown_drain_secs: u64,
+
+    /// Seconds between writes of the cumulative request counters to the
+    /// database; they are also written once on shutdown.
+    /// TOML: `basic.counters_flush_secs`. Default: `60`.
+    #[serde(default = "default_counters_flush_secs")]
+    pub counters_flush_secs: u64,
+
+    /// Limits applied per client key: `pollux_key` and every `api_keys` entry
+    /// without its own `rate_limit`. Admin routes are not limited.
+    /// TOML: `[basic.rate_limit]`. Default: unlimited.
+    #[serde(default)]
+    pub rate_limit: Option<RateLimitConfig>,
+
+    /// Server-wide cap on proxied requests in flight, streams included until
+    /// they end. Admin, health and OAuth routes are not counted.
+    /// TOML: `[basic.concurrency_limit]`. Default: unlimited.
+    #[serde(default)]
+    pub concurrency_limit: Option<ConcurrencyLimitConfig>,
+
+    /// Protections for the `/<provider>/resource:add` credential uploads.
+    /// TOML: `[basic.resource_add]`.
+    #[serde(default)]
+    pub resource_add: ResourceAddConfig,
+
+    /// Append one JSON line per proxied request (provider, model, credential,
+    /// client key fingerprint, status, latency, retries, tokens) to this file.
+    /// TOML: `basic.audit_log_path`. Default: unset (no audit log).
+    #[serde(default)]
+    pub audit_log_path: Option<PathBuf>,
+
+    /// Size at which the audit log is rotated to `<path>.1`.
+    /// TOML: `basic.audit_log_max_bytes`. Default: `104857600` (100 MiB).
+    #[serde(default = "default_audit_log_max_bytes")]
+    pub audit_log_max_bytes: u64,
+
+    /// Rotated audit logs kept (`<path>.1` is the newest); older ones are deleted.
+    /// TOML: `basic.audit_log_max_files`. Default: `5`.
+    #[serde(default = "default_audit_log_max_files")]
+    pub audit_log_max_files: usize,
+
+    /// Copy every streamed Gemini CLI and Antigravity upstream response to a
+    /// `.sse` file in this directory, for `pollux replay`. Debug only: files
+    /// hold full response content and are never rotated, so this is rejected
+    /// in compliance mode.
+    /// TOML: `basic.stream_capture_dir`. Default: unset (no capture).
+    #[serde(default)]
+    pub stream_capture_dir: Option<PathBuf>,
+
+    /// Lets two processes on one host share the database, e.g. the old and
+    /// new binary during an upgrade.
+    /// TOML: `[basic.coordination]`. Default: off.
+    #[serde(default)]
+    pub coordination: CoordinationConfig,
+}
+
+/// A scoped API key.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct ApiKeyConfig {
+    /// Label used in logs; never the key itself.
+    pub name: String,
+
+    #[serde(deserialize_with = "deserialize_string_lax")]
+    pub key: String,
+
+    /// Path patterns this key may call. `*` matches any run of characters,
+    /// e.g. `"/codex/*"` or `"/geminicli/v1beta/models/*:generateContent"`.
+    /// `v1beta` patterns also cover the `v1alpha` and `v1` Gemini aliases.
+    pub routes: Vec<String>,
+
+    /// Overrides `basic.rate_limit` for this key.
+    #[serde(default)]
+    pub rate_limit: Option<RateLimitConfig>,
+
+    /// Request parameters this key may not use.
+    #[serde(default)]
+    pub policy: Option<RequestPolicyConfig>,
+
+    /// Priority of this key's requests that send no `x-pollux-priority`, and
+    /// the highest one its `x-pollux-priority` may ask for. Default: `normal`.
+    #[serde(default)]
+    pub priority: Option<Priority>,
+}
+
+/// Hard limits on what one client key may ask for. Violations are rejected
+/// with a 400 before a credential is leased; only `strip_include` rewrites.
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct RequestPolicyConfig {
+    /// Highest `max_output_tokens` (Responses) or `maxOutputTokens` (Gemini)
+    /// accepted. Gemini requests that set none get this value.
+    #[serde(default)]
+    pub max_output_tokens: Option<u32>,
+
+    /// Reject Responses requests with `store: true`.
+    #[serde(default)]
+    pub forbid_store: bool,
+
+    /// Values removed from a Responses request's `include` list,
+    /// e.g. `"reasoning.encrypted_content"`.
+    #[serde(default)]
+    pub strip_include: Vec<String>,
+
+    /// Tools rejected by name: a Responses `tools[].type` such as
+    /// `web_search`, or a Gemini tool key such as `googleSearch`,
+    /// `codeExecution` or `functionDeclarations`.
+    #[serde(default)]
+    pub forbid_tools: Vec<String>,
+}
+
+/// Request limits for one client key; an unset limit is not enforced.
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct RateLimitConfig {
+    /// Sustained requests per second, which is also the burst size.
+    #[serde(default)]
+    pub rps: Option<u32>,
+
+    /// Prompt plus output tokens per minute. Tokens are charged when a
+    /// request finishes, so one large request can overshoot the budget.
+    #[serde(default)]
+    pub tpm: Option<u64>,
+}
+
+/// Backpressure for when Pollux itself, not the upstream pool, is the
+/// bottleneck. Requests over the cap wait for a slot and are turned away with
+/// a `429` (code `server_overloaded`) once the wait runs out.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct ConcurrencyLimitConfig {
+    /// Proxied requests served at once.
+    /// TOML: `basic.concurrency_limit.max_in_flight`. Must be set.
+    pub max_in_flight: usize,
+
+    /// How long a request over the cap may wait for a slot; `0` rejects it
+    /// at once.
+    /// TOML: `basic.concurrency_limit.queue_timeout_ms`. Default: `1000`.
+    #[serde(default = "default_queue_timeout_ms")]
+    pub queue_timeout_ms: u64,
+}
+
+/// `resource:add` accepts refresh tokens and kicks off onboarding for each,
+/// so it is guarded separately from proxy traffic.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct ResourceAddConfig {
+    /// Key required to upload credentials. When unset, only `pollux_key` is
+    /// accepted; scoped `api_keys` never are.
+    /// TOML: `basic.resource_add.admin_key`. Default: unset.
+    #[serde(default)]
+    pub admin_key: Option<String>,
+
+    /// Uploads allowed per minute, shared by all providers.
+    /// TOML: `basic.resource_add.requests_per_minute`. Default: `10`.
+    #[serde(default = "default_resource_add_rpm")]
+    pub requests_per_minute: u32,
+
+    /// Most entries accepted in one upload; larger batches get `413`.
+    /// TOML: `basic.resource_add.max_batch`. Default: `100`.
+    #[serde(default = "default_resource_add_max_batch")]
+    pub max_batch: usize,
+}
+
+impl Default for ResourceAddConfig {
+    fn default() -> Self {
+        Self {
+            admin_key: None,
+            requests_per_minute: default_resource_add_rpm(),
+            max_batch: default_resource_add_max_batch(),
+        }
+    }
+}
+
+/// Leader election between Pollux processes sharing one database.
+///
+/// Every process serves traffic. Only the one holding the lock refreshes
+/// tokens and onboards new credentials; the others pick refreshed tokens up
+/// from the database and take over the lock when the leader exits.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct CoordinationConfig {
+    /// Lock file used for the election; coordination is off when unset.
+    /// TOML: `basic.coordination.lock_path`. Default: unset.
+    #[serde(default)]
+    pub lock_path: Option<PathBuf>,
+
+    /// How often a follower retries the lock.
+    /// TOML: `basic.coordination.poll_secs`. Default: `2`.
+    #[serde(default = "default_coordination_poll_secs")]
+    pub poll_secs: u64,
+
+    /// Bind the listener with `SO_REUSEPORT` so a new process can start
+    /// accepting on the same port before the old one drains and exits.
+    /// Unix only.
+    /// TOML: `basic.coordination.reuse_port`. Default: `false`.
+    #[serde(default)]
+    pub reuse_port: bool,
+}
+
+impl Default for CoordinationConfig {
+    fn default() -> Self {
+        Self {
+            lock_path: None,
+            poll_secs: default_coordination_poll_secs(),
+            reuse_port: false,
+        }
+    }
 }
 
 impl Default for BasicConfig {
@@ -44,15 +306,58 @@ impl Default for BasicConfig {
         Self {
             listen_addr: default_listen_ip(),
             listen_port: default_listen_port(),
+            listen: None,
             database_url: "sqlite://data.db".to_string(),
+            db_encryption_key: None,
+            db_encryption_key_file: None,
             loglevel: "info".to_string(),
             // No insecure default. `Config::from_toml()` enforces non-empty.
             pollux_key: String::new(),
+            pollux_key_file: None,
             insecure_cookie: false,
+            api_keys: Vec::new(),
+            compliance_mode: false,
+            shutdown_drain_secs: default_shutdown_drain_secs(),
+            counters_flush_secs: default_counters_flush_secs(),
+            rate_limit: None,
+            concurrency_limit: None,
+            resource_add: ResourceAddConfig::default(),
+            audit_log_path: None,
+            audit_log_max_bytes: default_audit_log_max_bytes(),
+            audit_log_max_files: default_audit_log_max_files(),
+            stream_capture_dir: None,
+            coordination: CoordinationConfig::default(),
         }
     }
 }
 
+/// Where the HTTP server listens, from `basic.listen` or `listen_addr`/`listen_port`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ListenTarget {
+    Tcp(SocketAddr),
+    Unix(PathBuf),
+}
+
+impl BasicConfig {
+    pub fn listen_target(&self) -> Result<ListenTarget, String> {
+        let Some(listen) = self.listen.as_deref() else {
+            return Ok(ListenTarget::Tcp(SocketAddr::new(
+                self.listen_addr,
+                self.listen_port,
+            )));
+        };
+        if let Some(path) = listen.strip_prefix("unix:") {
+            if path.is_empty() {
+                return Err("basic.listen: `unix:` needs a socket path".to_string());
+            }
+            return Ok(ListenTarget::Unix(PathBuf::from(path)));
+        }
+        listen.parse().map(ListenTarget::Tcp).map_err(|_| {
+            format!("basic.listen: expected `unix:<path>` or `ip:port`, got `{listen}`")
+        })
+    }
+}
+
 fn deserialize_string_lax<'de, D>(deserializer: D) -> Result<String, D::Error>
 where
     D: Deserializer<'de>,
@@ -63,7 +368,7 @@ where
         Value::String(s) => Ok(s),
         Value::Number(n) => Ok(n.to_string()),
         _ => Err(serde::de::Error::custom(
-            "expected a string or a number for basic.pollux_key",
+            "expected a string or a number for an API key",
         )),
     }
 }
@@ -77,3 +382,35 @@ fn default_listen_ip() -> IpAddr {
 fn default_listen_port() -> u16 {
     8188
 }
+
+fn default_shutdown_drain_secs() -> u64 {
+    30
+}
+
+fn default_counters_flush_secs() -> u64 {
+    60
+}
+
+fn default_coordination_poll_secs() -> u64 {
+    2
+}
+
+fn default_queue_timeout_ms() -> u64 {
+    1000
+}
+
+fn default_resource_add_rpm() -> u32 {
+    10
+}
+
+fn default_resource_add_max_batch() -> usize {
+    100
+}
+
+fn default_audit_log_max_bytes() -> u64 {
+    100 * 1024 * 1024
+}
+
+fn default_audit_log_max_files() -> usize {
+    5
+}
@@ -0,0 +1,426 @@
+//! `pollux check-config`: validate `config.toml` without starting the server.
+//!
+//! Loads and validates the file with its environment overrides, resolves
+//! every provider, cross-checks model lists, aliases and upstream URLs, then
+//! opens the database read-only to compare its schema version and saved
+//! model registry with this build.
+//! Errors fail the check; warnings are printed but do not.
+
+use super::{Config, DEFAULT_CONFIG_FILE, ENV_PREFIX, ModelAliases};
+use crate::db::{DbBackendKind, DbPool, migrations};
+use crate::model_catalog::consistency;
+use crate::providers::manifest::ProviderKind;
+use crate::utils::http::redact_proxy;
+use std::collections::HashSet;
+use std::fmt;
+use std::path::Path;
+use url::Url;
+
+pub const USAGE: &str = "\
+Usage: pollux check-config [--skip-db]
+
+Loads config.toml and POLLUX_* overrides, resolves every provider and
+checks model lists, aliases, upstream URLs and proxies. Unless --skip-db is
+given, also opens the database (without creating or migrating it) and
+compares its schema version and saved model registry with this build.
+Exits non-zero on any error.";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CheckArgs {
+    pub database: bool,
+}
+
+impl CheckArgs {
+    /// Parse the arguments following `check-config`.
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Self, String> {
+        let mut database = true;
+        for arg in args {
+            match arg.as_str() {
+                "--skip-db" => database = false,
+                other => return Err(format!("unexpected argument `{other}`")),
+            }
+        }
+        Ok(Self { database })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Ok,
+    Warn,
+    Error,
+}
+
+#[derive(Debug)]
+pub struct Finding {
+    pub section: &'static str,
+    pub severity: Severity,
+    pub message: String,
+}
+
+#[derive(Debug, Default)]
+pub struct CheckReport {
+    pub findings: Vec<Finding>,
+}
+
+impl CheckReport {
+    /// `true` when no finding is an error.
+    #[must_use]
+    pub fn passed(&self) -> bool {
+        self.count(Severity::Error) == 0
+    }
+
+    fn count(&self, severity: Severity) -> usize {
+        self.findings
+            .iter()
+            .filter(|f| f.severity == severity)
+            .count()
+    }
+
+    fn push(&mut self, section: &'static str, severity: Severity, message: impl Into<String>) {
+        self.findings.push(Finding {
+            section,
+            severity,
+            message: message.into(),
+        });
+    }
+
+    fn ok(&mut self, section: &'static str, message: impl Into<String>) {
+        self.push(section, Severity::Ok, message);
+    }
+
+    fn warn(&mut self, section: &'static str, message: impl Into<String>) {
+        self.push(section, Severity::Warn, message);
+    }
+
+    fn error(&mut self, section: &'static str, message: impl Into<String>) {
+        self.push(section, Severity::Error, message);
+    }
+}
+
+impl fmt::Display for CheckReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "check-config {DEFAULT_CONFIG_FILE}")?;
+        for finding in &self.findings {
+            let status = match finding.severity {
+                Severity::Ok => "ok",
+                Severity::Warn => "WARN",
+                Severity::Error => "FAIL",
+            };
+            writeln!(
+                f,
+                "  {status:<4}  {:<11}  {}",
+                finding.section, finding.message
+            )?;
+        }
+        writeln!(
+            f,
+            "{} errors, {} warnings",
+            self.count(Severity::Error),
+            self.count(Severity::Warn)
+        )
+    }
+}
+
+/// Run every check and collect the findings.
+pub async fn run(args: &CheckArgs) -> CheckReport {
+    let mut report = CheckReport::default();
+    let from_file = Path::new(DEFAULT_CONFIG_FILE).is_file();
+    if !from_file {
+        report.warn(
+            "config",
+            format!("{DEFAULT_CONFIG_FILE} not found; using defaults and {ENV_PREFIX}* variables"),
+        );
+    }
+    let mut cfg: Config = match Config::figment().extract() {
+        Ok(cfg) => cfg,
+        Err(e) => {
+            report.error("config", format!("failed to parse: {e}"));
+            return report;
+        }
+    };
+    let mut problems = cfg.load_secret_files();
+    problems.extend(cfg.validate());
+    if problems.is_empty() && from_file {
+        report.ok("config", format!("loaded {DEFAULT_CONFIG_FILE}"));
+    }
+    for problem in problems {
+        report.error("config", problem);
+    }
+
+    check_providers(&cfg, &mut report);
+    if args.database {
+        check_database(&cfg, &mut report).await;
+    }
+    report
+}
+
+/// One provider's resolved settings, as far as the checks care.
+struct ProviderView<'a> {
+    kind: ProviderKind,
+    models: Vec<&'a str>,
+    aliases: &'a ModelAliases,
+    stream_only: &'a [String],
+    urls: Vec<(&'static str, &'a Url)>,
+    proxy: Option<&'a Url>,
+    proxy_pool: &'a [Url],
+}
+
+fn check_providers(cfg: &Config, report: &mut CheckReport) {
+    let geminicli = cfg.geminicli();
+    let codex = cfg.codex();
+    let antigravity = cfg.antigravity();
+    let claude = cfg.claude();
+    let qwen = cfg.qwen();
+    let views = [
+        ProviderView {
+            kind: ProviderKind::GeminiCli,
+            models: geminicli
+                .model_list
+                .iter()
+                .chain(&geminicli.embedding_model_list)
+                .chain(&geminicli.image_model_list)
+                .map(String::as_str)
+                .collect(),
+            aliases: &geminicli.model_aliases,
+            stream_only: &geminicli.stream_only_models,
+            urls: vec![
+                ("custom_api_url", &geminicli.custom_api_url),
+                ("oauth_token_url", &cfg.providers.geminicli.oauth_token_url),
+            ],
+            proxy: geminicli.proxy.as_ref(),
+            proxy_pool: &geminicli.proxy_pool,
+        },
+        ProviderView {
+            kind: ProviderKind::Codex,
+            models: codex.model_list.iter().map(String::as_str).collect(),
+            aliases: &codex.model_aliases,
+            stream_only: &[],
+            urls: vec![
+                ("custom_api_url", &codex.custom_api_url),
+                ("oauth_token_url", &cfg.providers.codex.oauth_token_url),
+            ],
+            proxy: codex.proxy.as_ref(),
+            proxy_pool: &codex.proxy_pool,
+        },
+        ProviderView {
+            kind: ProviderKind::Antigravity,
+            models: antigravity.model_list.iter().map(String::as_str).collect(),
+            aliases: &antigravity.model_aliases,
+            stream_only: &antigravity.stream_only_models,
+            urls: vec![
+                ("api_url", &antigravity.api_url),
+                ("oauth_token_url", &antigravity.oauth_token_url),
+            ],
+            proxy: antigravity.proxy.as_ref(),
+            proxy_pool: &antigravity.proxy_pool,
+        },
+        ProviderView {
+            kind: ProviderKind::Claude,
+            models: claude.model_list.iter().map(String::as_str).collect(),
+            aliases: &claude.model_aliases,
+            stream_only: &[],
+            urls: vec![
+                ("custom_api_url", &claude.custom_api_url),
+                ("oauth_token_url", &claude.oauth_token_url),
+            ],
+            proxy: claude.proxy.as_ref(),
+            proxy_pool: &claude.proxy_pool,
+        },
+        ProviderView {
+            kind: ProviderKind::Qwen,
+            models: qwen.model_list.iter().map(String::as_str).collect(),
+            aliases: &qwen.model_aliases,
+            stream_only: &[],
+            urls: qwen
+                .custom_api_url
+                .iter()
+                .map(|url| ("custom_api_url", url))
+                .chain([("oauth_base_url", &qwen.oauth_base_url)])
+                .collect(),
+            proxy: qwen.proxy.as_ref(),
+            proxy_pool: &qwen.proxy_pool,
+        },
+    ];
+    for view in &views {
+        check_provider(view, report);
+    }
+}
+
+fn check_provider(view: &ProviderView<'_>, report: &mut CheckReport) {
+    let section = view.kind.label();
+    let before = report.count(Severity::Error);
+
+    let mut seen = HashSet::new();
+    for model in &view.models {
+        if model.trim().is_empty() {
+            report.error(section, "model_list contains an empty model name");
+        } else if !seen.insert(*model) {
+            report.error(section, format!("model `{model}` is listed more than once"));
+        }
+    }
+    if view.models.is_empty() {
+        report.warn(section, "model_list is empty; no requests will be served");
+    }
+    for (alias, target) in view.aliases.iter() {
+        if !target.contains('*') && !seen.contains(target) {
+            report.error(
+                section,
+                format!("model_aliases `{alias}` points at `{target}`, which is not configured"),
+            );
+        }
+        if seen.contains(alias) {
+            report.warn(
+                section,
+                format!("model_aliases `{alias}` hides the configured model of that name"),
+            );
+        }
+    }
+    for model in view.stream_only {
+        if !seen.contains(model.as_str()) {
+            report.warn(
+                section,
+                format!("stream_only_models `{model}` is not in model_list"),
+            );
+        }
+    }
+    for (name, url) in &view.urls {
+        if !matches!(url.scheme(), "http" | "https") || url.host().is_none() {
+            report.error(section, format!("{name} must be an http(s) URL, got {url}"));
+        }
+    }
+
+    if report.count(Severity::Error) == before {
+        let proxy = view.proxy.map_or_else(|| "none".to_string(), redact_proxy);
+        report.ok(
+            section,
+            format!(
+                "{} models, {} aliases, proxy {proxy}, {} pooled proxies",
+                view.models.len(),
+                view.aliases.iter().count(),
+                view.proxy_pool.len()
+            ),
+        );
+    }
+}
+
+async fn check_database(cfg: &Config, report: &mut CheckReport) {
+    const SECTION: &str = "database";
+    let url = cfg.basic.database_url.as_str();
+    let kind = DbBackendKind::from_url(url);
+    let pool = match DbPool::open(url).await {
+        Ok(pool) => pool,
+        Err(e) => {
+            report.error(
+                SECTION,
+                format!("cannot open {} database: {e}", kind.as_str()),
+            );
+            return;
+        }
+    };
+
+    let latest = migrations::latest_version();
+    match migrations::current_version(&pool).await {
+        Ok(version) if version == latest => {
+            report.ok(
+                SECTION,
+                format!("{} reachable, schema at version {version}", kind.as_str()),
+            );
+        }
+        Ok(version) if version > latest => {
+            report.error(
+                SECTION,
+                format!("schema is at version {version}, newer than this build ({latest})"),
+            );
+            return;
+        }
+        Ok(version) => {
+            report.warn(
+                SECTION,
+                format!("schema is at version {version}; startup will migrate it to {latest}"),
+            );
+            return;
+        }
+        Err(e) => {
+            report.error(SECTION, format!("cannot read schema version: {e}"));
+            return;
+        }
+    }
+
+    match crate::db::actor::load_model_state(&pool).await {
+        Ok((previous, counters)) => {
+            let drift =
+                consistency::compare(&consistency::registry_rows(cfg), &previous, &counters);
+            for provider in &drift.providers {
+                report.warn(
+                    SECTION,
+                    format!(
+                        "{} models differ from the last boot: added {:?}, removed {:?}, reindexed {}, orphaned counters {:?}",
+                        provider.provider,
+                        provider.added,
+                        provider.removed,
+                        provider.reindexed.len(),
+                        provider.orphaned_counters
+                    ),
+                );
+            }
+        }
+        Err(e) => report.error(SECTION, format!("cannot read saved model registry: {e}")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn provider_report(models: &[&str], aliases: &[(&str, &str)]) -> CheckReport {
+        let aliases: ModelAliases = aliases.iter().copied().collect();
+        let url = Url::parse("https://example.com").unwrap();
+        let view = ProviderView {
+            kind: ProviderKind::Codex,
+            models: models.to_vec(),
+            aliases: &aliases,
+            stream_only: &[],
+            urls: vec![("custom_api_url", &url)],
+            proxy: None,
+            proxy_pool: &[],
+        };
+        let mut report = CheckReport::default();
+        check_provider(&view, &mut report);
+        report
+    }
+
+    #[test]
+    fn flags_duplicate_models_and_dangling_aliases() {
+        let report = provider_report(
+            &["gpt-5", "gpt-5"],
+            &[("latest", "gpt-6"), ("vendor/*", "*")],
+        );
+        let errors: Vec<_> = report
+            .findings
+            .iter()
+            .filter(|f| f.severity == Severity::Error)
+            .map(|f| f.message.as_str())
+            .collect();
+        assert_eq!(
+            errors,
+            [
+                "model `gpt-5` is listed more than once",
+                "model_aliases `latest` points at `gpt-6`, which is not configured",
+            ]
+        );
+        assert!(!report.passed());
+
+        let clean = provider_report(&["gpt-5"], &[("latest", "gpt-5")]);
+        assert!(clean.passed());
+        assert_eq!(clean.findings[0].severity, Severity::Ok);
+    }
+
+    #[test]
+    fn parses_skip_db() {
+        let parse = |list: &[&str]| CheckArgs::parse(list.iter().map(ToString::to_string));
+        assert_eq!(parse(&[]), Ok(CheckArgs { database: true }));
+        assert_eq!(parse(&["--skip-db"]), Ok(CheckArgs { database: false }));
+        assert!(parse(&["--db"]).is_err());
+    }
+}
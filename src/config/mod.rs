@@ -1,15 +1,32 @@
+pub mod check;
+
+mod alerts;
 mod basic;
 mod providers;
+mod routing;
+mod secrets;
 
-pub use basic::BasicConfig;
+pub use alerts::{AlertFormat, AlertsConfig};
+pub use basic::{
+    ApiKeyConfig, BasicConfig, ConcurrencyLimitConfig, CoordinationConfig, ListenTarget,
+    RateLimitConfig, RequestPolicyConfig, ResourceAddConfig,
+};
 pub use providers::{
-    AntigravityConfig, AntigravityResolvedConfig, CodexConfig, CodexResolvedConfig,
-    GeminiCliConfig, GeminiCliResolvedConfig, ProviderDefaults, ProvidersConfig,
+    AntigravityConfig, AntigravityResolvedConfig, AutoDisableConfig, CapabilityProbeConfig,
+    ClaudeConfig, ClaudeResolvedConfig, CodexConfig, CodexReasoningConfig, CodexResolvedConfig,
+    DailyQuotaConfig, DnsConfig, ExperimentConfig, GeminiCliConfig, GeminiCliResolvedConfig,
+    HealthAction, HealthConfig, HttpClientConfig, IpPreference, ModelAliases, ModelDiscoveryConfig,
+    ModelOverrideConfig, Priority, ProbeMethod, ProviderDefaults, ProvidersConfig, QwenConfig,
+    QwenResolvedConfig, ResponseCacheConfig, SchedulingPolicy, SseFlushConfig,
+    StreamTransformerConfig, SystemInstructionConfig, SystemInstructionMode, ThoughtSigConfig,
+    ThoughtSigStorage,
 };
+pub use routing::{MirrorConfig, RoutingConfig};
 
+use crate::providers::manifest::ProviderKind;
 use figment::{
     Figment,
-    providers::{Format, Serialized, Toml},
+    providers::{Env, Format, Serialized, Toml},
 };
 use serde::{Deserialize, Serialize};
 use std::{path::PathBuf, sync::LazyLock};
@@ -24,22 +41,40 @@ pub struct Config {
     /// Provider and upstream settings (see `providers` table in config.toml).
     #[serde(default)]
     pub providers: ProvidersConfig,
+
+    /// Cross-provider routing (see `routing` table in config.toml).
+    #[serde(default)]
+    pub routing: RoutingConfig,
+
+    /// Webhook alerts on pool events (see `alerts` table in config.toml).
+    #[serde(default)]
+    pub alerts: AlertsConfig,
 }
 
 const DEFAULT_CONFIG_FILE: &str = "config.toml";
 
+/// Prefix of environment variables layered over the TOML file.
+///
+/// The rest of the name is the TOML key path in upper case with `__` between
+/// table levels: `POLLUX_BASIC__POLLUX_KEY`, `POLLUX_PROVIDERS__CODEX__OAUTH_TPS=10`,
+/// `POLLUX_PROVIDERS__GEMINICLI__MODEL_LIST=[gemini-2.5-pro, gemini-2.5-flash]`.
+/// Values are parsed like TOML scalars, arrays and inline tables.
+pub const ENV_PREFIX: &str = "POLLUX_";
+
 impl Config {
-    /// Builds a Figment that merges defaults and a config TOML file.
+    /// Builds a Figment that merges defaults, a config TOML file and
+    /// [`ENV_PREFIX`] environment variables, later layers winning.
     pub fn figment() -> Figment {
         let figment = Figment::new().merge(Serialized::defaults(Config::default()));
-        if PathBuf::from(DEFAULT_CONFIG_FILE).is_file() {
+        let figment = if PathBuf::from(DEFAULT_CONFIG_FILE).is_file() {
             figment.merge(Toml::file(DEFAULT_CONFIG_FILE))
         } else {
             figment
-        }
+        };
+        figment.merge(Env::prefixed(ENV_PREFIX).split("__"))
     }
 
-    /// Loads configuration by merging defaults and `config.toml` if present.
+    /// Loads configuration by merging defaults, `config.toml` if present and the environment.
     ///
     /// Note: this does **not** validate required fields like `basic.pollux_key`. Binaries should
     /// call `Config::from_toml()` instead (or validate explicitly) to avoid running with insecure
@@ -50,22 +85,194 @@ impl Config {
         })
     }
 
-    /// Loads configuration from the TOML file (with defaults) and validates required fields.
+    /// Loads configuration like [`Self::figment`], reads `*_file` secrets and
+    /// validates required fields.
+    ///
+    /// `config.toml` may be absent when the environment supplies everything
+    /// required (at least `POLLUX_BASIC__POLLUX_KEY` or `..._FILE`).
     pub fn from_toml() -> Self {
-        assert!(
-            PathBuf::from(DEFAULT_CONFIG_FILE).is_file(),
-            "config file not found: {DEFAULT_CONFIG_FILE}"
-        );
-        let cfg: Self = Self::figment().extract().unwrap_or_else(|err| {
-            panic!("failed to extract configuration from {DEFAULT_CONFIG_FILE}: {err}")
+        let mut cfg: Self = Self::figment().extract().unwrap_or_else(|err| {
+            panic!(
+                "failed to extract configuration from {DEFAULT_CONFIG_FILE} and environment: {err}"
+            )
         });
-        assert!(
-            !cfg.basic.pollux_key.trim().is_empty(),
-            "basic.pollux_key must be set and non-empty"
-        );
+        let mut problems = cfg.load_secret_files();
+        problems.extend(cfg.validate());
+        assert!(problems.is_empty(), "{}", problems.join("; "));
         cfg
     }
 
+    /// Every problem that makes this configuration unusable, in file order.
+    #[must_use]
+    pub fn validate(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        let basic = &self.basic;
+        if let Err(e) = basic.listen_target() {
+            problems.push(e);
+        }
+        if basic.pollux_key.trim().is_empty() {
+            problems.push("basic.pollux_key must be set and non-empty".to_string());
+        }
+        for (i, api_key) in basic.api_keys.iter().enumerate() {
+            if api_key.key.trim().is_empty() {
+                problems.push(format!("basic.api_keys[{i}].key must be set and non-empty"));
+            }
+            if api_key.key == basic.pollux_key {
+                problems.push(format!(
+                    "basic.api_keys[{i}].key must differ from basic.pollux_key"
+                ));
+            }
+            if api_key.routes.is_empty() {
+                problems.push(format!(
+                    "basic.api_keys[{i}].routes must list at least one route pattern"
+                ));
+            }
+            if basic.api_keys[..i].iter().any(|k| k.key == api_key.key) {
+                problems.push(format!("basic.api_keys[{i}].key is duplicated"));
+            }
+        }
+        if let Some(admin_key) = &basic.resource_add.admin_key {
+            if admin_key.trim().is_empty() {
+                problems
+                    .push("basic.resource_add.admin_key must be non-empty when set".to_string());
+            }
+            if admin_key == &basic.pollux_key || basic.api_keys.iter().any(|k| &k.key == admin_key)
+            {
+                problems.push(
+                    "basic.resource_add.admin_key must differ from every proxy key".to_string(),
+                );
+            }
+        }
+        if basic
+            .concurrency_limit
+            .is_some_and(|limit| limit.max_in_flight == 0)
+        {
+            problems.push("basic.concurrency_limit.max_in_flight must be at least 1".to_string());
+        }
+        if basic.stream_capture_dir.is_some()
+            && (basic.compliance_mode || cfg!(feature = "compliance"))
+        {
+            problems.push(
+                "basic.stream_capture_dir cannot be set in compliance mode: captures hold response bodies"
+                    .to_string(),
+            );
+        }
+        problems.extend(self.validate_providers());
+        for (i, provider) in self.routing.fallback.iter().enumerate() {
+            if !matches!(
+                provider,
+                ProviderKind::Antigravity | ProviderKind::GeminiCli
+            ) {
+                problems.push(format!(
+                    "routing.fallback[{i}]: only antigravity and geminicli share models"
+                ));
+            }
+            if self.routing.fallback[..i].contains(provider) {
+                problems.push(format!("routing.fallback[{i}] is duplicated"));
+            }
+        }
+        if let Some(mirror) = &self.routing.mirror {
+            problems.extend(mirror.validate());
+        }
+        problems.extend(self.alerts.validate());
+        problems
+    }
+
+    /// Problems in the `providers` tables.
+    fn validate_providers(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        for (name, quota) in [
+            ("defaults", self.providers.defaults.daily_quota),
+            ("geminicli", self.providers.geminicli.daily_quota),
+            ("codex", self.providers.codex.daily_quota),
+            ("antigravity", self.providers.antigravity.daily_quota),
+            ("claude", self.providers.claude.daily_quota),
+            ("qwen", self.providers.qwen.daily_quota),
+        ] {
+            if quota.is_some_and(|q| q.reset_hour_utc >= 24) {
+                problems.push(format!(
+                    "providers.{name}.daily_quota.reset_hour_utc must be 0..=23"
+                ));
+            }
+        }
+        for (name, proxy, pool) in [
+            (
+                "defaults",
+                self.providers.defaults.proxy.as_ref(),
+                Some(&self.providers.defaults.proxy_pool),
+            ),
+            (
+                "geminicli",
+                self.providers.geminicli.proxy.as_ref(),
+                self.providers.geminicli.proxy_pool.as_ref(),
+            ),
+            (
+                "codex",
+                self.providers.codex.proxy.as_ref(),
+                self.providers.codex.proxy_pool.as_ref(),
+            ),
+            (
+                "antigravity",
+                self.providers.antigravity.proxy.as_ref(),
+                self.providers.antigravity.proxy_pool.as_ref(),
+            ),
+            (
+                "claude",
+                self.providers.claude.proxy.as_ref(),
+                self.providers.claude.proxy_pool.as_ref(),
+            ),
+            (
+                "qwen",
+                self.providers.qwen.proxy.as_ref(),
+                self.providers.qwen.proxy_pool.as_ref(),
+            ),
+        ] {
+            for url in proxy.into_iter().chain(pool.into_iter().flatten()) {
+                if !crate::utils::http::is_supported_proxy(url) {
+                    problems.push(format!(
+                        "providers.{name}: unsupported proxy scheme in {url}"
+                    ));
+                }
+            }
+        }
+        for (name, reserve) in [
+            ("defaults", Some(self.providers.defaults.priority_reserve)),
+            ("geminicli", self.providers.geminicli.priority_reserve),
+            ("codex", self.providers.codex.priority_reserve),
+            ("antigravity", self.providers.antigravity.priority_reserve),
+            ("claude", self.providers.claude.priority_reserve),
+            ("qwen", self.providers.qwen.priority_reserve),
+        ] {
+            if reserve.is_some_and(|r| !(0.0..1.0).contains(&r)) {
+                problems.push(format!(
+                    "providers.{name}.priority_reserve must be at least 0 and below 1"
+                ));
+            }
+        }
+        if self
+            .providers
+            .antigravity
+            .model_discovery
+            .is_some_and(|d| d.interval_secs == 0)
+        {
+            problems.push(
+                "providers.antigravity.model_discovery.interval_secs must be at least 1"
+                    .to_string(),
+            );
+        }
+        for (name, overrides) in [
+            ("geminicli", &self.providers.geminicli.model_overrides),
+            ("antigravity", &self.providers.antigravity.model_overrides),
+        ] {
+            for (model, rule) in overrides {
+                problems.extend(
+                    rule.validate(&format!("providers.{name}.model_overrides.\"{model}\"")),
+                );
+            }
+        }
+        problems
+    }
+
     pub fn geminicli(&self) -> GeminiCliResolvedConfig {
         self.providers.geminicli.resolve(&self.providers.defaults)
     }
@@ -77,7 +284,35 @@ impl Config {
     pub fn antigravity(&self) -> AntigravityResolvedConfig {
         self.providers.antigravity.resolve(&self.providers.defaults)
     }
+
+    pub fn claude(&self) -> ClaudeResolvedConfig {
+        self.providers.claude.resolve(&self.providers.defaults)
+    }
+
+    pub fn qwen(&self) -> QwenResolvedConfig {
+        self.providers.qwen.resolve(&self.providers.defaults)
+    }
 }
 
 /// Global, lazily-initialized configuration instance.
 pub static CONFIG: LazyLock<Config> = LazyLock::new(Config::from_optional_toml);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stream_capture_is_rejected_in_compliance_mode() {
+        let mut cfg = Config::default();
+        cfg.basic.pollux_key = "pollux".to_string();
+        cfg.basic.stream_capture_dir = Some(PathBuf::from("/tmp/pollux-capture"));
+        assert_eq!(cfg.validate().is_empty(), !cfg!(feature = "compliance"));
+
+        cfg.basic.compliance_mode = true;
+        assert!(
+            cfg.validate()
+                .iter()
+                .any(|p| p.starts_with("basic.stream_capture_dir"))
+        );
+    }
+}
@@ -0,0 +1,74 @@
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+
+/// Client model name → canonical model name.
+///
+/// TOML: `[providers.<p>.model_aliases]`, e.g.
+/// `"gemini-pro-latest" = "gemini-2.5-pro"`. A key ending in `*` is a prefix
+/// rule; a `*` in its value is replaced by the rest of the name, so
+/// `"google/*" = "*"` strips a vendor prefix. Exact keys win over prefix
+/// rules, and the longest matching prefix wins among rules. Aliases are
+/// applied once and do not chain.
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(transparent)]
+pub struct ModelAliases(BTreeMap<String, String>);
+
+impl ModelAliases {
+    /// Canonical name for `model`; borrowed when no alias applies.
+    pub fn resolve<'a>(&self, model: &'a str) -> Cow<'a, str> {
+        if let Some(target) = self.0.get(model) {
+            return Cow::Owned(target.clone());
+        }
+        self.0
+            .iter()
+            .filter_map(|(key, target)| {
+                let prefix = key.strip_suffix('*')?;
+                let rest = model.strip_prefix(prefix)?;
+                Some((prefix.len(), target.replacen('*', rest, 1)))
+            })
+            .max_by_key(|(len, _)| *len)
+            .map_or(Cow::Borrowed(model), |(_, target)| Cow::Owned(target))
+    }
+
+    /// `(key, target)` pairs, exact names and prefix rules alike.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.0.iter().map(|(k, v)| (k.as_str(), v.as_str()))
+    }
+}
+
+impl<K: Into<String>, V: Into<String>> FromIterator<(K, V)> for ModelAliases {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        Self(
+            iter.into_iter()
+                .map(|(k, v)| (k.into(), v.into()))
+                .collect(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ModelAliases;
+
+    #[test]
+    fn exact_aliases_win_over_prefix_rules() {
+        let aliases: ModelAliases = [
+            ("gemini-pro-latest", "gemini-2.5-pro"),
+            ("google/*", "*"),
+            ("google/gemini-*", "gemini-*"),
+            ("google/legacy", "gemini-2.0-flash"),
+        ]
+        .into_iter()
+        .collect();
+
+        assert_eq!(aliases.resolve("gemini-pro-latest"), "gemini-2.5-pro");
+        assert_eq!(aliases.resolve("google/legacy"), "gemini-2.0-flash");
+        assert_eq!(
+            aliases.resolve("google/gemini-2.5-flash"),
+            "gemini-2.5-flash"
+        );
+        assert_eq!(aliases.resolve("google/other"), "other");
+        assert_eq!(aliases.resolve("gemini-2.5-pro"), "gemini-2.5-pro");
+    }
+}
@@ -1,30 +1,59 @@
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
 use url::Url;
 
-use super::ProviderDefaults;
+use super::{
+    AutoDisableConfig, CapabilityProbeConfig, DailyQuotaConfig, HealthConfig, HttpClientConfig,
+    ModelAliases, ModelDiscoveryConfig, ModelOverrideConfig, ProviderDefaults, ResponseCacheConfig,
+    SchedulingPolicy, StreamTransformerConfig, SystemInstructionConfig, ThoughtSigConfig,
+};
 
 /// Antigravity provider configuration managed by Figment.
 ///
 /// Notes:
 /// - Provider defaults (proxy/multiplexing/retry) follow the same fallback semantics as other
 ///   providers: provider-level overrides win, otherwise `providers.defaults.*`.
-/// - OAuth endpoints/client credentials are intentionally fixed to built-in defaults
-///   (not configurable via `config.toml`).
+/// - The OAuth client ID is fixed to the built-in default; the token endpoint can be
+///   moved and the client secret replaced (`oauth_client_secret[_file]`).
 #[derive(Debug, Clone, Deserialize, Serialize)]
 #[serde(deny_unknown_fields)]
 pub struct AntigravityConfig {
     /// Base API URL for the antigravity upstream.
-    /// TOML: `providers.antigravity.api_url`.
+    /// TOML: `providers.antigravity.api_url` (alias `base_url`).
     /// Default: `https://daily-cloudcode-pa.googleapis.com`.
-    #[serde(default = "default_api_url")]
+    #[serde(default = "default_api_url", alias = "base_url")]
     pub api_url: Url,
 
-    /// Optional upstream HTTP proxy. If set, used for reqwest clients.
-    /// TOML: `providers.antigravity.proxy`. Example: `http://127.0.0.1:1080`.
+    /// OAuth token endpoint for code exchange and refresh.
+    /// TOML: `providers.antigravity.oauth_token_url`. Default: `https://oauth2.googleapis.com/token`.
+    #[serde(default = "default_oauth_token_url")]
+    pub oauth_token_url: Url,
+
+    /// OAuth client secret; the built-in Antigravity secret when unset.
+    /// TOML: `providers.antigravity.oauth_client_secret`.
+    #[serde(default)]
+    pub oauth_client_secret: Option<String>,
+
+    /// File holding `oauth_client_secret`, read once at startup.
+    /// Mutually exclusive with `oauth_client_secret`.
+    /// TOML: `providers.antigravity.oauth_client_secret_file`. Default: unset.
+    #[serde(default)]
+    pub oauth_client_secret_file: Option<PathBuf>,
+
+    /// Optional upstream proxy (`http`, `https`, `socks5` or `socks5h`).
+    /// TOML: `providers.antigravity.proxy`. Example: `socks5h://127.0.0.1:1080`.
     /// Falls back to `providers.defaults.proxy` when unset.
     #[serde(default)]
     pub proxy: Option<Url>,
 
+    /// Egress proxies spread across credentials; each credential always
+    /// goes out through `proxy_pool[id % len]`. Empty uses `proxy` for all.
+    /// TOML: `providers.antigravity.proxy_pool`.
+    /// Falls back to `providers.defaults.proxy_pool` when unset.
+    #[serde(default)]
+    pub proxy_pool: Option<Vec<Url>>,
+
     /// OAuth refresh requests per second (TPS) for the refresh worker.
     /// TOML: `providers.antigravity.oauth_tps`. Default: `5`.
     #[serde(default = "default_oauth_tps")]
@@ -36,33 +65,193 @@ pub struct AntigravityConfig {
     #[serde(default = "default_model_list")]
     pub model_list: Vec<String>,
 
+    /// Models the upstream only serves on `streamGenerateContent`; non-streaming
+    /// calls for them read the SSE stream and return one merged response.
+    /// TOML: `providers.antigravity.stream_only_models`. Default: empty.
+    #[serde(default)]
+    pub stream_only_models: Vec<String>,
+
+    /// Alternate client model names, resolved before the `model_list` check.
+    /// TOML: `[providers.antigravity.model_aliases]`. Default: none.
+    #[serde(default)]
+    pub model_aliases: ModelAliases,
+
     /// Allow HTTP/2 multiplexing for reqwest clients; disabled forces HTTP/1.
     /// TOML: `providers.antigravity.enable_multiplexing`.
     /// Falls back to `providers.defaults.enable_multiplexing`.
     #[serde(default)]
     pub enable_multiplexing: Option<bool>,
 
+    /// Upstream HTTP client tuning, merged field by field.
+    /// TOML: `[providers.antigravity.http_client]`.
+    /// Falls back to `providers.defaults.http_client`.
+    #[serde(default)]
+    pub http_client: HttpClientConfig,
+
     /// Max retry attempts for antigravity upstream calls.
     /// TOML: `providers.antigravity.retry_max_times`.
     /// Falls back to `providers.defaults.retry_max_times`.
     #[serde(default)]
     pub retry_max_times: Option<usize>,
+
+    /// Success-rate driven model auto-disable.
+    /// TOML: `[providers.antigravity.auto_disable]`.
+    /// Falls back to `providers.defaults.auto_disable`.
+    #[serde(default)]
+    pub auto_disable: Option<AutoDisableConfig>,
+
+    /// Park or ban credentials whose health score drops too low.
+    /// TOML: `[providers.antigravity.health]`.
+    /// Falls back to `providers.defaults.health`.
+    #[serde(default)]
+    pub health: Option<HealthConfig>,
+
+    /// Onboarding check of which models a new credential can serve.
+    /// TOML: `[providers.antigravity.capability_probe]`.
+    /// Falls back to `providers.defaults.capability_probe`.
+    #[serde(default)]
+    pub capability_probe: Option<CapabilityProbeConfig>,
+
+    /// How the client's `systemInstruction` is merged with configured text.
+    /// TOML: `[providers.antigravity.system_instruction]`.
+    /// Falls back to `providers.defaults.system_instruction`.
+    #[serde(default)]
+    pub system_instruction: Option<SystemInstructionConfig>,
+
+    /// Per-model generation defaults, keyed by canonical model name (after
+    /// `model_aliases`), e.g. `"gemini-2.5-pro" = { temperature = 0.7, max_output_tokens = 8192 }`.
+    /// TOML: `[providers.antigravity.model_overrides."<model>"]`. Default: empty.
+    #[serde(default)]
+    pub model_overrides: HashMap<String, ModelOverrideConfig>,
+
+    /// Relative share of traffic per quota tier reported by `loadCodeAssist`, e.g.
+    /// `{ standard-tier = 4, free-tier = 0 }`. Unlisted tiers weigh 1;
+    /// weight 0 is only used when nothing weighted is available.
+    /// TOML: `[providers.antigravity.tier_weights]`. Default: empty (plain round-robin).
+    #[serde(default)]
+    pub tier_weights: HashMap<String, u32>,
+
+    /// Soft per-credential daily limits.
+    /// TOML: `[providers.antigravity.daily_quota]`.
+    /// Falls back to `providers.defaults.daily_quota`.
+    #[serde(default)]
+    pub daily_quota: Option<DailyQuotaConfig>,
+
+    /// Minimum remaining access-token lifetime, in seconds, at lease time.
+    /// TOML: `providers.antigravity.min_token_validity_secs`.
+    /// Falls back to `providers.defaults.min_token_validity_secs`.
+    #[serde(default)]
+    pub min_token_validity_secs: Option<u64>,
+
+    /// Grace past expiry for serving a token whose refresh is in flight.
+    /// TOML: `providers.antigravity.stale_grace_secs`.
+    /// Falls back to `providers.defaults.stale_grace_secs`.
+    #[serde(default)]
+    pub stale_grace_secs: Option<u64>,
+
+    /// Delay before a lost model is restored to a credential.
+    /// TOML: `providers.antigravity.capability_restore_secs`.
+    /// Falls back to `providers.defaults.capability_restore_secs`.
+    #[serde(default)]
+    pub capability_restore_secs: Option<u64>,
+
+    /// Concurrent requests per credential; `0` lifts a limit set in defaults.
+    /// TOML: `providers.antigravity.max_concurrent_per_credential`.
+    /// Falls back to `providers.defaults.max_concurrent_per_credential`.
+    #[serde(default)]
+    pub max_concurrent_per_credential: Option<u32>,
+
+    /// Time a request may queue for a credential before giving up.
+    /// TOML: `providers.antigravity.lease_wait_ms`.
+    /// Falls back to `providers.defaults.lease_wait_ms`.
+    #[serde(default)]
+    pub lease_wait_ms: Option<u64>,
+
+    /// How credentials ready to serve a request are chosen.
+    /// TOML: `providers.antigravity.scheduling_policy`.
+    /// Falls back to `providers.defaults.scheduling_policy`.
+    #[serde(default)]
+    pub scheduling_policy: Option<SchedulingPolicy>,
+
+    /// Fraction of each model's credentials held back for `high` priority
+    /// requests.
+    /// TOML: `providers.antigravity.priority_reserve`.
+    /// Falls back to `providers.defaults.priority_reserve`.
+    #[serde(default)]
+    pub priority_reserve: Option<f64>,
+
+    /// Replays of a stream cut short before its finish event.
+    /// TOML: `providers.antigravity.stream_resume_max_times`.
+    /// Falls back to `providers.defaults.stream_resume_max_times`.
+    #[serde(default)]
+    pub stream_resume_max_times: Option<usize>,
+
+    /// Cap on decoded `inlineData` bytes per request.
+    /// TOML: `providers.antigravity.max_inline_data_bytes`.
+    /// Falls back to `providers.defaults.max_inline_data_bytes`.
+    #[serde(default)]
+    pub max_inline_data_bytes: Option<usize>,
+
+    /// Opt-in cache for non-streaming `temperature = 0` requests; hits are
+    /// answered without an upstream call and carry `x-pollux-cache: hit`.
+    /// TOML: `[providers.antigravity.response_cache]`. Default: unset (off).
+    #[serde(default)]
+    pub response_cache: Option<ResponseCacheConfig>,
+
+    /// Transformations applied to generated text, in order.
+    /// TOML: `[[providers.antigravity.stream_transformers]]`. Default: none.
+    #[serde(default)]
+    pub stream_transformers: Vec<StreamTransformerConfig>,
+
+    /// Thought-signature cache storage and bounds.
+    /// TOML: `[providers.antigravity.thoughtsig]`. Default: in memory.
+    #[serde(default)]
+    pub thoughtsig: ThoughtSigConfig,
+
+    /// Periodic upstream query of each credential's available models.
+    /// TOML: `[providers.antigravity.model_discovery]`. Default: unset (off).
+    #[serde(default)]
+    pub model_discovery: Option<ModelDiscoveryConfig>,
 }
 
 #[derive(Debug, Clone)]
 pub struct AntigravityResolvedConfig {
     pub api_url: Url,
     pub proxy: Option<Url>,
+    pub proxy_pool: Vec<Url>,
     pub oauth_tps: usize,
     pub model_list: Vec<String>,
+    pub stream_only_models: Vec<String>,
+    pub model_aliases: ModelAliases,
     pub enable_multiplexing: bool,
+    pub http_client: HttpClientConfig,
     pub retry_max_times: usize,
+    pub auto_disable: Option<AutoDisableConfig>,
+    pub health: Option<HealthConfig>,
+    pub capability_probe: Option<CapabilityProbeConfig>,
+    pub system_instruction: Option<SystemInstructionConfig>,
+    pub model_overrides: HashMap<String, ModelOverrideConfig>,
+    pub tier_weights: HashMap<String, u32>,
+    pub daily_quota: Option<DailyQuotaConfig>,
+    pub min_token_validity_secs: u64,
+    pub stale_grace_secs: u64,
+    pub capability_restore_secs: u64,
+    pub max_concurrent_per_credential: Option<u32>,
+    pub lease_wait_ms: u64,
+    pub scheduling_policy: SchedulingPolicy,
+    pub priority_reserve: f64,
+    pub stream_resume_max_times: usize,
+    pub max_inline_data_bytes: usize,
+    pub response_cache: Option<ResponseCacheConfig>,
     pub oauth_auth_url: Url,
     pub oauth_token_url: Url,
     pub oauth_redirect_url: Url,
     pub oauth_client_id: String,
     pub oauth_client_secret: String,
     pub oauth_scopes: Vec<String>,
+    pub stream_transformers: Vec<StreamTransformerConfig>,
+    pub thoughtsig: ThoughtSigConfig,
+    pub model_discovery: Option<ModelDiscoveryConfig>,
 }
 
 impl AntigravityConfig {
@@ -70,18 +259,61 @@ impl AntigravityConfig {
         AntigravityResolvedConfig {
             api_url: self.api_url.clone(),
             proxy: self.proxy.clone().or_else(|| defaults.proxy.clone()),
+            proxy_pool: self
+                .proxy_pool
+                .clone()
+                .unwrap_or_else(|| defaults.proxy_pool.clone()),
             oauth_tps: self.oauth_tps,
             model_list: self.model_list.clone(),
+            stream_only_models: self.stream_only_models.clone(),
+            model_aliases: self.model_aliases.clone(),
             enable_multiplexing: self
                 .enable_multiplexing
                 .unwrap_or(defaults.enable_multiplexing),
+            http_client: self.http_client.or(defaults.http_client),
             retry_max_times: self.retry_max_times.unwrap_or(defaults.retry_max_times),
+            auto_disable: self.auto_disable.or(defaults.auto_disable),
+            health: self.health.or(defaults.health),
+            capability_probe: self.capability_probe.or(defaults.capability_probe),
+            system_instruction: self
+                .system_instruction
+                .clone()
+                .or_else(|| defaults.system_instruction.clone()),
+            model_overrides: self.model_overrides.clone(),
+            tier_weights: self.tier_weights.clone(),
+            daily_quota: self.daily_quota.or(defaults.daily_quota),
+            min_token_validity_secs: self
+                .min_token_validity_secs
+                .unwrap_or(defaults.min_token_validity_secs),
+            stale_grace_secs: self.stale_grace_secs.unwrap_or(defaults.stale_grace_secs),
+            capability_restore_secs: self
+                .capability_restore_secs
+                .unwrap_or(defaults.capability_restore_secs),
+            max_concurrent_per_credential: self
+                .max_concurrent_per_credential
+                .or(defaults.max_concurrent_per_credential),
+            lease_wait_ms: self.lease_wait_ms.unwrap_or(defaults.lease_wait_ms),
+            scheduling_policy: self.scheduling_policy.unwrap_or(defaults.scheduling_policy),
+            priority_reserve: self.priority_reserve.unwrap_or(defaults.priority_reserve),
+            stream_resume_max_times: self
+                .stream_resume_max_times
+                .unwrap_or(defaults.stream_resume_max_times),
+            max_inline_data_bytes: self
+                .max_inline_data_bytes
+                .unwrap_or(defaults.max_inline_data_bytes),
+            response_cache: self.response_cache.clone(),
             oauth_auth_url: default_oauth_auth_url(),
-            oauth_token_url: default_oauth_token_url(),
+            oauth_token_url: self.oauth_token_url.clone(),
             oauth_redirect_url: default_oauth_redirect_url(),
             oauth_client_id: default_oauth_client_id(),
-            oauth_client_secret: default_oauth_client_secret(),
+            oauth_client_secret: self
+                .oauth_client_secret
+                .clone()
+                .unwrap_or_else(default_oauth_client_secret),
             oauth_scopes: default_oauth_scopes(),
+            stream_transformers: self.stream_transformers.clone(),
+            thoughtsig: self.thoughtsig,
+            model_discovery: self.model_discovery,
         }
     }
 }
@@ -90,11 +322,38 @@ impl Default for AntigravityConfig {
     fn default() -> Self {
         Self {
             api_url: default_api_url(),
+            oauth_token_url: default_oauth_token_url(),
+            oauth_client_secret: None,
+            oauth_client_secret_file: None,
             proxy: None,
+            proxy_pool: None,
             oauth_tps: default_oauth_tps(),
             model_list: default_model_list(),
+            stream_only_models: Vec::new(),
+            model_aliases: ModelAliases::default(),
             enable_multiplexing: None,
+            http_client: HttpClientConfig::default(),
             retry_max_times: None,
+            auto_disable: None,
+            health: None,
+            capability_probe: None,
+            system_instruction: None,
+            model_overrides: HashMap::new(),
+            tier_weights: HashMap::new(),
+            daily_quota: None,
+            min_token_validity_secs: None,
+            stale_grace_secs: None,
+            capability_restore_secs: None,
+            max_concurrent_per_credential: None,
+            lease_wait_ms: None,
+            scheduling_policy: None,
+            priority_reserve: None,
+            stream_resume_max_times: None,
+            max_inline_data_bytes: None,
+            response_cache: None,
+            stream_transformers: Vec::new(),
+            thoughtsig: ThoughtSigConfig::default(),
+            model_discovery: None,
         }
     }
 }
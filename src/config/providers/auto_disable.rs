@@ -0,0 +1,38 @@
+use serde::{Deserialize, Serialize};
+
+/// Success-rate policy that clears a credential's capability bit for a model
+/// once it keeps failing on that model.
+///
+/// Only outcomes not already handled elsewhere count: successes and generic
+/// upstream errors (e.g. `500`). Rate limits, bans and auth failures have
+/// their own handling and are ignored here.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct AutoDisableConfig {
+    /// Number of most recent outcomes tracked per credential and model, `1..=64`.
+    /// TOML: `providers.<p>.auto_disable.window`. Default: `20`.
+    #[serde(default = "default_window")]
+    pub window: usize,
+
+    /// Once the window is full, a success rate below this disables the model.
+    /// TOML: `providers.<p>.auto_disable.min_success_rate`. Default: `0.2`.
+    #[serde(default = "default_min_success_rate")]
+    pub min_success_rate: f64,
+}
+
+impl Default for AutoDisableConfig {
+    fn default() -> Self {
+        Self {
+            window: default_window(),
+            min_success_rate: default_min_success_rate(),
+        }
+    }
+}
+
+fn default_window() -> usize {
+    20
+}
+
+fn default_min_success_rate() -> f64 {
+    0.2
+}
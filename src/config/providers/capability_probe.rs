@@ -0,0 +1,24 @@
+use serde::{Deserialize, Serialize};
+
+/// Request sent per model by the onboarding capability probe.
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ProbeMethod {
+    /// `countTokens`: free, but may accept models the account cannot generate with.
+    #[default]
+    CountTokens,
+    /// `generateContent` capped at one output token.
+    Generate,
+}
+
+/// Check which configured models a newly onboarded credential can serve
+/// before it enters rotation, instead of learning it from failed requests.
+///
+/// Applies to Gemini CLI and Antigravity; probes share the `oauth_tps` budget.
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct CapabilityProbeConfig {
+    /// TOML: `providers.<p>.capability_probe.method`. Default: `count_tokens`.
+    #[serde(default)]
+    pub method: ProbeMethod,
+}
@@ -0,0 +1,248 @@
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use super::{
+    AutoDisableConfig, DailyQuotaConfig, HealthConfig, HttpClientConfig, ModelAliases,
+    ProviderDefaults, SchedulingPolicy,
+};
+
+fn default_api_url() -> Url {
+    Url::parse("https://api.anthropic.com").expect("invalid fixed Anthropic base URL")
+}
+
+fn default_oauth_token_url() -> Url {
+    Url::parse("https://console.anthropic.com/v1/oauth/token")
+        .expect("invalid fixed Anthropic token URL")
+}
+
+/// Claude provider configuration managed by Figment.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct ClaudeConfig {
+    /// Anthropic API base URL.
+    /// TOML: `providers.claude.custom_api_url` (alias `base_url`). Default: `https://api.anthropic.com`.
+    #[serde(default = "default_api_url", alias = "base_url")]
+    pub custom_api_url: Url,
+
+    /// OAuth token endpoint for code exchange and refresh.
+    /// TOML: `providers.claude.oauth_token_url`. Default: `https://console.anthropic.com/v1/oauth/token`.
+    #[serde(default = "default_oauth_token_url")]
+    pub oauth_token_url: Url,
+
+    /// Optional upstream proxy (`http`, `https`, `socks5` or `socks5h`).
+    /// TOML: `providers.claude.proxy`.
+    /// Falls back to `providers.defaults.proxy` when unset.
+    #[serde(default)]
+    pub proxy: Option<Url>,
+
+    /// Egress proxies spread across credentials; each credential always
+    /// goes out through `proxy_pool[id % len]`. Empty uses `proxy` for all.
+    /// TOML: `providers.claude.proxy_pool`.
+    /// Falls back to `providers.defaults.proxy_pool` when unset.
+    #[serde(default)]
+    pub proxy_pool: Option<Vec<Url>>,
+
+    /// OAuth refresh requests per second (TPS) for the refresh worker.
+    /// TOML: `providers.claude.oauth_tps`. Default: `5`.
+    #[serde(default = "default_oauth_tps")]
+    pub oauth_tps: usize,
+
+    /// List of supported model names (allowlist). Each name maps to a bit in the global model
+    /// catalog and corresponds to an independent credential queue.
+    /// TOML: `providers.claude.model_list`.
+    #[serde(default = "default_model_list")]
+    pub model_list: Vec<String>,
+
+    /// Alternate client model names, resolved before the `model_list` check.
+    /// TOML: `[providers.claude.model_aliases]`. Default: none.
+    #[serde(default)]
+    pub model_aliases: ModelAliases,
+
+    /// Allow HTTP/2 multiplexing for reqwest clients; disabled forces HTTP/1.
+    /// TOML: `providers.claude.enable_multiplexing`.
+    /// Falls back to `providers.defaults.enable_multiplexing`.
+    #[serde(default)]
+    pub enable_multiplexing: Option<bool>,
+
+    /// Upstream HTTP client tuning, merged field by field.
+    /// TOML: `[providers.claude.http_client]`.
+    /// Falls back to `providers.defaults.http_client`.
+    #[serde(default)]
+    pub http_client: HttpClientConfig,
+
+    /// Max retry attempts for Claude upstream calls.
+    /// TOML: `providers.claude.retry_max_times`.
+    /// Falls back to `providers.defaults.retry_max_times`.
+    #[serde(default)]
+    pub retry_max_times: Option<usize>,
+
+    /// Success-rate driven model auto-disable.
+    /// TOML: `[providers.claude.auto_disable]`.
+    /// Falls back to `providers.defaults.auto_disable`.
+    #[serde(default)]
+    pub auto_disable: Option<AutoDisableConfig>,
+
+    /// Park or ban credentials whose health score drops too low.
+    /// TOML: `[providers.claude.health]`.
+    /// Falls back to `providers.defaults.health`.
+    #[serde(default)]
+    pub health: Option<HealthConfig>,
+
+    /// Soft per-credential daily limits.
+    /// TOML: `[providers.claude.daily_quota]`.
+    /// Falls back to `providers.defaults.daily_quota`.
+    #[serde(default)]
+    pub daily_quota: Option<DailyQuotaConfig>,
+
+    /// Minimum remaining access-token lifetime, in seconds, at lease time.
+    /// TOML: `providers.claude.min_token_validity_secs`.
+    /// Falls back to `providers.defaults.min_token_validity_secs`.
+    #[serde(default)]
+    pub min_token_validity_secs: Option<u64>,
+
+    /// Grace past expiry for serving a token whose refresh is in flight.
+    /// TOML: `providers.claude.stale_grace_secs`.
+    /// Falls back to `providers.defaults.stale_grace_secs`.
+    #[serde(default)]
+    pub stale_grace_secs: Option<u64>,
+
+    /// Delay before a lost model is restored to a credential.
+    /// TOML: `providers.claude.capability_restore_secs`.
+    /// Falls back to `providers.defaults.capability_restore_secs`.
+    #[serde(default)]
+    pub capability_restore_secs: Option<u64>,
+
+    /// Concurrent requests per credential; `0` lifts a limit set in defaults.
+    /// TOML: `providers.claude.max_concurrent_per_credential`.
+    /// Falls back to `providers.defaults.max_concurrent_per_credential`.
+    #[serde(default)]
+    pub max_concurrent_per_credential: Option<u32>,
+
+    /// Time a request may queue for a credential before giving up.
+    /// TOML: `providers.claude.lease_wait_ms`.
+    /// Falls back to `providers.defaults.lease_wait_ms`.
+    #[serde(default)]
+    pub lease_wait_ms: Option<u64>,
+
+    /// How credentials ready to serve a request are chosen.
+    /// TOML: `providers.claude.scheduling_policy`.
+    /// Falls back to `providers.defaults.scheduling_policy`.
+    #[serde(default)]
+    pub scheduling_policy: Option<SchedulingPolicy>,
+
+    /// Fraction of each model's credentials held back for `high` priority
+    /// requests.
+    /// TOML: `providers.claude.priority_reserve`.
+    /// Falls back to `providers.defaults.priority_reserve`.
+    #[serde(default)]
+    pub priority_reserve: Option<f64>,
+
+    /// Optional custom trace header name for upstream requests.
+    /// TOML: `providers.claude.trace_header`.
+    /// Falls back to `providers.defaults.trace_header`.
+    #[serde(default)]
+    pub trace_header: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ClaudeResolvedConfig {
+    pub custom_api_url: Url,
+    pub oauth_token_url: Url,
+    pub proxy: Option<Url>,
+    pub proxy_pool: Vec<Url>,
+    pub oauth_tps: usize,
+    pub model_list: Vec<String>,
+    pub model_aliases: ModelAliases,
+    pub enable_multiplexing: bool,
+    pub http_client: HttpClientConfig,
+    pub retry_max_times: usize,
+    pub auto_disable: Option<AutoDisableConfig>,
+    pub health: Option<HealthConfig>,
+    pub daily_quota: Option<DailyQuotaConfig>,
+    pub min_token_validity_secs: u64,
+    pub stale_grace_secs: u64,
+    pub capability_restore_secs: u64,
+    pub max_concurrent_per_credential: Option<u32>,
+    pub lease_wait_ms: u64,
+    pub scheduling_policy: SchedulingPolicy,
+    pub priority_reserve: f64,
+    pub trace_header: Option<String>,
+}
+
+impl ClaudeConfig {
+    pub fn resolve(&self, defaults: &ProviderDefaults) -> ClaudeResolvedConfig {
+        ClaudeResolvedConfig {
+            custom_api_url: self.custom_api_url.clone(),
+            oauth_token_url: self.oauth_token_url.clone(),
+            proxy: self.proxy.clone().or_else(|| defaults.proxy.clone()),
+            proxy_pool: self
+                .proxy_pool
+                .clone()
+                .unwrap_or_else(|| defaults.proxy_pool.clone()),
+            oauth_tps: self.oauth_tps,
+            model_list: self.model_list.clone(),
+            model_aliases: self.model_aliases.clone(),
+            enable_multiplexing: self
+                .enable_multiplexing
+                .unwrap_or(defaults.enable_multiplexing),
+            http_client: self.http_client.or(defaults.http_client),
+            retry_max_times: self.retry_max_times.unwrap_or(defaults.retry_max_times),
+            auto_disable: self.auto_disable.or(defaults.auto_disable),
+            health: self.health.or(defaults.health),
+            daily_quota: self.daily_quota.or(defaults.daily_quota),
+            min_token_validity_secs: self
+                .min_token_validity_secs
+                .unwrap_or(defaults.min_token_validity_secs),
+            stale_grace_secs: self.stale_grace_secs.unwrap_or(defaults.stale_grace_secs),
+            capability_restore_secs: self
+                .capability_restore_secs
+                .unwrap_or(defaults.capability_restore_secs),
+            max_concurrent_per_credential: self
+                .max_concurrent_per_credential
+                .or(defaults.max_concurrent_per_credential),
+            lease_wait_ms: self.lease_wait_ms.unwrap_or(defaults.lease_wait_ms),
+            scheduling_policy: self.scheduling_policy.unwrap_or(defaults.scheduling_policy),
+            priority_reserve: self.priority_reserve.unwrap_or(defaults.priority_reserve),
+            trace_header: self
+                .trace_header
+                .clone()
+                .or_else(|| defaults.trace_header.clone()),
+        }
+    }
+}
+
+impl Default for ClaudeConfig {
+    fn default() -> Self {
+        Self {
+            custom_api_url: default_api_url(),
+            oauth_token_url: default_oauth_token_url(),
+            proxy: None,
+            proxy_pool: None,
+            oauth_tps: default_oauth_tps(),
+            model_list: default_model_list(),
+            model_aliases: ModelAliases::default(),
+            enable_multiplexing: None,
+            http_client: HttpClientConfig::default(),
+            retry_max_times: None,
+            auto_disable: None,
+            health: None,
+            daily_quota: None,
+            min_token_validity_secs: None,
+            stale_grace_secs: None,
+            capability_restore_secs: None,
+            max_concurrent_per_credential: None,
+            lease_wait_ms: None,
+            scheduling_policy: None,
+            priority_reserve: None,
+            trace_header: None,
+        }
+    }
+}
+
+fn default_oauth_tps() -> usize {
+    5
+}
+
+fn default_model_list() -> Vec<String> {
+    vec!["claude-sonnet-4-5".to_string()]
+}
@@ -0,0 +1,71 @@
+use serde::{Deserialize, Serialize};
+
+/// Address family order handed to the connector.
+///
+/// The connector dials the first family and races the other one after a
+/// short delay (happy eyeballs), so the order decides which family wins
+/// when both work; the `_only` variants skip the other family entirely.
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum IpPreference {
+    #[default]
+    Ipv4First,
+    Ipv6First,
+    Ipv4Only,
+    Ipv6Only,
+}
+
+/// Resolver used for every upstream HTTP client.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct DnsConfig {
+    /// Resolve through the built-in caching resolver instead of the system's
+    /// `getaddrinfo`, which caches nothing and blocks a thread per lookup.
+    /// TOML: `providers.dns.enabled`. Default: `false`.
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// TOML: `providers.dns.ip_preference`. Default: `"ipv4_first"`.
+    #[serde(default)]
+    pub ip_preference: IpPreference,
+
+    /// Upper bound on how long an answer is cached; records with a shorter
+    /// TTL expire sooner.
+    /// TOML: `providers.dns.max_ttl_secs`. Default: `300`.
+    #[serde(default = "default_max_ttl_secs")]
+    pub max_ttl_secs: u64,
+
+    /// Per-query timeout before the next attempt.
+    /// TOML: `providers.dns.timeout_secs`. Default: `2`.
+    #[serde(default = "default_timeout_secs")]
+    pub timeout_secs: u64,
+
+    /// Number of cached responses.
+    /// TOML: `providers.dns.cache_size`. Default: `256`.
+    #[serde(default = "default_cache_size")]
+    pub cache_size: u64,
+}
+
+impl Default for DnsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            ip_preference: IpPreference::default(),
+            max_ttl_secs: default_max_ttl_secs(),
+            timeout_secs: default_timeout_secs(),
+            cache_size: default_cache_size(),
+        }
+    }
+}
+
+fn default_max_ttl_secs() -> u64 {
+    300
+}
+
+fn default_timeout_secs() -> u64 {
+    2
+}
+
+fn default_cache_size() -> u64 {
+    256
+}
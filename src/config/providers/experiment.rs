@@ -0,0 +1,33 @@
+use serde::{Deserialize, Serialize};
+
+/// A single upstream request-shape experiment.
+///
+/// A `fraction` of requests is routed to the treatment arm, which applies the
+/// overrides below; everything else is the control arm and is left untouched.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct ExperimentConfig {
+    /// Label reported in metrics and logs.
+    /// TOML: `providers.<p>.experiment.name`.
+    pub name: String,
+
+    /// Share of requests assigned to the treatment arm, clamped to `0.0..=1.0`.
+    /// TOML: `providers.<p>.experiment.fraction`. Default: `0.0`.
+    #[serde(default)]
+    pub fraction: f64,
+
+    /// Replacement upstream User-Agent; `{model}` is substituted with the request model.
+    /// TOML: `providers.<p>.experiment.user_agent`.
+    #[serde(default)]
+    pub user_agent: Option<String>,
+
+    /// Text inserted as the first `systemInstruction` part.
+    /// TOML: `providers.<p>.experiment.preamble`.
+    #[serde(default)]
+    pub preamble: Option<String>,
+
+    /// `thinkingConfig.thinkingBudget` applied when the client did not send a `thinkingConfig`.
+    /// TOML: `providers.<p>.experiment.thinking_budget`.
+    #[serde(default)]
+    pub thinking_budget: Option<i64>,
+}
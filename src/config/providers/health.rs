@@ -0,0 +1,57 @@
+use serde::{Deserialize, Serialize};
+
+/// What happens to a credential whose health score falls below the threshold.
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum HealthAction {
+    /// Cool every model down for `park_secs`, then give it a fresh score.
+    #[default]
+    Park,
+    /// Disable it in storage, like an upstream ban.
+    Ban,
+}
+
+/// Health score policy built from consecutive failures per credential.
+///
+/// The score starts at `100` and loses `40` per consecutive `403`, `10` per
+/// consecutive generic upstream error (e.g. `500`) and `25` per consecutive
+/// failed token refresh. A successful request clears the request failures, a
+/// successful refresh the refresh failures.
+///
+/// With a policy set, a `403` counts against the score instead of banning the
+/// credential outright.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct HealthConfig {
+    /// Scores below this trigger `action`, `0..=100`.
+    /// TOML: `providers.<p>.health.min_score`. Default: `50`.
+    #[serde(default = "default_min_score")]
+    pub min_score: u8,
+
+    /// TOML: `providers.<p>.health.action`. Default: `park`.
+    #[serde(default)]
+    pub action: HealthAction,
+
+    /// How long a parked credential sits out.
+    /// TOML: `providers.<p>.health.park_secs`. Default: `1800`.
+    #[serde(default = "default_park_secs")]
+    pub park_secs: u64,
+}
+
+impl Default for HealthConfig {
+    fn default() -> Self {
+        Self {
+            min_score: default_min_score(),
+            action: HealthAction::default(),
+            park_secs: default_park_secs(),
+        }
+    }
+}
+
+fn default_min_score() -> u8 {
+    50
+}
+
+fn default_park_secs() -> u64 {
+    1800
+}
@@ -0,0 +1,63 @@
+use serde::{Deserialize, Serialize};
+
+/// Tuning for a provider's upstream `reqwest` clients.
+///
+/// Unset fields keep the built-in value. The pool and HTTP/2 options only
+/// matter with `enable_multiplexing`; without it every request uses a fresh
+/// connection.
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct HttpClientConfig {
+    /// Idle connections kept per host. Default: reqwest's (unbounded).
+    /// TOML: `providers.<p>.http_client.pool_max_idle_per_host`.
+    #[serde(default)]
+    pub pool_max_idle_per_host: Option<usize>,
+
+    /// Seconds an idle pooled connection is kept. Default: `90`.
+    /// TOML: `providers.<p>.http_client.pool_idle_timeout_secs`.
+    #[serde(default)]
+    pub pool_idle_timeout_secs: Option<u64>,
+
+    /// Seconds allowed to establish a connection. Default: `10` for API
+    /// calls, `5` for OAuth refresh.
+    /// TOML: `providers.<p>.http_client.connect_timeout_secs`.
+    #[serde(default)]
+    pub connect_timeout_secs: Option<u64>,
+
+    /// Seconds a single read may wait for data, including between stream
+    /// chunks. Default: unlimited.
+    /// TOML: `providers.<p>.http_client.read_timeout_secs`.
+    #[serde(default)]
+    pub read_timeout_secs: Option<u64>,
+
+    /// TCP keepalive probe interval in seconds. Default: off.
+    /// TOML: `providers.<p>.http_client.tcp_keepalive_secs`.
+    #[serde(default)]
+    pub tcp_keepalive_secs: Option<u64>,
+
+    /// HTTP/2 PING interval in seconds, also sent while idle. Default: off.
+    /// TOML: `providers.<p>.http_client.http2_keep_alive_interval_secs`.
+    #[serde(default)]
+    pub http2_keep_alive_interval_secs: Option<u64>,
+}
+
+impl HttpClientConfig {
+    /// Fields set here win; the rest come from `defaults`.
+    #[must_use]
+    pub fn or(self, defaults: Self) -> Self {
+        Self {
+            pool_max_idle_per_host: self
+                .pool_max_idle_per_host
+                .or(defaults.pool_max_idle_per_host),
+            pool_idle_timeout_secs: self
+                .pool_idle_timeout_secs
+                .or(defaults.pool_idle_timeout_secs),
+            connect_timeout_secs: self.connect_timeout_secs.or(defaults.connect_timeout_secs),
+            read_timeout_secs: self.read_timeout_secs.or(defaults.read_timeout_secs),
+            tcp_keepalive_secs: self.tcp_keepalive_secs.or(defaults.tcp_keepalive_secs),
+            http2_keep_alive_interval_secs: self
+                .http2_keep_alive_interval_secs
+                .or(defaults.http2_keep_alive_interval_secs),
+        }
+    }
+}
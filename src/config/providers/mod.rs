@@ -1,10 +1,44 @@
+mod alias;
 mod antigravity;
+mod auto_disable;
+mod capability_probe;
+mod claude;
 mod codex;
+mod dns;
+mod experiment;
 mod geminicli;
+mod health;
+mod http_client;
+mod model_discovery;
+mod model_override;
+mod quota;
+mod qwen;
+mod response_cache;
+mod scheduling;
+mod stream;
+mod system_instruction;
+mod thoughtsig;
 
+pub use alias::ModelAliases;
 pub use antigravity::{AntigravityConfig, AntigravityResolvedConfig};
-pub use codex::{CodexConfig, CodexResolvedConfig};
+pub use auto_disable::AutoDisableConfig;
+pub use capability_probe::{CapabilityProbeConfig, ProbeMethod};
+pub use claude::{ClaudeConfig, ClaudeResolvedConfig};
+pub use codex::{CodexConfig, CodexReasoningConfig, CodexResolvedConfig};
+pub use dns::{DnsConfig, IpPreference};
+pub use experiment::ExperimentConfig;
 pub use geminicli::{GeminiCliConfig, GeminiCliResolvedConfig};
+pub use health::{HealthAction, HealthConfig};
+pub use http_client::HttpClientConfig;
+pub use model_discovery::ModelDiscoveryConfig;
+pub use model_override::ModelOverrideConfig;
+pub use quota::DailyQuotaConfig;
+pub use qwen::{QwenConfig, QwenResolvedConfig};
+pub use response_cache::ResponseCacheConfig;
+pub use scheduling::{Priority, SchedulingPolicy};
+pub use stream::{SseFlushConfig, StreamTransformerConfig};
+pub use system_instruction::{SystemInstructionConfig, SystemInstructionMode};
+pub use thoughtsig::{ThoughtSigConfig, ThoughtSigStorage};
 
 use serde::{Deserialize, Serialize};
 use url::Url;
@@ -12,16 +46,26 @@ use url::Url;
 /// Global provider defaults (used when provider-level config is unset).
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct ProviderDefaults {
-    /// Optional upstream HTTP proxy. If set, used for reqwest clients.
+    /// Optional upstream proxy (`http`, `https`, `socks5` or `socks5h`).
     /// TOML: `providers.defaults.proxy`. Example: `http://127.0.0.1:1080`.
     #[serde(default)]
     pub proxy: Option<Url>,
 
+    /// Egress proxies spread across credentials by id.
+    /// TOML: `providers.defaults.proxy_pool`. Default: empty.
+    #[serde(default)]
+    pub proxy_pool: Vec<Url>,
+
     /// Allow HTTP/2 multiplexing for reqwest clients; disabled forces HTTP/1.
     /// TOML: `providers.defaults.enable_multiplexing`. Default: `false`.
     #[serde(default = "default_enable_multiplexing")]
     pub enable_multiplexing: bool,
 
+    /// Pool, timeout and keepalive tuning for upstream HTTP clients.
+    /// TOML: `[providers.defaults.http_client]`.
+    #[serde(default)]
+    pub http_client: HttpClientConfig,
+
     /// Max retry attempts for upstream calls.
     /// TOML: `providers.defaults.retry_max_times`. Default: `3`.
     #[serde(default = "default_retry_max_times")]
@@ -32,15 +76,115 @@ pub struct ProviderDefaults {
     /// TOML: `providers.defaults.trace_header`. Example: `"X-Trace-ID"`.
     #[serde(default)]
     pub trace_header: Option<String>,
+
+    /// Success-rate driven model auto-disable; off when unset.
+    /// TOML: `[providers.defaults.auto_disable]`.
+    #[serde(default)]
+    pub auto_disable: Option<AutoDisableConfig>,
+
+    /// Park or ban credentials whose health score drops too low; off when unset.
+    /// TOML: `[providers.defaults.health]`.
+    #[serde(default)]
+    pub health: Option<HealthConfig>,
+
+    /// Probe new credentials' models at onboarding; off when unset.
+    /// TOML: `[providers.defaults.capability_probe]`.
+    #[serde(default)]
+    pub capability_probe: Option<CapabilityProbeConfig>,
+
+    /// Gemini CLI and Antigravity `systemInstruction` policy; untouched when unset.
+    /// TOML: `[providers.defaults.system_instruction]`.
+    #[serde(default)]
+    pub system_instruction: Option<SystemInstructionConfig>,
+
+    /// Soft per-credential daily request and token limits; off when unset.
+    /// TOML: `[providers.defaults.daily_quota]`.
+    #[serde(default)]
+    pub daily_quota: Option<DailyQuotaConfig>,
+
+    /// Minimum remaining access-token lifetime, in seconds, for a credential
+    /// to be leased; tokens expiring sooner are refreshed first.
+    /// TOML: `providers.defaults.min_token_validity_secs`. Default: `300`.
+    #[serde(default = "default_min_token_validity_secs")]
+    pub min_token_validity_secs: u64,
+
+    /// Seconds past expiry a token may still be leased while its refresh is
+    /// in flight; `0` fails over to fresh credentials immediately.
+    /// TOML: `providers.defaults.stale_grace_secs`. Default: `0`.
+    #[serde(default)]
+    pub stale_grace_secs: u64,
+
+    /// Seconds after which a model a credential lost (upstream reported it
+    /// unsupported, or auto-disable cleared it) is restored, letting the next
+    /// request re-validate it. `0` keeps lost models off until restart.
+    /// TOML: `providers.defaults.capability_restore_secs`. Default: `0`.
+    #[serde(default)]
+    pub capability_restore_secs: u64,
+
+    /// Requests one credential may serve at the same time; further requests
+    /// go to other credentials. Unset or `0` means unlimited.
+    /// TOML: `providers.defaults.max_concurrent_per_credential`. Default: unset.
+    #[serde(default)]
+    pub max_concurrent_per_credential: Option<u32>,
+
+    /// How long a request may wait in the provider's queue for a credential
+    /// to free up (cooldown ending, refresh completing, lease released)
+    /// before failing with no available credential. `0` fails immediately.
+    /// TOML: `providers.defaults.lease_wait_ms`. Default: `0`.
+    #[serde(default)]
+    pub lease_wait_ms: u64,
+
+    /// How credentials ready to serve a request are chosen: `round_robin`,
+    /// `least_recently_used`, `least_latency`, `p2c_latency` or `least_errors`.
+    /// TOML: `providers.defaults.scheduling_policy`. Default: `round_robin`.
+    #[serde(default)]
+    pub scheduling_policy: SchedulingPolicy,
+
+    /// Fraction of each model's credentials held back for `high` priority
+    /// requests: `normal` and `low` ones are not served once no more than
+    /// that many credentials are ready. `0` reserves nothing.
+    /// TOML: `providers.defaults.priority_reserve`. Default: `0`.
+    #[serde(default)]
+    pub priority_reserve: f64,
+
+    /// Times a Gemini CLI or Antigravity stream that ends before its finish
+    /// event is replayed on a new credential and continued from the text
+    /// already sent. Codex streams cannot be continued and are not resumed.
+    /// TOML: `providers.defaults.stream_resume_max_times`. Default: `0` (off).
+    #[serde(default)]
+    pub stream_resume_max_times: usize,
+
+    /// Largest total of decoded `inlineData` bytes a Gemini CLI or
+    /// Antigravity request may carry; larger requests are rejected with 400
+    /// before a credential is leased. `0` disables the check.
+    /// TOML: `providers.defaults.max_inline_data_bytes`. Default: 20 MiB.
+    #[serde(default = "default_max_inline_data_bytes")]
+    pub max_inline_data_bytes: usize,
 }
 
 impl Default for ProviderDefaults {
     fn default() -> Self {
         Self {
             proxy: None,
+            proxy_pool: Vec::new(),
             enable_multiplexing: default_enable_multiplexing(),
+            http_client: HttpClientConfig::default(),
             retry_max_times: default_retry_max_times(),
             trace_header: None,
+            auto_disable: None,
+            health: None,
+            capability_probe: None,
+            system_instruction: None,
+            daily_quota: None,
+            min_token_validity_secs: default_min_token_validity_secs(),
+            stale_grace_secs: 0,
+            capability_restore_secs: 0,
+            max_concurrent_per_credential: None,
+            lease_wait_ms: 0,
+            scheduling_policy: SchedulingPolicy::default(),
+            priority_reserve: 0.0,
+            stream_resume_max_times: 0,
+            max_inline_data_bytes: default_max_inline_data_bytes(),
         }
     }
 }
@@ -63,6 +207,24 @@ pub struct ProvidersConfig {
     /// Antigravity provider configuration.
     #[serde(default)]
     pub antigravity: AntigravityConfig,
+
+    /// Claude (Anthropic OAuth) provider configuration.
+    #[serde(default)]
+    pub claude: ClaudeConfig,
+
+    /// Qwen Code (qwen.ai OAuth) provider configuration.
+    #[serde(default)]
+    pub qwen: QwenConfig,
+
+    /// Upstream DNS resolution, shared by all providers.
+    /// TOML: `[providers.dns]`.
+    #[serde(default)]
+    pub dns: DnsConfig,
+
+    /// Write batching for streamed responses, shared by all providers.
+    /// TOML: `[providers.sse]`.
+    #[serde(default)]
+    pub sse: SseFlushConfig,
 }
 
 fn default_enable_multiplexing() -> bool {
@@ -72,3 +234,50 @@ fn default_enable_multiplexing() -> bool {
 fn default_retry_max_times() -> usize {
     3
 }
+
+fn default_min_token_validity_secs() -> u64 {
+    300
+}
+
+fn default_max_inline_data_bytes() -> usize {
+    20 * 1024 * 1024
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ProvidersConfig;
+    use serde_json::json;
+
+    #[test]
+    fn base_url_overrides_every_provider_upstream() {
+        let cfg: ProvidersConfig = serde_json::from_value(json!({
+            "geminicli": {
+                "base_url": "http://mirror.local/gemini/",
+                "oauth_token_url": "http://mirror.local/token"
+            },
+            "codex": { "base_url": "http://mirror.local/codex/" },
+            "antigravity": { "base_url": "http://mirror.local/antigravity/" }
+        }))
+        .unwrap();
+        assert_eq!(
+            cfg.geminicli.custom_api_url.as_str(),
+            "http://mirror.local/gemini/"
+        );
+        assert_eq!(
+            cfg.geminicli.oauth_token_url.as_str(),
+            "http://mirror.local/token"
+        );
+        assert_eq!(
+            cfg.codex.custom_api_url.as_str(),
+            "http://mirror.local/codex/"
+        );
+        assert_eq!(
+            cfg.codex.oauth_token_url.as_str(),
+            "https://auth.openai.com/oauth/token"
+        );
+        assert_eq!(
+            cfg.antigravity.api_url.as_str(),
+            "http://mirror.local/antigravity/"
+        );
+    }
+}
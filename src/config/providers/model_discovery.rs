@@ -0,0 +1,26 @@
+use serde::{Deserialize, Serialize};
+
+/// Periodic upstream query of the models each credential may call.
+///
+/// Antigravity only. The model list route then serves the configured models
+/// at least one credential currently offers, instead of the static list.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct ModelDiscoveryConfig {
+    /// Seconds between discovery passes over the active credentials.
+    /// TOML: `providers.antigravity.model_discovery.interval_secs`. Default: `600`.
+    #[serde(default = "default_interval_secs")]
+    pub interval_secs: u64,
+}
+
+impl Default for ModelDiscoveryConfig {
+    fn default() -> Self {
+        Self {
+            interval_secs: default_interval_secs(),
+        }
+    }
+}
+
+fn default_interval_secs() -> u64 {
+    600
+}
@@ -0,0 +1,46 @@
+use serde::{Deserialize, Serialize};
+
+/// Generation parameters for one model of a Gemini-shaped provider.
+///
+/// Values fill in what the client left out; with `force` they replace what it
+/// sent. `max_output_tokens` also lowers larger client values either way.
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct ModelOverrideConfig {
+    /// `generationConfig.temperature`, `0.0..=2.0`.
+    #[serde(default)]
+    pub temperature: Option<f64>,
+
+    /// `generationConfig.maxOutputTokens`.
+    #[serde(default)]
+    pub max_output_tokens: Option<u32>,
+
+    /// `generationConfig.thinkingConfig.thinkingBudget`.
+    #[serde(default)]
+    pub thinking_budget: Option<i64>,
+
+    /// `generationConfig.thinkingConfig.thinkingLevel`, e.g. `low` or `high`.
+    /// Mutually exclusive with `thinking_budget`.
+    #[serde(default)]
+    pub reasoning_effort: Option<String>,
+
+    /// Overwrite client-sent values instead of only filling gaps.
+    #[serde(default)]
+    pub force: bool,
+}
+
+impl ModelOverrideConfig {
+    /// Problems with this entry, prefixed by `name`.
+    pub(crate) fn validate(&self, name: &str) -> Vec<String> {
+        let mut problems = Vec::new();
+        if self.temperature.is_some_and(|t| !(0.0..=2.0).contains(&t)) {
+            problems.push(format!("{name}.temperature must be within 0.0..=2.0"));
+        }
+        if self.thinking_budget.is_some() && self.reasoning_effort.is_some() {
+            problems.push(format!(
+                "{name}: thinking_budget and reasoning_effort are mutually exclusive"
+            ));
+        }
+        problems
+    }
+}
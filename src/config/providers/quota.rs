@@ -0,0 +1,25 @@
+use serde::{Deserialize, Serialize};
+
+/// Soft daily limits per credential and model.
+///
+/// A credential that reaches either limit for a model is cooled down for that
+/// model until the next reset, before upstream starts answering `429`.
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct DailyQuotaConfig {
+    /// Requests per credential and model per quota day; unset is unlimited.
+    /// TOML: `providers.<p>.daily_quota.max_requests`. Default: unset.
+    #[serde(default)]
+    pub max_requests: Option<u64>,
+
+    /// Prompt plus output tokens per credential and model per quota day;
+    /// unset is unlimited.
+    /// TOML: `providers.<p>.daily_quota.max_tokens`. Default: unset.
+    #[serde(default)]
+    pub max_tokens: Option<u64>,
+
+    /// UTC hour, `0..=23`, at which upstream quotas (and these counters) reset.
+    /// TOML: `providers.<p>.daily_quota.reset_hour_utc`. Default: `0`.
+    #[serde(default)]
+    pub reset_hour_utc: u32,
+}
@@ -0,0 +1,41 @@
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// Cache of non-streaming `temperature = 0` responses, keyed by the
+/// normalized request body.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct ResponseCacheConfig {
+    /// Entries kept in memory.
+    /// TOML: `providers.<p>.response_cache.max_entries`. Default: `1024`.
+    #[serde(default = "default_max_entries")]
+    pub max_entries: u64,
+
+    /// How long a cached response is served after it was stored.
+    /// TOML: `providers.<p>.response_cache.ttl_secs`. Default: `3600`.
+    #[serde(default = "default_ttl_secs")]
+    pub ttl_secs: u64,
+
+    /// Directory that entries are also written to, so they survive restarts.
+    /// TOML: `providers.<p>.response_cache.disk_path`. Default: unset (memory only).
+    #[serde(default)]
+    pub disk_path: Option<PathBuf>,
+}
+
+impl Default for ResponseCacheConfig {
+    fn default() -> Self {
+        Self {
+            max_entries: default_max_entries(),
+            ttl_secs: default_ttl_secs(),
+            disk_path: None,
+        }
+    }
+}
+
+fn default_max_entries() -> u64 {
+    1024
+}
+
+fn default_ttl_secs() -> u64 {
+    60 * 60
+}
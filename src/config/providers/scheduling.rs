@@ -0,0 +1,52 @@
+use serde::{Deserialize, Serialize};
+
+/// How a provider picks among credentials that can all serve a request.
+///
+/// Latency and error feedback is tracked per credential and model from the
+/// outcomes clients report after each upstream call. Tier weights, when set,
+/// take precedence over the policy.
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SchedulingPolicy {
+    /// Rotate through credentials in queue order.
+    #[default]
+    RoundRobin,
+    /// Prefer the credential leased longest ago on any model, spreading
+    /// account quota evenly across models.
+    LeastRecentlyUsed,
+    /// Prefer the credential with the lowest recent time to response headers.
+    LeastLatency,
+    /// Compare two random ready credentials and take the faster one; follows
+    /// latency without herding every request onto the single fastest account.
+    P2cLatency,
+    /// Prefer the credential with the lowest recent upstream error rate.
+    LeastErrors,
+}
+
+/// Priority class of a request, from `x-pollux-priority` or the client key's
+/// default.
+///
+/// Parked requests are served highest class first, and only `high` requests
+/// may lease the credentials held back by `priority_reserve`.
+#[derive(
+    Debug, Clone, Copy, Default, Deserialize, Serialize, PartialEq, Eq, PartialOrd, Ord, Hash,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum Priority {
+    High,
+    #[default]
+    Normal,
+    Low,
+}
+
+impl Priority {
+    /// Parse a header value, case-insensitively.
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "high" => Some(Self::High),
+            "normal" => Some(Self::Normal),
+            "low" => Some(Self::Low),
+            _ => None,
+        }
+    }
+}
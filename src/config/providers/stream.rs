@@ -0,0 +1,99 @@
+use serde::{Deserialize, Serialize};
+
+/// One stage of a route's SSE transformation pipeline.
+///
+/// Stages run in the order listed. TOML: `[[providers.<p>.stream_transformers]]`
+/// with a `kind` key, e.g. `kind = "strip_thoughts"`.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum StreamTransformerConfig {
+    /// Drop model thought text before it reaches the client.
+    StripThoughts,
+
+    /// Replace listed words (whole word, case-insensitive) with `replacement`.
+    Redact {
+        words: Vec<String>,
+        /// Default: `"***"`.
+        #[serde(default = "default_replacement")]
+        replacement: String,
+    },
+
+    /// Merge small deltas until at least `min_chars` bytes are buffered.
+    Coalesce {
+        /// Default: `64`.
+        #[serde(default = "default_min_chars")]
+        min_chars: usize,
+    },
+
+    /// Repair streaming artifacts that break client rendering.
+    Repair {
+        /// Close a code fence still open when the response ends (e.g. on
+        /// `MAX_TOKENS`). Default: `true`.
+        #[serde(default = "default_true")]
+        fences: bool,
+        /// Rejoin UTF-16 surrogate escapes split across chunks, and replace
+        /// unpaired ones instead of dropping the chunk. Default: `true`.
+        #[serde(default = "default_true")]
+        surrogates: bool,
+    },
+}
+
+/// How streamed responses are written to the client socket.
+///
+/// By default every SSE event is written as soon as it is produced, which
+/// gives the best time-to-first-token. Buffering trades that for fewer,
+/// larger writes on slow or high-overhead links.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct SseFlushConfig {
+    /// Write once this many bytes are buffered; `0` writes every event
+    /// immediately and disables the other buffering settings.
+    /// TOML: `providers.sse.max_buffer_bytes`. Default: `0`.
+    #[serde(default)]
+    pub max_buffer_bytes: usize,
+
+    /// Longest a buffered byte may wait for the buffer to fill.
+    /// TOML: `providers.sse.max_latency_ms`. Default: `0`.
+    #[serde(default)]
+    pub max_latency_ms: u64,
+
+    /// Write events larger than `max_buffer_bytes` in `max_buffer_bytes`
+    /// pieces instead of as one write.
+    /// TOML: `providers.sse.split_oversized`. Default: `false`.
+    #[serde(default)]
+    pub split_oversized: bool,
+
+    /// Send a `: ping` comment frame after this many seconds without an
+    /// upstream event, so proxies do not drop streams during long thinking
+    /// phases; `0` disables it.
+    /// TOML: `providers.sse.heartbeat_secs`. Default: `15`.
+    #[serde(default = "default_heartbeat_secs")]
+    pub heartbeat_secs: u64,
+}
+
+impl Default for SseFlushConfig {
+    fn default() -> Self {
+        Self {
+            max_buffer_bytes: 0,
+            max_latency_ms: 0,
+            split_oversized: false,
+            heartbeat_secs: default_heartbeat_secs(),
+        }
+    }
+}
+
+fn default_heartbeat_secs() -> u64 {
+    15
+}
+
+fn default_replacement() -> String {
+    "***".to_string()
+}
+
+fn default_min_chars() -> usize {
+    64
+}
+
+fn default_true() -> bool {
+    true
+}
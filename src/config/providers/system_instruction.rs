@@ -0,0 +1,29 @@
+use serde::{Deserialize, Serialize};
+
+/// How the configured text is combined with the client's `systemInstruction`.
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SystemInstructionMode {
+    /// Insert the text as the first part, before the client's own.
+    Prepend,
+    /// Add the text as the last part, after the client's own.
+    Append,
+    /// Send only the text; an empty text drops the client's instruction.
+    Replace,
+    /// Forward the client's instruction untouched.
+    #[default]
+    Off,
+}
+
+/// Server-side `systemInstruction` policy for Gemini-shaped upstream requests.
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct SystemInstructionConfig {
+    /// TOML: `providers.<p>.system_instruction.mode`. Default: `off`.
+    #[serde(default)]
+    pub mode: SystemInstructionMode,
+
+    /// TOML: `providers.<p>.system_instruction.text`. Default: empty.
+    #[serde(default)]
+    pub text: String,
+}
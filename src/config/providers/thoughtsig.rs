@@ -0,0 +1,84 @@
+use pollux_thoughtsig_core::FingerprintVersion;
+use serde::{Deserialize, Serialize};
+
+/// Where captured thought signatures are kept.
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ThoughtSigStorage {
+    /// In-process only; lost on restart.
+    #[default]
+    Memory,
+
+    /// In memory, written through to the database and reloaded at startup.
+    Persistent,
+}
+
+/// Thought-signature cache settings.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct ThoughtSigConfig {
+    /// TOML: `providers.<p>.thoughtsig.storage`. Default: `"memory"`.
+    #[serde(default)]
+    pub storage: ThoughtSigStorage,
+
+    /// How long a signature stays usable after it was captured.
+    /// TOML: `providers.<p>.thoughtsig.ttl_secs`. Default: `3600`.
+    #[serde(default = "default_ttl_secs")]
+    pub ttl_secs: u64,
+
+    /// Maximum signatures kept, in memory and (when persistent) in the database.
+    /// TOML: `providers.<p>.thoughtsig.max_capacity`. Default: `200000`.
+    #[serde(default = "default_max_capacity")]
+    pub max_capacity: u64,
+
+    /// Thought text held per streamed candidate while waiting for its
+    /// signature; longer thoughts are forwarded but not cached.
+    /// TOML: `providers.<p>.thoughtsig.max_buffered_thought_bytes`. Default: `1048576`.
+    #[serde(default = "default_max_buffered_thought_bytes")]
+    pub max_buffered_thought_bytes: usize,
+
+    /// Fingerprint algorithm for cache keys. `v2` is stable across builds and
+    /// hosts, so persisted signatures survive upgrades; `v1` is not.
+    /// TOML: `providers.<p>.thoughtsig.fingerprint`. Default: `"v1"`.
+    #[serde(default)]
+    pub fingerprint: FingerprintVersion,
+
+    /// Previous `fingerprint`, still probed on a miss while its entries age
+    /// out; hits are moved to the current version. Persisted rows of any
+    /// other version are deleted at startup and on every prune.
+    /// TOML: `providers.<p>.thoughtsig.migrate_from`. Default: unset.
+    #[serde(default)]
+    pub migrate_from: Option<FingerprintVersion>,
+
+    /// Debug aid: report how request thought signatures were patched in an
+    /// `x-pollux-thoughtsig` response header (`hits=..; misses=..; dropped=..`).
+    /// TOML: `providers.<p>.thoughtsig.report_outcomes`. Default: `false`.
+    #[serde(default)]
+    pub report_outcomes: bool,
+}
+
+impl Default for ThoughtSigConfig {
+    fn default() -> Self {
+        Self {
+            storage: ThoughtSigStorage::default(),
+            ttl_secs: default_ttl_secs(),
+            max_capacity: default_max_capacity(),
+            max_buffered_thought_bytes: default_max_buffered_thought_bytes(),
+            fingerprint: FingerprintVersion::default(),
+            migrate_from: None,
+            report_outcomes: false,
+        }
+    }
+}
+
+fn default_ttl_secs() -> u64 {
+    60 * 60
+}
+
+fn default_max_capacity() -> u64 {
+    200_000
+}
+
+fn default_max_buffered_thought_bytes() -> usize {
+    1024 * 1024
+}
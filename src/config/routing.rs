@@ -0,0 +1,139 @@
+use crate::providers::manifest::ProviderKind;
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+/// Cross-provider routing configuration managed by Figment.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct RoutingConfig {
+    /// Failover order between providers that serve the same models (the
+    /// `gemini-*` family on `antigravity` and `geminicli`). A request whose
+    /// own pool has no credential left for the model, or is still rate
+    /// limited after its retries, is retried through the providers listed
+    /// after its own.
+    /// TOML: `routing.fallback`, e.g. `["antigravity", "geminicli"]`. Default: `[]` (off).
+    #[serde(default)]
+    pub fallback: Vec<ProviderKind>,
+
+    /// Shadow traffic from one provider to another (see [`MirrorConfig`]).
+    /// TOML: `routing.mirror`. Default: unset (off).
+    #[serde(default)]
+    pub mirror: Option<MirrorConfig>,
+}
+
+/// Copies a share of native Gemini requests to a second target in the
+/// background. The client only ever sees the primary response; the mirror's
+/// is read to the end and discarded, and both outcomes are counted for
+/// `/admin/v1/mirror`.
+///
+/// Exactly one of `to` and `url` must be set.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct MirrorConfig {
+    /// Provider whose requests are copied (`antigravity` or `geminicli`).
+    /// TOML: `routing.mirror.from`.
+    pub from: ProviderKind,
+
+    /// Provider that receives the copies, using its own credential pool.
+    /// TOML: `routing.mirror.to`.
+    #[serde(default)]
+    pub to: Option<ProviderKind>,
+
+    /// Gemini-compatible base URL that receives the copies instead, e.g. a
+    /// staging Pollux at `https://staging.example/geminicli/v1beta`.
+    /// TOML: `routing.mirror.url`.
+    #[serde(default)]
+    pub url: Option<Url>,
+
+    /// Sent as `x-goog-api-key` to `url`.
+    /// TOML: `routing.mirror.key`.
+    #[serde(default)]
+    pub key: Option<String>,
+
+    /// Share of requests copied, `0.0..=1.0`.
+    /// TOML: `routing.mirror.fraction`. Default: `0.0`.
+    #[serde(default)]
+    pub fraction: f64,
+}
+
+impl MirrorConfig {
+    /// Problems that make this mirror unusable.
+    pub(crate) fn validate(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        if !(0.0..=1.0).contains(&self.fraction) {
+            problems.push("routing.mirror.fraction must be within 0.0..=1.0".to_string());
+        }
+        if !matches!(
+            self.from,
+            ProviderKind::Antigravity | ProviderKind::GeminiCli
+        ) {
+            problems.push("routing.mirror.from: only antigravity and geminicli".to_string());
+        }
+        match (self.to, &self.url) {
+            (Some(_), Some(_)) | (None, None) => {
+                problems.push("routing.mirror: set exactly one of `to` and `url`".to_string());
+            }
+            (Some(to), None) => {
+                if to == self.from {
+                    problems.push("routing.mirror.to must differ from `from`".to_string());
+                } else if !matches!(to, ProviderKind::Antigravity | ProviderKind::GeminiCli) {
+                    problems.push("routing.mirror.to: only antigravity and geminicli".to_string());
+                }
+            }
+            (None, Some(url)) => {
+                if !matches!(url.scheme(), "http" | "https") {
+                    problems.push(format!(
+                        "routing.mirror.url must be an http(s) URL, got {url}"
+                    ));
+                }
+            }
+        }
+        problems
+    }
+}
+
+impl RoutingConfig {
+    /// Providers to try, in order, once `from` has given up.
+    pub fn fallbacks_after(&self, from: ProviderKind) -> &[ProviderKind] {
+        self.fallback
+            .iter()
+            .position(|&p| p == from)
+            .map_or(&[], |i| &self.fallback[i + 1..])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_providers_listed_after_the_origin_are_tried() {
+        let cfg: RoutingConfig =
+            serde_json::from_value(serde_json::json!({"fallback": ["antigravity", "geminicli"]}))
+                .unwrap();
+        assert_eq!(
+            cfg.fallbacks_after(ProviderKind::Antigravity),
+            [ProviderKind::GeminiCli]
+        );
+        assert!(cfg.fallbacks_after(ProviderKind::GeminiCli).is_empty());
+        assert!(cfg.fallbacks_after(ProviderKind::Codex).is_empty());
+    }
+
+    #[test]
+    fn mirror_needs_exactly_one_target() {
+        let mirror =
+            |value: serde_json::Value| -> MirrorConfig { serde_json::from_value(value).unwrap() };
+        let ok =
+            mirror(serde_json::json!({"from": "antigravity", "to": "geminicli", "fraction": 0.1}));
+        assert!(ok.validate().is_empty());
+
+        let both = mirror(serde_json::json!({
+            "from": "antigravity", "to": "geminicli", "url": "https://staging.example/v1beta"
+        }));
+        assert_eq!(both.validate().len(), 1);
+
+        let to_self =
+            mirror(serde_json::json!({"from": "geminicli", "to": "geminicli", "fraction": 2.0}));
+        assert_eq!(to_self.validate().len(), 2);
+    }
+}
@@ -0,0 +1,145 @@
+//! `*_file` settings: secrets read from files (e.g. mounted container or
+//! systemd credentials) instead of living in `config.toml` or the environment.
+
+use super::Config;
+use std::path::Path;
+
+impl Config {
+    /// Fill every secret from its `*_file` twin; returns one problem per
+    /// unreadable file or secret set both inline and by file.
+    pub fn load_secret_files(&mut self) -> Vec<String> {
+        let mut problems = Vec::new();
+
+        let basic = &mut self.basic;
+        if let Some(secret) = secret_from_file(
+            &mut problems,
+            "basic.pollux_key",
+            !basic.pollux_key.is_empty(),
+            basic.pollux_key_file.as_deref(),
+        ) {
+            basic.pollux_key = secret;
+        }
+        if let Some(secret) = secret_from_file(
+            &mut problems,
+            "basic.db_encryption_key",
+            basic.db_encryption_key.is_some(),
+            basic.db_encryption_key_file.as_deref(),
+        ) {
+            basic.db_encryption_key = Some(secret);
+        }
+
+        // Codex, Claude and Qwen sign in as public clients (PKCE or device
+        // flow) and have no client secret to load.
+        let geminicli = &mut self.providers.geminicli;
+        if let Some(secret) = secret_from_file(
+            &mut problems,
+            "providers.geminicli.oauth_client_secret",
+            geminicli.oauth_client_secret.is_some(),
+            geminicli.oauth_client_secret_file.as_deref(),
+        ) {
+            geminicli.oauth_client_secret = Some(secret);
+        }
+
+        let antigravity = &mut self.providers.antigravity;
+        if let Some(secret) = secret_from_file(
+            &mut problems,
+            "providers.antigravity.oauth_client_secret",
+            antigravity.oauth_client_secret.is_some(),
+            antigravity.oauth_client_secret_file.as_deref(),
+        ) {
+            antigravity.oauth_client_secret = Some(secret);
+        }
+
+        problems
+    }
+}
+
+/// Contents of `path` without the trailing newline. Records a problem and
+/// yields `None` when `name` is also set inline or the file is unusable.
+fn secret_from_file(
+    problems: &mut Vec<String>,
+    name: &str,
+    inline: bool,
+    path: Option<&Path>,
+) -> Option<String> {
+    let path = path?;
+    if inline {
+        problems.push(format!("{name} and {name}_file are both set"));
+        return None;
+    }
+    let raw = std::fs::read_to_string(path)
+        .map_err(|e| problems.push(format!("{name}_file: cannot read {}: {e}", path.display())))
+        .ok()?;
+    let secret = raw.trim_end_matches(['\r', '\n']);
+    if secret.is_empty() {
+        problems.push(format!("{name}_file: {} is empty", path.display()));
+        return None;
+    }
+    Some(secret.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    type SetFile = fn(&mut Config, PathBuf);
+    type SetInline = fn(&mut Config);
+    type Loaded = fn(&Config) -> Option<String>;
+
+    /// Every `*_file` secret: point it at a file, set it inline, read it back.
+    /// Codex, Claude and Qwen are public OAuth clients with no secret.
+    const SECRET_FILES: &[(&str, SetFile, SetInline, Loaded)] = &[
+        (
+            "basic.pollux_key",
+            |cfg, path| cfg.basic.pollux_key_file = Some(path),
+            |cfg| cfg.basic.pollux_key = "inline".to_string(),
+            |cfg| Some(cfg.basic.pollux_key.clone()),
+        ),
+        (
+            "basic.db_encryption_key",
+            |cfg, path| cfg.basic.db_encryption_key_file = Some(path),
+            |cfg| cfg.basic.db_encryption_key = Some("inline".to_string()),
+            |cfg| cfg.basic.db_encryption_key.clone(),
+        ),
+        (
+            "providers.geminicli.oauth_client_secret",
+            |cfg, path| cfg.providers.geminicli.oauth_client_secret_file = Some(path),
+            |cfg| cfg.providers.geminicli.oauth_client_secret = Some("inline".to_string()),
+            |cfg| cfg.providers.geminicli.oauth_client_secret.clone(),
+        ),
+        (
+            "providers.antigravity.oauth_client_secret",
+            |cfg, path| cfg.providers.antigravity.oauth_client_secret_file = Some(path),
+            |cfg| cfg.providers.antigravity.oauth_client_secret = Some("inline".to_string()),
+            |cfg| Some(cfg.antigravity().oauth_client_secret),
+        ),
+    ];
+
+    #[test]
+    fn reads_secret_files_and_rejects_duplicates() {
+        let path = std::env::temp_dir().join(format!("pollux_secret_{}", uuid::Uuid::new_v4()));
+        std::fs::write(&path, "s3cret\n").unwrap();
+
+        for (name, set_file, set_inline, loaded) in SECRET_FILES {
+            let mut cfg = Config::default();
+            set_file(&mut cfg, path.clone());
+            assert!(cfg.load_secret_files().is_empty(), "{name}");
+            assert_eq!(loaded(&cfg).as_deref(), Some("s3cret"), "{name}");
+
+            let mut both = Config::default();
+            set_inline(&mut both);
+            set_file(&mut both, path.clone());
+            let problems = both.load_secret_files();
+            assert_eq!(problems, [format!("{name} and {name}_file are both set")]);
+
+            let mut missing = Config::default();
+            set_file(&mut missing, path.with_extension("missing"));
+            let problems = missing.load_secret_files();
+            assert_eq!(problems.len(), 1, "{name}");
+            assert!(problems[0].starts_with(&format!("{name}_file: cannot read")));
+        }
+
+        std::fs::remove_file(path).unwrap();
+    }
+}
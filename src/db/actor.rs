@@ -1,14 +1,21 @@
-use crate::db::models::{DbAntigravityResource, DbCodexResource, DbGeminiCliResource};
-use crate::db::patch::{ProviderCreate, ProviderPatch};
-use crate::db::schema::SQLITE_INIT;
-use crate::db::traits::DbPatchable;
+use crate::db::backend::{DbPool, with_pool};
+use crate::db::crypto::{TokenCipher, TokenColumns};
+use crate::db::models::{
+    DbAntigravityResource, DbClaudeResource, DbCodexResource, DbGeminiCliResource, DbQwenResource,
+    ModelRegistryRow, ModelUsageStats, RequestCounterRow, ThoughtSignatureRow, UsageAggregate,
+    UsageQuery, UsageRecord, join_labels,
+};
+use crate::db::patch::{
+    AntigravityPatch, ClaudePatch, CodexPatch, GeminiCliPatch, ProviderCreate, ProviderDelete,
+    ProviderIdentity, ProviderPatch, QwenPatch,
+};
+use crate::db::traits::{DbPatchable, SqlDialect};
 use crate::error::PolluxError;
-use chrono::Utc;
+use crate::server::alerts::{AlertEvent, alert};
+use chrono::{DateTime, Utc};
+use pollux_thoughtsig_core::Invalidation;
 use ractor::{Actor, ActorProcessingErr, ActorRef, RpcReplyPort};
-use sqlx::SqlitePool;
-use sqlx::sqlite::{SqliteConnectOptions, SqliteJournalMode, SqlitePoolOptions, SqliteSynchronous};
-use std::{str::FromStr, time::Duration};
-use tracing::info;
+use tracing::{info, warn};
 
 #[derive(Debug)]
 pub enum DbActorMessage {
@@ -27,8 +34,112 @@ pub enum DbActorMessage {
     /// List active Antigravity credentials (status=1).
     ListActiveAntigravity(RpcReplyPort<Result<Vec<DbAntigravityResource>, PolluxError>>),
 
+    /// List active Claude credentials (status=1).
+    ListActiveClaude(RpcReplyPort<Result<Vec<DbClaudeResource>, PolluxError>>),
+
+    /// List active Qwen credentials (status=1).
+    ListActiveQwen(RpcReplyPort<Result<Vec<DbQwenResource>, PolluxError>>),
+
+    /// List all Gemini CLI credentials, including disabled ones.
+    ListGeminiCli(RpcReplyPort<Result<Vec<DbGeminiCliResource>, PolluxError>>),
+
+    /// List all Codex keys, including disabled ones.
+    ListCodex(RpcReplyPort<Result<Vec<DbCodexResource>, PolluxError>>),
+
+    /// List all Antigravity credentials, including disabled ones.
+    ListAntigravity(RpcReplyPort<Result<Vec<DbAntigravityResource>, PolluxError>>),
+
+    /// List all Claude credentials, including disabled ones.
+    ListClaude(RpcReplyPort<Result<Vec<DbClaudeResource>, PolluxError>>),
+
+    /// List all Qwen credentials, including disabled ones.
+    ListQwen(RpcReplyPort<Result<Vec<DbQwenResource>, PolluxError>>),
+
+    /// Get Gemini CLI credential by id.
+    GetGeminiCliById(i64, RpcReplyPort<Result<DbGeminiCliResource, PolluxError>>),
+
     /// Get Codex key by id.
     GetCodexById(i64, RpcReplyPort<Result<DbCodexResource, PolluxError>>),
+
+    /// Get Antigravity credential by id.
+    GetAntigravityById(
+        i64,
+        RpcReplyPort<Result<DbAntigravityResource, PolluxError>>,
+    ),
+
+    /// Get Claude credential by id.
+    GetClaudeById(i64, RpcReplyPort<Result<DbClaudeResource, PolluxError>>),
+
+    /// Get Qwen credential by id.
+    GetQwenById(i64, RpcReplyPort<Result<DbQwenResource, PolluxError>>),
+
+    /// Id of the stored row for an account identity, preferring enabled rows.
+    FindByIdentity(
+        ProviderIdentity,
+        RpcReplyPort<Result<Option<i64>, PolluxError>>,
+    ),
+
+    /// Delete a provider record by id.
+    Delete(ProviderDelete, RpcReplyPort<Result<(), PolluxError>>),
+
+    /// Append a usage row (fire-and-forget).
+    RecordUsage(UsageRecord),
+
+    /// Aggregate usage rows by provider, model and credential.
+    UsageSummary(
+        UsageQuery,
+        RpcReplyPort<Result<Vec<UsageAggregate>, PolluxError>>,
+    ),
+
+    /// Outcome counts per (provider, model) since the given instant.
+    ModelUsageStats(
+        DateTime<Utc>,
+        RpcReplyPort<Result<Vec<ModelUsageStats>, PolluxError>>,
+    ),
+
+    /// Load the persisted request counters.
+    LoadRequestCounters(RpcReplyPort<Result<Vec<RequestCounterRow>, PolluxError>>),
+
+    /// Overwrite the persisted totals for each given (provider, model).
+    SaveRequestCounters(
+        Vec<RequestCounterRow>,
+        RpcReplyPort<Result<(), PolluxError>>,
+    ),
+
+    /// Load the model list saved at the last boot.
+    LoadModelRegistry(RpcReplyPort<Result<Vec<ModelRegistryRow>, PolluxError>>),
+
+    /// Replace the saved model list.
+    SaveModelRegistry(Vec<ModelRegistryRow>, RpcReplyPort<Result<(), PolluxError>>),
+
+    /// Upsert a captured thought signature (fire-and-forget).
+    RecordThoughtSignature(ThoughtSignatureRow),
+
+    /// Load a provider's thought signatures created at or after the given
+    /// unix second, newest first, at most `limit` rows.
+    LoadThoughtSignatures {
+        provider: String,
+        since: i64,
+        limit: i64,
+        reply: RpcReplyPort<Result<Vec<ThoughtSignatureRow>, PolluxError>>,
+    },
+
+    /// Delete a provider's signatures in `scope` (fire-and-forget).
+    DeleteThoughtSignatures {
+        provider: String,
+        scope: Invalidation,
+    },
+
+    /// Delete a provider's signatures older than `before`, keyed by a
+    /// fingerprint version outside `versions`, and all but the newest `keep`;
+    /// replies with the number of rows removed.
+    PruneThoughtSignatures {
+        provider: String,
+        before: i64,
+        keep: i64,
+        versions: [i16; 2],
+        reply: RpcReplyPort<Result<u64, PolluxError>>,
+    },
 }
 
 #[derive(Clone)]
@@ -65,15 +176,229 @@ impl DbActorHandle {
         })?
     }
 
+    pub async fn list_active_claude(&self) -> Result<Vec<DbClaudeResource>, PolluxError> {
+        ractor::call!(self.actor, DbActorMessage::ListActiveClaude).map_err(|e| {
+            PolluxError::RactorError(format!("DbActor ListActiveClaude RPC failed: {e}"))
+        })?
+    }
+
+    pub async fn list_active_qwen(&self) -> Result<Vec<DbQwenResource>, PolluxError> {
+        ractor::call!(self.actor, DbActorMessage::ListActiveQwen).map_err(|e| {
+            PolluxError::RactorError(format!("DbActor ListActiveQwen RPC failed: {e}"))
+        })?
+    }
+
+    pub async fn list_geminicli(&self) -> Result<Vec<DbGeminiCliResource>, PolluxError> {
+        ractor::call!(self.actor, DbActorMessage::ListGeminiCli).map_err(|e| {
+            PolluxError::RactorError(format!("DbActor ListGeminiCli RPC failed: {e}"))
+        })?
+    }
+
+    pub async fn list_codex(&self) -> Result<Vec<DbCodexResource>, PolluxError> {
+        ractor::call!(self.actor, DbActorMessage::ListCodex)
+            .map_err(|e| PolluxError::RactorError(format!("DbActor ListCodex RPC failed: {e}")))?
+    }
+
+    pub async fn list_antigravity(&self) -> Result<Vec<DbAntigravityResource>, PolluxError> {
+        ractor::call!(self.actor, DbActorMessage::ListAntigravity).map_err(|e| {
+            PolluxError::RactorError(format!("DbActor ListAntigravity RPC failed: {e}"))
+        })?
+    }
+
+    pub async fn list_claude(&self) -> Result<Vec<DbClaudeResource>, PolluxError> {
+        ractor::call!(self.actor, DbActorMessage::ListClaude)
+            .map_err(|e| PolluxError::RactorError(format!("DbActor ListClaude RPC failed: {e}")))?
+    }
+
+    pub async fn list_qwen(&self) -> Result<Vec<DbQwenResource>, PolluxError> {
+        ractor::call!(self.actor, DbActorMessage::ListQwen)
+            .map_err(|e| PolluxError::RactorError(format!("DbActor ListQwen RPC failed: {e}")))?
+    }
+
+    pub async fn get_geminicli_by_id(&self, id: i64) -> Result<DbGeminiCliResource, PolluxError> {
+        ractor::call!(self.actor, DbActorMessage::GetGeminiCliById, id).map_err(|e| {
+            PolluxError::RactorError(format!("DbActor GetGeminiCliById RPC failed: {e}"))
+        })?
+    }
+
     pub async fn get_codex_by_id(&self, id: i64) -> Result<DbCodexResource, PolluxError> {
         ractor::call!(self.actor, DbActorMessage::GetCodexById, id).map_err(|e| {
             PolluxError::RactorError(format!("DbActor GetCodexById RPC failed: {e}"))
         })?
     }
+
+    pub async fn get_antigravity_by_id(
+        &self,
+        id: i64,
+    ) -> Result<DbAntigravityResource, PolluxError> {
+        ractor::call!(self.actor, DbActorMessage::GetAntigravityById, id).map_err(|e| {
+            PolluxError::RactorError(format!("DbActor GetAntigravityById RPC failed: {e}"))
+        })?
+    }
+
+    pub async fn get_claude_by_id(&self, id: i64) -> Result<DbClaudeResource, PolluxError> {
+        ractor::call!(self.actor, DbActorMessage::GetClaudeById, id).map_err(|e| {
+            PolluxError::RactorError(format!("DbActor GetClaudeById RPC failed: {e}"))
+        })?
+    }
+
+    pub async fn get_qwen_by_id(&self, id: i64) -> Result<DbQwenResource, PolluxError> {
+        ractor::call!(self.actor, DbActorMessage::GetQwenById, id)
+            .map_err(|e| PolluxError::RactorError(format!("DbActor GetQwenById RPC failed: {e}")))?
+    }
+
+    pub async fn find_by_identity(
+        &self,
+        identity: ProviderIdentity,
+    ) -> Result<Option<i64>, PolluxError> {
+        ractor::call!(self.actor, DbActorMessage::FindByIdentity, identity).map_err(|e| {
+            PolluxError::RactorError(format!("DbActor FindByIdentity RPC failed: {e}"))
+        })?
+    }
+
+    pub async fn delete(&self, delete: ProviderDelete) -> Result<(), PolluxError> {
+        ractor::call!(self.actor, DbActorMessage::Delete, delete)
+            .map_err(|e| PolluxError::RactorError(format!("DbActor Delete RPC failed: {e}")))?
+    }
+
+    pub fn record_usage(&self, record: UsageRecord) {
+        let _ = ractor::cast!(self.actor, DbActorMessage::RecordUsage(record));
+    }
+
+    pub async fn usage_summary(
+        &self,
+        query: UsageQuery,
+    ) -> Result<Vec<UsageAggregate>, PolluxError> {
+        ractor::call!(self.actor, DbActorMessage::UsageSummary, query).map_err(|e| {
+            PolluxError::RactorError(format!("DbActor UsageSummary RPC failed: {e}"))
+        })?
+    }
+
+    pub async fn load_request_counters(&self) -> Result<Vec<RequestCounterRow>, PolluxError> {
+        ractor::call!(self.actor, DbActorMessage::LoadRequestCounters).map_err(|e| {
+            PolluxError::RactorError(format!("DbActor LoadRequestCounters RPC failed: {e}"))
+        })?
+    }
+
+    pub async fn save_request_counters(
+        &self,
+        rows: Vec<RequestCounterRow>,
+    ) -> Result<(), PolluxError> {
+        ractor::call!(self.actor, DbActorMessage::SaveRequestCounters, rows).map_err(|e| {
+            PolluxError::RactorError(format!("DbActor SaveRequestCounters RPC failed: {e}"))
+        })?
+    }
+
+    pub async fn load_model_registry(&self) -> Result<Vec<ModelRegistryRow>, PolluxError> {
+        ractor::call!(self.actor, DbActorMessage::LoadModelRegistry).map_err(|e| {
+            PolluxError::RactorError(format!("DbActor LoadModelRegistry RPC failed: {e}"))
+        })?
+    }
+
+    pub async fn save_model_registry(
+        &self,
+        rows: Vec<ModelRegistryRow>,
+    ) -> Result<(), PolluxError> {
+        ractor::call!(self.actor, DbActorMessage::SaveModelRegistry, rows).map_err(|e| {
+            PolluxError::RactorError(format!("DbActor SaveModelRegistry RPC failed: {e}"))
+        })?
+    }
+
+    pub fn record_thought_signature(&self, row: ThoughtSignatureRow) {
+        let _ = ractor::cast!(self.actor, DbActorMessage::RecordThoughtSignature(row));
+    }
+
+    pub fn delete_thought_signatures(&self, provider: &str, scope: Invalidation) {
+        let _ = ractor::cast!(
+            self.actor,
+            DbActorMessage::DeleteThoughtSignatures {
+                provider: provider.to_string(),
+                scope,
+            }
+        );
+    }
+
+    pub async fn load_thought_signatures(
+        &self,
+        provider: &str,
+        since: i64,
+        limit: i64,
+    ) -> Result<Vec<ThoughtSignatureRow>, PolluxError> {
+        ractor::call!(self.actor, |reply| DbActorMessage::LoadThoughtSignatures {
+            provider: provider.to_string(),
+            since,
+            limit,
+            reply,
+        })
+        .map_err(|e| {
+            PolluxError::RactorError(format!("DbActor LoadThoughtSignatures RPC failed: {e}"))
+        })?
+    }
+
+    pub async fn prune_thought_signatures(
+        &self,
+        provider: &str,
+        before: i64,
+        keep: i64,
+        versions: [i16; 2],
+    ) -> Result<u64, PolluxError> {
+        ractor::call!(self.actor, |reply| DbActorMessage::PruneThoughtSignatures {
+            provider: provider.to_string(),
+            before,
+            keep,
+            versions,
+            reply,
+        })
+        .map_err(|e| {
+            PolluxError::RactorError(format!("DbActor PruneThoughtSignatures RPC failed: {e}"))
+        })?
+    }
+
+    pub async fn model_usage_stats(
+        &self,
+        since: DateTime<Utc>,
+    ) -> Result<Vec<ModelUsageStats>, PolluxError> {
+        ractor::call!(self.actor, DbActorMessage::ModelUsageStats, since).map_err(|e| {
+            PolluxError::RactorError(format!("DbActor ModelUsageStats RPC failed: {e}"))
+        })?
+    }
 }
 
 struct DbActorState {
-    pool: SqlitePool,
+    pool: DbPool,
+    cipher: Option<TokenCipher>,
+}
+
+impl DbActorState {
+    fn open_row<T: TokenColumns>(&self, row: T) -> Result<T, PolluxError> {
+        match &self.cipher {
+            Some(cipher) => row.open_tokens(cipher),
+            None => Ok(row),
+        }
+    }
+
+    fn open_rows<T: TokenColumns>(&self, rows: Vec<T>) -> Result<Vec<T>, PolluxError> {
+        rows.into_iter().map(|row| self.open_row(row)).collect()
+    }
+}
+
+/// Pass `res` through, raising an alert if the query itself failed.
+fn alerted<T>(res: Result<T, PolluxError>) -> Result<T, PolluxError> {
+    if let Err(PolluxError::DatabaseError(e)) = &res
+        && !matches!(e, sqlx::Error::RowNotFound)
+    {
+        alert(AlertEvent::DatabaseError {
+            error: e.to_string(),
+        });
+    }
+    res
+}
+
+fn seal(cipher: Option<&TokenCipher>, value: String) -> Result<String, PolluxError> {
+    match cipher {
+        Some(cipher) => cipher.seal(&value),
+        None => Ok(value),
+    }
 }
 
 struct DbActor;
@@ -82,33 +407,36 @@ struct DbActor;
 impl Actor for DbActor {
     type Msg = DbActorMessage;
     type State = DbActorState;
-    type Arguments = String;
+    type Arguments = (String, Option<TokenCipher>);
 
     async fn pre_start(
         &self,
         _myself: ActorRef<Self::Msg>,
-        database_url: Self::Arguments,
+        (database_url, cipher): Self::Arguments,
     ) -> Result<Self::State, ActorProcessingErr> {
-        let connect_opts = SqliteConnectOptions::from_str(database_url.as_str())
-            .map_err(|e| ActorProcessingErr::from(format!("invalid database url: {e}")))?
-            .create_if_missing(true)
-            .busy_timeout(Duration::from_secs(5))
-            .journal_mode(SqliteJournalMode::Wal)
-            .synchronous(SqliteSynchronous::Normal);
-
-        let pool = SqlitePoolOptions::new()
-            .connect_with(connect_opts)
+        let pool = DbPool::connect(database_url.as_str())
             .await
-            .map_err(|e| ActorProcessingErr::from(format!("db connect failed: {e}")))?;
+            .map_err(|e| ActorProcessingErr::from(format!("db init failed: {e}")))?;
 
-        apply_schema(&pool)
-            .await
-            .map_err(|e| ActorProcessingErr::from(format!("db schema init failed: {e}")))?;
+        if let Some(cipher) = &cipher {
+            let sealed = self
+                .seal_plaintext_tokens(&pool, cipher)
+                .await
+                .map_err(|e| ActorProcessingErr::from(format!("token encryption failed: {e}")))?;
+            if sealed > 0 {
+                info!(rows = sealed, "Encrypted plaintext credential tokens");
+            }
+        }
 
-        info!("DbActor initialized");
-        Ok(DbActorState { pool })
+        info!(
+            backend = pool.kind().as_str(),
+            encrypted = cipher.is_some(),
+            "DbActor initialized"
+        );
+        Ok(DbActorState { pool, cipher })
     }
 
+    #[allow(clippy::too_many_lines)]
     async fn handle(
         &self,
         _myself: ActorRef<Self::Msg>,
@@ -117,28 +445,198 @@ impl Actor for DbActor {
     ) -> Result<(), ActorProcessingErr> {
         match message {
             DbActorMessage::Create(create, reply) => {
-                let res = self.create_provider(&state.pool, create).await;
-                let _ = reply.send(res);
+                let res = self
+                    .create_provider(&state.pool, state.cipher.as_ref(), create)
+                    .await;
+                let _ = reply.send(alerted(res));
             }
             DbActorMessage::Patch(patch, reply) => {
-                let res = patch.apply_patch(&state.pool).await;
-                let _ = reply.send(res);
+                let res = match &state.cipher {
+                    Some(cipher) => match cipher.seal_patch(patch) {
+                        Ok(patch) => patch.apply_patch(&state.pool).await,
+                        Err(e) => Err(e),
+                    },
+                    None => patch.apply_patch(&state.pool).await,
+                };
+                let _ = reply.send(alerted(res));
             }
             DbActorMessage::ListActiveGeminiCli(reply) => {
-                let res = self.list_active_geminicli(&state.pool).await;
-                let _ = reply.send(res);
+                let res = self
+                    .list_geminicli(&state.pool, true)
+                    .await
+                    .and_then(|rows| state.open_rows(rows));
+                let _ = reply.send(alerted(res));
             }
             DbActorMessage::ListActiveCodex(reply) => {
-                let res = self.list_active_codex(&state.pool).await;
-                let _ = reply.send(res);
+                let res = self
+                    .list_codex(&state.pool, true)
+                    .await
+                    .and_then(|rows| state.open_rows(rows));
+                let _ = reply.send(alerted(res));
             }
             DbActorMessage::ListActiveAntigravity(reply) => {
-                let res = self.list_active_antigravity(&state.pool).await;
-                let _ = reply.send(res);
+                let res = self
+                    .list_antigravity(&state.pool, true)
+                    .await
+                    .and_then(|rows| state.open_rows(rows));
+                let _ = reply.send(alerted(res));
+            }
+            DbActorMessage::ListActiveClaude(reply) => {
+                let res = self
+                    .list_claude(&state.pool, true)
+                    .await
+                    .and_then(|rows| state.open_rows(rows));
+                let _ = reply.send(alerted(res));
+            }
+            DbActorMessage::ListActiveQwen(reply) => {
+                let res = self
+                    .list_qwen(&state.pool, true)
+                    .await
+                    .and_then(|rows| state.open_rows(rows));
+                let _ = reply.send(alerted(res));
+            }
+            DbActorMessage::ListGeminiCli(reply) => {
+                let res = self
+                    .list_geminicli(&state.pool, false)
+                    .await
+                    .and_then(|rows| state.open_rows(rows));
+                let _ = reply.send(alerted(res));
+            }
+            DbActorMessage::ListCodex(reply) => {
+                let res = self
+                    .list_codex(&state.pool, false)
+                    .await
+                    .and_then(|rows| state.open_rows(rows));
+                let _ = reply.send(alerted(res));
+            }
+            DbActorMessage::ListAntigravity(reply) => {
+                let res = self
+                    .list_antigravity(&state.pool, false)
+                    .await
+                    .and_then(|rows| state.open_rows(rows));
+                let _ = reply.send(alerted(res));
+            }
+            DbActorMessage::ListClaude(reply) => {
+                let res = self
+                    .list_claude(&state.pool, false)
+                    .await
+                    .and_then(|rows| state.open_rows(rows));
+                let _ = reply.send(alerted(res));
+            }
+            DbActorMessage::ListQwen(reply) => {
+                let res = self
+                    .list_qwen(&state.pool, false)
+                    .await
+                    .and_then(|rows| state.open_rows(rows));
+                let _ = reply.send(alerted(res));
+            }
+            DbActorMessage::GetGeminiCliById(id, reply) => {
+                let res = self
+                    .get_geminicli_by_id(&state.pool, id)
+                    .await
+                    .and_then(|row| state.open_row(row));
+                let _ = reply.send(alerted(res));
             }
             DbActorMessage::GetCodexById(id, reply) => {
-                let res = self.get_codex_by_id(&state.pool, id).await;
-                let _ = reply.send(res);
+                let res = self
+                    .get_codex_by_id(&state.pool, id)
+                    .await
+                    .and_then(|row| state.open_row(row));
+                let _ = reply.send(alerted(res));
+            }
+            DbActorMessage::GetAntigravityById(id, reply) => {
+                let res = self
+                    .get_antigravity_by_id(&state.pool, id)
+                    .await
+                    .and_then(|row| state.open_row(row));
+                let _ = reply.send(alerted(res));
+            }
+            DbActorMessage::GetClaudeById(id, reply) => {
+                let res = self
+                    .get_claude_by_id(&state.pool, id)
+                    .await
+                    .and_then(|row| state.open_row(row));
+                let _ = reply.send(alerted(res));
+            }
+            DbActorMessage::GetQwenById(id, reply) => {
+                let res = self
+                    .get_qwen_by_id(&state.pool, id)
+                    .await
+                    .and_then(|row| state.open_row(row));
+                let _ = reply.send(alerted(res));
+            }
+            DbActorMessage::FindByIdentity(identity, reply) => {
+                let res = self.find_by_identity(&state.pool, identity).await;
+                let _ = reply.send(alerted(res));
+            }
+            DbActorMessage::Delete(delete, reply) => {
+                let res = self.delete_provider(&state.pool, delete).await;
+                let _ = reply.send(alerted(res));
+            }
+            DbActorMessage::RecordUsage(record) => {
+                if let Err(e) = alerted(self.insert_usage(&state.pool, &record).await) {
+                    warn!(error = %e, "[DbActor] Failed to record usage");
+                }
+            }
+            DbActorMessage::UsageSummary(query, reply) => {
+                let res = self.usage_summary(&state.pool, query).await;
+                let _ = reply.send(alerted(res));
+            }
+            DbActorMessage::ModelUsageStats(since, reply) => {
+                let res = self.model_usage_stats(&state.pool, since).await;
+                let _ = reply.send(alerted(res));
+            }
+            DbActorMessage::LoadRequestCounters(reply) => {
+                let res = self.load_request_counters(&state.pool).await;
+                let _ = reply.send(alerted(res));
+            }
+            DbActorMessage::SaveRequestCounters(rows, reply) => {
+                let res = self.save_request_counters(&state.pool, &rows).await;
+                let _ = reply.send(alerted(res));
+            }
+            DbActorMessage::LoadModelRegistry(reply) => {
+                let res = self.load_model_registry(&state.pool).await;
+                let _ = reply.send(alerted(res));
+            }
+            DbActorMessage::SaveModelRegistry(rows, reply) => {
+                let res = self.save_model_registry(&state.pool, &rows).await;
+                let _ = reply.send(alerted(res));
+            }
+            DbActorMessage::RecordThoughtSignature(row) => {
+                if let Err(e) = alerted(self.upsert_thought_signature(&state.pool, &row).await) {
+                    warn!(error = %e, "[DbActor] Failed to record thought signature");
+                }
+            }
+            DbActorMessage::LoadThoughtSignatures {
+                provider,
+                since,
+                limit,
+                reply,
+            } => {
+                let res = self
+                    .load_thought_signatures(&state.pool, &provider, since, limit)
+                    .await;
+                let _ = reply.send(alerted(res));
+            }
+            DbActorMessage::DeleteThoughtSignatures { provider, scope } => {
+                if let Err(e) = alerted(
+                    self.delete_thought_signatures(&state.pool, &provider, &scope)
+                        .await,
+                ) {
+                    warn!(error = %e, "[DbActor] Failed to delete thought signatures");
+                }
+            }
+            DbActorMessage::PruneThoughtSignatures {
+                provider,
+                before,
+                keep,
+                versions,
+                reply,
+            } => {
+                let res = self
+                    .prune_thought_signatures(&state.pool, &provider, before, keep, versions)
+                    .await;
+                let _ = reply.send(alerted(res));
             }
         }
         Ok(())
@@ -146,75 +644,97 @@ impl Actor for DbActor {
 }
 
 impl DbActor {
+    #[allow(clippy::too_many_lines)]
     async fn create_provider(
         &self,
-        pool: &SqlitePool,
+        pool: &DbPool,
+        cipher: Option<&TokenCipher>,
         create: ProviderCreate,
     ) -> Result<i64, PolluxError> {
         match create {
             ProviderCreate::GeminiCli(c) => {
                 let now = Utc::now();
-                let id: i64 = sqlx::query_scalar(
-                    r"
-                INSERT INTO gemini_cli (
-                    email, sub, project_id, refresh_token, access_token, expiry, status, created_at, updated_at
-                )
-                VALUES (?, ?, ?, ?, ?, ?, 1, ?, ?)
-                ON CONFLICT(sub, project_id) DO UPDATE SET
-                    email=excluded.email,
-                    refresh_token=excluded.refresh_token,
-                    access_token=excluded.access_token,
-                    expiry=excluded.expiry,
-                    status=1,
-                    updated_at=excluded.updated_at
-                RETURNING id
-                ",
-                )
-                .bind(c.email)
-                .bind(c.sub)
-                .bind(c.project_id)
-                .bind(c.refresh_token)
-                .bind(c.access_token)
-                .bind(c.expiry)
-                .bind(now)
-                .bind(now)
-                .fetch_one(pool)
-                .await?;
+                let refresh_token = seal(cipher, c.refresh_token)?;
+                let access_token = c.access_token.map(|t| seal(cipher, t)).transpose()?;
+                let id: i64 = with_pool!(pool, |p| {
+                    p.insert_id(
+                        sqlx::query(&p.sql(
+                            r"
+                    INSERT INTO gemini_cli (
+                        email, sub, project_id, tier, refresh_token, access_token, expiry, labels, proxy_url, status, created_at, updated_at
+                    )
+                    VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, TRUE, $10, $11)
+                    ON CONFLICT(sub, project_id) DO UPDATE SET
+                        email=excluded.email,
+                        tier=COALESCE(excluded.tier, gemini_cli.tier),
+                        refresh_token=excluded.refresh_token,
+                        access_token=excluded.access_token,
+                        expiry=excluded.expiry,
+                        labels=excluded.labels,
+                        proxy_url=excluded.proxy_url,
+                        status=TRUE,
+                        updated_at=excluded.updated_at
+                    RETURNING id
+                    ",
+                        ))
+                        .bind(c.email)
+                        .bind(c.sub)
+                        .bind(c.project_id)
+                        .bind(c.tier)
+                        .bind(refresh_token)
+                        .bind(access_token)
+                        .bind(c.expiry)
+                        .bind(join_labels(&c.labels))
+                        .bind(c.proxy_url.map(String::from))
+                        .bind(now)
+                        .bind(now),
+                    )
+                    .await
+                })?;
 
                 Ok(id)
             }
 
             ProviderCreate::Codex(c) => {
                 let now = Utc::now();
+                let refresh_token = seal(cipher, c.refresh_token)?;
+                let access_token = seal(cipher, c.access_token)?;
 
-                let id: i64 = sqlx::query_scalar(
-                    r"
-                INSERT INTO codex (
-                    email, sub, account_id, refresh_token, access_token, expiry, chatgpt_plan_type, status, created_at, updated_at
-                )
-                VALUES (?, ?, ?, ?, ?, ?, ?, 1, ?, ?)
-                ON CONFLICT(sub, account_id) DO UPDATE SET
-                    email = COALESCE(excluded.email, email),
-                    refresh_token = excluded.refresh_token,
-                    access_token = excluded.access_token,
-                    expiry = excluded.expiry,
-                    chatgpt_plan_type = COALESCE(excluded.chatgpt_plan_type, chatgpt_plan_type),
-                    status = 1,
-                    updated_at = excluded.updated_at
-                RETURNING id
-                ",
-                )
-                .bind(c.email)
-                .bind(c.sub)
-                .bind(c.account_id)
-                .bind(c.refresh_token)
-                .bind(c.access_token)
-                .bind(c.expiry)
-                .bind(c.chatgpt_plan_type)
-                .bind(now)
-                .bind(now)
-                .fetch_one(pool)
-                .await?;
+                let id: i64 = with_pool!(pool, |p| {
+                    p.insert_id(
+                        sqlx::query(&p.sql(
+                            r"
+                    INSERT INTO codex (
+                        email, sub, account_id, refresh_token, access_token, expiry, chatgpt_plan_type, labels, proxy_url, status, created_at, updated_at
+                    )
+                    VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, TRUE, $10, $11)
+                    ON CONFLICT(sub, account_id) DO UPDATE SET
+                        email = COALESCE(excluded.email, codex.email),
+                        refresh_token = excluded.refresh_token,
+                        access_token = excluded.access_token,
+                        expiry = excluded.expiry,
+                        chatgpt_plan_type = COALESCE(excluded.chatgpt_plan_type, codex.chatgpt_plan_type),
+                        labels = excluded.labels,
+                        proxy_url = excluded.proxy_url,
+                        status = TRUE,
+                        updated_at = excluded.updated_at
+                    RETURNING id
+                    ",
+                        ))
+                        .bind(c.email)
+                        .bind(c.sub)
+                        .bind(c.account_id)
+                        .bind(refresh_token)
+                        .bind(access_token)
+                        .bind(c.expiry)
+                        .bind(c.chatgpt_plan_type)
+                        .bind(join_labels(&c.labels))
+                        .bind(c.proxy_url.map(String::from))
+                        .bind(now)
+                        .bind(now),
+                    )
+                    .await
+                })?;
 
                 Ok(id)
             }
@@ -224,114 +744,866 @@ impl DbActor {
                 let sub = c
                     .sub
                     .unwrap_or_else(|| synthetic_sub_from_refresh_token(&c.refresh_token));
+                let refresh_token = seal(cipher, c.refresh_token)?;
+                let access_token = c.access_token.map(|t| seal(cipher, t)).transpose()?;
 
-                let id: i64 = sqlx::query_scalar(
-                    r"
-                INSERT INTO antigravity (
-                    email, sub, project_id, refresh_token, access_token, expiry, status, created_at, updated_at
-                )
-                VALUES (?, ?, ?, ?, ?, ?, 1, ?, ?)
-                ON CONFLICT(sub, project_id) DO UPDATE SET
-                    email=excluded.email,
-                    refresh_token=excluded.refresh_token,
-                    access_token=excluded.access_token,
-                    expiry=excluded.expiry,
-                    status=1,
-                    updated_at=excluded.updated_at
-                RETURNING id
-                ",
-                )
-                .bind(c.email)
-                .bind(sub)
-                .bind(c.project_id)
-                .bind(c.refresh_token)
-                .bind(c.access_token)
-                .bind(c.expiry)
-                .bind(now)
-                .bind(now)
-                .fetch_one(pool)
-                .await?;
+                let id: i64 = with_pool!(pool, |p| {
+                    p.insert_id(
+                        sqlx::query(&p.sql(
+                            r"
+                    INSERT INTO antigravity (
+                        email, sub, project_id, tier, refresh_token, access_token, expiry, labels, proxy_url, status, created_at, updated_at
+                    )
+                    VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, TRUE, $10, $11)
+                    ON CONFLICT(sub, project_id) DO UPDATE SET
+                        email=excluded.email,
+                        tier=COALESCE(excluded.tier, antigravity.tier),
+                        refresh_token=excluded.refresh_token,
+                        access_token=excluded.access_token,
+                        expiry=excluded.expiry,
+                        labels=excluded.labels,
+                        proxy_url=excluded.proxy_url,
+                        status=TRUE,
+                        updated_at=excluded.updated_at
+                    RETURNING id
+                    ",
+                        ))
+                        .bind(c.email)
+                        .bind(sub)
+                        .bind(c.project_id)
+                        .bind(c.tier)
+                        .bind(refresh_token)
+                        .bind(access_token)
+                        .bind(c.expiry)
+                        .bind(join_labels(&c.labels))
+                        .bind(c.proxy_url.map(String::from))
+                        .bind(now)
+                        .bind(now),
+                    )
+                    .await
+                })?;
+
+                Ok(id)
+            }
+
+            ProviderCreate::Claude(c) => {
+                let now = Utc::now();
+                let refresh_token = seal(cipher, c.refresh_token)?;
+                let access_token = seal(cipher, c.access_token)?;
+
+                let id: i64 = with_pool!(pool, |p| {
+                    p.insert_id(
+                        sqlx::query(&p.sql(
+                            r"
+                    INSERT INTO claude (
+                        email, account_uuid, organization_uuid, refresh_token, access_token, expiry, labels, proxy_url, status, created_at, updated_at
+                    )
+                    VALUES ($1, $2, $3, $4, $5, $6, $7, $8, TRUE, $9, $10)
+                    ON CONFLICT(account_uuid, organization_uuid) DO UPDATE SET
+                        email = COALESCE(excluded.email, claude.email),
+                        refresh_token = excluded.refresh_token,
+                        access_token = excluded.access_token,
+                        expiry = excluded.expiry,
+                        labels = excluded.labels,
+                        proxy_url = excluded.proxy_url,
+                        status = TRUE,
+                        updated_at = excluded.updated_at
+                    RETURNING id
+                    ",
+                        ))
+                        .bind(c.email)
+                        .bind(c.account_uuid)
+                        .bind(c.organization_uuid)
+                        .bind(refresh_token)
+                        .bind(access_token)
+                        .bind(c.expiry)
+                        .bind(join_labels(&c.labels))
+                        .bind(c.proxy_url.map(String::from))
+                        .bind(now)
+                        .bind(now),
+                    )
+                    .await
+                })?;
+
+                Ok(id)
+            }
+
+            ProviderCreate::Qwen(c) => {
+                let now = Utc::now();
+                let sub = synthetic_sub_from_refresh_token(&c.refresh_token);
+                let refresh_token = seal(cipher, c.refresh_token)?;
+                let access_token = seal(cipher, c.access_token)?;
+
+                let id: i64 = with_pool!(pool, |p| {
+                    p.insert_id(
+                        sqlx::query(&p.sql(
+                            r"
+                    INSERT INTO qwen (
+                        sub, refresh_token, access_token, resource_url, expiry, labels, proxy_url, status, created_at, updated_at
+                    )
+                    VALUES ($1, $2, $3, $4, $5, $6, $7, TRUE, $8, $9)
+                    ON CONFLICT(sub) DO UPDATE SET
+                        refresh_token = excluded.refresh_token,
+                        access_token = excluded.access_token,
+                        resource_url = COALESCE(excluded.resource_url, qwen.resource_url),
+                        expiry = excluded.expiry,
+                        labels = excluded.labels,
+                        proxy_url = excluded.proxy_url,
+                        status = TRUE,
+                        updated_at = excluded.updated_at
+                    RETURNING id
+                    ",
+                        ))
+                        .bind(sub)
+                        .bind(refresh_token)
+                        .bind(access_token)
+                        .bind(c.resource_url)
+                        .bind(c.expiry)
+                        .bind(join_labels(&c.labels))
+                        .bind(c.proxy_url.map(String::from))
+                        .bind(now)
+                        .bind(now),
+                    )
+                    .await
+                })?;
 
                 Ok(id)
             }
         }
     }
 
-    async fn list_active_geminicli(
+    /// Re-write every row that still has a plaintext token, sealed.
+    async fn seal_plaintext_tokens(
         &self,
-        pool: &SqlitePool,
+        pool: &DbPool,
+        cipher: &TokenCipher,
+    ) -> Result<usize, PolluxError> {
+        let mut patches = Vec::new();
+        for row in self.list_geminicli(pool, false).await? {
+            if row.has_plaintext_tokens() {
+                patches.push(ProviderPatch::GeminiCli {
+                    id: row.id.cast_unsigned(),
+                    patch: GeminiCliPatch {
+                        refresh_token: Some(row.refresh_token),
+                        access_token: row.access_token,
+                        ..GeminiCliPatch::default()
+                    },
+                });
+            }
+        }
+        for row in self.list_codex(pool, false).await? {
+            if row.has_plaintext_tokens() {
+                patches.push(ProviderPatch::Codex {
+                    id: row.id.cast_unsigned(),
+                    patch: CodexPatch {
+                        refresh_token: Some(row.refresh_token),
+                        access_token: Some(row.access_token),
+                        ..CodexPatch::default()
+                    },
+                });
+            }
+        }
+        for row in self.list_antigravity(pool, false).await? {
+            if row.has_plaintext_tokens() {
+                patches.push(ProviderPatch::Antigravity {
+                    id: row.id.cast_unsigned(),
+                    patch: AntigravityPatch {
+                        refresh_token: Some(row.refresh_token),
+                        access_token: row.access_token,
+                        ..AntigravityPatch::default()
+                    },
+                });
+            }
+        }
+        for row in self.list_claude(pool, false).await? {
+            if row.has_plaintext_tokens() {
+                patches.push(ProviderPatch::Claude {
+                    id: row.id.cast_unsigned(),
+                    patch: ClaudePatch {
+                        refresh_token: Some(row.refresh_token),
+                        access_token: Some(row.access_token),
+                        ..ClaudePatch::default()
+                    },
+                });
+            }
+        }
+        for row in self.list_qwen(pool, false).await? {
+            if row.has_plaintext_tokens() {
+                patches.push(ProviderPatch::Qwen {
+                    id: row.id.cast_unsigned(),
+                    patch: QwenPatch {
+                        refresh_token: Some(row.refresh_token),
+                        access_token: Some(row.access_token),
+                        ..QwenPatch::default()
+                    },
+                });
+            }
+        }
+
+        let count = patches.len();
+        for patch in patches {
+            cipher.seal_patch(patch)?.apply_patch(pool).await?;
+        }
+        Ok(count)
+    }
+
+    async fn list_geminicli(
+        &self,
+        pool: &DbPool,
+        active_only: bool,
     ) -> Result<Vec<DbGeminiCliResource>, PolluxError> {
-        let rows = sqlx::query_as::<_, DbGeminiCliResource>(
-            r"
-        SELECT id, email, sub, project_id, refresh_token, access_token, expiry, status, created_at, updated_at
-        FROM gemini_cli
-        WHERE status = 1
-        ORDER BY id
-        ",
-        )
-        .fetch_all(pool)
-        .await?;
+        let rows = with_pool!(pool, |p| {
+            sqlx::query_as::<_, DbGeminiCliResource>(
+                &p.sql(r"
+            SELECT id, email, sub, project_id, tier, refresh_token, access_token, expiry, labels, proxy_url, status, created_at, updated_at
+            FROM gemini_cli
+            WHERE ($1 = FALSE OR status = TRUE)
+            ORDER BY id
+            "),
+            )
+            .bind(active_only)
+            .fetch_all(p)
+            .await
+        })?;
 
         Ok(rows)
     }
 
-    async fn list_active_codex(
+    async fn list_codex(
         &self,
-        pool: &SqlitePool,
+        pool: &DbPool,
+        active_only: bool,
     ) -> Result<Vec<DbCodexResource>, PolluxError> {
-        let rows = sqlx::query_as::<_, DbCodexResource>(
-            r"
-        SELECT id, email, sub, account_id, refresh_token, access_token, expiry, chatgpt_plan_type, status, created_at, updated_at
-        FROM codex
-        WHERE status = 1
-        ORDER BY id
-        ",
-        )
-        .fetch_all(pool)
-        .await?;
+        let rows = with_pool!(pool, |p| {
+            sqlx::query_as::<_, DbCodexResource>(
+                &p.sql(r"
+            SELECT id, email, sub, account_id, refresh_token, access_token, expiry, chatgpt_plan_type, labels, proxy_url, status, created_at, updated_at
+            FROM codex
+            WHERE ($1 = FALSE OR status = TRUE)
+            ORDER BY id
+            "),
+            )
+            .bind(active_only)
+            .fetch_all(p)
+            .await
+        })?;
 
         Ok(rows)
     }
 
-    async fn list_active_antigravity(
+    async fn list_antigravity(
         &self,
-        pool: &SqlitePool,
+        pool: &DbPool,
+        active_only: bool,
     ) -> Result<Vec<DbAntigravityResource>, PolluxError> {
-        let rows = sqlx::query_as::<_, DbAntigravityResource>(
-            r"
-        SELECT id, email, sub, project_id, refresh_token, access_token, expiry, status, created_at, updated_at
-        FROM antigravity
-        WHERE status = 1
-        ORDER BY id
-        ",
-        )
-        .fetch_all(pool)
-        .await?;
+        let rows = with_pool!(pool, |p| {
+            sqlx::query_as::<_, DbAntigravityResource>(
+                &p.sql(r"
+            SELECT id, email, sub, project_id, tier, refresh_token, access_token, expiry, labels, proxy_url, status, created_at, updated_at
+            FROM antigravity
+            WHERE ($1 = FALSE OR status = TRUE)
+            ORDER BY id
+            "),
+            )
+            .bind(active_only)
+            .fetch_all(p)
+            .await
+        })?;
+
+        Ok(rows)
+    }
+
+    async fn list_claude(
+        &self,
+        pool: &DbPool,
+        active_only: bool,
+    ) -> Result<Vec<DbClaudeResource>, PolluxError> {
+        let rows = with_pool!(pool, |p| {
+            sqlx::query_as::<_, DbClaudeResource>(
+                &p.sql(r"
+            SELECT id, email, account_uuid, organization_uuid, refresh_token, access_token, expiry, labels, proxy_url, status, created_at, updated_at
+            FROM claude
+            WHERE ($1 = FALSE OR status = TRUE)
+            ORDER BY id
+            "),
+            )
+            .bind(active_only)
+            .fetch_all(p)
+            .await
+        })?;
+
+        Ok(rows)
+    }
+
+    async fn list_qwen(
+        &self,
+        pool: &DbPool,
+        active_only: bool,
+    ) -> Result<Vec<DbQwenResource>, PolluxError> {
+        let rows = with_pool!(pool, |p| {
+            sqlx::query_as::<_, DbQwenResource>(
+                &p.sql(r"
+            SELECT id, sub, refresh_token, access_token, resource_url, expiry, labels, proxy_url, status, created_at, updated_at
+            FROM qwen
+            WHERE ($1 = FALSE OR status = TRUE)
+            ORDER BY id
+            "),
+            )
+            .bind(active_only)
+            .fetch_all(p)
+            .await
+        })?;
 
         Ok(rows)
     }
 
+    async fn get_geminicli_by_id(
+        &self,
+        pool: &DbPool,
+        id: i64,
+    ) -> Result<DbGeminiCliResource, PolluxError> {
+        let row = with_pool!(pool, |p| {
+            sqlx::query_as::<_, DbGeminiCliResource>(
+                &p.sql(r"
+            SELECT id, email, sub, project_id, tier, refresh_token, access_token, expiry, labels, proxy_url, status, created_at, updated_at
+            FROM gemini_cli
+            WHERE id = $1
+            "),
+            )
+            .bind(id)
+            .fetch_one(p)
+            .await
+        })?;
+
+        Ok(row)
+    }
+
     async fn get_codex_by_id(
         &self,
-        pool: &SqlitePool,
+        pool: &DbPool,
         id: i64,
     ) -> Result<DbCodexResource, PolluxError> {
-        let row = sqlx::query_as::<_, DbCodexResource>(
-            r"
-        SELECT id, email, sub, account_id, refresh_token, access_token, expiry, chatgpt_plan_type, status, created_at, updated_at
-        FROM codex
-        WHERE id = ?
-        ",
-        )
-        .bind(id)
-        .fetch_one(pool)
-        .await?;
+        let row = with_pool!(pool, |p| {
+            sqlx::query_as::<_, DbCodexResource>(
+                &p.sql(r"
+            SELECT id, email, sub, account_id, refresh_token, access_token, expiry, chatgpt_plan_type, labels, proxy_url, status, created_at, updated_at
+            FROM codex
+            WHERE id = $1
+            "),
+            )
+            .bind(id)
+            .fetch_one(p)
+            .await
+        })?;
+
+        Ok(row)
+    }
+
+    async fn get_antigravity_by_id(
+        &self,
+        pool: &DbPool,
+        id: i64,
+    ) -> Result<DbAntigravityResource, PolluxError> {
+        let row = with_pool!(pool, |p| {
+            sqlx::query_as::<_, DbAntigravityResource>(
+                &p.sql(r"
+            SELECT id, email, sub, project_id, tier, refresh_token, access_token, expiry, labels, proxy_url, status, created_at, updated_at
+            FROM antigravity
+            WHERE id = $1
+            "),
+            )
+            .bind(id)
+            .fetch_one(p)
+            .await
+        })?;
+
+        Ok(row)
+    }
+
+    async fn get_claude_by_id(
+        &self,
+        pool: &DbPool,
+        id: i64,
+    ) -> Result<DbClaudeResource, PolluxError> {
+        let row = with_pool!(pool, |p| {
+            sqlx::query_as::<_, DbClaudeResource>(
+                &p.sql(r"
+            SELECT id, email, account_uuid, organization_uuid, refresh_token, access_token, expiry, labels, proxy_url, status, created_at, updated_at
+            FROM claude
+            WHERE id = $1
+            "),
+            )
+            .bind(id)
+            .fetch_one(p)
+            .await
+        })?;
+
+        Ok(row)
+    }
+
+    async fn get_qwen_by_id(&self, pool: &DbPool, id: i64) -> Result<DbQwenResource, PolluxError> {
+        let row = with_pool!(pool, |p| {
+            sqlx::query_as::<_, DbQwenResource>(
+                &p.sql(r"
+            SELECT id, sub, refresh_token, access_token, resource_url, expiry, labels, proxy_url, status, created_at, updated_at
+            FROM qwen
+            WHERE id = $1
+            "),
+            )
+            .bind(id)
+            .fetch_one(p)
+            .await
+        })?;
 
         Ok(row)
     }
+
+    async fn find_by_identity(
+        &self,
+        pool: &DbPool,
+        identity: ProviderIdentity,
+    ) -> Result<Option<i64>, PolluxError> {
+        let id = match identity {
+            ProviderIdentity::GeminiCli { sub } => with_pool!(pool, |p| {
+                sqlx::query_scalar(&p.sql(
+                    r"
+                SELECT id FROM gemini_cli
+                WHERE sub = $1
+                ORDER BY status DESC, id
+                LIMIT 1
+                ",
+                ))
+                .bind(sub)
+                .fetch_optional(p)
+                .await
+            })?,
+            ProviderIdentity::Codex { sub, account_id } => with_pool!(pool, |p| {
+                sqlx::query_scalar(&p.sql(
+                    r"
+                SELECT id FROM codex
+                WHERE sub = $1 AND account_id = $2
+                ORDER BY status DESC, id
+                LIMIT 1
+                ",
+                ))
+                .bind(sub)
+                .bind(account_id)
+                .fetch_optional(p)
+                .await
+            })?,
+            ProviderIdentity::Antigravity { sub } => with_pool!(pool, |p| {
+                sqlx::query_scalar(&p.sql(
+                    r"
+                SELECT id FROM antigravity
+                WHERE sub = $1
+                ORDER BY status DESC, id
+                LIMIT 1
+                ",
+                ))
+                .bind(sub)
+                .fetch_optional(p)
+                .await
+            })?,
+            ProviderIdentity::Claude {
+                account_uuid,
+                organization_uuid,
+            } => with_pool!(pool, |p| {
+                sqlx::query_scalar(&p.sql(
+                    r"
+                SELECT id FROM claude
+                WHERE account_uuid = $1 AND organization_uuid = $2
+                ORDER BY status DESC, id
+                LIMIT 1
+                ",
+                ))
+                .bind(account_uuid)
+                .bind(organization_uuid)
+                .fetch_optional(p)
+                .await
+            })?,
+            ProviderIdentity::Qwen { sub } => with_pool!(pool, |p| {
+                sqlx::query_scalar(&p.sql(
+                    r"
+                SELECT id FROM qwen
+                WHERE sub = $1
+                ORDER BY status DESC, id
+                LIMIT 1
+                ",
+                ))
+                .bind(sub)
+                .fetch_optional(p)
+                .await
+            })?,
+        };
+        Ok(id)
+    }
+
+    async fn delete_provider(
+        &self,
+        pool: &DbPool,
+        delete: ProviderDelete,
+    ) -> Result<(), PolluxError> {
+        let (table, id) = match delete {
+            ProviderDelete::GeminiCli(id) => ("gemini_cli", id),
+            ProviderDelete::Codex(id) => ("codex", id),
+            ProviderDelete::Antigravity(id) => ("antigravity", id),
+            ProviderDelete::Claude(id) => ("claude", id),
+            ProviderDelete::Qwen(id) => ("qwen", id),
+        };
+        let sql = format!("DELETE FROM {table} WHERE id = $1");
+        let affected = with_pool!(pool, |p| {
+            sqlx::query(&p.sql(&sql))
+                .bind(id)
+                .execute(p)
+                .await
+                .map(|r| r.rows_affected())
+        })?;
+
+        if affected == 0 {
+            return Err(PolluxError::NotFound(format!("{table} record id={id}")));
+        }
+        Ok(())
+    }
+
+    async fn insert_usage(&self, pool: &DbPool, record: &UsageRecord) -> Result<(), PolluxError> {
+        with_pool!(pool, |p| {
+            sqlx::query(&p.sql(
+                r#"
+                INSERT INTO "usage" (
+                    created_at, provider, model, credential_id,
+                    prompt_tokens, output_tokens, latency_ms, status
+                )
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+                "#,
+            ))
+            .bind(record.created_at.timestamp())
+            .bind(&record.provider)
+            .bind(&record.model)
+            .bind(record.credential_id)
+            .bind(record.prompt_tokens)
+            .bind(record.output_tokens)
+            .bind(record.latency_ms)
+            .bind(record.status)
+            .execute(p)
+            .await
+            .map(|_| ())
+        })?;
+        Ok(())
+    }
+
+    async fn usage_summary(
+        &self,
+        pool: &DbPool,
+        query: UsageQuery,
+    ) -> Result<Vec<UsageAggregate>, PolluxError> {
+        let since = query.since.map_or(0, |t| t.timestamp());
+        let rows = with_pool!(pool, |p| {
+            sqlx::query_as::<_, UsageAggregate>(&p.sql(
+                r#"
+                SELECT
+                    provider,
+                    model,
+                    credential_id,
+                    COUNT(*) AS requests,
+                    CAST(SUM(CASE WHEN status >= 400 THEN 1 ELSE 0 END) AS BIGINT) AS errors,
+                    CAST(SUM(prompt_tokens) AS BIGINT) AS prompt_tokens,
+                    CAST(SUM(output_tokens) AS BIGINT) AS output_tokens,
+                    CAST(AVG(latency_ms) AS BIGINT) AS avg_latency_ms
+                FROM "usage"
+                WHERE created_at >= $1 AND ($2 IS NULL OR provider = $3)
+                GROUP BY provider, model, credential_id
+                ORDER BY SUM(output_tokens) DESC, COUNT(*) DESC
+                "#,
+            ))
+            .bind(since)
+            .bind(query.provider.as_deref())
+            .bind(query.provider.as_deref())
+            .fetch_all(p)
+            .await
+        })?;
+        Ok(rows)
+    }
+
+    async fn model_usage_stats(
+        &self,
+        pool: &DbPool,
+        since: DateTime<Utc>,
+    ) -> Result<Vec<ModelUsageStats>, PolluxError> {
+        let rows = with_pool!(pool, |p| {
+            sqlx::query_as::<_, ModelUsageStats>(&p.sql(
+                r#"
+                SELECT
+                    provider,
+                    model,
+                    COUNT(*) AS requests,
+                    CAST(SUM(CASE WHEN status = 503 THEN 1 ELSE 0 END) AS BIGINT) AS unavailable,
+                    CAST(SUM(CASE WHEN status = 429 THEN 1 ELSE 0 END) AS BIGINT) AS rate_limited,
+                    CAST(SUM(
+                        CASE WHEN status >= 400 AND status NOT IN (429, 503) THEN 1 ELSE 0 END
+                    ) AS BIGINT) AS other_errors
+                FROM "usage"
+                WHERE created_at >= $1
+                GROUP BY provider, model
+                ORDER BY provider, model
+                "#,
+            ))
+            .bind(since.timestamp())
+            .fetch_all(p)
+            .await
+        })?;
+        Ok(rows)
+    }
+
+    async fn load_request_counters(
+        &self,
+        pool: &DbPool,
+    ) -> Result<Vec<RequestCounterRow>, PolluxError> {
+        let rows = with_pool!(pool, |p| {
+            sqlx::query_as::<_, RequestCounterRow>(&p.sql(
+                r"
+                SELECT provider, model, requests, errors
+                FROM request_counters
+                ORDER BY provider, model
+                ",
+            ))
+            .fetch_all(p)
+            .await
+        })?;
+        Ok(rows)
+    }
+
+    async fn save_request_counters(
+        &self,
+        pool: &DbPool,
+        rows: &[RequestCounterRow],
+    ) -> Result<(), PolluxError> {
+        let now = Utc::now().timestamp();
+        for row in rows {
+            with_pool!(pool, |p| {
+                sqlx::query(&p.sql(
+                    r"
+                    INSERT INTO request_counters (provider, model, requests, errors, updated_at)
+                    VALUES ($1, $2, $3, $4, $5)
+                    ON CONFLICT (provider, model) DO UPDATE SET
+                        requests = excluded.requests,
+                        errors = excluded.errors,
+                        updated_at = excluded.updated_at
+                    ",
+                ))
+                .bind(&row.provider)
+                .bind(&row.model)
+                .bind(row.requests)
+                .bind(row.errors)
+                .bind(now)
+                .execute(p)
+                .await
+                .map(|_| ())
+            })?;
+        }
+        Ok(())
+    }
+
+    async fn load_model_registry(
+        &self,
+        pool: &DbPool,
+    ) -> Result<Vec<ModelRegistryRow>, PolluxError> {
+        let rows = with_pool!(pool, |p| {
+            sqlx::query_as::<_, ModelRegistryRow>(&p.sql(
+                r"
+                SELECT provider, model, model_id, idx
+                FROM model_registry
+                ORDER BY provider, idx
+                ",
+            ))
+            .fetch_all(p)
+            .await
+        })?;
+        Ok(rows)
+    }
+
+    async fn save_model_registry(
+        &self,
+        pool: &DbPool,
+        rows: &[ModelRegistryRow],
+    ) -> Result<(), PolluxError> {
+        with_pool!(pool, |p| {
+            sqlx::query(&p.sql("DELETE FROM model_registry"))
+                .execute(p)
+                .await
+                .map(|_| ())
+        })?;
+        for row in rows {
+            with_pool!(pool, |p| {
+                sqlx::query(
+                    &p.sql("INSERT INTO model_registry (provider, model, model_id, idx) VALUES ($1, $2, $3, $4)"),
+                )
+                .bind(&row.provider)
+                .bind(&row.model)
+                .bind(row.model_id)
+                .bind(row.idx)
+                    .execute(p)
+                    .await
+                    .map(|_| ())
+            })?;
+        }
+        Ok(())
+    }
+
+    async fn upsert_thought_signature(
+        &self,
+        pool: &DbPool,
+        row: &ThoughtSignatureRow,
+    ) -> Result<(), PolluxError> {
+        with_pool!(pool, |p| {
+            sqlx::query(&p.sql(
+                r"
+                INSERT INTO thought_signatures
+                    (provider, cache_key, key_version, signature, model, created_at)
+                VALUES ($1, $2, $3, $4, $5, $6)
+                ON CONFLICT (provider, cache_key) DO UPDATE SET
+                    key_version = excluded.key_version,
+                    signature = excluded.signature,
+                    model = excluded.model,
+                    created_at = excluded.created_at
+                ",
+            ))
+            .bind(&row.provider)
+            .bind(row.cache_key)
+            .bind(row.key_version)
+            .bind(&row.signature)
+            .bind(&row.model)
+            .bind(row.created_at)
+            .execute(p)
+            .await
+            .map(|_| ())
+        })?;
+        Ok(())
+    }
+
+    async fn load_thought_signatures(
+        &self,
+        pool: &DbPool,
+        provider: &str,
+        since: i64,
+        limit: i64,
+    ) -> Result<Vec<ThoughtSignatureRow>, PolluxError> {
+        let rows = with_pool!(pool, |p| {
+            sqlx::query_as::<_, ThoughtSignatureRow>(&p.sql(
+                r"
+                SELECT provider, cache_key, key_version, signature, model, created_at
+                FROM thought_signatures
+                WHERE provider = $1 AND created_at >= $2
+                ORDER BY created_at DESC
+                LIMIT $3
+                ",
+            ))
+            .bind(provider)
+            .bind(since)
+            .bind(limit)
+            .fetch_all(p)
+            .await
+        })?;
+        Ok(rows)
+    }
+
+    async fn delete_thought_signatures(
+        &self,
+        pool: &DbPool,
+        provider: &str,
+        scope: &Invalidation,
+    ) -> Result<u64, PolluxError> {
+        let removed = with_pool!(pool, |p| match scope {
+            Invalidation::Key(key) => {
+                sqlx::query(&p.sql(
+                    r"
+                    DELETE FROM thought_signatures
+                    WHERE provider = $1 AND cache_key = $2 AND key_version = $3
+                    ",
+                ))
+                .bind(provider)
+                .bind(key.hash.cast_signed())
+                .bind(i16::from(key.version.byte()))
+                .execute(p)
+                .await
+            }
+            Invalidation::Model(model) => {
+                sqlx::query(
+                    &p.sql("DELETE FROM thought_signatures WHERE provider = $1 AND model = $2"),
+                )
+                .bind(provider)
+                .bind(model)
+                .execute(p)
+                .await
+            }
+            Invalidation::All => {
+                sqlx::query(&p.sql("DELETE FROM thought_signatures WHERE provider = $1"))
+                    .bind(provider)
+                    .execute(p)
+                    .await
+            }
+        }
+        .map(|r| r.rows_affected()))?;
+        Ok(removed)
+    }
+
+    async fn prune_thought_signatures(
+        &self,
+        pool: &DbPool,
+        provider: &str,
+        before: i64,
+        keep: i64,
+        versions: [i16; 2],
+    ) -> Result<u64, PolluxError> {
+        let stale = with_pool!(pool, |p| {
+            sqlx::query(&p.sql(
+                r"
+                DELETE FROM thought_signatures
+                WHERE provider = $1 AND key_version NOT IN ($2, $3)
+                ",
+            ))
+            .bind(provider)
+            .bind(versions[0])
+            .bind(versions[1])
+            .execute(p)
+            .await
+            .map(|r| r.rows_affected())
+        })?;
+        let expired = with_pool!(pool, |p| {
+            sqlx::query(
+                &p.sql("DELETE FROM thought_signatures WHERE provider = $1 AND created_at < $2"),
+            )
+            .bind(provider)
+            .bind(before)
+            .execute(p)
+            .await
+            .map(|r| r.rows_affected())
+        })?;
+        let overflow = with_pool!(pool, |p| {
+            sqlx::query(&p.sql(
+                r"
+                DELETE FROM thought_signatures
+                WHERE provider = $1 AND cache_key NOT IN (
+                    SELECT cache_key FROM (
+                        SELECT cache_key FROM thought_signatures
+                        WHERE provider = $2
+                        ORDER BY created_at DESC
+                        LIMIT $3
+                    ) AS kept
+                )
+                ",
+            ))
+            .bind(provider)
+            .bind(provider)
+            .bind(keep)
+            .execute(p)
+            .await
+            .map(|r| r.rows_affected())
+        })?;
+        Ok(stale + expired + overflow)
+    }
 }
 
-fn synthetic_sub_from_refresh_token(refresh_token: &str) -> String {
+pub(crate) fn synthetic_sub_from_refresh_token(refresh_token: &str) -> String {
     use std::collections::hash_map::DefaultHasher;
     use std::hash::{Hash, Hasher};
 
@@ -340,26 +1612,31 @@ fn synthetic_sub_from_refresh_token(refresh_token: &str) -> String {
     format!("rt_hash:{:016x}", h.finish())
 }
 
+/// Saved model registry and request counters, read straight from `pool`
+/// without starting the actor.
+pub(crate) async fn load_model_state(
+    pool: &DbPool,
+) -> Result<(Vec<ModelRegistryRow>, Vec<RequestCounterRow>), PolluxError> {
+    Ok((
+        DbActor.load_model_registry(pool).await?,
+        DbActor.load_request_counters(pool).await?,
+    ))
+}
+
 /// Spawn the database actor and return a cloneable handle.
 pub async fn spawn(database_url: &str) -> DbActorHandle {
+    spawn_with_cipher(database_url, None).await
+}
+
+/// Like [`spawn`], encrypting credential tokens at rest when `cipher` is set.
+pub async fn spawn_with_cipher(database_url: &str, cipher: Option<TokenCipher>) -> DbActorHandle {
     let (actor, _jh) = ractor::Actor::spawn(
         Some("DbActor".to_string()),
         DbActor,
-        database_url.to_string(),
+        (database_url.to_string(), cipher),
     )
     .await
     .expect("failed to spawn DbActor");
 
     DbActorHandle { actor }
 }
-
-async fn apply_schema(pool: &SqlitePool) -> Result<(), PolluxError> {
-    for stmt in SQLITE_INIT.split(';') {
-        let s = stmt.trim();
-        if s.is_empty() {
-            continue;
-        }
-        sqlx::query(s).execute(pool).await?;
-    }
-    Ok(())
-}
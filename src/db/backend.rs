@@ -0,0 +1,167 @@
+//! Database backend selection.
+//!
+//! The backend is picked at runtime from the `database_url` scheme:
+//! - `postgres://` / `postgresql://` => `PostgreSQL` (shared store for multi-instance deployments)
+//! - `mysql://` / `mariadb://` => `MySQL`/`MariaDB` (needs the `mysql` cargo feature)
+//! - anything else => `SQLite`
+//!
+//! Queries are written once in the `SQLite`/`PostgreSQL` dialect, passed
+//! through [`SqlDialect::sql`](crate::db::traits::SqlDialect::sql) and dispatched
+//! to the concrete pool through [`with_pool!`](crate::db::backend::with_pool).
+
+use crate::db::migrations;
+use crate::error::PolluxError;
+use sqlx::SqlitePool;
+#[cfg(feature = "mysql")]
+use sqlx::mysql::{MySqlPool, MySqlPoolOptions};
+use sqlx::postgres::{PgPool, PgPoolOptions};
+use sqlx::sqlite::{SqliteConnectOptions, SqliteJournalMode, SqlitePoolOptions, SqliteSynchronous};
+use std::{str::FromStr, time::Duration};
+
+/// Which SQL dialect a `database_url` points at.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DbBackendKind {
+    Sqlite,
+    Postgres,
+    MySql,
+}
+
+impl DbBackendKind {
+    #[must_use]
+    pub fn from_url(database_url: &str) -> Self {
+        let scheme = database_url
+            .split_once(':')
+            .map_or("", |(scheme, _)| scheme)
+            .to_ascii_lowercase();
+        match scheme.as_str() {
+            "postgres" | "postgresql" => Self::Postgres,
+            "mysql" | "mariadb" => Self::MySql,
+            _ => Self::Sqlite,
+        }
+    }
+
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Sqlite => "sqlite",
+            Self::Postgres => "postgres",
+            Self::MySql => "mysql",
+        }
+    }
+}
+
+/// Connection pool for the selected backend.
+#[derive(Debug, Clone)]
+pub enum DbPool {
+    Sqlite(SqlitePool),
+    Postgres(PgPool),
+    #[cfg(feature = "mysql")]
+    MySql(MySqlPool),
+}
+
+/// Run the same expression against whichever concrete pool is active.
+///
+/// Each arm is type-checked separately, so the body can use the usual
+/// `sqlx::query(...).bind(...).fetch_*(pool)` chain unchanged.
+macro_rules! with_pool {
+    ($pool:expr, |$p:ident| $body:expr) => {
+        match $pool {
+            $crate::db::backend::DbPool::Sqlite($p) => $body,
+            $crate::db::backend::DbPool::Postgres($p) => $body,
+            #[cfg(feature = "mysql")]
+            $crate::db::backend::DbPool::MySql($p) => $body,
+        }
+    };
+}
+pub(crate) use with_pool;
+
+impl DbPool {
+    /// Connect to `database_url` and migrate the schema to the latest version.
+    pub async fn connect(database_url: &str) -> Result<Self, PolluxError> {
+        let pool = Self::open_with(database_url, true).await?;
+        migrations::run(&pool).await?;
+        Ok(pool)
+    }
+
+    /// Connect to an existing database without creating or migrating it.
+    pub async fn open(database_url: &str) -> Result<Self, PolluxError> {
+        Self::open_with(database_url, false).await
+    }
+
+    async fn open_with(database_url: &str, create: bool) -> Result<Self, PolluxError> {
+        let pool = match DbBackendKind::from_url(database_url) {
+            DbBackendKind::Sqlite => {
+                let connect_opts = SqliteConnectOptions::from_str(database_url)?
+                    .create_if_missing(create)
+                    .busy_timeout(Duration::from_secs(5))
+                    .journal_mode(SqliteJournalMode::Wal)
+                    .synchronous(SqliteSynchronous::Normal);
+
+                Self::Sqlite(SqlitePoolOptions::new().connect_with(connect_opts).await?)
+            }
+            DbBackendKind::Postgres => Self::Postgres(
+                PgPoolOptions::new()
+                    .acquire_timeout(Duration::from_secs(5))
+                    .connect(database_url)
+                    .await?,
+            ),
+            #[cfg(feature = "mysql")]
+            DbBackendKind::MySql => Self::MySql(
+                MySqlPoolOptions::new()
+                    .acquire_timeout(Duration::from_secs(5))
+                    .connect(&database_url.replacen("mariadb:", "mysql:", 1))
+                    .await?,
+            ),
+            #[cfg(not(feature = "mysql"))]
+            DbBackendKind::MySql => {
+                return Err(PolluxError::UnexpectedError(
+                    "database_url is MySQL, but this build lacks the `mysql` feature".to_string(),
+                ));
+            }
+        };
+        Ok(pool)
+    }
+
+    #[must_use]
+    pub fn kind(&self) -> DbBackendKind {
+        match self {
+            Self::Sqlite(_) => DbBackendKind::Sqlite,
+            Self::Postgres(_) => DbBackendKind::Postgres,
+            #[cfg(feature = "mysql")]
+            Self::MySql(_) => DbBackendKind::MySql,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DbBackendKind;
+
+    #[test]
+    fn backend_kind_follows_url_scheme() {
+        assert_eq!(
+            DbBackendKind::from_url("postgres://u:p@db/pollux"),
+            DbBackendKind::Postgres
+        );
+        assert_eq!(
+            DbBackendKind::from_url("PostgreSQL://db/pollux"),
+            DbBackendKind::Postgres
+        );
+        assert_eq!(
+            DbBackendKind::from_url("sqlite://data.db"),
+            DbBackendKind::Sqlite
+        );
+        assert_eq!(
+            DbBackendKind::from_url("mysql://u:p@db/pollux"),
+            DbBackendKind::MySql
+        );
+        assert_eq!(
+            DbBackendKind::from_url("mariadb://db/pollux"),
+            DbBackendKind::MySql
+        );
+        assert_eq!(
+            DbBackendKind::from_url("sqlite::memory:"),
+            DbBackendKind::Sqlite
+        );
+    }
+}
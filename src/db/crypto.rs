@@ -0,0 +1,222 @@
+//! At-rest encryption of credential tokens (`basic.db_encryption_key`).
+//!
+//! `refresh_token` and `access_token` values are sealed with AES-256-GCM
+//! before they are bound into SQL and opened again when rows are read.
+//! Sealed values carry a `enc:v1:` prefix; anything without it is treated as
+//! a plaintext row written before encryption was turned on, returned as-is
+//! and re-written sealed by [`DbActor`](super::actor) at startup.
+
+use crate::db::models::{
+    DbAntigravityResource, DbClaudeResource, DbCodexResource, DbGeminiCliResource, DbQwenResource,
+};
+use crate::error::PolluxError;
+use crate::patches::ProviderPatch;
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use base64::Engine as _;
+use base64::engine::general_purpose::STANDARD;
+use sha2::{Digest, Sha256};
+use std::fmt;
+
+const SEALED_PREFIX: &str = "enc:v1:";
+const NONCE_LEN: usize = 12;
+
+#[derive(Clone)]
+pub struct TokenCipher {
+    cipher: Aes256Gcm,
+}
+
+impl fmt::Debug for TokenCipher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("TokenCipher(..)")
+    }
+}
+
+impl TokenCipher {
+    /// Key is the SHA-256 of `secret`.
+    #[must_use]
+    pub fn new(secret: &str) -> Self {
+        let digest = Sha256::digest(secret.as_bytes());
+        Self {
+            cipher: Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&digest)),
+        }
+    }
+
+    pub fn is_sealed(value: &str) -> bool {
+        value.starts_with(SEALED_PREFIX)
+    }
+
+    /// Encrypt `plaintext` under a fresh random nonce. Already sealed values
+    /// are returned unchanged.
+    pub fn seal(&self, plaintext: &str) -> Result<String, PolluxError> {
+        if Self::is_sealed(plaintext) {
+            return Ok(plaintext.to_string());
+        }
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ciphertext = self
+            .cipher
+            .encrypt(&nonce, plaintext.as_bytes())
+            .map_err(|_| PolluxError::UnexpectedError("token encryption failed".to_string()))?;
+        let mut blob = nonce.to_vec();
+        blob.extend_from_slice(&ciphertext);
+        Ok(format!("{SEALED_PREFIX}{}", STANDARD.encode(blob)))
+    }
+
+    /// Decrypt a sealed value; plaintext values pass through.
+    ///
+    /// # Errors
+    /// Fails when the value was sealed under a different key or is corrupt.
+    pub fn open(&self, stored: &str) -> Result<String, PolluxError> {
+        let Some(encoded) = stored.strip_prefix(SEALED_PREFIX) else {
+            return Ok(stored.to_string());
+        };
+        let invalid = || {
+            PolluxError::UnexpectedError(
+                "cannot decrypt stored token; is basic.db_encryption_key correct?".to_string(),
+            )
+        };
+        let blob = STANDARD.decode(encoded).map_err(|_| invalid())?;
+        if blob.len() < NONCE_LEN {
+            return Err(invalid());
+        }
+        let (nonce, ciphertext) = blob.split_at(NONCE_LEN);
+        let plaintext = self
+            .cipher
+            .decrypt(Nonce::from_slice(nonce), ciphertext)
+            .map_err(|_| invalid())?;
+        String::from_utf8(plaintext).map_err(|_| invalid())
+    }
+
+    pub(crate) fn seal_opt(&self, value: Option<String>) -> Result<Option<String>, PolluxError> {
+        value.map(|v| self.seal(&v)).transpose()
+    }
+
+    pub(crate) fn open_opt(&self, value: Option<String>) -> Result<Option<String>, PolluxError> {
+        value.map(|v| self.open(&v)).transpose()
+    }
+
+    /// Seal the token fields a patch sets.
+    pub(crate) fn seal_patch(&self, patch: ProviderPatch) -> Result<ProviderPatch, PolluxError> {
+        Ok(match patch {
+            ProviderPatch::GeminiCli { id, mut patch } => {
+                patch.refresh_token = self.seal_opt(patch.refresh_token)?;
+                patch.access_token = self.seal_opt(patch.access_token)?;
+                ProviderPatch::GeminiCli { id, patch }
+            }
+            ProviderPatch::Codex { id, mut patch } => {
+                patch.refresh_token = self.seal_opt(patch.refresh_token)?;
+                patch.access_token = self.seal_opt(patch.access_token)?;
+                ProviderPatch::Codex { id, patch }
+            }
+            ProviderPatch::Antigravity { id, mut patch } => {
+                patch.refresh_token = self.seal_opt(patch.refresh_token)?;
+                patch.access_token = self.seal_opt(patch.access_token)?;
+                ProviderPatch::Antigravity { id, patch }
+            }
+            ProviderPatch::Claude { id, mut patch } => {
+                patch.refresh_token = self.seal_opt(patch.refresh_token)?;
+                patch.access_token = self.seal_opt(patch.access_token)?;
+                ProviderPatch::Claude { id, patch }
+            }
+            ProviderPatch::Qwen { id, mut patch } => {
+                patch.refresh_token = self.seal_opt(patch.refresh_token)?;
+                patch.access_token = self.seal_opt(patch.access_token)?;
+                ProviderPatch::Qwen { id, patch }
+            }
+        })
+    }
+}
+
+/// Rows with token columns that are opened after every read.
+pub(crate) trait TokenColumns: Sized {
+    fn open_tokens(self, cipher: &TokenCipher) -> Result<Self, PolluxError>;
+
+    /// Whether any token column still holds plaintext.
+    fn has_plaintext_tokens(&self) -> bool;
+}
+
+impl TokenColumns for DbGeminiCliResource {
+    fn open_tokens(mut self, cipher: &TokenCipher) -> Result<Self, PolluxError> {
+        self.refresh_token = cipher.open(&self.refresh_token)?;
+        self.access_token = cipher.open_opt(self.access_token)?;
+        Ok(self)
+    }
+
+    fn has_plaintext_tokens(&self) -> bool {
+        !TokenCipher::is_sealed(&self.refresh_token)
+            || self
+                .access_token
+                .as_deref()
+                .is_some_and(|t| !TokenCipher::is_sealed(t))
+    }
+}
+
+impl TokenColumns for DbCodexResource {
+    fn open_tokens(mut self, cipher: &TokenCipher) -> Result<Self, PolluxError> {
+        self.refresh_token = cipher.open(&self.refresh_token)?;
+        self.access_token = cipher.open(&self.access_token)?;
+        Ok(self)
+    }
+
+    fn has_plaintext_tokens(&self) -> bool {
+        !TokenCipher::is_sealed(&self.refresh_token) || !TokenCipher::is_sealed(&self.access_token)
+    }
+}
+
+impl TokenColumns for DbAntigravityResource {
+    fn open_tokens(mut self, cipher: &TokenCipher) -> Result<Self, PolluxError> {
+        self.refresh_token = cipher.open(&self.refresh_token)?;
+        self.access_token = cipher.open_opt(self.access_token)?;
+        Ok(self)
+    }
+
+    fn has_plaintext_tokens(&self) -> bool {
+        !TokenCipher::is_sealed(&self.refresh_token)
+            || self
+                .access_token
+                .as_deref()
+                .is_some_and(|t| !TokenCipher::is_sealed(t))
+    }
+}
+
+impl TokenColumns for DbClaudeResource {
+    fn open_tokens(mut self, cipher: &TokenCipher) -> Result<Self, PolluxError> {
+        self.refresh_token = cipher.open(&self.refresh_token)?;
+        self.access_token = cipher.open(&self.access_token)?;
+        Ok(self)
+    }
+
+    fn has_plaintext_tokens(&self) -> bool {
+        !TokenCipher::is_sealed(&self.refresh_token) || !TokenCipher::is_sealed(&self.access_token)
+    }
+}
+
+impl TokenColumns for DbQwenResource {
+    fn open_tokens(mut self, cipher: &TokenCipher) -> Result<Self, PolluxError> {
+        self.refresh_token = cipher.open(&self.refresh_token)?;
+        self.access_token = cipher.open(&self.access_token)?;
+        Ok(self)
+    }
+
+    fn has_plaintext_tokens(&self) -> bool {
+        !TokenCipher::is_sealed(&self.refresh_token) || !TokenCipher::is_sealed(&self.access_token)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sealed_tokens_round_trip_and_plaintext_passes_through() {
+        let cipher = TokenCipher::new("secret");
+        let sealed = cipher.seal("1//refresh-token").unwrap();
+        assert!(TokenCipher::is_sealed(&sealed));
+        assert_ne!(sealed, cipher.seal("1//refresh-token").unwrap());
+        assert_eq!(cipher.open(&sealed).unwrap(), "1//refresh-token");
+        assert_eq!(cipher.seal(&sealed).unwrap(), sealed);
+
+        assert_eq!(cipher.open("legacy-plaintext").unwrap(), "legacy-plaintext");
+        assert!(TokenCipher::new("other").open(&sealed).is_err());
+    }
+}
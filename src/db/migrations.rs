@@ -0,0 +1,355 @@
+//! Versioned schema migrations.
+//!
+//! Each [`Migration`] carries the DDL for every backend and runs once, in
+//! version order, inside a transaction. Applied versions are recorded in
+//! `schema_version`, so a new migration only touches databases that have not
+//! seen it yet. Databases created before versioning already match version 1,
+//! whose statements are all `IF NOT EXISTS`.
+//!
+//! To change the schema, append a migration with the next version; never edit
+//! one that has shipped.
+
+use crate::db::backend::{DbBackendKind, DbPool, with_pool};
+use crate::db::schema::{MYSQL_INIT, POSTGRES_INIT, SQLITE_INIT};
+use crate::db::traits::SqlDialect;
+use crate::error::PolluxError;
+use chrono::Utc;
+use sqlx::Executor;
+use tracing::info;
+
+/// One schema step, written once per backend.
+#[derive(Debug, Clone, Copy)]
+pub struct Migration {
+    pub version: i64,
+    pub description: &'static str,
+    pub sqlite: &'static str,
+    pub postgres: &'static str,
+    pub mysql: &'static str,
+}
+
+impl Migration {
+    #[must_use]
+    pub const fn sql(&self, kind: DbBackendKind) -> &'static str {
+        match kind {
+            DbBackendKind::Sqlite => self.sqlite,
+            DbBackendKind::Postgres => self.postgres,
+            DbBackendKind::MySql => self.mysql,
+        }
+    }
+}
+
+/// All migrations, in ascending version order.
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        description: "initial schema",
+        sqlite: SQLITE_INIT,
+        postgres: POSTGRES_INIT,
+        mysql: MYSQL_INIT,
+    },
+    Migration {
+        version: 2,
+        description: "credential pool labels",
+        sqlite: ADD_LABELS,
+        postgres: ADD_LABELS,
+        mysql: r"
+ALTER TABLE gemini_cli ADD COLUMN labels VARCHAR(512) NOT NULL DEFAULT '';
+ALTER TABLE codex ADD COLUMN labels VARCHAR(512) NOT NULL DEFAULT '';
+ALTER TABLE antigravity ADD COLUMN labels VARCHAR(512) NOT NULL DEFAULT '';
+",
+    },
+    Migration {
+        version: 3,
+        description: "thought signature model tags",
+        sqlite: "ALTER TABLE thought_signatures ADD COLUMN model TEXT NOT NULL DEFAULT '';",
+        postgres: "ALTER TABLE thought_signatures ADD COLUMN model TEXT NOT NULL DEFAULT '';",
+        mysql: "ALTER TABLE thought_signatures ADD COLUMN model VARCHAR(128) NOT NULL DEFAULT '';",
+    },
+    Migration {
+        version: 4,
+        description: "credential proxy bindings",
+        sqlite: ADD_PROXY_URL,
+        postgres: ADD_PROXY_URL,
+        mysql: r"
+ALTER TABLE gemini_cli ADD COLUMN proxy_url VARCHAR(512) NULL;
+ALTER TABLE codex ADD COLUMN proxy_url VARCHAR(512) NULL;
+ALTER TABLE antigravity ADD COLUMN proxy_url VARCHAR(512) NULL;
+",
+    },
+    Migration {
+        version: 5,
+        description: "claude provider",
+        sqlite: r"
+CREATE TABLE IF NOT EXISTS claude (
+    id INTEGER PRIMARY KEY NOT NULL,
+    email TEXT NULL,
+    account_uuid TEXT NOT NULL,
+    organization_uuid TEXT NOT NULL,
+    refresh_token TEXT NOT NULL,
+    access_token TEXT NOT NULL,
+    expiry TEXT NOT NULL, -- RFC3339
+    labels TEXT NOT NULL DEFAULT '',
+    proxy_url TEXT NULL,
+    status INTEGER NOT NULL DEFAULT 1,
+    created_at TEXT NOT NULL, -- RFC3339
+    updated_at TEXT NOT NULL, -- RFC3339
+    UNIQUE(account_uuid, organization_uuid)
+);
+
+CREATE INDEX IF NOT EXISTS idx_claude_status ON claude(status);
+",
+        postgres: r"
+CREATE TABLE IF NOT EXISTS claude (
+    id BIGSERIAL PRIMARY KEY,
+    email TEXT NULL,
+    account_uuid TEXT NOT NULL,
+    organization_uuid TEXT NOT NULL,
+    refresh_token TEXT NOT NULL,
+    access_token TEXT NOT NULL,
+    expiry TIMESTAMPTZ NOT NULL,
+    labels TEXT NOT NULL DEFAULT '',
+    proxy_url TEXT NULL,
+    status BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at TIMESTAMPTZ NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL,
+    UNIQUE(account_uuid, organization_uuid)
+);
+
+CREATE INDEX IF NOT EXISTS idx_claude_status ON claude(status);
+",
+        mysql: r"
+CREATE TABLE IF NOT EXISTS claude (
+    id BIGINT NOT NULL AUTO_INCREMENT PRIMARY KEY,
+    email TEXT NULL,
+    account_uuid VARCHAR(191) NOT NULL,
+    organization_uuid VARCHAR(191) NOT NULL,
+    refresh_token TEXT NOT NULL,
+    access_token TEXT NOT NULL,
+    expiry DATETIME(6) NOT NULL,
+    labels VARCHAR(512) NOT NULL DEFAULT '',
+    proxy_url VARCHAR(512) NULL,
+    status BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at DATETIME(6) NOT NULL,
+    updated_at DATETIME(6) NOT NULL,
+    UNIQUE KEY uq_claude_identity (account_uuid, organization_uuid),
+    KEY idx_claude_status (status)
+);
+",
+    },
+    Migration {
+        version: 6,
+        description: "qwen provider",
+        sqlite: r"
+CREATE TABLE IF NOT EXISTS qwen (
+    id INTEGER PRIMARY KEY NOT NULL,
+    refresh_token TEXT NOT NULL,
+    access_token TEXT NOT NULL,
+    resource_url TEXT NULL,
+    expiry TEXT NOT NULL, -- RFC3339
+    labels TEXT NOT NULL DEFAULT '',
+    proxy_url TEXT NULL,
+    status INTEGER NOT NULL DEFAULT 1,
+    created_at TEXT NOT NULL, -- RFC3339
+    updated_at TEXT NOT NULL -- RFC3339
+);
+
+CREATE INDEX IF NOT EXISTS idx_qwen_status ON qwen(status);
+",
+        postgres: r"
+CREATE TABLE IF NOT EXISTS qwen (
+    id BIGSERIAL PRIMARY KEY,
+    refresh_token TEXT NOT NULL,
+    access_token TEXT NOT NULL,
+    resource_url TEXT NULL,
+    expiry TIMESTAMPTZ NOT NULL,
+    labels TEXT NOT NULL DEFAULT '',
+    proxy_url TEXT NULL,
+    status BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at TIMESTAMPTZ NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_qwen_status ON qwen(status);
+",
+        mysql: r"
+CREATE TABLE IF NOT EXISTS qwen (
+    id BIGINT NOT NULL AUTO_INCREMENT PRIMARY KEY,
+    refresh_token TEXT NOT NULL,
+    access_token TEXT NOT NULL,
+    resource_url VARCHAR(255) NULL,
+    expiry DATETIME(6) NOT NULL,
+    labels VARCHAR(512) NOT NULL DEFAULT '',
+    proxy_url VARCHAR(512) NULL,
+    status BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at DATETIME(6) NOT NULL,
+    updated_at DATETIME(6) NOT NULL,
+    KEY idx_qwen_status (status)
+);
+",
+    },
+    Migration {
+        version: 7,
+        description: "thought signature fingerprint versions",
+        sqlite: ADD_KEY_VERSION,
+        postgres: ADD_KEY_VERSION,
+        mysql: ADD_KEY_VERSION,
+    },
+    Migration {
+        version: 8,
+        description: "qwen credential identity",
+        sqlite: r"
+ALTER TABLE qwen ADD COLUMN sub TEXT NOT NULL DEFAULT '';
+UPDATE qwen SET sub = 'legacy:' || id;
+CREATE UNIQUE INDEX IF NOT EXISTS uq_qwen_sub ON qwen(sub);
+",
+        postgres: r"
+ALTER TABLE qwen ADD COLUMN sub TEXT NOT NULL DEFAULT '';
+UPDATE qwen SET sub = 'legacy:' || id;
+CREATE UNIQUE INDEX IF NOT EXISTS uq_qwen_sub ON qwen(sub);
+",
+        mysql: r"
+ALTER TABLE qwen ADD COLUMN sub VARCHAR(191) NOT NULL DEFAULT '';
+UPDATE qwen SET sub = CONCAT('legacy:', id);
+CREATE UNIQUE INDEX uq_qwen_sub ON qwen(sub);
+",
+    },
+    Migration {
+        version: 9,
+        description: "gemini account tiers",
+        sqlite: ADD_TIER,
+        postgres: ADD_TIER,
+        mysql: ADD_TIER,
+    },
+];
+
+const ADD_LABELS: &str = r"
+ALTER TABLE gemini_cli ADD COLUMN labels TEXT NOT NULL DEFAULT '';
+ALTER TABLE codex ADD COLUMN labels TEXT NOT NULL DEFAULT '';
+ALTER TABLE antigravity ADD COLUMN labels TEXT NOT NULL DEFAULT '';
+";
+
+const ADD_PROXY_URL: &str = r"
+ALTER TABLE gemini_cli ADD COLUMN proxy_url TEXT NULL;
+ALTER TABLE codex ADD COLUMN proxy_url TEXT NULL;
+ALTER TABLE antigravity ADD COLUMN proxy_url TEXT NULL;
+";
+
+// Filled on the next ingest; rows without one weigh as an unlisted tier.
+const ADD_TIER: &str = r"
+ALTER TABLE gemini_cli ADD COLUMN tier TEXT NULL;
+ALTER TABLE antigravity ADD COLUMN tier TEXT NULL;
+";
+
+// Rows captured before versioning were keyed by `FingerprintVersion::V1`.
+const ADD_KEY_VERSION: &str =
+    "ALTER TABLE thought_signatures ADD COLUMN key_version SMALLINT NOT NULL DEFAULT 1;";
+
+/// Latest version this build knows about.
+#[must_use]
+pub fn latest_version() -> i64 {
+    MIGRATIONS.last().map_or(0, |m| m.version)
+}
+
+const SCHEMA_VERSION_TABLE: &str = r"
+CREATE TABLE IF NOT EXISTS schema_version (
+    version BIGINT NOT NULL PRIMARY KEY,
+    description TEXT NOT NULL,
+    applied_at BIGINT NOT NULL -- unix seconds
+)
+";
+
+/// Highest applied version; fails if `schema_version` does not exist yet.
+pub async fn current_version(pool: &DbPool) -> Result<i64, PolluxError> {
+    let current: Option<i64> = with_pool!(pool, |p| {
+        sqlx::query_scalar(&p.sql("SELECT MAX(version) FROM schema_version"))
+            .fetch_one(p)
+            .await
+    })?;
+    Ok(current.unwrap_or(0))
+}
+
+/// Bring the schema up to [`latest_version`], returning the version it is at.
+pub async fn run(pool: &DbPool) -> Result<i64, PolluxError> {
+    with_pool!(pool, |p| {
+        sqlx::raw_sql(SCHEMA_VERSION_TABLE)
+            .execute(p)
+            .await
+            .map(|_| ())
+    })?;
+    let current = current_version(pool).await?;
+    if current > latest_version() {
+        return Err(PolluxError::UnexpectedError(format!(
+            "database schema is at version {current}, newer than this build ({})",
+            latest_version()
+        )));
+    }
+
+    let kind = pool.kind();
+    for migration in MIGRATIONS.iter().filter(|m| m.version > current) {
+        info!(
+            version = migration.version,
+            description = migration.description,
+            backend = kind.as_str(),
+            "Applying schema migration"
+        );
+        apply(pool, migration).await?;
+    }
+    Ok(latest_version())
+}
+
+async fn apply(pool: &DbPool, migration: &Migration) -> Result<(), PolluxError> {
+    let kind = pool.kind();
+    let now = Utc::now().timestamp();
+    with_pool!(pool, |p| {
+        let mut tx = p.begin().await?;
+        // `Executor::execute` rather than `RawSql::execute`, whose future is not
+        // `Send` over a borrowed connection.
+        tx.execute(sqlx::raw_sql(migration.sql(kind))).await?;
+        sqlx::query(&p.sql(
+            "INSERT INTO schema_version (version, description, applied_at) VALUES ($1, $2, $3)",
+        ))
+        .bind(migration.version)
+        .bind(migration.description)
+        .bind(now)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await
+    })?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn versions_are_strictly_ascending_from_one() {
+        assert_eq!(MIGRATIONS.first().map(|m| m.version), Some(1));
+        for pair in MIGRATIONS.windows(2) {
+            assert_eq!(pair[1].version, pair[0].version + 1);
+        }
+    }
+
+    #[tokio::test]
+    async fn migrations_apply_once() {
+        let path =
+            std::env::temp_dir().join(format!("pollux_migrations_{}.sqlite", uuid::Uuid::new_v4()));
+        let url = format!("sqlite://{}", path.display());
+        let pool = DbPool::connect(&url).await.expect("connect");
+        assert_eq!(run(&pool).await.expect("rerun"), latest_version());
+
+        let DbPool::Sqlite(p) = &pool else {
+            unreachable!("sqlite url");
+        };
+        let applied: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM schema_version")
+            .fetch_one(p)
+            .await
+            .expect("count");
+        assert_eq!(applied, i64::try_from(MIGRATIONS.len()).unwrap());
+
+        p.close().await;
+        for suffix in ["", "-wal", "-shm"] {
+            let _ = std::fs::remove_file(format!("{}{suffix}", path.display()));
+        }
+    }
+}
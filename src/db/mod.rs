@@ -2,9 +2,16 @@
 //!
 //! Layout:
 //! - `models.rs`: Rust structs mirroring DB rows
-//! - `schema.rs`: SQL DDL for initializing the database (SQLite-first)
+//! - `schema.rs`: SQL DDL for initializing the database (SQLite-first, `PostgreSQL` and `MySQL` mirrors)
+//! - `migrations.rs`: versioned schema steps applied at startup
+//! - `backend.rs`: runtime backend selection from the `database_url` scheme
+//! - `traits.rs`: patch application and per-backend SQL dialect hooks
+//! - `crypto.rs`: optional at-rest encryption of credential tokens
 
 pub mod actor;
+pub mod backend;
+pub mod crypto;
+pub mod migrations;
 pub mod models;
 pub mod patch;
 pub mod schema;
@@ -12,11 +19,19 @@ pub mod traits;
 
 mod patch_impl;
 
-pub use models::{DbAntigravityResource, DbCodexResource, DbGeminiCliResource};
+pub use backend::{DbBackendKind, DbPool};
+pub use models::{
+    DbAntigravityResource, DbClaudeResource, DbCodexResource, DbGeminiCliResource, DbQwenResource,
+    ModelRegistryRow, ModelUsageStats, RequestCounterRow, ThoughtSignatureRow, UsageAggregate,
+    UsageQuery, UsageRecord, join_labels, normalize_labels, split_labels,
+};
 pub use patch::{
-    AntigravityCreate, AntigravityPatch, CodexCreate, CodexPatch, GeminiCliCreate, GeminiCliPatch,
-    ProviderCreate, ProviderPatch,
+    AntigravityCreate, AntigravityPatch, ClaudeCreate, ClaudePatch, CodexCreate, CodexPatch,
+    GeminiCliCreate, GeminiCliPatch, ProviderCreate, ProviderDelete, ProviderIdentity,
+    ProviderPatch, QwenCreate, QwenPatch,
 };
-pub use schema::SQLITE_INIT;
+pub use schema::{MYSQL_INIT, POSTGRES_INIT, SQLITE_INIT};
 
-pub use actor::{DbActorHandle, spawn};
+pub(crate) use actor::synthetic_sub_from_refresh_token;
+pub use actor::{DbActorHandle, spawn, spawn_with_cipher};
+pub use crypto::TokenCipher;
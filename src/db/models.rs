@@ -8,9 +8,15 @@ pub struct DbGeminiCliResource {
     pub email: Option<String>,
     pub sub: String,
     pub project_id: String,
+    /// Quota tier from `loadCodeAssist` (e.g. `standard-tier`), if known.
+    pub tier: Option<String>,
     pub refresh_token: String,
     pub access_token: Option<String>,
     pub expiry: DateTime<Utc>,
+    /// Comma-separated pool labels; see [`split_labels`].
+    pub labels: String,
+    /// Egress proxy this credential is bound to, if any.
+    pub proxy_url: Option<String>,
     pub status: bool,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
@@ -26,6 +32,10 @@ pub struct DbCodexResource {
     pub access_token: String,
     pub expiry: DateTime<Utc>,
     pub chatgpt_plan_type: Option<String>,
+    /// Comma-separated pool labels; see [`split_labels`].
+    pub labels: String,
+    /// Egress proxy this credential is bound to, if any.
+    pub proxy_url: Option<String>,
     pub status: bool,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
@@ -38,10 +48,166 @@ pub struct DbAntigravityResource {
     /// Stable unique key (real subject if available, otherwise synthetic).
     pub sub: String,
     pub project_id: String,
+    /// Quota tier from `loadCodeAssist` (e.g. `standard-tier`), if known.
+    pub tier: Option<String>,
     pub refresh_token: String,
     pub access_token: Option<String>,
     pub expiry: DateTime<Utc>,
+    /// Comma-separated pool labels; see [`split_labels`].
+    pub labels: String,
+    /// Egress proxy this credential is bound to, if any.
+    pub proxy_url: Option<String>,
     pub status: bool,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, FromRow)]
+pub struct DbClaudeResource {
+    pub id: i64,
+    pub email: Option<String>,
+    pub account_uuid: String,
+    pub organization_uuid: String,
+    pub refresh_token: String,
+    pub access_token: String,
+    pub expiry: DateTime<Utc>,
+    /// Comma-separated pool labels; see [`split_labels`].
+    pub labels: String,
+    /// Egress proxy this credential is bound to, if any.
+    pub proxy_url: Option<String>,
+    pub status: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, FromRow)]
+pub struct DbQwenResource {
+    pub id: i64,
+    /// Stable unique key: a hash of the refresh token the row was first
+    /// stored with (`legacy:<id>` for rows created before it existed).
+    pub sub: String,
+    pub refresh_token: String,
+    pub access_token: String,
+    /// Host serving this account's API, as returned by the token endpoint.
+    pub resource_url: Option<String>,
+    pub expiry: DateTime<Utc>,
+    /// Comma-separated pool labels; see [`split_labels`].
+    pub labels: String,
+    /// Egress proxy this credential is bound to, if any.
+    pub proxy_url: Option<String>,
+    pub status: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Normalize pool labels: comma-separated entries are split, whitespace
+/// trimmed, and empty or repeated labels dropped.
+pub fn normalize_labels<S: AsRef<str>>(labels: impl IntoIterator<Item = S>) -> Vec<String> {
+    let mut out: Vec<String> = Vec::new();
+    for label in labels {
+        for label in label.as_ref().split(',').map(str::trim) {
+            if !label.is_empty() && !out.iter().any(|l| l == label) {
+                out.push(label.to_string());
+            }
+        }
+    }
+    out
+}
+
+/// Labels as stored in a provider table's `labels` column.
+pub fn split_labels(stored: &str) -> Vec<String> {
+    normalize_labels([stored])
+}
+
+/// Inverse of [`split_labels`].
+pub fn join_labels(labels: &[String]) -> String {
+    labels.join(",")
+}
+
+/// One proxied request, as written to the `usage` table.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UsageRecord {
+    pub created_at: DateTime<Utc>,
+    pub provider: String,
+    pub model: String,
+    /// Credential that served the (last) upstream attempt, if one was leased.
+    pub credential_id: Option<i64>,
+    pub prompt_tokens: i64,
+    pub output_tokens: i64,
+    pub latency_ms: i64,
+    pub status: i32,
+}
+
+/// Filters for [`UsageAggregate`] queries.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct UsageQuery {
+    /// Only count requests at or after this instant.
+    pub since: Option<DateTime<Utc>>,
+    pub provider: Option<String>,
+}
+
+/// Usage totals grouped by (provider, model, credential).
+#[derive(Debug, Clone, Serialize, PartialEq, Eq, FromRow)]
+pub struct UsageAggregate {
+    pub provider: String,
+    pub model: String,
+    pub credential_id: Option<i64>,
+    pub requests: i64,
+    pub errors: i64,
+    pub prompt_tokens: i64,
+    pub output_tokens: i64,
+    pub avg_latency_ms: i64,
+}
+
+/// Outcome counts per (provider, model), used for capacity analysis.
+#[derive(Debug, Clone, Serialize, PartialEq, Eq, FromRow)]
+pub struct ModelUsageStats {
+    pub provider: String,
+    pub model: String,
+    pub requests: i64,
+    /// `503`: no credential could be leased.
+    pub unavailable: i64,
+    /// `429` passed through from upstream.
+    pub rate_limited: i64,
+    /// Any other `4xx`/`5xx`.
+    pub other_errors: i64,
+}
+
+/// Cumulative request totals for one (provider, model) since the counters
+/// were first created; restored at startup so they survive restarts.
+#[derive(Debug, Clone, Serialize, PartialEq, Eq, FromRow)]
+pub struct RequestCounterRow {
+    pub provider: String,
+    pub model: String,
+    pub requests: i64,
+    /// Responses with status `>= 400`.
+    pub errors: i64,
+}
+
+/// A configured model and the registry index it had when last saved.
+#[derive(Debug, Clone, Serialize, PartialEq, Eq, FromRow)]
+pub struct ModelRegistryRow {
+    pub provider: String,
+    pub model: String,
+    /// `ModelId` reinterpreted as `i64`; unaffected by list order.
+    pub model_id: i64,
+    /// Position in the global model registry (the capability bit).
+    pub idx: i64,
+}
+
+/// One captured thought signature, keyed by the provider and the
+/// content fingerprint it was recorded under.
+#[derive(Debug, Clone, PartialEq, Eq, FromRow)]
+pub struct ThoughtSignatureRow {
+    pub provider: String,
+    /// The `u64` cache key reinterpreted as `i64`, since neither backend has
+    /// an unsigned 64-bit column.
+    pub cache_key: i64,
+    /// `FingerprintVersion` byte of `cache_key`.
+    pub key_version: i16,
+    pub signature: String,
+    /// Model of the response it was captured from; empty if unknown.
+    pub model: String,
+    /// Unix seconds.
+    pub created_at: i64,
+}
@@ -1,19 +1,31 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use url::Url;
 
 // Re-export patch payload/envelope types from the neutral crate-private module.
 // This keeps `pollux::db::{ProviderPatch, GeminiCliPatch, CodexPatch}` stable,
 // and also preserves `pollux::db::patch::ProviderPatch`.
-pub use crate::patches::{AntigravityPatch, CodexPatch, GeminiCliPatch, ProviderPatch};
+pub use crate::patches::{
+    AntigravityPatch, ClaudePatch, CodexPatch, GeminiCliPatch, ProviderPatch, QwenPatch,
+};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GeminiCliCreate {
     pub email: Option<String>,
     pub sub: String,
     pub project_id: String,
+    /// Quota tier from `loadCodeAssist`, if known.
+    #[serde(default)]
+    pub tier: Option<String>,
     pub refresh_token: String,
     pub access_token: Option<String>,
     pub expiry: DateTime<Utc>,
+    /// Pool labels, already normalized.
+    #[serde(default)]
+    pub labels: Vec<String>,
+    /// Egress proxy for this credential's upstream traffic.
+    #[serde(default)]
+    pub proxy_url: Option<Url>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -25,6 +37,12 @@ pub struct CodexCreate {
     pub access_token: String,
     pub expiry: DateTime<Utc>,
     pub chatgpt_plan_type: Option<String>,
+    /// Pool labels, already normalized.
+    #[serde(default)]
+    pub labels: Vec<String>,
+    /// Egress proxy for this credential's upstream traffic.
+    #[serde(default)]
+    pub proxy_url: Option<Url>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -33,9 +51,50 @@ pub struct AntigravityCreate {
     /// May be missing depending on upstream/OAuth flow; `DbActor` will synthesize a stable value.
     pub sub: Option<String>,
     pub project_id: String,
+    /// Quota tier from `loadCodeAssist`, if known.
+    #[serde(default)]
+    pub tier: Option<String>,
     pub refresh_token: String,
     pub access_token: Option<String>,
     pub expiry: DateTime<Utc>,
+    /// Pool labels, already normalized.
+    #[serde(default)]
+    pub labels: Vec<String>,
+    /// Egress proxy for this credential's upstream traffic.
+    #[serde(default)]
+    pub proxy_url: Option<Url>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClaudeCreate {
+    pub email: Option<String>,
+    pub account_uuid: String,
+    pub organization_uuid: String,
+    pub refresh_token: String,
+    pub access_token: String,
+    pub expiry: DateTime<Utc>,
+    /// Pool labels, already normalized.
+    #[serde(default)]
+    pub labels: Vec<String>,
+    /// Egress proxy for this credential's upstream traffic.
+    #[serde(default)]
+    pub proxy_url: Option<Url>,
+}
+
+/// Qwen tokens carry no account identity; `DbActor` keys the row by a hash
+/// of the refresh token, so the same credential submitted twice stays one row.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QwenCreate {
+    pub refresh_token: String,
+    pub access_token: String,
+    pub resource_url: Option<String>,
+    pub expiry: DateTime<Utc>,
+    /// Pool labels, already normalized.
+    #[serde(default)]
+    pub labels: Vec<String>,
+    /// Egress proxy for this credential's upstream traffic.
+    #[serde(default)]
+    pub proxy_url: Option<Url>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -45,4 +104,45 @@ pub enum ProviderCreate {
     GeminiCli(GeminiCliCreate),
     Codex(CodexCreate),
     Antigravity(AntigravityCreate),
+    Claude(ClaudeCreate),
+    Qwen(QwenCreate),
+}
+
+/// Account identity of a credential about to be stored, used to find an
+/// existing row for the same account.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", content = "data")]
+#[serde(rename_all = "snake_case")]
+pub enum ProviderIdentity {
+    GeminiCli {
+        sub: String,
+    },
+    Codex {
+        sub: String,
+        account_id: String,
+    },
+    /// `sub` as stored, i.e. synthesized from the refresh token when missing.
+    Antigravity {
+        sub: String,
+    },
+    Claude {
+        account_uuid: String,
+        organization_uuid: String,
+    },
+    /// `sub` as stored, i.e. synthesized from the refresh token.
+    Qwen {
+        sub: String,
+    },
+}
+
+/// Hard delete of one provider record by id.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(tag = "type", content = "id")]
+#[serde(rename_all = "snake_case")]
+pub enum ProviderDelete {
+    GeminiCli(i64),
+    Codex(i64),
+    Antigravity(i64),
+    Claude(i64),
+    Qwen(i64),
 }
@@ -4,16 +4,21 @@
 
 use async_trait::async_trait;
 use chrono::Utc;
-use sqlx::SqlitePool;
 use tracing::debug;
 
+use crate::db::backend::{DbPool, with_pool};
+use crate::db::models::join_labels;
+use crate::db::traits::SqlDialect;
 use crate::error::PolluxError;
-use crate::patches::{AntigravityPatch, CodexPatch, DbPatchable, GeminiCliPatch, ProviderPatch};
+use crate::patches::{
+    AntigravityPatch, ClaudePatch, CodexPatch, DbPatchable, GeminiCliPatch, ProviderPatch,
+    QwenPatch,
+};
 
 #[allow(clippy::too_many_lines)]
 #[async_trait]
 impl DbPatchable for ProviderPatch {
-    async fn apply_patch(&self, pool: &SqlitePool) -> Result<(), PolluxError> {
+    async fn apply_patch(&self, pool: &DbPool) -> Result<(), PolluxError> {
         match self {
             ProviderPatch::GeminiCli { id, patch } => {
                 let id = i64::try_from(*id).map_err(|_| {
@@ -25,40 +30,51 @@ impl DbPatchable for ProviderPatch {
                     refresh_token,
                     access_token,
                     expiry,
+                    tier,
                     status,
+                    labels,
                 } = patch.clone();
 
                 let email_set = email.is_some();
                 let refresh_token_set = refresh_token.is_some();
                 let access_token_set = access_token.is_some();
                 let expiry_set = expiry.is_some();
+                let tier_set = tier.is_some();
                 let status_set = status.is_some();
+                let labels_set = labels.is_some();
+                let labels = labels.map(|l| join_labels(&l));
                 let updated_at = Utc::now();
 
-                let res = sqlx::query!(
-                    r"
-                    UPDATE gemini_cli
-                    SET
-                        email = COALESCE(?, email),
-                        refresh_token = COALESCE(?, refresh_token),
-                        access_token = COALESCE(?, access_token),
-                        expiry = COALESCE(?, expiry),
-                        status = COALESCE(?, status),
-                        updated_at = ?
-                    WHERE id = ?
-                    ",
-                    email,
-                    refresh_token,
-                    access_token,
-                    expiry,
-                    status,
-                    updated_at,
-                    id,
-                )
-                .execute(pool)
-                .await?;
+                let affected = with_pool!(pool, |p| {
+                    sqlx::query(&p.sql(
+                        r"
+                        UPDATE gemini_cli
+                        SET
+                            email = COALESCE($1, email),
+                            refresh_token = COALESCE($2, refresh_token),
+                            access_token = COALESCE($3, access_token),
+                            expiry = COALESCE($4, expiry),
+                            tier = COALESCE($5, tier),
+                            status = COALESCE($6, status),
+                            labels = COALESCE($7, labels),
+                            updated_at = $8
+                        WHERE id = $9
+                        ",
+                    ))
+                    .bind(email)
+                    .bind(refresh_token)
+                    .bind(access_token)
+                    .bind(expiry)
+                    .bind(tier)
+                    .bind(status)
+                    .bind(labels)
+                    .bind(updated_at)
+                    .bind(id)
+                    .execute(p)
+                    .await
+                    .map(|r| r.rows_affected())
+                })?;
 
-                let affected = res.rows_affected();
                 debug!(
                     provider = "gemini_cli",
                     id,
@@ -68,7 +84,9 @@ impl DbPatchable for ProviderPatch {
                     refresh_token_set,
                     access_token_set,
                     expiry_set,
+                    tier_set,
                     status_set,
+                    labels_set,
                     "db patch applied"
                 );
 
@@ -94,6 +112,7 @@ impl DbPatchable for ProviderPatch {
                     expiry,
                     chatgpt_plan_type,
                     status,
+                    labels,
                 } = patch.clone();
 
                 let email_set = email.is_some();
@@ -104,39 +123,45 @@ impl DbPatchable for ProviderPatch {
                 let expiry_set = expiry.is_some();
                 let chatgpt_plan_type_set = chatgpt_plan_type.is_some();
                 let status_set = status.is_some();
+                let labels_set = labels.is_some();
+                let labels = labels.map(|l| join_labels(&l));
                 let updated_at = Utc::now();
 
                 // Use the non-macro query API so we don't have to keep SQLx's offline cache in sync.
-                let res = sqlx::query(
-                    r"
-                    UPDATE codex
-                    SET
-                        email = COALESCE(?, email),
-                        account_id = COALESCE(?, account_id),
-                        sub = COALESCE(?, sub),
-                        refresh_token = COALESCE(?, refresh_token),
-                        access_token = COALESCE(?, access_token),
-                        expiry = COALESCE(?, expiry),
-                        chatgpt_plan_type = COALESCE(?, chatgpt_plan_type),
-                        status = COALESCE(?, status),
-                        updated_at = ?
-                    WHERE id = ?
-                    ",
-                )
-                .bind(email)
-                .bind(account_id)
-                .bind(sub)
-                .bind(refresh_token)
-                .bind(access_token)
-                .bind(expiry)
-                .bind(chatgpt_plan_type)
-                .bind(status)
-                .bind(updated_at)
-                .bind(id)
-                .execute(pool)
-                .await?;
-
-                let affected = res.rows_affected();
+                let affected = with_pool!(pool, |p| {
+                    sqlx::query(&p.sql(
+                        r"
+                        UPDATE codex
+                        SET
+                            email = COALESCE($1, email),
+                            account_id = COALESCE($2, account_id),
+                            sub = COALESCE($3, sub),
+                            refresh_token = COALESCE($4, refresh_token),
+                            access_token = COALESCE($5, access_token),
+                            expiry = COALESCE($6, expiry),
+                            chatgpt_plan_type = COALESCE($7, chatgpt_plan_type),
+                            status = COALESCE($8, status),
+                            labels = COALESCE($9, labels),
+                            updated_at = $10
+                        WHERE id = $11
+                        ",
+                    ))
+                    .bind(email)
+                    .bind(account_id)
+                    .bind(sub)
+                    .bind(refresh_token)
+                    .bind(access_token)
+                    .bind(expiry)
+                    .bind(chatgpt_plan_type)
+                    .bind(status)
+                    .bind(labels)
+                    .bind(updated_at)
+                    .bind(id)
+                    .execute(p)
+                    .await
+                    .map(|r| r.rows_affected())
+                })?;
+
                 debug!(
                     provider = "codex",
                     id,
@@ -150,6 +175,7 @@ impl DbPatchable for ProviderPatch {
                     expiry_set,
                     chatgpt_plan_type_set,
                     status_set,
+                    labels_set,
                     "db patch applied"
                 );
 
@@ -172,41 +198,52 @@ impl DbPatchable for ProviderPatch {
                     refresh_token,
                     access_token,
                     expiry,
+                    tier,
                     status,
+                    labels,
                 } = patch.clone();
 
                 let email_set = email.is_some();
                 let refresh_token_set = refresh_token.is_some();
                 let access_token_set = access_token.is_some();
                 let expiry_set = expiry.is_some();
+                let tier_set = tier.is_some();
                 let status_set = status.is_some();
+                let labels_set = labels.is_some();
+                let labels = labels.map(|l| join_labels(&l));
                 let updated_at = Utc::now();
 
                 // Use bind query API to avoid SQLx offline cache requirements.
-                let res = sqlx::query(
-                    r"
-                    UPDATE antigravity
-                    SET
-                        email = COALESCE(?, email),
-                        refresh_token = COALESCE(?, refresh_token),
-                        access_token = COALESCE(?, access_token),
-                        expiry = COALESCE(?, expiry),
-                        status = COALESCE(?, status),
-                        updated_at = ?
-                    WHERE id = ?
-                    ",
-                )
-                .bind(email)
-                .bind(refresh_token)
-                .bind(access_token)
-                .bind(expiry)
-                .bind(status)
-                .bind(updated_at)
-                .bind(id)
-                .execute(pool)
-                .await?;
-
-                let affected = res.rows_affected();
+                let affected = with_pool!(pool, |p| {
+                    sqlx::query(&p.sql(
+                        r"
+                        UPDATE antigravity
+                        SET
+                            email = COALESCE($1, email),
+                            refresh_token = COALESCE($2, refresh_token),
+                            access_token = COALESCE($3, access_token),
+                            expiry = COALESCE($4, expiry),
+                            tier = COALESCE($5, tier),
+                            status = COALESCE($6, status),
+                            labels = COALESCE($7, labels),
+                            updated_at = $8
+                        WHERE id = $9
+                        ",
+                    ))
+                    .bind(email)
+                    .bind(refresh_token)
+                    .bind(access_token)
+                    .bind(expiry)
+                    .bind(tier)
+                    .bind(status)
+                    .bind(labels)
+                    .bind(updated_at)
+                    .bind(id)
+                    .execute(p)
+                    .await
+                    .map(|r| r.rows_affected())
+                })?;
+
                 debug!(
                     provider = "antigravity",
                     id,
@@ -216,7 +253,9 @@ impl DbPatchable for ProviderPatch {
                     refresh_token_set,
                     access_token_set,
                     expiry_set,
+                    tier_set,
                     status_set,
+                    labels_set,
                     "db patch applied"
                 );
 
@@ -228,6 +267,152 @@ impl DbPatchable for ProviderPatch {
 
                 Ok(())
             }
+
+            ProviderPatch::Claude { id, patch } => {
+                let id = i64::try_from(*id)
+                    .map_err(|_| PolluxError::UnexpectedError(format!("Invalid Claude id {id}")))?;
+
+                let ClaudePatch {
+                    email,
+                    refresh_token,
+                    access_token,
+                    expiry,
+                    status,
+                    labels,
+                } = patch.clone();
+
+                let email_set = email.is_some();
+                let refresh_token_set = refresh_token.is_some();
+                let access_token_set = access_token.is_some();
+                let expiry_set = expiry.is_some();
+                let status_set = status.is_some();
+                let labels_set = labels.is_some();
+                let labels = labels.map(|l| join_labels(&l));
+                let updated_at = Utc::now();
+
+                let affected = with_pool!(pool, |p| {
+                    sqlx::query(&p.sql(
+                        r"
+                        UPDATE claude
+                        SET
+                            email = COALESCE($1, email),
+                            refresh_token = COALESCE($2, refresh_token),
+                            access_token = COALESCE($3, access_token),
+                            expiry = COALESCE($4, expiry),
+                            status = COALESCE($5, status),
+                            labels = COALESCE($6, labels),
+                            updated_at = $7
+                        WHERE id = $8
+                        ",
+                    ))
+                    .bind(email)
+                    .bind(refresh_token)
+                    .bind(access_token)
+                    .bind(expiry)
+                    .bind(status)
+                    .bind(labels)
+                    .bind(updated_at)
+                    .bind(id)
+                    .execute(p)
+                    .await
+                    .map(|r| r.rows_affected())
+                })?;
+
+                debug!(
+                    provider = "claude",
+                    id,
+                    affected,
+                    updated_at = %updated_at,
+                    email_set,
+                    refresh_token_set,
+                    access_token_set,
+                    expiry_set,
+                    status_set,
+                    labels_set,
+                    "db patch applied"
+                );
+
+                if affected == 0 {
+                    return Err(PolluxError::UnexpectedError(format!(
+                        "Claude credential not found for id={id}"
+                    )));
+                }
+
+                Ok(())
+            }
+
+            ProviderPatch::Qwen { id, patch } => {
+                let id = i64::try_from(*id)
+                    .map_err(|_| PolluxError::UnexpectedError(format!("Invalid Qwen id {id}")))?;
+
+                let QwenPatch {
+                    refresh_token,
+                    access_token,
+                    resource_url,
+                    expiry,
+                    status,
+                    labels,
+                } = patch.clone();
+
+                let refresh_token_set = refresh_token.is_some();
+                let access_token_set = access_token.is_some();
+                let resource_url_set = resource_url.is_some();
+                let expiry_set = expiry.is_some();
+                let status_set = status.is_some();
+                let labels_set = labels.is_some();
+                let labels = labels.map(|l| join_labels(&l));
+                let updated_at = Utc::now();
+
+                let affected = with_pool!(pool, |p| {
+                    sqlx::query(&p.sql(
+                        r"
+                        UPDATE qwen
+                        SET
+                            refresh_token = COALESCE($1, refresh_token),
+                            access_token = COALESCE($2, access_token),
+                            resource_url = COALESCE($3, resource_url),
+                            expiry = COALESCE($4, expiry),
+                            status = COALESCE($5, status),
+                            labels = COALESCE($6, labels),
+                            updated_at = $7
+                        WHERE id = $8
+                        ",
+                    ))
+                    .bind(refresh_token)
+                    .bind(access_token)
+                    .bind(resource_url)
+                    .bind(expiry)
+                    .bind(status)
+                    .bind(labels)
+                    .bind(updated_at)
+                    .bind(id)
+                    .execute(p)
+                    .await
+                    .map(|r| r.rows_affected())
+                })?;
+
+                debug!(
+                    provider = "qwen",
+                    id,
+                    affected,
+                    updated_at = %updated_at,
+                    refresh_token_set,
+                    access_token_set,
+                    resource_url_set,
+                    expiry_set,
+                    status_set,
+                    labels_set,
+                    "db patch applied"
+                );
+
+                if affected == 0 {
+                    return Err(PolluxError::UnexpectedError(format!(
+                        "Qwen credential not found for id={id}"
+                    )));
+                }
+
+                Ok(())
+            }
         }
     }
 }
@@ -1,10 +1,18 @@
-//! SQL DDL for initializing the database schema.
-//! SQLite-first design; can be adapted for other RDBMS.
+//! SQL DDL for initializing the database schema (migration version 1).
+//! SQLite-first design; `POSTGRES_INIT` and `MYSQL_INIT` mirror it table for table.
+//! Later changes go in [`crate::db::migrations`], not here.
 
 /// `SQLite` schema includes:
 /// - `gemini_cli` table (Gemini CLI provider, one (sub, `project_id`) per row)
 /// - `codex` table (Codex provider, one (sub, `account_id`) per row)
 /// - `antigravity` table (Antigravity provider, one (sub, `project_id`) per row)
+/// - `usage` table (one row per proxied request; timestamps are unix seconds so
+///   range filters compare the same way on both backends)
+/// - `request_counters` table (running request/error totals per provider and model)
+/// - `thought_signatures` table (captured thought signatures, when thoughtsig storage
+///   is persistent)
+/// - `model_registry` table (model list, stable model ids and registry indices seen
+///   at the last boot)
 pub const SQLITE_INIT: &str = r"
 -- ---------------------------------------------------------------------------
 -- Gemini CLI provider
@@ -63,4 +71,289 @@ CREATE TABLE IF NOT EXISTS antigravity (
 );
 
 CREATE INDEX IF NOT EXISTS idx_antigravity_status ON antigravity(status);
+
+-- ---------------------------------------------------------------------------
+-- Per-request usage accounting (append-only)
+-- ---------------------------------------------------------------------------
+CREATE TABLE IF NOT EXISTS usage (
+    id INTEGER PRIMARY KEY NOT NULL,
+    created_at INTEGER NOT NULL, -- unix seconds
+    provider TEXT NOT NULL,
+    model TEXT NOT NULL,
+    credential_id INTEGER NULL,
+    prompt_tokens INTEGER NOT NULL DEFAULT 0,
+    output_tokens INTEGER NOT NULL DEFAULT 0,
+    latency_ms INTEGER NOT NULL,
+    status INTEGER NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_usage_created_at ON usage(created_at);
+
+-- ---------------------------------------------------------------------------
+-- Cumulative request counters per (provider, model), flushed periodically
+-- ---------------------------------------------------------------------------
+CREATE TABLE IF NOT EXISTS request_counters (
+    provider TEXT NOT NULL,
+    model TEXT NOT NULL,
+    requests INTEGER NOT NULL DEFAULT 0,
+    errors INTEGER NOT NULL DEFAULT 0,
+    updated_at INTEGER NOT NULL, -- unix seconds
+    PRIMARY KEY (provider, model)
+);
+
+-- ---------------------------------------------------------------------------
+-- Captured thought signatures (persistent thoughtsig storage only)
+-- ---------------------------------------------------------------------------
+CREATE TABLE IF NOT EXISTS thought_signatures (
+    provider TEXT NOT NULL,
+    cache_key INTEGER NOT NULL, -- u64 fingerprint stored as its signed bit pattern
+    signature TEXT NOT NULL,
+    created_at INTEGER NOT NULL, -- unix seconds
+    PRIMARY KEY (provider, cache_key)
+);
+
+CREATE INDEX IF NOT EXISTS idx_thought_signatures_created_at
+    ON thought_signatures(provider, created_at);
+
+-- ---------------------------------------------------------------------------
+-- Configured models and their registry index as of the last boot
+-- ---------------------------------------------------------------------------
+CREATE TABLE IF NOT EXISTS model_registry (
+    provider TEXT NOT NULL,
+    model TEXT NOT NULL,
+    model_id INTEGER NOT NULL, -- stable ModelId stored as its signed bit pattern
+    idx INTEGER NOT NULL,
+    PRIMARY KEY (provider, model)
+);
+";
+
+/// `PostgreSQL` schema, equivalent to [`SQLITE_INIT`].
+///
+/// Timestamps are `TIMESTAMPTZ` and ids are `BIGSERIAL`; row shapes match
+/// the `SQLite` tables so the same models decode from either backend.
+pub const POSTGRES_INIT: &str = r"
+-- ---------------------------------------------------------------------------
+-- Gemini CLI provider
+-- ---------------------------------------------------------------------------
+CREATE TABLE IF NOT EXISTS gemini_cli (
+    id BIGSERIAL PRIMARY KEY,
+    email TEXT NULL,
+    sub TEXT NOT NULL,
+    project_id TEXT NOT NULL,
+    refresh_token TEXT NOT NULL,
+    access_token TEXT NULL,
+    expiry TIMESTAMPTZ NOT NULL,
+    status BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at TIMESTAMPTZ NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL,
+    UNIQUE(sub, project_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_gemini_cli_status ON gemini_cli(status);
+
+-- ---------------------------------------------------------------------------
+-- Codex provider (one (sub, account_id) per row)
+-- ---------------------------------------------------------------------------
+CREATE TABLE IF NOT EXISTS codex (
+    id BIGSERIAL PRIMARY KEY,
+    email TEXT NULL,
+    sub TEXT NOT NULL,
+    account_id TEXT NOT NULL,
+    refresh_token TEXT NOT NULL,
+    access_token TEXT NOT NULL,
+    expiry TIMESTAMPTZ NOT NULL,
+    chatgpt_plan_type TEXT NULL,
+    status BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at TIMESTAMPTZ NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL,
+    UNIQUE(sub, account_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_codex_status ON codex(status);
+
+-- ---------------------------------------------------------------------------
+-- Antigravity provider (one (sub, project_id) per row)
+-- ---------------------------------------------------------------------------
+CREATE TABLE IF NOT EXISTS antigravity (
+    id BIGSERIAL PRIMARY KEY,
+    email TEXT NULL,
+    sub TEXT NOT NULL,
+    project_id TEXT NOT NULL,
+    refresh_token TEXT NOT NULL,
+    access_token TEXT NULL,
+    expiry TIMESTAMPTZ NOT NULL,
+    status BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at TIMESTAMPTZ NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL,
+    UNIQUE(sub, project_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_antigravity_status ON antigravity(status);
+
+-- ---------------------------------------------------------------------------
+-- Per-request usage accounting (append-only)
+-- ---------------------------------------------------------------------------
+CREATE TABLE IF NOT EXISTS usage (
+    id BIGSERIAL PRIMARY KEY,
+    created_at BIGINT NOT NULL, -- unix seconds
+    provider TEXT NOT NULL,
+    model TEXT NOT NULL,
+    credential_id BIGINT NULL,
+    prompt_tokens BIGINT NOT NULL DEFAULT 0,
+    output_tokens BIGINT NOT NULL DEFAULT 0,
+    latency_ms BIGINT NOT NULL,
+    status INTEGER NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_usage_created_at ON usage(created_at);
+
+-- ---------------------------------------------------------------------------
+-- Cumulative request counters per (provider, model), flushed periodically
+-- ---------------------------------------------------------------------------
+CREATE TABLE IF NOT EXISTS request_counters (
+    provider TEXT NOT NULL,
+    model TEXT NOT NULL,
+    requests BIGINT NOT NULL DEFAULT 0,
+    errors BIGINT NOT NULL DEFAULT 0,
+    updated_at BIGINT NOT NULL, -- unix seconds
+    PRIMARY KEY (provider, model)
+);
+
+-- ---------------------------------------------------------------------------
+-- Captured thought signatures (persistent thoughtsig storage only)
+-- ---------------------------------------------------------------------------
+CREATE TABLE IF NOT EXISTS thought_signatures (
+    provider TEXT NOT NULL,
+    cache_key BIGINT NOT NULL, -- u64 fingerprint stored as its signed bit pattern
+    signature TEXT NOT NULL,
+    created_at BIGINT NOT NULL, -- unix seconds
+    PRIMARY KEY (provider, cache_key)
+);
+
+CREATE INDEX IF NOT EXISTS idx_thought_signatures_created_at
+    ON thought_signatures(provider, created_at);
+
+-- ---------------------------------------------------------------------------
+-- Configured models and their registry index as of the last boot
+-- ---------------------------------------------------------------------------
+CREATE TABLE IF NOT EXISTS model_registry (
+    provider TEXT NOT NULL,
+    model TEXT NOT NULL,
+    model_id BIGINT NOT NULL, -- stable ModelId stored as its signed bit pattern
+    idx BIGINT NOT NULL,
+    PRIMARY KEY (provider, model)
+);
+";
+
+/// `MySQL`/`MariaDB` schema, equivalent to [`SQLITE_INIT`].
+///
+/// Indexes are declared inline (`MySQL` has no `CREATE INDEX IF NOT EXISTS`),
+/// keyed text columns are `VARCHAR` so they fit an index, and `usage` is
+/// quoted since it is a reserved word.
+pub const MYSQL_INIT: &str = r"
+-- ---------------------------------------------------------------------------
+-- Gemini CLI provider
+-- ---------------------------------------------------------------------------
+CREATE TABLE IF NOT EXISTS gemini_cli (
+    id BIGINT NOT NULL AUTO_INCREMENT PRIMARY KEY,
+    email TEXT NULL,
+    sub VARCHAR(191) NOT NULL,
+    project_id VARCHAR(191) NOT NULL,
+    refresh_token TEXT NOT NULL,
+    access_token TEXT NULL,
+    expiry DATETIME(6) NOT NULL,
+    status BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at DATETIME(6) NOT NULL,
+    updated_at DATETIME(6) NOT NULL,
+    UNIQUE KEY uq_gemini_cli_identity (sub, project_id),
+    KEY idx_gemini_cli_status (status)
+);
+
+-- ---------------------------------------------------------------------------
+-- Codex provider (one (sub, account_id) per row)
+-- ---------------------------------------------------------------------------
+CREATE TABLE IF NOT EXISTS codex (
+    id BIGINT NOT NULL AUTO_INCREMENT PRIMARY KEY,
+    email TEXT NULL,
+    sub VARCHAR(191) NOT NULL,
+    account_id VARCHAR(191) NOT NULL,
+    refresh_token TEXT NOT NULL,
+    access_token TEXT NOT NULL,
+    expiry DATETIME(6) NOT NULL,
+    chatgpt_plan_type TEXT NULL,
+    status BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at DATETIME(6) NOT NULL,
+    updated_at DATETIME(6) NOT NULL,
+    UNIQUE KEY uq_codex_identity (sub, account_id),
+    KEY idx_codex_status (status)
+);
+
+-- ---------------------------------------------------------------------------
+-- Antigravity provider (one (sub, project_id) per row)
+-- ---------------------------------------------------------------------------
+CREATE TABLE IF NOT EXISTS antigravity (
+    id BIGINT NOT NULL AUTO_INCREMENT PRIMARY KEY,
+    email TEXT NULL,
+    sub VARCHAR(191) NOT NULL,
+    project_id VARCHAR(191) NOT NULL,
+    refresh_token TEXT NOT NULL,
+    access_token TEXT NULL,
+    expiry DATETIME(6) NOT NULL,
+    status BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at DATETIME(6) NOT NULL,
+    updated_at DATETIME(6) NOT NULL,
+    UNIQUE KEY uq_antigravity_identity (sub, project_id),
+    KEY idx_antigravity_status (status)
+);
+
+-- ---------------------------------------------------------------------------
+-- Per-request usage accounting (append-only)
+-- ---------------------------------------------------------------------------
+CREATE TABLE IF NOT EXISTS `usage` (
+    id BIGINT NOT NULL AUTO_INCREMENT PRIMARY KEY,
+    created_at BIGINT NOT NULL, -- unix seconds
+    provider VARCHAR(64) NOT NULL,
+    model VARCHAR(191) NOT NULL,
+    credential_id BIGINT NULL,
+    prompt_tokens BIGINT NOT NULL DEFAULT 0,
+    output_tokens BIGINT NOT NULL DEFAULT 0,
+    latency_ms BIGINT NOT NULL,
+    status INT NOT NULL,
+    KEY idx_usage_created_at (created_at)
+);
+
+-- ---------------------------------------------------------------------------
+-- Cumulative request counters per (provider, model), flushed periodically
+-- ---------------------------------------------------------------------------
+CREATE TABLE IF NOT EXISTS request_counters (
+    provider VARCHAR(64) NOT NULL,
+    model VARCHAR(191) NOT NULL,
+    requests BIGINT NOT NULL DEFAULT 0,
+    errors BIGINT NOT NULL DEFAULT 0,
+    updated_at BIGINT NOT NULL, -- unix seconds
+    PRIMARY KEY (provider, model)
+);
+
+-- ---------------------------------------------------------------------------
+-- Captured thought signatures (persistent thoughtsig storage only)
+-- ---------------------------------------------------------------------------
+CREATE TABLE IF NOT EXISTS thought_signatures (
+    provider VARCHAR(64) NOT NULL,
+    cache_key BIGINT NOT NULL, -- u64 fingerprint stored as its signed bit pattern
+    signature TEXT NOT NULL,
+    created_at BIGINT NOT NULL, -- unix seconds
+    PRIMARY KEY (provider, cache_key),
+    KEY idx_thought_signatures_created_at (provider, created_at)
+);
+
+-- ---------------------------------------------------------------------------
+-- Configured models and their registry index as of the last boot
+-- ---------------------------------------------------------------------------
+CREATE TABLE IF NOT EXISTS model_registry (
+    provider VARCHAR(64) NOT NULL,
+    model VARCHAR(191) NOT NULL,
+    model_id BIGINT NOT NULL, -- stable ModelId stored as its signed bit pattern
+    idx BIGINT NOT NULL,
+    PRIMARY KEY (provider, model)
+);
 ";
@@ -1,2 +1,159 @@
 // Keep `crate::db::traits::DbPatchable` working, but avoid duplicating the trait.
 pub use crate::patches::DbPatchable;
+
+use sqlx::query::Query;
+use sqlx::{Database, Row};
+use std::borrow::Cow;
+
+/// Dialect hooks for the concrete pool a query runs on.
+///
+/// Queries are written once in the `SQLite`/`PostgreSQL` dialect: `$N`
+/// placeholders, each used once and in order; `"quoted"` identifiers;
+/// `ON CONFLICT (...) DO UPDATE SET col = excluded.col` upserts; and
+/// `CAST(... AS BIGINT)`. [`SqlDialect::sql`] rewrites them for backends that
+/// speak something else.
+pub trait SqlDialect {
+    type Db: Database;
+
+    /// The query as this backend expects it.
+    fn sql<'q>(&self, query: &'q str) -> Cow<'q, str> {
+        Cow::Borrowed(query)
+    }
+
+    /// Run an `INSERT ... RETURNING id` (passed through [`Self::sql`]) and
+    /// return the id of the inserted or updated row.
+    fn insert_id<'q>(
+        &self,
+        query: Query<'q, Self::Db, <Self::Db as Database>::Arguments<'q>>,
+    ) -> impl Future<Output = Result<i64, sqlx::Error>> + Send;
+}
+
+impl SqlDialect for sqlx::SqlitePool {
+    type Db = sqlx::Sqlite;
+
+    async fn insert_id<'q>(
+        &self,
+        query: Query<'q, sqlx::Sqlite, sqlx::sqlite::SqliteArguments<'q>>,
+    ) -> Result<i64, sqlx::Error> {
+        query.fetch_one(self).await?.try_get(0)
+    }
+}
+
+impl SqlDialect for sqlx::PgPool {
+    type Db = sqlx::Postgres;
+
+    async fn insert_id(
+        &self,
+        query: Query<'_, sqlx::Postgres, sqlx::postgres::PgArguments>,
+    ) -> Result<i64, sqlx::Error> {
+        query.fetch_one(self).await?.try_get(0)
+    }
+}
+
+#[cfg(feature = "mysql")]
+impl SqlDialect for sqlx::MySqlPool {
+    type Db = sqlx::MySql;
+
+    fn sql<'q>(&self, query: &'q str) -> Cow<'q, str> {
+        Cow::Owned(mysql_sql(query))
+    }
+
+    /// `MySQL` has no `RETURNING`; [`mysql_sql`] makes the upsert set
+    /// `LAST_INSERT_ID(id)` instead, which the driver reports back.
+    async fn insert_id(
+        &self,
+        query: Query<'_, sqlx::MySql, sqlx::mysql::MySqlArguments>,
+    ) -> Result<i64, sqlx::Error> {
+        let id = query.execute(self).await?.last_insert_id();
+        i64::try_from(id).map_err(|e| sqlx::Error::Decode(Box::new(e)))
+    }
+}
+
+/// Rewrite a query for `MySQL`/`MariaDB`:
+/// - `$N` => `?` (placeholders must already appear once each, in order)
+/// - `"ident"` => `` `ident` ``
+/// - `CAST(... AS BIGINT)` => `CAST(... AS SIGNED)`
+/// - `ON CONFLICT (...) DO UPDATE SET` => `ON DUPLICATE KEY UPDATE`, with
+///   `excluded.col` => `VALUES(col)`
+/// - a trailing `RETURNING id` => `id = LAST_INSERT_ID(id)` in the update list
+#[cfg_attr(not(feature = "mysql"), allow(dead_code))]
+pub(crate) fn mysql_sql(query: &str) -> String {
+    let returning_id = query.trim_end().ends_with("RETURNING id");
+    let query = if returning_id {
+        query.trim_end().trim_end_matches("RETURNING id").trim_end()
+    } else {
+        query
+    };
+
+    let mut out = String::with_capacity(query.len());
+    let mut chars = query.chars().peekable();
+    let mut in_literal = false;
+    while let Some(c) = chars.next() {
+        match c {
+            '\'' => {
+                in_literal = !in_literal;
+                out.push(c);
+            }
+            '$' if !in_literal && chars.peek().is_some_and(char::is_ascii_digit) => {
+                while chars.peek().is_some_and(char::is_ascii_digit) {
+                    chars.next();
+                }
+                out.push('?');
+            }
+            '"' if !in_literal => out.push('`'),
+            _ => out.push(c),
+        }
+    }
+
+    let mut out = out.replace(" AS BIGINT)", " AS SIGNED)");
+    if let Some(start) = out.find("ON CONFLICT") {
+        const DO_UPDATE: &str = "DO UPDATE SET";
+        if let Some(offset) = out[start..].find(DO_UPDATE) {
+            let end = start + offset + DO_UPDATE.len();
+            let mut clause = String::from("ON DUPLICATE KEY UPDATE");
+            if returning_id {
+                clause.push_str(" id = LAST_INSERT_ID(id),");
+            }
+            out.replace_range(start..end, &clause);
+        }
+    }
+    while let Some(start) = out.find("excluded.") {
+        let col_start = start + "excluded.".len();
+        let col_end = out[col_start..]
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+            .map_or(out.len(), |i| col_start + i);
+        let col = out[col_start..col_end].to_string();
+        out.replace_range(start..col_end, &format!("VALUES({col})"));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::mysql_sql;
+
+    #[test]
+    fn mysql_rewrites_placeholders_quotes_and_casts() {
+        assert_eq!(
+            mysql_sql(r#"SELECT CAST(SUM(x) AS BIGINT) FROM "usage" WHERE a = $1 AND b = '$2'"#),
+            "SELECT CAST(SUM(x) AS SIGNED) FROM `usage` WHERE a = ? AND b = '$2'"
+        );
+    }
+
+    #[test]
+    fn mysql_rewrites_upserts_returning_id() {
+        let sql = mysql_sql(
+            "INSERT INTO t (a, b) VALUES ($1, $2)
+            ON CONFLICT(a) DO UPDATE SET
+                b = COALESCE(excluded.b, t.b)
+            RETURNING id
+            ",
+        );
+        assert_eq!(
+            sql,
+            "INSERT INTO t (a, b) VALUES (?, ?)
+            ON DUPLICATE KEY UPDATE id = LAST_INSERT_ID(id),
+                b = COALESCE(VALUES(b), t.b)"
+        );
+    }
+}
@@ -0,0 +1,288 @@
+use axum::{
+    Json,
+    extract::rejection::JsonRejection,
+    http::StatusCode,
+    response::{IntoResponse, Response, sse::Event},
+};
+use std::time::Duration;
+use thiserror::Error as ThisError;
+
+use super::{IsRetryable, set_retry_after};
+use crate::providers::UPSTREAM_BODY_PREVIEW_CHARS;
+use crate::utils::logging::body_preview;
+use pollux_schema::anthropic::AnthropicErrorBody;
+
+#[derive(Debug, ThisError)]
+pub(crate) enum ClaudeError {
+    #[error("Request rejected")]
+    RequestRejected {
+        status: StatusCode,
+        body: AnthropicErrorBody,
+        debug_message: Option<String>,
+    },
+
+    /// No usable credential is currently available.
+    #[error("No available credential")]
+    NoAvailableCredential,
+
+    /// Upstream error that matched a provider mapping rule.
+    #[error("Upstream mapped error: status={status}, body={body:?}")]
+    UpstreamMappedError {
+        status: StatusCode,
+        body: AnthropicErrorBody,
+    },
+
+    /// Upstream fallback error (rule unmatched or body unstructured).
+    #[error("Upstream fallback error: status={status}, body={body:.200}")]
+    UpstreamFallbackError {
+        status: StatusCode,
+        /// Raw upstream body is preserved for internal diagnostics/logging only.
+        body: String,
+    },
+
+    /// JSON serialization or parsing failure while preparing provider payloads.
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+
+    /// Transport-level failure (DNS, connect, timeouts, etc).
+    #[error("HTTP request error: {0}")]
+    Reqwest(#[from] reqwest::Error),
+
+    #[error("Stream protocol error: {0}")]
+    StreamProtocolError(String),
+
+    #[error("Internal error: {0}")]
+    Internal(String),
+
+    /// The pool gave up; a cooling credential frees up again after `after`.
+    #[error("{error} (retry after {after:?})")]
+    RetryAfter {
+        error: Box<ClaudeError>,
+        after: Duration,
+    },
+}
+
+impl ClaudeError {
+    /// Client-side rejection rendered as an `invalid_request_error`.
+    pub(crate) fn invalid_request(message: impl Into<String>) -> Self {
+        ClaudeError::RequestRejected {
+            status: StatusCode::BAD_REQUEST,
+            body: AnthropicErrorBody::new("invalid_request_error", message),
+            debug_message: None,
+        }
+    }
+
+    /// The pool could not take the request: no credential left for the model,
+    /// or still rate limited after the provider's own retries.
+    pub(crate) fn pool_gave_up(&self) -> bool {
+        match self {
+            ClaudeError::NoAvailableCredential | ClaudeError::RetryAfter { .. } => true,
+            ClaudeError::UpstreamFallbackError { status, .. }
+            | ClaudeError::UpstreamMappedError { status, .. } => {
+                *status == StatusCode::TOO_MANY_REQUESTS
+            }
+            _ => false,
+        }
+    }
+
+    /// Attach the pool's cooldown hint, if any, to an error it gave up with.
+    pub(crate) fn with_retry_after(self, after: Option<Duration>) -> Self {
+        match after {
+            Some(after) if !matches!(self, ClaudeError::RetryAfter { .. }) => {
+                ClaudeError::RetryAfter {
+                    error: Box::new(self),
+                    after,
+                }
+            }
+            _ => self,
+        }
+    }
+}
+
+impl From<JsonRejection> for ClaudeError {
+    fn from(rejection: JsonRejection) -> Self {
+        let debug_message = Some(rejection.to_string());
+        let (status, message) = match rejection {
+            JsonRejection::BytesRejection(_) => {
+                (StatusCode::PAYLOAD_TOO_LARGE, "request body too large")
+            }
+            JsonRejection::JsonSyntaxError(_) => (StatusCode::BAD_REQUEST, "invalid JSON"),
+            _ => (StatusCode::BAD_REQUEST, "invalid request"),
+        };
+        let r#type = if status == StatusCode::PAYLOAD_TOO_LARGE {
+            "request_too_large"
+        } else {
+            "invalid_request_error"
+        };
+        ClaudeError::RequestRejected {
+            status,
+            body: AnthropicErrorBody::new(r#type, message),
+            debug_message,
+        }
+    }
+}
+
+impl IntoResponse for ClaudeError {
+    fn into_response(self) -> Response {
+        if let ClaudeError::RetryAfter { error, after } = self {
+            let mut resp = error.into_response();
+            set_retry_after(&mut resp, after);
+            return resp;
+        }
+        let (status, body) = self.into_parts();
+        (status, Json(body)).into_response()
+    }
+}
+
+impl ClaudeError {
+    fn into_parts(self) -> (StatusCode, AnthropicErrorBody) {
+        match self {
+            ClaudeError::RetryAfter { error, .. } => error.into_parts(),
+
+            ClaudeError::RequestRejected {
+                status,
+                body,
+                debug_message,
+            } => {
+                tracing::warn!(
+                    status = %status,
+                    r#type = %body.error.r#type,
+                    message = %body.error.message,
+                    debug_message = ?debug_message,
+                    "Claude request rejected"
+                );
+                (status, body)
+            }
+
+            ClaudeError::UpstreamMappedError { status, body } => {
+                tracing::warn!(
+                    status = %status,
+                    r#type = %body.error.r#type,
+                    message = %body.error.message,
+                    "Claude upstream mapped error"
+                );
+                // Drop upstream-only fields such as `request_id`.
+                let cleaned = AnthropicErrorBody::new(body.error.r#type, body.error.message);
+                (status, cleaned)
+            }
+
+            ClaudeError::UpstreamFallbackError { status, body } => {
+                tracing::warn!(
+                    status = %status,
+                    raw_body = %body_preview(&body, UPSTREAM_BODY_PREVIEW_CHARS),
+                    "Claude upstream fallback error"
+                );
+                (
+                    status,
+                    AnthropicErrorBody::new("api_error", format!("Upstream returned {status}")),
+                )
+            }
+
+            ClaudeError::NoAvailableCredential => (
+                StatusCode::SERVICE_UNAVAILABLE,
+                AnthropicErrorBody::new(
+                    "overloaded_error",
+                    "No available credentials to process the request.",
+                ),
+            ),
+
+            ClaudeError::Reqwest(e) => {
+                tracing::warn!(error = %e, status = ?e.status(), "Claude reqwest error");
+                (
+                    StatusCode::BAD_GATEWAY,
+                    AnthropicErrorBody::new("api_error", "Upstream service error."),
+                )
+            }
+
+            ClaudeError::Json(e) => {
+                tracing::error!(error = %e, "Claude JSON error");
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    AnthropicErrorBody::new("api_error", "An internal server error occurred."),
+                )
+            }
+
+            ClaudeError::StreamProtocolError(e) => {
+                tracing::warn!(error = %e, "Claude stream protocol error");
+                (
+                    StatusCode::BAD_GATEWAY,
+                    AnthropicErrorBody::new("api_error", "Upstream stream protocol error."),
+                )
+            }
+
+            ClaudeError::Internal(e) => {
+                tracing::error!(error = %e, "Claude internal error");
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    AnthropicErrorBody::new("api_error", "An internal server error occurred."),
+                )
+            }
+        }
+    }
+
+    /// Final `error` event, as the Messages API sends it, for a stream that
+    /// broke after its headers were sent.
+    pub(crate) fn into_sse_event(self) -> Event {
+        let (_, body) = self.into_parts();
+        Event::default()
+            .event("error")
+            .json_data(&body)
+            .unwrap_or_else(|_| Event::default().event("error"))
+    }
+}
+
+impl From<crate::PolluxError> for ClaudeError {
+    fn from(err: crate::PolluxError) -> Self {
+        match err {
+            crate::PolluxError::NoAvailableCredential => ClaudeError::NoAvailableCredential,
+            crate::PolluxError::RetryAfter { error, after } => {
+                ClaudeError::from(*error).with_retry_after(Some(after))
+            }
+            crate::PolluxError::ReqwestError(e) => ClaudeError::Reqwest(e),
+            crate::PolluxError::StreamProtocolError(s) => ClaudeError::StreamProtocolError(s),
+            other => ClaudeError::Internal(other.to_string()),
+        }
+    }
+}
+
+impl IsRetryable for ClaudeError {
+    fn is_retryable(&self) -> bool {
+        match self {
+            ClaudeError::UpstreamFallbackError { status, .. } => matches!(
+                *status,
+                StatusCode::UNAUTHORIZED | StatusCode::TOO_MANY_REQUESTS | StatusCode::FORBIDDEN
+            ),
+            ClaudeError::UpstreamMappedError { status, body } => match *status {
+                // The model is missing for this account only; another may serve it.
+                StatusCode::NOT_FOUND => body.error.r#type == "not_found_error",
+
+                StatusCode::UNAUTHORIZED
+                | StatusCode::TOO_MANY_REQUESTS
+                | StatusCode::FORBIDDEN => true,
+
+                _ => false,
+            },
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn no_available_credential_renders_anthropic_error_envelope() {
+        let resp = ClaudeError::NoAvailableCredential
+            .with_retry_after(Some(Duration::from_secs(30)))
+            .into_response();
+
+        assert_eq!(resp.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(
+            resp.headers()
+                .get(axum::http::header::RETRY_AFTER)
+                .and_then(|v| v.to_str().ok()),
+            Some("30")
+        );
+    }
+}
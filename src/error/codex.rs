@@ -2,12 +2,15 @@ use axum::{
     Json,
     extract::rejection::JsonRejection,
     http::StatusCode,
-    response::{IntoResponse, Response},
+    response::{IntoResponse, Response, sse::Event},
 };
+use serde_json::json;
+use std::time::Duration;
 use thiserror::Error as ThisError;
 
-use super::IsRetryable;
+use super::{IsRetryable, chat_error_event, set_retry_after};
 use crate::providers::UPSTREAM_BODY_PREVIEW_CHARS;
+use crate::utils::logging::body_preview;
 use pollux_schema::{CodexErrorBody, OpenaiResponsesErrorBody, OpenaiResponsesErrorObject};
 
 #[derive(Debug, ThisError)]
@@ -51,6 +54,41 @@ pub(crate) enum CodexError {
 
     #[error("Internal error: {0}")]
     Internal(String),
+
+    /// The pool gave up; a cooling credential frees up again after `after`.
+    #[error("{error} (retry after {after:?})")]
+    RetryAfter {
+        error: Box<CodexError>,
+        after: Duration,
+    },
+}
+
+impl CodexError {
+    /// The pool could not take the request: no credential left for the model,
+    /// or still rate limited after the provider's own retries.
+    pub(crate) fn pool_gave_up(&self) -> bool {
+        match self {
+            CodexError::NoAvailableCredential | CodexError::RetryAfter { .. } => true,
+            CodexError::UpstreamFallbackError { status, .. }
+            | CodexError::UpstreamMappedError { status, .. } => {
+                *status == StatusCode::TOO_MANY_REQUESTS
+            }
+            _ => false,
+        }
+    }
+
+    /// Attach the pool's cooldown hint, if any, to an error it gave up with.
+    pub(crate) fn with_retry_after(self, after: Option<Duration>) -> Self {
+        match after {
+            Some(after) if !matches!(self, CodexError::RetryAfter { .. }) => {
+                CodexError::RetryAfter {
+                    error: Box::new(self),
+                    after,
+                }
+            }
+            _ => self,
+        }
+    }
 }
 
 impl From<JsonRejection> for CodexError {
@@ -92,9 +130,24 @@ impl From<JsonRejection> for CodexError {
 }
 
 impl IntoResponse for CodexError {
-    #[allow(clippy::too_many_lines)]
     fn into_response(self) -> Response {
-        let (status, error_body) = match self {
+        if let CodexError::RetryAfter { error, after } = self {
+            let mut resp = error.into_response();
+            set_retry_after(&mut resp, after);
+            return resp;
+        }
+        let (status, error_body) = self.into_parts();
+        let resp_json = OpenaiResponsesErrorBody { inner: error_body };
+        (status, Json(resp_json)).into_response()
+    }
+}
+
+impl CodexError {
+    #[allow(clippy::too_many_lines)]
+    fn into_parts(self) -> (StatusCode, OpenaiResponsesErrorObject) {
+        match self {
+            CodexError::RetryAfter { error, .. } => error.into_parts(),
+
             CodexError::RequestRejected {
                 status,
                 body,
@@ -142,7 +195,7 @@ impl IntoResponse for CodexError {
                     status = %status,
                     code = ?error_body.code,
                     message = %error_body.message,
-                    raw_body = %format!("{:.len$}", body, len = UPSTREAM_BODY_PREVIEW_CHARS),
+                    raw_body = %body_preview(&body, UPSTREAM_BODY_PREVIEW_CHARS),
 
                     "Codex upstream fallback error"
                 );
@@ -210,10 +263,31 @@ impl IntoResponse for CodexError {
                     },
                 )
             }
-        };
+        }
+    }
 
-        let resp_json = OpenaiResponsesErrorBody { inner: error_body };
-        (status, Json(resp_json)).into_response()
+    /// Final `response.failed` event for a Responses stream that broke after
+    /// its headers were sent.
+    pub(crate) fn into_failed_event(self) -> Event {
+        let (_, error) = self.into_parts();
+        let data = json!({
+            "type": "response.failed",
+            "response": {
+                "object": "response",
+                "status": "failed",
+                "error": { "code": error.code, "message": error.message },
+            },
+        });
+        Event::default()
+            .event("response.failed")
+            .data(data.to_string())
+    }
+
+    /// Final `{"error": ...}` chunk for a Chat Completions stream that broke
+    /// after its headers were sent.
+    pub(crate) fn into_chat_error_event(self) -> Event {
+        let (_, error_body) = self.into_parts();
+        chat_error_event(&OpenaiResponsesErrorBody { inner: error_body })
     }
 }
 
@@ -221,6 +295,9 @@ impl From<crate::PolluxError> for CodexError {
     fn from(err: crate::PolluxError) -> Self {
         match err {
             crate::PolluxError::NoAvailableCredential => CodexError::NoAvailableCredential,
+            crate::PolluxError::RetryAfter { error, after } => {
+                CodexError::from(*error).with_retry_after(Some(after))
+            }
             crate::PolluxError::ReqwestError(e) => CodexError::Reqwest(e),
             crate::PolluxError::StreamProtocolError(s) => CodexError::StreamProtocolError(s),
             other => CodexError::Internal(other.to_string()),
@@ -274,4 +351,23 @@ mod tests {
 
         assert!(error.is_retryable());
     }
+
+    #[tokio::test]
+    async fn failed_event_carries_the_error_object() {
+        use axum::response::sse::Sse;
+        let event = CodexError::StreamProtocolError("boom".to_string()).into_failed_event();
+        let stream = futures::stream::iter([Ok::<_, std::convert::Infallible>(event)]);
+        let body = Sse::new(stream).into_response().into_body();
+        let bytes = axum::body::to_bytes(body, usize::MAX).await.unwrap();
+        let text = String::from_utf8(bytes.to_vec()).unwrap();
+
+        let data = text
+            .strip_prefix("event: response.failed\ndata: ")
+            .and_then(|rest| rest.strip_suffix("\n\n"))
+            .expect("single response.failed event");
+        let value: serde_json::Value = serde_json::from_str(data).unwrap();
+        assert_eq!(value["type"], "response.failed");
+        assert_eq!(value["response"]["status"], "failed");
+        assert!(value["response"]["error"]["message"].is_string());
+    }
 }
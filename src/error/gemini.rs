@@ -1,19 +1,20 @@
-use super::IsRetryable;
+use super::{IsRetryable, retry_after_secs, set_retry_after};
 use axum::{
     Json,
     extract::rejection::JsonRejection,
     http::StatusCode,
-    response::{IntoResponse, Response},
+    response::{IntoResponse, Response, sse::Event},
 };
 use chrono::{DateTime, Utc};
 use rand::Rng;
 use serde::{Deserialize, Serialize};
-use serde_json::Value;
+use serde_json::{Value, json};
 use std::collections::BTreeMap;
 use std::time::Duration;
 use thiserror::Error as ThisError;
 
 use crate::providers::{ActionForError, MappingAction, UPSTREAM_BODY_PREVIEW_CHARS};
+use crate::utils::logging::body_preview;
 
 #[derive(Debug, ThisError)]
 pub enum GeminiCliError {
@@ -56,6 +57,41 @@ pub enum GeminiCliError {
 
     #[error("Internal error: {0}")]
     Internal(String),
+
+    /// The pool gave up; a cooling credential frees up again after `after`.
+    #[error("{error} (retry after {after:?})")]
+    RetryAfter {
+        error: Box<GeminiCliError>,
+        after: Duration,
+    },
+}
+
+impl GeminiCliError {
+    /// The pool could not take the request: no credential left for the model,
+    /// or still rate limited after the provider's own retries.
+    pub(crate) fn pool_gave_up(&self) -> bool {
+        match self {
+            GeminiCliError::NoAvailableCredential | GeminiCliError::RetryAfter { .. } => true,
+            GeminiCliError::UpstreamFallbackError { status, .. }
+            | GeminiCliError::UpstreamMappedError { status, .. } => {
+                *status == StatusCode::TOO_MANY_REQUESTS
+            }
+            _ => false,
+        }
+    }
+
+    /// Attach the pool's cooldown hint, if any, to an error it gave up with.
+    pub(crate) fn with_retry_after(self, after: Option<Duration>) -> Self {
+        match after {
+            Some(after) if !matches!(self, GeminiCliError::RetryAfter { .. }) => {
+                GeminiCliError::RetryAfter {
+                    error: Box::new(self),
+                    after,
+                }
+            }
+            _ => self,
+        }
+    }
 }
 
 impl From<JsonRejection> for GeminiCliError {
@@ -94,9 +130,32 @@ impl From<JsonRejection> for GeminiCliError {
 }
 
 impl IntoResponse for GeminiCliError {
-    #[allow(clippy::too_many_lines)]
     fn into_response(self) -> Response {
-        let (status, error_body) = match self {
+        let retry_after = match &self {
+            GeminiCliError::RetryAfter { after, .. } => Some(*after),
+            _ => None,
+        };
+        let (status, error_body) = self.into_parts();
+        let mut resp = (status, Json(GeminiErrorBody { inner: error_body })).into_response();
+        if let Some(after) = retry_after {
+            set_retry_after(&mut resp, after);
+        }
+        resp
+    }
+}
+
+impl GeminiCliError {
+    /// Final `data: {"error": ...}` event for a stream that fails mid-flight.
+    pub fn into_sse_event(self) -> Event {
+        let (_, inner) = self.into_parts();
+        Event::default()
+            .json_data(GeminiErrorBody { inner })
+            .unwrap_or_else(|_| Event::default().data("{}"))
+    }
+
+    #[allow(clippy::too_many_lines)]
+    fn into_parts(self) -> (StatusCode, GeminiErrorObject) {
+        match self {
             GeminiCliError::RequestRejected {
                 status,
                 body,
@@ -145,7 +204,7 @@ impl IntoResponse for GeminiCliError {
                 };
                 tracing::warn!(
                     status = %status,
-                    raw_body = %format!("{:.len$}", body, len = UPSTREAM_BODY_PREVIEW_CHARS),
+                    raw_body = %body_preview(&body, UPSTREAM_BODY_PREVIEW_CHARS),
                     "Gemini upstream fallback error"
                 );
                 (
@@ -214,10 +273,16 @@ impl IntoResponse for GeminiCliError {
                     ),
                 )
             }
-        };
 
-        let resp_json = GeminiErrorBody { inner: error_body };
-        (status, Json(resp_json)).into_response()
+            GeminiCliError::RetryAfter { error, after } => {
+                let (status, mut body) = error.into_parts();
+                body.details = Some(vec![json!({
+                    "@type": "type.googleapis.com/google.rpc.RetryInfo",
+                    "retryDelay": format!("{}s", retry_after_secs(after)),
+                })]);
+                (status, body)
+            }
+        }
     }
 }
 
@@ -225,6 +290,9 @@ impl From<crate::PolluxError> for GeminiCliError {
     fn from(err: crate::PolluxError) -> Self {
         match err {
             crate::PolluxError::NoAvailableCredential => GeminiCliError::NoAvailableCredential,
+            crate::PolluxError::RetryAfter { error, after } => {
+                GeminiCliError::from(*error).with_retry_after(Some(after))
+            }
             crate::PolluxError::ReqwestError(e) => GeminiCliError::Reqwest(e),
             crate::PolluxError::StreamProtocolError(s) => GeminiCliError::StreamProtocolError(s),
             other => GeminiCliError::Internal(other.to_string()),
@@ -267,6 +335,9 @@ pub struct GeminiErrorObject {
     pub code: u16,
     pub message: String,
     pub status: String,
+    /// `google.rpc` detail objects, e.g. `RetryInfo` once the pool gave up.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub details: Option<Vec<Value>>,
 }
 
 impl GeminiErrorObject {
@@ -279,6 +350,7 @@ impl GeminiErrorObject {
             code: code.as_u16(),
             message: message.into(),
             status: status.to_string(),
+            details: None,
         }
     }
 }
@@ -302,6 +374,7 @@ impl From<GeminiCliErrorBody> for GeminiErrorBody {
                 status: status
                     .filter(|s| !s.trim().is_empty())
                     .unwrap_or_else(|| "UNKNOWN".to_string()),
+                details: None,
             },
         }
     }
@@ -601,6 +674,23 @@ mod tests {
         );
     }
 
+    #[test]
+    fn retry_after_sets_header_and_retry_info() {
+        let err = GeminiCliError::NoAvailableCredential
+            .with_retry_after(Some(Duration::from_millis(29_500)));
+        assert!(err.pool_gave_up());
+
+        let resp = err.into_response();
+        assert_eq!(resp.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(resp.headers()[axum::http::header::RETRY_AFTER], "30");
+
+        let (_, body) = GeminiCliError::NoAvailableCredential
+            .with_retry_after(Some(Duration::from_secs(30)))
+            .into_parts();
+        let details = body.details.expect("retry info");
+        assert_eq!(details[0]["retryDelay"], "30s");
+    }
+
     #[test]
     fn map_404_not_found() {
         let raw = r#"{
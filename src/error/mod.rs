@@ -1,8 +1,11 @@
+mod claude;
 mod codex;
 mod gemini;
 mod oauth;
 mod pollux;
+mod qwen;
 
+pub(crate) use claude::ClaudeError;
 pub(crate) use codex::CodexError;
 pub use gemini::{
     GeminiCliError, GeminiCliErrorBody, GeminiCliErrorObject, GeminiErrorBody, GeminiErrorObject,
@@ -10,7 +13,30 @@ pub use gemini::{
 };
 pub use oauth::OauthError;
 pub use pollux::{ApiErrorBody, ApiErrorObject, PolluxError};
+pub(crate) use qwen::QwenError;
+
+use axum::http::{HeaderValue, header::RETRY_AFTER};
+use axum::response::{Response, sse::Event};
+use pollux_schema::OpenaiResponsesErrorBody;
+use std::time::Duration;
 
 pub trait IsRetryable {
     fn is_retryable(&self) -> bool;
 }
+
+/// Whole seconds for a `Retry-After` header: rounded up, at least one.
+pub(crate) fn retry_after_secs(wait: Duration) -> u64 {
+    (wait.as_secs() + u64::from(wait.subsec_nanos() > 0)).max(1)
+}
+
+/// `data: {"error": ...}` chunk ending an OpenAI-style stream early.
+pub(crate) fn chat_error_event(body: &OpenaiResponsesErrorBody) -> Event {
+    Event::default()
+        .json_data(body)
+        .unwrap_or_else(|_| Event::default().data("{}"))
+}
+
+pub(crate) fn set_retry_after(resp: &mut Response, wait: Duration) {
+    resp.headers_mut()
+        .insert(RETRY_AFTER, HeaderValue::from(retry_after_secs(wait)));
+}
@@ -1,10 +1,11 @@
 use axum::{Json, http::StatusCode, response::IntoResponse};
 use serde::Serialize;
 use serde_json::Value;
+use std::time::Duration;
 use thiserror::Error as ThisError;
 
-use super::IsRetryable;
 use super::oauth::OauthError;
+use super::{IsRetryable, set_retry_after};
 
 #[derive(Debug, ThisError)]
 pub enum PolluxError {
@@ -41,18 +42,58 @@ pub enum PolluxError {
     #[error("No available credential")]
     NoAvailableCredential,
 
+    #[error("Not found: {0}")]
+    NotFound(String),
+
     #[error("Ractor error: {0}")]
     RactorError(String),
 
     #[error("Database error: {0}")]
     DatabaseError(#[from] sqlx::Error),
+
+    /// The pool gave up; a cooling credential frees up again after `after`.
+    #[error("{error} (retry after {after:?})")]
+    RetryAfter {
+        error: Box<PolluxError>,
+        after: Duration,
+    },
 }
 
-impl PolluxError {}
+impl PolluxError {
+    /// The pool could not take the request: no credential left for the model,
+    /// or still rate limited after the provider's own retries.
+    pub(crate) fn pool_gave_up(&self) -> bool {
+        matches!(
+            self,
+            PolluxError::NoAvailableCredential
+                | PolluxError::RetryAfter { .. }
+                | PolluxError::UpstreamStatus(StatusCode::TOO_MANY_REQUESTS)
+        )
+    }
+
+    /// Attach the pool's cooldown hint, if any, to an error it gave up with.
+    pub(crate) fn with_retry_after(self, after: Option<Duration>) -> Self {
+        match after {
+            Some(after) if !matches!(self, PolluxError::RetryAfter { .. }) => {
+                PolluxError::RetryAfter {
+                    error: Box::new(self),
+                    after,
+                }
+            }
+            _ => self,
+        }
+    }
+}
 
 impl IntoResponse for PolluxError {
     fn into_response(self) -> axum::response::Response {
         let (status, error_body) = match self {
+            PolluxError::RetryAfter { error, after } => {
+                let mut resp = error.into_response();
+                set_retry_after(&mut resp, after);
+                return resp;
+            }
+
             PolluxError::DatabaseError(_)
             | PolluxError::RactorError(_)
             | PolluxError::UnexpectedError(_)
@@ -106,6 +147,16 @@ impl IntoResponse for PolluxError {
                 (status, body)
             }
 
+            PolluxError::NotFound(what) => {
+                let status = StatusCode::NOT_FOUND;
+                let body = ApiErrorObject {
+                    code: "NOT_FOUND".to_string(),
+                    message: format!("Not found: {what}"),
+                    details: None,
+                };
+                (status, body)
+            }
+
             PolluxError::NoAvailableCredential => {
                 let status = StatusCode::SERVICE_UNAVAILABLE;
                 let body = ApiErrorObject {
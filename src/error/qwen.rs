@@ -0,0 +1,328 @@
+use axum::{
+    Json,
+    extract::rejection::JsonRejection,
+    http::StatusCode,
+    response::{IntoResponse, Response, sse::Event},
+};
+use std::time::Duration;
+use thiserror::Error as ThisError;
+
+use super::{IsRetryable, chat_error_event, set_retry_after};
+use crate::providers::UPSTREAM_BODY_PREVIEW_CHARS;
+use crate::utils::logging::body_preview;
+use pollux_schema::{OpenaiResponsesErrorBody, OpenaiResponsesErrorObject, QwenErrorBody};
+
+#[derive(Debug, ThisError)]
+pub(crate) enum QwenError {
+    #[error("Request rejected")]
+    RequestRejected {
+        status: StatusCode,
+        body: OpenaiResponsesErrorObject,
+        debug_message: Option<String>,
+    },
+
+    /// No usable credential is currently available.
+    #[error("No available credential")]
+    NoAvailableCredential,
+
+    /// Upstream error that matched a provider mapping rule.
+    #[error("Upstream mapped error: status={status}, body={body:?}")]
+    UpstreamMappedError {
+        status: StatusCode,
+        body: QwenErrorBody,
+    },
+
+    /// Upstream fallback error (rule unmatched or body unstructured).
+    #[error("Upstream fallback error: status={status}, body={body:.200}")]
+    UpstreamFallbackError {
+        status: StatusCode,
+        /// Raw upstream body is preserved for internal diagnostics/logging only.
+        body: String,
+    },
+
+    /// JSON serialization or parsing failure while preparing provider payloads.
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+
+    /// Transport-level failure (DNS, connect, timeouts, etc).
+    #[error("HTTP request error: {0}")]
+    Reqwest(#[from] reqwest::Error),
+
+    #[error("Stream protocol error: {0}")]
+    StreamProtocolError(String),
+
+    #[error("Internal error: {0}")]
+    Internal(String),
+
+    /// The pool gave up; a cooling credential frees up again after `after`.
+    #[error("{error} (retry after {after:?})")]
+    RetryAfter {
+        error: Box<QwenError>,
+        after: Duration,
+    },
+}
+
+impl QwenError {
+    /// The pool could not take the request: no credential left for the model,
+    /// or still rate limited after the provider's own retries.
+    pub(crate) fn pool_gave_up(&self) -> bool {
+        match self {
+            QwenError::NoAvailableCredential | QwenError::RetryAfter { .. } => true,
+            QwenError::UpstreamFallbackError { status, .. }
+            | QwenError::UpstreamMappedError { status, .. } => {
+                *status == StatusCode::TOO_MANY_REQUESTS
+            }
+            _ => false,
+        }
+    }
+
+    /// Attach the pool's cooldown hint, if any, to an error it gave up with.
+    pub(crate) fn with_retry_after(self, after: Option<Duration>) -> Self {
+        match after {
+            Some(after) if !matches!(self, QwenError::RetryAfter { .. }) => QwenError::RetryAfter {
+                error: Box::new(self),
+                after,
+            },
+            _ => self,
+        }
+    }
+}
+
+impl From<JsonRejection> for QwenError {
+    fn from(rejection: JsonRejection) -> Self {
+        let debug_message = rejection.to_string();
+        match rejection {
+            JsonRejection::BytesRejection(_) => QwenError::RequestRejected {
+                status: StatusCode::PAYLOAD_TOO_LARGE,
+                body: OpenaiResponsesErrorObject {
+                    code: Some("PAYLOAD_TOO_LARGE".to_string()),
+                    message: "request body too large".to_string(),
+                    r#type: "PAYLOAD_TOO_LARGE".to_string(),
+                    param: None,
+                },
+                debug_message: Some(debug_message),
+            },
+            JsonRejection::JsonSyntaxError(_) => QwenError::RequestRejected {
+                status: StatusCode::BAD_REQUEST,
+                body: OpenaiResponsesErrorObject {
+                    code: Some("INVALID_JSON".to_string()),
+                    message: "invalid JSON".to_string(),
+                    r#type: "INVALID_JSON".to_string(),
+                    param: None,
+                },
+                debug_message: Some(debug_message),
+            },
+            _ => QwenError::RequestRejected {
+                status: StatusCode::BAD_REQUEST,
+                body: OpenaiResponsesErrorObject {
+                    code: Some("INVALID_REQUEST".to_string()),
+                    message: "invalid request".to_string(),
+                    r#type: "INVALID_REQUEST".to_string(),
+                    param: None,
+                },
+                debug_message: Some(debug_message),
+            },
+        }
+    }
+}
+
+impl IntoResponse for QwenError {
+    fn into_response(self) -> Response {
+        if let QwenError::RetryAfter { error, after } = self {
+            let mut resp = error.into_response();
+            set_retry_after(&mut resp, after);
+            return resp;
+        }
+        let (status, error_body) = self.into_parts();
+        let resp_json = OpenaiResponsesErrorBody { inner: error_body };
+        (status, Json(resp_json)).into_response()
+    }
+}
+
+impl QwenError {
+    #[allow(clippy::too_many_lines)]
+    fn into_parts(self) -> (StatusCode, OpenaiResponsesErrorObject) {
+        match self {
+            QwenError::RetryAfter { error, .. } => error.into_parts(),
+
+            QwenError::RequestRejected {
+                status,
+                body,
+                debug_message,
+            } => {
+                if let Some(debug_message) = debug_message {
+                    tracing::warn!(
+                        status = %status,
+                        code = ?body.code,
+                        message = %body.message,
+                        debug_message = %debug_message,
+                        "Qwen request rejected"
+                    );
+                } else {
+                    tracing::warn!(
+                        status = %status,
+                        code = ?body.code,
+                        message = %body.message,
+                        "Qwen request rejected"
+                    );
+                }
+
+                (status, body)
+            }
+
+            QwenError::UpstreamMappedError { status, body } => {
+                let cleaned = OpenaiResponsesErrorBody::from(body).inner;
+                tracing::warn!(
+                    status = %status,
+                    code = ?cleaned.code,
+                    message = %cleaned.message,
+                    "Qwen upstream mapped error"
+                );
+                (status, cleaned)
+            }
+
+            QwenError::UpstreamFallbackError { status, body } => {
+                let error_body = OpenaiResponsesErrorObject {
+                    code: Some(status.as_u16().to_string()),
+                    message: format!("Upstream returned {status}"),
+                    r#type: "UPSTREAM_ERROR".to_string(),
+                    param: None,
+                };
+                tracing::warn!(
+                    status = %status,
+                    code = ?error_body.code,
+                    message = %error_body.message,
+                    raw_body = %body_preview(&body, UPSTREAM_BODY_PREVIEW_CHARS),
+
+                    "Qwen upstream fallback error"
+                );
+                (status, error_body)
+            }
+
+            QwenError::NoAvailableCredential => (
+                StatusCode::SERVICE_UNAVAILABLE,
+                OpenaiResponsesErrorObject {
+                    code: Some("NO_CREDENTIAL".to_string()),
+                    message: "No available credentials to process the request.".to_string(),
+                    r#type: "NO_CREDENTIAL".to_string(),
+                    param: None,
+                },
+            ),
+
+            QwenError::Reqwest(e) => {
+                tracing::warn!(error = %e, status = ?e.status(), "Qwen reqwest error");
+                (
+                    StatusCode::BAD_GATEWAY,
+                    OpenaiResponsesErrorObject {
+                        code: Some("UPSTREAM_ERROR".to_string()),
+                        message: "Upstream service error.".to_string(),
+                        r#type: "UPSTREAM_ERROR".to_string(),
+                        param: None,
+                    },
+                )
+            }
+
+            QwenError::Json(e) => {
+                tracing::error!(error = %e, "Qwen JSON error");
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    OpenaiResponsesErrorObject {
+                        code: Some("INTERNAL_ERROR".to_string()),
+                        message: "An internal server error occurred.".to_string(),
+                        r#type: "INTERNAL_ERROR".to_string(),
+                        param: None,
+                    },
+                )
+            }
+
+            QwenError::StreamProtocolError(e) => {
+                tracing::warn!(error = %e, "Qwen stream protocol error");
+                (
+                    StatusCode::BAD_GATEWAY,
+                    OpenaiResponsesErrorObject {
+                        code: Some("UPSTREAM_ERROR".to_string()),
+                        message: "Upstream stream protocol error.".to_string(),
+                        r#type: "UPSTREAM_ERROR".to_string(),
+                        param: None,
+                    },
+                )
+            }
+
+            QwenError::Internal(e) => {
+                tracing::error!(error = %e, "Qwen internal error");
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    OpenaiResponsesErrorObject {
+                        code: Some("INTERNAL_ERROR".to_string()),
+                        message: "An internal server error occurred.".to_string(),
+                        r#type: "INTERNAL_ERROR".to_string(),
+                        param: None,
+                    },
+                )
+            }
+        }
+    }
+
+    /// Final `{"error": ...}` chunk for a stream that broke after its headers
+    /// were sent.
+    pub(crate) fn into_sse_event(self) -> Event {
+        let (_, error_body) = self.into_parts();
+        chat_error_event(&OpenaiResponsesErrorBody { inner: error_body })
+    }
+}
+
+impl From<crate::PolluxError> for QwenError {
+    fn from(err: crate::PolluxError) -> Self {
+        match err {
+            crate::PolluxError::NoAvailableCredential => QwenError::NoAvailableCredential,
+            crate::PolluxError::RetryAfter { error, after } => {
+                QwenError::from(*error).with_retry_after(Some(after))
+            }
+            crate::PolluxError::ReqwestError(e) => QwenError::Reqwest(e),
+            crate::PolluxError::StreamProtocolError(s) => QwenError::StreamProtocolError(s),
+            other => QwenError::Internal(other.to_string()),
+        }
+    }
+}
+
+impl IsRetryable for QwenError {
+    fn is_retryable(&self) -> bool {
+        match self {
+            QwenError::UpstreamFallbackError { status, .. } => matches!(
+                *status,
+                StatusCode::UNAUTHORIZED | StatusCode::TOO_MANY_REQUESTS | StatusCode::FORBIDDEN
+            ),
+            QwenError::UpstreamMappedError { status, body } => match *status {
+                // The model is missing for this account only; another may serve it.
+                StatusCode::NOT_FOUND => body.code() == Some("model_not_found"),
+
+                // Status-driven recoverable errors (credential refresh / cooldown).
+                StatusCode::UNAUTHORIZED
+                | StatusCode::TOO_MANY_REQUESTS
+                | StatusCode::FORBIDDEN => true,
+
+                _ => false,
+            },
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quota_exhausted_is_retryable_on_another_credential() {
+        let raw = r#"{"error":{"code":"insufficient_quota","message":"Free allocated quota exceeded.","type":"insufficient_quota"}}"#;
+        let body = serde_json::from_str::<QwenErrorBody>(raw).expect("parse sample");
+
+        let error = QwenError::UpstreamMappedError {
+            status: StatusCode::TOO_MANY_REQUESTS,
+            body,
+        };
+
+        assert!(error.is_retryable());
+        assert!(error.pool_gave_up());
+    }
+}
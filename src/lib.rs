@@ -10,3 +10,5 @@ pub(crate) mod utils;
 
 pub use error::PolluxError;
 pub use providers::geminicli::client::oauth::ops::GoogleOauthOps;
+pub use utils::dns::install_dns_resolver;
+pub use utils::logging::set_compliance_mode;
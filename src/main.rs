@@ -1,24 +1,25 @@
 use mimalloc::MiMalloc;
-use std::net::SocketAddr;
+use pollux::server::drain::ShutdownDrain;
+use pollux::server::listener::HttpListener;
+use pollux::server::log_level::LogLevelControl;
+use std::future::IntoFuture;
 use std::sync::Arc;
-use tokio::{net::TcpListener, signal};
-use tracing::info;
+use std::time::Duration;
+use tokio::signal;
+use tracing::{info, warn};
 use tracing_subscriber::{EnvFilter, layer::SubscriberExt, util::SubscriberInitExt};
 
 #[global_allocator]
 static GLOBAL: MiMalloc = MiMalloc;
 
-#[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    // The server binary requires a real config file with a non-empty pollux_key.
-    // (Library code uses `config::CONFIG` which is best-effort and does not validate.)
-    let cfg = pollux::config::Config::from_toml();
-
-    let env_filter = EnvFilter::try_from_default_env()
-        .unwrap_or_else(|_| EnvFilter::new(cfg.basic.loglevel.clone()));
+/// `RUST_LOG` wins over `basic.loglevel` when set. The filter can be changed
+/// later through the returned control (`/admin/v1/loglevel`).
+fn init_tracing(loglevel: &str) -> LogLevelControl {
+    let env_filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(loglevel));
+    let (filter_layer, control) = LogLevelControl::new(env_filter);
 
     tracing_subscriber::registry()
-        .with(env_filter)
+        .with(filter_layer)
         .with(
             tracing_subscriber::fmt::layer()
                 // .compact()
@@ -26,24 +27,185 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 .with_target(false),
         )
         .init();
+    control
+}
+
+/// `pollux check-config`: print the report and exit non-zero on errors.
+async fn check_config(args: impl Iterator<Item = String>) -> ! {
+    let args = pollux::config::check::CheckArgs::parse(args).unwrap_or_else(|e| {
+        eprintln!("{e}\n\n{}", pollux::config::check::USAGE);
+        std::process::exit(2);
+    });
+    let report = pollux::config::check::run(&args).await;
+    print!("{report}");
+    std::process::exit(i32::from(!report.passed()));
+}
 
-    let db = pollux::db::spawn(cfg.basic.database_url.as_str()).await;
+/// `pollux replay`: print a captured stream as a client would receive it.
+async fn replay(args: impl Iterator<Item = String>) -> ! {
+    let args = pollux::server::replay::ReplayArgs::parse(args).unwrap_or_else(|e| {
+        eprintln!("{e}\n\n{}", pollux::server::replay::USAGE);
+        std::process::exit(2);
+    });
+    let cfg = pollux::config::Config::from_optional_toml();
+    match pollux::server::replay::run(&cfg, &args).await {
+        Ok(events) => {
+            print!("{events}");
+            std::process::exit(0);
+        }
+        Err(e) => {
+            eprintln!("{e}");
+            std::process::exit(1);
+        }
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let mut args = std::env::args().skip(1);
+    let doctor = match args.next().as_deref() {
+        Some("doctor") => match pollux::providers::doctor::DoctorArgs::parse(args) {
+            Ok(parsed) => Some(parsed),
+            Err(e) => {
+                eprintln!("{e}\n\n{}", pollux::providers::doctor::USAGE);
+                std::process::exit(2);
+            }
+        },
+        Some("check-config") => check_config(args).await,
+        Some("replay") => replay(args).await,
+        _ => None,
+    };
+
+    // The server binary requires a non-empty pollux_key, from config.toml or POLLUX_ variables.
+    // (Library code uses `config::CONFIG` which is best-effort and does not validate.)
+    let cfg = pollux::config::Config::from_toml();
+
+    let log_level = init_tracing(&cfg.basic.loglevel);
+
+    pollux::set_compliance_mode(cfg.basic.compliance_mode);
+    if cfg.basic.compliance_mode || cfg!(feature = "compliance") {
+        info!("Compliance mode on: request and response payloads are never logged");
+    }
+    pollux::install_dns_resolver(&cfg.providers.dns)?;
+    pollux::server::alerts::spawn(&cfg.alerts).await;
+
+    let db = pollux::db::spawn_with_cipher(
+        cfg.basic.database_url.as_str(),
+        cfg.basic
+            .db_encryption_key
+            .as_deref()
+            .map(pollux::db::TokenCipher::new),
+    )
+    .await;
+    if let Some(args) = doctor {
+        let report = pollux::providers::doctor::run(&cfg, &db, &args).await;
+        print!("{report}");
+        std::process::exit(i32::from(!report.passed()));
+    }
+    let counters = pollux::server::request_counters::RequestCounters::restore(
+        db.load_request_counters().await?,
+    );
+    counters.spawn_flusher(
+        db.clone(),
+        Duration::from_secs(cfg.basic.counters_flush_secs.max(1)),
+    );
+    let model_report =
+        pollux::model_catalog::consistency::check(&db, &cfg, &counters.snapshot()).await;
     let providers = pollux::providers::Providers::spawn(db.clone(), &cfg).await;
     // Build axum router and serve
     let pollux_key: Arc<str> = Arc::from(cfg.basic.pollux_key.clone());
-    let state =
-        pollux::server::router::PolluxState::new(providers, pollux_key, cfg.basic.insecure_cookie);
+    let mut state =
+        pollux::server::router::PolluxState::new(providers, pollux_key, cfg.basic.insecure_cookie)
+            .with_api_keys(cfg.basic.api_keys.clone())
+            .with_resource_add(&cfg.basic.resource_add)
+            .with_rate_limits(pollux::server::guards::rate_limit::KeyRateLimits::new(
+                &cfg.basic,
+            ))
+            .with_concurrency_limit(pollux::server::guards::concurrency::ConcurrencyLimit::new(
+                cfg.basic.concurrency_limit,
+            ))
+            .with_request_counters(counters.clone())
+            .with_model_report(model_report)
+            .with_sse_flush(cfg.providers.sse)
+            .with_routing(cfg.routing.clone())
+            .with_log_level(log_level);
+    if let Some(path) = cfg.basic.audit_log_path.clone() {
+        info!(path = %path.display(), "Audit log enabled");
+        state = state.with_audit_log(pollux::server::audit_log::AuditLog::open(
+            path,
+            cfg.basic.audit_log_max_bytes,
+            cfg.basic.audit_log_max_files,
+        )?);
+    }
+    if let Some(dir) = cfg.basic.stream_capture_dir.clone() {
+        warn!(dir = %dir.display(), "Stream capture enabled: raw upstream responses are written to disk");
+        state = state.with_stream_capture(pollux::server::stream_capture::StreamCapture::new(dir)?);
+    }
+    state.spawn_model_discovery();
+    let drain = state.drain.clone();
     let app = pollux::server::router::pollux_router(state);
 
-    let addr = SocketAddr::from((cfg.basic.listen_addr, cfg.basic.listen_port));
-    let listener = TcpListener::bind(addr).await?;
-    info!("HTTP server listening on {}", addr);
-    axum::serve(listener, app)
-        .with_graceful_shutdown(shutdown_signal())
-        .await?;
+    pollux::server::coordination::start(&cfg.basic.coordination)?;
+    let listener = pollux::server::listener::bind(&cfg.basic)?;
+    info!("HTTP server listening on {}", listener);
+    let socket_path = listener.socket_path();
+    let drain_secs = cfg.basic.shutdown_drain_secs;
+    match listener {
+        HttpListener::Tcp(listener) => serve(listener, app, drain, drain_secs).await?,
+        #[cfg(unix)]
+        HttpListener::Unix { listener, .. } => serve(listener, app, drain, drain_secs).await?,
+    }
+    if let Some(path) = socket_path
+        && let Err(e) = std::fs::remove_file(&path)
+    {
+        warn!(path = %path.display(), error = %e, "Failed to remove Unix socket");
+    }
+    if let Err(e) = counters.flush(&db).await {
+        warn!(error = %e, "Failed to persist request counters on shutdown");
+    }
     info!("Server has shut down gracefully.");
     Ok(())
 }
+
+/// Serve `app` until a shutdown signal, then drain in-flight requests for up
+/// to `drain_secs` before closing the remaining streams.
+async fn serve<L>(
+    listener: L,
+    app: axum::Router,
+    drain: ShutdownDrain,
+    drain_secs: u64,
+) -> std::io::Result<()>
+where
+    L: axum::serve::Listener,
+    L::Addr: std::fmt::Debug,
+{
+    let server = axum::serve(listener, app).with_graceful_shutdown({
+        let drain = drain.clone();
+        async move {
+            shutdown_signal().await;
+            info!(
+                in_flight = drain.in_flight(),
+                drain_secs, "Shutdown signal received, draining in-flight requests"
+            );
+            drain.begin();
+        }
+    });
+    let mut server = std::pin::pin!(server.into_future());
+    tokio::select! {
+        res = &mut server => res?,
+        () = drain.expired(Duration::from_secs(drain_secs)) => {
+            warn!(
+                in_flight = drain.in_flight(),
+                "Drain timeout reached, closing remaining streams"
+            );
+            drain.force_close();
+            // Give the final frames a moment to reach clients.
+            let _ = tokio::time::timeout(Duration::from_secs(1), server).await;
+        }
+    }
+    Ok(())
+}
+
 async fn shutdown_signal() {
     let ctrl_c = async {
         signal::ctrl_c()
@@ -1,35 +1,57 @@
-use serde::{Deserialize, Serialize};
+use smallvec::SmallVec;
+use std::fmt;
+use std::ops::{BitAnd, BitOr};
+
+const WORD_BITS: usize = u64::BITS as usize;
 
-/// Newtype wrapper around `u64`.
-/// Runtime representation is identical to `u64`, but the type encodes intent:
-/// a bitset of model capabilities (each bit corresponds to a model index).
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Hash, Serialize, Deserialize)]
-#[repr(transparent)] // ABI-compatible with u64 for FFI or direct passthrough.
-pub struct ModelCapabilities(u64);
+/// Growable bitset of model indices.
+///
+/// Used both for a credential's capabilities and for a request's target model
+/// (a single bit). The first 128 models are stored inline; larger registries
+/// spill to the heap. Trailing zero words are always trimmed so equal sets
+/// compare and hash equally regardless of how they were built.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Hash)]
+pub struct ModelCapabilities {
+    words: SmallVec<[u64; 2]>,
+}
 
 impl ModelCapabilities {
     /// Creates an empty set (no capabilities enabled).
     #[inline]
     pub fn none() -> Self {
-        Self(0)
+        Self::default()
     }
 
-    /// Creates a full set (all bits enabled), often used as a default.
-    #[inline]
-    pub fn all() -> Self {
-        Self(u64::MAX) // Use ((1 << n) - 1) to bound by the actual model count.
+    /// Creates a set with every model in `0..model_count` enabled.
+    pub fn all(model_count: usize) -> Self {
+        let mut words: SmallVec<[u64; 2]> = SmallVec::from_elem(u64::MAX, model_count / WORD_BITS);
+        let rem = model_count % WORD_BITS;
+        if rem != 0 {
+            words.push((1u64 << rem) - 1);
+        }
+        Self { words }
     }
 
-    /// Builds from raw bits (e.g., loaded from storage).
-    #[inline]
+    /// Creates a set containing only `index`.
+    pub fn single(index: usize) -> Self {
+        let mut caps = Self::none();
+        caps.enable(index);
+        caps
+    }
+
+    /// Builds from the low 64 bits (e.g., fixtures or legacy values).
     pub fn from_bits(bits: u64) -> Self {
-        Self(bits)
+        let mut caps = Self {
+            words: SmallVec::from_elem(bits, 1),
+        };
+        caps.trim();
+        caps
     }
 
-    /// Returns the raw bitset (e.g., for persistence or interop).
-    #[inline]
-    pub fn bits(&self) -> u64 {
-        self.0
+    fn trim(&mut self) {
+        while self.words.last() == Some(&0) {
+            self.words.pop();
+        }
     }
 
     // ================= Bitset helpers =================
@@ -38,63 +60,209 @@ impl ModelCapabilities {
     /// Semantics: `caps.supports(index)`.
     #[inline]
     pub fn supports(&self, index: usize) -> bool {
-        (self.0 & (1u64 << index)) != 0
+        self.words
+            .get(index / WORD_BITS)
+            .is_some_and(|w| w & (1u64 << (index % WORD_BITS)) != 0)
     }
 
     /// Enables the bit for a given model index.
-    #[inline]
     pub fn enable(&mut self, index: usize) {
-        self.0 |= 1u64 << index;
+        let word = index / WORD_BITS;
+        if self.words.len() <= word {
+            self.words.resize(word + 1, 0);
+        }
+        self.words[word] |= 1u64 << (index % WORD_BITS);
     }
 
     /// Clears the bit for a given model index.
-    #[inline]
     pub fn disable(&mut self, index: usize) {
-        self.0 &= !(1u64 << index);
+        if let Some(w) = self.words.get_mut(index / WORD_BITS) {
+            *w &= !(1u64 << (index % WORD_BITS));
+            self.trim();
+        }
     }
 
-    /// Clears bits for all models included in the given bitmask.
+    /// Clears bits for all models included in `mask`.
     /// This is useful when the caller naturally has a model mask instead of an index.
+    pub fn disable_mask(&mut self, mask: &ModelCapabilities) {
+        for (w, m) in self.words.iter_mut().zip(&mask.words) {
+            *w &= !m;
+        }
+        self.trim();
+    }
+
+    /// Returns true if no model is enabled.
     #[inline]
-    pub fn disable_mask(&mut self, mask: u64) {
-        self.0 &= !mask;
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty()
+    }
+
+    /// Returns the index when exactly one model is enabled.
+    pub fn single_index(&self) -> Option<usize> {
+        let mut found = None;
+        for (i, &w) in self.words.iter().enumerate() {
+            if w == 0 {
+                continue;
+            }
+            if found.is_some() || w.count_ones() != 1 {
+                return None;
+            }
+            found = Some(i * WORD_BITS + w.trailing_zeros() as usize);
+        }
+        found
+    }
+
+    /// Iterates enabled model indices in ascending order.
+    pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        self.words.iter().enumerate().flat_map(|(i, &w)| {
+            (0..WORD_BITS)
+                .filter(move |bit| w & (1u64 << bit) != 0)
+                .map(move |bit| i * WORD_BITS + bit)
+        })
     }
 
     /// Returns true if `self` is a superset of `required`.
     /// Example: a request needs [GPT4 + Stream], so the provider must contain both.
-    #[inline]
-    pub fn contains_all(&self, required: ModelCapabilities) -> bool {
-        (self.0 & required.0) == required.0
+    pub fn contains_all(&self, required: &ModelCapabilities) -> bool {
+        required.words.iter().enumerate().all(|(i, &r)| {
+            let w = self.words.get(i).copied().unwrap_or(0);
+            w & r == r
+        })
     }
 
     /// Returns true if there is any overlap between the two sets.
     /// Example: supporting any one of several fallback models is sufficient.
-    #[inline]
-    pub fn intersects(&self, other: ModelCapabilities) -> bool {
-        (self.0 & other.0) != 0
+    pub fn intersects(&self, other: &ModelCapabilities) -> bool {
+        self.words.iter().zip(&other.words).any(|(a, b)| a & b != 0)
     }
 
     /// Returns the union of two capability sets.
-    #[inline]
     #[must_use]
-    pub fn merge(&self, other: ModelCapabilities) -> Self {
-        Self(self.0 | other.0)
+    pub fn merge(&self, other: &ModelCapabilities) -> Self {
+        let (long, short) = if self.words.len() >= other.words.len() {
+            (self, other)
+        } else {
+            (other, self)
+        };
+        let mut out = long.clone();
+        for (w, s) in out.words.iter_mut().zip(&short.words) {
+            *w |= s;
+        }
+        out
+    }
+
+    /// Returns the intersection of two capability sets.
+    #[must_use]
+    pub fn intersection(&self, other: &ModelCapabilities) -> Self {
+        let mut out = Self {
+            words: self
+                .words
+                .iter()
+                .zip(&other.words)
+                .map(|(a, b)| a & b)
+                .collect(),
+        };
+        out.trim();
+        out
     }
 }
 
-// Enable direct use of bitwise operators (e.g., caps_a | caps_b).
-use std::ops::{BitAnd, BitOr};
+impl FromIterator<usize> for ModelCapabilities {
+    fn from_iter<I: IntoIterator<Item = usize>>(iter: I) -> Self {
+        let mut caps = Self::none();
+        for index in iter {
+            caps.enable(index);
+        }
+        caps
+    }
+}
 
+// Enable direct use of bitwise operators (e.g., caps_a | caps_b).
 impl BitOr for ModelCapabilities {
     type Output = Self;
     fn bitor(self, rhs: Self) -> Self {
-        Self(self.0 | rhs.0)
+        self.merge(&rhs)
     }
 }
 
 impl BitAnd for ModelCapabilities {
     type Output = Self;
     fn bitand(self, rhs: Self) -> Self {
-        Self(self.0 & rhs.0)
+        self.intersection(&rhs)
+    }
+}
+
+/// Hex, most significant word first; one word renders as `0x{:016x}`.
+impl fmt::Display for ModelCapabilities {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("0x")?;
+        if self.words.is_empty() {
+            return write!(f, "{:016x}", 0);
+        }
+        for w in self.words.iter().rev() {
+            write!(f, "{w:016x}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bits_beyond_64_are_kept() {
+        let mut caps = ModelCapabilities::all(70);
+        assert!(caps.supports(0) && caps.supports(63) && caps.supports(69));
+        assert!(!caps.supports(70));
+        assert_eq!(caps.iter().count(), 70);
+
+        caps.disable_mask(&ModelCapabilities::single(65));
+        assert!(!caps.supports(65));
+        assert!(caps.contains_all(&ModelCapabilities::single(69)));
+        assert!(!caps.intersects(&ModelCapabilities::single(65)));
+    }
+
+    #[test]
+    fn equal_sets_compare_equal_after_trimming() {
+        let mut high = ModelCapabilities::single(130);
+        high.disable(130);
+        assert_eq!(high, ModelCapabilities::none());
+        assert!(high.is_empty());
+
+        let built: ModelCapabilities = [1, 100].into_iter().collect();
+        assert_eq!(
+            built,
+            ModelCapabilities::single(1).merge(&ModelCapabilities::single(100))
+        );
+        assert_eq!(
+            built.intersection(&ModelCapabilities::from_bits(0b10)),
+            ModelCapabilities::single(1)
+        );
+    }
+
+    #[test]
+    fn single_index_requires_exactly_one_bit() {
+        assert_eq!(ModelCapabilities::single(77).single_index(), Some(77));
+        assert_eq!(ModelCapabilities::none().single_index(), None);
+        assert_eq!(ModelCapabilities::from_bits(0b11).single_index(), None);
+        assert_eq!(
+            ModelCapabilities::single(3)
+                .merge(&ModelCapabilities::single(90))
+                .single_index(),
+            None
+        );
+    }
+
+    #[test]
+    fn display_matches_legacy_hex_for_small_sets() {
+        assert_eq!(
+            ModelCapabilities::from_bits(0x2a).to_string(),
+            "0x000000000000002a"
+        );
+        assert_eq!(
+            ModelCapabilities::single(64).to_string(),
+            "0x00000000000000010000000000000000"
+        );
     }
 }
@@ -0,0 +1,264 @@
+//! Boot-time check of the configured model lists against the last boot.
+//!
+//! Capability bits are positions in [`MODEL_REGISTRY`](super::MODEL_REGISTRY),
+//! which follows `model_list` order, so editing the lists silently changes
+//! what a bit means. Each boot saves the lists with their stable
+//! [`ModelId`](super::ModelId)s and indices to the `model_registry` table; the next boot diffs against it and also flags
+//! persisted per-model rows (request counters) for models no longer configured.
+
+use super::{ModelRegistry, collect_global_model_names};
+use crate::config::Config;
+use crate::db::{DbActorHandle, ModelRegistryRow, RequestCounterRow};
+use crate::providers::manifest::ProviderKind;
+use serde::Serialize;
+use std::collections::{BTreeSet, HashMap};
+use tracing::{info, warn};
+
+/// A model whose registry index moved since the last boot.
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct ReindexedModel {
+    pub model: String,
+    pub previous_index: i64,
+    pub index: i64,
+}
+
+/// Differences for one provider; every list is sorted.
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct ProviderModelDrift {
+    pub provider: &'static str,
+    /// Configured now, absent last boot.
+    pub added: Vec<String>,
+    /// Configured last boot, gone now.
+    pub removed: Vec<String>,
+    pub reindexed: Vec<ReindexedModel>,
+    /// Models with `request_counters` rows that are not configured.
+    pub orphaned_counters: Vec<String>,
+}
+
+impl ProviderModelDrift {
+    fn is_empty(&self) -> bool {
+        self.added.is_empty()
+            && self.removed.is_empty()
+            && self.reindexed.is_empty()
+            && self.orphaned_counters.is_empty()
+    }
+}
+
+/// Result of the startup check, served at `/admin/v1/models/consistency`.
+#[derive(Debug, Clone, Default, Serialize, PartialEq, Eq)]
+pub struct ModelConsistencyReport {
+    /// False on first boot, when there was nothing to compare against.
+    pub had_previous: bool,
+    /// Only providers with at least one difference.
+    pub providers: Vec<ProviderModelDrift>,
+}
+
+impl ModelConsistencyReport {
+    #[must_use]
+    pub fn is_consistent(&self) -> bool {
+        self.providers.is_empty()
+    }
+}
+
+/// The configured models of every provider with their global registry index.
+#[must_use]
+pub fn registry_rows(cfg: &Config) -> Vec<ModelRegistryRow> {
+    let registry = ModelRegistry::new(&collect_global_model_names(cfg));
+    let geminicli = cfg.geminicli();
+    let lists = [
+        (
+            ProviderKind::GeminiCli,
+            [
+                geminicli.model_list,
+                geminicli.embedding_model_list,
+                geminicli.image_model_list,
+            ]
+            .concat(),
+        ),
+        (ProviderKind::Codex, cfg.codex().model_list),
+        (ProviderKind::Antigravity, cfg.antigravity().model_list),
+        (ProviderKind::Claude, cfg.claude().model_list),
+        (ProviderKind::Qwen, cfg.qwen().model_list),
+    ];
+    lists
+        .into_iter()
+        .flat_map(|(kind, models)| {
+            let registry = &registry;
+            models.into_iter().filter_map(move |model| {
+                let idx = registry.get_index(&model)?;
+                Some(ModelRegistryRow {
+                    provider: kind.label().to_string(),
+                    model_id: registry.get_id(idx)?.to_i64(),
+                    idx: i64::try_from(idx).ok()?,
+                    model,
+                })
+            })
+        })
+        .collect()
+}
+
+/// Diff `current` against `previous` (empty on first boot) and `counters`.
+#[must_use]
+pub fn compare(
+    current: &[ModelRegistryRow],
+    previous: &[ModelRegistryRow],
+    counters: &[RequestCounterRow],
+) -> ModelConsistencyReport {
+    let index_of = |rows: &[ModelRegistryRow], provider: &str| -> HashMap<String, i64> {
+        rows.iter()
+            .filter(|r| r.provider == provider)
+            .map(|r| (r.model.clone(), r.idx))
+            .collect()
+    };
+
+    let mut providers = Vec::new();
+    for kind in ProviderKind::ALL {
+        let provider = kind.label();
+        let now = index_of(current, provider);
+        let before = index_of(previous, provider);
+
+        let mut drift = ProviderModelDrift {
+            provider,
+            added: Vec::new(),
+            removed: Vec::new(),
+            reindexed: Vec::new(),
+            orphaned_counters: Vec::new(),
+        };
+        if !previous.is_empty() {
+            drift.added = sorted(now.keys().filter(|m| !before.contains_key(*m)));
+            drift.removed = sorted(before.keys().filter(|m| !now.contains_key(*m)));
+            drift.reindexed = now
+                .iter()
+                .filter_map(|(model, &index)| {
+                    let &previous_index = before.get(model)?;
+                    (previous_index != index).then(|| ReindexedModel {
+                        model: model.clone(),
+                        previous_index,
+                        index,
+                    })
+                })
+                .collect();
+            drift.reindexed.sort_by(|a, b| a.model.cmp(&b.model));
+        }
+        drift.orphaned_counters = sorted(
+            counters
+                .iter()
+                .filter(|c| c.provider == provider && !now.contains_key(&c.model))
+                .map(|c| &c.model),
+        );
+
+        if !drift.is_empty() {
+            providers.push(drift);
+        }
+    }
+
+    ModelConsistencyReport {
+        had_previous: !previous.is_empty(),
+        providers,
+    }
+}
+
+fn sorted<'a>(models: impl Iterator<Item = &'a String>) -> Vec<String> {
+    models
+        .cloned()
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect()
+}
+
+/// Run the check, log what changed and save the current lists for next boot.
+///
+/// Database errors are logged and yield an empty report; the check is
+/// advisory and never blocks startup.
+pub async fn check(
+    db: &DbActorHandle,
+    cfg: &Config,
+    counters: &[RequestCounterRow],
+) -> ModelConsistencyReport {
+    let current = registry_rows(cfg);
+    let previous = match db.load_model_registry().await {
+        Ok(rows) => rows,
+        Err(e) => {
+            warn!(error = %e, "[Models] Failed to load saved model registry");
+            return ModelConsistencyReport::default();
+        }
+    };
+
+    let report = compare(&current, &previous, counters);
+    for drift in &report.providers {
+        warn!(
+            provider = drift.provider,
+            added = ?drift.added,
+            removed = ?drift.removed,
+            reindexed = ?drift.reindexed,
+            orphaned_counters = ?drift.orphaned_counters,
+            "[Models] Configured models differ from stored data"
+        );
+    }
+    if report.had_previous && report.is_consistent() {
+        info!("[Models] Configured models match the previous boot");
+    }
+
+    if let Err(e) = db.save_model_registry(current).await {
+        warn!(error = %e, "[Models] Failed to save model registry");
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model_catalog::ModelId;
+
+    fn row(provider: &str, model: &str, idx: i64) -> ModelRegistryRow {
+        ModelRegistryRow {
+            provider: provider.to_string(),
+            model: model.to_string(),
+            model_id: ModelId::of(model).to_i64(),
+            idx,
+        }
+    }
+
+    #[test]
+    fn reports_added_removed_reindexed_and_orphaned_models() {
+        let previous = [
+            row("geminicli", "gemini-2.5-pro", 0),
+            row("geminicli", "gemini-2.0-flash", 1),
+            row("codex", "gpt-5", 2),
+        ];
+        let current = [
+            row("geminicli", "gemini-2.5-pro", 0),
+            row("codex", "gpt-5", 1),
+            row("codex", "gpt-5-codex", 2),
+        ];
+        let counters = [RequestCounterRow {
+            provider: "geminicli".to_string(),
+            model: "gemini-2.0-flash".to_string(),
+            requests: 3,
+            errors: 0,
+        }];
+
+        let report = compare(&current, &previous, &counters);
+        assert!(report.had_previous);
+        assert_eq!(report.providers.len(), 2);
+
+        let gemini = &report.providers[0];
+        assert_eq!(gemini.provider, "geminicli");
+        assert_eq!(gemini.removed, vec!["gemini-2.0-flash"]);
+        assert_eq!(gemini.orphaned_counters, vec!["gemini-2.0-flash"]);
+
+        let codex = &report.providers[1];
+        assert_eq!(codex.added, vec!["gpt-5-codex"]);
+        assert_eq!(
+            codex.reindexed,
+            vec![ReindexedModel {
+                model: "gpt-5".to_string(),
+                previous_index: 2,
+                index: 1,
+            }]
+        );
+
+        // First boot: only leftovers in other tables are worth reporting.
+        assert!(compare(&current, &[], &[]).is_consistent());
+    }
+}
@@ -1,8 +1,9 @@
 pub mod capabilities;
+pub mod consistency;
 pub mod registry;
 
 pub use capabilities::ModelCapabilities;
-pub use registry::ModelRegistry;
+pub use registry::{ModelId, ModelRegistry};
 
 use crate::config::{CONFIG, Config};
 use std::collections::HashSet;
@@ -14,51 +15,42 @@ pub static MODEL_REGISTRY: LazyLock<ModelRegistry> = LazyLock::new(|| {
     ModelRegistry::new(&models)
 });
 
-pub static MODEL_MASK_ALL: LazyLock<u64> = LazyLock::new(|| {
-    let model_count = MODEL_REGISTRY.len();
-    if model_count >= 64 {
-        u64::MAX
-    } else {
-        (1u64 << model_count) - 1
-    }
-});
+pub static MODEL_MASK_ALL: LazyLock<ModelCapabilities> =
+    LazyLock::new(|| ModelCapabilities::all(MODEL_REGISTRY.len()));
 
-pub fn mask(name: &str) -> Option<u64> {
-    MODEL_REGISTRY.get_index(name).map(|idx| 1u64 << idx)
+/// Single-model mask for `name`, if it is in the registry.
+pub fn mask(name: &str) -> Option<ModelCapabilities> {
+    MODEL_REGISTRY
+        .get_index(name)
+        .map(ModelCapabilities::single)
 }
 
 /// Resolve a bitmask into a list of model names (best-effort).
 ///
 /// Unknown bits (outside the registry) are ignored here; use `format_model_mask` if you want
 /// those shown explicitly in logs.
-pub fn model_names_from_mask(model_mask: u64) -> Vec<String> {
-    let mut names = Vec::new();
-    for idx in 0..MODEL_REGISTRY.len() {
-        let bit = 1u64 << idx;
-        if (model_mask & bit) != 0 {
-            names.push(MODEL_REGISTRY.get_name(idx).to_string());
-        }
-    }
-    names
+pub fn model_names_from_mask(model_mask: &ModelCapabilities) -> Vec<String> {
+    model_mask
+        .iter()
+        .take_while(|&idx| idx < MODEL_REGISTRY.len())
+        .map(|idx| MODEL_REGISTRY.get_name(idx).to_string())
+        .collect()
 }
 
 /// Human-friendly formatting for model masks, intended for logs.
-pub fn format_model_mask(model_mask: u64) -> String {
-    if model_mask == 0 {
+pub fn format_model_mask(model_mask: &ModelCapabilities) -> String {
+    if model_mask.is_empty() {
         return "[]".to_string();
     }
 
     let names = model_names_from_mask(model_mask);
-    let unknown_bits = model_mask & !*MODEL_MASK_ALL;
-
-    if unknown_bits != 0 {
-        format!(
-            "[{}] (unknown_bits=0x{:016x})",
-            names.join(", "),
-            unknown_bits
-        )
-    } else {
+    let mut unknown_bits = model_mask.clone();
+    unknown_bits.disable_mask(&MODEL_MASK_ALL);
+
+    if unknown_bits.is_empty() {
         format!("[{}]", names.join(", "))
+    } else {
+        format!("[{}] (unknown_bits={unknown_bits})", names.join(", "))
     }
 }
 
@@ -68,7 +60,12 @@ fn collect_global_model_names(cfg: &Config) -> Vec<String> {
 
     // Provider: geminicli
     let geminicli = cfg.geminicli();
-    for name in geminicli.model_list {
+    for name in geminicli
+        .model_list
+        .into_iter()
+        .chain(geminicli.embedding_model_list)
+        .chain(geminicli.image_model_list)
+    {
         if seen.insert(name.clone()) {
             out.push(name);
         }
@@ -90,5 +87,21 @@ fn collect_global_model_names(cfg: &Config) -> Vec<String> {
         }
     }
 
+    // Provider: claude
+    let claude = cfg.claude();
+    for name in claude.model_list {
+        if seen.insert(name.clone()) {
+            out.push(name);
+        }
+    }
+
+    // Provider: qwen
+    let qwen = cfg.qwen();
+    for name in qwen.model_list {
+        if seen.insert(name.clone()) {
+            out.push(name);
+        }
+    }
+
     out
 }
@@ -1,4 +1,42 @@
+use super::ModelCapabilities;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use std::collections::HashMap;
+use std::fmt;
+
+/// Stable identifier of a model name, independent of `model_list` order.
+///
+/// Registry indices (capability bits) shift whenever the configured lists
+/// are edited; anything persisted should store `ModelId`s and translate them
+/// back with [`ModelRegistry::index_of_id`] on load. Derived from the first
+/// eight bytes of the name's SHA-256, so it is the same on every build.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ModelId(pub u64);
+
+impl ModelId {
+    pub fn of(name: &str) -> Self {
+        let digest = Sha256::digest(name.as_bytes());
+        let mut bytes = [0u8; 8];
+        bytes.copy_from_slice(&digest[..8]);
+        Self(u64::from_be_bytes(bytes))
+    }
+
+    /// Bit pattern for integer columns (neither backend has `u64`).
+    pub const fn to_i64(self) -> i64 {
+        self.0.cast_signed()
+    }
+
+    pub const fn from_i64(value: i64) -> Self {
+        Self(value.cast_unsigned())
+    }
+}
+
+impl fmt::Display for ModelId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:016x}", self.0)
+    }
+}
 
 /// Immutable registry of model names and indices.
 /// Maintains a bidirectional mapping between `Model Name (String)` and
@@ -9,39 +47,49 @@ pub struct ModelRegistry {
     name_to_index: HashMap<String, usize>,
     /// Index-to-name lookup for logs and diagnostics.
     index_to_name: Vec<String>,
+    /// Index-to-id lookup for persistence.
+    index_to_id: Vec<ModelId>,
+    /// Id-to-index lookup for restoring persisted state.
+    id_to_index: HashMap<ModelId, usize>,
 }
 
 impl ModelRegistry {
     /// Builds a registry from an ordered list of model names.
     /// The list order defines the model index assignment (0, 1, 2...).
     ///
-    /// # Panics
-    /// Panics if the number of models exceeds 64, because the bitset is `u64`.
+    /// Panics if two names hash to the same [`ModelId`].
     pub fn new(models: &[String]) -> Self {
-        assert!(
-            models.len() <= 64,
-            "ModelRegistry limits to 64 models (current: {}). \
-            Consider upgrading to u128 or separating clusters.",
-            models.len()
-        );
-
         let mut name_to_index = HashMap::with_capacity(models.len());
         let mut index_to_name = Vec::with_capacity(models.len());
+        let mut index_to_id = Vec::with_capacity(models.len());
+        let mut id_to_index = HashMap::with_capacity(models.len());
 
         for (idx, name) in models.iter().enumerate() {
+            let id = ModelId::of(name);
+            if let Some(&other) = id_to_index.get(&id) {
+                let other: &String = &index_to_name[other];
+                assert!(
+                    other == name,
+                    "model ids collide: {other:?} and {name:?} both map to {id}"
+                );
+            }
             name_to_index.insert(name.clone(), idx);
             index_to_name.push(name.clone());
+            index_to_id.push(id);
+            id_to_index.insert(id, idx);
         }
 
         Self {
             name_to_index,
             index_to_name,
+            index_to_id,
+            id_to_index,
         }
     }
 
-    /// Dictionary lookup: get the index for a model name (0..63).
+    /// Dictionary lookup: get the index for a model name.
     ///
-    /// Used by: bitmask computation (`1 << index`) and manager queue operations.
+    /// Used by: bitmask computation (`ModelCapabilities::single`) and manager queue operations.
     pub fn get_index(&self, name: &str) -> Option<usize> {
         self.name_to_index.get(name).copied()
     }
@@ -56,6 +104,30 @@ impl ModelRegistry {
             .map_or("UNKNOWN_MODEL", String::as_str)
     }
 
+    /// Stable id of the model at `index`.
+    pub fn get_id(&self, index: usize) -> Option<ModelId> {
+        self.index_to_id.get(index).copied()
+    }
+
+    /// Current index of a persisted id; `None` once the model is no longer configured.
+    pub fn index_of_id(&self, id: ModelId) -> Option<usize> {
+        self.id_to_index.get(&id).copied()
+    }
+
+    /// Ids of every model in `mask`; bits outside the registry are dropped.
+    pub fn mask_to_ids(&self, mask: &ModelCapabilities) -> Vec<ModelId> {
+        mask.iter().filter_map(|idx| self.get_id(idx)).collect()
+    }
+
+    /// Rebuild a mask from persisted ids; ids of unconfigured models are dropped.
+    pub fn mask_from_ids(&self, ids: &[ModelId]) -> ModelCapabilities {
+        let mut mask = ModelCapabilities::none();
+        for idx in ids.iter().filter_map(|&id| self.index_of_id(id)) {
+            mask.enable(idx);
+        }
+        mask
+    }
+
     /// Returns the total number of models in the registry.
     ///
     /// Used by: sizing the manager queue vectors.
@@ -68,3 +140,35 @@ impl ModelRegistry {
         self.index_to_name.is_empty()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn registry(names: &[&str]) -> ModelRegistry {
+        let names: Vec<String> = names.iter().map(ToString::to_string).collect();
+        ModelRegistry::new(&names)
+    }
+
+    #[test]
+    fn ids_survive_reordering_the_model_list() {
+        let before = registry(&["gemini-2.5-pro", "gemini-2.5-flash", "gpt-5"]);
+        let after = registry(&["gpt-5", "gemini-2.5-flash"]);
+
+        let mut mask = ModelCapabilities::none();
+        mask.enable(before.get_index("gemini-2.5-pro").unwrap());
+        mask.enable(before.get_index("gpt-5").unwrap());
+        let persisted = before.mask_to_ids(&mask);
+
+        let restored = after.mask_from_ids(&persisted);
+        assert_eq!(
+            restored.iter().collect::<Vec<_>>(),
+            vec![after.get_index("gpt-5").unwrap()]
+        );
+        assert_eq!(ModelId::of("gpt-5"), after.get_id(0).unwrap());
+        assert_eq!(
+            ModelId::from_i64(ModelId::of("gpt-5").to_i64()),
+            ModelId::of("gpt-5")
+        );
+    }
+}
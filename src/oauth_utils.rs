@@ -16,7 +16,7 @@ use std::collections::HashMap;
 /// We keep `OpenID` Connect's `id_token` plus any additional JSON fields via `flatten` for forward
 /// compatibility. Debug output is redacted to avoid leaking secrets.
 #[derive(Clone, Deserialize, Serialize)]
-pub(crate) struct CustomTokenFields {
+pub struct CustomTokenFields {
     pub id_token: Option<String>,
 
     #[serde(flatten)]
@@ -6,7 +6,6 @@
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use sqlx::SqlitePool;
 
 /// Abstraction for applying a patch payload/envelope to the database.
 ///
@@ -14,7 +13,7 @@ use sqlx::SqlitePool;
 /// providers, and higher-level orchestrators can share the same contract.
 #[async_trait]
 pub trait DbPatchable {
-    async fn apply_patch(&self, pool: &SqlitePool) -> Result<(), crate::error::PolluxError>;
+    async fn apply_patch(&self, pool: &crate::db::DbPool) -> Result<(), crate::error::PolluxError>;
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
@@ -25,7 +24,11 @@ pub struct GeminiCliPatch {
     /// `None` => do not change; `Some(v)` => update
     pub access_token: Option<String>,
     pub expiry: Option<DateTime<Utc>>,
+    /// `None` => do not change; `Some(v)` => update
+    pub tier: Option<String>,
     pub status: Option<bool>,
+    /// `None` => do not change; `Some(v)` => replace the pool labels
+    pub labels: Option<Vec<String>>,
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
@@ -43,6 +46,8 @@ pub struct CodexPatch {
     /// `None` => do not change; `Some(v)` => update
     pub chatgpt_plan_type: Option<String>,
     pub status: Option<bool>,
+    /// `None` => do not change; `Some(v)` => replace the pool labels
+    pub labels: Option<Vec<String>>,
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
@@ -53,7 +58,37 @@ pub struct AntigravityPatch {
     /// `None` => do not change; `Some(v)` => update
     pub access_token: Option<String>,
     pub expiry: Option<DateTime<Utc>>,
+    /// `None` => do not change; `Some(v)` => update
+    pub tier: Option<String>,
+    pub status: Option<bool>,
+    /// `None` => do not change; `Some(v)` => replace the pool labels
+    pub labels: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ClaudePatch {
+    /// `None` => do not change; `Some(v)` => update
+    pub email: Option<String>,
+    pub refresh_token: Option<String>,
+    /// `None` => do not change; `Some(v)` => update
+    pub access_token: Option<String>,
+    pub expiry: Option<DateTime<Utc>>,
+    pub status: Option<bool>,
+    /// `None` => do not change; `Some(v)` => replace the pool labels
+    pub labels: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct QwenPatch {
+    pub refresh_token: Option<String>,
+    /// `None` => do not change; `Some(v)` => update
+    pub access_token: Option<String>,
+    /// `None` => do not change; `Some(v)` => update
+    pub resource_url: Option<String>,
+    pub expiry: Option<DateTime<Utc>>,
     pub status: Option<bool>,
+    /// `None` => do not change; `Some(v)` => replace the pool labels
+    pub labels: Option<Vec<String>>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -63,6 +98,8 @@ pub enum ProviderPatch {
     GeminiCli { id: u64, patch: GeminiCliPatch },
     Codex { id: u64, patch: CodexPatch },
     Antigravity { id: u64, patch: AntigravityPatch },
+    Claude { id: u64, patch: ClaudePatch },
+    Qwen { id: u64, patch: QwenPatch },
 }
 
 impl ProviderPatch {
@@ -70,7 +107,9 @@ impl ProviderPatch {
         match self {
             ProviderPatch::GeminiCli { id, .. }
             | ProviderPatch::Codex { id, .. }
-            | ProviderPatch::Antigravity { id, .. } => *id,
+            | ProviderPatch::Antigravity { id, .. }
+            | ProviderPatch::Claude { id, .. }
+            | ProviderPatch::Qwen { id, .. } => *id,
         }
     }
 }
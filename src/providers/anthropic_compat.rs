@@ -0,0 +1,170 @@
+//! Anthropic Messages → Gemini translation for token counting.
+//!
+//! Cloud Code's `countTokens` only counts `contents`, so the system prompt and
+//! tool definitions are folded into a leading user turn. Blocks without a
+//! Gemini counterpart that matters for counting (images, documents, redacted
+//! thinking) are left out, which makes the result an approximation.
+
+use pollux_schema::anthropic::{AnthropicCountTokensRequest, AnthropicMessage};
+use pollux_schema::gemini::{Content, FunctionCall, Part};
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+/// Rough characters per token, for the local estimate.
+const CHARS_PER_TOKEN: u64 = 4;
+
+/// Gemini `contents` carrying everything the request counts.
+pub fn count_tokens_contents(req: &AnthropicCountTokensRequest) -> Vec<Content> {
+    let mut preamble = Vec::new();
+    if let Some(system) = &req.system {
+        preamble.extend(text_blocks(system));
+    }
+    for tool in req.tools.iter().flatten() {
+        preamble.push(text_part(tool.to_string()));
+    }
+
+    let mut contents = Vec::with_capacity(req.messages.len() + 1);
+    if !preamble.is_empty() {
+        contents.push(turn("user", preamble));
+    }
+    for message in &req.messages {
+        let parts = message_parts(message);
+        if !parts.is_empty() {
+            let role = if message.role == "assistant" {
+                "model"
+            } else {
+                "user"
+            };
+            contents.push(turn(role, parts));
+        }
+    }
+    contents
+}
+
+/// Local estimate for `contents`, used when upstream cannot count them.
+pub fn estimate_tokens(contents: &[Content]) -> u64 {
+    let chars: usize = contents
+        .iter()
+        .flat_map(|c| &c.parts)
+        .map(|part| match (&part.text, &part.function_call) {
+            (Some(text), _) => text.chars().count(),
+            (None, Some(call)) => serde_json::to_string(call).map_or(0, |s| s.chars().count()),
+            (None, None) => 0,
+        })
+        .sum();
+    u64::try_from(chars)
+        .unwrap_or(u64::MAX)
+        .div_ceil(CHARS_PER_TOKEN)
+}
+
+fn turn(role: &str, parts: Vec<Part>) -> Content {
+    Content {
+        role: Some(role.to_string()),
+        parts,
+        extra: BTreeMap::new(),
+    }
+}
+
+fn text_part(text: impl Into<String>) -> Part {
+    Part {
+        text: Some(text.into()),
+        ..Part::default()
+    }
+}
+
+/// `system` or `tool_result.content`: a string or an array of text blocks.
+fn text_blocks(value: &Value) -> Vec<Part> {
+    match value {
+        Value::String(s) if !s.is_empty() => vec![text_part(s.as_str())],
+        Value::Array(blocks) => blocks
+            .iter()
+            .filter_map(|b| b.get("text").and_then(Value::as_str))
+            .map(text_part)
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn message_parts(message: &AnthropicMessage) -> Vec<Part> {
+    let Value::Array(blocks) = &message.content else {
+        return text_blocks(&message.content);
+    };
+    let mut parts = Vec::with_capacity(blocks.len());
+    for block in blocks {
+        let field = |name: &str| block.get(name).and_then(Value::as_str);
+        match field("type") {
+            Some("text") => parts.extend(field("text").map(text_part)),
+            Some("thinking") => parts.extend(field("thinking").map(|t| Part {
+                thought: Some(true),
+                ..text_part(t)
+            })),
+            Some("tool_use") => parts.push(Part {
+                function_call: Some(FunctionCall {
+                    name: field("name").map(ToString::to_string),
+                    args: block.get("input").cloned(),
+                    ..FunctionCall::default()
+                }),
+                ..Part::default()
+            }),
+            Some("tool_result") => {
+                parts.extend(block.get("content").map(text_blocks).unwrap_or_default());
+            }
+            _ => {}
+        }
+    }
+    parts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn system_tools_and_blocks_are_counted() {
+        let req: AnthropicCountTokensRequest = serde_json::from_value(json!({
+            "model": "claude-sonnet-4-5",
+            "system": "Be brief.",
+            "tools": [{"name": "Read", "input_schema": {"type": "object"}}],
+            "messages": [
+                {"role": "user", "content": "What is in a.txt?"},
+                {"role": "assistant", "content": [
+                    {"type": "thinking", "thinking": "Read it.", "signature": "sig"},
+                    {"type": "tool_use", "id": "toolu_1", "name": "Read", "input": {"path": "a.txt"}}
+                ]},
+                {"role": "user", "content": [
+                    {"type": "tool_result", "tool_use_id": "toolu_1", "content": [
+                        {"type": "text", "text": "hello"}
+                    ]},
+                    {"type": "image", "source": {"type": "base64", "data": "AAAA"}}
+                ]}
+            ]
+        }))
+        .unwrap();
+
+        let contents = count_tokens_contents(&req);
+        let roles: Vec<_> = contents.iter().map(|c| c.role.as_deref()).collect();
+        assert_eq!(
+            roles,
+            [Some("user"), Some("user"), Some("model"), Some("user")]
+        );
+        assert_eq!(contents[0].parts.len(), 2);
+        assert_eq!(contents[2].parts[0].thought, Some(true));
+        assert_eq!(
+            contents[2].parts[1]
+                .function_call
+                .as_ref()
+                .and_then(|c| c.name.as_deref()),
+            Some("Read")
+        );
+        assert_eq!(contents[3].parts.len(), 1);
+        assert!(estimate_tokens(&contents) > 0);
+    }
+
+    #[test]
+    fn estimate_rounds_up() {
+        let contents = vec![turn("user", vec![text_part("abcde")])];
+        assert_eq!(estimate_tokens(&contents), 2);
+        assert_eq!(estimate_tokens(&[]), 0);
+    }
+}
@@ -1,20 +1,27 @@
-use crate::config::AntigravityResolvedConfig;
+use crate::config::{AntigravityResolvedConfig, ModelOverrideConfig, SystemInstructionConfig};
 use crate::error::{GeminiCliErrorBody, IsRetryable, PolluxError};
+use crate::model_catalog::ModelCapabilities;
 use crate::providers::antigravity::AntigravityActorHandle;
+use crate::providers::error_clusters::ErrorClusters;
+use crate::providers::manifest::{AntigravityLease, ProviderKind};
 use crate::providers::policy::classify_upstream_error;
 use crate::providers::provider_endpoints::ProviderEndpoints;
 use crate::providers::upstream_retry::post_json_bytes_with_retry;
+use crate::providers::{model_overrides, system_instruction};
+use crate::utils::http::EgressClients;
 use crate::utils::logging::with_pretty_json_debug;
 use axum::body::Bytes;
 use backon::{ExponentialBuilder, Retryable};
 use chrono::Utc;
 use pollux_schema::{
-    antigravity::AntigravityRequestMeta, gemini::GeminiGenerateContentRequest,
-    gemini::GenerationConfig,
+    antigravity::AntigravityRequestMeta,
+    gemini::{Content, GeminiGenerateContentRequest, GenerationConfig},
+    geminicli::VertexCountTokensRequest,
 };
 use rand::Rng as _;
 use reqwest::header::{AUTHORIZATION, HeaderMap, HeaderValue, USER_AGENT};
 use serde_json::{Value, json};
+use std::collections::HashMap;
 use std::time::{Duration, Instant};
 use tracing::{debug, error, info, warn};
 use url::Url;
@@ -29,21 +36,35 @@ pub struct AntigravityContext {
     pub model: String,
     pub stream: bool,
     pub path: String,
-    pub model_mask: u64,
+    pub model_mask: ModelCapabilities,
+    /// Hash of `x-pollux-session`; pins the conversation to one credential while healthy.
+    pub route_key: Option<u64>,
+    /// Label from `x-pollux-pool`; only credentials carrying it are leased.
+    pub pool: Option<String>,
+}
+
+impl AntigravityContext {
+    /// `models/{model}:countTokens` rather than a generate call.
+    pub fn is_count_tokens(&self) -> bool {
+        self.path.ends_with(":countTokens")
+    }
 }
 
 pub struct AntigravityClient {
-    client: reqwest::Client,
-    stream_client: reqwest::Client,
+    clients: EgressClients,
     retry_policy: ExponentialBuilder,
     endpoints: ProviderEndpoints,
+    count_tokens_url: Url,
+    error_clusters: ErrorClusters,
+    system_instruction: Option<SystemInstructionConfig>,
+    stream_only_models: Vec<String>,
+    model_overrides: HashMap<String, ModelOverrideConfig>,
 }
 
 impl AntigravityClient {
-    pub fn new(
+    pub(crate) fn new(
         cfg: &AntigravityResolvedConfig,
-        client: reqwest::Client,
-        stream_client: reqwest::Client,
+        clients: EgressClients,
         base_url: Option<Url>,
     ) -> Self {
         let retry_policy = ExponentialBuilder::default()
@@ -51,21 +72,34 @@ impl AntigravityClient {
             .with_max_delay(Duration::from_millis(300))
             .with_max_times(cfg.retry_max_times)
             .with_jitter();
-        let endpoints = base_url.map_or_else(Self::default_endpoints, Self::endpoints_for_base);
+        let base_url = base_url.unwrap_or_else(Self::default_base_url);
+        let count_tokens_url = base_url
+            .join("./v1internal:countTokens")
+            .expect("valid endpoint path");
+        let endpoints = Self::endpoints_for_base(base_url);
 
         Self {
-            client,
-            stream_client,
+            clients,
             retry_policy,
             endpoints,
+            count_tokens_url,
+            error_clusters: ErrorClusters::default(),
+            system_instruction: cfg.system_instruction.clone(),
+            stream_only_models: cfg.stream_only_models.clone(),
+            model_overrides: cfg.model_overrides.clone(),
         }
     }
 
-    fn default_endpoints() -> ProviderEndpoints {
-        Self::endpoints_for_base(
-            Url::parse("https://daily-cloudcode-pa.googleapis.com")
-                .expect("invalid fixed Antigravity base URL"),
-        )
+    /// Count upstream error responses into `clusters`.
+    #[must_use]
+    pub(crate) fn with_error_clusters(mut self, clusters: ErrorClusters) -> Self {
+        self.error_clusters = clusters;
+        self
+    }
+
+    fn default_base_url() -> Url {
+        Url::parse("https://daily-cloudcode-pa.googleapis.com")
+            .expect("invalid fixed Antigravity base URL")
     }
 
     #[allow(clippy::needless_pass_by_value)]
@@ -79,39 +113,112 @@ impl AntigravityClient {
         )
     }
 
-    #[allow(clippy::too_many_lines)]
     pub async fn call_antigravity(
         &self,
         handle: &AntigravityActorHandle,
         ctx: &AntigravityContext,
         body: &GeminiGenerateContentRequest,
     ) -> Result<reqwest::Response, PolluxError> {
-        let handle = handle.clone();
-        let client = if ctx.stream {
-            self.stream_client.clone()
+        let upstream_ctx;
+        let ctx = if !ctx.stream && self.stream_only_models.contains(&ctx.model) {
+            upstream_ctx = AntigravityContext {
+                stream: true,
+                ..ctx.clone()
+            };
+            &upstream_ctx
         } else {
-            self.client.clone()
+            ctx
+        };
+        let model = ctx.model.as_str();
+        let payload = |lease: &AntigravityLease| {
+            let mut payload = AntigravityRequestMeta {
+                project: lease.project_id.clone(),
+                request_id: Self::generate_request_id(),
+                model: model.to_string(),
+            }
+            .into_request(body.clone());
+
+            system_instruction::apply(self.system_instruction.as_ref(), &mut payload.request);
+            if let Some(rule) = self.model_overrides.get(model) {
+                model_overrides::apply(model, rule, &mut payload.request);
+            }
+            Self::apply_claude_thinking_defaults(model, &mut payload.request);
+            Self::backfill_function_call_ids(model, &mut payload.request);
+
+            payload
+                .request
+                .extra
+                .entry("sessionId".to_string())
+                .or_insert_with(|| Value::String(Self::generate_session_id()));
+
+            with_pretty_json_debug(&payload, |pretty_payload| {
+                debug!(
+                    channel = "antigravity",
+                    lease.id = lease.id,
+                    req.model = %model,
+                    req.stream = ctx.stream,
+                    req.path = %ctx.path,
+                    body = %pretty_payload,
+                    "[Antigravity] Prepared upstream payload"
+                );
+            });
+            Ok(Bytes::from(serde_json::to_vec(&payload)?))
+        };
+        self.send(handle, ctx, self.endpoints.select(ctx.stream), &payload)
+            .await
+    }
+
+    /// `models/{model}:countTokens` on the Cloud Code `countTokens` RPC.
+    pub async fn count_tokens(
+        &self,
+        handle: &AntigravityActorHandle,
+        ctx: &AntigravityContext,
+        contents: &[Content],
+    ) -> Result<reqwest::Response, PolluxError> {
+        let payload = |_: &AntigravityLease| {
+            let payload = VertexCountTokensRequest::new(&ctx.model, contents);
+            Ok(Bytes::from(serde_json::to_vec(&payload)?))
         };
-        let endpoints = self.endpoints.clone();
+        self.send(handle, ctx, &self.count_tokens_url, &payload)
+            .await
+    }
+
+    #[allow(clippy::too_many_lines)]
+    async fn send<F>(
+        &self,
+        handle: &AntigravityActorHandle,
+        ctx: &AntigravityContext,
+        url: &Url,
+        payload: &F,
+    ) -> Result<reqwest::Response, PolluxError>
+    where
+        F: Fn(&AntigravityLease) -> Result<Bytes, PolluxError> + Sync,
+    {
+        let actor = handle.clone();
+        let clients = self.clients.clone();
+        let url = url.clone();
         let stream = ctx.stream;
         let model = ctx.model.clone();
-        let model_mask = ctx.model_mask;
+        let model_mask = ctx.model_mask.clone();
+        let route_key = ctx.route_key;
+        let pool = ctx.pool.clone();
         let path = ctx.path.clone();
-        let gemini_request = body.clone();
+        let error_clusters = self.error_clusters.clone();
 
         let op = {
-            let gemini_request = gemini_request.clone();
             move || {
-                let handle = handle.clone();
-                let client = client.clone();
-                let endpoints = endpoints.clone();
-                let gemini_request = gemini_request.clone();
+                let handle = actor.clone();
+                let error_clusters = error_clusters.clone();
+                let clients = clients.clone();
+                let url = url.clone();
                 let model = model.clone();
+                let model_mask = model_mask.clone();
                 let path = path.clone();
+                let pool = pool.clone();
                 async move {
                     let start = Instant::now();
                     let assigned = handle
-                        .get_credential(model_mask)
+                        .get_credential(model_mask.clone(), route_key, pool)
                         .await?
                         .ok_or(PolluxError::NoAvailableCredential)?;
 
@@ -129,44 +236,22 @@ impl AntigravityClient {
                         model
                     );
 
-                    let mut payload = AntigravityRequestMeta {
-                        project: assigned.project_id.clone(),
-                        request_id: Self::generate_request_id(),
-                        model: model.clone(),
-                    }
-                    .into_request(gemini_request.clone());
-
-                    Self::apply_claude_thinking_defaults(model.as_str(), &mut payload.request);
-                    Self::backfill_function_call_ids(model.as_str(), &mut payload.request);
-
-                    payload
-                        .request
-                        .extra
-                        .entry("sessionId".to_string())
-                        .or_insert_with(|| Value::String(Self::generate_session_id()));
-
-                    with_pretty_json_debug(&payload, |pretty_payload| {
-                        debug!(
-                            channel = "antigravity",
-                            lease.id = assigned.id,
-                            req.model = %model,
-                            req.stream = stream,
-                            req.path = %path,
-                            body = %pretty_payload,
-                            "[Antigravity] Prepared upstream payload"
-                        );
-                    });
-
-                    let request_body = Bytes::from(serde_json::to_vec(&payload)?);
+                    let request_body = payload(&assigned)?;
 
-                    let resp = post_json_bytes_with_retry(
+                    let sent = Instant::now();
+                    let mut resp = post_json_bytes_with_retry(
                         "Antigravity",
-                        &client,
-                        endpoints.select(stream),
+                        &clients.select(assigned.id, assigned.proxy_url.as_ref(), stream),
+                        &url,
                         Some(Self::headers(assigned.access_token.as_str())),
                         request_body,
                     )
-                    .await?;
+                    .await
+                    .inspect_err(|e| {
+                        if e.status().is_some_and(|s| s.is_server_error()) {
+                            handle.report_outcome(assigned.id, model_mask.clone(), false);
+                        }
+                    })?;
 
                     if !resp.status().is_success() {
                         let status = resp.status();
@@ -175,12 +260,24 @@ impl AntigravityClient {
                             resp,
                             |_json: GeminiCliErrorBody| PolluxError::UpstreamStatus(status),
                             |status, _body| PolluxError::UpstreamStatus(status),
+                            |status, body| {
+                                error_clusters.record(
+                                    ProviderKind::Antigravity.label(),
+                                    &model,
+                                    status,
+                                    body,
+                                );
+                            },
                         )
                         .await;
 
                         match &action {
                             crate::providers::ActionForError::RateLimit(duration) => {
-                                handle.report_rate_limit(assigned.id, model_mask, *duration);
+                                handle.report_rate_limit(
+                                    assigned.id,
+                                    model_mask.clone(),
+                                    *duration,
+                                );
                                 info!(
                                     "Project: {}, rate limited, retry in {:?}",
                                     assigned.project_id, duration
@@ -191,14 +288,18 @@ impl AntigravityClient {
                                 info!("Project: {}, banned", assigned.project_id);
                             }
                             crate::providers::ActionForError::ModelUnsupported => {
-                                handle.report_model_unsupported(assigned.id, model_mask);
+                                handle.report_model_unsupported(assigned.id, model_mask.clone());
                                 info!("Project: {}, model unsupported", assigned.project_id);
                             }
                             crate::providers::ActionForError::Invalid => {
                                 handle.report_invalid(assigned.id);
                                 info!("Project: {}, invalid", assigned.project_id);
                             }
-                            crate::providers::ActionForError::None => {}
+                            crate::providers::ActionForError::None => {
+                                if status.is_server_error() {
+                                    handle.report_outcome(assigned.id, model_mask.clone(), false);
+                                }
+                            }
                         }
 
                         warn!(
@@ -211,12 +312,15 @@ impl AntigravityClient {
 
                         return Err(final_error);
                     }
+                    handle.report_success(assigned.id, model_mask.clone(), sent.elapsed());
+                    assigned.attach(&mut resp);
                     Ok(resp)
                 }
             }
         };
 
-        op.retry(&self.retry_policy)
+        let result = op
+            .retry(&self.retry_policy)
             .when(|err: &PolluxError| err.is_retryable())
             .notify(|err, dur: Duration| {
                 error!(
@@ -224,11 +328,22 @@ impl AntigravityClient {
                     err.to_string(),
                     dur
                 );
+                crate::server::audit_log::note_retry();
             })
-            .await
+            .await;
+
+        match result {
+            Err(err) if err.pool_gave_up() => {
+                let after = handle
+                    .retry_after(ctx.model_mask.clone(), ctx.pool.clone())
+                    .await;
+                Err(err.with_retry_after(after))
+            }
+            other => other,
+        }
     }
 
-    fn headers(access_token: &str) -> HeaderMap {
+    pub(crate) fn headers(access_token: &str) -> HeaderMap {
         let mut headers = HeaderMap::new();
         headers.insert(
             AUTHORIZATION,
@@ -246,7 +361,7 @@ impl AntigravityClient {
         format!("{REQUEST_ID_PREFIX}/{timestamp_ms}/{request_uuid}")
     }
 
-    fn generate_request_id() -> String {
+    pub(crate) fn generate_request_id() -> String {
         Self::request_id_from_parts(Utc::now().timestamp_millis(), Uuid::new_v4())
     }
 
@@ -287,33 +402,28 @@ impl AntigravityClient {
             match content.role.as_deref() {
                 Some("model") => {
                     pending_call_ids.clear();
-                    for part in &mut content.parts {
-                        let Some(obj) = part.function_call.as_mut().and_then(|v| v.as_object_mut())
-                        else {
-                            continue;
-                        };
-                        let id_val = obj.entry("id").or_insert_with(|| {
-                            Value::String(format!("toolu_{}", Uuid::new_v4().simple()))
-                        });
-                        if let Some(id_str) = id_val.as_str() {
-                            pending_call_ids.push(id_str.to_string());
-                        }
+                    for call in content
+                        .parts
+                        .iter_mut()
+                        .filter_map(|part| part.function_call.as_mut())
+                    {
+                        let id = call
+                            .id
+                            .get_or_insert_with(|| format!("toolu_{}", Uuid::new_v4().simple()));
+                        pending_call_ids.push(id.clone());
                     }
                 }
                 Some("user") => {
                     let mut id_iter = pending_call_ids.drain(..);
-                    for part in &mut content.parts {
-                        let Some(obj) = part
-                            .function_response
-                            .as_mut()
-                            .and_then(|v| v.as_object_mut())
-                        else {
-                            continue;
-                        };
+                    for response in content
+                        .parts
+                        .iter_mut()
+                        .filter_map(|part| part.function_response.as_mut())
+                    {
                         let Some(matching_id) = id_iter.next() else {
                             break;
                         };
-                        obj.entry("id").or_insert(Value::String(matching_id));
+                        response.id.get_or_insert(matching_id);
                     }
                 }
                 _ => {}
@@ -338,7 +448,8 @@ mod tests {
 
     #[test]
     fn endpoints_use_expected_literals() {
-        let endpoints = AntigravityClient::default_endpoints();
+        let endpoints =
+            AntigravityClient::endpoints_for_base(AntigravityClient::default_base_url());
         assert_eq!(
             endpoints.select(false).as_str(),
             "https://daily-cloudcode-pa.googleapis.com/v1internal:generateContent"
@@ -47,6 +47,8 @@ struct OnboardUserRequest<'a> {
 #[derive(Debug, Clone, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub(crate) struct LoadCodeAssistResponse {
+    #[serde(default)]
+    pub current_tier: Option<AllowedTier>,
     pub cloudaicompanion_project: Option<String>,
     #[serde(default)]
     pub allowed_tiers: Vec<AllowedTier>,
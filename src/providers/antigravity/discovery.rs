@@ -0,0 +1,311 @@
+//! Live Antigravity model list from upstream `fetchAvailableModels`.
+//!
+//! With `providers.antigravity.model_discovery` set, a background task asks
+//! upstream which models every active credential may call and keeps the
+//! merged result. The model list route serves the configured models at least
+//! one credential offers; `/admin/v1/antigravity/models` reports per-model
+//! credential counts, unconfigured models included.
+
+use super::AntigravityClient;
+use super::manager::ops::CredentialOps;
+use crate::PolluxError;
+use crate::db::DbActorHandle;
+use crate::providers::manifest::AntigravityLease;
+use crate::providers::traits::provider::CredentialStore;
+use crate::providers::traits::scheduler::{CredentialId, Schedulable};
+use crate::utils::http::EgressClients;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tracing::{debug, info, warn};
+use url::Url;
+
+/// `v1internal:fetchAvailableModels` body; only model ids and quota are read.
+#[derive(Debug, Default, Deserialize)]
+struct AvailableModelsPayload {
+    #[serde(default)]
+    models: HashMap<String, ModelEntry>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ModelEntry {
+    #[serde(default)]
+    quota_info: Option<QuotaInfo>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct QuotaInfo {
+    /// Omitted by upstream once it reaches zero.
+    #[serde(default)]
+    remaining_fraction: Option<f64>,
+}
+
+impl AvailableModelsPayload {
+    /// Listed models with quota left; entries without quota info count as available.
+    fn available(self) -> BTreeSet<String> {
+        self.models
+            .into_iter()
+            .filter(|(_, entry)| {
+                entry
+                    .quota_info
+                    .as_ref()
+                    .is_none_or(|quota| quota.remaining_fraction.unwrap_or(0.0) > 0.0)
+            })
+            .map(|(name, _)| name)
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ModelAvailability {
+    pub model: String,
+    /// Credentials upstream offers the model to, with quota left.
+    pub credentials: usize,
+    /// In `model_list`, so requests for it are routed.
+    pub configured: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DiscoveryReport {
+    pub enabled: bool,
+    /// Last pass in which any credential answered; `None` before the first.
+    pub refreshed_at: Option<DateTime<Utc>>,
+    /// Credentials with a known model set.
+    pub credentials: usize,
+    pub models: Vec<ModelAvailability>,
+}
+
+#[derive(Debug, Default)]
+struct Discovered {
+    /// Models per credential, from its last successful answer.
+    per_credential: HashMap<CredentialId, BTreeSet<String>>,
+    refreshed_at: Option<DateTime<Utc>>,
+}
+
+/// Merged discovery results; cheap to clone.
+#[derive(Debug, Clone, Default)]
+pub struct ModelDiscovery {
+    inner: Arc<Mutex<Discovered>>,
+}
+
+impl ModelDiscovery {
+    /// Store one pass. Active credentials that did not answer keep their
+    /// previous set; credentials no longer active are dropped. A pass in
+    /// which nobody answered changes nothing.
+    fn record(
+        &self,
+        active: &HashSet<CredentialId>,
+        answers: HashMap<CredentialId, BTreeSet<String>>,
+    ) {
+        if answers.is_empty() {
+            return;
+        }
+        let Ok(mut inner) = self.inner.lock() else {
+            return;
+        };
+        inner.per_credential.retain(|id, _| active.contains(id));
+        inner.per_credential.extend(answers);
+        inner.refreshed_at = Some(Utc::now());
+    }
+
+    /// Models of `model_list`, in order, that at least one credential offers.
+    /// `None` until a pass succeeded, so callers fall back to the static list.
+    pub fn served(&self, model_list: &[String]) -> Option<Vec<String>> {
+        let inner = self.inner.lock().ok()?;
+        inner.refreshed_at?;
+        Some(
+            model_list
+                .iter()
+                .filter(|model| {
+                    inner
+                        .per_credential
+                        .values()
+                        .any(|models| models.contains(*model))
+                })
+                .cloned()
+                .collect(),
+        )
+    }
+
+    /// Per-model credential counts, configured models first in `model_list` order.
+    pub fn report(&self, model_list: &[String], enabled: bool) -> DiscoveryReport {
+        let Ok(inner) = self.inner.lock() else {
+            return DiscoveryReport {
+                enabled,
+                refreshed_at: None,
+                credentials: 0,
+                models: Vec::new(),
+            };
+        };
+        let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+        for model in inner.per_credential.values().flatten() {
+            *counts.entry(model).or_default() += 1;
+        }
+        let mut models: Vec<ModelAvailability> = model_list
+            .iter()
+            .map(|model| ModelAvailability {
+                model: model.clone(),
+                credentials: counts.remove(model.as_str()).unwrap_or(0),
+                configured: true,
+            })
+            .collect();
+        models.extend(
+            counts
+                .into_iter()
+                .map(|(model, credentials)| ModelAvailability {
+                    model: model.to_string(),
+                    credentials,
+                    configured: false,
+                }),
+        );
+        DiscoveryReport {
+            enabled,
+            refreshed_at: inner.refreshed_at,
+            credentials: inner.per_credential.len(),
+            models,
+        }
+    }
+
+    /// Ask upstream once for every active credential with a live access
+    /// token. Expired ones keep their previous set until the scheduler
+    /// refreshes them.
+    async fn refresh(
+        &self,
+        ops: &CredentialOps,
+        egress: &EgressClients,
+        url: &Url,
+    ) -> Result<(), PolluxError> {
+        let creds = ops.load_active().await?;
+        let active = creds.iter().map(|(id, _)| *id).collect::<HashSet<_>>();
+        let mut answers = HashMap::with_capacity(creds.len());
+        for (id, cred) in creds {
+            if cred.expires_within(Duration::ZERO) {
+                continue;
+            }
+            match fetch(egress, url, &cred.make_lease(id)).await {
+                Ok(models) => {
+                    answers.insert(id, models);
+                }
+                Err(e) => debug!(id, "[Antigravity] Model discovery request failed: {e}"),
+            }
+        }
+        info!(
+            active = active.len(),
+            answered = answers.len(),
+            "[Antigravity] Model discovery pass finished"
+        );
+        self.record(&active, answers);
+        Ok(())
+    }
+
+    /// Run a pass now and then every `interval` until the runtime shuts down.
+    pub(crate) fn spawn_poller(
+        &self,
+        db: DbActorHandle,
+        egress: EgressClients,
+        api_url: &Url,
+        interval: Duration,
+    ) {
+        let discovery = self.clone();
+        let ops = CredentialOps::new(db);
+        let url = api_url
+            .join("./v1internal:fetchAvailableModels")
+            .expect("valid endpoint path");
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                if let Err(e) = discovery.refresh(&ops, &egress, &url).await {
+                    warn!(error = %e, "[Antigravity] Model discovery pass failed");
+                }
+            }
+        });
+    }
+}
+
+async fn fetch(
+    egress: &EgressClients,
+    url: &Url,
+    lease: &AntigravityLease,
+) -> Result<BTreeSet<String>, PolluxError> {
+    let resp = egress
+        .select(lease.id, lease.proxy_url.as_ref(), false)
+        .post(url.clone())
+        .headers(AntigravityClient::headers(&lease.access_token))
+        .json(&serde_json::json!({ "project": lease.project_id }))
+        .send()
+        .await?;
+    let status = resp.status();
+    if !status.is_success() {
+        return Err(PolluxError::UpstreamStatus(status));
+    }
+    Ok(resp.json::<AvailableModelsPayload>().await?.available())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn set(models: &[&str]) -> BTreeSet<String> {
+        models.iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn exhausted_models_are_not_available() {
+        let payload: AvailableModelsPayload = serde_json::from_str(
+            r#"{"models":{
+                "gemini-2.5-pro":{"quotaInfo":{"remainingFraction":0.5}},
+                "gemini-2.5-flash":{"quotaInfo":{"resetTime":"2026-01-01T00:00:00Z"}},
+                "claude-sonnet-4-5":{}
+            }}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            payload.available(),
+            set(&["claude-sonnet-4-5", "gemini-2.5-pro"])
+        );
+    }
+
+    #[test]
+    fn passes_merge_per_credential_and_filter_the_configured_list() {
+        let discovery = ModelDiscovery::default();
+        let configured = vec!["a".to_string(), "b".to_string(), "c".to_string()];
+        assert_eq!(discovery.served(&configured), None);
+
+        let active = HashSet::from([1, 2]);
+        discovery.record(
+            &active,
+            HashMap::from([(1, set(&["a", "x"])), (2, set(&["a", "c"]))]),
+        );
+        assert_eq!(
+            discovery.served(&configured),
+            Some(vec!["a".to_string(), "c".to_string()])
+        );
+        let unconfigured = discovery.report(&configured, true).models.pop().unwrap();
+        assert_eq!(
+            (unconfigured.model.as_str(), unconfigured.credentials),
+            ("x", 1)
+        );
+        assert!(!unconfigured.configured);
+
+        // Credential 2 fails this pass and keeps its set; 3 is new.
+        discovery.record(&HashSet::from([1, 2, 3]), HashMap::from([(3, set(&["b"]))]));
+        assert_eq!(discovery.served(&configured).unwrap(), configured);
+
+        // Credential 1 is gone; an empty pass is ignored.
+        discovery.record(&HashSet::from([2, 3]), HashMap::from([(3, set(&["b"]))]));
+        discovery.record(&HashSet::new(), HashMap::new());
+        let report = discovery.report(&configured, true);
+        assert_eq!(report.credentials, 2);
+        let counts = report
+            .models
+            .iter()
+            .map(|m| (m.model.as_str(), m.credentials, m.configured))
+            .collect::<Vec<_>>();
+        assert_eq!(counts, [("a", 1, true), ("b", 1, true), ("c", 1, true)]);
+    }
+}
@@ -0,0 +1,161 @@
+//! Antigravity probes for `pollux doctor`.
+
+use super::ANTIGRAVITY_USER_AGENT;
+use super::client::AntigravityClient;
+use super::client::oauth::OAUTH_RETRY_POLICY;
+use super::manager::{Antigravity, ops::CredentialOps};
+use super::resource::AntigravityResource;
+use crate::config::AntigravityResolvedConfig;
+use crate::db::DbActorHandle;
+use crate::providers::doctor::{
+    DoctorArgs, DoctorReport, PROBE_PROMPT, db_id, expect_json, http_client, probe_sse,
+    token_validity,
+};
+use crate::providers::manifest::AntigravityLease;
+use crate::providers::traits::oauth_worker::refresh_credential;
+use crate::providers::traits::provider::{CredentialStore, ProviderResource};
+use crate::providers::traits::scheduler::Schedulable;
+use pollux_schema::{antigravity::AntigravityRequestMeta, gemini::GeminiGenerateContentRequest};
+use serde_json::{Value, json};
+use std::sync::Arc;
+use std::time::Instant;
+use url::Url;
+
+pub(crate) async fn diagnose(
+    report: &mut DoctorReport,
+    cfg: &AntigravityResolvedConfig,
+    db: &DbActorHandle,
+    args: &DoctorArgs,
+) {
+    let id = args.id;
+    let Some(mut cred) = report
+        .step("load", async {
+            let row = db
+                .get_antigravity_by_id(db_id(id)?)
+                .await
+                .map_err(|e| e.to_string())?;
+            let cred = AntigravityResource::from(row);
+            let detail = format!(
+                "project {}, {}",
+                cred.make_lease(id).project_id,
+                token_validity(cred.expiry())
+            );
+            Ok((cred, detail))
+        })
+        .await
+    else {
+        return;
+    };
+
+    if args.refresh {
+        let client = http_client(cfg.proxy.as_ref(), Some(ANTIGRAVITY_USER_AGENT));
+        let provider = Antigravity::new(Arc::new(cfg.clone()));
+        report
+            .step("refresh", async {
+                refresh_credential(&provider, client, *OAUTH_RETRY_POLICY, &mut cred, None)
+                    .await
+                    .map_err(|e| e.to_string())?;
+                CredentialOps::new(db.clone())
+                    .save_refreshed(id, &cred)
+                    .await
+                    .map_err(|e| format!("refreshed but not saved: {e}"))?;
+                Ok(((), format!("{}, saved", token_validity(cred.expiry()))))
+            })
+            .await;
+    } else {
+        report.skip("refresh");
+    }
+
+    let lease = cred.make_lease(id);
+    let client = http_client(cfg.proxy.as_ref(), None);
+    let base = &cfg.api_url;
+    let model = args
+        .model
+        .clone()
+        .or_else(|| cfg.model_list.first().cloned())
+        .unwrap_or_default();
+
+    report
+        .step("models", async {
+            let resp = client
+                .post(endpoint(base, "fetchAvailableModels", None))
+                .headers(AntigravityClient::headers(&lease.access_token))
+                .json(&json!({ "project": lease.project_id }))
+                .send()
+                .await
+                .map_err(|e| e.to_string())?;
+            let body = expect_json(resp).await?;
+            let models: Vec<&str> = body
+                .get("models")
+                .and_then(Value::as_object)
+                .into_iter()
+                .flat_map(|m| m.keys().map(String::as_str))
+                .collect();
+            Ok((
+                (),
+                format!("{} models: {}", models.len(), models.join(", ")),
+            ))
+        })
+        .await;
+
+    report
+        .step("generate", async {
+            let resp = generate(&client, base, &lease, &model, false).await?;
+            let body = expect_json(resp).await?;
+            let finish = body
+                .pointer("/response/candidates/0/finishReason")
+                .and_then(Value::as_str)
+                .unwrap_or("no candidate");
+            Ok(((), format!("{model}: {finish}")))
+        })
+        .await;
+
+    report
+        .step("stream", async {
+            let sent = Instant::now();
+            let resp = generate(&client, base, &lease, &model, true).await?;
+            let probe = probe_sse(resp, sent).await?;
+            Ok(((), probe.summary()))
+        })
+        .await;
+}
+
+fn endpoint(base: &Url, method: &str, query: Option<&str>) -> Url {
+    let mut url = base
+        .join(&format!("./v1internal:{method}"))
+        .expect("valid Antigravity endpoint path");
+    url.set_query(query);
+    url
+}
+
+async fn generate(
+    client: &reqwest::Client,
+    base: &Url,
+    lease: &AntigravityLease,
+    model: &str,
+    stream: bool,
+) -> Result<reqwest::Response, String> {
+    let request: GeminiGenerateContentRequest = serde_json::from_value(json!({
+        "contents": [{ "role": "user", "parts": [{ "text": PROBE_PROMPT }] }],
+        "generationConfig": { "maxOutputTokens": 1 },
+    }))
+    .map_err(|e| e.to_string())?;
+    let body = AntigravityRequestMeta {
+        project: lease.project_id.clone(),
+        request_id: AntigravityClient::generate_request_id(),
+        model: model.to_string(),
+    }
+    .into_request(request);
+    let url = if stream {
+        endpoint(base, "streamGenerateContent", Some("alt=sse"))
+    } else {
+        endpoint(base, "generateContent", None)
+    };
+    client
+        .post(url)
+        .headers(AntigravityClient::headers(&lease.access_token))
+        .json(&body)
+        .send()
+        .await
+        .map_err(|e| e.to_string())
+}
@@ -1,4 +1,143 @@
-pub(crate) mod actor;
+mod onboard;
 pub(crate) mod ops;
 
-pub(in crate::providers) use actor::spawn;
+use super::client::oauth::{OAUTH_RETRY_POLICY, endpoints::AntigravityOauthEndpoints};
+use super::resource::AntigravityResource;
+use crate::config::AntigravityResolvedConfig;
+use crate::error::{OauthError, PolluxError};
+use crate::model_catalog::{MODEL_REGISTRY, ModelCapabilities};
+use crate::oauth_utils::OauthTokenResponse;
+use crate::providers::ModelProber;
+use crate::providers::manifest::ProviderKind;
+use crate::providers::traits::actor::ProviderActorHandle;
+use crate::providers::traits::provider::{
+    OauthWorkerSettings, PoolSettings, Provider, ProviderResource,
+};
+use backon::ExponentialBuilder;
+use governor::DefaultDirectRateLimiter;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+
+use ops::CredentialOps;
+
+/// Antigravity accounts on the shared pool actor. Ingests also discover the
+/// account's project and, when configured, probe which models it serves.
+pub struct Antigravity {
+    cfg: Arc<AntigravityResolvedConfig>,
+    prober: Option<ModelProber>,
+}
+
+impl Antigravity {
+    pub(crate) fn new(cfg: Arc<AntigravityResolvedConfig>) -> Self {
+        let prober = cfg
+            .capability_probe
+            .map(|probe| ModelProber::new(probe, &cfg.api_url, cfg.model_list.clone()));
+        Self { cfg, prober }
+    }
+}
+
+impl Provider for Antigravity {
+    const NAME: &'static str = "Antigravity";
+    const KIND: ProviderKind = ProviderKind::Antigravity;
+
+    type Resource = AntigravityResource;
+    type Store = CredentialOps;
+
+    fn supported_models(&self) -> (ModelCapabilities, Vec<String>) {
+        let mask = self
+            .cfg
+            .model_list
+            .iter()
+            .filter_map(|name| MODEL_REGISTRY.get_index(name))
+            .collect();
+        (mask, self.cfg.model_list.clone())
+    }
+
+    fn oauth_retry_policy(&self) -> ExponentialBuilder {
+        *OAUTH_RETRY_POLICY
+    }
+
+    fn refresh_access_token(
+        &self,
+        refresh_token: &str,
+        http_client: reqwest::Client,
+    ) -> impl Future<Output = Result<OauthTokenResponse, OauthError>> + Send {
+        AntigravityOauthEndpoints::refresh_access_token(&self.cfg, refresh_token, http_client)
+    }
+
+    async fn onboard(
+        &self,
+        cred: &mut AntigravityResource,
+        http_client: reqwest::Client,
+        limiter: &DefaultDirectRateLimiter,
+    ) -> Result<ModelCapabilities, PolluxError> {
+        let (project_id, tier) =
+            onboard::ensure_project_id(cred.access_token(), &self.cfg, http_client.clone()).await?;
+        cred.set_project_id(project_id);
+        if let Some(tier) = tier {
+            cred.set_tier(tier);
+        }
+
+        Ok(match &self.prober {
+            Some(prober) => onboard::probe_models(prober, &http_client, limiter, cred).await,
+            None => ModelCapabilities::none(),
+        })
+    }
+}
+
+pub type AntigravityActorHandle = ProviderActorHandle<Antigravity>;
+
+pub(in crate::providers) async fn spawn(
+    db: crate::db::DbActorHandle,
+    cfg: Arc<AntigravityResolvedConfig>,
+) -> AntigravityActorHandle {
+    let settings = PoolSettings {
+        endpoint: cfg.api_url.to_string(),
+        retry_max_times: cfg.retry_max_times,
+        auto_disable: cfg.auto_disable,
+        health: cfg.health,
+        min_token_validity: Duration::from_secs(cfg.min_token_validity_secs),
+        stale_grace: Duration::from_secs(cfg.stale_grace_secs),
+        capability_restore: Duration::from_secs(cfg.capability_restore_secs),
+        max_concurrent_per_credential: cfg.max_concurrent_per_credential,
+        tier_weights: cfg.tier_weights.clone(),
+        scheduling_policy: cfg.scheduling_policy,
+        priority_reserve: cfg.priority_reserve,
+        lease_wait: Duration::from_millis(cfg.lease_wait_ms),
+        oauth: OauthWorkerSettings {
+            proxy: cfg.proxy.clone(),
+            user_agent: Some("antigravity-oauth/1.0"),
+            enable_multiplexing: cfg.enable_multiplexing,
+            http_client: cfg.http_client,
+            oauth_tps: cfg.oauth_tps,
+        },
+    };
+    crate::providers::traits::actor::spawn(db, Antigravity::new(cfg), settings).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::RefreshTokenSeed;
+    use serde_json::json;
+
+    #[test]
+    fn untrusted_ingest_keeps_seed_refresh_token() {
+        let token_response: OauthTokenResponse = serde_json::from_value(json!({
+            "access_token": "at-1",
+            "token_type": "bearer",
+            "expires_in": 3600,
+        }))
+        .expect("token response deserializes");
+        let seed = RefreshTokenSeed::new("seed-rt").expect("valid seed");
+
+        let cred = AntigravityResource::try_from_oauth_token_response(&token_response, Some(&seed))
+            .expect("credential from token response");
+
+        assert_eq!(cred.access_token(), "at-1");
+        assert_eq!(cred.refresh_token(), "seed-rt");
+        // The project comes from onboarding, after the token grant.
+        assert!(!cred.has_identity());
+    }
+}
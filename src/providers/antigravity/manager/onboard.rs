@@ -0,0 +1,179 @@
+//! Project discovery and capability probe run when an Antigravity
+//! credential is ingested.
+
+use crate::config::AntigravityResolvedConfig;
+use crate::error::{OauthError, PolluxError};
+use crate::model_catalog::ModelCapabilities;
+use crate::providers::ModelProber;
+use crate::providers::antigravity::AntigravityClient;
+use crate::providers::antigravity::client::oauth::ops::{
+    AntigravityOauthOps, LoadCodeAssistResponse,
+};
+use crate::providers::antigravity::resource::AntigravityResource;
+use crate::providers::traits::provider::ProviderResource;
+use crate::utils::logging::payload_logging_enabled;
+use governor::DefaultDirectRateLimiter;
+use pollux_schema::antigravity::AntigravityRequestMeta;
+use serde::Deserialize;
+use serde_json::Value;
+use std::time::Duration;
+use tokio::time::sleep;
+use tracing::{debug, info};
+
+pub(super) async fn probe_models(
+    prober: &ModelProber,
+    client: &reqwest::Client,
+    limiter: &DefaultDirectRateLimiter,
+    cred: &AntigravityResource,
+) -> ModelCapabilities {
+    let access_token = cred.access_token();
+    let generate_body = |model: &str, request: &_| {
+        let payload = AntigravityRequestMeta {
+            project: cred.project_id().to_string(),
+            request_id: AntigravityClient::generate_request_id(),
+            model: model.to_string(),
+        }
+        .into_request(Clone::clone(request));
+        serde_json::to_vec(&payload)
+    };
+    prober
+        .probe(
+            "Antigravity",
+            client,
+            limiter,
+            |_| AntigravityClient::headers(access_token),
+            generate_body,
+        )
+        .await
+}
+
+/// Resolves (or provisions) the project id and reports the account's quota
+/// tier id alongside it, when upstream names one.
+pub(super) async fn ensure_project_id(
+    access_token: &str,
+    cfg: &AntigravityResolvedConfig,
+    http_client: reqwest::Client,
+) -> Result<(String, Option<String>), PolluxError> {
+    let load_json =
+        AntigravityOauthOps::load_code_assist_with_retry(cfg, access_token, http_client.clone())
+            .await?;
+    if payload_logging_enabled() {
+        debug!(body = %load_json, "antigravity loadCodeAssist upstream body");
+    }
+
+    let load_resp: LoadCodeAssistResponse =
+        serde_json::from_value(load_json.clone()).map_err(PolluxError::JsonError)?;
+
+    let default_tier = load_resp
+        .allowed_tiers
+        .iter()
+        .find(|t| t.is_default)
+        .and_then(|t| t.id.clone());
+    let tier = load_resp
+        .current_tier
+        .as_ref()
+        .and_then(|t| t.id.clone())
+        .or_else(|| default_tier.clone());
+
+    if let Some(pid) = load_resp
+        .cloudaicompanion_project
+        .clone()
+        .filter(|s| !s.trim().is_empty())
+    {
+        return Ok((pid, tier));
+    }
+
+    let tier_id = default_tier.unwrap_or_else(|| "LEGACY".to_string());
+    let project_id = perform_onboarding(access_token, cfg, tier_id.as_str(), http_client).await?;
+    Ok((project_id, tier))
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct OnboardUserOperation {
+    #[serde(default)]
+    done: bool,
+    #[serde(default)]
+    response: Option<OnboardUserResponse>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct OnboardUserResponse {
+    #[serde(rename = "cloudaicompanionProject")]
+    project: Option<ProjectIdOrObject>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum ProjectIdOrObject {
+    String(String),
+    Object { id: String },
+}
+
+impl ProjectIdOrObject {
+    fn into_id(self) -> Option<String> {
+        match self {
+            ProjectIdOrObject::String(s) if !s.trim().is_empty() => Some(s),
+            ProjectIdOrObject::Object { id } if !id.trim().is_empty() => Some(id),
+            _ => None,
+        }
+    }
+}
+
+async fn perform_onboarding(
+    access_token: &str,
+    cfg: &AntigravityResolvedConfig,
+    tier_id: &str,
+    http_client: reqwest::Client,
+) -> Result<String, PolluxError> {
+    const MAX_ATTEMPTS: usize = 5;
+    const RETRY_DELAY: Duration = Duration::from_secs(2);
+    let mut last_resp: Option<Value> = None;
+
+    for attempt in 1..=MAX_ATTEMPTS {
+        let resp_json = AntigravityOauthOps::onboard_user_with_retry(
+            cfg,
+            access_token,
+            tier_id,
+            http_client.clone(),
+        )
+        .await?;
+        if payload_logging_enabled() {
+            debug!(body = %resp_json, "antigravity onboardUser upstream body");
+        }
+        last_resp = Some(resp_json.clone());
+
+        let op: OnboardUserOperation =
+            serde_json::from_value(resp_json.clone()).map_err(PolluxError::JsonError)?;
+        if op.done {
+            return op
+                .response
+                .and_then(|r| r.project)
+                .and_then(ProjectIdOrObject::into_id)
+                .ok_or_else(|| {
+                    OauthError::Flow {
+                        code: "ONBOARD_FAILED".to_string(),
+                        message: "Onboarding completed but returned no project ID".to_string(),
+                        details: Some(resp_json),
+                    }
+                    .into()
+                });
+        }
+
+        if attempt < MAX_ATTEMPTS {
+            info!(
+                "antigravity onboardUser pending (attempt {}/{}), retrying in {:?}...",
+                attempt, MAX_ATTEMPTS, RETRY_DELAY
+            );
+            sleep(RETRY_DELAY).await;
+        }
+    }
+
+    Err(OauthError::Flow {
+        code: "ONBOARD_TIMEOUT".to_string(),
+        message: "Project provisioning timed out".to_string(),
+        details: last_resp,
+    }
+    .into())
+}
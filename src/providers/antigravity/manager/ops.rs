@@ -1,8 +1,12 @@
 use crate::db::{
-    AntigravityCreate, AntigravityPatch, DbActorHandle, ProviderCreate, ProviderPatch,
+    AntigravityCreate, AntigravityPatch, DbActorHandle, ProviderCreate, ProviderDelete,
+    ProviderIdentity, ProviderPatch, synthetic_sub_from_refresh_token,
 };
 use crate::error::PolluxError;
 use crate::providers::antigravity::resource::AntigravityResource;
+use crate::providers::credential_view::CredentialView;
+use crate::providers::seed::StoredSeed;
+use crate::providers::traits::provider::{CredentialStore, ProviderResource};
 use crate::providers::traits::scheduler::CredentialId;
 
 #[derive(Clone)]
@@ -11,25 +15,7 @@ pub struct CredentialOps {
 }
 
 impl CredentialOps {
-    pub fn new(db: DbActorHandle) -> Self {
-        Self { db }
-    }
-
-    pub async fn load_active(
-        &self,
-    ) -> Result<Vec<(CredentialId, AntigravityResource)>, PolluxError> {
-        let rows = self.db.list_active_antigravity().await?;
-        let mut result = Vec::with_capacity(rows.len());
-        for row in rows {
-            let id = u64::try_from(row.id).map_err(|_| {
-                PolluxError::UnexpectedError(format!("Invalid credential id {}", row.id))
-            })?;
-            result.push((id, row.into()));
-        }
-        Ok(result)
-    }
-
-    pub async fn upsert(&self, create: AntigravityCreate) -> Result<CredentialId, PolluxError> {
+    async fn upsert(&self, create: AntigravityCreate) -> Result<CredentialId, PolluxError> {
         if create.project_id.trim().is_empty() {
             return Err(PolluxError::UnexpectedError(
                 "Antigravity credential missing project_id".to_string(),
@@ -41,7 +27,7 @@ impl CredentialOps {
             .map_err(|_| PolluxError::UnexpectedError(format!("Invalid credential id {id}")))
     }
 
-    pub async fn update_by_id(
+    async fn update_by_id(
         &self,
         id: CredentialId,
         patch: AntigravityPatch,
@@ -55,8 +41,88 @@ impl CredentialOps {
             .await?;
         Ok(())
     }
+}
 
-    pub async fn set_status(&self, id: CredentialId, status: bool) -> Result<(), PolluxError> {
+impl CredentialStore<AntigravityResource> for CredentialOps {
+    fn new(db: DbActorHandle) -> Self {
+        Self { db }
+    }
+
+    async fn load_active(&self) -> Result<Vec<(CredentialId, AntigravityResource)>, PolluxError> {
+        let rows = self.db.list_active_antigravity().await?;
+        let mut result = Vec::with_capacity(rows.len());
+        for row in rows {
+            let id = u64::try_from(row.id).map_err(|_| {
+                PolluxError::UnexpectedError(format!("Invalid credential id {}", row.id))
+            })?;
+            result.push((id, row.into()));
+        }
+        Ok(result)
+    }
+
+    /// Store a freshly onboarded credential. An account (`sub`, synthesized
+    /// from the refresh token when missing, as the DB layer does) that already
+    /// has a row keeps its id: the row is updated in place and re-enabled.
+    async fn store_onboarded(
+        &self,
+        cred: AntigravityResource,
+    ) -> Result<StoredSeed<AntigravityResource>, PolluxError> {
+        let create = AntigravityCreate::from(cred);
+        let sub = create
+            .sub
+            .clone()
+            .unwrap_or_else(|| synthetic_sub_from_refresh_token(&create.refresh_token));
+        let existing = self
+            .db
+            .find_by_identity(ProviderIdentity::Antigravity { sub })
+            .await?;
+        let Some(id) = existing
+            .map(|id| {
+                u64::try_from(id).map_err(|_| {
+                    PolluxError::UnexpectedError(format!("Invalid credential id {id}"))
+                })
+            })
+            .transpose()?
+        else {
+            let id = self.upsert(create.clone()).await?;
+            return Ok(StoredSeed {
+                id,
+                credential: create.into(),
+                existing: false,
+            });
+        };
+
+        let patch = AntigravityPatch {
+            email: create.email,
+            refresh_token: Some(create.refresh_token),
+            access_token: create.access_token,
+            expiry: Some(create.expiry),
+            tier: create.tier,
+            status: Some(true),
+            labels: (!create.labels.is_empty()).then_some(create.labels),
+        };
+        self.update_by_id(id, patch).await?;
+        Ok(StoredSeed {
+            id,
+            credential: self.get_by_id(id).await?,
+            existing: true,
+        })
+    }
+
+    async fn save_refreshed(
+        &self,
+        id: CredentialId,
+        cred: &AntigravityResource,
+    ) -> Result<(), PolluxError> {
+        let patch = AntigravityPatch {
+            access_token: Some(cred.access_token().to_string()),
+            expiry: Some(cred.expiry()),
+            ..Default::default()
+        };
+        self.update_by_id(id, patch).await
+    }
+
+    async fn set_status(&self, id: CredentialId, status: bool) -> Result<(), PolluxError> {
         let _ = i64::try_from(id)
             .map_err(|_| PolluxError::UnexpectedError(format!("Invalid credential id {id}")))?;
 
@@ -68,4 +134,28 @@ impl CredentialOps {
             .patch(ProviderPatch::Antigravity { id, patch })
             .await
     }
+
+    /// All stored rows (any status), for admin listing.
+    async fn load_all_views(&self) -> Result<Vec<CredentialView>, PolluxError> {
+        let rows = self.db.list_antigravity().await?;
+        Ok(rows.into_iter().map(CredentialView::from).collect())
+    }
+
+    async fn get_by_id(&self, id: CredentialId) -> Result<AntigravityResource, PolluxError> {
+        let db_id = i64::try_from(id)
+            .map_err(|_| PolluxError::UnexpectedError(format!("Invalid credential id {id}")))?;
+        match self.db.get_antigravity_by_id(db_id).await {
+            Ok(row) => Ok(row.into()),
+            Err(PolluxError::DatabaseError(sqlx::Error::RowNotFound)) => {
+                Err(PolluxError::NotFound(format!("antigravity record id={id}")))
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    async fn delete(&self, id: CredentialId) -> Result<(), PolluxError> {
+        let db_id = i64::try_from(id)
+            .map_err(|_| PolluxError::UnexpectedError(format!("Invalid credential id {id}")))?;
+        self.db.delete(ProviderDelete::Antigravity(db_id)).await
+    }
 }
@@ -1,22 +1,33 @@
-use crate::db::{AntigravityCreate, DbAntigravityResource};
+use crate::db::{AntigravityCreate, DbAntigravityResource, split_labels};
 use crate::error::PolluxError;
+use crate::oauth_utils::OauthTokenResponse;
+use crate::providers::RefreshTokenSeed;
 use crate::providers::manifest::{AntigravityLease, AntigravityProfile};
+use crate::providers::traits::provider::ProviderResource;
 use crate::providers::traits::scheduler::{CredentialId, Schedulable};
 use chrono::{DateTime, Duration, Utc};
+use oauth2::TokenResponse;
 use serde::{Deserialize, Serialize};
 use tracing::debug;
+use url::Url;
 
 /// In-memory credential state for the Antigravity provider.
 ///
 /// Mirrors `geminicli::resource` to keep scheduling and refresh semantics aligned.
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub(crate) struct AntigravityResource {
+pub struct AntigravityResource {
     email: Option<String>,
     sub: String,
     project_id: String,
+    #[serde(default)]
+    tier: Option<String>,
     refresh_token: String,
     access_token: Option<String>,
     expiry: DateTime<Utc>,
+    #[serde(default)]
+    labels: Vec<String>,
+    #[serde(default)]
+    proxy_url: Option<Url>,
 }
 
 impl Default for AntigravityResource {
@@ -25,44 +36,71 @@ impl Default for AntigravityResource {
             email: None,
             sub: String::new(),
             project_id: String::new(),
+            tier: None,
             refresh_token: String::new(),
             access_token: None,
             expiry: Utc::now(),
+            labels: Vec::new(),
+            proxy_url: None,
         }
     }
 }
 
 impl AntigravityResource {
-    /// Return true if current time is within 5 minutes of expiry (inclusive).
-    /// This early-expiry buffer avoids edge cases during requests.
-    pub fn is_expired(&self) -> bool {
-        Utc::now() + Duration::minutes(5) >= self.expiry
-    }
-
     #[allow(dead_code)]
     pub fn sub(&self) -> &str {
         &self.sub
     }
 
-    #[allow(dead_code)]
-    pub fn email(&self) -> Option<&str> {
-        self.email.as_deref()
+    pub fn project_id(&self) -> &str {
+        &self.project_id
+    }
+
+    pub fn set_project_id(&mut self, project_id: String) {
+        self.project_id = project_id;
+    }
+
+    pub fn set_tier(&mut self, tier: String) {
+        self.tier = Some(tier);
+    }
+
+    #[cfg(test)]
+    pub fn from_payload(payload: impl Serialize) -> Result<Self, PolluxError> {
+        let mut cred = AntigravityResource::default();
+        cred.update_credential(payload)?;
+        Ok(cred)
     }
+}
 
-    pub fn refresh_token(&self) -> &str {
+impl ProviderResource for AntigravityResource {
+    fn refresh_token(&self) -> &str {
         &self.refresh_token
     }
 
-    #[allow(dead_code)]
-    pub fn expiry(&self) -> DateTime<Utc> {
+    fn access_token(&self) -> &str {
+        self.access_token.as_deref().unwrap_or_default()
+    }
+
+    fn expiry(&self) -> DateTime<Utc> {
         self.expiry
     }
 
+    fn proxy_url(&self) -> Option<&Url> {
+        self.proxy_url.as_ref()
+    }
+
+    fn set_labels(&mut self, labels: Vec<String>) {
+        self.labels = labels;
+    }
+
+    fn set_proxy_url(&mut self, proxy_url: Option<Url>) {
+        self.proxy_url = proxy_url;
+    }
+
     /// Merge updates from any JSON-serializable payload into this resource.
     ///
     /// This is intentionally similar to other providers' resource patch merge.
-    #[allow(dead_code)]
-    pub fn update_credential(&mut self, payload: impl Serialize) -> Result<(), PolluxError> {
+    fn update_credential(&mut self, payload: impl Serialize) -> Result<(), PolluxError> {
         use crate::providers::credential_update::{apply_expiry, parse_patch, set_opt, set_plain};
 
         #[derive(Debug, Default, Deserialize)]
@@ -89,12 +127,48 @@ impl AntigravityResource {
         Ok(())
     }
 
-    #[cfg(test)]
-    #[allow(dead_code)]
-    pub fn from_payload(payload: impl Serialize) -> Result<Self, PolluxError> {
-        let mut cred = AntigravityResource::default();
-        cred.update_credential(payload)?;
-        Ok(cred)
+    /// Only the tokens: the project is resolved by onboarding, and the
+    /// account is keyed by its refresh token when the store sees no `sub`.
+    fn try_from_oauth_token_response(
+        token_response: &OauthTokenResponse,
+        refresh_seed: Option<&RefreshTokenSeed>,
+    ) -> Result<Self, PolluxError> {
+        let access_token = Some(token_response.access_token().secret().trim())
+            .filter(|&s| !s.is_empty())
+            .map(ToString::to_string)
+            .ok_or_else(|| {
+                PolluxError::UnexpectedError("Missing access_token in OAuth token response".into())
+            })?;
+
+        let expiry = Utc::now()
+            + token_response
+                .expires_in()
+                .unwrap_or(std::time::Duration::from_hours(1));
+
+        let refresh_token = token_response
+            .refresh_token()
+            .map(|t| t.secret().trim())
+            .filter(|&s| !s.is_empty())
+            .or_else(|| refresh_seed.map(RefreshTokenSeed::refresh_token))
+            .map(ToString::to_string)
+            .ok_or_else(|| {
+                PolluxError::UnexpectedError("Missing refresh_token in OAuth token response".into())
+            })?;
+
+        Ok(AntigravityResource {
+            refresh_token,
+            access_token: Some(access_token),
+            expiry,
+            ..Default::default()
+        })
+    }
+
+    fn has_identity(&self) -> bool {
+        !self.project_id.trim().is_empty()
+    }
+
+    fn email(&self) -> Option<&str> {
+        self.email.as_deref()
     }
 }
 
@@ -105,8 +179,18 @@ impl Schedulable for AntigravityResource {
         &self.project_id
     }
 
-    fn is_expired(&self) -> bool {
-        self.is_expired()
+    fn expires_within(&self, min_validity: std::time::Duration) -> bool {
+        Duration::from_std(min_validity)
+            .ok()
+            .and_then(|margin| Utc::now().checked_add_signed(margin))
+            .is_none_or(|deadline| deadline >= self.expiry)
+    }
+
+    fn expired_beyond(&self, grace: std::time::Duration) -> bool {
+        Duration::from_std(grace)
+            .ok()
+            .and_then(|grace| self.expiry.checked_add_signed(grace))
+            .is_none_or(|deadline| Utc::now() > deadline)
     }
 
     fn make_lease(&self, id: CredentialId) -> AntigravityLease {
@@ -114,8 +198,17 @@ impl Schedulable for AntigravityResource {
             id,
             project_id: self.project_id.clone(),
             access_token: self.access_token.clone().unwrap_or_default(),
+            proxy_url: self.proxy_url.clone(),
         }
     }
+
+    fn labels(&self) -> &[String] {
+        &self.labels
+    }
+
+    fn tier(&self) -> Option<&str> {
+        self.tier.as_deref()
+    }
 }
 
 impl From<AntigravityProfile> for AntigravityResource {
@@ -136,9 +229,12 @@ impl From<AntigravityCreate> for AntigravityResource {
             email: c.email,
             sub: c.sub.unwrap_or_default(),
             project_id: c.project_id,
+            tier: c.tier,
             refresh_token: c.refresh_token,
             access_token: c.access_token,
             expiry: c.expiry,
+            labels: c.labels,
+            proxy_url: c.proxy_url,
         }
     }
 }
@@ -149,9 +245,12 @@ impl From<DbAntigravityResource> for AntigravityResource {
             email: d.email,
             sub: d.sub,
             project_id: d.project_id,
+            tier: d.tier,
             refresh_token: d.refresh_token,
             access_token: d.access_token,
             expiry: d.expiry,
+            labels: split_labels(&d.labels),
+            proxy_url: d.proxy_url.and_then(|url| url.parse().ok()),
         }
     }
 }
@@ -160,11 +259,16 @@ impl From<AntigravityResource> for AntigravityCreate {
     fn from(cred: AntigravityResource) -> Self {
         AntigravityCreate {
             email: cred.email,
-            sub: Some(cred.sub),
+            // Empty until upstream reports one; the DB layer then keys the
+            // account by its refresh token.
+            sub: (!cred.sub.is_empty()).then_some(cred.sub),
             project_id: cred.project_id,
+            tier: cred.tier,
             refresh_token: cred.refresh_token,
             access_token: cred.access_token,
             expiry: cred.expiry,
+            labels: cred.labels,
+            proxy_url: cred.proxy_url,
         }
     }
 }
@@ -1,6 +1,6 @@
 use pollux_schema::gemini::{GeminiGenerateContentRequest, Part};
 use pollux_thoughtsig_core::{
-    PatchEvent, PatchOutcome, Patchable, SignaturePatcher, SignaturePreview,
+    PatchEvent, PatchOutcome, PatchSummary, Patchable, SignaturePatcher, SignaturePreview,
 };
 use tracing::debug;
 
@@ -8,6 +8,12 @@ struct GeminiPartPatch<'a>(&'a mut Part);
 
 impl Patchable for GeminiPartPatch<'_> {
     fn data(&self) -> PatchEvent<'_> {
+        // Media parts (e.g. generated images) carry no text to key a cached
+        // signature on; leave them exactly as the client sent them.
+        if self.0.is_media() {
+            return PatchEvent::None;
+        }
+
         if self.0.thought == Some(true) {
             if let Some(text) = self.0.text.as_deref() {
                 return PatchEvent::ThoughtText(text);
@@ -33,7 +39,8 @@ impl Patchable for GeminiPartPatch<'_> {
 pub(super) fn patch_request(
     request: &mut GeminiGenerateContentRequest,
     patcher: &SignaturePatcher,
-) {
+) -> PatchSummary {
+    let mut summary = PatchSummary::default();
     for (content_idx, content) in request.contents.iter_mut().enumerate() {
         if content.role.as_deref() != Some("model") {
             continue;
@@ -46,10 +53,11 @@ pub(super) fn patch_request(
 
             let mut part_patch = GeminiPartPatch(part);
             let outcome = patcher.patch(&mut part_patch);
+            summary.record(&outcome);
 
             match outcome {
                 PatchOutcome::Skipped => true,
-                PatchOutcome::Patched { cache_key } => {
+                PatchOutcome::Patched { cache_key } | PatchOutcome::Fallback { cache_key } => {
                     debug!(
                         channel = "antigravity",
                         thoughtsig.phase = "fill",
@@ -75,6 +83,7 @@ pub(super) fn patch_request(
             }
         });
     }
+    summary
 }
 
 #[cfg(test)]
@@ -201,7 +210,9 @@ mod tests {
     #[test]
     fn patch_request_keeps_cached_thought_part() {
         let (patcher, engine) = drop_patcher_with_engine();
-        let key = CacheKeyGenerator::generate_text("model thought").expect("text key must exist");
+        let key = CacheKeyGenerator::default()
+            .generate_text("model thought")
+            .expect("text key must exist");
         engine.put_signature(key, Arc::from("sig_thought_001"));
 
         let mut request = parse_request(json!({
@@ -218,13 +229,14 @@ mod tests {
             ]
         }));
 
-        patch_request(&mut request, &patcher);
+        let summary = patch_request(&mut request, &patcher);
 
         assert_eq!(request.contents[0].parts.len(), 1);
         assert_eq!(
             request.contents[0].parts[0].thought_signature.as_deref(),
             Some("sig_thought_001")
         );
+        assert_eq!(summary.hits, 1);
     }
 
     #[test]
@@ -274,4 +286,41 @@ mod tests {
             Some("client_sig_123")
         );
     }
+
+    #[test]
+    fn patch_request_keeps_media_thought_parts() {
+        let patcher = drop_patcher();
+        let mut request = parse_request(json!({
+            "contents": [
+                {
+                    "role": "model",
+                    "parts": [
+                        {
+                            "thought": true,
+                            "inlineData": {"mimeType": "image/png", "data": "iVBORw0KGgo="}
+                        },
+                        {
+                            "thought": true,
+                            "text": "model thought"
+                        }
+                    ]
+                }
+            ]
+        }));
+
+        let summary = patch_request(&mut request, &patcher);
+
+        let parts = &request.contents[0].parts;
+        assert_eq!(parts.len(), 1);
+        assert_eq!(
+            summary,
+            PatchSummary {
+                hits: 0,
+                misses: 0,
+                dropped: 1
+            }
+        );
+        assert!(parts[0].is_media());
+        assert!(parts[0].thought_signature.is_none());
+    }
 }
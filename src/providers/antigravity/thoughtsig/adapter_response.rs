@@ -1,56 +1,74 @@
 use pollux_schema::gemini::{GeminiResponseBody, Part};
-use pollux_thoughtsig_core::{SniffEvent, Sniffable};
+use pollux_thoughtsig_core::{SniffEvent, Sniffable, SniffableChunk};
+use std::borrow::Cow;
 
 pub(super) struct GeminiResponseAdapter<'a>(pub &'a GeminiResponseBody);
 
-impl Sniffable for GeminiResponseAdapter<'_> {
-    fn data(&self) -> SniffEvent<'_> {
-        let part = self
-            .0
-            .candidates
-            .first()
-            .and_then(|candidate| candidate.content.as_ref())
-            .and_then(|content| content.parts.first());
-
-        let Some(part) = part else {
-            return SniffEvent::None;
+/// One part of the first candidate, as the sniffer sees it.
+struct GeminiPartSniff<'a> {
+    part: Option<&'a Part>,
+    index: Option<u32>,
+    finished: bool,
+}
+
+impl SniffableChunk for GeminiResponseAdapter<'_> {
+    fn visit_parts(&self, visit: &mut dyn FnMut(&dyn Sniffable)) {
+        let Some(candidate) = self.0.candidates.first() else {
+            return;
         };
+        let parts = candidate
+            .content
+            .as_ref()
+            .map_or(&[][..], |content| content.parts.as_slice());
+        let finished = candidate.finish_reason.is_some();
 
-        match part {
-            Part {
+        if parts.is_empty() {
+            // A bare finish chunk still closes the candidate.
+            visit(&GeminiPartSniff {
+                part: None,
+                index: candidate.index,
+                finished,
+            });
+            return;
+        }
+        let last = parts.len() - 1;
+        for (i, part) in parts.iter().enumerate() {
+            visit(&GeminiPartSniff {
+                part: Some(part),
+                index: candidate.index,
+                finished: finished && i == last,
+            });
+        }
+    }
+}
+
+impl Sniffable for GeminiPartSniff<'_> {
+    fn data(&self) -> SniffEvent<'_> {
+        match self.part {
+            Some(Part {
                 function_call: Some(function_call),
                 ..
-            } => SniffEvent::FunctionCall(function_call),
-            Part {
+            }) => serde_json::to_value(function_call).map_or(SniffEvent::None, |value| {
+                SniffEvent::FunctionCall(Cow::Owned(value))
+            }),
+            Some(Part {
                 thought: Some(true),
                 text: Some(text),
                 ..
-            } => SniffEvent::ThoughtText(text),
+            }) => SniffEvent::ThoughtText(text),
             _ => SniffEvent::None,
         }
     }
 
     fn thought_signature(&self) -> Option<&str> {
-        self.0
-            .candidates
-            .first()
-            .and_then(|candidate| candidate.content.as_ref())
-            .and_then(|content| content.parts.first())
-            .and_then(|part| part.thought_signature.as_deref())
+        self.part.and_then(|part| part.thought_signature.as_deref())
     }
 
     fn index(&self) -> Option<u32> {
-        self.0
-            .candidates
-            .first()
-            .and_then(|candidate| candidate.index)
+        self.index
     }
 
     fn is_finished(&self) -> bool {
-        self.0
-            .candidates
-            .first()
-            .and_then(|candidate| candidate.finish_reason.as_ref())
-            .is_some()
+        self.finished
     }
 }
@@ -2,7 +2,8 @@ use super::adapter_request::patch_request;
 use super::adapter_response::GeminiResponseAdapter;
 use pollux_schema::gemini::{GeminiGenerateContentRequest, GeminiResponseBody};
 use pollux_thoughtsig_core::{
-    CacheMissPolicy, SignaturePatcher, SignatureSniffer, ThoughtSignatureEngine,
+    CacheMissPolicy, Invalidation, MemorySignatureStore, PatchSummary, SignatureCacheStats,
+    SignatureCacheStore, SignaturePatcher, SignatureSniffer, SniffLimits, ThoughtSignatureEngine,
 };
 use std::sync::Arc;
 
@@ -13,6 +14,7 @@ const DEFAULT_MAX_CAPACITY: u64 = 200_000;
 pub struct AntigravityThoughtSigService {
     engine: Arc<ThoughtSignatureEngine>,
     patcher: Arc<SignaturePatcher>,
+    limits: SniffLimits,
 }
 
 impl Default for AntigravityThoughtSigService {
@@ -23,26 +25,54 @@ impl Default for AntigravityThoughtSigService {
 
 impl AntigravityThoughtSigService {
     pub fn new() -> Self {
-        let engine = Arc::new(ThoughtSignatureEngine::new(
+        Self::with_store(Arc::new(MemorySignatureStore::new(
             DEFAULT_TTL_SECS,
             DEFAULT_MAX_CAPACITY,
-        ));
+        )))
+    }
+
+    pub fn with_store(store: Arc<dyn SignatureCacheStore>) -> Self {
+        Self::with_engine(ThoughtSignatureEngine::with_store(store))
+    }
+
+    /// Service over a configured engine, e.g. one with a non-default fingerprint.
+    pub fn with_engine(engine: ThoughtSignatureEngine) -> Self {
+        let engine = Arc::new(engine);
         let patcher = Arc::new(SignaturePatcher::new(engine.clone(), CacheMissPolicy::Drop));
 
-        Self { engine, patcher }
+        Self {
+            engine,
+            patcher,
+            limits: SniffLimits::default(),
+        }
+    }
+
+    /// Bound what each response sniffer buffers.
+    #[must_use]
+    pub fn with_sniff_limits(mut self, limits: SniffLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    pub fn patch_request(&self, request: &mut GeminiGenerateContentRequest) -> PatchSummary {
+        patch_request(request, &self.patcher)
+    }
+
+    /// Sniffer for one response to a `model` request.
+    pub fn build_sniffer(&self, model: &str) -> SignatureSniffer {
+        SignatureSniffer::with_limits(self.engine.clone(), self.limits).with_model(model)
     }
 
-    pub fn patch_request(&self, request: &mut GeminiGenerateContentRequest) {
-        patch_request(request, &self.patcher);
+    pub fn cache_stats(&self) -> SignatureCacheStats {
+        self.engine.stats()
     }
 
-    pub fn build_sniffer(&self) -> SignatureSniffer {
-        SignatureSniffer::new(self.engine.clone())
+    pub fn invalidate_cache(&self, scope: &Invalidation) {
+        self.engine.invalidate(scope);
     }
 
     pub fn sniff_response(&self, response: &GeminiResponseBody, sniffer: &mut SignatureSniffer) {
-        let adapter = GeminiResponseAdapter(response);
-        sniffer.inspect(&adapter);
+        sniffer.inspect_chunk(&GeminiResponseAdapter(response));
     }
 }
 
@@ -96,7 +126,7 @@ mod tests {
         }))
         .expect("response json must parse");
 
-        let mut sniffer = service.build_sniffer();
+        let mut sniffer = service.build_sniffer("gemini-2.5-pro");
         service.sniff_response(&response, &mut sniffer);
 
         let mut req: GeminiGenerateContentRequest = serde_json::from_value(json!({
@@ -149,7 +179,7 @@ mod tests {
         }))
         .expect("response json must parse");
 
-        let mut sniffer = service.build_sniffer();
+        let mut sniffer = service.build_sniffer("gemini-2.5-pro");
         service.sniff_response(&response, &mut sniffer);
 
         let mut req: GeminiGenerateContentRequest = serde_json::from_value(json!({
@@ -216,7 +246,7 @@ mod tests {
         }))
         .expect("chunk with signature must parse");
 
-        let mut sniffer = service.build_sniffer();
+        let mut sniffer = service.build_sniffer("gemini-2.5-pro");
         service.sniff_response(&chunk_without_signature, &mut sniffer);
         service.sniff_response(&chunk_with_signature, &mut sniffer);
 
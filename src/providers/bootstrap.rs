@@ -1,13 +1,27 @@
 use crate::config::{
-    AntigravityResolvedConfig, CodexResolvedConfig, Config, GeminiCliResolvedConfig,
+    AntigravityResolvedConfig, ClaudeResolvedConfig, CodexResolvedConfig, Config, DailyQuotaConfig,
+    GeminiCliResolvedConfig, QwenResolvedConfig,
 };
-use crate::db::DbActorHandle;
+use crate::db::{DbActorHandle, UsageQuery};
+use crate::model_catalog::ModelCapabilities;
 use crate::providers::antigravity::AntigravityActorHandle;
 use crate::providers::antigravity::AntigravityThoughtSigService;
+use crate::providers::claude::ClaudeActorHandle;
 use crate::providers::codex::CodexActorHandle;
+use crate::providers::error_clusters::ErrorClusters;
+use crate::providers::experiment::ExperimentService;
 use crate::providers::geminicli::{GeminiCliActorHandle, GeminiThoughtSigService};
+use crate::providers::manifest::ProviderKind;
+use crate::providers::quota::DailyQuota;
+use crate::providers::qwen::QwenActorHandle;
+use crate::providers::response_cache::ResponseCache;
+use crate::providers::thoughtsig_store::signature_store;
+use crate::providers::traits::scheduler::CredentialId;
+use crate::providers::usage::UsageTracker;
+use pollux_thoughtsig_core::{SniffLimits, ThoughtSignatureEngine};
 use std::sync::Arc;
-use tracing::info;
+use std::time::Duration;
+use tracing::{info, warn};
 
 /// Aggregates handles for all enabled providers.
 ///
@@ -15,42 +29,78 @@ use tracing::info;
 /// compile-time ergonomics and avoid over-abstracting too early.
 #[derive(Clone)]
 pub struct Providers {
+    pub db: DbActorHandle,
     pub geminicli: GeminiCliActorHandle,
     pub geminicli_cfg: Arc<GeminiCliResolvedConfig>,
     pub geminicli_thoughtsig: GeminiThoughtSigService,
+    pub geminicli_experiment: ExperimentService,
     pub codex: CodexActorHandle,
     pub codex_cfg: Arc<CodexResolvedConfig>,
     pub antigravity: AntigravityActorHandle,
     pub antigravity_cfg: Arc<AntigravityResolvedConfig>,
     pub antigravity_thoughtsig: AntigravityThoughtSigService,
+    pub claude: ClaudeActorHandle,
+    pub claude_cfg: Arc<ClaudeResolvedConfig>,
+    pub qwen: QwenActorHandle,
+    pub qwen_cfg: Arc<QwenResolvedConfig>,
+    /// Per-provider caches for deterministic non-streaming requests; `None`
+    /// unless `providers.<p>.response_cache` is set.
+    pub geminicli_response_cache: Option<ResponseCache>,
+    pub codex_response_cache: Option<ResponseCache>,
+    pub antigravity_response_cache: Option<ResponseCache>,
+    /// Per-provider soft daily limits; `None` unless
+    /// `providers.<p>.daily_quota` is set.
+    pub geminicli_quota: Option<DailyQuota>,
+    pub codex_quota: Option<DailyQuota>,
+    pub antigravity_quota: Option<DailyQuota>,
+    pub claude_quota: Option<DailyQuota>,
+    pub qwen_quota: Option<DailyQuota>,
+    /// Fingerprinted upstream errors for `/admin/v1/errors`.
+    pub error_clusters: ErrorClusters,
 }
 
 impl Providers {
+    #[allow(clippy::too_many_lines)]
     pub async fn spawn(db: DbActorHandle, cfg: &Config) -> Self {
         let provider_defaults = &cfg.providers.defaults;
         let geminicli_cfg = Arc::new(cfg.geminicli());
         let codex_cfg = Arc::new(cfg.codex());
         let antigravity_cfg = Arc::new(cfg.antigravity());
+        let claude_cfg = Arc::new(cfg.claude());
+        let qwen_cfg = Arc::new(cfg.qwen());
 
         // Log resolved provider configs here so `main` stays wiring-only.
         info!(
             providers_defaults_proxy = %provider_defaults.proxy.as_ref().map_or("<none>", url::Url::as_str),
+            providers_defaults_proxy_pool = provider_defaults.proxy_pool.len(),
             providers_defaults_enable_multiplexing = provider_defaults.enable_multiplexing,
             providers_defaults_retry_max_times = provider_defaults.retry_max_times,
             "Provider defaults loaded"
         );
         info!(
             geminicli_proxy = %geminicli_cfg.proxy.as_ref().map_or("<none>", url::Url::as_str),
+            geminicli_proxy_pool = geminicli_cfg.proxy_pool.len(),
             geminicli_enable_multiplexing = geminicli_cfg.enable_multiplexing,
             geminicli_retry_max_times = geminicli_cfg.retry_max_times,
             geminicli_oauth_tps = geminicli_cfg.oauth_tps,
             geminicli_model_list = ?geminicli_cfg.model_list,
             "Gemini CLI config (effective)"
         );
+        if let Some(exp) = &geminicli_cfg.experiment {
+            info!(
+                experiment = %exp.name,
+                fraction = exp.fraction,
+                user_agent = exp.user_agent.is_some(),
+                preamble = exp.preamble.is_some(),
+                thinking_budget = ?exp.thinking_budget,
+                "Gemini CLI request-shape experiment enabled"
+            );
+        }
 
         info!(
             codex_custom_api_url = %codex_cfg.custom_api_url,
             codex_proxy = %codex_cfg.proxy.as_ref().map_or("<none>", url::Url::as_str),
+            codex_proxy_pool = codex_cfg.proxy_pool.len(),
             codex_enable_multiplexing = codex_cfg.enable_multiplexing,
             codex_retry_max_times = codex_cfg.retry_max_times,
             codex_oauth_tps = codex_cfg.oauth_tps,
@@ -61,6 +111,7 @@ impl Providers {
         info!(
             antigravity_api_url = %antigravity_cfg.api_url.as_str(),
             antigravity_proxy = %antigravity_cfg.proxy.as_ref().map_or("<none>", url::Url::as_str),
+            antigravity_proxy_pool = antigravity_cfg.proxy_pool.len(),
             antigravity_enable_multiplexing = antigravity_cfg.enable_multiplexing,
             antigravity_retry_max_times = antigravity_cfg.retry_max_times,
             antigravity_oauth_tps = antigravity_cfg.oauth_tps,
@@ -68,21 +119,194 @@ impl Providers {
             "Antigravity config (effective)"
         );
 
+        info!(
+            claude_custom_api_url = %claude_cfg.custom_api_url,
+            claude_proxy = %claude_cfg.proxy.as_ref().map_or("<none>", url::Url::as_str),
+            claude_proxy_pool = claude_cfg.proxy_pool.len(),
+            claude_enable_multiplexing = claude_cfg.enable_multiplexing,
+            claude_retry_max_times = claude_cfg.retry_max_times,
+            claude_oauth_tps = claude_cfg.oauth_tps,
+            claude_model_list = ?claude_cfg.model_list,
+            "Claude config (effective)"
+        );
+
+        info!(
+            qwen_custom_api_url = %qwen_cfg
+                .custom_api_url
+                .as_ref()
+                .map_or("<per-account resource_url>", url::Url::as_str),
+            qwen_oauth_base_url = %qwen_cfg.oauth_base_url,
+            qwen_proxy = %qwen_cfg.proxy.as_ref().map_or("<none>", url::Url::as_str),
+            qwen_proxy_pool = qwen_cfg.proxy_pool.len(),
+            qwen_enable_multiplexing = qwen_cfg.enable_multiplexing,
+            qwen_retry_max_times = qwen_cfg.retry_max_times,
+            qwen_oauth_tps = qwen_cfg.oauth_tps,
+            qwen_model_list = ?qwen_cfg.model_list,
+            "Qwen config (effective)"
+        );
+
         let geminicli = crate::providers::geminicli::spawn(db.clone(), geminicli_cfg.clone()).await;
-        let geminicli_thoughtsig = GeminiThoughtSigService::new();
+        let geminicli_thoughtsig = GeminiThoughtSigService::with_engine(
+            ThoughtSignatureEngine::with_store(
+                signature_store(&db, ProviderKind::GeminiCli, &geminicli_cfg.thoughtsig).await,
+            )
+            .with_fingerprint(
+                geminicli_cfg.thoughtsig.fingerprint,
+                geminicli_cfg.thoughtsig.migrate_from,
+            ),
+        )
+        .with_sniff_limits(SniffLimits {
+            max_thought_bytes: geminicli_cfg.thoughtsig.max_buffered_thought_bytes,
+        });
+        let geminicli_experiment = ExperimentService::new(geminicli_cfg.experiment.clone());
         let codex = crate::providers::codex::spawn(db.clone(), codex_cfg.clone()).await;
-        let antigravity = crate::providers::antigravity::spawn(db, antigravity_cfg.clone()).await;
-        let antigravity_thoughtsig = AntigravityThoughtSigService::new();
+        let antigravity =
+            crate::providers::antigravity::spawn(db.clone(), antigravity_cfg.clone()).await;
+        let antigravity_thoughtsig = AntigravityThoughtSigService::with_engine(
+            ThoughtSignatureEngine::with_store(
+                signature_store(&db, ProviderKind::Antigravity, &antigravity_cfg.thoughtsig).await,
+            )
+            .with_fingerprint(
+                antigravity_cfg.thoughtsig.fingerprint,
+                antigravity_cfg.thoughtsig.migrate_from,
+            ),
+        )
+        .with_sniff_limits(SniffLimits {
+            max_thought_bytes: antigravity_cfg.thoughtsig.max_buffered_thought_bytes,
+        });
+        let claude = crate::providers::claude::spawn(db.clone(), claude_cfg.clone()).await;
+        let qwen = crate::providers::qwen::spawn(db.clone(), qwen_cfg.clone()).await;
+
+        let geminicli_response_cache = geminicli_cfg
+            .response_cache
+            .as_ref()
+            .map(ResponseCache::new);
+        let codex_response_cache = codex_cfg.response_cache.as_ref().map(ResponseCache::new);
+        let antigravity_response_cache = antigravity_cfg
+            .response_cache
+            .as_ref()
+            .map(ResponseCache::new);
+
+        let geminicli_quota = {
+            let handle = geminicli.clone();
+            daily_quota(
+                &db,
+                ProviderKind::GeminiCli,
+                geminicli_cfg.daily_quota,
+                move |id, mask, cd| {
+                    handle.report_rate_limit(id, mask, cd);
+                },
+            )
+            .await
+        };
+        let codex_quota = {
+            let handle = codex.clone();
+            daily_quota(
+                &db,
+                ProviderKind::Codex,
+                codex_cfg.daily_quota,
+                move |id, mask, cd| {
+                    handle.report_rate_limit(id, mask, cd);
+                },
+            )
+            .await
+        };
+        let antigravity_quota = {
+            let handle = antigravity.clone();
+            daily_quota(
+                &db,
+                ProviderKind::Antigravity,
+                antigravity_cfg.daily_quota,
+                move |id, mask, cd| {
+                    handle.report_rate_limit(id, mask, cd);
+                },
+            )
+            .await
+        };
+        let claude_quota = {
+            let handle = claude.clone();
+            daily_quota(
+                &db,
+                ProviderKind::Claude,
+                claude_cfg.daily_quota,
+                move |id, mask, cd| {
+                    handle.report_rate_limit(id, mask, cd);
+                },
+            )
+            .await
+        };
+        let qwen_quota = {
+            let handle = qwen.clone();
+            daily_quota(
+                &db,
+                ProviderKind::Qwen,
+                qwen_cfg.daily_quota,
+                move |id, mask, cd| {
+                    handle.report_rate_limit(id, mask, cd);
+                },
+            )
+            .await
+        };
 
         Self {
+            db,
             geminicli,
             geminicli_cfg,
             geminicli_thoughtsig,
+            geminicli_experiment,
             codex,
             codex_cfg,
             antigravity,
             antigravity_cfg,
             antigravity_thoughtsig,
+            claude,
+            claude_cfg,
+            qwen,
+            qwen_cfg,
+            geminicli_response_cache,
+            codex_response_cache,
+            antigravity_response_cache,
+            geminicli_quota,
+            codex_quota,
+            antigravity_quota,
+            claude_quota,
+            qwen_quota,
+            error_clusters: ErrorClusters::default(),
         }
     }
+
+    /// Start usage accounting for one proxied request.
+    pub fn track_usage(&self, provider: ProviderKind, model: &str) -> UsageTracker {
+        UsageTracker::start(self.db.clone(), provider.label(), model)
+            .with_quota(self.quota(provider).cloned())
+    }
+
+    pub fn quota(&self, provider: ProviderKind) -> Option<&DailyQuota> {
+        match provider {
+            ProviderKind::GeminiCli => self.geminicli_quota.as_ref(),
+            ProviderKind::Codex => self.codex_quota.as_ref(),
+            ProviderKind::Antigravity => self.antigravity_quota.as_ref(),
+            ProviderKind::Claude => self.claude_quota.as_ref(),
+            ProviderKind::Qwen => self.qwen_quota.as_ref(),
+        }
+    }
+}
+
+/// Build a provider's daily quota, seeded with today's recorded usage.
+async fn daily_quota(
+    db: &DbActorHandle,
+    kind: ProviderKind,
+    cfg: Option<DailyQuotaConfig>,
+    cooldown: impl Fn(CredentialId, ModelCapabilities, Duration) + Send + Sync + 'static,
+) -> Option<DailyQuota> {
+    let quota = DailyQuota::new(kind.label(), cfg?, cooldown);
+    let query = UsageQuery {
+        since: Some(quota.current_day_start()),
+        provider: Some(kind.label().to_string()),
+    };
+    match db.usage_summary(query).await {
+        Ok(rows) => quota.seed(&rows),
+        Err(e) => warn!(provider = kind.label(), "Daily quota seed failed: {e}"),
+    }
+    Some(quota)
 }
@@ -0,0 +1,159 @@
+//! Onboarding probe of which models a credential can serve.
+//!
+//! Without it a credential enters rotation with every configured model
+//! enabled and only loses a bit after a user request fails as unsupported.
+//! The probe sends one cheap request per generation model before activation.
+//! Only an upstream "model unsupported" answer clears a bit; rate limits,
+//! network errors and other failures leave it to real traffic to decide.
+
+use crate::config::{CapabilityProbeConfig, ProbeMethod};
+use crate::error::GeminiCliErrorBody;
+use crate::model_catalog::{MODEL_REGISTRY, ModelCapabilities};
+use crate::providers::policy::{ActionForError, classify_upstream_error};
+use crate::providers::upstream_retry::post_json_bytes_with_retry;
+use axum::body::Bytes;
+use governor::DefaultDirectRateLimiter;
+use pollux_schema::gemini::{Content, GeminiGenerateContentRequest, GenerationConfig, Part};
+use pollux_schema::geminicli::VertexCountTokensRequest;
+use reqwest::header::HeaderMap;
+use std::collections::BTreeMap;
+use tracing::{debug, info};
+use url::Url;
+
+const PROBE_TEXT: &str = "hi";
+
+/// Per-model probe against a Cloud Code `v1internal` endpoint.
+#[derive(Debug, Clone)]
+pub(crate) struct ModelProber {
+    method: ProbeMethod,
+    count_tokens_url: Url,
+    generate_url: Url,
+    models: Vec<String>,
+}
+
+impl ModelProber {
+    /// `base_url` is the Cloud Code root; `models` are the generation models to check.
+    pub(crate) fn new(cfg: CapabilityProbeConfig, base_url: &Url, models: Vec<String>) -> Self {
+        let rpc_url = |rpc: &str| {
+            base_url
+                .join(&format!("./v1internal:{rpc}"))
+                .expect("valid endpoint path")
+        };
+        Self {
+            method: cfg.method,
+            count_tokens_url: rpc_url("countTokens"),
+            generate_url: rpc_url("generateContent"),
+            models,
+        }
+    }
+
+    /// Models upstream rejected as unsupported for this credential.
+    ///
+    /// Each request waits on `limiter`, so probes share the refresh budget.
+    /// `headers` and `generate_body` supply the provider-specific auth and
+    /// envelope; `generate_body` is only used by [`ProbeMethod::Generate`].
+    pub(crate) async fn probe(
+        &self,
+        provider: &'static str,
+        client: &reqwest::Client,
+        limiter: &DefaultDirectRateLimiter,
+        headers: impl Fn(&str) -> HeaderMap,
+        generate_body: impl Fn(&str, &GeminiGenerateContentRequest) -> serde_json::Result<Vec<u8>>,
+    ) -> ModelCapabilities {
+        let contents = [Content {
+            role: Some("user".to_string()),
+            parts: vec![Part {
+                text: Some(PROBE_TEXT.to_string()),
+                ..Part::default()
+            }],
+            extra: BTreeMap::new(),
+        }];
+        let request = GeminiGenerateContentRequest {
+            contents: contents.to_vec(),
+            system_instruction: None,
+            generation_config: Some(GenerationConfig {
+                max_output_tokens: Some(1),
+                ..GenerationConfig::default()
+            }),
+            tools: None,
+            tool_config: None,
+            extra: BTreeMap::new(),
+        };
+
+        let mut unsupported = ModelCapabilities::none();
+        for model in &self.models {
+            let Some(index) = MODEL_REGISTRY.get_index(model) else {
+                continue;
+            };
+            let (url, body) = match self.method {
+                ProbeMethod::CountTokens => (
+                    &self.count_tokens_url,
+                    serde_json::to_vec(&VertexCountTokensRequest::new(model, &contents)),
+                ),
+                ProbeMethod::Generate => (&self.generate_url, generate_body(model, &request)),
+            };
+            let Ok(body) = body else {
+                continue;
+            };
+
+            limiter.until_ready().await;
+            match post_json_bytes_with_retry(
+                provider,
+                client,
+                url,
+                Some(headers(model)),
+                Bytes::from(body),
+            )
+            .await
+            {
+                Ok(resp) if resp.status().is_success() => {}
+                Ok(resp) => {
+                    if is_model_unsupported(resp).await {
+                        unsupported.enable(index);
+                    }
+                }
+                Err(e) => debug!(provider, model = %model, "capability probe request failed: {e}"),
+            }
+        }
+
+        if !unsupported.is_empty() {
+            info!(
+                provider,
+                unsupported = %crate::model_catalog::format_model_mask(&unsupported),
+                "capability probe found unsupported models"
+            );
+        }
+        unsupported
+    }
+}
+
+async fn is_model_unsupported(resp: reqwest::Response) -> bool {
+    let (action, ()) =
+        classify_upstream_error::<GeminiCliErrorBody, _>(resp, |_| (), |_, _| (), |_, _| {}).await;
+    action == ActionForError::ModelUnsupported
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http;
+
+    fn response(status: u16, body: &str) -> reqwest::Response {
+        let resp = http::Response::builder()
+            .status(status)
+            .body(body.to_string())
+            .unwrap();
+        reqwest::Response::from(resp)
+    }
+
+    #[tokio::test]
+    async fn only_not_found_counts_as_unsupported() {
+        let not_found =
+            r#"{"error":{"code":404,"message":"model not found","status":"NOT_FOUND"}}"#;
+        let exhausted = r#"{"error":{"code":429,"message":"quota","status":"RESOURCE_EXHAUSTED"}}"#;
+
+        assert!(is_model_unsupported(response(404, not_found)).await);
+        assert!(!is_model_unsupported(response(429, exhausted)).await);
+        assert!(!is_model_unsupported(response(400, "bad request")).await);
+    }
+}
@@ -0,0 +1,257 @@
+//! Pool rebalancing recommendations.
+//!
+//! Combines recorded usage outcomes per model with the live credential pool
+//! (which credentials can serve a model, and how many are cooling down) and
+//! turns them into capacity guidance for operators.
+
+use crate::db::ModelUsageStats;
+use crate::providers::credential_view::{CredentialState, CredentialView};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// Don't draw conclusions from a handful of requests.
+const MIN_REQUESTS: i64 = 20;
+/// Share of a model's credentials in cooldown that is worth flagging.
+const COOLDOWN_ALERT_RATIO: f64 = 0.5;
+/// Share of non-capacity errors that suggests a problem other than pool size.
+const ERROR_ALERT_RATE: f64 = 0.10;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RecommendationKind {
+    /// More credentials are needed to keep the `503` rate under target.
+    AddCredentials,
+    /// Most credentials for the model are in cooldown right now.
+    CooldownPressure,
+    /// High non-capacity error rate; adding credentials is unlikely to help.
+    InvestigateErrors,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Recommendation {
+    pub provider: String,
+    pub model: String,
+    pub kind: RecommendationKind,
+    /// Suggested number of additional credentials (`add_credentials` only).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub additional_credentials: Option<u64>,
+    pub message: String,
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+struct PoolCounts {
+    serving: u64,
+    cooling: u64,
+}
+
+fn pool_counts(views: &[CredentialView]) -> BTreeMap<(&'static str, &str), PoolCounts> {
+    let mut counts: BTreeMap<(&'static str, &str), PoolCounts> = BTreeMap::new();
+    for view in views {
+        if !matches!(
+            view.state,
+            CredentialState::Active | CredentialState::Refreshing
+        ) {
+            continue;
+        }
+        let provider = view.provider.label();
+        for model in &view.models {
+            counts.entry((provider, model)).or_default().serving += 1;
+        }
+        for cooldown in &view.cooldowns {
+            counts
+                .entry((provider, cooldown.model.as_str()))
+                .or_default()
+                .cooling += 1;
+        }
+    }
+    counts
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn rate(part: i64, total: i64) -> f64 {
+    if total <= 0 {
+        0.0
+    } else {
+        part as f64 / total as f64
+    }
+}
+
+/// Credentials needed so that, at the observed demand, the unavailable rate
+/// drops to `target`. Assumes capacity scales linearly with pool size.
+#[allow(
+    clippy::cast_possible_truncation,
+    clippy::cast_precision_loss,
+    clippy::cast_sign_loss
+)]
+fn additional_needed(serving: u64, unavailable_rate: f64, target: f64) -> u64 {
+    if serving == 0 {
+        return 1;
+    }
+    let served = (1.0 - unavailable_rate).max(f64::EPSILON);
+    let needed = (serving as f64 * (1.0 - target) / served).ceil() as u64;
+    needed.saturating_sub(serving).max(1)
+}
+
+/// Build recommendations from usage stats and the current credential pool.
+///
+/// `target_unavailable_rate` is the acceptable share of `503` responses
+/// (e.g. `0.01` for 1%).
+#[must_use]
+pub fn recommend(
+    stats: &[ModelUsageStats],
+    views: &[CredentialView],
+    target_unavailable_rate: f64,
+) -> Vec<Recommendation> {
+    let target = target_unavailable_rate.clamp(0.0, 0.99);
+    let pool = pool_counts(views);
+    let mut out = Vec::new();
+
+    for s in stats.iter().filter(|s| s.requests >= MIN_REQUESTS) {
+        let counts = pool
+            .get(&(s.provider.as_str(), s.model.as_str()))
+            .copied()
+            .unwrap_or_default();
+        let unavailable_rate = rate(s.unavailable, s.requests);
+        let error_rate = rate(s.other_errors, s.requests);
+
+        if unavailable_rate > target {
+            let extra = additional_needed(counts.serving, unavailable_rate, target);
+            out.push(Recommendation {
+                provider: s.provider.clone(),
+                model: s.model.clone(),
+                kind: RecommendationKind::AddCredentials,
+                additional_credentials: Some(extra),
+                message: format!(
+                    "model {} needs ~{extra} more credential(s) to keep the 503 rate under {:.1}% \
+                     (currently {:.1}% with {} serving)",
+                    s.model,
+                    target * 100.0,
+                    unavailable_rate * 100.0,
+                    counts.serving
+                ),
+            });
+        }
+
+        #[allow(clippy::cast_precision_loss)]
+        let cooling_ratio = if counts.serving == 0 {
+            0.0
+        } else {
+            counts.cooling as f64 / counts.serving as f64
+        };
+        if cooling_ratio >= COOLDOWN_ALERT_RATIO && s.rate_limited + s.unavailable > 0 {
+            out.push(Recommendation {
+                provider: s.provider.clone(),
+                model: s.model.clone(),
+                kind: RecommendationKind::CooldownPressure,
+                additional_credentials: None,
+                message: format!(
+                    "{} of {} credential(s) for model {} are cooling down",
+                    counts.cooling, counts.serving, s.model
+                ),
+            });
+        }
+
+        if error_rate >= ERROR_ALERT_RATE {
+            out.push(Recommendation {
+                provider: s.provider.clone(),
+                model: s.model.clone(),
+                kind: RecommendationKind::InvestigateErrors,
+                additional_credentials: None,
+                message: format!(
+                    "{:.1}% of requests for model {} failed with non-capacity errors",
+                    error_rate * 100.0,
+                    s.model
+                ),
+            });
+        }
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::credential_view::CooldownView;
+    use crate::providers::manifest::ProviderKind;
+    use chrono::Utc;
+
+    fn stats(requests: i64, unavailable: i64, other_errors: i64) -> ModelUsageStats {
+        ModelUsageStats {
+            provider: "codex".to_string(),
+            model: "gpt-5".to_string(),
+            requests,
+            unavailable,
+            rate_limited: 0,
+            other_errors,
+        }
+    }
+
+    fn view(id: u64, cooling: bool) -> CredentialView {
+        CredentialView {
+            id,
+            provider: ProviderKind::Codex,
+            state: CredentialState::Active,
+            email: None,
+            project_id: None,
+            account_id: None,
+            plan_type: None,
+            labels: Vec::new(),
+            proxy_url: None,
+            capability_mask: None,
+            models: vec!["gpt-5".to_string()],
+            cooldowns: if cooling {
+                vec![CooldownView {
+                    model: "gpt-5".to_string(),
+                    remaining_secs: 30,
+                }]
+            } else {
+                Vec::new()
+            },
+            in_flight: 0,
+            quota_remaining_percent: None,
+            health: None,
+            expiry: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn high_unavailable_rate_suggests_more_credentials() {
+        // 3 credentials serve 75% of demand; ~4 are needed to serve 99%.
+        let recs = recommend(
+            &[stats(100, 25, 0)],
+            &[view(1, false), view(2, false), view(3, false)],
+            0.01,
+        );
+        assert_eq!(recs.len(), 1);
+        assert_eq!(recs[0].kind, RecommendationKind::AddCredentials);
+        assert_eq!(recs[0].additional_credentials, Some(1));
+        assert!(recs[0].message.contains("gpt-5"));
+    }
+
+    #[test]
+    fn empty_pool_and_small_samples() {
+        let recs = recommend(&[stats(50, 50, 0)], &[], 0.01);
+        assert_eq!(recs[0].additional_credentials, Some(1));
+
+        assert!(recommend(&[stats(5, 5, 5)], &[], 0.01).is_empty());
+    }
+
+    #[test]
+    fn cooldowns_and_errors_are_flagged_separately() {
+        let mut s = stats(100, 2, 20);
+        s.rate_limited = 10;
+        let kinds: Vec<_> = recommend(&[s], &[view(1, true), view(2, true), view(3, false)], 0.05)
+            .into_iter()
+            .map(|r| r.kind)
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                RecommendationKind::CooldownPressure,
+                RecommendationKind::InvestigateErrors
+            ]
+        );
+    }
+}
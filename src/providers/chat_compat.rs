@@ -0,0 +1,1015 @@
+//! `OpenAI` Chat Completions ⇄ Gemini translation.
+//!
+//! Used by the OpenAI-compatible routes of Gemini-shaped providers. Requests
+//! are rewritten into a native `generateContent` body (including `tools`,
+//! `tool_choice` and `tool` turns); responses and stream chunks are rewritten
+//! back into `chat.completion` / `chat.completion.chunk` objects.
+//!
+//! Gemini has no tool call ids, so ids are minted here and mapped back to
+//! function names through the assistant turns of the conversation history.
+
+use chrono::Utc;
+use pollux_schema::gemini::{
+    Blob, Content, FunctionCall, FunctionCallingConfig, FunctionDeclaration, FunctionResponse,
+    GeminiGenerateContentRequest, GeminiResponseBody, GenerationConfig, Part, Tool, ToolConfig,
+};
+use pollux_schema::openai::{
+    ChatChoice, ChatChunkChoice, ChatCompletion, ChatCompletionChunk, ChatCompletionRequest,
+    ChatDelta, ChatFunctionCall, ChatFunctionCallDelta, ChatMessage, ChatResponseMessage, ChatTool,
+    ChatToolCall, ChatToolCallDelta, ChatUsage,
+};
+use serde_json::{Map, Value, json};
+use std::collections::{BTreeMap, HashMap};
+use tracing::warn;
+
+/// How far past the end of an array a `partialArgs` path may index; the gap
+/// is filled with nulls. Larger indexes are ignored.
+const MAX_ARRAY_INDEX_GAP: usize = 16;
+
+/// Translate a Chat Completions request into a Gemini request body.
+///
+/// The error string is client-facing and describes the offending field.
+pub fn chat_request_to_gemini(
+    req: ChatCompletionRequest,
+) -> Result<GeminiGenerateContentRequest, String> {
+    let mut system_parts = Vec::new();
+    let mut contents: Vec<Content> = Vec::new();
+    // tool_call_id -> function name, filled from assistant turns.
+    let mut call_names: HashMap<String, String> = HashMap::new();
+
+    for (index, message) in req.messages.into_iter().enumerate() {
+        match message.role.as_str() {
+            "system" | "developer" => {
+                system_parts.extend(content_parts(message.content.as_ref(), index)?);
+            }
+            "user" => contents.push(turn(
+                "user",
+                content_parts(message.content.as_ref(), index)?,
+            )),
+            "assistant" => contents.push(assistant_turn(message, index, &mut call_names)?),
+            "tool" => {
+                let part = tool_result_part(&message, index, &call_names)?;
+                // Parallel call results must share one turn.
+                match contents.last_mut() {
+                    Some(last) if is_function_response_turn(last) => last.parts.push(part),
+                    _ => contents.push(turn("user", vec![part])),
+                }
+            }
+            other => return Err(format!("messages[{index}]: unsupported role '{other}'")),
+        }
+    }
+
+    let tools = req.tools.map(translate_tools).transpose()?.flatten();
+    let tool_config = req
+        .tool_choice
+        .as_ref()
+        .map(translate_tool_choice)
+        .transpose()?;
+
+    let request = GeminiGenerateContentRequest {
+        contents,
+        system_instruction: (!system_parts.is_empty()).then(|| Content {
+            role: None,
+            parts: system_parts,
+            extra: BTreeMap::new(),
+        }),
+        generation_config: generation_config(
+            req.temperature,
+            req.top_p,
+            req.max_completion_tokens.or(req.max_tokens),
+            req.stop,
+        ),
+        tools,
+        tool_config,
+        extra: BTreeMap::new(),
+    };
+    request.validate_tools().map_err(|e| e.to_string())?;
+    Ok(request)
+}
+
+fn turn(role: &str, parts: Vec<Part>) -> Content {
+    Content {
+        role: Some(role.to_string()),
+        parts,
+        extra: BTreeMap::new(),
+    }
+}
+
+fn text_part(text: impl Into<String>) -> Part {
+    Part {
+        text: Some(text.into()),
+        ..Part::default()
+    }
+}
+
+fn is_function_response_turn(content: &Content) -> bool {
+    content.role.as_deref() == Some("user")
+        && !content.parts.is_empty()
+        && content.parts.iter().all(|p| p.function_response.is_some())
+}
+
+/// `content` as a string or an array of `text` / `image_url` parts.
+fn content_parts(content: Option<&Value>, index: usize) -> Result<Vec<Part>, String> {
+    match content {
+        None | Some(Value::Null) => Ok(Vec::new()),
+        Some(Value::String(s)) => Ok(vec![text_part(s.as_str())]),
+        Some(Value::Array(items)) => items.iter().map(|item| content_item(item, index)).collect(),
+        Some(_) => Err(format!(
+            "messages[{index}].content must be a string or an array"
+        )),
+    }
+}
+
+fn content_item(item: &Value, index: usize) -> Result<Part, String> {
+    match item.get("type").and_then(Value::as_str) {
+        Some("text") => Ok(text_part(
+            item.get("text").and_then(Value::as_str).unwrap_or_default(),
+        )),
+        Some("image_url") => {
+            let url = item
+                .pointer("/image_url/url")
+                .or_else(|| item.get("image_url"))
+                .and_then(Value::as_str)
+                .unwrap_or_default();
+            let (mime_type, data) = url
+                .strip_prefix("data:")
+                .and_then(|rest| rest.split_once(";base64,"))
+                .ok_or_else(|| {
+                    format!("messages[{index}]: only base64 data URLs are supported for image_url")
+                })?;
+            Ok(Part {
+                inline_data: Some(Blob {
+                    mime_type: Some(mime_type.to_string()),
+                    data: data.to_string(),
+                    ..Blob::default()
+                }),
+                ..Part::default()
+            })
+        }
+        other => Err(format!(
+            "messages[{index}]: unsupported content part type {}",
+            other.unwrap_or("<missing>")
+        )),
+    }
+}
+
+fn assistant_turn(
+    message: ChatMessage,
+    index: usize,
+    call_names: &mut HashMap<String, String>,
+) -> Result<Content, String> {
+    let mut parts = content_parts(message.content.as_ref(), index)?;
+    for call in message.tool_calls.unwrap_or_default() {
+        let args = parse_arguments(&call.function.arguments).ok_or_else(|| {
+            format!(
+                "messages[{index}]: tool call '{}' has arguments that are not a JSON object",
+                call.id
+            )
+        })?;
+        parts.push(Part {
+            function_call: Some(FunctionCall {
+                name: Some(call.function.name.clone()),
+                args: Some(args),
+                ..FunctionCall::default()
+            }),
+            ..Part::default()
+        });
+        call_names.insert(call.id, call.function.name);
+    }
+    Ok(turn("model", parts))
+}
+
+fn parse_arguments(raw: &str) -> Option<Value> {
+    if raw.trim().is_empty() {
+        return Some(Value::Object(Map::new()));
+    }
+    serde_json::from_str::<Value>(raw)
+        .ok()
+        .filter(Value::is_object)
+}
+
+fn tool_result_part(
+    message: &ChatMessage,
+    index: usize,
+    call_names: &HashMap<String, String>,
+) -> Result<Part, String> {
+    let call_id = message
+        .tool_call_id
+        .as_deref()
+        .ok_or_else(|| format!("messages[{index}]: tool message is missing tool_call_id"))?;
+    let name = call_names
+        .get(call_id)
+        .cloned()
+        .or_else(|| message.name.clone())
+        .ok_or_else(|| {
+            format!(
+                "messages[{index}]: tool_call_id '{call_id}' does not match any earlier tool call"
+            )
+        })?;
+
+    // Gemini wants an object; JSON object results pass through as-is.
+    let text = match &message.content {
+        Some(Value::String(s)) => s.clone(),
+        Some(Value::Array(items)) => items
+            .iter()
+            .filter_map(|item| item.get("text").and_then(Value::as_str))
+            .collect(),
+        Some(other) => other.to_string(),
+        None => String::new(),
+    };
+    let response = serde_json::from_str::<Value>(&text)
+        .ok()
+        .filter(Value::is_object)
+        .unwrap_or_else(|| json!({"content": text}));
+
+    Ok(Part {
+        function_response: Some(FunctionResponse {
+            name: Some(name),
+            response: Some(response),
+            ..FunctionResponse::default()
+        }),
+        ..Part::default()
+    })
+}
+
+fn translate_tools(tools: Vec<ChatTool>) -> Result<Option<Vec<Tool>>, String> {
+    let declarations = tools
+        .into_iter()
+        .enumerate()
+        .map(|(index, tool)| match (tool.kind.as_str(), tool.function) {
+            ("function", Some(function)) => Ok(FunctionDeclaration {
+                name: function.name,
+                description: function.description.unwrap_or_default(),
+                behavior: None,
+                parameters: None,
+                parameters_json_schema: function.parameters,
+                response: None,
+                response_json_schema: None,
+                extra: BTreeMap::new(),
+            }),
+            (kind, _) => Err(format!("tools[{index}]: unsupported tool type '{kind}'")),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    if declarations.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(vec![Tool {
+        function_declarations: Some(declarations),
+        extra: BTreeMap::new(),
+    }]))
+}
+
+/// `none` → `NONE`, `auto` → `AUTO`, `required` → `ANY`, and a named
+/// function → `ANY` restricted to that function.
+fn translate_tool_choice(choice: &Value) -> Result<ToolConfig, String> {
+    let (mode, allowed) = match choice {
+        Value::String(mode) => match mode.as_str() {
+            "none" => ("NONE", None),
+            "auto" => ("AUTO", None),
+            "required" => ("ANY", None),
+            other => return Err(format!("unsupported tool_choice '{other}'")),
+        },
+        Value::Object(_) => {
+            let name = choice
+                .pointer("/function/name")
+                .and_then(Value::as_str)
+                .ok_or("tool_choice object must name a function")?;
+            ("ANY", Some(vec![name.to_string()]))
+        }
+        _ => return Err("tool_choice must be a string or an object".to_string()),
+    };
+    let config = FunctionCallingConfig {
+        mode: Some(mode.to_string()),
+        allowed_function_names: allowed,
+        extra: BTreeMap::new(),
+    };
+    Ok(ToolConfig {
+        function_calling_config: Some(config),
+        retrieval_config: None,
+        extra: BTreeMap::new(),
+    })
+}
+
+fn generation_config(
+    temperature: Option<f64>,
+    top_p: Option<f64>,
+    max_output_tokens: Option<u32>,
+    stop: Option<Value>,
+) -> Option<GenerationConfig> {
+    let stop = match stop {
+        Some(Value::String(s)) => Some(json!([s])),
+        Some(v @ Value::Array(_)) => Some(v),
+        _ => None,
+    };
+    if temperature.is_none() && top_p.is_none() && max_output_tokens.is_none() && stop.is_none() {
+        return None;
+    }
+    let mut config = GenerationConfig {
+        temperature,
+        top_p,
+        max_output_tokens,
+        ..GenerationConfig::default()
+    };
+    if let Some(stop) = stop {
+        config.extra.insert("stopSequences".to_string(), stop);
+    }
+    Some(config)
+}
+
+/// `chatcmpl-…` id plus creation timestamp shared by every object of one response.
+#[derive(Debug, Clone)]
+pub struct ChatResponseMeta {
+    pub id: String,
+    pub created: i64,
+    pub model: String,
+}
+
+impl ChatResponseMeta {
+    pub fn new(model: &str) -> Self {
+        Self {
+            id: format!("chatcmpl-{}", uuid::Uuid::new_v4().simple()),
+            created: Utc::now().timestamp(),
+            model: model.to_string(),
+        }
+    }
+}
+
+fn new_call_id() -> String {
+    format!("call_{}", uuid::Uuid::new_v4().simple())
+}
+
+fn tool_call_from_part(part: &Part) -> Option<ChatToolCall> {
+    let call = part.function_call.as_ref()?;
+    let name = call.name.clone()?;
+    let args = call.args.clone().unwrap_or_else(|| json!({}));
+    Some(ChatToolCall {
+        id: call.id.clone().unwrap_or_else(new_call_id),
+        kind: "function".to_string(),
+        function: ChatFunctionCall {
+            name,
+            arguments: args.to_string(),
+        },
+    })
+}
+
+/// Visible text of a part; thoughts are dropped.
+fn visible_text(part: &Part) -> Option<&str> {
+    if part.thought == Some(true) {
+        return None;
+    }
+    part.text.as_deref()
+}
+
+fn finish_reason(gemini: &str, has_tool_calls: bool) -> String {
+    if has_tool_calls {
+        return "tool_calls".to_string();
+    }
+    match gemini {
+        "MAX_TOKENS" => "length",
+        "SAFETY" | "RECITATION" | "BLOCKLIST" | "PROHIBITED_CONTENT" | "SPII" | "IMAGE_SAFETY" => {
+            "content_filter"
+        }
+        _ => "stop",
+    }
+    .to_string()
+}
+
+fn usage_from_gemini(usage: Option<&Value>) -> Option<ChatUsage> {
+    let usage = usage?;
+    let field = |name: &str| usage.get(name).and_then(Value::as_u64);
+    let prompt = field("promptTokenCount")?;
+    let completion =
+        field("candidatesTokenCount").unwrap_or(0) + field("thoughtsTokenCount").unwrap_or(0);
+    Some(ChatUsage {
+        prompt_tokens: prompt,
+        completion_tokens: completion,
+        total_tokens: field("totalTokenCount").unwrap_or(prompt + completion),
+    })
+}
+
+/// Translate a complete Gemini response into a `chat.completion`.
+pub fn gemini_to_chat_completion(
+    resp: &GeminiResponseBody,
+    meta: &ChatResponseMeta,
+) -> ChatCompletion {
+    let choices = resp
+        .candidates
+        .iter()
+        .enumerate()
+        .map(|(i, candidate)| {
+            let parts = candidate
+                .content
+                .as_ref()
+                .map_or(&[][..], |c| c.parts.as_slice());
+            let text: String = parts.iter().filter_map(visible_text).collect();
+            let tool_calls: Vec<ChatToolCall> =
+                parts.iter().filter_map(tool_call_from_part).collect();
+            let finish = candidate
+                .finish_reason
+                .as_deref()
+                .map(|r| finish_reason(r, !tool_calls.is_empty()));
+            ChatChoice {
+                index: candidate
+                    .index
+                    .unwrap_or_else(|| u32::try_from(i).unwrap_or(u32::MAX)),
+                message: ChatResponseMessage {
+                    role: "assistant".to_string(),
+                    content: (!text.is_empty()).then_some(text),
+                    tool_calls: (!tool_calls.is_empty()).then_some(tool_calls),
+                },
+                finish_reason: finish,
+            }
+        })
+        .collect();
+
+    ChatCompletion {
+        id: meta.id.clone(),
+        object: "chat.completion".to_string(),
+        created: meta.created,
+        model: meta.model.clone(),
+        choices,
+        usage: usage_from_gemini(resp.usageMetadata.as_ref()),
+    }
+}
+
+/// A streamed function call whose arguments are still arriving.
+#[derive(Debug)]
+struct PendingToolCall {
+    index: u32,
+    id: String,
+    name: String,
+    /// Whether the id/name delta has been sent.
+    announced: bool,
+    /// Arguments sent as objects or `partialArgs`.
+    args: Value,
+    /// Arguments sent as raw JSON text fragments.
+    raw_args: String,
+    /// Bytes of `raw_args` already forwarded.
+    raw_sent: usize,
+}
+
+impl PendingToolCall {
+    fn delta(&self, arguments: Option<String>) -> ChatToolCallDelta {
+        ChatToolCallDelta {
+            index: self.index,
+            id: None,
+            kind: None,
+            function: ChatFunctionCallDelta {
+                name: None,
+                arguments,
+            },
+        }
+    }
+
+    /// Raw argument text received but not yet forwarded.
+    fn unsent_raw(&mut self) -> String {
+        let unsent = self.raw_args[self.raw_sent..].to_string();
+        self.raw_sent = self.raw_args.len();
+        unsent
+    }
+
+    /// Delta for a part that continues: the id/name header the first time,
+    /// carrying whatever raw argument text has arrived so far.
+    fn progress(&mut self) -> Option<ChatToolCallDelta> {
+        if self.announced {
+            let unsent = self.unsent_raw();
+            return (!unsent.is_empty()).then(|| self.delta(Some(unsent)));
+        }
+        self.announced = true;
+        let unsent = self.unsent_raw();
+        let mut header = self.delta(Some(unsent));
+        header.id = Some(self.id.clone());
+        header.kind = Some("function".to_string());
+        header.function.name = Some(self.name.clone());
+        Some(header)
+    }
+
+    /// Fold one `functionCall` fragment into the arguments.
+    fn apply(&mut self, call: &FunctionCall) {
+        match &call.args {
+            Some(Value::Object(args)) => {
+                if let Value::Object(target) = &mut self.args {
+                    target.extend(args.clone());
+                }
+            }
+            Some(Value::String(fragment)) => self.raw_args.push_str(fragment),
+            _ => {}
+        }
+        for partial in call.partial_args.iter().flatten() {
+            apply_partial_arg(&mut self.args, partial);
+        }
+    }
+
+    /// The arguments still to send once the call is complete: the rest of
+    /// the raw text, passed through as is, or the accumulated object.
+    fn remaining_arguments(&mut self) -> Option<String> {
+        if self.raw_args.is_empty() {
+            return Some(self.args.to_string());
+        }
+        if !matches!(
+            serde_json::from_str::<Value>(&self.raw_args),
+            Ok(Value::Object(_))
+        ) {
+            warn!(
+                tool = %self.name,
+                len = self.raw_args.len(),
+                "[ChatCompat] Streamed function arguments are not a JSON object; passed through"
+            );
+        }
+        if self.args.as_object().is_some_and(|args| !args.is_empty()) {
+            warn!(
+                tool = %self.name,
+                "[ChatCompat] Dropping structured function arguments mixed with raw text"
+            );
+        }
+        let unsent = self.unsent_raw();
+        (!self.announced || !unsent.is_empty()).then_some(unsent)
+    }
+}
+
+/// Apply one `partialArgs` entry (`{jsonPath, stringValue | numberValue |
+/// boolValue | nullValue}`). String values with `willContinue` arrive in
+/// pieces and are appended to what the path already holds.
+fn apply_partial_arg(args: &mut Value, partial: &Value) {
+    let Some(path) = partial.get("jsonPath").and_then(Value::as_str) else {
+        return;
+    };
+    let Some(slot) = json_path_slot(args, path) else {
+        return;
+    };
+    if let Some(piece) = partial.get("stringValue").and_then(Value::as_str) {
+        match slot {
+            Value::String(existing) => existing.push_str(piece),
+            _ => *slot = Value::String(piece.to_string()),
+        }
+    } else if let Some(value) = partial
+        .get("numberValue")
+        .or_else(|| partial.get("boolValue"))
+    {
+        *slot = value.clone();
+    } else if partial.get("nullValue").is_some() {
+        *slot = Value::Null;
+    }
+}
+
+/// Resolve a `$.a.b[0]` path inside `root`, creating objects, arrays and
+/// array slots on the way. `None` for paths this subset does not cover and
+/// for indexes more than [`MAX_ARRAY_INDEX_GAP`] past an array's end.
+fn json_path_slot<'a>(root: &'a mut Value, path: &str) -> Option<&'a mut Value> {
+    let mut rest = path.strip_prefix('$')?;
+    let mut slot = root;
+    while !rest.is_empty() {
+        if let Some(after) = rest.strip_prefix('.') {
+            let end = after.find(['.', '[']).unwrap_or(after.len());
+            let (key, tail) = after.split_at(end);
+            if key.is_empty() {
+                return None;
+            }
+            if !slot.is_object() {
+                *slot = Value::Object(Map::new());
+            }
+            slot = slot.as_object_mut()?.entry(key).or_insert(Value::Null);
+            rest = tail;
+        } else if let Some(after) = rest.strip_prefix('[') {
+            let (inner, tail) = after.split_once(']')?;
+            if let Some(key) = inner
+                .strip_prefix('\'')
+                .and_then(|k| k.strip_suffix('\''))
+                .or_else(|| inner.strip_prefix('"').and_then(|k| k.strip_suffix('"')))
+            {
+                if !slot.is_object() {
+                    *slot = Value::Object(Map::new());
+                }
+                slot = slot.as_object_mut()?.entry(key).or_insert(Value::Null);
+            } else {
+                let idx: usize = inner.parse().ok()?;
+                if !slot.is_array() {
+                    *slot = Value::Array(Vec::new());
+                }
+                let items = slot.as_array_mut()?;
+                if idx > items.len() + MAX_ARRAY_INDEX_GAP {
+                    return None;
+                }
+                if items.len() <= idx {
+                    items.resize(idx + 1, Value::Null);
+                }
+                slot = &mut items[idx];
+            }
+            rest = tail;
+        } else {
+            return None;
+        }
+    }
+    Some(slot)
+}
+
+/// Per-stream state for turning Gemini chunks into `chat.completion.chunk`s.
+///
+/// A function call sent whole becomes one tool call delta with id, name and
+/// arguments. A call streamed over several chunks (`willContinue`) is
+/// announced with id and name first. Raw `args` text fragments are then
+/// forwarded as argument deltas as they arrive, unparsed. `partialArgs` set
+/// JSON paths in any order, so their text is not known until the call
+/// completes: they are accumulated and sent as one object at the end.
+#[derive(Debug)]
+pub struct ChatStreamState {
+    meta: ChatResponseMeta,
+    include_usage: bool,
+    role_sent: bool,
+    next_tool_index: u32,
+    pending_call: Option<PendingToolCall>,
+    finished: bool,
+    usage: Option<ChatUsage>,
+}
+
+impl ChatStreamState {
+    pub fn new(meta: ChatResponseMeta, include_usage: bool) -> Self {
+        Self {
+            meta,
+            include_usage,
+            role_sent: false,
+            next_tool_index: 0,
+            pending_call: None,
+            finished: false,
+            usage: None,
+        }
+    }
+
+    fn chunk(&self, delta: ChatDelta, finish_reason: Option<String>) -> ChatCompletionChunk {
+        ChatCompletionChunk {
+            id: self.meta.id.clone(),
+            object: "chat.completion.chunk".to_string(),
+            created: self.meta.created,
+            model: self.meta.model.clone(),
+            choices: vec![ChatChunkChoice {
+                index: 0,
+                delta,
+                finish_reason,
+            }],
+            usage: None,
+        }
+    }
+
+    /// Deltas for one `functionCall` part. A named part starts a new call
+    /// (closing any open one); an unnamed part continues the open call.
+    fn on_function_call(&mut self, call: &FunctionCall) -> Vec<ChatToolCallDelta> {
+        let mut out = Vec::new();
+        let name = call.name.as_deref().filter(|n| !n.is_empty());
+        if let Some(name) = name {
+            out.extend(self.close_pending_call());
+            let index = self.next_tool_index;
+            self.next_tool_index += 1;
+            self.pending_call = Some(PendingToolCall {
+                index,
+                id: call.id.clone().unwrap_or_else(new_call_id),
+                name: name.to_string(),
+                announced: false,
+                args: Value::Object(Map::new()),
+                raw_args: String::new(),
+                raw_sent: 0,
+            });
+        }
+        let Some(pending) = self.pending_call.as_mut() else {
+            return out;
+        };
+        pending.apply(call);
+
+        if call.will_continue == Some(true) {
+            out.extend(pending.progress());
+        } else {
+            out.extend(self.close_pending_call());
+        }
+        out
+    }
+
+    /// Final delta of the open call: the whole call if it was never
+    /// announced, otherwise the arguments not sent yet, if any.
+    fn close_pending_call(&mut self) -> Option<ChatToolCallDelta> {
+        let mut pending = self.pending_call.take()?;
+        let arguments = pending.remaining_arguments()?;
+        let mut delta = pending.delta(Some(arguments));
+        if !pending.announced {
+            delta.id = Some(pending.id);
+            delta.kind = Some("function".to_string());
+            delta.function.name = Some(pending.name);
+        }
+        Some(delta)
+    }
+
+    /// Chunks for one upstream Gemini event. Only the first candidate is used.
+    pub fn on_gemini(&mut self, resp: &GeminiResponseBody) -> Vec<ChatCompletionChunk> {
+        if let Some(usage) = usage_from_gemini(resp.usageMetadata.as_ref()) {
+            self.usage = Some(usage);
+        }
+        let Some(candidate) = resp.candidates.first() else {
+            return Vec::new();
+        };
+        let parts = candidate
+            .content
+            .as_ref()
+            .map_or(&[][..], |c| c.parts.as_slice());
+
+        let text: String = parts.iter().filter_map(visible_text).collect();
+        let mut tool_calls = Vec::new();
+        for call in parts.iter().filter_map(|p| p.function_call.as_ref()) {
+            tool_calls.extend(self.on_function_call(call));
+        }
+        if candidate.finish_reason.is_some() {
+            tool_calls.extend(self.close_pending_call());
+        }
+
+        let mut out = Vec::new();
+        if !text.is_empty() || !tool_calls.is_empty() {
+            let delta = ChatDelta {
+                role: (!self.role_sent).then(|| "assistant".to_string()),
+                content: (!text.is_empty()).then_some(text),
+                tool_calls: (!tool_calls.is_empty()).then_some(tool_calls),
+            };
+            self.role_sent = true;
+            out.push(self.chunk(delta, None));
+        }
+        if let Some(reason) = candidate.finish_reason.as_deref() {
+            self.finished = true;
+            let reason = finish_reason(reason, self.next_tool_index > 0);
+            out.push(self.chunk(ChatDelta::default(), Some(reason)));
+        }
+        out
+    }
+
+    /// Trailing chunks once upstream ends: a `stop` if Gemini never sent a
+    /// finish reason, and the usage chunk when `include_usage` was requested.
+    pub fn finish(&mut self) -> Vec<ChatCompletionChunk> {
+        let mut out = Vec::new();
+        if let Some(delta) = self.close_pending_call() {
+            let delta = ChatDelta {
+                tool_calls: Some(vec![delta]),
+                ..ChatDelta::default()
+            };
+            out.push(self.chunk(delta, None));
+        }
+        if !self.finished {
+            self.finished = true;
+            out.push(self.chunk(ChatDelta::default(), Some("stop".to_string())));
+        }
+        if self.include_usage {
+            let mut chunk = self.chunk(ChatDelta::default(), None);
+            chunk.choices.clear();
+            chunk.usage = Some(self.usage.unwrap_or_default());
+            out.push(chunk);
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(value: Value) -> ChatCompletionRequest {
+        serde_json::from_value(value).unwrap()
+    }
+
+    fn gemini_response(value: Value) -> GeminiResponseBody {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn tool_history_maps_to_function_parts() {
+        let body = chat_request_to_gemini(request(json!({
+            "model": "gemini-2.5-pro",
+            "messages": [
+                {"role": "system", "content": "be brief"},
+                {"role": "user", "content": "weather in Paris and Rome?"},
+                {"role": "assistant", "content": null, "tool_calls": [
+                    {"id": "a", "type": "function", "function": {"name": "get_weather", "arguments": "{\"city\":\"Paris\"}"}},
+                    {"id": "b", "type": "function", "function": {"name": "get_weather", "arguments": "{\"city\":\"Rome\"}"}}
+                ]},
+                {"role": "tool", "tool_call_id": "a", "content": "{\"temp\":18}"},
+                {"role": "tool", "tool_call_id": "b", "content": "sunny"}
+            ],
+            "tools": [{"type": "function", "function": {
+                "name": "get_weather",
+                "description": "Current weather",
+                "parameters": {"type": "object", "properties": {"city": {"type": "string"}}}
+            }}],
+            "tool_choice": {"type": "function", "function": {"name": "get_weather"}},
+            "max_tokens": 64,
+            "stop": "END"
+        })))
+        .unwrap();
+
+        let value = serde_json::to_value(&body).unwrap();
+        assert_eq!(value["systemInstruction"]["parts"][0]["text"], "be brief");
+        assert_eq!(value["contents"].as_array().unwrap().len(), 3);
+        assert_eq!(
+            value["contents"][1]["parts"][1]["functionCall"],
+            json!({"name": "get_weather", "args": {"city": "Rome"}})
+        );
+        assert_eq!(
+            value["contents"][2]["parts"],
+            json!([
+                {"functionResponse": {"name": "get_weather", "response": {"temp": 18}}},
+                {"functionResponse": {"name": "get_weather", "response": {"content": "sunny"}}}
+            ])
+        );
+        assert_eq!(
+            value["tools"][0]["functionDeclarations"][0]["parametersJsonSchema"]["type"],
+            "object"
+        );
+        assert_eq!(
+            value["toolConfig"]["functionCallingConfig"],
+            json!({"mode": "ANY", "allowedFunctionNames": ["get_weather"]})
+        );
+        assert_eq!(value["generationConfig"]["maxOutputTokens"], 64);
+        assert_eq!(value["generationConfig"]["stopSequences"], json!(["END"]));
+    }
+
+    #[test]
+    fn unknown_tool_call_id_and_bad_arguments_are_rejected() {
+        let err = chat_request_to_gemini(request(json!({
+            "model": "m",
+            "messages": [{"role": "tool", "tool_call_id": "x", "content": "1"}]
+        })))
+        .unwrap_err();
+        assert!(err.contains("tool_call_id 'x'"));
+
+        let err = chat_request_to_gemini(request(json!({
+            "model": "m",
+            "messages": [{"role": "assistant", "tool_calls": [
+                {"id": "a", "type": "function", "function": {"name": "f", "arguments": "[1]"}}
+            ]}]
+        })))
+        .unwrap_err();
+        assert!(err.contains("not a JSON object"));
+
+        let err = chat_request_to_gemini(request(json!({
+            "model": "m",
+            "messages": [{"role": "user", "content": "hi"}],
+            "tools": [{"type": "function", "function": {"name": "f"}}],
+            "tool_choice": {"type": "function", "function": {"name": "g"}}
+        })))
+        .unwrap_err();
+        assert!(err.contains("undeclared function 'g'"));
+    }
+
+    #[test]
+    fn function_call_response_maps_to_tool_calls() {
+        let resp = gemini_response(json!({
+            "candidates": [{
+                "content": {"role": "model", "parts": [
+                    {"text": "thinking", "thought": true},
+                    {"functionCall": {"name": "get_weather", "args": {"city": "Paris"}}}
+                ]},
+                "finishReason": "STOP"
+            }],
+            "usageMetadata": {"promptTokenCount": 10, "candidatesTokenCount": 4, "thoughtsTokenCount": 2}
+        }));
+        let completion = gemini_to_chat_completion(&resp, &ChatResponseMeta::new("gemini-2.5-pro"));
+
+        let choice = &completion.choices[0];
+        assert_eq!(choice.finish_reason.as_deref(), Some("tool_calls"));
+        assert_eq!(choice.message.content, None);
+        let call = &choice.message.tool_calls.as_ref().unwrap()[0];
+        assert!(call.id.starts_with("call_"));
+        assert_eq!(call.function.name, "get_weather");
+        assert_eq!(call.function.arguments, r#"{"city":"Paris"}"#);
+        assert_eq!(
+            completion.usage,
+            Some(ChatUsage {
+                prompt_tokens: 10,
+                completion_tokens: 6,
+                total_tokens: 16
+            })
+        );
+    }
+
+    #[test]
+    fn stream_emits_indexed_tool_call_deltas() {
+        let mut state = ChatStreamState::new(ChatResponseMeta::new("m"), true);
+
+        let first = state.on_gemini(&gemini_response(json!({
+            "candidates": [{"content": {"role": "model", "parts": [{"text": "Checking"}]}}]
+        })));
+        assert_eq!(first[0].choices[0].delta.role.as_deref(), Some("assistant"));
+        assert_eq!(
+            first[0].choices[0].delta.content.as_deref(),
+            Some("Checking")
+        );
+
+        let second = state.on_gemini(&gemini_response(json!({
+            "candidates": [{
+                "content": {"role": "model", "parts": [
+                    {"functionCall": {"name": "a", "args": {}}},
+                    {"functionCall": {"name": "b", "args": {"x": 1}}}
+                ]},
+                "finishReason": "STOP"
+            }],
+            "usageMetadata": {"promptTokenCount": 3, "candidatesTokenCount": 5}
+        })));
+        assert_eq!(second.len(), 2);
+        let deltas = second[0].choices[0].delta.tool_calls.as_ref().unwrap();
+        assert_eq!(second[0].choices[0].delta.role, None);
+        assert_eq!(
+            deltas.iter().map(|d| d.index).collect::<Vec<_>>(),
+            vec![0, 1]
+        );
+        assert_eq!(deltas[1].function.arguments.as_deref(), Some(r#"{"x":1}"#));
+        assert_eq!(
+            second[1].choices[0].finish_reason.as_deref(),
+            Some("tool_calls")
+        );
+
+        let tail = state.finish();
+        assert_eq!(tail.len(), 1);
+        assert!(tail[0].choices.is_empty());
+        assert_eq!(tail[0].usage.map(|u| u.total_tokens), Some(8));
+    }
+
+    fn function_call_chunk(call: &Value) -> GeminiResponseBody {
+        gemini_response(json!({
+            "candidates": [{"content": {"role": "model", "parts": [{"functionCall": call}]}}]
+        }))
+    }
+
+    fn tool_call_deltas(chunks: &[ChatCompletionChunk]) -> Vec<ChatToolCallDelta> {
+        chunks
+            .iter()
+            .flat_map(|c| c.choices.iter())
+            .flat_map(|c| c.delta.tool_calls.clone().unwrap_or_default())
+            .collect()
+    }
+
+    #[test]
+    fn partial_args_are_accumulated_before_sending() {
+        let mut state = ChatStreamState::new(ChatResponseMeta::new("m"), false);
+
+        let head = state.on_gemini(&function_call_chunk(
+            &json!({"name": "search", "willContinue": true}),
+        ));
+        let delta = &tool_call_deltas(&head)[0];
+        assert_eq!(delta.function.name.as_deref(), Some("search"));
+        assert_eq!(delta.function.arguments.as_deref(), Some(""));
+
+        let partial = state.on_gemini(&function_call_chunk(&json!({
+            "partialArgs": [
+                {"jsonPath": "$.query", "stringValue": "weather in ", "willContinue": true},
+                {"jsonPath": "$.filters[1].days", "numberValue": 3},
+                {"jsonPath": "$.filters[4294967295]", "boolValue": true}
+            ],
+            "willContinue": true
+        })));
+        assert!(partial.is_empty());
+
+        let done = state.on_gemini(&function_call_chunk(&json!({
+            "partialArgs": [{"jsonPath": "$.query", "stringValue": "Paris"}]
+        })));
+        let delta = &tool_call_deltas(&done)[0];
+        assert_eq!((delta.index, delta.id.as_deref()), (0, None));
+        let args: Value =
+            serde_json::from_str(delta.function.arguments.as_deref().unwrap()).unwrap();
+        assert_eq!(
+            args,
+            json!({"query": "weather in Paris", "filters": [null, {"days": 3}]})
+        );
+    }
+
+    #[test]
+    fn raw_argument_fragments_stream_as_they_arrive() {
+        let mut state = ChatStreamState::new(ChatResponseMeta::new("m"), false);
+
+        let head = state.on_gemini(&function_call_chunk(
+            &json!({"name": "lookup", "args": "{\"id\": ", "willContinue": true}),
+        ));
+        let delta = &tool_call_deltas(&head)[0];
+        assert_eq!(delta.function.name.as_deref(), Some("lookup"));
+        assert_eq!(delta.function.arguments.as_deref(), Some("{\"id\": "));
+
+        let more = state.on_gemini(&function_call_chunk(
+            &json!({"args": "42", "willContinue": true}),
+        ));
+        let delta = &tool_call_deltas(&more)[0];
+        assert_eq!((delta.index, delta.id.as_deref()), (0, None));
+        assert_eq!(delta.function.arguments.as_deref(), Some("42"));
+
+        let done = state.on_gemini(&function_call_chunk(&json!({"args": "}"})));
+        assert_eq!(
+            tool_call_deltas(&done)[0].function.arguments.as_deref(),
+            Some("}")
+        );
+
+        // A call cut off mid-arguments is not completed with made-up ones.
+        state.on_gemini(&function_call_chunk(
+            &json!({"name": "fetch", "args": "{\"url\": ", "willContinue": true}),
+        ));
+        assert!(tool_call_deltas(&state.finish()).is_empty());
+
+        let mut state = ChatStreamState::new(ChatResponseMeta::new("m"), false);
+        let whole = state.on_gemini(&function_call_chunk(
+            &json!({"name": "fetch", "args": "{\"url\": "}),
+        ));
+        let delta = &tool_call_deltas(&whole)[0];
+        assert_eq!(delta.function.name.as_deref(), Some("fetch"));
+        assert_eq!(delta.function.arguments.as_deref(), Some("{\"url\": "));
+    }
+}
@@ -0,0 +1,291 @@
+use crate::error::{ClaudeError, IsRetryable};
+use crate::providers::claude::ClaudeActorHandle;
+use crate::providers::claude::errors::rate_limit_reset;
+use crate::providers::claude::{ANTHROPIC_OAUTH_BETA, ANTHROPIC_VERSION};
+use crate::providers::error_clusters::ErrorClusters;
+use crate::providers::manifest::ClaudeLease;
+use crate::providers::manifest::ProviderKind;
+use crate::providers::provider_endpoints::ProviderEndpoints;
+use crate::providers::upstream_retry::post_json_bytes_with_retry;
+use crate::providers::{ActionForError, policy::classify_upstream_error};
+use crate::server::routes::claude::ClaudeContext;
+use crate::utils::http::EgressClients;
+use crate::utils::logging::with_pretty_json_debug;
+use axum::body::Bytes;
+use backon::{ExponentialBuilder, Retryable};
+use pollux_schema::anthropic::{AnthropicErrorBody, AnthropicMessagesRequest};
+use reqwest::header::{AUTHORIZATION, CONTENT_TYPE, HeaderMap, HeaderName, HeaderValue};
+
+use std::time::{Duration, Instant};
+use tracing::{debug, info};
+use url::Url;
+
+/// Passthrough client for the Anthropic Messages API on OAuth credentials.
+///
+/// The request body is forwarded unchanged; only auth and version headers
+/// are set per lease.
+#[derive(Clone)]
+pub(crate) struct ClaudeClient {
+    clients: EgressClients,
+    retry_policy: ExponentialBuilder,
+    endpoints: ProviderEndpoints,
+    trace_header: Option<String>,
+    error_clusters: ErrorClusters,
+}
+
+impl ClaudeClient {
+    pub(crate) fn new(
+        clients: EgressClients,
+        base_url: &Url,
+        retry_max_times: usize,
+        trace_header: Option<String>,
+    ) -> Self {
+        let retry_policy = ExponentialBuilder::default()
+            .with_min_delay(Duration::ZERO)
+            .with_max_delay(Duration::ZERO)
+            .with_max_times(retry_max_times);
+        let endpoints = Self::endpoints_for_base(base_url);
+        info!(endpoint = %endpoints.select(false), "ClaudeClient initialized");
+
+        Self {
+            clients,
+            retry_policy,
+            endpoints,
+            trace_header,
+            error_clusters: ErrorClusters::default(),
+        }
+    }
+
+    /// Count upstream error responses into `clusters`.
+    #[must_use]
+    pub(crate) fn with_error_clusters(mut self, clusters: ErrorClusters) -> Self {
+        self.error_clusters = clusters;
+        self
+    }
+
+    fn endpoints_for_base(base: &Url) -> ProviderEndpoints {
+        ProviderEndpoints::new(base, "./v1/messages", None, "./v1/messages", None)
+    }
+
+    /// Upstream headers for one attempt. The client's `anthropic-version` is
+    /// kept and its `anthropic-beta` flags are merged with the OAuth flag.
+    pub(crate) fn upstream_headers(
+        lease: &ClaudeLease,
+        anthropic_version: Option<&str>,
+        anthropic_beta: Option<&str>,
+    ) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+        if let Ok(value) = HeaderValue::from_str(&format!("Bearer {}", lease.access_token)) {
+            headers.insert(AUTHORIZATION, value);
+        }
+
+        let version = anthropic_version.unwrap_or(ANTHROPIC_VERSION);
+        if let Ok(value) = HeaderValue::from_str(version) {
+            headers.insert("anthropic-version", value);
+        }
+
+        let beta = match anthropic_beta {
+            Some(beta) if beta.split(',').any(|f| f.trim() == ANTHROPIC_OAUTH_BETA) => {
+                beta.to_string()
+            }
+            Some(beta) if !beta.trim().is_empty() => format!("{ANTHROPIC_OAUTH_BETA},{beta}"),
+            _ => ANTHROPIC_OAUTH_BETA.to_string(),
+        };
+        if let Ok(value) = HeaderValue::from_str(&beta) {
+            headers.insert("anthropic-beta", value);
+        }
+        headers
+    }
+
+    #[allow(clippy::too_many_lines)]
+    pub(crate) async fn call_claude(
+        &self,
+        handle: &ClaudeActorHandle,
+        ctx: &ClaudeContext,
+        body: &AnthropicMessagesRequest,
+    ) -> Result<reqwest::Response, ClaudeError> {
+        let clients = &self.clients;
+        let endpoints = &self.endpoints;
+        let trace_header = &self.trace_header;
+        let error_clusters = &self.error_clusters;
+        let model = &ctx.model;
+        let model_mask = &ctx.model_mask;
+        let stream = ctx.stream;
+        let request_body = Bytes::from(serde_json::to_vec(body)?);
+
+        let op = move || {
+            let request_body = request_body.clone();
+            async move {
+                let start = Instant::now();
+                let lease = handle
+                    .get_credential(model_mask.clone(), ctx.route_key, ctx.pool.clone())
+                    .await?
+                    .ok_or(ClaudeError::NoAvailableCredential)?;
+
+                let waited_us = start.elapsed().as_micros();
+                info!(
+                    waited_us,
+                    id = lease.id,
+                    model = %model,
+                    stream,
+                    "[Claude] Lease acquired"
+                );
+
+                with_pretty_json_debug(&body, |pretty_payload| {
+                    tracing::debug!(
+                        channel = "claude",
+                        lease.id = lease.id,
+                        req.model = %model,
+                        req.stream = stream,
+                        body = %pretty_payload,
+                        "[Claude] Prepared upstream payload"
+                    );
+                });
+
+                let mut upstream_headers = Self::upstream_headers(
+                    &lease,
+                    ctx.anthropic_version.as_deref(),
+                    ctx.anthropic_beta.as_deref(),
+                );
+                debug!(
+                    anthropic_beta = ?upstream_headers.get("anthropic-beta"),
+                    "[Claude] Prepared upstream headers for request"
+                );
+
+                if let Some(header_name) = trace_header {
+                    let email = lease.email.as_deref().unwrap_or("unknown");
+                    let trace_value = format!("claude:{}:{}", email, lease.id);
+                    if let (Ok(name), Ok(val)) = (
+                        HeaderName::from_bytes(header_name.as_bytes()),
+                        HeaderValue::from_str(&trace_value),
+                    ) {
+                        upstream_headers.insert(name, val);
+                    }
+                }
+
+                let sent = Instant::now();
+                let mut resp = post_json_bytes_with_retry(
+                    "Claude",
+                    &clients.select(lease.id, lease.proxy_url.as_ref(), stream),
+                    endpoints.select(stream),
+                    Some(upstream_headers),
+                    request_body,
+                )
+                .await
+                .inspect_err(|e| {
+                    if e.status().is_some_and(|s| s.is_server_error()) {
+                        handle.report_outcome(lease.id, model_mask.clone(), false);
+                    }
+                })?;
+
+                if resp.status().is_success() {
+                    handle.report_success(lease.id, model_mask.clone(), sent.elapsed());
+                    lease.attach(&mut resp);
+                    return Ok(resp);
+                }
+
+                let status = resp.status();
+                let reset_after = rate_limit_reset(resp.headers());
+                let (mut action, final_error) = classify_upstream_error(
+                    resp,
+                    |json: AnthropicErrorBody| ClaudeError::UpstreamMappedError {
+                        status,
+                        body: json,
+                    },
+                    |status, body| ClaudeError::UpstreamFallbackError { status, body },
+                    |status, body| {
+                        error_clusters.record(ProviderKind::Claude.label(), model, status, body);
+                    },
+                )
+                .await;
+
+                if let (ActionForError::RateLimit(duration), Some(reset_after)) =
+                    (&mut action, reset_after)
+                {
+                    *duration = reset_after;
+                }
+
+                match &action {
+                    ActionForError::RateLimit(duration) => {
+                        handle.report_rate_limit(lease.id, model_mask.clone(), *duration);
+                    }
+                    ActionForError::Ban => {
+                        handle.report_banned(lease.id);
+                    }
+                    ActionForError::ModelUnsupported => {
+                        handle.report_model_unsupported(lease.id, model_mask.clone());
+                    }
+                    ActionForError::Invalid => {
+                        handle.report_invalid(lease.id);
+                    }
+                    ActionForError::None => {
+                        if status.is_server_error() {
+                            handle.report_outcome(lease.id, model_mask.clone(), false);
+                        }
+                    }
+                }
+
+                match &final_error {
+                    ClaudeError::UpstreamMappedError { status, .. } => {
+                        tracing::warn!(
+                            lease_id = lease.id,
+                            model = %model,
+                            status = %status,
+                            action = ?action,
+                            "[Claude] Upstream mapped error"
+                        );
+                    }
+                    ClaudeError::UpstreamFallbackError { status, .. } => {
+                        tracing::warn!(
+                            lease_id = lease.id,
+                            model = %model,
+                            status = %status,
+                            action = ?action,
+                            "[Claude] Upstream fallback error"
+                        );
+                    }
+                    ClaudeError::Reqwest(error) => {
+                        tracing::warn!(
+                            lease_id = lease.id,
+                            model = %model,
+                            status = ?error.status(),
+                            action = ?action,
+                            "[Claude] Upstream reqwest error"
+                        );
+                    }
+                    _ => {
+                        tracing::warn!(
+                            lease_id = lease.id,
+                            model = %model,
+                            status = "N/A",
+                            action = ?action,
+                            "[Claude] Upstream other error"
+                        );
+                    }
+                }
+
+                Err(final_error)
+            }
+        };
+
+        let result = op
+            .retry(&self.retry_policy)
+            .when(|err: &ClaudeError| err.is_retryable())
+            .notify(|err, dur: Duration| {
+                tracing::warn!("Claude retrying after error {} in {:?}", err, dur);
+                crate::server::audit_log::note_retry();
+            })
+            .await;
+
+        match result {
+            Err(err) if err.pool_gave_up() => {
+                let after = handle
+                    .retry_after(ctx.model_mask.clone(), ctx.pool.clone())
+                    .await;
+                Err(err.with_retry_after(after))
+            }
+            other => other,
+        }
+    }
+}
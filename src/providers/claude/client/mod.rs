@@ -0,0 +1,5 @@
+pub mod oauth;
+#[path = "client.rs"]
+mod upstream;
+
+pub(crate) use upstream::ClaudeClient;
@@ -0,0 +1,48 @@
+use crate::config::CONFIG;
+use crate::error::OauthError;
+use crate::oauth_utils::OauthTokenResponse;
+
+use serde_json::{Value, json};
+
+/// Stateless Anthropic OAuth endpoints for Claude Pro/Max accounts.
+pub(crate) struct ClaudeOauthEndpoints;
+
+/// Fixed Claude Code OAuth client id (public client, no secret).
+const CLAUDE_CLIENT_ID: &str = "9d1c250a-e61b-44d9-88ed-5944d1962f5e";
+
+impl ClaudeOauthEndpoints {
+    /// Exchange a refresh token at `providers.claude.oauth_token_url`.
+    ///
+    /// The endpoint takes a JSON body rather than the form encoding the
+    /// `oauth2` crate sends, so the request is built by hand. A 4xx carrying
+    /// an OAuth `error` (e.g. `invalid_grant`) is a [`OauthError::ServerResponse`].
+    pub(crate) async fn refresh_access_token(
+        refresh_token: &str,
+        http_client: reqwest::Client,
+    ) -> Result<OauthTokenResponse, OauthError> {
+        let resp = http_client
+            .post(CONFIG.providers.claude.oauth_token_url.as_str())
+            .json(&json!({
+                "grant_type": "refresh_token",
+                "refresh_token": refresh_token,
+                "client_id": CLAUDE_CLIENT_ID,
+            }))
+            .send()
+            .await?;
+        let status = resp.status();
+        let body = resp.text().await?;
+        if !status.is_success() {
+            let error = serde_json::from_str::<Value>(&body)
+                .ok()
+                .and_then(|v| v.get("error").and_then(Value::as_str).map(str::to_string));
+            return Err(match error {
+                Some(error) if status.is_client_error() => OauthError::ServerResponse { error },
+                _ => OauthError::UpstreamStatus(status),
+            });
+        }
+        serde_json::from_str(&body).map_err(|e| OauthError::Parse {
+            message: e.to_string(),
+            body,
+        })
+    }
+}
@@ -0,0 +1,124 @@
+//! Claude probes for `pollux doctor`.
+
+use super::CLAUDE_USER_AGENT;
+use super::client::ClaudeClient;
+use super::client::oauth::OAUTH_RETRY_POLICY;
+use super::manager::{Claude, CredentialOps};
+use super::resource::ClaudeResource;
+use crate::config::ClaudeResolvedConfig;
+use crate::db::DbActorHandle;
+use crate::providers::doctor::{
+    DoctorArgs, DoctorReport, PROBE_PROMPT, SseProbe, db_id, expect_json, http_client, probe_sse,
+    token_validity,
+};
+use crate::providers::manifest::ClaudeLease;
+use crate::providers::traits::oauth_worker::refresh_credential;
+use crate::providers::traits::provider::{CredentialStore, ProviderResource};
+use crate::providers::traits::scheduler::Schedulable;
+use serde_json::{Value, json};
+use std::time::Instant;
+use url::Url;
+
+pub(crate) async fn diagnose(
+    report: &mut DoctorReport,
+    cfg: &ClaudeResolvedConfig,
+    db: &DbActorHandle,
+    args: &DoctorArgs,
+) {
+    let id = args.id;
+    let Some(mut cred) = report
+        .step("load", async {
+            let row = db
+                .get_claude_by_id(db_id(id)?)
+                .await
+                .map_err(|e| e.to_string())?;
+            let cred = ClaudeResource::from(row);
+            let detail = format!(
+                "account {} (org {}), {}",
+                cred.account_uuid(),
+                cred.organization_uuid(),
+                token_validity(cred.expiry())
+            );
+            Ok((cred, detail))
+        })
+        .await
+    else {
+        return;
+    };
+
+    if args.refresh {
+        let client = http_client(cfg.proxy.as_ref(), None);
+        report
+            .step("refresh", async {
+                refresh_credential(&Claude, client, *OAUTH_RETRY_POLICY, &mut cred, None)
+                    .await
+                    .map_err(|e| e.to_string())?;
+                CredentialOps::new(db.clone())
+                    .save_refreshed(id, &cred)
+                    .await
+                    .map_err(|e| format!("refreshed but not saved: {e}"))?;
+                Ok(((), format!("{}, saved", token_validity(cred.expiry()))))
+            })
+            .await;
+    } else {
+        report.skip("refresh");
+    }
+
+    let lease = cred.make_lease(id);
+    let client = http_client(cfg.proxy.as_ref(), Some(CLAUDE_USER_AGENT));
+    let base = &cfg.custom_api_url;
+    let model = args
+        .model
+        .clone()
+        .or_else(|| cfg.model_list.first().cloned())
+        .unwrap_or_default();
+
+    report
+        .step("generate", async {
+            let resp = messages(&client, base, &lease, &model, false).await?;
+            let body = expect_json(resp).await?;
+            let tokens = body
+                .pointer("/usage/output_tokens")
+                .and_then(Value::as_u64)
+                .unwrap_or(0);
+            let stop = body
+                .get("stop_reason")
+                .and_then(Value::as_str)
+                .unwrap_or("unknown");
+            Ok(((), format!("{model}: {stop}, {tokens} output tokens")))
+        })
+        .await;
+
+    report
+        .step("stream", async {
+            let sent = Instant::now();
+            let resp = messages(&client, base, &lease, &model, true).await?;
+            let probe: SseProbe = probe_sse(resp, sent).await?;
+            Ok(((), probe.summary()))
+        })
+        .await;
+}
+
+async fn messages(
+    client: &reqwest::Client,
+    base: &Url,
+    lease: &ClaudeLease,
+    model: &str,
+    stream: bool,
+) -> Result<reqwest::Response, String> {
+    let url = base
+        .join("./v1/messages")
+        .expect("valid messages endpoint path");
+    client
+        .post(url)
+        .headers(ClaudeClient::upstream_headers(lease, None, None))
+        .json(&json!({
+            "model": model,
+            "max_tokens": 64,
+            "stream": stream,
+            "messages": [{"role": "user", "content": PROBE_PROMPT}],
+        }))
+        .send()
+        .await
+        .map_err(|e| e.to_string())
+}
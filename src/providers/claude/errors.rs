@@ -0,0 +1,122 @@
+use crate::providers::{ActionForError, MappingAction};
+use chrono::Utc;
+use pollux_schema::anthropic::AnthropicErrorBody;
+use reqwest::StatusCode;
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use std::time::Duration;
+
+/// Unix timestamp (seconds) at which the account's unified rate limit resets.
+const UNIFIED_RESET_HEADER: &str = "anthropic-ratelimit-unified-reset";
+
+impl MappingAction for AnthropicErrorBody {
+    fn try_match_rule(&self, status: StatusCode) -> Option<ActionForError> {
+        match (status, self.error.r#type.as_str()) {
+            // 401: token revoked or expired; a refresh may bring it back.
+            (StatusCode::UNAUTHORIZED, "authentication_error") => Some(ActionForError::Invalid),
+
+            // 403: account disabled or not allowed to use the API.
+            (StatusCode::FORBIDDEN, "permission_error") => Some(ActionForError::Ban),
+
+            // 404: the model is not available to this account.
+            (StatusCode::NOT_FOUND, "not_found_error") => Some(ActionForError::ModelUnsupported),
+
+            // 429: subscription usage window exhausted; the caller prefers the
+            // reset advertised in the response headers when present.
+            (StatusCode::TOO_MANY_REQUESTS, "rate_limit_error") => {
+                Some(ActionForError::RateLimit(Duration::from_mins(5)))
+            }
+
+            _ => None,
+        }
+    }
+
+    fn action_from_status(status: StatusCode) -> ActionForError {
+        match status {
+            StatusCode::UNAUTHORIZED => ActionForError::Invalid,
+            StatusCode::FORBIDDEN => ActionForError::Ban,
+            StatusCode::TOO_MANY_REQUESTS => ActionForError::RateLimit(Duration::from_mins(5)),
+            _ => ActionForError::None,
+        }
+    }
+}
+
+/// Cooldown advertised by a 429 response, read before the body is consumed.
+///
+/// Prefers the unified reset timestamp and falls back to `retry-after` seconds.
+pub(super) fn rate_limit_reset(headers: &HeaderMap) -> Option<Duration> {
+    let header = |name| headers.get(name).and_then(|v| v.to_str().ok());
+
+    if let Some(reset_at) = header(UNIFIED_RESET_HEADER).and_then(|v| v.trim().parse::<i64>().ok())
+    {
+        let secs = reset_at.saturating_sub(Utc::now().timestamp());
+        return Some(Duration::from_secs(u64::try_from(secs).unwrap_or(0).max(1)));
+    }
+
+    header(RETRY_AFTER.as_str())
+        .and_then(|v| v.trim().parse::<u64>().ok())
+        .map(|secs| Duration::from_secs(secs.max(1)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    fn parse(raw: &str) -> AnthropicErrorBody {
+        serde_json::from_str(raw).expect("parse sample")
+    }
+
+    #[test]
+    fn try_match_rule_maps_documented_error_types() {
+        let auth = parse(
+            r#"{"type":"error","error":{"type":"authentication_error","message":"OAuth token has expired."}}"#,
+        );
+        assert_eq!(
+            auth.try_match_rule(StatusCode::UNAUTHORIZED),
+            Some(ActionForError::Invalid)
+        );
+
+        let permission = parse(
+            r#"{"type":"error","error":{"type":"permission_error","message":"This organization has been disabled."}}"#,
+        );
+        assert_eq!(
+            permission.try_match_rule(StatusCode::FORBIDDEN),
+            Some(ActionForError::Ban)
+        );
+
+        let not_found = parse(
+            r#"{"type":"error","error":{"type":"not_found_error","message":"model: claude-x"}}"#,
+        );
+        assert_eq!(
+            not_found.try_match_rule(StatusCode::NOT_FOUND),
+            Some(ActionForError::ModelUnsupported)
+        );
+    }
+
+    #[test]
+    fn try_match_rule_returns_none_when_status_mismatch() {
+        let body = parse(r#"{"type":"error","error":{"type":"overloaded_error"}}"#);
+
+        assert_eq!(body.try_match_rule(StatusCode::SERVICE_UNAVAILABLE), None);
+        assert_eq!(
+            AnthropicErrorBody::action_from_status(StatusCode::SERVICE_UNAVAILABLE),
+            ActionForError::None
+        );
+    }
+
+    #[test]
+    fn rate_limit_reset_prefers_unified_reset_header() {
+        let mut headers = HeaderMap::new();
+        let reset_at = Utc::now().timestamp() + 120;
+        headers.insert(UNIFIED_RESET_HEADER, HeaderValue::from(reset_at));
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("7"));
+
+        let wait = rate_limit_reset(&headers).expect("reset present");
+        assert!(wait > Duration::from_secs(100) && wait <= Duration::from_mins(2));
+
+        headers.remove(UNIFIED_RESET_HEADER);
+        assert_eq!(rate_limit_reset(&headers), Some(Duration::from_secs(7)));
+
+        assert_eq!(rate_limit_reset(&HeaderMap::new()), None);
+    }
+}
@@ -0,0 +1,128 @@
+mod ops;
+
+use super::client::oauth::{OAUTH_RETRY_POLICY, endpoints::ClaudeOauthEndpoints};
+use super::resource::ClaudeResource;
+use super::{SUPPORTED_MODEL_MASK, SUPPORTED_MODEL_NAMES};
+use crate::config::ClaudeResolvedConfig;
+use crate::error::OauthError;
+use crate::model_catalog::ModelCapabilities;
+use crate::oauth_utils::OauthTokenResponse;
+use crate::providers::manifest::ProviderKind;
+use crate::providers::traits::actor::ProviderActorHandle;
+use crate::providers::traits::provider::{OauthWorkerSettings, PoolSettings, Provider};
+use backon::ExponentialBuilder;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+
+pub(crate) use ops::CredentialOps;
+
+/// Anthropic OAuth (Claude Pro/Max) accounts on the shared pool actor.
+pub struct Claude;
+
+impl Provider for Claude {
+    const NAME: &'static str = "Claude";
+    const KIND: ProviderKind = ProviderKind::Claude;
+
+    type Resource = ClaudeResource;
+    type Store = CredentialOps;
+
+    fn supported_models(&self) -> (ModelCapabilities, Vec<String>) {
+        (SUPPORTED_MODEL_MASK.clone(), SUPPORTED_MODEL_NAMES.clone())
+    }
+
+    fn oauth_retry_policy(&self) -> ExponentialBuilder {
+        *OAUTH_RETRY_POLICY
+    }
+
+    fn refresh_access_token(
+        &self,
+        refresh_token: &str,
+        http_client: reqwest::Client,
+    ) -> impl Future<Output = Result<OauthTokenResponse, OauthError>> + Send {
+        ClaudeOauthEndpoints::refresh_access_token(refresh_token, http_client)
+    }
+}
+
+pub type ClaudeActorHandle = ProviderActorHandle<Claude>;
+
+pub(in crate::providers) async fn spawn(
+    db: crate::db::DbActorHandle,
+    cfg: Arc<ClaudeResolvedConfig>,
+) -> ClaudeActorHandle {
+    let settings = PoolSettings {
+        endpoint: cfg.custom_api_url.to_string(),
+        retry_max_times: cfg.retry_max_times,
+        auto_disable: cfg.auto_disable,
+        health: cfg.health,
+        min_token_validity: Duration::from_secs(cfg.min_token_validity_secs),
+        stale_grace: Duration::from_secs(cfg.stale_grace_secs),
+        capability_restore: Duration::from_secs(cfg.capability_restore_secs),
+        max_concurrent_per_credential: cfg.max_concurrent_per_credential,
+        tier_weights: HashMap::new(),
+        scheduling_policy: cfg.scheduling_policy,
+        priority_reserve: cfg.priority_reserve,
+        lease_wait: Duration::from_millis(cfg.lease_wait_ms),
+        oauth: OauthWorkerSettings {
+            proxy: cfg.proxy.clone(),
+            user_agent: None,
+            enable_multiplexing: cfg.enable_multiplexing,
+            http_client: cfg.http_client,
+            oauth_tps: cfg.oauth_tps,
+        },
+    };
+    crate::providers::traits::actor::spawn(db, Claude, settings).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::RefreshTokenSeed;
+    use crate::providers::traits::oauth_worker::{CredentialJob, CredentialJobKind};
+    use crate::providers::traits::provider::ProviderResource;
+    use serde_json::json;
+
+    #[test]
+    fn refresh_job_keeps_refresh_id() {
+        let cred = ClaudeResource::from_payload(json!({
+            "account_uuid": "acct-1",
+            "organization_uuid": "org-1",
+            "refresh_token": "rt-1",
+            "access_token": "at-1",
+            "expiry": "2026-01-01T00:00:00Z",
+        }))
+        .expect("valid credential payload");
+
+        let job = CredentialJob::refresh(42, cred);
+
+        assert_eq!(job.kind.credential_id(), Some(42));
+    }
+
+    #[test]
+    fn untrusted_ingest_job_sets_refresh_token() {
+        let seed = RefreshTokenSeed::new("seed-rt").expect("valid seed");
+
+        let job =
+            CredentialJob::<ClaudeResource>::ingest_untrusted_seed(&seed).expect("ingest job");
+
+        assert!(matches!(job.kind, CredentialJobKind::IngestUntrusted));
+        assert_eq!(job.cred.refresh_token(), "seed-rt");
+        assert_eq!(job.kind.credential_id(), None);
+    }
+
+    #[test]
+    fn seed_report_names_the_organization() {
+        let cred = ClaudeResource::from_payload(json!({
+            "email": "a@example.com",
+            "account_uuid": "acct-1",
+            "organization_uuid": "org-1",
+        }))
+        .expect("valid credential payload");
+
+        assert!(cred.has_identity());
+        assert_eq!(cred.seed_account(), "org-1");
+        assert_eq!(cred.email(), Some("a@example.com"));
+        assert!(!ClaudeResource::default().has_identity());
+    }
+}
@@ -0,0 +1,144 @@
+use crate::db::{
+    ClaudeCreate, ClaudePatch, DbActorHandle, ProviderCreate, ProviderDelete, ProviderIdentity,
+    ProviderPatch,
+};
+use crate::error::PolluxError;
+use crate::providers::claude::resource::ClaudeResource;
+use crate::providers::credential_view::CredentialView;
+use crate::providers::seed::StoredSeed;
+use crate::providers::traits::provider::{CredentialStore, ProviderResource};
+use crate::providers::traits::scheduler::{CredentialId, Schedulable};
+
+#[derive(Clone)]
+pub struct CredentialOps {
+    db: DbActorHandle,
+}
+
+impl CredentialOps {
+    async fn upsert(&self, cred: ClaudeResource) -> Result<CredentialId, PolluxError> {
+        let create: ClaudeCreate = cred.into();
+        let id = self.db.create(ProviderCreate::Claude(create)).await?;
+
+        u64::try_from(id)
+            .map_err(|_| PolluxError::UnexpectedError(format!("Invalid credential id {id}")))
+    }
+
+    async fn update_by_id(&self, id: CredentialId, patch: ClaudePatch) -> Result<(), PolluxError> {
+        let _ = i64::try_from(id)
+            .map_err(|_| PolluxError::UnexpectedError(format!("Invalid credential id {id}")))?;
+
+        self.db.patch(ProviderPatch::Claude { id, patch }).await?;
+        Ok(())
+    }
+}
+
+impl CredentialStore<ClaudeResource> for CredentialOps {
+    fn new(db: DbActorHandle) -> Self {
+        Self { db }
+    }
+
+    async fn load_active(&self) -> Result<Vec<(CredentialId, ClaudeResource)>, PolluxError> {
+        let rows = self.db.list_active_claude().await?;
+        let mut result = Vec::with_capacity(rows.len());
+        for row in rows {
+            let id = u64::try_from(row.id).map_err(|_| {
+                PolluxError::UnexpectedError(format!("Invalid credential id {}", row.id))
+            })?;
+            result.push((id, row.into()));
+        }
+        Ok(result)
+    }
+
+    /// Store a freshly onboarded credential. An account (`account_uuid`,
+    /// `organization_uuid`) that already has a row keeps its id: the row is updated in place and
+    /// re-enabled.
+    async fn store_onboarded(
+        &self,
+        cred: ClaudeResource,
+    ) -> Result<StoredSeed<ClaudeResource>, PolluxError> {
+        let identity = ProviderIdentity::Claude {
+            account_uuid: cred.account_uuid().to_string(),
+            organization_uuid: cred.organization_uuid().to_string(),
+        };
+        let existing = self.db.find_by_identity(identity).await?;
+        let Some(id) = existing
+            .map(|id| {
+                u64::try_from(id).map_err(|_| {
+                    PolluxError::UnexpectedError(format!("Invalid credential id {id}"))
+                })
+            })
+            .transpose()?
+        else {
+            let id = self.upsert(cred.clone()).await?;
+            return Ok(StoredSeed {
+                id,
+                credential: cred,
+                existing: false,
+            });
+        };
+
+        let patch = ClaudePatch {
+            email: cred.email().map(ToString::to_string),
+            refresh_token: Some(cred.refresh_token().to_string()),
+            access_token: Some(cred.access_token().to_string()),
+            expiry: Some(cred.expiry()),
+            status: Some(true),
+            labels: (!cred.labels().is_empty()).then(|| cred.labels().to_vec()),
+        };
+        self.update_by_id(id, patch).await?;
+        Ok(StoredSeed {
+            id,
+            credential: self.get_by_id(id).await?,
+            existing: true,
+        })
+    }
+
+    async fn save_refreshed(
+        &self,
+        id: CredentialId,
+        cred: &ClaudeResource,
+    ) -> Result<(), PolluxError> {
+        let patch = ClaudePatch {
+            email: cred.email().map(ToString::to_string),
+            refresh_token: Some(cred.refresh_token().to_string()),
+            access_token: Some(cred.access_token().to_string()),
+            expiry: Some(cred.expiry()),
+            ..Default::default()
+        };
+        self.update_by_id(id, patch).await
+    }
+
+    async fn set_status(&self, id: CredentialId, status: bool) -> Result<(), PolluxError> {
+        let _ = i64::try_from(id)
+            .map_err(|_| PolluxError::UnexpectedError(format!("Invalid credential id {id}")))?;
+        let patch = ClaudePatch {
+            status: Some(status),
+            ..Default::default()
+        };
+        self.db.patch(ProviderPatch::Claude { id, patch }).await
+    }
+
+    /// All stored rows (any status), for admin listing.
+    async fn load_all_views(&self) -> Result<Vec<CredentialView>, PolluxError> {
+        let rows = self.db.list_claude().await?;
+        Ok(rows.into_iter().map(CredentialView::from).collect())
+    }
+
+    async fn get_by_id(&self, id: CredentialId) -> Result<ClaudeResource, PolluxError> {
+        let db_id = i64::try_from(id)
+            .map_err(|_| PolluxError::UnexpectedError(format!("Invalid credential id {id}")))?;
+        match self.db.get_claude_by_id(db_id).await {
+            Ok(row) => Ok(row.into()),
+            Err(PolluxError::DatabaseError(sqlx::Error::RowNotFound)) => {
+                Err(PolluxError::NotFound(format!("claude record id={id}")))
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    async fn delete(&self, id: CredentialId) -> Result<(), PolluxError> {
+        let db_id = i64::try_from(id)
+            .map_err(|_| PolluxError::UnexpectedError(format!("Invalid credential id {id}")))?;
+        self.db.delete(ProviderDelete::Claude(db_id)).await
+    }
+}
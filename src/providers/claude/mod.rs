@@ -0,0 +1,21 @@
+pub mod client;
+pub(crate) mod doctor;
+mod errors;
+mod manager;
+mod model_mask;
+pub(crate) mod oauth;
+mod resource;
+
+pub use manager::ClaudeActorHandle;
+pub(in crate::providers) use manager::spawn;
+pub(crate) use model_mask::{SUPPORTED_MODEL_MASK, SUPPORTED_MODEL_NAMES, model_mask};
+
+/// `anthropic-version` sent when the client did not pin one.
+pub(crate) const ANTHROPIC_VERSION: &str = "2023-06-01";
+
+/// Beta flag Anthropic requires for requests authorized by an OAuth token.
+pub(crate) const ANTHROPIC_OAUTH_BETA: &str = "oauth-2025-04-20";
+
+/// Claude Code CLI User-Agent sent on API calls; OAuth tokens are issued to
+/// that client.
+pub(crate) const CLAUDE_USER_AGENT: &str = "claude-cli/2.0.14 (external, cli)";
@@ -0,0 +1,353 @@
+use crate::db::{ClaudeCreate, DbClaudeResource, split_labels};
+use crate::error::PolluxError;
+use crate::providers::RefreshTokenSeed;
+use crate::providers::claude::oauth::OauthTokenResponse;
+use crate::providers::manifest::{ClaudeLease, ClaudeProfile};
+use crate::providers::traits::provider::ProviderResource;
+use crate::providers::traits::scheduler::{CooldownScope, CredentialId, Schedulable};
+use chrono::{DateTime, Duration, Utc};
+use oauth2::TokenResponse;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tracing::debug;
+use url::Url;
+
+/// In-memory credential state for the Claude provider.
+///
+/// Identity (`account_uuid`, `organization_uuid`, email) comes from the
+/// `account` / `organization` objects Anthropic returns alongside the tokens.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClaudeResource {
+    email: Option<String>,
+    account_uuid: String,
+    organization_uuid: String,
+    refresh_token: String,
+    access_token: String,
+    expiry: DateTime<Utc>,
+    #[serde(default)]
+    labels: Vec<String>,
+    #[serde(default)]
+    proxy_url: Option<Url>,
+}
+
+impl Default for ClaudeResource {
+    fn default() -> Self {
+        Self {
+            email: None,
+            account_uuid: String::new(),
+            organization_uuid: String::new(),
+            refresh_token: String::new(),
+            access_token: String::new(),
+            expiry: Utc::now(),
+            labels: Vec::new(),
+            proxy_url: None,
+        }
+    }
+}
+
+impl ClaudeResource {
+    /// Return true if current time is within 5 minutes of expiry (inclusive).
+    #[allow(dead_code)]
+    pub fn is_expired(&self) -> bool {
+        Utc::now() + Duration::minutes(5) >= self.expiry
+    }
+
+    pub fn account_uuid(&self) -> &str {
+        &self.account_uuid
+    }
+
+    pub fn organization_uuid(&self) -> &str {
+        &self.organization_uuid
+    }
+
+    /// Build a resource from any JSON-like payload by applying updates to a default struct.
+    #[cfg(test)]
+    pub fn from_payload(payload: impl Serialize) -> Result<Self, PolluxError> {
+        let mut cred = ClaudeResource::default();
+        cred.update_credential(payload)?;
+        Ok(cred)
+    }
+}
+
+impl ProviderResource for ClaudeResource {
+    fn refresh_token(&self) -> &str {
+        &self.refresh_token
+    }
+
+    fn access_token(&self) -> &str {
+        &self.access_token
+    }
+
+    fn expiry(&self) -> DateTime<Utc> {
+        self.expiry
+    }
+
+    fn proxy_url(&self) -> Option<&Url> {
+        self.proxy_url.as_ref()
+    }
+
+    fn set_labels(&mut self, labels: Vec<String>) {
+        self.labels = labels;
+    }
+
+    fn set_proxy_url(&mut self, proxy_url: Option<Url>) {
+        self.proxy_url = proxy_url;
+    }
+
+    /// Merge updates from any JSON-serializable payload into this resource.
+    ///
+    /// This accepts both:
+    /// - full credential JSON (`account_uuid`, `refresh_token`, `expiry`, etc.)
+    /// - OAuth token refresh payloads (`access_token`, `expires_in`)
+    ///
+    /// Only updates fields present in the JSON; others remain unchanged.
+    fn update_credential(&mut self, payload: impl Serialize) -> Result<(), PolluxError> {
+        use crate::providers::credential_update::{apply_expiry, parse_patch, set_opt, set_plain};
+
+        #[derive(Debug, Default, Deserialize)]
+        struct CredentialPatch {
+            email: Option<String>,
+            account_uuid: Option<String>,
+            organization_uuid: Option<String>,
+            refresh_token: Option<String>,
+            access_token: Option<String>,
+            expiry: Option<DateTime<Utc>>,
+            expires_in: Option<i64>,
+        }
+
+        let patch: CredentialPatch = parse_patch(payload)?;
+
+        set_opt(&mut self.email, patch.email);
+        set_plain(&mut self.account_uuid, patch.account_uuid);
+        set_plain(&mut self.organization_uuid, patch.organization_uuid);
+        set_plain(&mut self.refresh_token, patch.refresh_token);
+        set_plain(&mut self.access_token, patch.access_token);
+        apply_expiry(&mut self.expiry, patch.expires_in, patch.expiry);
+
+        debug!(
+            "account_uuid={}, organization_uuid={}, Claude resource updated successfully",
+            self.account_uuid, self.organization_uuid
+        );
+
+        Ok(())
+    }
+
+    fn try_from_oauth_token_response(
+        token_response: &OauthTokenResponse,
+        refresh_seed: Option<&RefreshTokenSeed>,
+    ) -> Result<Self, PolluxError> {
+        let access_token = Some(token_response.access_token().secret().trim())
+            .filter(|&s| !s.is_empty())
+            .map(ToString::to_string)
+            .ok_or_else(|| {
+                PolluxError::UnexpectedError("Missing access_token in OAuth token response".into())
+            })?;
+
+        let expiry = Utc::now()
+            + token_response
+                .expires_in()
+                .unwrap_or(std::time::Duration::from_hours(1));
+
+        // Anthropic rotates refresh tokens, but keep the seed if none came back.
+        let refresh_token = token_response
+            .refresh_token()
+            .map(|t| t.secret().trim())
+            .filter(|&s| !s.is_empty())
+            .or_else(|| refresh_seed.map(RefreshTokenSeed::refresh_token))
+            .map(ToString::to_string)
+            .ok_or_else(|| {
+                PolluxError::UnexpectedError("Missing refresh_token in OAuth token response".into())
+            })?;
+
+        let extra = &token_response.extra_fields().extra;
+        let field = |object: &str, key: &str| {
+            extra
+                .get(object)
+                .and_then(|o| o.get(key))
+                .and_then(Value::as_str)
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+                .map(ToString::to_string)
+        };
+        let missing = |what: &str| {
+            PolluxError::UnexpectedError(format!("Missing {what} in OAuth token response"))
+        };
+
+        Ok(ClaudeResource {
+            email: field("account", "email_address"),
+            account_uuid: field("account", "uuid").ok_or_else(|| missing("account.uuid"))?,
+            organization_uuid: field("organization", "uuid")
+                .ok_or_else(|| missing("organization.uuid"))?,
+            refresh_token,
+            access_token,
+            expiry,
+            labels: Vec::new(),
+            proxy_url: None,
+        })
+    }
+
+    fn has_identity(&self) -> bool {
+        !self.account_uuid.trim().is_empty() && !self.organization_uuid.trim().is_empty()
+    }
+
+    fn email(&self) -> Option<&str> {
+        self.email.as_deref()
+    }
+
+    /// Onboarded accounts are reported by organization.
+    fn seed_account(&self) -> &str {
+        &self.organization_uuid
+    }
+}
+
+impl Schedulable for ClaudeResource {
+    type Lease = ClaudeLease;
+    const COOLDOWN_GRANULARITY: CooldownScope = CooldownScope::PerCredential;
+
+    fn identifier(&self) -> &str {
+        &self.account_uuid
+    }
+
+    fn expires_within(&self, min_validity: std::time::Duration) -> bool {
+        Duration::from_std(min_validity)
+            .ok()
+            .and_then(|margin| Utc::now().checked_add_signed(margin))
+            .is_none_or(|deadline| deadline >= self.expiry)
+    }
+
+    fn expired_beyond(&self, grace: std::time::Duration) -> bool {
+        Duration::from_std(grace)
+            .ok()
+            .and_then(|grace| self.expiry.checked_add_signed(grace))
+            .is_none_or(|deadline| Utc::now() > deadline)
+    }
+
+    fn make_lease(&self, id: CredentialId) -> ClaudeLease {
+        ClaudeLease {
+            id,
+            access_token: self.access_token.clone(),
+            organization_uuid: self.organization_uuid.clone(),
+            email: self.email.clone(),
+            proxy_url: self.proxy_url.clone(),
+        }
+    }
+
+    fn labels(&self) -> &[String] {
+        &self.labels
+    }
+}
+
+impl TryFrom<ClaudeProfile> for ClaudeResource {
+    type Error = PolluxError;
+
+    fn try_from(profile: ClaudeProfile) -> Result<Self, Self::Error> {
+        let access_token = profile
+            .access_token
+            .ok_or(PolluxError::MissingAccessToken)?;
+        let expiry = profile.expiry.ok_or(PolluxError::MissingExpiry)?;
+
+        Ok(ClaudeResource {
+            email: profile.email,
+            account_uuid: profile.account_uuid,
+            organization_uuid: profile.organization_uuid,
+            refresh_token: profile.refresh_token,
+            access_token,
+            expiry,
+            labels: Vec::new(),
+            proxy_url: None,
+        })
+    }
+}
+
+impl From<DbClaudeResource> for ClaudeResource {
+    fn from(d: DbClaudeResource) -> Self {
+        ClaudeResource {
+            email: d.email,
+            account_uuid: d.account_uuid,
+            organization_uuid: d.organization_uuid,
+            refresh_token: d.refresh_token,
+            access_token: d.access_token,
+            expiry: d.expiry,
+            labels: split_labels(&d.labels),
+            proxy_url: d.proxy_url.and_then(|url| url.parse().ok()),
+        }
+    }
+}
+
+impl From<ClaudeResource> for ClaudeCreate {
+    fn from(cred: ClaudeResource) -> Self {
+        ClaudeCreate {
+            email: cred.email,
+            account_uuid: cred.account_uuid,
+            organization_uuid: cred.organization_uuid,
+            refresh_token: cred.refresh_token,
+            access_token: cred.access_token,
+            expiry: cred.expiry,
+            labels: cred.labels,
+            proxy_url: cred.proxy_url,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn token_response_carries_account_identity() {
+        let token_response: OauthTokenResponse = serde_json::from_value(json!({
+            "access_token": "sk-ant-oat01-at",
+            "token_type": "Bearer",
+            "expires_in": 28800,
+            "refresh_token": "sk-ant-ort01-rt",
+            "scope": "user:inference user:profile",
+            "organization": { "uuid": "org-1", "name": "Personal" },
+            "account": { "uuid": "acct-1", "email_address": "me@example.com" },
+        }))
+        .expect("token response deserializes");
+
+        let cred = ClaudeResource::try_from_oauth_token_response(&token_response, None)
+            .expect("identity present");
+
+        assert_eq!(cred.account_uuid(), "acct-1");
+        assert_eq!(cred.organization_uuid(), "org-1");
+        assert_eq!(cred.email(), Some("me@example.com"));
+        assert_eq!(cred.refresh_token(), "sk-ant-ort01-rt");
+        assert!(!cred.expires_within(std::time::Duration::from_hours(7)));
+    }
+
+    #[test]
+    fn token_response_without_organization_is_rejected() {
+        let token_response: OauthTokenResponse = serde_json::from_value(json!({
+            "access_token": "at",
+            "token_type": "Bearer",
+            "refresh_token": "rt",
+            "account": { "uuid": "acct-1" },
+        }))
+        .expect("token response deserializes");
+
+        assert!(ClaudeResource::try_from_oauth_token_response(&token_response, None).is_err());
+    }
+
+    #[test]
+    fn update_credential_supports_expires_in() {
+        let mut cred = ClaudeResource::from_payload(json!({
+            "account_uuid": "acct-1",
+            "organization_uuid": "org-1",
+            "refresh_token": "rt",
+            "access_token": "at0",
+            "expiry": Utc::now() - chrono::Duration::minutes(10),
+        }))
+        .expect("valid payload");
+
+        cred.update_credential(json!({
+            "access_token": "at1",
+            "expires_in": 3600,
+        }))
+        .expect("valid update");
+
+        assert_eq!(cred.access_token(), "at1");
+        assert!(!cred.is_expired());
+    }
+}
@@ -0,0 +1,575 @@
+//! `OpenAI` Chat Completions ⇄ Responses translation for Codex.
+//!
+//! Requests are rewritten into a Responses API body, which the regular Codex
+//! path then turns into a `CodexRequestBody`. Responses (the buffered
+//! `response.completed` object or the live event stream) are rewritten back
+//! into `chat.completion` / `chat.completion.chunk` objects.
+//!
+//! Unlike Gemini, the Responses API carries its own `call_id`s, so tool call
+//! ids round-trip unchanged.
+
+use crate::providers::chat_compat::ChatResponseMeta;
+use pollux_schema::openai::{
+    ChatChoice, ChatChunkChoice, ChatCompletion, ChatCompletionChunk, ChatCompletionRequest,
+    ChatDelta, ChatFunctionCall, ChatFunctionCallDelta, ChatMessage, ChatResponseMessage, ChatTool,
+    ChatToolCall, ChatToolCallDelta, ChatUsage, OpenaiInput, OpenaiInputContent, OpenaiInputItem,
+    OpenaiRequestBody, OpenaiRole, Reasoning,
+};
+use serde_json::{Value, json};
+use std::collections::{BTreeMap, HashMap};
+
+/// Translate a Chat Completions request into a Responses API request body.
+///
+/// The error string is client-facing and describes the offending field.
+pub fn chat_request_to_responses(req: ChatCompletionRequest) -> Result<OpenaiRequestBody, String> {
+    let mut input = Vec::with_capacity(req.messages.len());
+    for (index, message) in req.messages.into_iter().enumerate() {
+        translate_message(message, index, &mut input)?;
+    }
+
+    let mut extra = BTreeMap::new();
+    if let Some(tools) = req.tools {
+        extra.insert("tools".to_string(), translate_tools(tools)?);
+    }
+    if let Some(choice) = req.tool_choice.as_ref() {
+        extra.insert("tool_choice".to_string(), translate_tool_choice(choice)?);
+    }
+    let reasoning = req
+        .extra
+        .get("reasoning_effort")
+        .and_then(Value::as_str)
+        .map(|effort| Reasoning {
+            effort: Some(effort.to_string()),
+            summary: None,
+        });
+
+    Ok(OpenaiRequestBody {
+        include: None,
+        input: Some(OpenaiInput::Items(input)),
+        instructions: None,
+        max_output_tokens: req.max_completion_tokens.or(req.max_tokens),
+        model: req.model,
+        parallel_tool_calls: None,
+        reasoning,
+        service_tier: None,
+        store: None,
+        stream: req.stream,
+        #[allow(clippy::cast_possible_truncation)]
+        temperature: req.temperature.map(|t| t as f32),
+        #[allow(clippy::cast_possible_truncation)]
+        top_p: req.top_p.map(|p| p as f32),
+        extra,
+    })
+}
+
+fn message_item(role: OpenaiRole, parts: Vec<Value>) -> OpenaiInputItem {
+    OpenaiInputItem {
+        role: Some(role),
+        content: Some(OpenaiInputContent::Parts(parts)),
+        extra: BTreeMap::new(),
+    }
+}
+
+/// Role-less input item such as `function_call` or `function_call_output`.
+fn typed_item(fields: Value) -> OpenaiInputItem {
+    let extra = match fields {
+        Value::Object(map) => map.into_iter().collect(),
+        _ => BTreeMap::new(),
+    };
+    OpenaiInputItem {
+        role: None,
+        content: None,
+        extra,
+    }
+}
+
+fn translate_message(
+    message: ChatMessage,
+    index: usize,
+    input: &mut Vec<OpenaiInputItem>,
+) -> Result<(), String> {
+    match message.role.as_str() {
+        "system" => input.push(message_item(
+            OpenaiRole::System,
+            content_parts(message.content.as_ref(), index)?,
+        )),
+        "developer" => input.push(message_item(
+            OpenaiRole::Developer,
+            content_parts(message.content.as_ref(), index)?,
+        )),
+        "user" => input.push(message_item(
+            OpenaiRole::User,
+            content_parts(message.content.as_ref(), index)?,
+        )),
+        "assistant" => {
+            let parts = content_parts(message.content.as_ref(), index)?;
+            if !parts.is_empty() {
+                input.push(message_item(OpenaiRole::Assistant, parts));
+            }
+            for call in message.tool_calls.unwrap_or_default() {
+                input.push(typed_item(json!({
+                    "type": "function_call",
+                    "call_id": call.id,
+                    "name": call.function.name,
+                    "arguments": call.function.arguments,
+                })));
+            }
+        }
+        "tool" => {
+            let call_id = message
+                .tool_call_id
+                .ok_or_else(|| format!("messages[{index}].tool_call_id is required"))?;
+            input.push(typed_item(json!({
+                "type": "function_call_output",
+                "call_id": call_id,
+                "output": content_text(message.content.as_ref()),
+            })));
+        }
+        other => return Err(format!("messages[{index}].role `{other}` is not supported")),
+    }
+    Ok(())
+}
+
+/// `content` as a string or an array of `text` / `image_url` parts.
+fn content_parts(content: Option<&Value>, index: usize) -> Result<Vec<Value>, String> {
+    match content {
+        None | Some(Value::Null) => Ok(Vec::new()),
+        Some(Value::String(text)) => Ok(vec![json!({"type": "input_text", "text": text})]),
+        Some(Value::Array(items)) => items.iter().map(|item| content_item(item, index)).collect(),
+        Some(_) => Err(format!(
+            "messages[{index}].content must be a string or an array"
+        )),
+    }
+}
+
+fn content_item(item: &Value, index: usize) -> Result<Value, String> {
+    match item.get("type").and_then(Value::as_str) {
+        Some("text") => {
+            let text = item.get("text").and_then(Value::as_str).unwrap_or_default();
+            Ok(json!({"type": "input_text", "text": text}))
+        }
+        Some("image_url") => {
+            let url = item
+                .pointer("/image_url/url")
+                .and_then(Value::as_str)
+                .ok_or_else(|| format!("messages[{index}].content image_url.url is required"))?;
+            let mut part = json!({"type": "input_image", "image_url": url});
+            if let Some(detail) = item.pointer("/image_url/detail") {
+                part["detail"] = detail.clone();
+            }
+            Ok(part)
+        }
+        other => Err(format!(
+            "messages[{index}].content part type `{}` is not supported",
+            other.unwrap_or("<missing>")
+        )),
+    }
+}
+
+/// Text of a `tool` message; array content is concatenated.
+fn content_text(content: Option<&Value>) -> String {
+    match content {
+        Some(Value::String(text)) => text.clone(),
+        Some(Value::Array(items)) => items
+            .iter()
+            .filter_map(|item| item.get("text").and_then(Value::as_str))
+            .collect(),
+        Some(Value::Null) | None => String::new(),
+        Some(other) => other.to_string(),
+    }
+}
+
+/// Chat nests the definition under `function`; Responses flattens it.
+fn translate_tools(tools: Vec<ChatTool>) -> Result<Value, String> {
+    tools
+        .into_iter()
+        .enumerate()
+        .map(|(i, tool)| {
+            if tool.kind != "function" {
+                return Err(format!("tools[{i}].type `{}` is not supported", tool.kind));
+            }
+            let function = tool
+                .function
+                .ok_or_else(|| format!("tools[{i}].function is required"))?;
+            let mut out = json!({"type": "function", "name": function.name});
+            if let Some(description) = function.description {
+                out["description"] = Value::String(description);
+            }
+            out["parameters"] = function
+                .parameters
+                .unwrap_or_else(|| json!({"type": "object", "properties": {}}));
+            if let Some(strict) = function.strict {
+                out["strict"] = Value::Bool(strict);
+            }
+            Ok(out)
+        })
+        .collect::<Result<Vec<_>, _>>()
+        .map(Value::Array)
+}
+
+/// String choices pass through; a named function is flattened.
+fn translate_tool_choice(choice: &Value) -> Result<Value, String> {
+    match choice {
+        Value::String(mode) if matches!(mode.as_str(), "none" | "auto" | "required") => {
+            Ok(choice.clone())
+        }
+        Value::Object(_) => choice
+            .pointer("/function/name")
+            .and_then(Value::as_str)
+            .map(|name| json!({"type": "function", "name": name}))
+            .ok_or_else(|| "tool_choice.function.name is required".to_string()),
+        _ => Err(format!("tool_choice `{choice}` is not supported")),
+    }
+}
+
+fn usage_from_responses(usage: Option<&Value>) -> Option<ChatUsage> {
+    let usage = usage?;
+    let field = |name: &str| usage.get(name).and_then(Value::as_u64);
+    let prompt = field("input_tokens")?;
+    let completion = field("output_tokens").unwrap_or(0);
+    Some(ChatUsage {
+        prompt_tokens: prompt,
+        completion_tokens: completion,
+        total_tokens: field("total_tokens").unwrap_or(prompt + completion),
+    })
+}
+
+/// `status: "incomplete"` because of the token limit maps to `length`.
+fn finish_reason(response: &Value, has_tool_calls: bool) -> String {
+    if has_tool_calls {
+        return "tool_calls".to_string();
+    }
+    match response
+        .pointer("/incomplete_details/reason")
+        .and_then(Value::as_str)
+    {
+        Some("max_output_tokens") => "length",
+        Some("content_filter") => "content_filter",
+        _ => "stop",
+    }
+    .to_string()
+}
+
+fn output_text(item: &Value) -> String {
+    item.get("content")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter(|part| part.get("type").and_then(Value::as_str) == Some("output_text"))
+        .filter_map(|part| part.get("text").and_then(Value::as_str))
+        .collect()
+}
+
+fn tool_call_from_item(item: &Value) -> Option<ChatToolCall> {
+    let field = |name: &str| item.get(name).and_then(Value::as_str);
+    Some(ChatToolCall {
+        id: field("call_id").or_else(|| field("id"))?.to_string(),
+        kind: "function".to_string(),
+        function: ChatFunctionCall {
+            name: field("name")?.to_string(),
+            arguments: field("arguments").unwrap_or_default().to_string(),
+        },
+    })
+}
+
+/// Translate a complete Responses `response` object into a `chat.completion`.
+///
+/// Reasoning items are dropped.
+pub fn responses_to_chat_completion(response: &Value, meta: &ChatResponseMeta) -> ChatCompletion {
+    let mut text = String::new();
+    let mut tool_calls = Vec::new();
+    for item in response
+        .get("output")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+    {
+        match item.get("type").and_then(Value::as_str) {
+            Some("message") => text.push_str(&output_text(item)),
+            Some("function_call") => tool_calls.extend(tool_call_from_item(item)),
+            _ => {}
+        }
+    }
+
+    ChatCompletion {
+        id: meta.id.clone(),
+        object: "chat.completion".to_string(),
+        created: meta.created,
+        model: meta.model.clone(),
+        choices: vec![ChatChoice {
+            index: 0,
+            finish_reason: Some(finish_reason(response, !tool_calls.is_empty())),
+            message: ChatResponseMessage {
+                role: "assistant".to_string(),
+                content: (!text.is_empty()).then_some(text),
+                tool_calls: (!tool_calls.is_empty()).then_some(tool_calls),
+            },
+        }],
+        usage: usage_from_responses(response.get("usage")),
+    }
+}
+
+/// Per-stream state for turning Responses events into `chat.completion.chunk`s.
+///
+/// Function calls arrive as an `output_item.added` event followed by argument
+/// deltas keyed by item id; each call gets the next tool call index.
+#[derive(Debug)]
+pub struct ResponsesChatStream {
+    meta: ChatResponseMeta,
+    include_usage: bool,
+    role_sent: bool,
+    tool_indices: HashMap<String, u32>,
+    finished: bool,
+    usage: Option<ChatUsage>,
+}
+
+impl ResponsesChatStream {
+    pub fn new(meta: ChatResponseMeta, include_usage: bool) -> Self {
+        Self {
+            meta,
+            include_usage,
+            role_sent: false,
+            tool_indices: HashMap::new(),
+            finished: false,
+            usage: None,
+        }
+    }
+
+    fn chunk(&self, delta: ChatDelta, finish_reason: Option<String>) -> ChatCompletionChunk {
+        ChatCompletionChunk {
+            id: self.meta.id.clone(),
+            object: "chat.completion.chunk".to_string(),
+            created: self.meta.created,
+            model: self.meta.model.clone(),
+            choices: vec![ChatChunkChoice {
+                index: 0,
+                delta,
+                finish_reason,
+            }],
+            usage: None,
+        }
+    }
+
+    fn delta(
+        &mut self,
+        content: Option<String>,
+        tool_call: Option<ChatToolCallDelta>,
+    ) -> ChatDelta {
+        let role = (!self.role_sent).then(|| "assistant".to_string());
+        self.role_sent = true;
+        ChatDelta {
+            role,
+            content,
+            tool_calls: tool_call.map(|call| vec![call]),
+        }
+    }
+
+    /// Chunks for one upstream event payload.
+    pub fn on_event(&mut self, event: &Value) -> Vec<ChatCompletionChunk> {
+        let field = |name: &str| event.get(name).and_then(Value::as_str);
+        match field("type") {
+            Some("response.output_text.delta") => {
+                let Some(text) = field("delta").filter(|d| !d.is_empty()) else {
+                    return Vec::new();
+                };
+                let delta = self.delta(Some(text.to_string()), None);
+                vec![self.chunk(delta, None)]
+            }
+            Some("response.output_item.added") => {
+                let Some(item) = event.get("item") else {
+                    return Vec::new();
+                };
+                if item.get("type").and_then(Value::as_str) != Some("function_call") {
+                    return Vec::new();
+                }
+                let Some(call) = tool_call_from_item(item) else {
+                    return Vec::new();
+                };
+                let index = u32::try_from(self.tool_indices.len()).unwrap_or(u32::MAX);
+                let key = item
+                    .get("id")
+                    .and_then(Value::as_str)
+                    .unwrap_or(&call.id)
+                    .to_string();
+                self.tool_indices.insert(key, index);
+                let arguments = call.function.arguments;
+                let delta = self.delta(
+                    None,
+                    Some(ChatToolCallDelta {
+                        index,
+                        id: Some(call.id),
+                        kind: Some(call.kind),
+                        function: ChatFunctionCallDelta {
+                            name: Some(call.function.name),
+                            arguments: (!arguments.is_empty()).then_some(arguments),
+                        },
+                    }),
+                );
+                vec![self.chunk(delta, None)]
+            }
+            Some("response.function_call_arguments.delta") => {
+                let (Some(item_id), Some(fragment)) = (field("item_id"), field("delta")) else {
+                    return Vec::new();
+                };
+                let Some(&index) = self.tool_indices.get(item_id) else {
+                    return Vec::new();
+                };
+                let delta = self.delta(
+                    None,
+                    Some(ChatToolCallDelta {
+                        index,
+                        id: None,
+                        kind: None,
+                        function: ChatFunctionCallDelta {
+                            name: None,
+                            arguments: Some(fragment.to_string()),
+                        },
+                    }),
+                );
+                vec![self.chunk(delta, None)]
+            }
+            Some("response.completed" | "response.incomplete") => {
+                let Some(response) = event.get("response") else {
+                    return Vec::new();
+                };
+                self.usage = usage_from_responses(response.get("usage"));
+                self.finished = true;
+                let reason = finish_reason(response, !self.tool_indices.is_empty());
+                vec![self.chunk(ChatDelta::default(), Some(reason))]
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    /// Trailing chunks once upstream ends: a `stop` if no terminal event was
+    /// seen, and the usage chunk when `include_usage` was requested.
+    pub fn finish(&mut self) -> Vec<ChatCompletionChunk> {
+        let mut out = Vec::new();
+        if !self.finished {
+            self.finished = true;
+            out.push(self.chunk(ChatDelta::default(), Some("stop".to_string())));
+        }
+        if self.include_usage {
+            let mut chunk = self.chunk(ChatDelta::default(), None);
+            chunk.choices.clear();
+            chunk.usage = Some(self.usage.unwrap_or_default());
+            out.push(chunk);
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tool_turns_map_to_function_call_items() {
+        let req: ChatCompletionRequest = serde_json::from_value(json!({
+            "model": "gpt-5",
+            "messages": [
+                {"role": "system", "content": "be brief"},
+                {"role": "user", "content": "weather?"},
+                {"role": "assistant", "content": null, "tool_calls": [{
+                    "id": "call_1",
+                    "type": "function",
+                    "function": {"name": "get_weather", "arguments": "{\"city\":\"Paris\"}"}
+                }]},
+                {"role": "tool", "tool_call_id": "call_1", "content": "sunny"}
+            ],
+            "tools": [{"type": "function", "function": {"name": "get_weather"}}],
+            "tool_choice": {"type": "function", "function": {"name": "get_weather"}},
+            "reasoning_effort": "low"
+        }))
+        .unwrap();
+
+        let body = chat_request_to_responses(req).unwrap();
+        let value = serde_json::to_value(&body).unwrap();
+        assert_eq!(value["input"][0]["role"], "system");
+        assert_eq!(
+            value["input"][2],
+            json!({
+                "type": "function_call",
+                "call_id": "call_1",
+                "name": "get_weather",
+                "arguments": "{\"city\":\"Paris\"}"
+            })
+        );
+        assert_eq!(
+            value["input"][3],
+            json!({"type": "function_call_output", "call_id": "call_1", "output": "sunny"})
+        );
+        assert_eq!(value["tools"][0]["name"], "get_weather");
+        assert_eq!(
+            value["tool_choice"],
+            json!({"type": "function", "name": "get_weather"})
+        );
+        assert_eq!(value["reasoning"]["effort"], "low");
+    }
+
+    #[test]
+    fn stream_events_become_indexed_tool_call_deltas() {
+        let mut chat = ResponsesChatStream::new(ChatResponseMeta::new("gpt-5"), true);
+        let events = [
+            json!({"type": "response.created", "response": {}}),
+            json!({"type": "response.output_text.delta", "delta": "Checking"}),
+            json!({"type": "response.output_item.added", "item": {
+                "type": "function_call", "id": "fc_1", "call_id": "call_1",
+                "name": "get_weather", "arguments": ""
+            }}),
+            json!({"type": "response.function_call_arguments.delta", "item_id": "fc_1", "delta": "{\"city\""}),
+            json!({"type": "response.function_call_arguments.delta", "item_id": "fc_1", "delta": ":\"Paris\"}"}),
+            json!({"type": "response.completed", "response": {
+                "usage": {"input_tokens": 10, "output_tokens": 5, "total_tokens": 15}
+            }}),
+        ];
+        let mut chunks: Vec<_> = events.iter().flat_map(|e| chat.on_event(e)).collect();
+        chunks.extend(chat.finish());
+
+        assert_eq!(chunks.len(), 6);
+        assert_eq!(
+            chunks[0].choices[0].delta.role.as_deref(),
+            Some("assistant")
+        );
+        assert_eq!(
+            chunks[0].choices[0].delta.content.as_deref(),
+            Some("Checking")
+        );
+        let first = &chunks[1].choices[0].delta.tool_calls.as_ref().unwrap()[0];
+        assert_eq!(first.id.as_deref(), Some("call_1"));
+        assert_eq!(first.function.arguments, None);
+        let args: String = chunks[2..4]
+            .iter()
+            .filter_map(|c| {
+                c.choices[0].delta.tool_calls.as_ref()?[0]
+                    .function
+                    .arguments
+                    .clone()
+            })
+            .collect();
+        assert_eq!(args, "{\"city\":\"Paris\"}");
+        assert_eq!(
+            chunks[4].choices[0].finish_reason.as_deref(),
+            Some("tool_calls")
+        );
+        assert_eq!(chunks[5].usage.unwrap().total_tokens, 15);
+    }
+
+    #[test]
+    fn completed_response_maps_to_chat_completion() {
+        let response = json!({
+            "status": "incomplete",
+            "incomplete_details": {"reason": "max_output_tokens"},
+            "output": [
+                {"type": "reasoning", "summary": []},
+                {"type": "message", "content": [{"type": "output_text", "text": "Hello"}]}
+            ],
+            "usage": {"input_tokens": 3, "output_tokens": 1}
+        });
+        let completion = responses_to_chat_completion(&response, &ChatResponseMeta::new("gpt-5"));
+        let choice = &completion.choices[0];
+        assert_eq!(choice.message.content.as_deref(), Some("Hello"));
+        assert_eq!(choice.finish_reason.as_deref(), Some("length"));
+        assert_eq!(completion.usage.unwrap().total_tokens, 4);
+    }
+}
@@ -1,16 +1,22 @@
+use crate::PolluxError;
 use crate::error::{CodexError, IsRetryable};
-use crate::providers::codex::CodexActorHandle;
+use crate::providers::codex::usage::UsagePayload;
+use crate::providers::codex::{CodexActorHandle, CodexRateLimits};
+use crate::providers::error_clusters::ErrorClusters;
+use crate::providers::manifest::CodexLease;
+use crate::providers::manifest::ProviderKind;
 use crate::providers::provider_endpoints::ProviderEndpoints;
 use crate::providers::upstream_retry::post_json_bytes_with_retry;
 use crate::providers::{ActionForError, policy::classify_upstream_error};
 use crate::server::routes::codex::CodexContext;
 use crate::server::routes::codex::headers::{CodexRequestHeaders, OpenaiRequestHeaders};
+use crate::utils::http::EgressClients;
 use crate::utils::logging::with_pretty_json_debug;
 use axum::body::Bytes;
 use backon::{ExponentialBuilder, Retryable};
 use pollux_schema::{CodexErrorBody, CodexRequestBody};
 use reqwest::header::{HeaderName, HeaderValue};
-
+use std::collections::BTreeMap;
 use std::time::{Duration, Instant};
 use tracing::{debug, info};
 use url::Url;
@@ -22,18 +28,18 @@ use url::Url;
 /// - OAuth/token refresh is intentionally left as future work (placeholders in config).
 #[derive(Clone)]
 pub(crate) struct CodexClient {
-    client: reqwest::Client,
-    stream_client: reqwest::Client,
+    clients: EgressClients,
     retry_policy: ExponentialBuilder,
     endpoints: ProviderEndpoints,
     compact_url: Url,
+    usage_url: Url,
     trace_header: Option<String>,
+    error_clusters: ErrorClusters,
 }
 
 impl CodexClient {
     pub(crate) fn new(
-        client: reqwest::Client,
-        stream_client: reqwest::Client,
+        clients: EgressClients,
         base_url: &Url,
         retry_max_times: usize,
         trace_header: Option<String>,
@@ -43,19 +49,30 @@ impl CodexClient {
             .with_max_delay(Duration::ZERO)
             .with_max_times(retry_max_times);
         let compact_url = Self::compact_url(base_url);
+        let usage_url = base_url
+            .join("./backend-api/wham/usage")
+            .expect("valid usage endpoint path");
         let endpoints = Self::endpoints_for_base(base_url);
         info!(endpoint = %endpoints.select(false), "CodexClient initialized");
 
         Self {
-            client,
-            stream_client,
+            clients,
             retry_policy,
             endpoints,
             compact_url,
+            usage_url,
             trace_header,
+            error_clusters: ErrorClusters::default(),
         }
     }
 
+    /// Count upstream error responses into `clusters`.
+    #[must_use]
+    pub(crate) fn with_error_clusters(mut self, clusters: ErrorClusters) -> Self {
+        self.error_clusters = clusters;
+        self
+    }
+
     fn endpoints_for_base(base: &Url) -> ProviderEndpoints {
         ProviderEndpoints::new(
             base,
@@ -71,6 +88,33 @@ impl CodexClient {
             .expect("valid compact endpoint path")
     }
 
+    /// Plan and usage windows of the account behind `lease`. Sent outside the
+    /// scheduler, so no outcome is reported.
+    pub(crate) async fn fetch_usage(
+        &self,
+        lease: &CodexLease,
+    ) -> Result<UsagePayload, PolluxError> {
+        let inbound = OpenaiRequestHeaders {
+            session_id: uuid::Uuid::new_v4().to_string(),
+            turn_metadata: None,
+            extra: BTreeMap::new(),
+        };
+        let headers = CodexRequestHeaders::build(&inbound, lease).into_header_map();
+        let resp = self
+            .clients
+            .select(lease.id, lease.proxy_url.as_ref(), false)
+            .get(self.usage_url.clone())
+            .headers(headers)
+            .send()
+            .await?;
+        let status = resp.status();
+        if !status.is_success() {
+            debug!(id = lease.id, %status, "[Codex] Usage endpoint rejected the request");
+            return Err(PolluxError::UpstreamStatus(status));
+        }
+        Ok(resp.json().await?)
+    }
+
     #[allow(clippy::too_many_lines)]
     pub(crate) async fn call_codex(
         &self,
@@ -79,15 +123,12 @@ impl CodexClient {
         body: &CodexRequestBody,
         inbound_headers: &OpenaiRequestHeaders,
     ) -> Result<reqwest::Response, CodexError> {
-        let client = if ctx.stream {
-            &self.stream_client
-        } else {
-            &self.client
-        };
+        let clients = &self.clients;
         let endpoints = &self.endpoints;
         let trace_header = &self.trace_header;
+        let error_clusters = &self.error_clusters;
         let model = &ctx.model;
-        let model_mask = ctx.model_mask;
+        let model_mask = &ctx.model_mask;
         let stream = ctx.stream;
         let request_body = Bytes::from(serde_json::to_vec(body)?);
 
@@ -96,7 +137,7 @@ impl CodexClient {
             async move {
                 let start = Instant::now();
                 let lease = handle
-                    .get_credential(model_mask, ctx.route_key)
+                    .get_credential(model_mask.clone(), ctx.route_key, ctx.pool.clone())
                     .await?
                     .ok_or(CodexError::NoAvailableCredential)?;
 
@@ -136,16 +177,32 @@ impl CodexClient {
                     }
                 }
 
-                let resp = post_json_bytes_with_retry(
+                let sent = Instant::now();
+                let mut resp = post_json_bytes_with_retry(
                     "Codex",
-                    client,
+                    &clients.select(lease.id, lease.proxy_url.as_ref(), stream),
                     endpoints.select(stream),
                     Some(upstream_headers),
                     request_body,
                 )
-                .await?;
+                .await
+                .inspect_err(|e| {
+                    if e.status().is_some_and(|s| s.is_server_error()) {
+                        handle.report_outcome(lease.id, model_mask.clone(), false);
+                    }
+                })?;
+
+                if let Some(limits) = CodexRateLimits::from_headers(resp.headers()) {
+                    handle.report_quota(
+                        lease.id,
+                        limits.remaining_percent(),
+                        limits.exhausted_for(),
+                    );
+                }
 
                 if resp.status().is_success() {
+                    handle.report_success(lease.id, model_mask.clone(), sent.elapsed());
+                    lease.attach(&mut resp);
                     return Ok(resp);
                 }
 
@@ -154,25 +211,30 @@ impl CodexClient {
                     resp,
                     |json: CodexErrorBody| CodexError::UpstreamMappedError { status, body: json },
                     |status, body| CodexError::UpstreamFallbackError { status, body },
+                    |status, body| {
+                        error_clusters.record(ProviderKind::Codex.label(), model, status, body);
+                    },
                 )
                 .await;
 
                 match &action {
                     ActionForError::RateLimit(duration) => {
-                        handle.report_rate_limit(lease.id, model_mask, *duration);
+                        handle.report_rate_limit(lease.id, model_mask.clone(), *duration);
                         // Optionally, could add a log here about when to retry
                     }
                     ActionForError::Ban => {
                         handle.report_banned(lease.id);
                     }
                     ActionForError::ModelUnsupported => {
-                        handle.report_model_unsupported(lease.id, model_mask);
+                        handle.report_model_unsupported(lease.id, model_mask.clone());
                     }
                     ActionForError::Invalid => {
                         handle.report_invalid(lease.id);
                     }
                     ActionForError::None => {
-                        // Do nothing
+                        if status.is_server_error() {
+                            handle.report_outcome(lease.id, model_mask.clone(), false);
+                        }
                     }
                 }
 
@@ -219,18 +281,31 @@ impl CodexClient {
             }
         };
 
-        op.retry(&self.retry_policy)
+        let result = op
+            .retry(&self.retry_policy)
             .when(|err: &CodexError| err.is_retryable())
             .notify(|err, dur: Duration| {
                 tracing::warn!("Codex retrying after error {} in {:?}", err, dur);
+                crate::server::audit_log::note_retry();
             })
-            .await
+            .await;
+
+        match result {
+            Err(err) if err.pool_gave_up() => {
+                let after = handle
+                    .retry_after(ctx.model_mask.clone(), ctx.pool.clone())
+                    .await;
+                Err(err.with_retry_after(after))
+            }
+            other => other,
+        }
     }
 
     /// Transparent passthrough for the `/v1/responses/compact` endpoint.
     ///
     /// The request body is forwarded as-is to the upstream compact endpoint and
     /// the response is returned without interpretation.
+    #[allow(clippy::too_many_lines)]
     pub(crate) async fn call_codex_compact(
         &self,
         handle: &CodexActorHandle,
@@ -238,11 +313,12 @@ impl CodexClient {
         body: &serde_json::Value,
         inbound_headers: &OpenaiRequestHeaders,
     ) -> Result<reqwest::Response, CodexError> {
-        let client = &self.client;
+        let clients = &self.clients;
         let compact_url = &self.compact_url;
         let trace_header = &self.trace_header;
+        let error_clusters = &self.error_clusters;
         let model = &ctx.model;
-        let model_mask = ctx.model_mask;
+        let model_mask = &ctx.model_mask;
         let request_body = Bytes::from(serde_json::to_vec(body)?);
 
         let op = move || {
@@ -250,7 +326,7 @@ impl CodexClient {
             async move {
                 let start = Instant::now();
                 let lease = handle
-                    .get_credential(model_mask, ctx.route_key)
+                    .get_credential(model_mask.clone(), ctx.route_key, ctx.pool.clone())
                     .await?
                     .ok_or(CodexError::NoAvailableCredential)?;
 
@@ -276,16 +352,32 @@ impl CodexClient {
                     }
                 }
 
-                let resp = post_json_bytes_with_retry(
+                let sent = Instant::now();
+                let mut resp = post_json_bytes_with_retry(
                     "Codex",
-                    client,
+                    &clients.select(lease.id, lease.proxy_url.as_ref(), false),
                     compact_url,
                     Some(upstream_headers),
                     request_body,
                 )
-                .await?;
+                .await
+                .inspect_err(|e| {
+                    if e.status().is_some_and(|s| s.is_server_error()) {
+                        handle.report_outcome(lease.id, model_mask.clone(), false);
+                    }
+                })?;
+
+                if let Some(limits) = CodexRateLimits::from_headers(resp.headers()) {
+                    handle.report_quota(
+                        lease.id,
+                        limits.remaining_percent(),
+                        limits.exhausted_for(),
+                    );
+                }
 
                 if resp.status().is_success() {
+                    handle.report_success(lease.id, model_mask.clone(), sent.elapsed());
+                    lease.attach(&mut resp);
                     return Ok(resp);
                 }
 
@@ -294,23 +386,30 @@ impl CodexClient {
                     resp,
                     |json: CodexErrorBody| CodexError::UpstreamMappedError { status, body: json },
                     |status, body| CodexError::UpstreamFallbackError { status, body },
+                    |status, body| {
+                        error_clusters.record(ProviderKind::Codex.label(), model, status, body);
+                    },
                 )
                 .await;
 
                 match &action {
                     ActionForError::RateLimit(duration) => {
-                        handle.report_rate_limit(lease.id, model_mask, *duration);
+                        handle.report_rate_limit(lease.id, model_mask.clone(), *duration);
                     }
                     ActionForError::Ban => {
                         handle.report_banned(lease.id);
                     }
                     ActionForError::ModelUnsupported => {
-                        handle.report_model_unsupported(lease.id, model_mask);
+                        handle.report_model_unsupported(lease.id, model_mask.clone());
                     }
                     ActionForError::Invalid => {
                         handle.report_invalid(lease.id);
                     }
-                    ActionForError::None => {}
+                    ActionForError::None => {
+                        if status.is_server_error() {
+                            handle.report_outcome(lease.id, model_mask.clone(), false);
+                        }
+                    }
                 }
 
                 tracing::warn!(
@@ -325,11 +424,23 @@ impl CodexClient {
             }
         };
 
-        op.retry(&self.retry_policy)
+        let result = op
+            .retry(&self.retry_policy)
             .when(|err: &CodexError| err.is_retryable())
             .notify(|err, dur: Duration| {
                 tracing::warn!("Codex compact retrying after error {} in {:?}", err, dur);
+                crate::server::audit_log::note_retry();
             })
-            .await
+            .await;
+
+        match result {
+            Err(err) if err.pool_gave_up() => {
+                let after = handle
+                    .retry_after(ctx.model_mask.clone(), ctx.pool.clone())
+                    .await;
+                Err(err.with_retry_after(after))
+            }
+            other => other,
+        }
     }
 }
@@ -1,3 +1,4 @@
+use crate::config::CONFIG;
 use crate::error::OauthError;
 use crate::oauth_utils::{OauthTokenResponse, build_oauth2_client};
 use crate::providers::codex::DEFAULT_ORIGINATOR;
@@ -7,7 +8,12 @@ use oauth2::{
     PkceCodeChallenge, PkceCodeVerifier, RedirectUrl, RefreshToken, Scope, StandardRevocableToken,
     basic::{BasicErrorResponse, BasicRevocationErrorResponse, BasicTokenIntrospectionResponse},
 };
+use reqwest::StatusCode;
+use serde::Deserialize;
+use serde_json::{Value, json};
+use std::borrow::Cow;
 use std::sync::LazyLock;
+use std::time::Duration;
 use tracing::info;
 
 /// Stateless `OpenAI` OAuth endpoints for the Codex CLI flow.
@@ -16,11 +22,49 @@ pub(crate) struct CodexOauthEndpoints;
 /// Fixed Codex CLI OAuth client id (public client, no secret).
 const CODEX_CLIENT_ID: &str = "app_EMoamEEZ73f0CkXaXp7hrann";
 
-/// Fixed `OpenAI` OAuth endpoints (not configurable).
-///
-/// This matches the Codex CLI flow and keeps the auth/token server stable.
+/// Fixed `OpenAI` OAuth consent page, matching the Codex CLI flow. The token
+/// endpoint comes from `providers.codex.oauth_token_url`.
 const OPENAI_AUTH_URL: &str = "https://auth.openai.com/oauth/authorize";
-const OPENAI_TOKEN_URL: &str = "https://auth.openai.com/oauth/token";
+
+/// Device authorization endpoints used by `codex login --device-auth`.
+const DEVICE_USER_CODE_URL: &str = "https://auth.openai.com/api/accounts/deviceauth/usercode";
+const DEVICE_TOKEN_URL: &str = "https://auth.openai.com/api/accounts/deviceauth/token";
+/// Page where the user enters the code shown to them.
+pub(crate) const DEVICE_VERIFICATION_URL: &str = "https://auth.openai.com/codex/device";
+
+static DEVICE_CALLBACK_URL: LazyLock<RedirectUrl> = LazyLock::new(|| {
+    RedirectUrl::new("https://auth.openai.com/deviceauth/callback".to_string())
+        .expect("valid device authorization callback URL")
+});
+
+/// A pending device authorization, as returned by the user-code endpoint.
+#[derive(Debug, Clone, Deserialize)]
+pub(crate) struct DeviceUserCode {
+    pub device_auth_id: String,
+    #[serde(alias = "usercode")]
+    pub user_code: String,
+    /// Seconds between polls; sent as a string by the server.
+    #[serde(default)]
+    interval: Option<Value>,
+}
+
+impl DeviceUserCode {
+    pub(crate) fn interval(&self) -> Duration {
+        let secs = match &self.interval {
+            Some(Value::Number(n)) => n.as_u64(),
+            Some(Value::String(s)) => s.trim().parse().ok(),
+            _ => None,
+        };
+        Duration::from_secs(secs.unwrap_or(5).max(1))
+    }
+}
+
+/// Authorization code released once the user has approved the device.
+#[derive(Debug, Deserialize)]
+pub(crate) struct DeviceAuthorization {
+    authorization_code: String,
+    code_verifier: String,
+}
 
 static OAUTH_CALLBACK_URL: LazyLock<RedirectUrl> = LazyLock::new(|| {
     // NOTE: This callback must match the OAuth app's pre-registered redirect URL for
@@ -45,7 +89,7 @@ pub(crate) static CODEX_OAUTH_CLIENT: LazyLock<CodexOauth2Client> = LazyLock::ne
         CODEX_CLIENT_ID,
         None,
         OPENAI_AUTH_URL,
-        OPENAI_TOKEN_URL,
+        CONFIG.providers.codex.oauth_token_url.as_str(),
         OAUTH_CALLBACK_URL.clone(),
     )
     .expect("valid Codex OAuth2 client with redirect")
@@ -85,7 +129,70 @@ impl CodexOauthEndpoints {
         Ok(token_result)
     }
 
-    #[allow(dead_code)]
+    /// Start a device authorization; the user then enters `user_code` at
+    /// [`DEVICE_VERIFICATION_URL`].
+    pub(crate) async fn request_device_code(
+        http_client: &reqwest::Client,
+    ) -> Result<DeviceUserCode, OauthError> {
+        let resp = http_client
+            .post(DEVICE_USER_CODE_URL)
+            .json(&json!({ "client_id": CODEX_CLIENT_ID }))
+            .send()
+            .await?;
+        if !resp.status().is_success() {
+            return Err(OauthError::UpstreamStatus(resp.status()));
+        }
+        let body = resp.text().await?;
+        serde_json::from_str(&body).map_err(|e| OauthError::Parse {
+            message: e.to_string(),
+            body,
+        })
+    }
+
+    /// One poll of a device authorization; `None` while the user has not
+    /// approved it yet.
+    pub(crate) async fn poll_device_code(
+        device: &DeviceUserCode,
+        http_client: &reqwest::Client,
+    ) -> Result<Option<DeviceAuthorization>, OauthError> {
+        let resp = http_client
+            .post(DEVICE_TOKEN_URL)
+            .json(&json!({
+                "device_auth_id": device.device_auth_id,
+                "user_code": device.user_code,
+            }))
+            .send()
+            .await?;
+        match resp.status() {
+            StatusCode::FORBIDDEN | StatusCode::NOT_FOUND => Ok(None),
+            status if status.is_success() => {
+                let body = resp.text().await?;
+                serde_json::from_str(&body)
+                    .map(Some)
+                    .map_err(|e| OauthError::Parse {
+                        message: e.to_string(),
+                        body,
+                    })
+            }
+            status => Err(OauthError::UpstreamStatus(status)),
+        }
+    }
+
+    /// Redeem an approved device authorization for tokens.
+    pub(crate) async fn exchange_device_authorization(
+        authorization: DeviceAuthorization,
+        http_client: reqwest::Client,
+    ) -> Result<OauthTokenResponse, OauthError> {
+        let token_result: OauthTokenResponse = Self::client()
+            .exchange_code(AuthorizationCode::new(authorization.authorization_code))
+            .set_pkce_verifier(PkceCodeVerifier::new(authorization.code_verifier))
+            .set_redirect_uri(Cow::Borrowed(&DEVICE_CALLBACK_URL))
+            .request_async(&http_client)
+            .await?;
+        info!("Codex OAuth2 device authorization completed successfully");
+        Ok(token_result)
+    }
+
     pub(crate) async fn refresh_access_token(
         refresh_token: &str,
         http_client: reqwest::Client,
@@ -0,0 +1,168 @@
+//! Codex probes for `pollux doctor`.
+
+use super::CODEX_USER_AGENT;
+use super::client::oauth::OAUTH_RETRY_POLICY;
+use super::manager::{Codex, CredentialOps};
+use super::resource::CodexResource;
+use crate::config::CodexResolvedConfig;
+use crate::db::DbActorHandle;
+use crate::providers::doctor::{
+    DoctorArgs, DoctorReport, PROBE_PROMPT, SseProbe, db_id, expect_json, http_client, probe_sse,
+    token_validity,
+};
+use crate::providers::manifest::CodexLease;
+use crate::providers::traits::oauth_worker::refresh_credential;
+use crate::providers::traits::provider::{CredentialStore, ProviderResource};
+use crate::providers::traits::scheduler::Schedulable;
+use crate::server::routes::codex::headers::{CodexRequestHeaders, OpenaiRequestHeaders};
+use pollux_schema::{CodexRequestBody, OpenaiRequestBody};
+use serde_json::{Value, json};
+use std::collections::BTreeMap;
+use std::time::Instant;
+use url::Url;
+
+pub(crate) async fn diagnose(
+    report: &mut DoctorReport,
+    cfg: &CodexResolvedConfig,
+    db: &DbActorHandle,
+    args: &DoctorArgs,
+) {
+    let id = args.id;
+    let Some(mut cred) = report
+        .step("load", async {
+            let row = db
+                .get_codex_by_id(db_id(id)?)
+                .await
+                .map_err(|e| e.to_string())?;
+            let cred = CodexResource::from(row);
+            let detail = format!(
+                "account {} ({}), {}",
+                cred.account_id(),
+                cred.chatgpt_plan_type().unwrap_or("unknown plan"),
+                token_validity(cred.expiry())
+            );
+            Ok((cred, detail))
+        })
+        .await
+    else {
+        return;
+    };
+
+    if args.refresh {
+        let client = http_client(cfg.proxy.as_ref(), None);
+        report
+            .step("refresh", async {
+                refresh_credential(&Codex, client, *OAUTH_RETRY_POLICY, &mut cred, None)
+                    .await
+                    .map_err(|e| e.to_string())?;
+                CredentialOps::new(db.clone())
+                    .save_refreshed(id, &cred)
+                    .await
+                    .map_err(|e| format!("refreshed but not saved: {e}"))?;
+                Ok(((), format!("{}, saved", token_validity(cred.expiry()))))
+            })
+            .await;
+    } else {
+        report.skip("refresh");
+    }
+
+    let lease = cred.make_lease(id);
+    let client = http_client(cfg.proxy.as_ref(), Some(CODEX_USER_AGENT));
+    let base = &cfg.custom_api_url;
+    let model = args
+        .model
+        .clone()
+        .or_else(|| cfg.model_list.first().cloned())
+        .unwrap_or_default();
+
+    report
+        .step("models", async {
+            let mut url = endpoint(base, "models");
+            url.query_pairs_mut()
+                .append_pair("client_version", client_version());
+            let resp = client
+                .get(url)
+                .headers(headers(&lease))
+                .send()
+                .await
+                .map_err(|e| e.to_string())?;
+            let body = expect_json(resp).await?;
+            let slugs: Vec<&str> = body
+                .get("models")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+                .filter_map(|m| m.get("slug").and_then(Value::as_str))
+                .collect();
+            Ok(((), format!("{} models: {}", slugs.len(), slugs.join(", "))))
+        })
+        .await;
+
+    report
+        .step("generate", async {
+            let probe = responses(&client, base, &lease, &model).await?;
+            let response = probe
+                .last
+                .as_ref()
+                .filter(|e| e.get("type").and_then(Value::as_str) == Some("response.completed"))
+                .and_then(|e| e.get("response"))
+                .ok_or("stream ended before response.completed")?;
+            let tokens = response
+                .pointer("/usage/output_tokens")
+                .and_then(Value::as_u64)
+                .unwrap_or(0);
+            Ok(((), format!("{model}: completed, {tokens} output tokens")))
+        })
+        .await;
+
+    report
+        .step("stream", async {
+            let probe = responses(&client, base, &lease, &model).await?;
+            Ok(((), probe.summary()))
+        })
+        .await;
+}
+
+fn endpoint(base: &Url, path: &str) -> Url {
+    base.join(&format!("./backend-api/codex/{path}"))
+        .expect("valid codex endpoint path")
+}
+
+/// Version segment of [`CODEX_USER_AGENT`], sent as `client_version`.
+fn client_version() -> &'static str {
+    CODEX_USER_AGENT
+        .split_once('/')
+        .and_then(|(_, rest)| rest.split_whitespace().next())
+        .unwrap_or_default()
+}
+
+fn headers(lease: &CodexLease) -> reqwest::header::HeaderMap {
+    let inbound = OpenaiRequestHeaders {
+        session_id: uuid::Uuid::new_v4().to_string(),
+        turn_metadata: None,
+        extra: BTreeMap::new(),
+    };
+    CodexRequestHeaders::build(&inbound, lease).into_header_map()
+}
+
+/// Codex only serves streamed responses, so both generation probes read SSE.
+async fn responses(
+    client: &reqwest::Client,
+    base: &Url,
+    lease: &CodexLease,
+    model: &str,
+) -> Result<SseProbe, String> {
+    let body: OpenaiRequestBody =
+        serde_json::from_value(json!({"model": model, "input": PROBE_PROMPT}))
+            .map_err(|e| e.to_string())?;
+    let body = CodexRequestBody::from(body);
+    let sent = Instant::now();
+    let resp = client
+        .post(endpoint(base, "responses"))
+        .headers(headers(lease))
+        .json(&body)
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+    probe_sse(resp, sent).await
+}
@@ -1,7 +1,171 @@
-mod actor;
 mod ops;
-mod router;
 
-pub use crate::providers::traits::scheduler::CredentialId;
-pub use actor::CodexActorHandle;
-pub(in crate::providers) use actor::spawn;
+use super::client::oauth::{OAUTH_RETRY_POLICY, endpoints::CodexOauthEndpoints};
+use super::resource::CodexResource;
+use super::{SUPPORTED_MODEL_MASK, SUPPORTED_MODEL_NAMES};
+use crate::config::CodexResolvedConfig;
+use crate::error::OauthError;
+use crate::model_catalog::ModelCapabilities;
+use crate::oauth_utils::OauthTokenResponse;
+use crate::providers::manifest::ProviderKind;
+use crate::providers::traits::actor::ProviderActorHandle;
+use crate::providers::traits::provider::{OauthWorkerSettings, PoolSettings, Provider};
+use backon::ExponentialBuilder;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+
+pub(crate) use ops::CredentialOps;
+
+/// `ChatGPT` (Codex) accounts on the shared pool actor.
+pub struct Codex;
+
+impl Provider for Codex {
+    const NAME: &'static str = "Codex";
+    const KIND: ProviderKind = ProviderKind::Codex;
+
+    type Resource = CodexResource;
+    type Store = CredentialOps;
+
+    fn supported_models(&self) -> (ModelCapabilities, Vec<String>) {
+        (SUPPORTED_MODEL_MASK.clone(), SUPPORTED_MODEL_NAMES.clone())
+    }
+
+    fn oauth_retry_policy(&self) -> ExponentialBuilder {
+        *OAUTH_RETRY_POLICY
+    }
+
+    fn refresh_access_token(
+        &self,
+        refresh_token: &str,
+        http_client: reqwest::Client,
+    ) -> impl Future<Output = Result<OauthTokenResponse, OauthError>> + Send {
+        CodexOauthEndpoints::refresh_access_token(refresh_token, http_client)
+    }
+}
+
+pub type CodexActorHandle = ProviderActorHandle<Codex>;
+
+pub(in crate::providers) async fn spawn(
+    db: crate::db::DbActorHandle,
+    cfg: Arc<CodexResolvedConfig>,
+) -> CodexActorHandle {
+    let settings = PoolSettings {
+        endpoint: cfg.custom_api_url.to_string(),
+        retry_max_times: cfg.retry_max_times,
+        auto_disable: cfg.auto_disable,
+        health: cfg.health,
+        min_token_validity: Duration::from_secs(cfg.min_token_validity_secs),
+        stale_grace: Duration::from_secs(cfg.stale_grace_secs),
+        capability_restore: Duration::from_secs(cfg.capability_restore_secs),
+        max_concurrent_per_credential: cfg.max_concurrent_per_credential,
+        tier_weights: cfg.tier_weights.clone(),
+        scheduling_policy: cfg.scheduling_policy,
+        priority_reserve: cfg.priority_reserve,
+        lease_wait: Duration::from_millis(cfg.lease_wait_ms),
+        oauth: OauthWorkerSettings {
+            proxy: cfg.proxy.clone(),
+            user_agent: None,
+            enable_multiplexing: cfg.enable_multiplexing,
+            http_client: cfg.http_client,
+            oauth_tps: cfg.oauth_tps,
+        },
+    };
+    crate::providers::traits::actor::spawn(db, Codex, settings).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::RefreshTokenSeed;
+    use crate::providers::traits::oauth_worker::{CredentialJob, CredentialJobKind};
+    use crate::providers::traits::provider::ProviderResource;
+    use base64::Engine as _;
+    use governor::{Quota, RateLimiter};
+    use serde_json::json;
+    use std::num::NonZeroU32;
+
+    fn make_test_jwt(payload: &serde_json::Value) -> String {
+        let header = base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(br#"{"alg":"none"}"#);
+        let payload_bytes = serde_json::to_vec(payload).expect("serialize payload");
+        let payload_b64 = base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(payload_bytes);
+        format!("{header}.{payload_b64}.sig")
+    }
+
+    #[test]
+    fn refresh_job_keeps_refresh_id() {
+        let cred = CodexResource::from_payload(json!({
+            "account_id": "acct-1",
+            "sub": "sub-1",
+            "refresh_token": "rt-1",
+            "access_token": "at-1",
+            "expiry": "2026-01-01T00:00:00Z",
+        }))
+        .expect("valid credential payload");
+
+        let job = CredentialJob::refresh(42, cred);
+
+        assert_eq!(job.kind.credential_id(), Some(42));
+    }
+
+    #[test]
+    fn untrusted_ingest_job_sets_refresh_token() {
+        let seed = RefreshTokenSeed::new("seed-rt").expect("valid seed");
+
+        let job = CredentialJob::<CodexResource>::ingest_untrusted_seed(&seed).expect("ingest job");
+
+        assert!(matches!(job.kind, CredentialJobKind::IngestUntrusted));
+        assert_eq!(job.cred.refresh_token(), "seed-rt");
+        assert_eq!(job.kind.credential_id(), None);
+    }
+
+    #[test]
+    fn trusted_ingest_job_skips_network_work_when_identity_is_present() {
+        let id_token = make_test_jwt(&json!({
+            "sub": "auth0|trusted-sub",
+            "email": "trusted@example.com",
+            "https://api.openai.com/auth": {
+                "chatgpt_account_id": "acct-trusted",
+                "chatgpt_plan_type": "plus",
+            }
+        }));
+
+        let token_response: OauthTokenResponse = serde_json::from_value(json!({
+            "access_token": "trusted-at",
+            "token_type": "bearer",
+            "expires_in": 3600,
+            "refresh_token": "trusted-rt",
+            "id_token": id_token,
+        }))
+        .expect("token response deserializes");
+
+        let job = CredentialJob::<CodexResource>::ingest_trusted_oauth(&token_response)
+            .expect("trusted oauth ingest job");
+
+        let limiter = RateLimiter::direct(Quota::per_second(NonZeroU32::MIN));
+        let runtime = tokio::runtime::Runtime::new().expect("tokio runtime");
+        let completed = runtime
+            .block_on(job.execute(&Codex, reqwest::Client::new(), &limiter))
+            .expect("trusted oauth ingest succeeds without refresh");
+
+        assert!(matches!(completed.kind, CredentialJobKind::IngestTrusted));
+        assert_eq!(completed.cred.account_id(), "acct-trusted");
+        assert_eq!(completed.cred.sub(), "auth0|trusted-sub");
+        assert_eq!(completed.cred.access_token(), "trusted-at");
+        assert_eq!(completed.cred.refresh_token(), "trusted-rt");
+        assert_eq!(completed.cred.email(), Some("trusted@example.com"));
+        assert_eq!(completed.cred.chatgpt_plan_type(), Some("plus"));
+    }
+
+    #[test]
+    fn identity_needs_sub_and_account_id() {
+        let cred = CodexResource::from_payload(json!({
+            "account_id": "acct-1",
+            "refresh_token": "rt-1",
+        }))
+        .expect("valid credential payload");
+
+        assert!(!cred.has_identity());
+        assert_eq!(cred.seed_account(), "acct-1");
+    }
+}
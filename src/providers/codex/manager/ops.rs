@@ -1,7 +1,13 @@
-use crate::db::{CodexCreate, CodexPatch, DbActorHandle, ProviderCreate, ProviderPatch};
+use crate::db::{
+    CodexCreate, CodexPatch, DbActorHandle, ProviderCreate, ProviderDelete, ProviderIdentity,
+    ProviderPatch,
+};
 use crate::error::PolluxError;
 use crate::providers::codex::resource::CodexResource;
-use crate::providers::traits::scheduler::CredentialId;
+use crate::providers::credential_view::CredentialView;
+use crate::providers::seed::StoredSeed;
+use crate::providers::traits::provider::{CredentialStore, ProviderResource};
+use crate::providers::traits::scheduler::{CredentialId, Schedulable};
 
 #[derive(Clone)]
 pub struct CredentialOps {
@@ -9,11 +15,29 @@ pub struct CredentialOps {
 }
 
 impl CredentialOps {
-    pub fn new(db: DbActorHandle) -> Self {
+    async fn upsert(&self, cred: CodexResource) -> Result<CredentialId, PolluxError> {
+        let create: CodexCreate = cred.into();
+        let id = self.db.create(ProviderCreate::Codex(create)).await?;
+
+        u64::try_from(id)
+            .map_err(|_| PolluxError::UnexpectedError(format!("Invalid credential id {id}")))
+    }
+
+    async fn update_by_id(&self, id: CredentialId, patch: CodexPatch) -> Result<(), PolluxError> {
+        let _ = i64::try_from(id)
+            .map_err(|_| PolluxError::UnexpectedError(format!("Invalid credential id {id}")))?;
+
+        self.db.patch(ProviderPatch::Codex { id, patch }).await?;
+        Ok(())
+    }
+}
+
+impl CredentialStore<CodexResource> for CredentialOps {
+    fn new(db: DbActorHandle) -> Self {
         Self { db }
     }
 
-    pub async fn load_active(&self) -> Result<Vec<(CredentialId, CodexResource)>, PolluxError> {
+    async fn load_active(&self) -> Result<Vec<(CredentialId, CodexResource)>, PolluxError> {
         let rows = self.db.list_active_codex().await?;
         let mut result = Vec::with_capacity(rows.len());
         for row in rows {
@@ -25,27 +49,69 @@ impl CredentialOps {
         Ok(result)
     }
 
-    pub async fn upsert(&self, cred: CodexResource) -> Result<CredentialId, PolluxError> {
-        let create: CodexCreate = cred.into();
-        let id = self.db.create(ProviderCreate::Codex(create)).await?;
+    /// Store a freshly onboarded credential. An account (`sub`, `account_id`)
+    /// that already has a row keeps its id: the row is updated in place and
+    /// re-enabled.
+    async fn store_onboarded(
+        &self,
+        cred: CodexResource,
+    ) -> Result<StoredSeed<CodexResource>, PolluxError> {
+        let identity = ProviderIdentity::Codex {
+            sub: cred.sub().to_string(),
+            account_id: cred.account_id().to_string(),
+        };
+        let existing = self.db.find_by_identity(identity).await?;
+        let Some(id) = existing
+            .map(|id| {
+                u64::try_from(id).map_err(|_| {
+                    PolluxError::UnexpectedError(format!("Invalid credential id {id}"))
+                })
+            })
+            .transpose()?
+        else {
+            let id = self.upsert(cred.clone()).await?;
+            return Ok(StoredSeed {
+                id,
+                credential: cred,
+                existing: false,
+            });
+        };
 
-        u64::try_from(id)
-            .map_err(|_| PolluxError::UnexpectedError(format!("Invalid credential id {id}")))
+        let patch = CodexPatch {
+            email: cred.email().map(ToString::to_string),
+            refresh_token: Some(cred.refresh_token().to_string()),
+            access_token: Some(cred.access_token().to_string()),
+            expiry: Some(cred.expiry()),
+            chatgpt_plan_type: cred.chatgpt_plan_type().map(ToString::to_string),
+            status: Some(true),
+            labels: (!cred.labels().is_empty()).then(|| cred.labels().to_vec()),
+            ..Default::default()
+        };
+        self.update_by_id(id, patch).await?;
+        Ok(StoredSeed {
+            id,
+            credential: self.get_by_id(id).await?,
+            existing: true,
+        })
     }
 
-    pub async fn update_by_id(
+    async fn save_refreshed(
         &self,
         id: CredentialId,
-        patch: CodexPatch,
+        cred: &CodexResource,
     ) -> Result<(), PolluxError> {
-        let _ = i64::try_from(id)
-            .map_err(|_| PolluxError::UnexpectedError(format!("Invalid credential id {id}")))?;
-
-        self.db.patch(ProviderPatch::Codex { id, patch }).await?;
-        Ok(())
+        let patch = CodexPatch {
+            email: cred.email().map(ToString::to_string),
+            refresh_token: Some(cred.refresh_token().to_string()),
+            access_token: Some(cred.access_token().to_string()),
+            expiry: Some(cred.expiry()),
+            chatgpt_plan_type: cred.chatgpt_plan_type().map(ToString::to_string),
+            ..Default::default()
+        };
+        self.update_by_id(id, patch).await
     }
 
-    pub async fn set_status(&self, id: CredentialId, status: bool) -> Result<(), PolluxError> {
+    async fn set_status(&self, id: CredentialId, status: bool) -> Result<(), PolluxError> {
         let _ = i64::try_from(id)
             .map_err(|_| PolluxError::UnexpectedError(format!("Invalid credential id {id}")))?;
         let patch = CodexPatch {
@@ -54,4 +120,28 @@ impl CredentialOps {
         };
         self.db.patch(ProviderPatch::Codex { id, patch }).await
     }
+
+    /// All stored rows (any status), for admin listing.
+    async fn load_all_views(&self) -> Result<Vec<CredentialView>, PolluxError> {
+        let rows = self.db.list_codex().await?;
+        Ok(rows.into_iter().map(CredentialView::from).collect())
+    }
+
+    async fn get_by_id(&self, id: CredentialId) -> Result<CodexResource, PolluxError> {
+        let db_id = i64::try_from(id)
+            .map_err(|_| PolluxError::UnexpectedError(format!("Invalid credential id {id}")))?;
+        match self.db.get_codex_by_id(db_id).await {
+            Ok(row) => Ok(row.into()),
+            Err(PolluxError::DatabaseError(sqlx::Error::RowNotFound)) => {
+                Err(PolluxError::NotFound(format!("codex record id={id}")))
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    async fn delete(&self, id: CredentialId) -> Result<(), PolluxError> {
+        let db_id = i64::try_from(id)
+            .map_err(|_| PolluxError::UnexpectedError(format!("Invalid credential id {id}")))?;
+        self.db.delete(ProviderDelete::Codex(db_id)).await
+    }
 }
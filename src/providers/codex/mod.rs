@@ -1,20 +1,20 @@
+pub(crate) mod chat_compat;
 pub mod client;
+pub(crate) mod doctor;
 mod errors;
 mod identity;
 mod manager;
 mod model_mask;
 pub(crate) mod oauth;
+mod rate_limits;
+pub(crate) mod reasoning;
 mod resource;
-mod workers;
-
-use workers::{
-    CodexOauthWorkerHandle, CredentialJob, CredentialJobKind, CredentialProcessError,
-    CredentialProcessResult,
-};
+pub(crate) mod usage;
 
 pub use manager::CodexActorHandle;
 pub(in crate::providers) use manager::spawn;
 pub(crate) use model_mask::{SUPPORTED_MODEL_MASK, SUPPORTED_MODEL_NAMES, model_mask};
+pub(crate) use rate_limits::CodexRateLimits;
 
 /// Hard-coded Codex-style User-Agent string kept as a fallback.
 ///
@@ -1,5 +1,5 @@
 use crate::config::CONFIG;
-use crate::model_catalog;
+use crate::model_catalog::{self, MODEL_REGISTRY, ModelCapabilities};
 use std::collections::HashSet;
 use std::sync::LazyLock;
 
@@ -13,21 +13,14 @@ pub(crate) static SUPPORTED_MODEL_NAMES: LazyLock<Vec<String>> = LazyLock::new(|
         .collect()
 });
 
-pub(crate) static SUPPORTED_MODEL_MASK: LazyLock<u64> = LazyLock::new(|| {
-    let mut mask = 0u64;
-    for name in SUPPORTED_MODEL_NAMES.iter() {
-        if let Some(bit) = model_catalog::mask(name) {
-            mask |= bit;
-        }
-    }
-    mask
+pub(crate) static SUPPORTED_MODEL_MASK: LazyLock<ModelCapabilities> = LazyLock::new(|| {
+    SUPPORTED_MODEL_NAMES
+        .iter()
+        .filter_map(|name| MODEL_REGISTRY.get_index(name))
+        .collect()
 });
 
-pub(crate) fn model_mask(name: &str) -> Option<u64> {
+pub(crate) fn model_mask(name: &str) -> Option<ModelCapabilities> {
     let bit = model_catalog::mask(name)?;
-    if (*SUPPORTED_MODEL_MASK & bit) != 0 {
-        Some(bit)
-    } else {
-        None
-    }
+    SUPPORTED_MODEL_MASK.intersects(&bit).then_some(bit)
 }
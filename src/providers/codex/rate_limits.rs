@@ -0,0 +1,154 @@
+//! `x-codex-*` usage-window headers sent with every Codex response.
+//!
+//! A `ChatGPT` account has a short primary window (about five hours) and a
+//! weekly secondary one. Each is reported as
+//! `x-codex-{primary,secondary}-used-percent`, `...-window-minutes` and either
+//! `...-reset-after-seconds` or `...-reset-at` (unix seconds).
+
+use chrono::Utc;
+use reqwest::header::HeaderMap;
+use std::time::Duration;
+
+/// Usage of one window as last reported by upstream.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct RateLimitWindow {
+    pub used_percent: f64,
+    pub window_minutes: Option<u64>,
+    pub resets_in: Option<Duration>,
+}
+
+impl RateLimitWindow {
+    fn from_headers(headers: &HeaderMap, prefix: &str) -> Option<Self> {
+        let used_percent = header_value::<f64>(headers, &format!("{prefix}-used-percent"))?;
+        let resets_in = header_value::<u64>(headers, &format!("{prefix}-reset-after-seconds"))
+            .map(Duration::from_secs)
+            .or_else(|| {
+                let at = header_value::<i64>(headers, &format!("{prefix}-reset-at"))?;
+                let secs = at.saturating_sub(Utc::now().timestamp());
+                Some(Duration::from_secs(secs.max(0).cast_unsigned()))
+            });
+        Some(Self {
+            used_percent: used_percent.clamp(0.0, 100.0),
+            window_minutes: header_value(headers, &format!("{prefix}-window-minutes")),
+            resets_in,
+        })
+    }
+
+    fn is_exhausted(&self) -> bool {
+        self.used_percent >= 100.0
+    }
+}
+
+/// Primary and secondary window usage of the credential that served a response.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub(crate) struct CodexRateLimits {
+    pub primary: Option<RateLimitWindow>,
+    pub secondary: Option<RateLimitWindow>,
+}
+
+impl CodexRateLimits {
+    /// Cooldown used when an exhausted window does not say when it resets.
+    const UNKNOWN_RESET: Duration = Duration::from_mins(10);
+
+    /// `None` when upstream sent no usage headers.
+    pub(crate) fn from_headers(headers: &HeaderMap) -> Option<Self> {
+        let limits = Self {
+            primary: RateLimitWindow::from_headers(headers, "x-codex-primary"),
+            secondary: RateLimitWindow::from_headers(headers, "x-codex-secondary"),
+        };
+        (limits.primary.is_some() || limits.secondary.is_some()).then_some(limits)
+    }
+
+    fn windows(&self) -> impl Iterator<Item = &RateLimitWindow> {
+        self.primary.iter().chain(self.secondary.iter())
+    }
+
+    /// Percent left in the tighter window.
+    pub(crate) fn remaining_percent(&self) -> u8 {
+        let used = self
+            .windows()
+            .map(|w| w.used_percent)
+            .fold(0.0_f64, f64::max);
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let remaining = (100.0 - used).round() as u8;
+        remaining
+    }
+
+    /// How long the credential stays unusable: until the latest reset among
+    /// the exhausted windows. `None` while every window has headroom.
+    pub(crate) fn exhausted_for(&self) -> Option<Duration> {
+        self.windows()
+            .filter(|w| w.is_exhausted())
+            .map(|w| {
+                w.resets_in
+                    .unwrap_or(Self::UNKNOWN_RESET)
+                    .max(Duration::from_secs(1))
+            })
+            .max()
+    }
+}
+
+fn header_value<T: std::str::FromStr>(headers: &HeaderMap, name: &str) -> Option<T> {
+    headers.get(name)?.to_str().ok()?.trim().parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::{HeaderName, HeaderValue};
+
+    fn headers(pairs: &[(&'static str, &str)]) -> HeaderMap {
+        pairs
+            .iter()
+            .map(|(k, v)| {
+                (
+                    HeaderName::from_static(k),
+                    HeaderValue::from_str(v).unwrap(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn parses_both_windows_and_reports_headroom() {
+        let limits = CodexRateLimits::from_headers(&headers(&[
+            ("x-codex-primary-used-percent", "42.5"),
+            ("x-codex-primary-window-minutes", "300"),
+            ("x-codex-primary-reset-after-seconds", "1200"),
+            ("x-codex-secondary-used-percent", "71"),
+            ("x-codex-secondary-window-minutes", "10080"),
+        ]))
+        .expect("usage headers present");
+
+        let primary = limits.primary.unwrap();
+        assert_eq!(primary.window_minutes, Some(300));
+        assert_eq!(primary.resets_in, Some(Duration::from_mins(20)));
+        assert_eq!(limits.remaining_percent(), 29);
+        assert_eq!(limits.exhausted_for(), None);
+    }
+
+    #[test]
+    fn exhausted_window_cools_until_its_reset() {
+        let reset_at = Utc::now().timestamp() + 3_600;
+        let limits = CodexRateLimits::from_headers(&headers(&[
+            ("x-codex-primary-used-percent", "12"),
+            ("x-codex-primary-reset-after-seconds", "60"),
+            ("x-codex-secondary-used-percent", "100"),
+            ("x-codex-secondary-reset-at", &reset_at.to_string()),
+        ]))
+        .unwrap();
+
+        assert_eq!(limits.remaining_percent(), 0);
+        let cooldown = limits.exhausted_for().unwrap();
+        assert!(cooldown > Duration::from_secs(3_500) && cooldown <= Duration::from_hours(1));
+    }
+
+    #[test]
+    fn missing_headers_yield_none() {
+        assert_eq!(CodexRateLimits::from_headers(&HeaderMap::new()), None);
+        let limits =
+            CodexRateLimits::from_headers(&headers(&[("x-codex-primary-used-percent", "100")]))
+                .unwrap();
+        assert_eq!(limits.exhausted_for(), Some(CodexRateLimits::UNKNOWN_RESET));
+    }
+}
@@ -0,0 +1,115 @@
+//! Per-model `reasoning` defaults (`providers.codex.reasoning`).
+//!
+//! Upstream treats effort differently per model and most clients either omit
+//! `reasoning` or send a level the model does not support well, so the
+//! configured defaults, forced values and caps are applied before routing.
+
+use crate::config::CodexReasoningConfig;
+use pollux_schema::openai::Reasoning;
+use std::collections::HashMap;
+use tracing::debug;
+
+const EFFORT_LEVELS: [&str; 6] = ["none", "minimal", "low", "medium", "high", "xhigh"];
+
+fn effort_rank(effort: &str) -> Option<usize> {
+    EFFORT_LEVELS.iter().position(|level| *level == effort)
+}
+
+/// Rewrite `reasoning` for `model` according to `rules`; models without an
+/// entry keep whatever the client sent.
+pub(crate) fn apply_reasoning_defaults(
+    rules: &HashMap<String, CodexReasoningConfig>,
+    model: &str,
+    reasoning: &mut Option<Reasoning>,
+) {
+    let Some(rule) = rules.get(model) else {
+        return;
+    };
+    let before = reasoning
+        .as_ref()
+        .map(|r| (r.effort.clone(), r.summary.clone()));
+
+    let mut effort = reasoning.as_ref().and_then(|r| r.effort.clone());
+    let mut summary = reasoning.as_ref().and_then(|r| r.summary.clone());
+    if let Some(forced) = &rule.force_effort {
+        effort = Some(forced.clone());
+    } else if effort.is_none() {
+        effort.clone_from(&rule.default_effort);
+    }
+    // Unknown levels on either side are left alone rather than guessed at.
+    if let (Some(current), Some(max)) = (&effort, &rule.max_effort)
+        && let (Some(rank), Some(max_rank)) = (effort_rank(current), effort_rank(max))
+        && rank > max_rank
+    {
+        effort = Some(max.clone());
+    }
+    if summary.is_none() {
+        summary.clone_from(&rule.default_summary);
+    }
+
+    if effort.is_none() && summary.is_none() {
+        return;
+    }
+    *reasoning = Some(Reasoning { effort, summary });
+    let after = reasoning
+        .as_ref()
+        .map(|r| (r.effort.clone(), r.summary.clone()));
+    if before != after {
+        debug!(model, ?before, ?after, "[Codex] Reasoning defaults applied");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules() -> HashMap<String, CodexReasoningConfig> {
+        HashMap::from([
+            (
+                "gpt-5.1-codex-max".to_string(),
+                CodexReasoningConfig {
+                    force_effort: Some("high".to_string()),
+                    default_summary: Some("auto".to_string()),
+                    ..Default::default()
+                },
+            ),
+            (
+                "gpt-5-codex".to_string(),
+                CodexReasoningConfig {
+                    default_effort: Some("medium".to_string()),
+                    max_effort: Some("high".to_string()),
+                    ..Default::default()
+                },
+            ),
+        ])
+    }
+
+    fn reasoning(effort: &str, summary: Option<&str>) -> Reasoning {
+        Reasoning {
+            effort: Some(effort.to_string()),
+            summary: summary.map(ToString::to_string),
+        }
+    }
+
+    fn applied(model: &str, mut r: Option<Reasoning>) -> (Option<String>, Option<String>) {
+        apply_reasoning_defaults(&rules(), model, &mut r);
+        r.map_or((None, None), |r| (r.effort, r.summary))
+    }
+
+    #[test]
+    fn defaults_forces_and_caps_effort_per_model() {
+        assert_eq!(
+            applied("gpt-5.1-codex-max", Some(reasoning("low", None))),
+            (Some("high".to_string()), Some("auto".to_string()))
+        );
+        assert_eq!(
+            applied("gpt-5-codex", None),
+            (Some("medium".to_string()), None)
+        );
+        assert_eq!(
+            applied("gpt-5-codex", Some(reasoning("xhigh", Some("detailed")))),
+            (Some("high".to_string()), Some("detailed".to_string()))
+        );
+        assert_eq!(applied("gpt-5", None), (None, None));
+    }
+}
@@ -1,20 +1,22 @@
-use crate::db::{CodexCreate, DbCodexResource};
+use crate::db::{CodexCreate, DbCodexResource, split_labels};
 use crate::error::PolluxError;
 use crate::providers::RefreshTokenSeed;
 use crate::providers::codex::oauth::OauthTokenResponse;
 use crate::providers::manifest::{CodexLease, CodexProfile};
+use crate::providers::traits::provider::ProviderResource;
 use crate::providers::traits::scheduler::{CooldownScope, CredentialId, Schedulable};
 use chrono::{DateTime, Duration, Utc};
 use oauth2::TokenResponse;
 use serde::{Deserialize, Serialize};
 use tracing::debug;
+use url::Url;
 
 /// In-memory credential state for the Codex provider.
 ///
 /// This mirrors `geminicli::resource` so that token refresh / hot reload can
 /// evolve in the same direction.
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub(super) struct CodexResource {
+pub struct CodexResource {
     email: Option<String>,
     account_id: String,
     sub: String,
@@ -22,6 +24,10 @@ pub(super) struct CodexResource {
     access_token: String,
     expiry: DateTime<Utc>,
     chatgpt_plan_type: Option<String>,
+    #[serde(default)]
+    labels: Vec<String>,
+    #[serde(default)]
+    proxy_url: Option<Url>,
 }
 
 impl Default for CodexResource {
@@ -34,6 +40,8 @@ impl Default for CodexResource {
             access_token: String::new(),
             expiry: Utc::now(),
             chatgpt_plan_type: None,
+            labels: Vec::new(),
+            proxy_url: None,
         }
     }
 }
@@ -57,28 +65,42 @@ impl CodexResource {
     }
 
     #[allow(dead_code)]
-    pub fn refresh_token(&self) -> &str {
+    pub fn chatgpt_plan_type(&self) -> Option<&str> {
+        self.chatgpt_plan_type.as_deref()
+    }
+
+    /// Build a resource from any JSON-like payload by applying updates to a default struct.
+    #[cfg(test)]
+    pub fn from_payload(payload: impl Serialize) -> Result<Self, PolluxError> {
+        let mut cred = CodexResource::default();
+        cred.update_credential(payload)?;
+        Ok(cred)
+    }
+}
+
+impl ProviderResource for CodexResource {
+    fn refresh_token(&self) -> &str {
         &self.refresh_token
     }
 
-    #[allow(dead_code)]
-    pub fn access_token(&self) -> &str {
+    fn access_token(&self) -> &str {
         &self.access_token
     }
 
-    #[allow(dead_code)]
-    pub fn email(&self) -> Option<&str> {
-        self.email.as_deref()
+    fn expiry(&self) -> DateTime<Utc> {
+        self.expiry
     }
 
-    #[allow(dead_code)]
-    pub fn chatgpt_plan_type(&self) -> Option<&str> {
-        self.chatgpt_plan_type.as_deref()
+    fn proxy_url(&self) -> Option<&Url> {
+        self.proxy_url.as_ref()
     }
 
-    #[allow(dead_code)]
-    pub fn expiry(&self) -> DateTime<Utc> {
-        self.expiry
+    fn set_labels(&mut self, labels: Vec<String>) {
+        self.labels = labels;
+    }
+
+    fn set_proxy_url(&mut self, proxy_url: Option<Url>) {
+        self.proxy_url = proxy_url;
     }
 
     /// Merge updates from any JSON-serializable payload into this resource.
@@ -88,8 +110,7 @@ impl CodexResource {
     /// - OAuth token refresh payloads (`access_token`, `expires_in`)
     ///
     /// Only updates fields present in the JSON; others remain unchanged.
-    #[allow(dead_code)]
-    pub fn update_credential(&mut self, payload: impl Serialize) -> Result<(), PolluxError> {
+    fn update_credential(&mut self, payload: impl Serialize) -> Result<(), PolluxError> {
         use crate::providers::credential_update::{apply_expiry, parse_patch, set_opt, set_plain};
 
         #[derive(Debug, Default, Deserialize)]
@@ -122,15 +143,7 @@ impl CodexResource {
         Ok(())
     }
 
-    /// Build a resource from any JSON-like payload by applying updates to a default struct.
-    #[cfg(test)]
-    pub fn from_payload(payload: impl Serialize) -> Result<Self, PolluxError> {
-        let mut cred = CodexResource::default();
-        cred.update_credential(payload)?;
-        Ok(cred)
-    }
-
-    pub(super) fn try_from_oauth_token_response(
+    fn try_from_oauth_token_response(
         token_response: &OauthTokenResponse,
         refresh_seed: Option<&RefreshTokenSeed>,
     ) -> Result<Self, PolluxError> {
@@ -179,8 +192,18 @@ impl CodexResource {
             access_token,
             expiry,
             chatgpt_plan_type: identity.chatgpt_plan_type,
+            labels: Vec::new(),
+            proxy_url: None,
         })
     }
+
+    fn has_identity(&self) -> bool {
+        !self.sub.trim().is_empty() && !self.account_id.trim().is_empty()
+    }
+
+    fn email(&self) -> Option<&str> {
+        self.email.as_deref()
+    }
 }
 
 impl Schedulable for CodexResource {
@@ -191,8 +214,18 @@ impl Schedulable for CodexResource {
         &self.account_id
     }
 
-    fn is_expired(&self) -> bool {
-        self.is_expired()
+    fn expires_within(&self, min_validity: std::time::Duration) -> bool {
+        Duration::from_std(min_validity)
+            .ok()
+            .and_then(|margin| Utc::now().checked_add_signed(margin))
+            .is_none_or(|deadline| deadline >= self.expiry)
+    }
+
+    fn expired_beyond(&self, grace: std::time::Duration) -> bool {
+        Duration::from_std(grace)
+            .ok()
+            .and_then(|grace| self.expiry.checked_add_signed(grace))
+            .is_none_or(|deadline| Utc::now() > deadline)
     }
 
     fn make_lease(&self, id: CredentialId) -> CodexLease {
@@ -201,8 +234,17 @@ impl Schedulable for CodexResource {
             access_token: self.access_token.clone(),
             account_id: self.account_id.clone(),
             email: self.email.clone(),
+            proxy_url: self.proxy_url.clone(),
         }
     }
+
+    fn labels(&self) -> &[String] {
+        &self.labels
+    }
+
+    fn tier(&self) -> Option<&str> {
+        self.chatgpt_plan_type.as_deref()
+    }
 }
 
 impl TryFrom<CodexProfile> for CodexResource {
@@ -222,6 +264,8 @@ impl TryFrom<CodexProfile> for CodexResource {
             access_token,
             expiry,
             chatgpt_plan_type: profile.chatgpt_plan_type,
+            labels: Vec::new(),
+            proxy_url: None,
         })
     }
 }
@@ -236,6 +280,8 @@ impl From<DbCodexResource> for CodexResource {
             access_token: d.access_token,
             expiry: d.expiry,
             chatgpt_plan_type: d.chatgpt_plan_type,
+            labels: split_labels(&d.labels),
+            proxy_url: d.proxy_url.and_then(|url| url.parse().ok()),
         }
     }
 }
@@ -250,6 +296,8 @@ impl From<CodexResource> for CodexCreate {
             access_token: cred.access_token,
             expiry: cred.expiry,
             chatgpt_plan_type: cred.chatgpt_plan_type,
+            labels: cred.labels,
+            proxy_url: cred.proxy_url,
         }
     }
 }
@@ -264,6 +312,8 @@ impl From<&CodexResource> for CodexCreate {
             access_token: cred.access_token.clone(),
             expiry: cred.expiry,
             chatgpt_plan_type: cred.chatgpt_plan_type.clone(),
+            labels: cred.labels.clone(),
+            proxy_url: cred.proxy_url.clone(),
         }
     }
 }
@@ -287,6 +337,21 @@ mod tests {
         assert!(cred.is_expired());
     }
 
+    #[test]
+    fn expires_within_honors_min_validity() {
+        let cred = CodexResource::from_payload(json!({
+            "account_id": "acct_test",
+            "refresh_token": "rt",
+            "access_token": "at",
+            "expiry": Utc::now() + chrono::Duration::minutes(4),
+        }))
+        .expect("valid payload");
+
+        assert!(!cred.expires_within(std::time::Duration::from_mins(1)));
+        assert!(cred.expires_within(std::time::Duration::from_mins(5)));
+        assert!(cred.expires_within(std::time::Duration::MAX));
+    }
+
     #[test]
     fn update_credential_supports_expires_in() {
         let mut cred = CodexResource::from_payload(json!({
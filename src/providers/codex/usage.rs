@@ -0,0 +1,191 @@
+//! Plan and usage-window snapshot of one stored Codex credential, read from
+//! the `ChatGPT` usage endpoint for `/admin/v1/codex/credentials/{id}/quota`.
+
+use super::client::CodexClient;
+use super::manager::CredentialOps;
+use crate::PolluxError;
+use crate::db::DbActorHandle;
+use crate::providers::traits::provider::{CredentialStore, ProviderResource};
+use crate::providers::traits::scheduler::{CredentialId, Schedulable};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// `GET backend-api/wham/usage` body; fields Pollux does not report are ignored.
+#[derive(Debug, Default, Deserialize)]
+pub(crate) struct UsagePayload {
+    #[serde(default)]
+    plan_type: Option<String>,
+    #[serde(default)]
+    rate_limit: Option<RateLimitPayload>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct RateLimitPayload {
+    #[serde(default)]
+    limit_reached: bool,
+    #[serde(default)]
+    primary_window: Option<WindowPayload>,
+    #[serde(default)]
+    secondary_window: Option<WindowPayload>,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+struct WindowPayload {
+    used_percent: f64,
+    #[serde(default)]
+    limit_window_seconds: Option<u64>,
+    #[serde(default)]
+    reset_after_seconds: Option<u64>,
+    /// Unix seconds.
+    #[serde(default)]
+    reset_at: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct UsageWindowView {
+    pub used_percent: f64,
+    pub window_minutes: Option<u64>,
+    pub resets_at: Option<DateTime<Utc>>,
+}
+
+impl UsageWindowView {
+    fn from_payload(window: WindowPayload, now: DateTime<Utc>) -> Self {
+        let resets_at = window
+            .reset_at
+            .and_then(|at| DateTime::from_timestamp(at, 0))
+            .or_else(|| {
+                let after = i64::try_from(window.reset_after_seconds?).ok()?;
+                now.checked_add_signed(chrono::Duration::seconds(after))
+            });
+        Self {
+            used_percent: window.used_percent,
+            window_minutes: window.limit_window_seconds.map(|secs| secs / 60),
+            resets_at,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CodexUsageSnapshot {
+    pub id: CredentialId,
+    pub email: Option<String>,
+    pub account_id: String,
+    /// Plan reported by upstream, falling back to the one stored at login.
+    pub plan_type: Option<String>,
+    pub limit_reached: bool,
+    /// Short rolling window (about five hours).
+    pub primary: Option<UsageWindowView>,
+    /// Weekly window.
+    pub secondary: Option<UsageWindowView>,
+    pub fetched_at: DateTime<Utc>,
+}
+
+impl CodexUsageSnapshot {
+    fn new(
+        id: CredentialId,
+        email: Option<String>,
+        account_id: String,
+        stored_plan: Option<String>,
+        payload: UsagePayload,
+    ) -> Self {
+        let now = Utc::now();
+        let rate_limit = payload.rate_limit.unwrap_or_default();
+        Self {
+            id,
+            email,
+            account_id,
+            plan_type: payload.plan_type.or(stored_plan),
+            limit_reached: rate_limit.limit_reached,
+            primary: rate_limit
+                .primary_window
+                .map(|w| UsageWindowView::from_payload(w, now)),
+            secondary: rate_limit
+                .secondary_window
+                .map(|w| UsageWindowView::from_payload(w, now)),
+            fetched_at: now,
+        }
+    }
+}
+
+/// Query upstream usage for credential `id` with its stored access token.
+///
+/// Nothing is leased: the credential may be cooling, busy or disabled.
+pub(crate) async fn snapshot(
+    caller: &CodexClient,
+    db: &DbActorHandle,
+    id: CredentialId,
+) -> Result<CodexUsageSnapshot, PolluxError> {
+    let cred = CredentialOps::new(db.clone()).get_by_id(id).await?;
+    let payload = caller.fetch_usage(&cred.make_lease(id)).await?;
+    Ok(CodexUsageSnapshot::new(
+        id,
+        cred.email().map(str::to_string),
+        cred.account_id().to_string(),
+        cred.chatgpt_plan_type().map(str::to_string),
+        payload,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn snapshot_maps_windows_and_prefers_upstream_plan() {
+        let payload: UsagePayload = serde_json::from_str(
+            r#"{
+                "plan_type": "pro",
+                "rate_limit": {
+                    "allowed": true,
+                    "limit_reached": false,
+                    "primary_window": {
+                        "used_percent": 37,
+                        "limit_window_seconds": 18000,
+                        "reset_after_seconds": 600,
+                        "reset_at": 1767225600
+                    },
+                    "secondary_window": {
+                        "used_percent": 81.5,
+                        "limit_window_seconds": 604800,
+                        "reset_after_seconds": 3600
+                    }
+                },
+                "credits": null
+            }"#,
+        )
+        .unwrap();
+
+        let snap = CodexUsageSnapshot::new(
+            7,
+            None,
+            "acct".to_string(),
+            Some("plus".to_string()),
+            payload,
+        );
+        assert_eq!(snap.plan_type.as_deref(), Some("pro"));
+        let primary = snap.primary.unwrap();
+        assert_eq!(primary.window_minutes, Some(300));
+        assert_eq!(
+            primary.resets_at,
+            DateTime::from_timestamp(1_767_225_600, 0)
+        );
+        let secondary = snap.secondary.unwrap();
+        assert_eq!(secondary.window_minutes, Some(10_080));
+        let in_secs = (secondary.resets_at.unwrap() - snap.fetched_at).num_seconds();
+        assert_eq!(in_secs, 3_600);
+    }
+
+    #[test]
+    fn missing_rate_limit_falls_back_to_stored_plan() {
+        let snap = CodexUsageSnapshot::new(
+            1,
+            None,
+            "acct".to_string(),
+            Some("plus".to_string()),
+            serde_json::from_str("{}").unwrap(),
+        );
+        assert_eq!(snap.plan_type.as_deref(), Some("plus"));
+        assert!(!snap.limit_reached);
+        assert!(snap.primary.is_none() && snap.secondary.is_none());
+    }
+}
@@ -1,6 +0,0 @@
-mod processor;
-
-pub(super) use processor::{
-    CodexOauthWorkerHandle, CredentialJob, CredentialJobKind, CredentialProcessError,
-    CredentialProcessResult,
-};
@@ -0,0 +1,265 @@
+//! Admin-facing credential views: a stored row merged with live scheduler state.
+
+use crate::db::{
+    DbAntigravityResource, DbClaudeResource, DbCodexResource, DbGeminiCliResource, DbQwenResource,
+    split_labels,
+};
+use crate::model_catalog::{MODEL_REGISTRY, model_names_from_mask};
+use crate::providers::manifest::ProviderKind;
+use crate::providers::traits::scheduler::{CredentialHealth, CredentialId, CredentialRuntime};
+use crate::utils::http::redact_proxy;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CredentialState {
+    /// Loaded in the scheduler and eligible for leases (cooldowns permitting).
+    Active,
+    /// Loaded in the scheduler, waiting on a token refresh.
+    Refreshing,
+    /// Enabled in storage but not (yet) loaded in the scheduler.
+    Pending,
+    /// Disabled in storage, either manually or after a ban/refresh failure.
+    Disabled,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CooldownView {
+    pub model: String,
+    pub remaining_secs: u64,
+}
+
+/// Health score and the consecutive failures behind it.
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct HealthView {
+    /// `0..=100`; policies act once it drops below their threshold.
+    pub score: u8,
+    pub consecutive_forbidden: u32,
+    pub consecutive_server_errors: u32,
+    pub consecutive_refresh_failures: u32,
+}
+
+impl From<CredentialHealth> for HealthView {
+    fn from(health: CredentialHealth) -> Self {
+        Self {
+            score: health.score(),
+            consecutive_forbidden: health.forbidden,
+            consecutive_server_errors: health.server_errors,
+            consecutive_refresh_failures: health.refresh_failures,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CredentialView {
+    pub id: CredentialId,
+    pub provider: ProviderKind,
+    pub state: CredentialState,
+    pub email: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub project_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub account_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub plan_type: Option<String>,
+    /// Pool labels selectable with `x-pollux-pool`.
+    pub labels: Vec<String>,
+    /// Bound egress proxy, password masked.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub proxy_url: Option<String>,
+    /// Scheduler capability bitmask (`0x...`); `None` when not loaded.
+    pub capability_mask: Option<String>,
+    /// Models currently schedulable for this credential.
+    pub models: Vec<String>,
+    pub cooldowns: Vec<CooldownView>,
+    /// Requests currently being served with this credential.
+    pub in_flight: u32,
+    /// Percent of account quota left, when upstream reports it (Codex).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub quota_remaining_percent: Option<u8>,
+    /// `None` when not loaded.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub health: Option<HealthView>,
+    pub expiry: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl CredentialView {
+    fn with_runtime(mut self, runtime: Option<&CredentialRuntime>) -> Self {
+        let Some(runtime) = runtime else {
+            if self.state == CredentialState::Active {
+                self.state = CredentialState::Pending;
+            }
+            return self;
+        };
+
+        if runtime.refreshing {
+            self.state = CredentialState::Refreshing;
+        }
+        self.capability_mask = Some(runtime.caps.to_string());
+        self.models = model_names_from_mask(&runtime.caps);
+        self.in_flight = runtime.in_flight;
+        self.quota_remaining_percent = runtime.quota_remaining;
+        self.health = Some(runtime.health.into());
+        self.cooldowns = runtime
+            .cooldowns
+            .iter()
+            .filter(|(idx, _)| *idx < MODEL_REGISTRY.len())
+            .map(|(idx, remaining)| CooldownView {
+                model: MODEL_REGISTRY.get_name(*idx).to_string(),
+                remaining_secs: remaining.as_secs(),
+            })
+            .collect();
+        self
+    }
+}
+
+/// Merge stored rows with a scheduler snapshot taken by the provider actor.
+pub(crate) fn merge_runtime(
+    views: Vec<CredentialView>,
+    runtime: &HashMap<CredentialId, CredentialRuntime>,
+) -> Vec<CredentialView> {
+    views
+        .into_iter()
+        .map(|view| {
+            let rt = runtime.get(&view.id);
+            view.with_runtime(rt)
+        })
+        .collect()
+}
+
+fn stored_state(status: bool) -> CredentialState {
+    if status {
+        CredentialState::Active
+    } else {
+        CredentialState::Disabled
+    }
+}
+
+fn shown_proxy(stored: Option<&str>) -> Option<String> {
+    stored
+        .and_then(|raw| raw.parse().ok())
+        .map(|url| redact_proxy(&url))
+}
+
+impl From<DbGeminiCliResource> for CredentialView {
+    fn from(row: DbGeminiCliResource) -> Self {
+        Self {
+            id: row.id.cast_unsigned(),
+            provider: ProviderKind::GeminiCli,
+            state: stored_state(row.status),
+            email: row.email,
+            project_id: Some(row.project_id),
+            account_id: None,
+            plan_type: row.tier,
+            labels: split_labels(&row.labels),
+            proxy_url: shown_proxy(row.proxy_url.as_deref()),
+            capability_mask: None,
+            models: Vec::new(),
+            cooldowns: Vec::new(),
+            in_flight: 0,
+            quota_remaining_percent: None,
+            health: None,
+            expiry: row.expiry,
+            updated_at: row.updated_at,
+        }
+    }
+}
+
+impl From<DbCodexResource> for CredentialView {
+    fn from(row: DbCodexResource) -> Self {
+        Self {
+            id: row.id.cast_unsigned(),
+            provider: ProviderKind::Codex,
+            state: stored_state(row.status),
+            email: row.email,
+            project_id: None,
+            account_id: Some(row.account_id),
+            plan_type: row.chatgpt_plan_type,
+            labels: split_labels(&row.labels),
+            proxy_url: shown_proxy(row.proxy_url.as_deref()),
+            capability_mask: None,
+            models: Vec::new(),
+            cooldowns: Vec::new(),
+            in_flight: 0,
+            quota_remaining_percent: None,
+            health: None,
+            expiry: row.expiry,
+            updated_at: row.updated_at,
+        }
+    }
+}
+
+impl From<DbAntigravityResource> for CredentialView {
+    fn from(row: DbAntigravityResource) -> Self {
+        Self {
+            id: row.id.cast_unsigned(),
+            provider: ProviderKind::Antigravity,
+            state: stored_state(row.status),
+            email: row.email,
+            project_id: Some(row.project_id),
+            account_id: None,
+            plan_type: row.tier,
+            labels: split_labels(&row.labels),
+            proxy_url: shown_proxy(row.proxy_url.as_deref()),
+            capability_mask: None,
+            models: Vec::new(),
+            cooldowns: Vec::new(),
+            in_flight: 0,
+            quota_remaining_percent: None,
+            health: None,
+            expiry: row.expiry,
+            updated_at: row.updated_at,
+        }
+    }
+}
+
+impl From<DbClaudeResource> for CredentialView {
+    fn from(row: DbClaudeResource) -> Self {
+        Self {
+            id: row.id.cast_unsigned(),
+            provider: ProviderKind::Claude,
+            state: stored_state(row.status),
+            email: row.email,
+            project_id: None,
+            account_id: Some(row.account_uuid),
+            plan_type: None,
+            labels: split_labels(&row.labels),
+            proxy_url: shown_proxy(row.proxy_url.as_deref()),
+            capability_mask: None,
+            models: Vec::new(),
+            cooldowns: Vec::new(),
+            in_flight: 0,
+            quota_remaining_percent: None,
+            health: None,
+            expiry: row.expiry,
+            updated_at: row.updated_at,
+        }
+    }
+}
+
+impl From<DbQwenResource> for CredentialView {
+    fn from(row: DbQwenResource) -> Self {
+        Self {
+            id: row.id.cast_unsigned(),
+            provider: ProviderKind::Qwen,
+            state: stored_state(row.status),
+            email: None,
+            project_id: None,
+            account_id: None,
+            plan_type: None,
+            labels: split_labels(&row.labels),
+            proxy_url: shown_proxy(row.proxy_url.as_deref()),
+            capability_mask: None,
+            models: Vec::new(),
+            cooldowns: Vec::new(),
+            in_flight: 0,
+            quota_remaining_percent: None,
+            health: None,
+            expiry: row.expiry,
+            updated_at: row.updated_at,
+        }
+    }
+}
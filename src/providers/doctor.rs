@@ -0,0 +1,324 @@
+//! `pollux doctor`: probe one stored credential against its real upstream.
+//!
+//! Runs outside the provider actors so the scheduler never hides a failure:
+//! the credential is loaded straight from the database, refreshed (and the
+//! new tokens saved), then used for a model listing, a tiny generation and a
+//! streaming request. Each step reports its latency and the upstream answer.
+
+use crate::config::Config;
+use crate::db::DbActorHandle;
+use crate::providers::UPSTREAM_BODY_PREVIEW_CHARS;
+use crate::providers::manifest::ProviderKind;
+use crate::providers::traits::scheduler::CredentialId;
+use crate::utils::dns::with_resolver;
+use crate::utils::logging::body_preview;
+use chrono::{DateTime, Utc};
+use eventsource_stream::Eventsource;
+use futures::StreamExt;
+use serde_json::Value;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+pub const USAGE: &str = "\
+Usage: pollux doctor --provider <geminicli|codex|antigravity> --id <ID> [--model <MODEL>] [--no-refresh]
+
+Probes one stored credential against the real upstream: token refresh,
+model list, a one-token generation and a streaming request.
+
+Refreshing saves the new tokens to the database. Codex rotates refresh
+tokens, so use --no-refresh while a server holding this credential is up.";
+
+/// Prompt used by the generation probes.
+pub(crate) const PROBE_PROMPT: &str = "Reply with OK.";
+
+const PROBE_TIMEOUT: Duration = Duration::from_mins(2);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DoctorArgs {
+    pub provider: ProviderKind,
+    pub id: CredentialId,
+    /// Defaults to the first entry of the provider's `model_list`.
+    pub model: Option<String>,
+    pub refresh: bool,
+}
+
+impl DoctorArgs {
+    /// Parse the arguments following `doctor`.
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Self, String> {
+        let mut provider = None;
+        let mut id = None;
+        let mut model = None;
+        let mut refresh = true;
+
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let mut value = |name: &str| {
+                args.next()
+                    .ok_or_else(|| format!("{name} requires a value"))
+            };
+            match arg.as_str() {
+                "--provider" => {
+                    let raw = value("--provider")?;
+                    provider = Some(
+                        ProviderKind::ALL
+                            .into_iter()
+                            .find(|kind| kind.label() == raw)
+                            .ok_or_else(|| format!("unknown provider `{raw}`"))?,
+                    );
+                }
+                "--id" => {
+                    let raw = value("--id")?;
+                    id = Some(
+                        raw.parse::<CredentialId>()
+                            .map_err(|_| format!("invalid credential id `{raw}`"))?,
+                    );
+                }
+                "--model" => model = Some(value("--model")?),
+                "--no-refresh" => refresh = false,
+                other => return Err(format!("unexpected argument `{other}`")),
+            }
+        }
+
+        Ok(Self {
+            provider: provider.ok_or("--provider is required")?,
+            id: id.ok_or("--id is required")?,
+            model,
+            refresh,
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct DoctorStep {
+    pub name: &'static str,
+    pub elapsed: Duration,
+    /// `None` when the step was skipped.
+    pub outcome: Option<Result<String, String>>,
+}
+
+#[derive(Debug)]
+pub struct DoctorReport {
+    pub provider: ProviderKind,
+    pub id: CredentialId,
+    pub steps: Vec<DoctorStep>,
+}
+
+impl DoctorReport {
+    fn new(provider: ProviderKind, id: CredentialId) -> Self {
+        Self {
+            provider,
+            id,
+            steps: Vec::new(),
+        }
+    }
+
+    /// `true` when no step failed.
+    #[must_use]
+    pub fn passed(&self) -> bool {
+        self.steps
+            .iter()
+            .all(|step| !matches!(step.outcome, Some(Err(_))))
+    }
+
+    /// Time `probe` and record its detail line; yields its value on success.
+    pub(crate) async fn step<T>(
+        &mut self,
+        name: &'static str,
+        probe: impl Future<Output = Result<(T, String), String>>,
+    ) -> Option<T> {
+        let start = Instant::now();
+        let result = tokio::time::timeout(PROBE_TIMEOUT, probe)
+            .await
+            .unwrap_or_else(|_| Err(format!("timed out after {}s", PROBE_TIMEOUT.as_secs())));
+        let (value, outcome) = match result {
+            Ok((value, detail)) => (Some(value), Ok(detail)),
+            Err(e) => (None, Err(e)),
+        };
+        self.steps.push(DoctorStep {
+            name,
+            elapsed: start.elapsed(),
+            outcome: Some(outcome),
+        });
+        value
+    }
+
+    pub(crate) fn skip(&mut self, name: &'static str) {
+        self.steps.push(DoctorStep {
+            name,
+            elapsed: Duration::ZERO,
+            outcome: None,
+        });
+    }
+}
+
+impl fmt::Display for DoctorReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{} credential {}", self.provider.label(), self.id)?;
+        for step in &self.steps {
+            let (status, detail) = match &step.outcome {
+                Some(Ok(detail)) => ("ok", detail.as_str()),
+                Some(Err(error)) => ("FAIL", error.as_str()),
+                None => ("skip", ""),
+            };
+            writeln!(
+                f,
+                "  {status:<4}  {:<8}  {:>6} ms  {detail}",
+                step.name,
+                step.elapsed.as_millis()
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Run every probe for `args.provider` and collect the results.
+pub async fn run(cfg: &Config, db: &DbActorHandle, args: &DoctorArgs) -> DoctorReport {
+    let mut report = DoctorReport::new(args.provider, args.id);
+    match args.provider {
+        ProviderKind::GeminiCli => {
+            crate::providers::geminicli::doctor::diagnose(&mut report, &cfg.geminicli(), db, args)
+                .await;
+        }
+        ProviderKind::Codex => {
+            crate::providers::codex::doctor::diagnose(&mut report, &cfg.codex(), db, args).await;
+        }
+        ProviderKind::Antigravity => {
+            crate::providers::antigravity::doctor::diagnose(
+                &mut report,
+                &cfg.antigravity(),
+                db,
+                args,
+            )
+            .await;
+        }
+        ProviderKind::Claude => {
+            crate::providers::claude::doctor::diagnose(&mut report, &cfg.claude(), db, args).await;
+        }
+        ProviderKind::Qwen => {
+            crate::providers::qwen::doctor::diagnose(&mut report, &cfg.qwen(), db, args).await;
+        }
+    }
+    report
+}
+
+/// Plain client honoring the provider proxy; no retries, no pooling tricks.
+pub(crate) fn http_client(proxy: Option<&url::Url>, user_agent: Option<&str>) -> reqwest::Client {
+    let mut builder = with_resolver(reqwest::Client::builder())
+        .redirect(reqwest::redirect::Policy::none())
+        .connect_timeout(Duration::from_secs(10));
+    if let Some(ua) = user_agent {
+        builder = builder.user_agent(ua);
+    }
+    if let Some(proxy_url) = proxy {
+        let proxy =
+            reqwest::Proxy::all(proxy_url.as_str()).expect("invalid proxy url for reqwest client");
+        builder = builder.proxy(proxy);
+    }
+    builder.build().expect("failed to build reqwest client")
+}
+
+pub(crate) fn db_id(id: CredentialId) -> Result<i64, String> {
+    i64::try_from(id).map_err(|_| format!("invalid credential id {id}"))
+}
+
+/// `valid for 3540s` / `expired 120s ago`.
+pub(crate) fn token_validity(expiry: DateTime<Utc>) -> String {
+    let secs = (expiry - Utc::now()).num_seconds();
+    if secs > 0 {
+        format!("token valid for {secs}s")
+    } else {
+        format!("token expired {}s ago", -secs)
+    }
+}
+
+/// Fail on non-2xx with a body preview, else parse the JSON body.
+pub(crate) async fn expect_json(resp: reqwest::Response) -> Result<Value, String> {
+    let status = resp.status();
+    let body = resp.text().await.map_err(|e| e.to_string())?;
+    if !status.is_success() {
+        return Err(format!(
+            "HTTP {status}: {}",
+            body_preview(&body, UPSTREAM_BODY_PREVIEW_CHARS)
+        ));
+    }
+    serde_json::from_str(&body).map_err(|e| format!("invalid JSON body: {e}"))
+}
+
+/// What a streaming probe observed.
+pub(crate) struct SseProbe {
+    /// Time from sending the request to the first event.
+    pub first_event: Duration,
+    pub events: usize,
+    /// Last event payload that parsed as JSON.
+    pub last: Option<Value>,
+}
+
+impl SseProbe {
+    pub(crate) fn summary(&self) -> String {
+        format!(
+            "first event after {} ms, {} events",
+            self.first_event.as_millis(),
+            self.events
+        )
+    }
+}
+
+/// Drain an event stream; `sent` is when the request went out.
+pub(crate) async fn probe_sse(resp: reqwest::Response, sent: Instant) -> Result<SseProbe, String> {
+    let status = resp.status();
+    if !status.is_success() {
+        let body = resp.text().await.unwrap_or_default();
+        return Err(format!(
+            "HTTP {status}: {}",
+            body_preview(&body, UPSTREAM_BODY_PREVIEW_CHARS)
+        ));
+    }
+
+    let mut stream = resp.bytes_stream().eventsource();
+    let mut probe = SseProbe {
+        first_event: Duration::ZERO,
+        events: 0,
+        last: None,
+    };
+    while let Some(event) = stream.next().await {
+        let event =
+            event.map_err(|e| format!("stream error after {} events: {e}", probe.events))?;
+        if probe.events == 0 {
+            probe.first_event = sent.elapsed();
+        }
+        probe.events += 1;
+        if let Ok(value) = serde_json::from_str::<Value>(&event.data) {
+            probe.last = Some(value);
+        }
+    }
+    if probe.events == 0 {
+        return Err("stream ended without events".to_string());
+    }
+    Ok(probe)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Result<DoctorArgs, String> {
+        DoctorArgs::parse(list.iter().map(ToString::to_string))
+    }
+
+    #[test]
+    fn parses_provider_id_and_flags() {
+        let parsed = args(&["--provider", "codex", "--id", "42", "--no-refresh"]).unwrap();
+        assert_eq!(
+            parsed,
+            DoctorArgs {
+                provider: ProviderKind::Codex,
+                id: 42,
+                model: None,
+                refresh: false,
+            }
+        );
+        assert!(args(&["--provider", "codex"]).is_err());
+        assert!(args(&["--provider", "nope", "--id", "1"]).is_err());
+        assert!(args(&["--provider", "codex", "--id"]).is_err());
+    }
+}
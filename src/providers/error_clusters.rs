@@ -0,0 +1,334 @@
+//! Upstream error fingerprinting (`/admin/v1/errors`).
+//!
+//! Every non-success upstream response is reduced to its message, normalized
+//! (lowercased, numbers and ids masked) and hashed together with the status.
+//! Responses that differ only in quota timers or request ids therefore share
+//! a fingerprint, while a previously unseen message (say, a new WAF page)
+//! starts a new cluster and is logged once. Counts are kept in one-minute
+//! buckets so the admin API can rank clusters over a recent window.
+//!
+//! Upstream messages often echo prompt content, so in compliance mode only the
+//! fingerprint and normalized pattern are logged and kept, never a sample.
+
+use crate::utils::logging::payload_logging_enabled;
+use axum::http::StatusCode;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, VecDeque};
+use std::fmt::Write as _;
+use std::sync::{Arc, Mutex};
+use tracing::warn;
+
+/// Per-minute buckets older than this are dropped.
+pub const ERROR_CLUSTER_RETENTION_MINS: u32 = 6 * 60;
+/// Once this many clusters exist, the least recently seen one is evicted.
+const MAX_CLUSTERS: usize = 512;
+const MAX_MESSAGE_CHARS: usize = 256;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct ClusterKey {
+    provider: &'static str,
+    model: String,
+    fingerprint: String,
+}
+
+#[derive(Debug)]
+struct Cluster {
+    status: u16,
+    /// Normalized message the fingerprint was computed from.
+    pattern: String,
+    /// First raw message seen for this cluster, truncated; `None` in
+    /// compliance mode.
+    sample: Option<String>,
+    first_seen: DateTime<Utc>,
+    last_seen: DateTime<Utc>,
+    total: u64,
+    /// `(unix minute, count)`, oldest first.
+    minutes: VecDeque<(i64, u64)>,
+}
+
+impl Cluster {
+    fn count_since(&self, minute: i64) -> u64 {
+        self.minutes
+            .iter()
+            .filter(|(m, _)| *m >= minute)
+            .map(|(_, n)| n)
+            .sum()
+    }
+}
+
+/// One cluster as served by the admin API.
+#[derive(Debug, Clone, Serialize)]
+pub struct ErrorClusterView {
+    pub fingerprint: String,
+    pub provider: &'static str,
+    pub model: String,
+    pub status: u16,
+    /// Occurrences inside the requested window.
+    pub count: u64,
+    /// Occurrences since startup.
+    pub total: u64,
+    pub first_seen: DateTime<Utc>,
+    pub last_seen: DateTime<Utc>,
+    pub pattern: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sample: Option<String>,
+}
+
+/// Shared cluster table; cheap to clone.
+#[derive(Debug, Clone, Default)]
+pub struct ErrorClusters {
+    inner: Arc<Mutex<HashMap<ClusterKey, Cluster>>>,
+}
+
+impl ErrorClusters {
+    /// Count one upstream error response; `body` is the raw response body.
+    pub fn record(&self, provider: &'static str, model: &str, status: StatusCode, body: &str) {
+        self.record_at(Utc::now(), provider, model, status, body);
+    }
+
+    fn record_at(
+        &self,
+        now: DateTime<Utc>,
+        provider: &'static str,
+        model: &str,
+        status: StatusCode,
+        body: &str,
+    ) {
+        let message = upstream_message(body);
+        let pattern = normalize(&message);
+        let key = ClusterKey {
+            provider,
+            model: model.to_string(),
+            fingerprint: fingerprint(status, &pattern),
+        };
+        let minute = now.timestamp().div_euclid(60);
+        let sample = payload_logging_enabled().then(|| truncate(&message));
+
+        let Ok(mut clusters) = self.inner.lock() else {
+            return;
+        };
+        if !clusters.contains_key(&key) {
+            warn!(
+                provider,
+                model,
+                status = status.as_u16(),
+                fingerprint = %key.fingerprint,
+                pattern = %truncate(&pattern),
+                message = sample.as_deref(),
+                "[Errors] New upstream error cluster"
+            );
+            if clusters.len() >= MAX_CLUSTERS
+                && let Some(oldest) = clusters
+                    .iter()
+                    .min_by_key(|(_, c)| c.last_seen)
+                    .map(|(k, _)| k.clone())
+            {
+                clusters.remove(&oldest);
+            }
+        }
+        let cluster = clusters.entry(key).or_insert_with(|| Cluster {
+            status: status.as_u16(),
+            pattern,
+            sample,
+            first_seen: now,
+            last_seen: now,
+            total: 0,
+            minutes: VecDeque::new(),
+        });
+        cluster.last_seen = now;
+        cluster.total += 1;
+        match cluster.minutes.back_mut() {
+            Some((m, n)) if *m == minute => *n += 1,
+            _ => cluster.minutes.push_back((minute, 1)),
+        }
+        let horizon = minute - i64::from(ERROR_CLUSTER_RETENTION_MINS);
+        while cluster.minutes.front().is_some_and(|(m, _)| *m <= horizon) {
+            cluster.minutes.pop_front();
+        }
+    }
+
+    /// Clusters seen in the last `window_mins` minutes, most frequent first.
+    #[must_use]
+    pub fn top(
+        &self,
+        window_mins: u32,
+        provider: Option<&str>,
+        limit: usize,
+    ) -> Vec<ErrorClusterView> {
+        self.top_at(Utc::now(), window_mins, provider, limit)
+    }
+
+    fn top_at(
+        &self,
+        now: DateTime<Utc>,
+        window_mins: u32,
+        provider: Option<&str>,
+        limit: usize,
+    ) -> Vec<ErrorClusterView> {
+        let window = window_mins.clamp(1, ERROR_CLUSTER_RETENTION_MINS);
+        let since = now.timestamp().div_euclid(60) - i64::from(window) + 1;
+        let Ok(clusters) = self.inner.lock() else {
+            return Vec::new();
+        };
+        let mut views: Vec<ErrorClusterView> = clusters
+            .iter()
+            .filter(|(k, _)| provider.is_none_or(|p| k.provider == p))
+            .filter_map(|(k, c)| {
+                let count = c.count_since(since);
+                (count > 0).then(|| ErrorClusterView {
+                    fingerprint: k.fingerprint.clone(),
+                    provider: k.provider,
+                    model: k.model.clone(),
+                    status: c.status,
+                    count,
+                    total: c.total,
+                    first_seen: c.first_seen,
+                    last_seen: c.last_seen,
+                    pattern: c.pattern.clone(),
+                    sample: c.sample.clone(),
+                })
+            })
+            .collect();
+        views.sort_by(|a, b| {
+            b.count
+                .cmp(&a.count)
+                .then_with(|| b.last_seen.cmp(&a.last_seen))
+        });
+        views.truncate(limit);
+        views
+    }
+}
+
+/// `error.message` (Google / `OpenAI` shape), a top-level `message` or
+/// `detail`, or the raw body when it is not JSON.
+fn upstream_message(body: &str) -> String {
+    let Ok(json) = serde_json::from_str::<Value>(body) else {
+        return body.trim().to_string();
+    };
+    [
+        json.pointer("/error/message"),
+        json.get("message"),
+        json.get("detail"),
+        json.get("error"),
+    ]
+    .into_iter()
+    .flatten()
+    .find_map(Value::as_str)
+    .map_or_else(|| body.trim().to_string(), str::to_string)
+}
+
+/// Lowercase and mask the parts that vary between otherwise identical errors:
+/// tokens containing digits become `#` (short ones, e.g. `3h12m`, keep their
+/// letters) and whitespace is collapsed.
+fn normalize(message: &str) -> String {
+    let mut out = String::new();
+    for token in message.split_whitespace().take(64) {
+        if !out.is_empty() {
+            out.push(' ');
+        }
+        let token = token.to_lowercase();
+        if !token.chars().any(|c| c.is_ascii_digit()) {
+            out.push_str(&token);
+        } else if token.len() >= 8 {
+            out.push('#');
+        } else {
+            let mut in_digits = false;
+            for c in token.chars() {
+                if c.is_ascii_digit() || (in_digits && c == '.') {
+                    if !in_digits {
+                        out.push('#');
+                    }
+                    in_digits = true;
+                } else {
+                    in_digits = false;
+                    out.push(c);
+                }
+            }
+        }
+    }
+    out
+}
+
+fn fingerprint(status: StatusCode, pattern: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(status.as_str().as_bytes());
+    hasher.update(b" ");
+    hasher.update(pattern.as_bytes());
+    let digest = hasher.finalize();
+    let mut out = String::with_capacity(16);
+    for byte in &digest[..8] {
+        let _ = write!(out, "{byte:02x}");
+    }
+    out
+}
+
+fn truncate(message: &str) -> String {
+    message.chars().take(MAX_MESSAGE_CHARS).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[test]
+    fn variable_parts_share_a_cluster_and_old_buckets_leave_the_window() {
+        let clusters = ErrorClusters::default();
+        let t0 = DateTime::parse_from_rfc3339("2026-01-01T00:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let quota = |secs: u32| {
+            format!(
+                r#"{{"error":{{"code":429,"message":"Your quota will reset after {secs}s. Request id a1b2c3d4e5f6."}}}}"#
+            )
+        };
+
+        clusters.record_at(
+            t0,
+            "geminicli",
+            "gemini-2.5-pro",
+            StatusCode::TOO_MANY_REQUESTS,
+            &quota(12),
+        );
+        clusters.record_at(
+            t0 + Duration::minutes(30),
+            "geminicli",
+            "gemini-2.5-pro",
+            StatusCode::TOO_MANY_REQUESTS,
+            &quota(45),
+        );
+        clusters.record_at(
+            t0 + Duration::minutes(31),
+            "geminicli",
+            "gemini-2.5-pro",
+            StatusCode::FORBIDDEN,
+            "<html>Request blocked by firewall</html>",
+        );
+
+        let now = t0 + Duration::minutes(31);
+        let all = clusters.top_at(now, 60, None, 10);
+        assert_eq!(all.len(), 2);
+        assert_eq!((all[0].status, all[0].count, all[0].total), (429, 2, 2));
+        assert_eq!(
+            all[0].pattern,
+            "your quota will reset after #s. request id #"
+        );
+
+        let recent = clusters.top_at(now, 5, Some("geminicli"), 10);
+        assert_eq!(
+            recent
+                .iter()
+                .map(|c| (c.status, c.count))
+                .collect::<Vec<_>>(),
+            vec![(403, 1), (429, 1)]
+        );
+        assert!(clusters.top_at(now, 60, Some("codex"), 10).is_empty());
+        assert_eq!(
+            recent[0].sample.as_deref(),
+            (!cfg!(feature = "compliance")).then_some("<html>Request blocked by firewall</html>")
+        );
+    }
+}
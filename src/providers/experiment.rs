@@ -0,0 +1,285 @@
+//! Upstream request-shape A/B experiments.
+//!
+//! Requests are split into a control arm (untouched) and a treatment arm that
+//! gets the configured overrides. Outcomes are counted per arm so a change in
+//! translation can be compared against the current behavior on live traffic.
+
+use crate::config::ExperimentConfig;
+use axum::http::StatusCode;
+use pollux_schema::gemini::{Content, GeminiGenerateContentRequest, Part};
+use rand::Rng;
+use serde::Serialize;
+use serde_json::json;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExperimentArm {
+    Control,
+    Treatment,
+}
+
+/// Outcome counters for one side of a comparison.
+#[derive(Debug, Default)]
+pub(crate) struct ArmCounters {
+    requests: AtomicU64,
+    successes: AtomicU64,
+    client_errors: AtomicU64,
+    server_errors: AtomicU64,
+    latency_ms_total: AtomicU64,
+}
+
+impl ArmCounters {
+    pub(crate) fn record(&self, status: StatusCode, latency: Duration) {
+        self.requests.fetch_add(1, Ordering::Relaxed);
+        let bucket = if status.is_success() {
+            &self.successes
+        } else if status.is_client_error() {
+            &self.client_errors
+        } else {
+            &self.server_errors
+        };
+        bucket.fetch_add(1, Ordering::Relaxed);
+        let ms = u64::try_from(latency.as_millis()).unwrap_or(u64::MAX);
+        self.latency_ms_total.fetch_add(ms, Ordering::Relaxed);
+    }
+
+    pub(crate) fn stats(&self) -> ArmStats {
+        let requests = self.requests.load(Ordering::Relaxed);
+        let latency_ms_total = self.latency_ms_total.load(Ordering::Relaxed);
+        ArmStats {
+            requests,
+            successes: self.successes.load(Ordering::Relaxed),
+            client_errors: self.client_errors.load(Ordering::Relaxed),
+            server_errors: self.server_errors.load(Ordering::Relaxed),
+            avg_latency_ms: latency_ms_total.checked_div(requests).unwrap_or(0),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ArmStats {
+    pub requests: u64,
+    pub successes: u64,
+    pub client_errors: u64,
+    pub server_errors: u64,
+    /// Time to response headers; stream duration is not included.
+    pub avg_latency_ms: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ExperimentReport {
+    pub name: String,
+    pub fraction: f64,
+    pub control: ArmStats,
+    pub treatment: ArmStats,
+}
+
+#[derive(Debug)]
+struct ExperimentInner {
+    cfg: ExperimentConfig,
+    control: ArmCounters,
+    treatment: ArmCounters,
+}
+
+/// Cheap-to-clone handle; a disabled service assigns everything to control.
+#[derive(Debug, Clone, Default)]
+pub struct ExperimentService {
+    inner: Option<Arc<ExperimentInner>>,
+}
+
+impl ExperimentService {
+    pub fn new(cfg: Option<ExperimentConfig>) -> Self {
+        let inner = cfg.map(|mut cfg| {
+            cfg.fraction = if cfg.fraction.is_finite() {
+                cfg.fraction.clamp(0.0, 1.0)
+            } else {
+                0.0
+            };
+            Arc::new(ExperimentInner {
+                cfg,
+                control: ArmCounters::default(),
+                treatment: ArmCounters::default(),
+            })
+        });
+        Self { inner }
+    }
+
+    pub fn assign(&self) -> ExperimentArm {
+        match &self.inner {
+            Some(inner) if rand::rng().random_bool(inner.cfg.fraction) => ExperimentArm::Treatment,
+            _ => ExperimentArm::Control,
+        }
+    }
+
+    /// Apply treatment payload overrides; control requests are left as-is.
+    pub fn apply(&self, arm: ExperimentArm, body: &mut GeminiGenerateContentRequest) {
+        let Some(cfg) = self.treatment_config(arm) else {
+            return;
+        };
+
+        if let Some(preamble) = cfg.preamble.as_deref().filter(|p| !p.is_empty()) {
+            let part = Part {
+                text: Some(preamble.to_string()),
+                ..Default::default()
+            };
+            match body.system_instruction_mut() {
+                Some(content) => content.parts.insert(0, part),
+                slot @ None => {
+                    *slot = Some(Content {
+                        role: None,
+                        parts: vec![part],
+                        extra: BTreeMap::new(),
+                    });
+                }
+            }
+        }
+
+        if let Some(budget) = cfg.thinking_budget {
+            let generation = body.generation_config.get_or_insert_with(Default::default);
+            if generation.thinking_config.is_none() {
+                generation.thinking_config = Some(json!({ "thinkingBudget": budget }));
+            }
+        }
+    }
+
+    /// Treatment User-Agent override for `model`, if configured.
+    pub fn user_agent(&self, arm: ExperimentArm, model: &str) -> Option<String> {
+        self.treatment_config(arm)?
+            .user_agent
+            .as_deref()
+            .map(|ua| ua.replace("{model}", model))
+    }
+
+    pub fn record(&self, arm: ExperimentArm, status: StatusCode, latency: Duration) {
+        let Some(inner) = &self.inner else {
+            return;
+        };
+        match arm {
+            ExperimentArm::Control => inner.control.record(status, latency),
+            ExperimentArm::Treatment => inner.treatment.record(status, latency),
+        }
+    }
+
+    pub fn report(&self) -> Option<ExperimentReport> {
+        let inner = self.inner.as_ref()?;
+        Some(ExperimentReport {
+            name: inner.cfg.name.clone(),
+            fraction: inner.cfg.fraction,
+            control: inner.control.stats(),
+            treatment: inner.treatment.stats(),
+        })
+    }
+
+    fn treatment_config(&self, arm: ExperimentArm) -> Option<&ExperimentConfig> {
+        match (&self.inner, arm) {
+            (Some(inner), ExperimentArm::Treatment) => Some(&inner.cfg),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cfg(fraction: f64) -> ExperimentConfig {
+        ExperimentConfig {
+            name: "exp".to_string(),
+            fraction,
+            user_agent: Some("GeminiCLI/9.9.9/{model}".to_string()),
+            preamble: Some("be brief".to_string()),
+            thinking_budget: Some(128),
+        }
+    }
+
+    fn request(value: serde_json::Value) -> GeminiGenerateContentRequest {
+        serde_json::from_value(value).expect("valid request")
+    }
+
+    #[test]
+    fn fraction_bounds_pin_assignment() {
+        let none = ExperimentService::new(Some(cfg(0.0)));
+        let all = ExperimentService::new(Some(cfg(1.0)));
+        let disabled = ExperimentService::new(None);
+        for _ in 0..32 {
+            assert_eq!(none.assign(), ExperimentArm::Control);
+            assert_eq!(all.assign(), ExperimentArm::Treatment);
+            assert_eq!(disabled.assign(), ExperimentArm::Control);
+        }
+        assert!(disabled.report().is_none());
+    }
+
+    #[test]
+    fn treatment_applies_overrides_and_control_does_not() {
+        let svc = ExperimentService::new(Some(cfg(0.5)));
+        let raw = serde_json::json!({
+            "contents": [{"role": "user", "parts": [{"text": "hi"}]}],
+            "systemInstruction": {"parts": [{"text": "sys"}]}
+        });
+
+        let mut control = request(raw.clone());
+        svc.apply(ExperimentArm::Control, &mut control);
+        assert!(control.generation_config.is_none());
+        assert_eq!(svc.user_agent(ExperimentArm::Control, "m"), None);
+
+        let mut treated = request(raw);
+        svc.apply(ExperimentArm::Treatment, &mut treated);
+        let sys = treated.system_instruction.expect("system instruction");
+        assert_eq!(sys.parts[0].text.as_deref(), Some("be brief"));
+        assert_eq!(
+            treated.generation_config.and_then(|g| g.thinking_config),
+            Some(serde_json::json!({"thinkingBudget": 128}))
+        );
+        assert_eq!(
+            svc.user_agent(ExperimentArm::Treatment, "gemini-2.5-pro")
+                .as_deref(),
+            Some("GeminiCLI/9.9.9/gemini-2.5-pro")
+        );
+    }
+
+    #[test]
+    fn client_thinking_config_is_preserved() {
+        let svc = ExperimentService::new(Some(cfg(1.0)));
+        let mut body = request(serde_json::json!({
+            "contents": [],
+            "generationConfig": {"thinkingConfig": {"thinkingBudget": 0}}
+        }));
+        svc.apply(ExperimentArm::Treatment, &mut body);
+        assert_eq!(
+            body.generation_config.and_then(|g| g.thinking_config),
+            Some(serde_json::json!({"thinkingBudget": 0}))
+        );
+    }
+
+    #[test]
+    fn outcomes_are_counted_per_arm() {
+        let svc = ExperimentService::new(Some(cfg(0.5)));
+        svc.record(
+            ExperimentArm::Control,
+            StatusCode::OK,
+            Duration::from_millis(10),
+        );
+        svc.record(
+            ExperimentArm::Treatment,
+            StatusCode::TOO_MANY_REQUESTS,
+            Duration::from_millis(30),
+        );
+        svc.record(
+            ExperimentArm::Treatment,
+            StatusCode::BAD_GATEWAY,
+            Duration::from_millis(50),
+        );
+
+        let report = svc.report().expect("enabled");
+        assert_eq!(report.control.requests, 1);
+        assert_eq!(report.control.successes, 1);
+        assert_eq!(report.treatment.requests, 2);
+        assert_eq!(report.treatment.client_errors, 1);
+        assert_eq!(report.treatment.server_errors, 1);
+        assert_eq!(report.treatment.avg_latency_ms, 40);
+    }
+}
@@ -0,0 +1,183 @@
+//! Fold `streamGenerateContent` chunks into one `generateContent` response.
+//!
+//! Used when a non-streaming client hits a model the upstream only serves over
+//! SSE. Candidates are merged by `index`; adjacent plain text parts of the same
+//! kind (text or thought) are concatenated, everything else is appended as is.
+//! Response-level fields such as `usageMetadata` keep the last value seen.
+
+use pollux_schema::gemini::{Candidate, GeminiResponseBody, Part};
+use reqwest::header::CONTENT_TYPE;
+use std::collections::BTreeMap;
+
+/// Whether the upstream answered with SSE rather than a JSON body.
+pub(crate) fn is_event_stream(resp: &reqwest::Response) -> bool {
+    resp.headers()
+        .get(CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.starts_with("text/event-stream"))
+}
+
+#[derive(Debug, Default)]
+pub struct GeminiAggregator {
+    body: Option<GeminiResponseBody>,
+    candidates: BTreeMap<u32, Candidate>,
+}
+
+impl GeminiAggregator {
+    pub fn push(&mut self, mut chunk: GeminiResponseBody) {
+        for candidate in std::mem::take(&mut chunk.candidates) {
+            self.push_candidate(candidate);
+        }
+        let Some(body) = self.body.as_mut() else {
+            self.body = Some(chunk);
+            return;
+        };
+        if chunk.promptFeedback.is_some() {
+            body.promptFeedback = chunk.promptFeedback;
+        }
+        if chunk.usageMetadata.is_some() {
+            body.usageMetadata = chunk.usageMetadata;
+        }
+        if chunk.modelVersion.is_some() {
+            body.modelVersion = chunk.modelVersion;
+        }
+        if chunk.responseId.is_some() {
+            body.responseId = chunk.responseId;
+        }
+        body.extra.extend(chunk.extra);
+    }
+
+    /// The merged response, or `None` if no chunk was pushed.
+    pub fn finish(self) -> Option<GeminiResponseBody> {
+        let mut body = self.body?;
+        body.candidates = self.candidates.into_values().collect();
+        Some(body)
+    }
+
+    fn push_candidate(&mut self, mut candidate: Candidate) {
+        let index = candidate.index.unwrap_or(0);
+        let Some(merged) = self.candidates.get_mut(&index) else {
+            self.candidates.insert(index, candidate);
+            return;
+        };
+        if candidate.finish_reason.is_some() {
+            merged.finish_reason = candidate.finish_reason;
+        }
+        merged.extra.append(&mut candidate.extra);
+        let Some(content) = candidate.content else {
+            return;
+        };
+        let Some(target) = merged.content.as_mut() else {
+            merged.content = Some(content);
+            return;
+        };
+        if content.role.is_some() {
+            target.role = content.role;
+        }
+        target.extra.extend(content.extra);
+        for part in content.parts {
+            match target.parts.last_mut() {
+                Some(last) if can_join(last, &part) => {
+                    last.text
+                        .get_or_insert_default()
+                        .push_str(part.text.as_deref().unwrap_or_default());
+                    if part.thought_signature.is_some() {
+                        last.thought_signature = part.thought_signature;
+                    }
+                }
+                _ => target.parts.push(part),
+            }
+        }
+    }
+}
+
+/// `next` continues the text of `last`: both are bare text of the same kind
+/// and `last` has not been sealed by a thought signature yet.
+fn can_join(last: &Part, next: &Part) -> bool {
+    is_plain_text(last)
+        && is_plain_text(next)
+        && last.thought_signature.is_none()
+        && last.thought.unwrap_or(false) == next.thought.unwrap_or(false)
+}
+
+fn is_plain_text(part: &Part) -> bool {
+    part.text.is_some()
+        && part.part_metadata.is_none()
+        && part.inline_data.is_none()
+        && part.function_call.is_none()
+        && part.function_response.is_none()
+        && part.file_data.is_none()
+        && part.executable_code.is_none()
+        && part.code_execution_result.is_none()
+        && part.video_metadata.is_none()
+        && part.extra.is_empty()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn chunk(value: serde_json::Value) -> GeminiResponseBody {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn merges_text_deltas_and_keeps_last_usage() {
+        let mut agg = GeminiAggregator::default();
+        agg.push(chunk(json!({
+            "candidates": [{"index": 0, "content": {"role": "model", "parts": [
+                {"text": "Let me", "thought": true}
+            ]}}],
+            "usageMetadata": {"totalTokenCount": 3},
+            "responseId": "r1"
+        })));
+        agg.push(chunk(json!({
+            "candidates": [{"index": 0, "content": {"role": "model", "parts": [
+                {"text": " think", "thought": true, "thoughtSignature": "sig"},
+                {"text": "Hello"}
+            ]}}]
+        })));
+        agg.push(chunk(json!({
+            "candidates": [{"index": 0, "content": {"role": "model", "parts": [
+                {"text": ", world"},
+                {"functionCall": {"name": "f", "args": {}}}
+            ]}, "finishReason": "STOP"}],
+            "usageMetadata": {"totalTokenCount": 9}
+        })));
+
+        let body = serde_json::to_value(agg.finish().unwrap()).unwrap();
+        assert_eq!(
+            body["candidates"][0]["content"]["parts"],
+            json!([
+                {"text": "Let me think", "thought": true, "thoughtSignature": "sig"},
+                {"text": "Hello, world"},
+                {"functionCall": {"name": "f", "args": {}}}
+            ])
+        );
+        assert_eq!(body["candidates"][0]["finishReason"], "STOP");
+        assert_eq!(body["usageMetadata"]["totalTokenCount"], 9);
+        assert_eq!(body["responseId"], "r1");
+    }
+
+    #[test]
+    fn keeps_candidates_apart_by_index() {
+        let mut agg = GeminiAggregator::default();
+        agg.push(chunk(json!({"candidates": [
+            {"index": 1, "content": {"parts": [{"text": "b"}]}},
+            {"index": 0, "content": {"parts": [{"text": "a"}]}}
+        ]})));
+        agg.push(chunk(json!({"candidates": [
+            {"index": 1, "content": {"parts": [{"text": "2"}]}}
+        ]})));
+
+        let body = agg.finish().unwrap();
+        let texts: Vec<_> = body
+            .candidates
+            .iter()
+            .map(|c| c.content.as_ref().unwrap().parts[0].text.clone().unwrap())
+            .collect();
+        assert_eq!(texts, ["a", "b2"]);
+        assert!(GeminiAggregator::default().finish().is_none());
+    }
+}
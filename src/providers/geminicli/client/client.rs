@@ -1,32 +1,48 @@
+use crate::config::{ModelOverrideConfig, SystemInstructionConfig};
 use crate::error::{GeminiCliError, GeminiCliErrorBody, IsRetryable};
+use crate::providers::error_clusters::ErrorClusters;
 use crate::providers::geminicli::{GeminiCliActorHandle, GeminiContext};
+use crate::providers::manifest::{GeminiCliLease, ProviderKind};
 use crate::providers::policy::classify_upstream_error;
 use crate::providers::provider_endpoints::ProviderEndpoints;
 use crate::providers::upstream_retry::post_json_bytes_with_retry;
+use crate::providers::{model_overrides, system_instruction};
+use crate::utils::http::EgressClients;
 use crate::utils::logging::with_pretty_json_debug;
 use axum::body::Bytes;
 use backon::{ExponentialBuilder, Retryable};
 use pollux_schema::{
-    gemini::GeminiGenerateContentRequest, geminicli::VertexGenerateContentRequest,
+    gemini::{Content, GeminiEmbedRequest, GeminiGenerateContentRequest, GeminiPredictRequest},
+    geminicli::{
+        VertexCountTokensRequest, VertexEmbedContentRequest, VertexGenerateContentRequest,
+        VertexPredictRequest,
+    },
 };
 use reqwest::header::{AUTHORIZATION, HeaderMap, HeaderValue};
+use std::collections::HashMap;
 use std::time::{Duration, Instant};
 use tracing::{debug, error, info, warn};
 use url::Url;
 
 #[derive(Clone)]
 pub(crate) struct GeminiClient {
-    client: reqwest::Client,
-    stream_client: reqwest::Client,
+    clients: EgressClients,
     retry_policy: ExponentialBuilder,
     endpoints: ProviderEndpoints,
+    count_tokens_url: Url,
+    embed_content_url: Url,
+    batch_embed_contents_url: Url,
+    predict_url: Url,
     trace_header: Option<String>,
+    error_clusters: ErrorClusters,
+    system_instruction: Option<SystemInstructionConfig>,
+    stream_only_models: Vec<String>,
+    model_overrides: HashMap<String, ModelOverrideConfig>,
 }
 
 impl GeminiClient {
     pub fn new(
-        client: reqwest::Client,
-        stream_client: reqwest::Client,
+        clients: EgressClients,
         base_url: &Url,
         retry_max_times: usize,
         trace_header: Option<String>,
@@ -36,17 +52,60 @@ impl GeminiClient {
             .with_max_delay(Duration::ZERO)
             .with_max_times(retry_max_times);
         let endpoints = Self::endpoints_for_base(base_url);
+        let rpc_url = |rpc: &str| {
+            base_url
+                .join(&format!("./v1internal:{rpc}"))
+                .expect("valid endpoint path")
+        };
         info!(endpoint = %endpoints.select(false), "GeminiClient initialized");
 
         Self {
-            client,
-            stream_client,
+            clients,
             retry_policy,
             endpoints,
+            count_tokens_url: rpc_url("countTokens"),
+            embed_content_url: rpc_url("embedContent"),
+            batch_embed_contents_url: rpc_url("batchEmbedContents"),
+            predict_url: rpc_url("predict"),
             trace_header,
+            error_clusters: ErrorClusters::default(),
+            system_instruction: None,
+            stream_only_models: Vec::new(),
+            model_overrides: HashMap::new(),
         }
     }
 
+    /// Count upstream error responses into `clusters`.
+    #[must_use]
+    pub(crate) fn with_error_clusters(mut self, clusters: ErrorClusters) -> Self {
+        self.error_clusters = clusters;
+        self
+    }
+
+    /// Merge `systemInstruction` with configured text before sending.
+    #[must_use]
+    pub(crate) fn with_system_instruction(mut self, cfg: Option<SystemInstructionConfig>) -> Self {
+        self.system_instruction = cfg;
+        self
+    }
+
+    /// Serve non-streaming calls for these models from `streamGenerateContent`.
+    #[must_use]
+    pub(crate) fn with_stream_only_models(mut self, models: Vec<String>) -> Self {
+        self.stream_only_models = models;
+        self
+    }
+
+    /// Merge per-model generation parameters before sending.
+    #[must_use]
+    pub(crate) fn with_model_overrides(
+        mut self,
+        overrides: HashMap<String, ModelOverrideConfig>,
+    ) -> Self {
+        self.model_overrides = overrides;
+        self
+    }
+
     fn endpoints_for_base(base: &Url) -> ProviderEndpoints {
         ProviderEndpoints::new(
             base,
@@ -57,29 +116,135 @@ impl GeminiClient {
         )
     }
 
-    #[allow(clippy::too_many_lines)]
     pub async fn call_gemini_cli(
         &self,
         handle: &GeminiCliActorHandle,
         ctx: &GeminiContext,
         body: &GeminiGenerateContentRequest,
     ) -> Result<reqwest::Response, GeminiCliError> {
+        let upstream_ctx;
+        let ctx = if !ctx.stream && self.stream_only_models.contains(&ctx.model) {
+            upstream_ctx = GeminiContext {
+                stream: true,
+                ..ctx.clone()
+            };
+            &upstream_ctx
+        } else {
+            ctx
+        };
         let model = &ctx.model;
-        let model_mask = ctx.model_mask;
         let stream = ctx.stream;
-        let client = if stream {
-            &self.stream_client
+        let mut body = system_instruction::merged(self.system_instruction.as_ref(), body);
+        if let Some(rule) = self.model_overrides.get(model) {
+            model_overrides::apply(model, rule, body.to_mut());
+        }
+        let payload = |lease: &GeminiCliLease| {
+            let payload = VertexGenerateContentRequest {
+                model,
+                project: &lease.project_id,
+                request: &body,
+            };
+
+            with_pretty_json_debug(&payload, |pretty_payload| {
+                debug!(
+                    channel = "geminicli",
+                    lease.id = lease.id,
+                    req.model = %model,
+                    req.stream = stream,
+                    body = %pretty_payload,
+                    "[GeminiCLI] Prepared upstream payload"
+                );
+            });
+            Ok(Bytes::from(serde_json::to_vec(&payload)?))
+        };
+        self.send(handle, ctx, self.endpoints.select(stream), &payload)
+            .await
+    }
+
+    /// `models/{model}:countTokens` on the Cloud Code `countTokens` RPC.
+    ///
+    /// Leases a credential like a generate call; the response body is the
+    /// native `{ "totalTokens": .. }` object.
+    pub async fn count_tokens(
+        &self,
+        handle: &GeminiCliActorHandle,
+        ctx: &GeminiContext,
+        contents: &[Content],
+    ) -> Result<reqwest::Response, GeminiCliError> {
+        let payload = |_: &GeminiCliLease| {
+            let payload = VertexCountTokensRequest::new(&ctx.model, contents);
+            Ok(Bytes::from(serde_json::to_vec(&payload)?))
+        };
+        self.send(handle, ctx, &self.count_tokens_url, &payload)
+            .await
+    }
+
+    /// `:embedContent` / `:batchEmbedContents`, wrapped like a generate call.
+    pub async fn embed(
+        &self,
+        handle: &GeminiCliActorHandle,
+        ctx: &GeminiContext,
+        request: &GeminiEmbedRequest,
+    ) -> Result<reqwest::Response, GeminiCliError> {
+        let url = if request.is_batch() {
+            &self.batch_embed_contents_url
         } else {
-            &self.client
+            &self.embed_content_url
+        };
+        let payload = |lease: &GeminiCliLease| {
+            let payload = VertexEmbedContentRequest {
+                model: &ctx.model,
+                project: &lease.project_id,
+                request,
+            };
+            Ok(Bytes::from(serde_json::to_vec(&payload)?))
         };
-        let endpoints = &self.endpoints;
+        self.send(handle, ctx, url, &payload).await
+    }
+
+    /// Imagen `:predict`, wrapped like a generate call.
+    pub async fn predict(
+        &self,
+        handle: &GeminiCliActorHandle,
+        ctx: &GeminiContext,
+        request: &GeminiPredictRequest,
+    ) -> Result<reqwest::Response, GeminiCliError> {
+        let payload = |lease: &GeminiCliLease| {
+            let payload = VertexPredictRequest {
+                model: &ctx.model,
+                project: &lease.project_id,
+                request,
+            };
+            Ok(Bytes::from(serde_json::to_vec(&payload)?))
+        };
+        self.send(handle, ctx, &self.predict_url, &payload).await
+    }
+
+    #[allow(clippy::too_many_lines)]
+    async fn send<F>(
+        &self,
+        handle: &GeminiCliActorHandle,
+        ctx: &GeminiContext,
+        url: &Url,
+        payload: &F,
+    ) -> Result<reqwest::Response, GeminiCliError>
+    where
+        F: Fn(&GeminiCliLease) -> Result<Bytes, GeminiCliError> + Sync,
+    {
+        let model = &ctx.model;
+        let model_mask = &ctx.model_mask;
+        let route_key = ctx.route_key;
+        let pool = &ctx.pool;
+        let stream = ctx.stream;
+        let clients = &self.clients;
         let trace_header = &self.trace_header;
+        let error_clusters = &self.error_clusters;
 
         let op = {
             move || async move {
                 let start = Instant::now();
                 let assigned = handle
-                    .get_credential(model_mask)
+                    .get_credential(model_mask.clone(), route_key, pool.clone())
                     .await?
                     .ok_or(GeminiCliError::NoAvailableCredential)?;
 
@@ -92,32 +257,17 @@ impl GeminiClient {
                     "[GeminiCli] Lease acquired"
                 );
 
-                let payload = VertexGenerateContentRequest {
-                    model,
-                    project: &assigned.project_id,
-                    request: body,
-                };
-
-                with_pretty_json_debug(&payload, |pretty_payload| {
-                    debug!(
-                        channel = "geminicli",
-                        lease.id = assigned.id,
-                        req.model = %model,
-                        req.stream = stream,
-                        body = %pretty_payload,
-                        "[GeminiCLI] Prepared upstream payload"
-                    );
-                });
-
                 let mut headers = HeaderMap::new();
                 headers.insert(
                     AUTHORIZATION,
                     HeaderValue::from_str(&format!("Bearer {}", assigned.access_token))
                         .expect("invalid fixed auth header value"),
                 );
-                if let Ok(ua) =
-                    HeaderValue::from_str(&crate::providers::geminicli::geminicli_user_agent(model))
-                {
+                let user_agent = ctx
+                    .user_agent_override
+                    .clone()
+                    .unwrap_or_else(|| crate::providers::geminicli::geminicli_user_agent(model));
+                if let Ok(ua) = HeaderValue::from_str(&user_agent) {
                     headers.insert(reqwest::header::USER_AGENT, ua);
                 }
 
@@ -132,16 +282,22 @@ impl GeminiClient {
                     }
                 }
 
-                let request_body = Bytes::from(serde_json::to_vec(&payload)?);
+                let request_body = payload(&assigned)?;
 
-                let resp = post_json_bytes_with_retry(
+                let sent = Instant::now();
+                let mut resp = post_json_bytes_with_retry(
                     "GeminiCLI",
-                    client,
-                    endpoints.select(stream),
+                    &clients.select(assigned.id, assigned.proxy_url.as_ref(), stream),
+                    url,
                     Some(headers),
                     request_body,
                 )
-                .await?;
+                .await
+                .inspect_err(|e| {
+                    if e.status().is_some_and(|s| s.is_server_error()) {
+                        handle.report_outcome(assigned.id, model_mask.clone(), false);
+                    }
+                })?;
                 if !resp.status().is_success() {
                     let status = resp.status();
 
@@ -152,12 +308,20 @@ impl GeminiClient {
                             body: json,
                         },
                         |status, body| GeminiCliError::UpstreamFallbackError { status, body },
+                        |status, body| {
+                            error_clusters.record(
+                                ProviderKind::GeminiCli.label(),
+                                model,
+                                status,
+                                body,
+                            );
+                        },
                     )
                     .await;
 
                     match &action {
                         crate::providers::ActionForError::RateLimit(duration) => {
-                            handle.report_rate_limit(assigned.id, model_mask, *duration);
+                            handle.report_rate_limit(assigned.id, model_mask.clone(), *duration);
                             info!(
                                 "Project: {}, rate limited, retry in {:?}",
                                 assigned.project_id, duration
@@ -168,14 +332,18 @@ impl GeminiClient {
                             info!("Project: {}, banned", assigned.project_id);
                         }
                         crate::providers::ActionForError::ModelUnsupported => {
-                            handle.report_model_unsupported(assigned.id, model_mask);
+                            handle.report_model_unsupported(assigned.id, model_mask.clone());
                             info!("Project: {}, model unsupported", assigned.project_id);
                         }
                         crate::providers::ActionForError::Invalid => {
                             handle.report_invalid(assigned.id);
                             info!("Project: {}, invalid", assigned.project_id);
                         }
-                        crate::providers::ActionForError::None => {}
+                        crate::providers::ActionForError::None => {
+                            if status.is_server_error() {
+                                handle.report_outcome(assigned.id, model_mask.clone(), false);
+                            }
+                        }
                     }
 
                     match &final_error {
@@ -225,11 +393,14 @@ impl GeminiClient {
 
                     return Err(final_error);
                 }
+                handle.report_success(assigned.id, model_mask.clone(), sent.elapsed());
+                assigned.attach(&mut resp);
                 Ok(resp)
             }
         };
 
-        op.retry(&self.retry_policy)
+        let result = op
+            .retry(&self.retry_policy)
             .when(|err: &GeminiCliError| err.is_retryable())
             .notify(|err, dur: Duration| {
                 error!(
@@ -237,7 +408,18 @@ impl GeminiClient {
                     err.to_string(),
                     dur
                 );
+                crate::server::audit_log::note_retry();
             })
-            .await
+            .await;
+
+        match result {
+            Err(err) if err.pool_gave_up() => {
+                let after = handle
+                    .retry_after(ctx.model_mask.clone(), ctx.pool.clone())
+                    .await;
+                Err(err.with_retry_after(after))
+            }
+            other => other,
+        }
     }
 }
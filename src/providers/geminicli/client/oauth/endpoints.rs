@@ -1,19 +1,16 @@
 use super::types::UserTier;
+use crate::config::CONFIG;
 use crate::error::{OauthError, PolluxError};
+use crate::oauth_utils::{OauthTokenResponse, StandardOauth2Client};
 use crate::providers::geminicli::{
-    GEMINICLI_SCOPES, GOOGLE_AUTH_URL, GOOGLE_TOKEN_URI, LOAD_CODE_ASSIST_URL, OAUTH_CALLBACK_URL,
+    GEMINICLI_SCOPES, GOOGLE_AUTH_URL, LOAD_CODE_ASSIST_URL, OAUTH_CALLBACK_URL,
     ONBOARD_CODE_ASSIST_URL,
 };
 use oauth2::{
     AuthUrl, AuthorizationCode, Client as OAuth2Client, ClientId, ClientSecret, CsrfToken,
-    EndpointNotSet, EndpointSet, ExtraTokenFields, PkceCodeChallenge, PkceCodeVerifier,
-    RefreshToken, StandardRevocableToken, StandardTokenResponse, TokenUrl,
-    basic::{
-        BasicErrorResponse, BasicRevocationErrorResponse, BasicTokenIntrospectionResponse,
-        BasicTokenType,
-    },
+    PkceCodeChallenge, PkceCodeVerifier, RefreshToken, TokenUrl,
 };
-use serde::{Deserialize, Serialize};
+use serde::Serialize;
 use serde_json::Value;
 use std::sync::LazyLock;
 use tracing::info;
@@ -21,7 +18,8 @@ use tracing::info;
 /// Stateless Google OAuth Endpoints.
 pub(crate) struct GoogleOauthEndpoints;
 
-/// Fixed Gemini CLI OAuth client credentials (not overridable via config).
+/// Built-in Gemini CLI OAuth client; the secret can be replaced with
+/// `providers.geminicli.oauth_client_secret[_file]`.
 const GCLI_CLIENT_ID: &str =
     "681255809395-oo8ft2oprdrnp9e3aqf6av3hmdib135j.apps.googleusercontent.com";
 const GCLI_CLIENT_SECRET: &str = "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";
@@ -96,8 +94,8 @@ impl GoogleOauthEndpoints {
     pub(crate) async fn refresh_access_token(
         refresh_token: &str,
         http_client: reqwest::Client,
-    ) -> Result<GoogleTokenResponse, OauthError> {
-        let token_result: GoogleTokenResponse = Self::client()
+    ) -> Result<OauthTokenResponse, OauthError> {
+        let token_result: OauthTokenResponse = Self::client()
             .exchange_refresh_token(&RefreshToken::new(refresh_token.to_string()))
             .request_async(&http_client)
             .await?;
@@ -109,8 +107,8 @@ impl GoogleOauthEndpoints {
         code: AuthorizationCode,
         verifier: PkceCodeVerifier,
         http_client: reqwest::Client,
-    ) -> Result<GoogleTokenResponse, OauthError> {
-        let token_result: GoogleTokenResponse = Self::client()
+    ) -> Result<OauthTokenResponse, OauthError> {
+        let token_result: OauthTokenResponse = Self::client()
             .exchange_code(code)
             .set_pkce_verifier(verifier)
             .request_async(&http_client)
@@ -129,7 +127,7 @@ impl GoogleOauthEndpoints {
             metadata: ClientMetadata::default(),
         };
         let resp = http_client
-            .post(LOAD_CODE_ASSIST_URL)
+            .post(LOAD_CODE_ASSIST_URL.as_str())
             .bearer_auth(access_token.as_ref())
             .json(&body)
             .send()
@@ -160,7 +158,7 @@ impl GoogleOauthEndpoints {
         };
 
         let resp = http_client
-            .post(ONBOARD_CODE_ASSIST_URL)
+            .post(ONBOARD_CODE_ASSIST_URL.as_str())
             .bearer_auth(access_token.as_ref())
             .json(&request)
             .send()
@@ -178,37 +176,20 @@ impl GoogleOauthEndpoints {
 /// Build the Google `OAuth2` client.
 fn build_oauth2_client() -> Result<GoogleOauth2Client, PolluxError> {
     let client = OAuth2Client::new(ClientId::new(GCLI_CLIENT_ID.to_string()))
-        .set_client_secret(ClientSecret::new(GCLI_CLIENT_SECRET.to_string()))
+        .set_client_secret(ClientSecret::new(
+            CONFIG
+                .providers
+                .geminicli
+                .oauth_client_secret
+                .clone()
+                .unwrap_or_else(|| GCLI_CLIENT_SECRET.to_string()),
+        ))
         .set_auth_uri(AuthUrl::new(GOOGLE_AUTH_URL.to_string())?)
-        .set_token_uri(TokenUrl::new(GOOGLE_TOKEN_URI.to_string())?)
+        .set_token_uri(TokenUrl::new(
+            CONFIG.providers.geminicli.oauth_token_url.to_string(),
+        )?)
         .set_redirect_uri(OAUTH_CALLBACK_URL.clone());
     Ok(client)
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
-pub(crate) struct GoogleTokenField {
-    #[serde(rename = "id_token")]
-    pub id_token: Option<String>,
-}
-impl ExtraTokenFields for GoogleTokenField {}
-
-pub(crate) type GoogleTokenResponse = StandardTokenResponse<GoogleTokenField, BasicTokenType>;
-
-pub(crate) type GoogleOauth2Client<
-    HasAuthUrl = EndpointSet,
-    HasDeviceAuthUrl = EndpointNotSet,
-    HasIntrospectionUrl = EndpointNotSet,
-    HasRevocationUrl = EndpointNotSet,
-    HasTokenUrl = EndpointSet,
-> = OAuth2Client<
-    BasicErrorResponse,
-    GoogleTokenResponse,
-    BasicTokenIntrospectionResponse,
-    StandardRevocableToken,
-    BasicRevocationErrorResponse,
-    HasAuthUrl,
-    HasDeviceAuthUrl,
-    HasIntrospectionUrl,
-    HasRevocationUrl,
-    HasTokenUrl,
->;
+pub(crate) type GoogleOauth2Client = StandardOauth2Client;
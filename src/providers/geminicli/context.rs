@@ -1,7 +1,25 @@
+use crate::model_catalog::ModelCapabilities;
+use crate::providers::experiment::ExperimentArm;
+
 #[derive(Debug, Clone)]
 pub struct GeminiContext {
     pub model: String,
     pub stream: bool,
     pub path: String,
-    pub model_mask: u64,
+    pub model_mask: ModelCapabilities,
+    /// Experiment arm this request was assigned to (control when no experiment is configured).
+    pub experiment_arm: ExperimentArm,
+    /// Upstream User-Agent override from the experiment treatment arm.
+    pub user_agent_override: Option<String>,
+    /// Hash of `x-pollux-session`; pins the conversation to one credential while healthy.
+    pub route_key: Option<u64>,
+    /// Label from `x-pollux-pool`; only credentials carrying it are leased.
+    pub pool: Option<String>,
+}
+
+impl GeminiContext {
+    /// `models/{model}:countTokens` rather than a generate call.
+    pub fn is_count_tokens(&self) -> bool {
+        self.path.ends_with(":countTokens")
+    }
 }
@@ -0,0 +1,166 @@
+//! Gemini CLI probes for `pollux doctor`.
+
+use super::client::oauth::OAUTH_RETRY_POLICY;
+use super::manager::{CredentialOps, GeminiCli};
+use super::resource::GeminiCliResource;
+use super::{GOOGLE_AUTH_LIB_USER_AGENT, geminicli_user_agent};
+use crate::config::GeminiCliResolvedConfig;
+use crate::db::DbActorHandle;
+use crate::providers::doctor::{
+    DoctorArgs, DoctorReport, PROBE_PROMPT, SseProbe, db_id, expect_json, http_client, probe_sse,
+    token_validity,
+};
+use crate::providers::traits::oauth_worker::refresh_credential;
+use crate::providers::traits::provider::{CredentialStore, ProviderResource};
+use pollux_schema::{
+    gemini::GeminiGenerateContentRequest, geminicli::VertexGenerateContentRequest,
+};
+use serde_json::{Value, json};
+use std::time::Instant;
+use url::Url;
+
+pub(crate) async fn diagnose(
+    report: &mut DoctorReport,
+    cfg: &GeminiCliResolvedConfig,
+    db: &DbActorHandle,
+    args: &DoctorArgs,
+) {
+    let id = args.id;
+    let Some(mut cred) = report
+        .step("load", async {
+            let row = db
+                .get_geminicli_by_id(db_id(id)?)
+                .await
+                .map_err(|e| e.to_string())?;
+            let cred = GeminiCliResource::from(row);
+            let detail = format!(
+                "project {}, {}",
+                cred.project_id(),
+                token_validity(cred.expiry())
+            );
+            Ok((cred, detail))
+        })
+        .await
+    else {
+        return;
+    };
+
+    if args.refresh {
+        let client = http_client(cfg.proxy.as_ref(), Some(GOOGLE_AUTH_LIB_USER_AGENT));
+        report
+            .step("refresh", async {
+                refresh_credential(
+                    &GeminiCli::new(cfg),
+                    client,
+                    *OAUTH_RETRY_POLICY,
+                    &mut cred,
+                    None,
+                )
+                .await
+                .map_err(|e| e.to_string())?;
+                CredentialOps::new(db.clone())
+                    .save_refreshed(id, &cred)
+                    .await
+                    .map_err(|e| format!("refreshed but not saved: {e}"))?;
+                Ok(((), format!("{}, saved", token_validity(cred.expiry()))))
+            })
+            .await;
+    } else {
+        report.skip("refresh");
+    }
+
+    let model = args
+        .model
+        .clone()
+        .or_else(|| cfg.model_list.first().cloned())
+        .unwrap_or_default();
+    let client = http_client(cfg.proxy.as_ref(), Some(&geminicli_user_agent(&model)));
+    let base = &cfg.custom_api_url;
+    let token = cred.access_token();
+    let project = cred.project_id();
+
+    report
+        .step("models", async {
+            let resp = client
+                .post(endpoint(base, "retrieveUserQuota", None))
+                .bearer_auth(token)
+                .json(&json!({ "project": project }))
+                .send()
+                .await
+                .map_err(|e| e.to_string())?;
+            let body = expect_json(resp).await?;
+            let models: Vec<&str> = body
+                .get("buckets")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+                .filter_map(|b| b.get("modelId").and_then(Value::as_str))
+                .collect();
+            Ok((
+                (),
+                format!("{} models: {}", models.len(), models.join(", ")),
+            ))
+        })
+        .await;
+
+    report
+        .step("generate", async {
+            let resp = generate(&client, base, token, project, &model, false).await?;
+            let body = expect_json(resp).await?;
+            let finish = body
+                .pointer("/response/candidates/0/finishReason")
+                .and_then(Value::as_str)
+                .unwrap_or("no candidate");
+            Ok(((), format!("{model}: {finish}")))
+        })
+        .await;
+
+    report
+        .step("stream", async {
+            let sent = Instant::now();
+            let resp = generate(&client, base, token, project, &model, true).await?;
+            let probe: SseProbe = probe_sse(resp, sent).await?;
+            Ok(((), probe.summary()))
+        })
+        .await;
+}
+
+fn endpoint(base: &Url, method: &str, query: Option<&str>) -> Url {
+    let mut url = base
+        .join(&format!("./v1internal:{method}"))
+        .expect("valid Gemini CLI endpoint path");
+    url.set_query(query);
+    url
+}
+
+async fn generate(
+    client: &reqwest::Client,
+    base: &Url,
+    token: &str,
+    project: &str,
+    model: &str,
+    stream: bool,
+) -> Result<reqwest::Response, String> {
+    let request: GeminiGenerateContentRequest = serde_json::from_value(json!({
+        "contents": [{ "role": "user", "parts": [{ "text": PROBE_PROMPT }] }],
+        "generationConfig": { "maxOutputTokens": 1 },
+    }))
+    .map_err(|e| e.to_string())?;
+    let payload = VertexGenerateContentRequest {
+        model,
+        project,
+        request: &request,
+    };
+    let url = if stream {
+        endpoint(base, "streamGenerateContent", Some("alt=sse"))
+    } else {
+        endpoint(base, "generateContent", None)
+    };
+    client
+        .post(url)
+        .bearer_auth(token)
+        .json(&payload)
+        .send()
+        .await
+        .map_err(|e| e.to_string())
+}
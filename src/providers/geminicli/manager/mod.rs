@@ -1,6 +1,187 @@
-mod actor;
+mod onboard;
 mod ops;
 
-pub use crate::providers::traits::scheduler::CredentialId;
-pub use actor::GeminiCliActorHandle;
-pub(in crate::providers) use actor::spawn;
+use super::client::oauth::{OAUTH_RETRY_POLICY, endpoints::GoogleOauthEndpoints};
+use super::resource::GeminiCliResource;
+use super::{GOOGLE_AUTH_LIB_USER_AGENT, SUPPORTED_MODEL_MASK, SUPPORTED_MODEL_NAMES};
+use crate::config::GeminiCliResolvedConfig;
+use crate::error::{OauthError, PolluxError};
+use crate::model_catalog::ModelCapabilities;
+use crate::oauth_utils::OauthTokenResponse;
+use crate::providers::ModelProber;
+use crate::providers::manifest::ProviderKind;
+use crate::providers::traits::actor::ProviderActorHandle;
+use crate::providers::traits::provider::{
+    OauthWorkerSettings, PoolSettings, Provider, ProviderResource,
+};
+use backon::ExponentialBuilder;
+use governor::DefaultDirectRateLimiter;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+
+pub(crate) use ops::CredentialOps;
+
+/// Gemini CLI (Cloud Code) accounts on the shared pool actor. Ingests also
+/// resolve the account's companion project and, when configured, probe
+/// which models it serves.
+pub struct GeminiCli {
+    prober: Option<ModelProber>,
+}
+
+impl GeminiCli {
+    pub(crate) fn new(cfg: &GeminiCliResolvedConfig) -> Self {
+        Self {
+            prober: cfg.capability_probe.map(|probe| {
+                ModelProber::new(probe, &cfg.custom_api_url, SUPPORTED_MODEL_NAMES.clone())
+            }),
+        }
+    }
+}
+
+impl Provider for GeminiCli {
+    const NAME: &'static str = "GeminiCli";
+    const KIND: ProviderKind = ProviderKind::GeminiCli;
+
+    type Resource = GeminiCliResource;
+    type Store = CredentialOps;
+
+    fn supported_models(&self) -> (ModelCapabilities, Vec<String>) {
+        (SUPPORTED_MODEL_MASK.clone(), SUPPORTED_MODEL_NAMES.clone())
+    }
+
+    fn oauth_retry_policy(&self) -> ExponentialBuilder {
+        *OAUTH_RETRY_POLICY
+    }
+
+    fn refresh_access_token(
+        &self,
+        refresh_token: &str,
+        http_client: reqwest::Client,
+    ) -> impl Future<Output = Result<OauthTokenResponse, OauthError>> + Send {
+        GoogleOauthEndpoints::refresh_access_token(refresh_token, http_client)
+    }
+
+    async fn onboard(
+        &self,
+        cred: &mut GeminiCliResource,
+        http_client: reqwest::Client,
+        limiter: &DefaultDirectRateLimiter,
+    ) -> Result<ModelCapabilities, PolluxError> {
+        if cred.sub().is_empty() {
+            return Err(PolluxError::UnexpectedError(
+                "Missing sub in id_token claims".into(),
+            ));
+        }
+
+        let (project_id, tier) =
+            onboard::ensure_companion_project(cred.access_token(), http_client.clone()).await?;
+        cred.set_project_id(project_id);
+        cred.set_tier(tier.as_str().to_string());
+
+        Ok(match &self.prober {
+            Some(prober) => onboard::probe_models(prober, &http_client, limiter, cred).await,
+            None => ModelCapabilities::none(),
+        })
+    }
+}
+
+pub type GeminiCliActorHandle = ProviderActorHandle<GeminiCli>;
+
+pub(in crate::providers) async fn spawn(
+    db: crate::db::DbActorHandle,
+    cfg: Arc<GeminiCliResolvedConfig>,
+) -> GeminiCliActorHandle {
+    let settings = PoolSettings {
+        endpoint: cfg.custom_api_url.to_string(),
+        retry_max_times: cfg.retry_max_times,
+        auto_disable: cfg.auto_disable,
+        health: cfg.health,
+        min_token_validity: Duration::from_secs(cfg.min_token_validity_secs),
+        stale_grace: Duration::from_secs(cfg.stale_grace_secs),
+        capability_restore: Duration::from_secs(cfg.capability_restore_secs),
+        max_concurrent_per_credential: cfg.max_concurrent_per_credential,
+        tier_weights: cfg.tier_weights.clone(),
+        scheduling_policy: cfg.scheduling_policy,
+        priority_reserve: cfg.priority_reserve,
+        lease_wait: Duration::from_millis(cfg.lease_wait_ms),
+        oauth: OauthWorkerSettings {
+            proxy: cfg.proxy.clone(),
+            user_agent: Some(GOOGLE_AUTH_LIB_USER_AGENT),
+            enable_multiplexing: cfg.enable_multiplexing,
+            http_client: cfg.http_client,
+            oauth_tps: cfg.oauth_tps,
+        },
+    };
+    crate::providers::traits::actor::spawn(db, GeminiCli::new(&cfg), settings).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::RefreshTokenSeed;
+    use base64::Engine as _;
+    use chrono::Utc;
+    use serde_json::{Value, json};
+
+    fn make_test_jwt(payload: &Value) -> String {
+        // Signature is irrelevant; production code only base64url-decodes the payload.
+        let header = base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(br#"{"alg":"none"}"#);
+        let payload_bytes = serde_json::to_vec(payload).expect("serialize payload");
+        let payload_b64 = base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(payload_bytes);
+        format!("{header}.{payload_b64}.sig")
+    }
+
+    #[test]
+    fn token_response_carries_id_token_identity() {
+        let id_token = make_test_jwt(&json!({
+            "email": "test@example.com",
+            "sub": "google-subject-1",
+        }));
+        let token_response: OauthTokenResponse = serde_json::from_value(json!({
+            "access_token": "new-access-token",
+            "expires_in": 3600,
+            "token_type": "bearer",
+            "id_token": id_token,
+        }))
+        .expect("token response deserializes");
+        let seed = RefreshTokenSeed::new("seed-rt").expect("valid seed");
+
+        let cred = GeminiCliResource::try_from_oauth_token_response(&token_response, Some(&seed))
+            .expect("credential from token response");
+
+        assert!(cred.expiry() > Utc::now() + chrono::Duration::minutes(30));
+        assert_eq!(cred.access_token(), "new-access-token");
+        assert_eq!(cred.refresh_token(), "seed-rt");
+        assert_eq!(cred.email(), Some("test@example.com"));
+        assert_eq!(cred.sub(), "google-subject-1");
+        // The project comes from onboarding, after the token grant.
+        assert!(!cred.has_identity());
+    }
+
+    #[test]
+    fn refresh_payload_preserves_email_without_id_token() {
+        let mut cred = GeminiCliResource::from_payload(json!({
+            "email": "old@example.com",
+            "sub": "google-subject-1",
+            "project_id": "project-b",
+            "refresh_token": "refresh-token",
+            "expiry": Utc::now() + chrono::Duration::minutes(10),
+        }))
+        .expect("valid credential payload");
+
+        let token_response: OauthTokenResponse = serde_json::from_value(json!({
+            "access_token": "new-token",
+            "expires_in": 3600,
+            "token_type": "bearer",
+        }))
+        .expect("token response deserializes");
+        cred.update_credential(&token_response)
+            .expect("refresh payload applied");
+
+        assert_eq!(cred.email(), Some("old@example.com"));
+        assert_eq!(cred.access_token(), "new-token");
+        assert_eq!(cred.project_id(), "project-b");
+        assert!(cred.has_identity());
+    }
+}
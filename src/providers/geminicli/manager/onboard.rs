@@ -0,0 +1,146 @@
+//! Companion project resolution and capability probe run when a Gemini CLI
+//! credential is ingested.
+
+use crate::error::{OauthError, PolluxError};
+use crate::model_catalog::ModelCapabilities;
+use crate::providers::ModelProber;
+use crate::providers::geminicli::client::oauth::{
+    ops::GoogleOauthOps,
+    types::{LoadCodeAssistResponse, OnboardOperationResponse, UserTier},
+};
+use crate::providers::geminicli::geminicli_user_agent;
+use crate::providers::geminicli::resource::GeminiCliResource;
+use crate::providers::traits::provider::ProviderResource;
+use crate::utils::logging::payload_logging_enabled;
+use governor::DefaultDirectRateLimiter;
+use pollux_schema::geminicli::VertexGenerateContentRequest;
+use reqwest::header::{AUTHORIZATION, HeaderMap, HeaderValue, USER_AGENT};
+use std::time::Duration;
+use tokio::time::sleep;
+use tracing::{debug, info};
+
+pub(super) async fn probe_models(
+    prober: &ModelProber,
+    client: &reqwest::Client,
+    limiter: &DefaultDirectRateLimiter,
+    cred: &GeminiCliResource,
+) -> ModelCapabilities {
+    let headers = |model: &str| {
+        let mut headers = HeaderMap::new();
+        if let Ok(auth) = HeaderValue::from_str(&format!("Bearer {}", cred.access_token())) {
+            headers.insert(AUTHORIZATION, auth);
+        }
+        if let Ok(ua) = HeaderValue::from_str(&geminicli_user_agent(model)) {
+            headers.insert(USER_AGENT, ua);
+        }
+        headers
+    };
+    let generate_body = |model: &str, request: &_| {
+        serde_json::to_vec(&VertexGenerateContentRequest {
+            model,
+            project: cred.project_id(),
+            request,
+        })
+    };
+    prober
+        .probe("GeminiCLI", client, limiter, headers, generate_body)
+        .await
+}
+
+/// Resolves (or provisions) the companion project and reports the account's
+/// effective quota tier alongside it.
+pub(super) async fn ensure_companion_project(
+    access_token: &str,
+    client: reqwest::Client,
+) -> Result<(String, UserTier), PolluxError> {
+    let load_json =
+        GoogleOauthOps::load_code_assist_with_retry(access_token, client.clone()).await?;
+    if payload_logging_enabled() {
+        debug!(body = %load_json, "loadCodeAssist upstream body");
+    }
+
+    let load_resp: LoadCodeAssistResponse =
+        serde_json::from_value(load_json.clone()).map_err(PolluxError::JsonError)?;
+
+    load_resp.ensure_eligible(load_json)?;
+
+    let tier = load_resp.resolve_effective_tier();
+
+    if let Some(existing_project_id) = load_resp.cloudaicompanion_project {
+        info!(
+            project_id = %existing_project_id,
+            tier = %tier.as_str(),
+            "loadCodeAssist resolved companion project id"
+        );
+        return Ok((existing_project_id, tier));
+    }
+
+    info!(
+        tier = %tier.as_str(),
+        "No existing companion project found; starting onboarding"
+    );
+    let new_project_id = perform_onboarding(access_token, tier.clone(), client).await?;
+
+    info!(
+        project_id = %new_project_id,
+        "Companion project provisioning completed"
+    );
+    Ok((new_project_id, tier))
+}
+
+async fn perform_onboarding(
+    access_token: &str,
+    tier: UserTier,
+    client: reqwest::Client,
+) -> Result<String, PolluxError> {
+    const MAX_ATTEMPTS: usize = 5;
+    const RETRY_DELAY: Duration = Duration::from_secs(5);
+    let mut last_resp: Option<serde_json::Value> = None;
+
+    for attempt in 1..=MAX_ATTEMPTS {
+        let resp_json = GoogleOauthOps::onboard_code_assist_with_retry(
+            access_token,
+            tier.clone(),
+            None,
+            client.clone(),
+        )
+        .await?;
+        if payload_logging_enabled() {
+            debug!(body = %resp_json, "onboardCodeAssist upstream body");
+        }
+
+        last_resp = Some(resp_json.clone());
+        let op_resp: OnboardOperationResponse =
+            serde_json::from_value(resp_json.clone()).map_err(PolluxError::JsonError)?;
+
+        if op_resp.done {
+            return op_resp
+                .response
+                .and_then(|r| r.project_details)
+                .map(|p| p.id)
+                .ok_or_else(|| {
+                    OauthError::Flow {
+                        code: "ONBOARD_FAILED".to_string(),
+                        message: "Onboarding completed but returned no project ID".to_string(),
+                        details: Some(resp_json),
+                    }
+                    .into()
+                });
+        }
+
+        if attempt < MAX_ATTEMPTS {
+            info!(
+                "onboardCodeAssist pending (attempt {}/{}), retrying in {:?}...",
+                attempt, MAX_ATTEMPTS, RETRY_DELAY
+            );
+            sleep(RETRY_DELAY).await;
+        }
+    }
+
+    Err(OauthError::Flow {
+        code: "ONBOARD_TIMEOUT".to_string(),
+        message: "Companion project provisioning timed out".to_string(),
+        details: last_resp,
+    }
+    .into())
+}
@@ -1,7 +1,13 @@
-use crate::db::{DbActorHandle, GeminiCliCreate, GeminiCliPatch, ProviderCreate, ProviderPatch};
+use crate::db::{
+    DbActorHandle, GeminiCliCreate, GeminiCliPatch, ProviderCreate, ProviderDelete,
+    ProviderIdentity, ProviderPatch,
+};
 use crate::error::PolluxError;
+use crate::providers::credential_view::CredentialView;
 use crate::providers::geminicli::resource::GeminiCliResource;
-use crate::providers::traits::scheduler::CredentialId;
+use crate::providers::seed::StoredSeed;
+use crate::providers::traits::provider::{CredentialStore, ProviderResource};
+use crate::providers::traits::scheduler::{CredentialId, Schedulable};
 
 #[derive(Clone)]
 pub struct CredentialOps {
@@ -9,23 +15,7 @@ pub struct CredentialOps {
 }
 
 impl CredentialOps {
-    pub fn new(db: DbActorHandle) -> Self {
-        Self { db }
-    }
-
-    pub async fn load_active(&self) -> Result<Vec<(CredentialId, GeminiCliResource)>, PolluxError> {
-        let rows = self.db.list_active_geminicli().await?;
-        let mut result = Vec::with_capacity(rows.len());
-        for row in rows {
-            let id = u64::try_from(row.id).map_err(|_| {
-                PolluxError::UnexpectedError(format!("Invalid credential id {}", row.id))
-            })?;
-            result.push((id, row.into()));
-        }
-        Ok(result)
-    }
-
-    pub async fn upsert(&self, cred: GeminiCliResource) -> Result<CredentialId, PolluxError> {
+    async fn upsert(&self, cred: GeminiCliResource) -> Result<CredentialId, PolluxError> {
         if cred.sub().is_empty() {
             return Err(PolluxError::UnexpectedError(
                 "GeminiCli credential missing sub (id_token claims)".to_string(),
@@ -38,7 +28,7 @@ impl CredentialOps {
             .map_err(|_| PolluxError::UnexpectedError(format!("Invalid credential id {id}")))
     }
 
-    pub async fn update_by_id(
+    async fn update_by_id(
         &self,
         id: CredentialId,
         patch: GeminiCliPatch,
@@ -52,8 +42,83 @@ impl CredentialOps {
             .await?;
         Ok(())
     }
+}
+
+impl CredentialStore<GeminiCliResource> for CredentialOps {
+    fn new(db: DbActorHandle) -> Self {
+        Self { db }
+    }
+
+    async fn load_active(&self) -> Result<Vec<(CredentialId, GeminiCliResource)>, PolluxError> {
+        let rows = self.db.list_active_geminicli().await?;
+        let mut result = Vec::with_capacity(rows.len());
+        for row in rows {
+            let id = u64::try_from(row.id).map_err(|_| {
+                PolluxError::UnexpectedError(format!("Invalid credential id {}", row.id))
+            })?;
+            result.push((id, row.into()));
+        }
+        Ok(result)
+    }
+
+    /// Store a freshly onboarded credential. An account (`sub`) that already
+    /// has a row keeps its id: the row is updated in place and re-enabled.
+    async fn store_onboarded(
+        &self,
+        cred: GeminiCliResource,
+    ) -> Result<StoredSeed<GeminiCliResource>, PolluxError> {
+        let identity = ProviderIdentity::GeminiCli {
+            sub: cred.sub().to_string(),
+        };
+        let existing = self.db.find_by_identity(identity).await?;
+        let Some(id) = existing
+            .map(|id| {
+                u64::try_from(id).map_err(|_| {
+                    PolluxError::UnexpectedError(format!("Invalid credential id {id}"))
+                })
+            })
+            .transpose()?
+        else {
+            let id = self.upsert(cred.clone()).await?;
+            return Ok(StoredSeed {
+                id,
+                credential: cred,
+                existing: false,
+            });
+        };
+
+        let patch = GeminiCliPatch {
+            email: cred.email().map(ToString::to_string),
+            refresh_token: Some(cred.refresh_token().to_string()),
+            access_token: Some(cred.access_token().to_string()),
+            expiry: Some(cred.expiry()),
+            tier: cred.tier().map(ToString::to_string),
+            status: Some(true),
+            labels: (!cred.labels().is_empty()).then(|| cred.labels().to_vec()),
+        };
+        self.update_by_id(id, patch).await?;
+        Ok(StoredSeed {
+            id,
+            credential: self.get_by_id(id).await?,
+            existing: true,
+        })
+    }
 
-    pub async fn set_status(&self, id: CredentialId, status: bool) -> Result<(), PolluxError> {
+    async fn save_refreshed(
+        &self,
+        id: CredentialId,
+        cred: &GeminiCliResource,
+    ) -> Result<(), PolluxError> {
+        let patch = GeminiCliPatch {
+            email: cred.email().map(ToString::to_string),
+            access_token: Some(cred.access_token().to_string()),
+            expiry: Some(cred.expiry()),
+            ..Default::default()
+        };
+        self.update_by_id(id, patch).await
+    }
+
+    async fn set_status(&self, id: CredentialId, status: bool) -> Result<(), PolluxError> {
         // Keep the same validation semantics: the DB layer uses `i64` ids.
         let _ = i64::try_from(id)
             .map_err(|_| PolluxError::UnexpectedError(format!("Invalid credential id {id}")))?;
@@ -63,4 +128,28 @@ impl CredentialOps {
         };
         self.db.patch(ProviderPatch::GeminiCli { id, patch }).await
     }
+
+    /// All stored rows (any status), for admin listing.
+    async fn load_all_views(&self) -> Result<Vec<CredentialView>, PolluxError> {
+        let rows = self.db.list_geminicli().await?;
+        Ok(rows.into_iter().map(CredentialView::from).collect())
+    }
+
+    async fn get_by_id(&self, id: CredentialId) -> Result<GeminiCliResource, PolluxError> {
+        let db_id = i64::try_from(id)
+            .map_err(|_| PolluxError::UnexpectedError(format!("Invalid credential id {id}")))?;
+        match self.db.get_geminicli_by_id(db_id).await {
+            Ok(row) => Ok(row.into()),
+            Err(PolluxError::DatabaseError(sqlx::Error::RowNotFound)) => {
+                Err(PolluxError::NotFound(format!("gemini_cli record id={id}")))
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    async fn delete(&self, id: CredentialId) -> Result<(), PolluxError> {
+        let db_id = i64::try_from(id)
+            .map_err(|_| PolluxError::UnexpectedError(format!("Invalid credential id {id}")))?;
+        self.db.delete(ProviderDelete::GeminiCli(db_id)).await
+    }
 }
@@ -1,5 +1,6 @@
 pub mod client;
 mod context;
+pub(crate) mod doctor;
 #[cfg(not(feature = "bench"))]
 mod manager;
 #[cfg(feature = "bench")]
@@ -10,12 +11,14 @@ mod resource;
 #[cfg(feature = "bench")]
 pub mod resource;
 mod thoughtsig;
-mod workers;
 
 pub use context::GeminiContext;
 pub use manager::GeminiCliActorHandle;
 pub(in crate::providers) use manager::spawn;
-pub(crate) use model_mask::{SUPPORTED_MODEL_MASK, SUPPORTED_MODEL_NAMES, model_mask};
+pub(crate) use model_mask::{
+    EMBEDDING_MODEL_NAMES, IMAGE_MODEL_NAMES, SUPPORTED_MODEL_MASK, SUPPORTED_MODEL_NAMES,
+    embedding_model_mask, image_model_mask, model_mask,
+};
 pub use thoughtsig::GeminiThoughtSigService;
 
 use crate::config::CONFIG;
@@ -40,13 +43,24 @@ pub(crate) fn geminicli_user_agent(model: &str) -> String {
 /// OAuth token refresh in the original Gemini CLI.
 pub(crate) const GOOGLE_AUTH_LIB_USER_AGENT: &str = "google-api-nodejs-client/9.15.1";
 
-/// Fixed Google OAuth endpoints used by Gemini CLI.
+/// Fixed Google OAuth consent page used by Gemini CLI.
 const GOOGLE_AUTH_URL: &str = "https://accounts.google.com/o/oauth2/v2/auth";
-const GOOGLE_TOKEN_URI: &str = "https://oauth2.googleapis.com/token";
 
-/// Fixed Cloud Code Gemini endpoints used by Gemini CLI.
-const LOAD_CODE_ASSIST_URL: &str = "https://cloudcode-pa.googleapis.com/v1internal:loadCodeAssist";
-const ONBOARD_CODE_ASSIST_URL: &str = "https://cloudcode-pa.googleapis.com/v1internal:onboardUser";
+/// Cloud Code onboarding endpoints under `providers.geminicli.custom_api_url`.
+static LOAD_CODE_ASSIST_URL: LazyLock<String> = LazyLock::new(|| code_assist_url("loadCodeAssist"));
+static ONBOARD_CODE_ASSIST_URL: LazyLock<String> = LazyLock::new(|| code_assist_url("onboardUser"));
+
+fn code_assist_url(rpc: &str) -> String {
+    format!(
+        "{}/v1internal:{rpc}",
+        CONFIG
+            .providers
+            .geminicli
+            .custom_api_url
+            .as_str()
+            .trim_end_matches('/')
+    )
+}
 
 static OAUTH_CALLBACK_URL: LazyLock<RedirectUrl> = LazyLock::new(|| {
     RedirectUrl::new(format!(
@@ -1,5 +1,5 @@
 use crate::config::CONFIG;
-use crate::model_catalog;
+use crate::model_catalog::{self, MODEL_REGISTRY, ModelCapabilities};
 use std::collections::HashSet;
 use std::sync::LazyLock;
 
@@ -13,21 +13,73 @@ pub(crate) static SUPPORTED_MODEL_NAMES: LazyLock<Vec<String>> = LazyLock::new(|
         .collect()
 });
 
-pub(crate) static SUPPORTED_MODEL_MASK: LazyLock<u64> = LazyLock::new(|| {
-    let mut mask = 0u64;
-    for name in SUPPORTED_MODEL_NAMES.iter() {
-        if let Some(bit) = model_catalog::mask(name) {
-            mask |= bit;
-        }
-    }
-    mask
+/// Embedding models; a name in both lists is treated as a generation model.
+pub(crate) static EMBEDDING_MODEL_NAMES: LazyLock<Vec<String>> = LazyLock::new(|| {
+    let cfg = CONFIG.geminicli();
+
+    let mut seen = SUPPORTED_MODEL_NAMES
+        .iter()
+        .cloned()
+        .collect::<HashSet<_>>();
+    cfg.embedding_model_list
+        .into_iter()
+        .filter(|name| seen.insert(name.clone()))
+        .collect()
+});
+
+/// Image generation models; names already listed above keep their first role.
+pub(crate) static IMAGE_MODEL_NAMES: LazyLock<Vec<String>> = LazyLock::new(|| {
+    let cfg = CONFIG.geminicli();
+
+    let mut seen = SUPPORTED_MODEL_NAMES
+        .iter()
+        .chain(EMBEDDING_MODEL_NAMES.iter())
+        .cloned()
+        .collect::<HashSet<_>>();
+    cfg.image_model_list
+        .into_iter()
+        .filter(|name| seen.insert(name.clone()))
+        .collect()
+});
+
+static GENERATION_MODEL_MASK: LazyLock<ModelCapabilities> =
+    LazyLock::new(|| names_to_mask(&SUPPORTED_MODEL_NAMES));
+
+static EMBEDDING_MODEL_MASK: LazyLock<ModelCapabilities> =
+    LazyLock::new(|| names_to_mask(&EMBEDDING_MODEL_NAMES));
+
+static IMAGE_MODEL_MASK: LazyLock<ModelCapabilities> =
+    LazyLock::new(|| names_to_mask(&IMAGE_MODEL_NAMES));
+
+/// Every model a Gemini CLI credential is queued for: generation, embedding
+/// and image generation.
+pub(crate) static SUPPORTED_MODEL_MASK: LazyLock<ModelCapabilities> = LazyLock::new(|| {
+    GENERATION_MODEL_MASK
+        .merge(&EMBEDDING_MODEL_MASK)
+        .merge(&IMAGE_MODEL_MASK)
 });
 
-pub(crate) fn model_mask(name: &str) -> Option<u64> {
+fn names_to_mask(names: &[String]) -> ModelCapabilities {
+    names
+        .iter()
+        .filter_map(|name| MODEL_REGISTRY.get_index(name))
+        .collect()
+}
+
+/// Bit of a generation model (`generateContent`, `countTokens`, chat).
+pub(crate) fn model_mask(name: &str) -> Option<ModelCapabilities> {
+    let bit = model_catalog::mask(name)?;
+    GENERATION_MODEL_MASK.intersects(&bit).then_some(bit)
+}
+
+/// Bit of an embedding model (`embedContent`, `batchEmbedContents`).
+pub(crate) fn embedding_model_mask(name: &str) -> Option<ModelCapabilities> {
+    let bit = model_catalog::mask(name)?;
+    EMBEDDING_MODEL_MASK.intersects(&bit).then_some(bit)
+}
+
+/// Bit of an image generation model (`predict`, image `generateContent`).
+pub(crate) fn image_model_mask(name: &str) -> Option<ModelCapabilities> {
     let bit = model_catalog::mask(name)?;
-    if (*SUPPORTED_MODEL_MASK & bit) != 0 {
-        Some(bit)
-    } else {
-        None
-    }
+    IMAGE_MODEL_MASK.intersects(&bit).then_some(bit)
 }
@@ -1,19 +1,31 @@
-use crate::db::{DbGeminiCliResource, GeminiCliCreate};
+use super::client::oauth::utils::attach_email_from_id_token;
+use crate::db::{DbGeminiCliResource, GeminiCliCreate, split_labels};
 use crate::error::PolluxError;
+use crate::oauth_utils::OauthTokenResponse;
+use crate::providers::RefreshTokenSeed;
 use crate::providers::manifest::{GeminiCliLease, GeminiCliProfile};
+use crate::providers::traits::provider::ProviderResource;
 use crate::providers::traits::scheduler::{CredentialId, Schedulable};
+use crate::utils::logging::payload_logging_enabled;
 use chrono::{DateTime, Duration, Utc};
 use serde::{Deserialize, Serialize};
 use tracing::debug;
+use url::Url;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GeminiCliResource {
     email: Option<String>,
     sub: String,
     project_id: String,
+    #[serde(default)]
+    tier: Option<String>,
     refresh_token: String,
     access_token: String,
     expiry: DateTime<Utc>,
+    #[serde(default)]
+    labels: Vec<String>,
+    #[serde(default)]
+    proxy_url: Option<Url>,
 }
 
 impl Default for GeminiCliResource {
@@ -22,20 +34,17 @@ impl Default for GeminiCliResource {
             email: None,
             sub: String::new(),
             project_id: String::new(),
+            tier: None,
             refresh_token: String::new(),
             access_token: String::new(),
             expiry: Utc::now(),
+            labels: Vec::new(),
+            proxy_url: None,
         }
     }
 }
 
 impl GeminiCliResource {
-    /// Return true if current time is within 5 minutes of expiry (inclusive).
-    /// This early-expiry buffer avoids edge cases during requests.
-    pub fn is_expired(&self) -> bool {
-        Utc::now() + Duration::minutes(5) >= self.expiry
-    }
-
     pub fn project_id(&self) -> &str {
         &self.project_id
     }
@@ -44,37 +53,54 @@ impl GeminiCliResource {
         &self.sub
     }
 
-    pub fn email(&self) -> Option<&str> {
-        self.email.as_deref()
-    }
-
     pub fn set_project_id(&mut self, project_id: String) {
         self.project_id = project_id;
     }
 
-    #[allow(dead_code)]
-    pub fn set_sub(&mut self, sub: String) {
-        self.sub = sub;
+    pub fn set_tier(&mut self, tier: String) {
+        self.tier = Some(tier);
     }
 
-    pub fn refresh_token(&self) -> &str {
+    /// Build a resource from any JSON-like payload by applying updates to a default struct.
+    #[cfg(any(test, feature = "bench"))]
+    pub fn from_payload(payload: impl Serialize) -> Result<Self, PolluxError> {
+        let mut cred = GeminiCliResource::default();
+        cred.update_credential(payload)?;
+        Ok(cred)
+    }
+}
+
+impl ProviderResource for GeminiCliResource {
+    fn refresh_token(&self) -> &str {
         &self.refresh_token
     }
 
-    pub fn access_token(&self) -> &str {
+    fn access_token(&self) -> &str {
         &self.access_token
     }
 
-    pub fn expiry(&self) -> DateTime<Utc> {
+    fn expiry(&self) -> DateTime<Utc> {
         self.expiry
     }
 
+    fn proxy_url(&self) -> Option<&Url> {
+        self.proxy_url.as_ref()
+    }
+
+    fn set_labels(&mut self, labels: Vec<String>) {
+        self.labels = labels;
+    }
+
+    fn set_proxy_url(&mut self, proxy_url: Option<Url>) {
+        self.proxy_url = proxy_url;
+    }
+
     /// Merge updates from any JSON-serializable payload into this resource.
     /// - Accepts any `T: Serialize` and converts to `serde_json::Value` internally.
     /// - Supports both OAuth token response (`access_token`, `expires_in`)
     ///   and full credential JSON (`project_id`, `expiry`, etc.).
     /// - Only updates fields present in the JSON; others remain unchanged.
-    pub fn update_credential(&mut self, payload: impl Serialize) -> Result<(), PolluxError> {
+    fn update_credential(&mut self, payload: impl Serialize) -> Result<(), PolluxError> {
         use crate::providers::credential_update::{apply_expiry, parse_patch, set_opt, set_plain};
 
         #[derive(Debug, Default, Deserialize)]
@@ -104,13 +130,36 @@ impl GeminiCliResource {
         Ok(())
     }
 
-    /// Build a resource from any JSON-like payload by applying updates to a default struct.
-    #[cfg(any(test, feature = "bench"))]
-    pub fn from_payload(payload: impl Serialize) -> Result<Self, PolluxError> {
+    /// The account (`sub`, email) comes from the `id_token` claims; the
+    /// project is resolved later by [`super::manager::GeminiCli`]'s onboarding.
+    fn try_from_oauth_token_response(
+        token_response: &OauthTokenResponse,
+        refresh_seed: Option<&RefreshTokenSeed>,
+    ) -> Result<Self, PolluxError> {
+        let mut payload = serde_json::to_value(token_response)?;
+        if payload_logging_enabled() {
+            debug!("Token response payload: {}", payload);
+        }
+        attach_email_from_id_token(&mut payload);
+
         let mut cred = GeminiCliResource::default();
         cred.update_credential(payload)?;
+        if cred.refresh_token.trim().is_empty()
+            && let Some(seed) = refresh_seed
+        {
+            cred.refresh_token = seed.refresh_token().to_string();
+        }
         Ok(cred)
     }
+
+    /// Accounts are stored by `sub` and served under their `project_id`.
+    fn has_identity(&self) -> bool {
+        !self.sub.trim().is_empty() && !self.project_id.trim().is_empty()
+    }
+
+    fn email(&self) -> Option<&str> {
+        self.email.as_deref()
+    }
 }
 
 impl Schedulable for GeminiCliResource {
@@ -120,8 +169,18 @@ impl Schedulable for GeminiCliResource {
         &self.project_id
     }
 
-    fn is_expired(&self) -> bool {
-        self.is_expired()
+    fn expires_within(&self, min_validity: std::time::Duration) -> bool {
+        Duration::from_std(min_validity)
+            .ok()
+            .and_then(|margin| Utc::now().checked_add_signed(margin))
+            .is_none_or(|deadline| deadline >= self.expiry)
+    }
+
+    fn expired_beyond(&self, grace: std::time::Duration) -> bool {
+        Duration::from_std(grace)
+            .ok()
+            .and_then(|grace| self.expiry.checked_add_signed(grace))
+            .is_none_or(|deadline| Utc::now() > deadline)
     }
 
     fn make_lease(&self, id: CredentialId) -> GeminiCliLease {
@@ -130,8 +189,17 @@ impl Schedulable for GeminiCliResource {
             project_id: self.project_id.clone(),
             access_token: self.access_token.clone(),
             email: self.email.clone(),
+            proxy_url: self.proxy_url.clone(),
         }
     }
+
+    fn labels(&self) -> &[String] {
+        &self.labels
+    }
+
+    fn tier(&self) -> Option<&str> {
+        self.tier.as_deref()
+    }
 }
 
 impl From<GeminiCliProfile> for GeminiCliResource {
@@ -152,9 +220,12 @@ impl From<DbGeminiCliResource> for GeminiCliResource {
             email: d.email,
             sub: d.sub,
             project_id: d.project_id,
+            tier: d.tier,
             refresh_token: d.refresh_token,
             access_token: d.access_token.unwrap_or_default(),
             expiry: d.expiry,
+            labels: split_labels(&d.labels),
+            proxy_url: d.proxy_url.and_then(|url| url.parse().ok()),
         }
     }
 }
@@ -165,9 +236,12 @@ impl From<GeminiCliResource> for GeminiCliCreate {
             email: cred.email,
             sub: cred.sub,
             project_id: cred.project_id,
+            tier: cred.tier,
             refresh_token: cred.refresh_token,
             access_token: Some(cred.access_token),
             expiry: cred.expiry,
+            labels: cred.labels,
+            proxy_url: cred.proxy_url,
         }
     }
 }
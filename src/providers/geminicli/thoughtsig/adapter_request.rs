@@ -1,7 +1,8 @@
 use pollux_schema::gemini::{GeminiGenerateContentRequest, Part};
 use pollux_thoughtsig_core::{
-    PatchEvent, PatchOutcome, Patchable, SignaturePatcher, SignaturePreview,
+    PatchEvent, PatchOutcome, PatchSummary, Patchable, SignaturePatcher, SignaturePreview,
 };
+use std::borrow::Cow;
 use tracing::debug;
 
 // Minimal wrapper for `Part` due to orphan rule:
@@ -12,7 +13,15 @@ struct GeminiPartPatch<'a>(&'a mut Part);
 impl Patchable for GeminiPartPatch<'_> {
     fn data(&self) -> PatchEvent<'_> {
         if let Some(function_call) = self.0.function_call.as_ref() {
-            return PatchEvent::FunctionCall(function_call);
+            return serde_json::to_value(function_call).map_or(PatchEvent::None, |value| {
+                PatchEvent::FunctionCall(Cow::Owned(value))
+            });
+        }
+
+        // Media parts (e.g. generated images) carry no text to key a cached
+        // signature on; leave them exactly as the client sent them.
+        if self.0.is_media() {
+            return PatchEvent::None;
         }
 
         if self.0.thought == Some(true) {
@@ -39,7 +48,8 @@ impl Patchable for GeminiPartPatch<'_> {
 pub(super) fn patch_request(
     request: &mut GeminiGenerateContentRequest,
     patcher: &SignaturePatcher,
-) {
+) -> PatchSummary {
+    let mut summary = PatchSummary::default();
     request
         .contents
         .iter_mut()
@@ -47,7 +57,11 @@ pub(super) fn patch_request(
         .flat_map(|content| content.parts.iter_mut())
         .for_each(|part| {
             let mut patch = GeminiPartPatch(part);
-            if let PatchOutcome::Patched { cache_key } = patcher.patch(&mut patch) {
+            let outcome = patcher.patch(&mut patch);
+            summary.record(&outcome);
+            if let PatchOutcome::Patched { cache_key } | PatchOutcome::Fallback { cache_key } =
+                outcome
+            {
                 debug!(
                     channel = "geminicli",
                     thoughtsig.phase = "fill",
@@ -57,6 +71,7 @@ pub(super) fn patch_request(
                 );
             }
         });
+    summary
 }
 
 #[cfg(test)]
@@ -107,13 +122,21 @@ mod tests {
             ]
         }));
 
-        patch_request(&mut request, &patcher);
+        let summary = patch_request(&mut request, &patcher);
 
         assert!(request.contents[0].parts[0].thought_signature.is_none());
         assert_eq!(
             request.contents[1].parts[0].thought_signature.as_deref(),
             Some("skip_thought_signature_validator")
         );
+        assert_eq!(
+            summary,
+            PatchSummary {
+                hits: 0,
+                misses: 1,
+                dropped: 0
+            }
+        );
     }
 
     #[test]
@@ -126,8 +149,9 @@ mod tests {
                 "unit": "c"
             }
         });
-        let key =
-            CacheKeyGenerator::generate_json(&function_call).expect("function call key must exist");
+        let key = CacheKeyGenerator::default()
+            .generate_json(&function_call)
+            .expect("function call key must exist");
         engine.put_signature(key, Arc::from("sig_fn_001"));
 
         let mut request = parse_request(json!({
@@ -149,12 +173,13 @@ mod tests {
             ]
         }));
 
-        patch_request(&mut request, &patcher);
+        let summary = patch_request(&mut request, &patcher);
 
         assert_eq!(
             request.contents[0].parts[0].thought_signature.as_deref(),
             Some("sig_fn_001")
         );
+        assert_eq!(summary.hits, 1);
     }
 
     #[test]
@@ -202,4 +227,30 @@ mod tests {
             Some("client_sig_123")
         );
     }
+
+    #[test]
+    fn patch_request_leaves_media_parts_untouched() {
+        let patcher = fallback_patcher();
+        let mut request = parse_request(json!({
+            "contents": [
+                {
+                    "role": "model",
+                    "parts": [
+                        {
+                            "thought": true,
+                            "inlineData": {"mimeType": "image/png", "data": "iVBORw0KGgo="}
+                        },
+                        {
+                            "fileData": {"mimeType": "image/png", "fileUri": "gs://a/b.png"}
+                        }
+                    ]
+                }
+            ]
+        }));
+
+        patch_request(&mut request, &patcher);
+
+        let parts = &request.contents[0].parts;
+        assert!(parts.iter().all(|part| part.thought_signature.is_none()));
+    }
 }
@@ -2,7 +2,8 @@ use super::adapter_request::patch_request;
 use super::adapter_response::GeminiResponseAdapter;
 use pollux_schema::gemini::{GeminiGenerateContentRequest, GeminiResponseBody};
 use pollux_thoughtsig_core::{
-    CacheMissPolicy, SignaturePatcher, SignatureSniffer, ThoughtSignatureEngine,
+    CacheMissPolicy, Invalidation, MemorySignatureStore, PatchSummary, SignatureCacheStats,
+    SignatureCacheStore, SignaturePatcher, SignatureSniffer, SniffLimits, ThoughtSignatureEngine,
 };
 use std::sync::Arc;
 
@@ -13,6 +14,7 @@ const DEFAULT_MAX_CAPACITY: u64 = 200_000;
 pub struct GeminiThoughtSigService {
     engine: Arc<ThoughtSignatureEngine>,
     patcher: Arc<SignaturePatcher>,
+    limits: SniffLimits,
 }
 
 impl Default for GeminiThoughtSigService {
@@ -23,29 +25,57 @@ impl Default for GeminiThoughtSigService {
 
 impl GeminiThoughtSigService {
     pub fn new() -> Self {
-        let engine = Arc::new(ThoughtSignatureEngine::new(
+        Self::with_store(Arc::new(MemorySignatureStore::new(
             DEFAULT_TTL_SECS,
             DEFAULT_MAX_CAPACITY,
-        ));
+        )))
+    }
+
+    pub fn with_store(store: Arc<dyn SignatureCacheStore>) -> Self {
+        Self::with_engine(ThoughtSignatureEngine::with_store(store))
+    }
+
+    /// Service over a configured engine, e.g. one with a non-default fingerprint.
+    pub fn with_engine(engine: ThoughtSignatureEngine) -> Self {
+        let engine = Arc::new(engine);
         let patcher = Arc::new(SignaturePatcher::new(
             engine.clone(),
             CacheMissPolicy::Fallback,
         ));
 
-        Self { engine, patcher }
+        Self {
+            engine,
+            patcher,
+            limits: SniffLimits::default(),
+        }
+    }
+
+    /// Bound what each response sniffer buffers.
+    #[must_use]
+    pub fn with_sniff_limits(mut self, limits: SniffLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    pub fn patch_request(&self, request: &mut GeminiGenerateContentRequest) -> PatchSummary {
+        patch_request(request, &self.patcher)
+    }
+
+    /// Sniffer for one response to a `model` request.
+    pub fn build_sniffer(&self, model: &str) -> SignatureSniffer {
+        SignatureSniffer::with_limits(self.engine.clone(), self.limits).with_model(model)
     }
 
-    pub fn patch_request(&self, request: &mut GeminiGenerateContentRequest) {
-        patch_request(request, &self.patcher);
+    pub fn cache_stats(&self) -> SignatureCacheStats {
+        self.engine.stats()
     }
 
-    pub fn build_sniffer(&self) -> SignatureSniffer {
-        SignatureSniffer::new(self.engine.clone())
+    pub fn invalidate_cache(&self, scope: &Invalidation) {
+        self.engine.invalidate(scope);
     }
 
     pub fn sniff_response(&self, response: &GeminiResponseBody, sniffer: &mut SignatureSniffer) {
-        let adapter = GeminiResponseAdapter(response);
-        sniffer.inspect(&adapter);
+        sniffer.inspect_chunk(&GeminiResponseAdapter(response));
     }
 }
 
@@ -102,7 +132,7 @@ mod tests {
         }))
         .expect("response json must parse");
 
-        let mut sniffer = service.build_sniffer();
+        let mut sniffer = service.build_sniffer("gemini-2.5-pro");
         service.sniff_response(&response, &mut sniffer);
 
         let mut req: GeminiGenerateContentRequest = serde_json::from_value(json!({
@@ -155,7 +185,7 @@ mod tests {
         }))
         .expect("response json must parse");
 
-        let mut sniffer = service.build_sniffer();
+        let mut sniffer = service.build_sniffer("gemini-2.5-pro");
         service.sniff_response(&response, &mut sniffer);
 
         let mut req: GeminiGenerateContentRequest = serde_json::from_value(json!({
@@ -224,7 +254,7 @@ mod tests {
         }))
         .expect("chunk with signature must parse");
 
-        let mut sniffer = service.build_sniffer();
+        let mut sniffer = service.build_sniffer("gemini-2.5-pro");
         service.sniff_response(&chunk_without_signature, &mut sniffer);
         service.sniff_response(&chunk_with_signature, &mut sniffer);
 
@@ -249,4 +279,48 @@ mod tests {
             Some("stream_sig_001")
         );
     }
+
+    #[test]
+    fn every_part_of_a_chunk_is_sniffed() {
+        let service = GeminiThoughtSigService::new();
+        let chunk: GeminiResponseBody = serde_json::from_value(json!({
+            "candidates": [
+                {
+                    "index": 0,
+                    "finishReason": "STOP",
+                    "content": {
+                        "parts": [
+                            {"thought": true, "text": "plan the call"},
+                            {
+                                "functionCall": {"name": "get_time", "args": {}},
+                                "thoughtSignature": "multi_part_sig"
+                            }
+                        ]
+                    }
+                }
+            ]
+        }))
+        .expect("chunk must parse");
+
+        let mut sniffer = service.build_sniffer("gemini-2.5-pro");
+        service.sniff_response(&chunk, &mut sniffer);
+
+        let mut req: GeminiGenerateContentRequest = serde_json::from_value(json!({
+            "contents": [
+                {
+                    "role": "model",
+                    "parts": [
+                        {"thought": true, "text": "plan the call"},
+                        {"functionCall": {"name": "get_time", "args": {}}}
+                    ]
+                }
+            ]
+        }))
+        .expect("request json must parse");
+
+        service.patch_request(&mut req);
+        for part in &req.contents[0].parts {
+            assert_eq!(part.thought_signature.as_deref(), Some("multi_part_sig"));
+        }
+    }
 }
@@ -1,6 +0,0 @@
-mod refresher;
-
-pub(super) use refresher::{
-    CredentialJob, CredentialJobKind, CredentialProcessError, CredentialProcessResult,
-    GeminiCliOauthWorkerHandle,
-};
@@ -0,0 +1,113 @@
+//! Lease guards returned by the provider actors' `get_credential`.
+//!
+//! Each lease counts against the credential's
+//! `max_concurrent_per_credential` until every handle to it is dropped. The
+//! client [attaches](Lease::attach) a [`HeldLease`] to successful responses
+//! and the [`UsageTracker`](super::UsageTracker) keeps it, so a streamed
+//! response holds its credential until the client stream ends.
+
+use super::LeasedCredential;
+use super::traits::scheduler::CredentialId;
+use std::fmt;
+use std::ops::Deref;
+use std::sync::Arc;
+
+type ReleaseFn = Box<dyn FnOnce() + Send + Sync>;
+
+struct Release(Option<ReleaseFn>);
+
+impl Drop for Release {
+    fn drop(&mut self) {
+        if let Some(release) = self.0.take() {
+            release();
+        }
+    }
+}
+
+/// Keeps a lease outstanding until the last clone is dropped.
+#[derive(Clone)]
+pub struct HeldLease {
+    _release: Arc<Release>,
+}
+
+impl fmt::Debug for HeldLease {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("HeldLease")
+    }
+}
+
+/// A provider lease (`CodexLease`, `GeminiCliLease`, …) that reports back to
+/// its actor once the request no longer needs it.
+pub struct Lease<L> {
+    id: CredentialId,
+    inner: L,
+    held: HeldLease,
+}
+
+impl<L> Lease<L> {
+    /// `release` runs exactly once, when the lease and every [`HeldLease`]
+    /// taken from it are gone.
+    pub(crate) fn new(
+        id: CredentialId,
+        inner: L,
+        release: impl FnOnce() + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            id,
+            inner,
+            held: HeldLease {
+                _release: Arc::new(Release(Some(Box::new(release)))),
+            },
+        }
+    }
+
+    /// Handle that keeps the lease outstanding after this guard is dropped.
+    pub fn held(&self) -> HeldLease {
+        self.held.clone()
+    }
+
+    /// Mark `resp` as served by this credential and keep the lease with it.
+    pub fn attach(&self, resp: &mut reqwest::Response) {
+        resp.extensions_mut().insert(LeasedCredential(self.id));
+        resp.extensions_mut().insert(self.held());
+    }
+}
+
+impl<L> Deref for Lease<L> {
+    type Target = L;
+
+    fn deref(&self) -> &L {
+        &self.inner
+    }
+}
+
+impl<L: fmt::Debug> fmt::Debug for Lease<L> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.inner.fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Lease;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn released_once_after_the_last_handle_drops() {
+        let released = Arc::new(AtomicUsize::new(0));
+        let counter = released.clone();
+        let lease = Lease::new(1, 7_u64, move || {
+            counter.fetch_add(1, Ordering::Relaxed);
+        });
+        assert_eq!(*lease, 7);
+
+        let held = lease.held();
+        drop(lease);
+        assert_eq!(released.load(Ordering::Relaxed), 0);
+        let again = held.clone();
+        drop(held);
+        drop(again);
+        assert_eq!(released.load(Ordering::Relaxed), 1);
+    }
+}
@@ -1,15 +1,43 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::fmt;
+use url::Url;
 
 use crate::providers::traits::lease_status::LeaseLabel;
+use crate::providers::traits::provider::PoolLease;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum ProviderKind {
+    #[serde(alias = "geminicli")]
     GeminiCli,
     Codex,
     Antigravity,
+    Claude,
+    Qwen,
+}
+
+impl ProviderKind {
+    pub const ALL: [Self; 5] = [
+        Self::GeminiCli,
+        Self::Codex,
+        Self::Antigravity,
+        Self::Claude,
+        Self::Qwen,
+    ];
+
+    /// Route prefix and metrics label (`geminicli`, `codex`, `antigravity`,
+    /// `claude`, `qwen`).
+    #[must_use]
+    pub const fn label(self) -> &'static str {
+        match self {
+            Self::GeminiCli => "geminicli",
+            Self::Codex => "codex",
+            Self::Antigravity => "antigravity",
+            Self::Claude => "claude",
+            Self::Qwen => "qwen",
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -43,6 +71,30 @@ pub struct AntigravityProfile {
     pub access_token: Option<String>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClaudeProfile {
+    pub account_uuid: String,
+    pub organization_uuid: String,
+    pub refresh_token: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub access_token: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expiry: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QwenProfile {
+    pub refresh_token: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub access_token: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resource_url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expiry: Option<DateTime<Utc>>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type", content = "data")]
 #[serde(rename_all = "snake_case")]
@@ -50,6 +102,8 @@ pub enum ProfileInner {
     GeminiCli(GeminiCliProfile),
     Codex(CodexProfile),
     Antigravity(AntigravityProfile),
+    Claude(ClaudeProfile),
+    Qwen(QwenProfile),
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -63,6 +117,9 @@ pub struct GeminiCliLease {
     pub access_token: String,
     pub project_id: String,
     pub email: Option<String>,
+    /// Egress proxy bound to the credential through `resource:add`.
+    #[serde(default)]
+    pub proxy_url: Option<Url>,
 }
 
 impl LeaseLabel for GeminiCliLease {
@@ -75,12 +132,21 @@ impl LeaseLabel for GeminiCliLease {
     }
 }
 
+impl PoolLease for GeminiCliLease {
+    fn credential_id(&self) -> u64 {
+        self.id
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CodexLease {
     pub id: u64,
     pub access_token: String,
     pub account_id: String,
     pub email: Option<String>,
+    /// Egress proxy bound to the credential through `resource:add`.
+    #[serde(default)]
+    pub proxy_url: Option<Url>,
 }
 
 impl LeaseLabel for CodexLease {
@@ -93,11 +159,20 @@ impl LeaseLabel for CodexLease {
     }
 }
 
+impl PoolLease for CodexLease {
+    fn credential_id(&self) -> u64 {
+        self.id
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AntigravityLease {
     pub id: u64,
     pub access_token: String,
     pub project_id: String,
+    /// Egress proxy bound to the credential through `resource:add`.
+    #[serde(default)]
+    pub proxy_url: Option<Url>,
 }
 
 impl LeaseLabel for AntigravityLease {
@@ -106,6 +181,66 @@ impl LeaseLabel for AntigravityLease {
     }
 }
 
+impl PoolLease for AntigravityLease {
+    fn credential_id(&self) -> u64 {
+        self.id
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClaudeLease {
+    pub id: u64,
+    pub access_token: String,
+    pub organization_uuid: String,
+    pub email: Option<String>,
+    /// Egress proxy bound to the credential through `resource:add`.
+    #[serde(default)]
+    pub proxy_url: Option<Url>,
+}
+
+impl LeaseLabel for ClaudeLease {
+    fn fmt_label(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "id={}, org={}", self.id, self.organization_uuid)?;
+        if let Some(email) = self.email.as_deref() {
+            write!(f, ", email={email}")?;
+        }
+        Ok(())
+    }
+}
+
+impl PoolLease for ClaudeLease {
+    fn credential_id(&self) -> u64 {
+        self.id
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QwenLease {
+    pub id: u64,
+    pub access_token: String,
+    /// API host from the token response (e.g. `portal.qwen.ai`).
+    pub resource_url: Option<String>,
+    /// Egress proxy bound to the credential through `resource:add`.
+    #[serde(default)]
+    pub proxy_url: Option<Url>,
+}
+
+impl LeaseLabel for QwenLease {
+    fn fmt_label(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "id={}", self.id)?;
+        if let Some(resource_url) = self.resource_url.as_deref() {
+            write!(f, ", resource={resource_url}")?;
+        }
+        Ok(())
+    }
+}
+
+impl PoolLease for QwenLease {
+    fn credential_id(&self) -> u64 {
+        self.id
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type", content = "data")]
 #[serde(rename_all = "snake_case")]
@@ -113,6 +248,8 @@ pub enum ProviderLease {
     GeminiCli(GeminiCliLease),
     Codex(CodexLease),
     Antigravity(AntigravityLease),
+    Claude(ClaudeLease),
+    Qwen(QwenLease),
 }
 
 impl ProviderLease {
@@ -121,6 +258,8 @@ impl ProviderLease {
             ProviderLease::GeminiCli(_) => ProviderKind::GeminiCli,
             ProviderLease::Codex(_) => ProviderKind::Codex,
             ProviderLease::Antigravity(_) => ProviderKind::Antigravity,
+            ProviderLease::Claude(_) => ProviderKind::Claude,
+            ProviderLease::Qwen(_) => ProviderKind::Qwen,
         }
     }
 
@@ -129,6 +268,8 @@ impl ProviderLease {
             ProviderLease::GeminiCli(l) => l.id,
             ProviderLease::Codex(l) => l.id,
             ProviderLease::Antigravity(l) => l.id,
+            ProviderLease::Claude(l) => l.id,
+            ProviderLease::Qwen(l) => l.id,
         }
     }
 }
@@ -1,20 +1,48 @@
+pub mod anthropic_compat;
 pub mod antigravity;
+pub mod capacity;
+pub mod chat_compat;
+pub mod claude;
 pub mod codex;
+pub mod credential_view;
+pub mod doctor;
+pub mod error_clusters;
+pub mod experiment;
+pub mod gemini_aggregate;
 pub mod geminicli;
+pub mod lease;
 pub mod manifest;
+pub mod pool_status;
+pub mod quota;
+pub mod qwen;
+pub mod response_cache;
+pub mod stream_transform;
+pub mod thoughtsig_store;
 #[cfg(not(feature = "bench"))]
 pub(crate) mod traits;
 #[cfg(feature = "bench")]
 pub mod traits;
+pub mod usage;
 
 mod bootstrap;
+mod capability_probe;
 mod credential_update;
+mod model_overrides;
 mod policy;
 mod provider_endpoints;
 mod seed;
+mod system_instruction;
 mod upstream_retry;
 
-pub(crate) use seed::RefreshTokenSeed;
+pub(crate) use capability_probe::ModelProber;
+pub(crate) use seed::{PendingSeedReport, RefreshTokenSeed, SeedValidations};
+pub use seed::{SeedReport, SeedStatus};
 
 pub use bootstrap::Providers;
+pub use credential_view::{CredentialState, CredentialView};
+pub use experiment::{ExperimentArm, ExperimentService};
+pub use lease::{HeldLease, Lease};
 pub use policy::{ActionForError, MappingAction, UPSTREAM_BODY_PREVIEW_CHARS};
+pub use quota::DailyQuota;
+pub use response_cache::ResponseCache;
+pub use usage::{LeasedCredential, UsageTracker};
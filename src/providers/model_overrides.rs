@@ -0,0 +1,120 @@
+//! Per-model generation parameters (`providers.<p>.model_overrides`) merged
+//! into Gemini-shaped payloads before they are sent upstream.
+
+use crate::config::ModelOverrideConfig;
+use pollux_schema::gemini::GeminiGenerateContentRequest;
+use serde_json::{Map, Value};
+use tracing::debug;
+
+const THINKING_BUDGET: &str = "thinkingBudget";
+const THINKING_LEVEL: &str = "thinkingLevel";
+
+/// Apply `rule` for `model` to `body`.
+pub(crate) fn apply(
+    model: &str,
+    rule: &ModelOverrideConfig,
+    body: &mut GeminiGenerateContentRequest,
+) {
+    let generation = body.generation_config.get_or_insert_with(Default::default);
+
+    if let Some(temperature) = rule.temperature
+        && (rule.force || generation.temperature.is_none())
+    {
+        generation.temperature = Some(temperature);
+    }
+    if let Some(max) = rule.max_output_tokens {
+        generation.max_output_tokens = Some(match generation.max_output_tokens {
+            Some(sent) if !rule.force => sent.min(max),
+            _ => max,
+        });
+    }
+
+    let thinking = match (rule.thinking_budget, &rule.reasoning_effort) {
+        (Some(budget), _) => Some((THINKING_BUDGET, Value::from(budget), THINKING_LEVEL)),
+        (None, Some(level)) => Some((THINKING_LEVEL, Value::from(level.as_str()), THINKING_BUDGET)),
+        (None, None) => None,
+    };
+    if let Some((key, value, other)) = thinking {
+        let config = generation
+            .thinking_config
+            .get_or_insert_with(|| Value::Object(Map::new()));
+        if let Value::Object(config) = config {
+            // Upstream rejects a thinkingConfig carrying both a budget and a level.
+            let client_set = config.contains_key(key) || config.contains_key(other);
+            if rule.force || !client_set {
+                config.remove(other);
+                config.insert(key.to_string(), value);
+            }
+        }
+    }
+
+    debug!(model, ?rule, "Model overrides applied");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn request(generation: &Value) -> GeminiGenerateContentRequest {
+        serde_json::from_value(json!({"contents": [], "generationConfig": generation}))
+            .expect("valid request")
+    }
+
+    fn generation(body: &GeminiGenerateContentRequest) -> Value {
+        serde_json::to_value(body.generation_config.as_ref().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn fills_gaps_and_caps_output_tokens() {
+        let rule = ModelOverrideConfig {
+            temperature: Some(0.2),
+            max_output_tokens: Some(1024),
+            thinking_budget: Some(512),
+            ..Default::default()
+        };
+
+        let mut empty = request(&json!({}));
+        apply("m", &rule, &mut empty);
+        assert_eq!(
+            generation(&empty),
+            json!({"temperature": 0.2, "maxOutputTokens": 1024, "thinkingConfig": {"thinkingBudget": 512}})
+        );
+
+        let mut sent = request(&json!({
+            "temperature": 1.0,
+            "maxOutputTokens": 8192,
+            "thinkingConfig": {"thinkingLevel": "high"}
+        }));
+        apply("m", &rule, &mut sent);
+        assert_eq!(
+            generation(&sent),
+            json!({"temperature": 1.0, "maxOutputTokens": 1024, "thinkingConfig": {"thinkingLevel": "high"}})
+        );
+    }
+
+    #[test]
+    fn force_replaces_client_values() {
+        let rule = ModelOverrideConfig {
+            temperature: Some(0.0),
+            max_output_tokens: Some(4096),
+            reasoning_effort: Some("low".to_string()),
+            force: true,
+            ..Default::default()
+        };
+        let mut sent = request(&json!({
+            "temperature": 1.5,
+            "maxOutputTokens": 100,
+            "thinkingConfig": {"thinkingBudget": 24576, "includeThoughts": true}
+        }));
+        apply("m", &rule, &mut sent);
+        assert_eq!(
+            generation(&sent),
+            json!({
+                "temperature": 0.0,
+                "maxOutputTokens": 4096,
+                "thinkingConfig": {"thinkingLevel": "low", "includeThoughts": true}
+            })
+        );
+    }
+}
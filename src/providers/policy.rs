@@ -1,4 +1,4 @@
-use crate::utils::logging::with_pretty_json_debug;
+use crate::utils::logging::{body_preview, with_pretty_json_debug};
 use reqwest::StatusCode;
 use serde::{Serialize, de::DeserializeOwned};
 use std::time::Duration;
@@ -32,6 +32,7 @@ pub async fn classify_upstream_error<E, MappedError>(
     resp: reqwest::Response,
     map_raw: impl FnOnce(E) -> MappedError,
     map_status: impl FnOnce(StatusCode, String) -> MappedError,
+    observe: impl FnOnce(StatusCode, &str),
 ) -> (ActionForError, MappedError)
 where
     E: MappingAction,
@@ -39,6 +40,7 @@ where
     let status = resp.status();
     let bytes = resp.bytes().await.unwrap_or_default();
     let raw_body_owned = String::from_utf8_lossy(&bytes).into_owned();
+    observe(status, &raw_body_owned);
 
     if let Ok(error) = serde_json::from_slice::<E>(&bytes) {
         if let Some(action) = error.try_match_rule(status) {
@@ -75,7 +77,7 @@ where
     tracing::debug!(
         %status,
         ?action,
-        body = %format!("{:.len$}", raw_body_owned, len = UPSTREAM_BODY_PREVIEW_CHARS),
+        body = %body_preview(&raw_body_owned, UPSTREAM_BODY_PREVIEW_CHARS),
         "Upstream unstructured error"
     );
 
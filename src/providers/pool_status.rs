@@ -0,0 +1,186 @@
+//! Live pool snapshots for the admin dashboard.
+//!
+//! Each provider actor keeps a small ring of recent credential errors and
+//! answers a status RPC with a [`PoolStatus`] built from its scheduler. Nothing
+//! here touches storage; stored-state counts come from [`CredentialCounts`].
+
+use crate::model_catalog::{MODEL_REGISTRY, ModelCapabilities, model_names_from_mask};
+use crate::providers::credential_view::{CredentialState, CredentialView};
+use crate::providers::manifest::ProviderKind;
+use crate::providers::traits::scheduler::{CredentialId, ResourceScheduler, Schedulable};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::VecDeque;
+
+/// How many recent errors each provider keeps.
+const RECENT_ERRORS_CAPACITY: usize = 50;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PoolErrorKind {
+    RateLimited,
+    ModelUnsupported,
+    /// Request failure counted towards auto-disable.
+    Failed,
+    Invalid,
+    Banned,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PoolError {
+    pub at: DateTime<Utc>,
+    pub id: CredentialId,
+    pub kind: PoolErrorKind,
+    /// Empty for credential-wide errors.
+    pub models: Vec<String>,
+}
+
+/// Bounded, newest-last log of credential errors.
+#[derive(Debug, Default)]
+pub(crate) struct RecentErrors(VecDeque<PoolError>);
+
+impl RecentErrors {
+    pub(crate) fn push(
+        &mut self,
+        id: CredentialId,
+        kind: PoolErrorKind,
+        model_mask: &ModelCapabilities,
+    ) {
+        if self.0.len() == RECENT_ERRORS_CAPACITY {
+            self.0.pop_front();
+        }
+        self.0.push_back(PoolError {
+            at: Utc::now(),
+            id,
+            kind,
+            models: model_names_from_mask(model_mask),
+        });
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ModelPoolStatus {
+    pub model: String,
+    /// Credentials ready to serve this model right now.
+    pub queue_depth: usize,
+    pub cooling: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CooldownStatus {
+    pub id: CredentialId,
+    pub model: String,
+    pub remaining_secs: u64,
+}
+
+/// Point-in-time scheduler view of one provider.
+#[derive(Debug, Clone, Serialize)]
+pub struct PoolStatus {
+    pub provider: ProviderKind,
+    /// Credentials loaded in the scheduler.
+    pub loaded: usize,
+    /// Loaded credentials ready for at least one model.
+    pub usable: usize,
+    pub refresh_in_flight: usize,
+    pub models: Vec<ModelPoolStatus>,
+    /// Longest first.
+    pub cooldowns: Vec<CooldownStatus>,
+    /// Newest first.
+    pub recent_errors: Vec<PoolError>,
+}
+
+impl PoolStatus {
+    pub(crate) fn collect<R: Schedulable>(
+        provider: ProviderKind,
+        scheduler: &ResourceScheduler<R>,
+        supported: &ModelCapabilities,
+        errors: &RecentErrors,
+    ) -> Self {
+        let models = supported
+            .iter()
+            .take_while(|&idx| idx < MODEL_REGISTRY.len())
+            .map(|idx| {
+                let stats = scheduler.stats(&ModelCapabilities::single(idx));
+                ModelPoolStatus {
+                    model: MODEL_REGISTRY.get_name(idx).to_string(),
+                    queue_depth: stats.queue_len,
+                    cooling: stats.cooldowns,
+                }
+            })
+            .collect();
+
+        let mut cooldowns: Vec<CooldownStatus> = scheduler
+            .runtime_snapshot()
+            .into_iter()
+            .flat_map(|(id, runtime)| {
+                runtime
+                    .cooldowns
+                    .into_iter()
+                    .filter(|(idx, _)| *idx < MODEL_REGISTRY.len())
+                    .map(move |(idx, remaining)| CooldownStatus {
+                        id,
+                        model: MODEL_REGISTRY.get_name(idx).to_string(),
+                        remaining_secs: remaining.as_secs(),
+                    })
+            })
+            .collect();
+        cooldowns.sort_by(|a, b| {
+            b.remaining_secs
+                .cmp(&a.remaining_secs)
+                .then(a.id.cmp(&b.id))
+        });
+
+        let stats = scheduler.stats(&ModelCapabilities::none());
+        Self {
+            provider,
+            loaded: stats.total_creds,
+            usable: scheduler.usable_creds(),
+            refresh_in_flight: stats.refreshing,
+            models,
+            cooldowns,
+            recent_errors: errors.0.iter().rev().cloned().collect(),
+        }
+    }
+}
+
+/// Stored credentials per [`CredentialState`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct CredentialCounts {
+    pub active: usize,
+    pub refreshing: usize,
+    pub pending: usize,
+    pub disabled: usize,
+}
+
+impl CredentialCounts {
+    #[must_use]
+    pub fn from_views(views: &[CredentialView]) -> Self {
+        let mut counts = Self::default();
+        for view in views {
+            match view.state {
+                CredentialState::Active => counts.active += 1,
+                CredentialState::Refreshing => counts.refreshing += 1,
+                CredentialState::Pending => counts.pending += 1,
+                CredentialState::Disabled => counts.disabled += 1,
+            }
+        }
+        counts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recent_errors_drop_the_oldest_when_full() {
+        let mut errors = RecentErrors::default();
+        for id in 0..(RECENT_ERRORS_CAPACITY as u64 + 5) {
+            errors.push(id, PoolErrorKind::Failed, &ModelCapabilities::none());
+        }
+        let newest_first: Vec<_> = errors.0.iter().rev().map(|e| e.id).collect();
+        assert_eq!(newest_first.len(), RECENT_ERRORS_CAPACITY);
+        assert_eq!(newest_first[0], RECENT_ERRORS_CAPACITY as u64 + 4);
+        assert_eq!(newest_first.last(), Some(&5));
+    }
+}
@@ -0,0 +1,281 @@
+//! Soft per-credential daily quotas (`providers.<p>.daily_quota`).
+//!
+//! Completed requests are counted per (credential, model) for the current
+//! quota day, which starts at `reset_hour_utc`. Once a credential reaches a
+//! configured limit for a model it is cooled down for that model until the
+//! next reset, so the pool moves on before upstream starts answering `429`.
+//! Counts are rebuilt from the `usage` table at startup.
+
+use crate::config::DailyQuotaConfig;
+use crate::db::UsageAggregate;
+use crate::model_catalog::{self, ModelCapabilities};
+use crate::providers::traits::scheduler::CredentialId;
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tracing::info;
+
+type Cooldown = Arc<dyn Fn(CredentialId, ModelCapabilities, Duration) + Send + Sync>;
+
+#[derive(Debug, Default, Clone, Copy)]
+struct QuotaCounter {
+    requests: u64,
+    tokens: u64,
+}
+
+struct QuotaDay {
+    start: DateTime<Utc>,
+    counts: HashMap<(CredentialId, String), QuotaCounter>,
+}
+
+/// One provider's counters; clones share state.
+#[derive(Clone)]
+pub struct DailyQuota {
+    provider: &'static str,
+    cfg: DailyQuotaConfig,
+    day: Arc<Mutex<QuotaDay>>,
+    cooldown: Cooldown,
+}
+
+/// Counters for one (credential, model) as served by the admin API.
+#[derive(Debug, Clone, Serialize)]
+pub struct QuotaCounterView {
+    pub credential_id: CredentialId,
+    pub model: String,
+    pub requests: u64,
+    pub tokens: u64,
+    pub exhausted: bool,
+}
+
+/// One provider's quota day as served by the admin API.
+#[derive(Debug, Clone, Serialize)]
+pub struct QuotaView {
+    pub provider: &'static str,
+    pub max_requests: Option<u64>,
+    pub max_tokens: Option<u64>,
+    pub day_start: DateTime<Utc>,
+    pub resets_at: DateTime<Utc>,
+    pub counters: Vec<QuotaCounterView>,
+}
+
+impl DailyQuota {
+    /// `cooldown` parks a credential for the given models, as an upstream
+    /// `429` would.
+    pub fn new(
+        provider: &'static str,
+        cfg: DailyQuotaConfig,
+        cooldown: impl Fn(CredentialId, ModelCapabilities, Duration) + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            provider,
+            cfg,
+            day: Arc::new(Mutex::new(QuotaDay {
+                start: day_start(&cfg, Utc::now()),
+                counts: HashMap::new(),
+            })),
+            cooldown: Arc::new(cooldown),
+        }
+    }
+
+    /// Start of the quota day containing now.
+    pub fn current_day_start(&self) -> DateTime<Utc> {
+        day_start(&self.cfg, Utc::now())
+    }
+
+    fn exhausted(&self, counter: QuotaCounter) -> bool {
+        self.cfg.max_requests.is_some_and(|m| counter.requests >= m)
+            || self.cfg.max_tokens.is_some_and(|m| counter.tokens >= m)
+    }
+
+    fn cool_down(&self, id: CredentialId, model: &str, now: DateTime<Utc>, start: DateTime<Utc>) {
+        let Some(mask) = model_catalog::mask(model) else {
+            return;
+        };
+        let until = start + ChronoDuration::days(1);
+        let cooldown = (until - now).to_std().unwrap_or_default();
+        info!(
+            provider = self.provider,
+            credential_id = id,
+            model,
+            resets_at = %until,
+            "Daily quota reached; cooling credential down until reset"
+        );
+        (self.cooldown)(id, mask, cooldown);
+    }
+
+    /// Load today's totals, as summed from the `usage` table since
+    /// [`Self::current_day_start`]. Credentials already over a limit are
+    /// cooled down right away.
+    pub fn seed(&self, rows: &[UsageAggregate]) {
+        let now = Utc::now();
+        let Ok(mut day) = self.day.lock() else {
+            return;
+        };
+        day.start = day_start(&self.cfg, now);
+        day.counts.clear();
+        for row in rows {
+            let Some(id) = row.credential_id.and_then(|id| u64::try_from(id).ok()) else {
+                continue;
+            };
+            let counter = QuotaCounter {
+                requests: u64::try_from(row.requests).unwrap_or(0),
+                tokens: u64::try_from(row.prompt_tokens.saturating_add(row.output_tokens))
+                    .unwrap_or(0),
+            };
+            day.counts.insert((id, row.model.clone()), counter);
+            if self.exhausted(counter) {
+                self.cool_down(id, &row.model, now, day.start);
+            }
+        }
+    }
+
+    /// Count one completed request.
+    pub fn record(&self, id: CredentialId, model: &str, tokens: u64) {
+        self.record_at(Utc::now(), id, model, tokens);
+    }
+
+    fn record_at(&self, now: DateTime<Utc>, id: CredentialId, model: &str, tokens: u64) {
+        let Ok(mut day) = self.day.lock() else {
+            return;
+        };
+        let start = day_start(&self.cfg, now);
+        if start != day.start {
+            day.start = start;
+            day.counts.clear();
+        }
+        let counter = day.counts.entry((id, model.to_string())).or_default();
+        let before = *counter;
+        counter.requests += 1;
+        counter.tokens = counter.tokens.saturating_add(tokens);
+        let after = *counter;
+        if !self.exhausted(before) && self.exhausted(after) {
+            self.cool_down(id, model, now, start);
+        }
+    }
+
+    pub fn view(&self) -> QuotaView {
+        let now = Utc::now();
+        let start = day_start(&self.cfg, now);
+        let mut counters: Vec<QuotaCounterView> = match self.day.lock() {
+            Ok(day) if day.start == start => day
+                .counts
+                .iter()
+                .map(|((id, model), &c)| QuotaCounterView {
+                    credential_id: *id,
+                    model: model.clone(),
+                    requests: c.requests,
+                    tokens: c.tokens,
+                    exhausted: self.exhausted(c),
+                })
+                .collect(),
+            _ => Vec::new(),
+        };
+        counters.sort_by(|a, b| (a.credential_id, &a.model).cmp(&(b.credential_id, &b.model)));
+        QuotaView {
+            provider: self.provider,
+            max_requests: self.cfg.max_requests,
+            max_tokens: self.cfg.max_tokens,
+            day_start: start,
+            resets_at: start + ChronoDuration::days(1),
+            counters,
+        }
+    }
+}
+
+/// Most recent `reset_hour_utc` at or before `now`.
+fn day_start(cfg: &DailyQuotaConfig, now: DateTime<Utc>) -> DateTime<Utc> {
+    let reset = now
+        .date_naive()
+        .and_hms_opt(cfg.reset_hour_utc.min(23), 0, 0)
+        .expect("valid reset hour")
+        .and_utc();
+    if reset > now {
+        reset - ChronoDuration::days(1)
+    } else {
+        reset
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    type Calls = Arc<Mutex<Vec<(CredentialId, Duration)>>>;
+
+    fn quota(cfg: DailyQuotaConfig) -> (DailyQuota, Calls) {
+        let calls = Calls::default();
+        let seen = calls.clone();
+        let quota = DailyQuota::new("geminicli", cfg, move |id, _, cooldown| {
+            seen.lock().unwrap().push((id, cooldown));
+        });
+        (quota, calls)
+    }
+
+    fn model() -> String {
+        crate::config::CONFIG.geminicli().model_list[0].clone()
+    }
+
+    #[test]
+    fn day_starts_at_the_reset_hour() {
+        let cfg = DailyQuotaConfig {
+            reset_hour_utc: 7,
+            ..DailyQuotaConfig::default()
+        };
+        let before = Utc.with_ymd_and_hms(2026, 3, 2, 6, 59, 0).unwrap();
+        let after = Utc.with_ymd_and_hms(2026, 3, 2, 7, 0, 0).unwrap();
+        assert_eq!(
+            day_start(&cfg, before),
+            Utc.with_ymd_and_hms(2026, 3, 1, 7, 0, 0).unwrap()
+        );
+        assert_eq!(day_start(&cfg, after), after);
+    }
+
+    #[test]
+    fn reaching_a_limit_cools_down_once_until_reset() {
+        let (quota, calls) = quota(DailyQuotaConfig {
+            max_requests: Some(2),
+            max_tokens: None,
+            reset_hour_utc: 0,
+        });
+        let model = model();
+        let now = Utc.with_ymd_and_hms(2026, 3, 2, 18, 0, 0).unwrap();
+        quota.record_at(now, 1, &model, 10);
+        assert!(calls.lock().unwrap().is_empty());
+        quota.record_at(now, 1, &model, 10);
+        quota.record_at(now, 1, &model, 10);
+        assert_eq!(*calls.lock().unwrap(), [(1, Duration::from_hours(6))]);
+
+        // A new day starts from zero.
+        let tomorrow = now + ChronoDuration::days(1);
+        quota.record_at(tomorrow, 1, &model, 10);
+        assert_eq!(calls.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn seeded_usage_counts_toward_token_limit() {
+        let (quota, calls) = quota(DailyQuotaConfig {
+            max_requests: None,
+            max_tokens: Some(100),
+            reset_hour_utc: 0,
+        });
+        let model = model();
+        quota.seed(&[UsageAggregate {
+            provider: "geminicli".to_string(),
+            model: model.clone(),
+            credential_id: Some(3),
+            requests: 4,
+            errors: 0,
+            prompt_tokens: 60,
+            output_tokens: 50,
+            avg_latency_ms: 0,
+        }]);
+        assert_eq!(calls.lock().unwrap().len(), 1);
+        let view = quota.view();
+        assert_eq!(view.counters.len(), 1);
+        assert_eq!(view.counters[0].tokens, 110);
+        assert!(view.counters[0].exhausted);
+    }
+}
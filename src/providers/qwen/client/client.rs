@@ -0,0 +1,328 @@
+use crate::error::{IsRetryable, QwenError};
+use crate::providers::error_clusters::ErrorClusters;
+use crate::providers::manifest::ProviderKind;
+use crate::providers::manifest::QwenLease;
+use crate::providers::qwen::{DEFAULT_RESOURCE_HOST, QWEN_USER_AGENT, QwenActorHandle};
+use crate::providers::upstream_retry::post_json_bytes_with_retry;
+use crate::providers::{ActionForError, policy::classify_upstream_error};
+use crate::server::routes::qwen::QwenContext;
+use crate::utils::http::EgressClients;
+use crate::utils::logging::with_pretty_json_debug;
+use axum::body::Bytes;
+use backon::{ExponentialBuilder, Retryable};
+use pollux_schema::QwenErrorBody;
+use pollux_schema::openai::ChatCompletionRequest;
+use reqwest::header::{
+    AUTHORIZATION, CONTENT_TYPE, HeaderMap, HeaderName, HeaderValue, USER_AGENT,
+};
+
+use std::time::{Duration, Instant};
+use tracing::{debug, info};
+use url::Url;
+
+/// Passthrough client for Qwen's OpenAI-compatible chat completions API.
+///
+/// Each account is served from the host its token response named
+/// (`resource_url`), unless `custom_api_url` pins every account to one base.
+#[derive(Clone)]
+pub(crate) struct QwenClient {
+    clients: EgressClients,
+    retry_policy: ExponentialBuilder,
+    custom_api_url: Option<Url>,
+    trace_header: Option<String>,
+    error_clusters: ErrorClusters,
+}
+
+impl QwenClient {
+    pub(crate) fn new(
+        clients: EgressClients,
+        custom_api_url: Option<&Url>,
+        retry_max_times: usize,
+        trace_header: Option<String>,
+    ) -> Self {
+        let retry_policy = ExponentialBuilder::default()
+            .with_min_delay(Duration::ZERO)
+            .with_max_delay(Duration::ZERO)
+            .with_max_times(retry_max_times);
+        info!(
+            endpoint = %custom_api_url.map_or("<per-account resource_url>", Url::as_str),
+            "QwenClient initialized"
+        );
+
+        Self {
+            clients,
+            retry_policy,
+            custom_api_url: custom_api_url.cloned(),
+            trace_header,
+            error_clusters: ErrorClusters::default(),
+        }
+    }
+
+    /// Count upstream error responses into `clusters`.
+    #[must_use]
+    pub(crate) fn with_error_clusters(mut self, clusters: ErrorClusters) -> Self {
+        self.error_clusters = clusters;
+        self
+    }
+
+    /// `chat/completions` under `custom_api_url`, or under
+    /// `https://{resource_url}/v1` for the leased account.
+    pub(crate) fn chat_completions_url(custom_api_url: Option<&Url>, lease: &QwenLease) -> Url {
+        let mut base = custom_api_url.cloned().unwrap_or_else(|| {
+            let host = lease
+                .resource_url
+                .as_deref()
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+                .unwrap_or(DEFAULT_RESOURCE_HOST);
+            let base = if host.starts_with("http://") || host.starts_with("https://") {
+                host.trim_end_matches('/').to_string()
+            } else {
+                format!("https://{}", host.trim_end_matches('/'))
+            };
+            let base = if base.ends_with("/v1") {
+                base
+            } else {
+                format!("{base}/v1")
+            };
+            Url::parse(&base).unwrap_or_else(|_| {
+                Url::parse(&format!("https://{DEFAULT_RESOURCE_HOST}/v1"))
+                    .expect("valid default Qwen resource URL")
+            })
+        });
+        if !base.path().ends_with('/') {
+            base.set_path(&format!("{}/", base.path()));
+        }
+        base.join("chat/completions")
+            .expect("valid chat completions endpoint path")
+    }
+
+    /// Upstream headers for one attempt, matching what Qwen Code sends.
+    pub(crate) fn upstream_headers(lease: &QwenLease) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+        if let Ok(value) = HeaderValue::from_str(&format!("Bearer {}", lease.access_token)) {
+            headers.insert(AUTHORIZATION, value);
+        }
+        headers.insert(USER_AGENT, HeaderValue::from_static(QWEN_USER_AGENT));
+        headers.insert(
+            "x-dashscope-useragent",
+            HeaderValue::from_static(QWEN_USER_AGENT),
+        );
+        headers.insert(
+            "x-dashscope-authtype",
+            HeaderValue::from_static("qwen-oauth"),
+        );
+        headers.insert(
+            "x-dashscope-cachecontrol",
+            HeaderValue::from_static("enable"),
+        );
+        headers
+    }
+
+    #[allow(clippy::too_many_lines)]
+    pub(crate) async fn call_qwen(
+        &self,
+        handle: &QwenActorHandle,
+        ctx: &QwenContext,
+        body: &ChatCompletionRequest,
+    ) -> Result<reqwest::Response, QwenError> {
+        let clients = &self.clients;
+        let custom_api_url = self.custom_api_url.as_ref();
+        let trace_header = &self.trace_header;
+        let error_clusters = &self.error_clusters;
+        let model = &ctx.model;
+        let model_mask = &ctx.model_mask;
+        let stream = ctx.stream;
+        let request_body = Bytes::from(serde_json::to_vec(body)?);
+
+        let op = move || {
+            let request_body = request_body.clone();
+            async move {
+                let start = Instant::now();
+                let lease = handle
+                    .get_credential(model_mask.clone(), ctx.route_key, ctx.pool.clone())
+                    .await?
+                    .ok_or(QwenError::NoAvailableCredential)?;
+
+                let waited_us = start.elapsed().as_micros();
+                info!(
+                    waited_us,
+                    id = lease.id,
+                    model = %model,
+                    stream,
+                    "[Qwen] Lease acquired"
+                );
+
+                with_pretty_json_debug(&body, |pretty_payload| {
+                    tracing::debug!(
+                        channel = "qwen",
+                        lease.id = lease.id,
+                        req.model = %model,
+                        req.stream = stream,
+                        body = %pretty_payload,
+                        "[Qwen] Prepared upstream payload"
+                    );
+                });
+
+                let url = Self::chat_completions_url(custom_api_url, &lease);
+                let mut upstream_headers = Self::upstream_headers(&lease);
+                debug!(url = %url, "[Qwen] Prepared upstream request");
+
+                if let Some(header_name) = trace_header {
+                    let trace_value = format!("qwen:{}", lease.id);
+                    if let (Ok(name), Ok(val)) = (
+                        HeaderName::from_bytes(header_name.as_bytes()),
+                        HeaderValue::from_str(&trace_value),
+                    ) {
+                        upstream_headers.insert(name, val);
+                    }
+                }
+
+                let sent = Instant::now();
+                let mut resp = post_json_bytes_with_retry(
+                    "Qwen",
+                    &clients.select(lease.id, lease.proxy_url.as_ref(), stream),
+                    &url,
+                    Some(upstream_headers),
+                    request_body,
+                )
+                .await
+                .inspect_err(|e| {
+                    if e.status().is_some_and(|s| s.is_server_error()) {
+                        handle.report_outcome(lease.id, model_mask.clone(), false);
+                    }
+                })?;
+
+                if resp.status().is_success() {
+                    handle.report_success(lease.id, model_mask.clone(), sent.elapsed());
+                    lease.attach(&mut resp);
+                    return Ok(resp);
+                }
+
+                let status = resp.status();
+                let (action, final_error) = classify_upstream_error(
+                    resp,
+                    |json: QwenErrorBody| QwenError::UpstreamMappedError { status, body: json },
+                    |status, body| QwenError::UpstreamFallbackError { status, body },
+                    |status, body| {
+                        error_clusters.record(ProviderKind::Qwen.label(), model, status, body);
+                    },
+                )
+                .await;
+
+                match &action {
+                    ActionForError::RateLimit(duration) => {
+                        handle.report_rate_limit(lease.id, model_mask.clone(), *duration);
+                    }
+                    ActionForError::Ban => {
+                        handle.report_banned(lease.id);
+                    }
+                    ActionForError::ModelUnsupported => {
+                        handle.report_model_unsupported(lease.id, model_mask.clone());
+                    }
+                    ActionForError::Invalid => {
+                        handle.report_invalid(lease.id);
+                    }
+                    ActionForError::None => {
+                        if status.is_server_error() {
+                            handle.report_outcome(lease.id, model_mask.clone(), false);
+                        }
+                    }
+                }
+
+                match &final_error {
+                    QwenError::UpstreamMappedError { status, .. } => {
+                        tracing::warn!(
+                            lease_id = lease.id,
+                            model = %model,
+                            status = %status,
+                            action = ?action,
+                            "[Qwen] Upstream mapped error"
+                        );
+                    }
+                    QwenError::UpstreamFallbackError { status, .. } => {
+                        tracing::warn!(
+                            lease_id = lease.id,
+                            model = %model,
+                            status = %status,
+                            action = ?action,
+                            "[Qwen] Upstream fallback error"
+                        );
+                    }
+                    QwenError::Reqwest(error) => {
+                        tracing::warn!(
+                            lease_id = lease.id,
+                            model = %model,
+                            status = ?error.status(),
+                            action = ?action,
+                            "[Qwen] Upstream reqwest error"
+                        );
+                    }
+                    _ => {
+                        tracing::warn!(
+                            lease_id = lease.id,
+                            model = %model,
+                            status = "N/A",
+                            action = ?action,
+                            "[Qwen] Upstream other error"
+                        );
+                    }
+                }
+
+                Err(final_error)
+            }
+        };
+
+        let result = op
+            .retry(&self.retry_policy)
+            .when(|err: &QwenError| err.is_retryable())
+            .notify(|err, dur: Duration| {
+                tracing::warn!("Qwen retrying after error {} in {:?}", err, dur);
+                crate::server::audit_log::note_retry();
+            })
+            .await;
+
+        match result {
+            Err(err) if err.pool_gave_up() => {
+                let after = handle
+                    .retry_after(ctx.model_mask.clone(), ctx.pool.clone())
+                    .await;
+                Err(err.with_retry_after(after))
+            }
+            other => other,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lease(resource_url: Option<&str>) -> QwenLease {
+        QwenLease {
+            id: 1,
+            access_token: "at".to_string(),
+            resource_url: resource_url.map(ToString::to_string),
+            proxy_url: None,
+        }
+    }
+
+    #[test]
+    fn chat_completions_url_follows_resource_url_unless_pinned() {
+        assert_eq!(
+            QwenClient::chat_completions_url(None, &lease(Some("portal.qwen.ai"))).as_str(),
+            "https://portal.qwen.ai/v1/chat/completions"
+        );
+        assert_eq!(
+            QwenClient::chat_completions_url(None, &lease(None)).as_str(),
+            "https://portal.qwen.ai/v1/chat/completions"
+        );
+        let custom = Url::parse("http://127.0.0.1:9000/compatible-mode/v1").expect("valid url");
+        assert_eq!(
+            QwenClient::chat_completions_url(Some(&custom), &lease(Some("portal.qwen.ai")))
+                .as_str(),
+            "http://127.0.0.1:9000/compatible-mode/v1/chat/completions"
+        );
+    }
+}
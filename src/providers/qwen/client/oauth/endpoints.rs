@@ -0,0 +1,150 @@
+use crate::config::CONFIG;
+use crate::error::OauthError;
+use crate::oauth_utils::OauthTokenResponse;
+
+use oauth2::{PkceCodeChallenge, PkceCodeVerifier};
+use reqwest::StatusCode;
+use serde::Deserialize;
+use serde_json::Value;
+use std::time::Duration;
+use tracing::info;
+use url::Url;
+
+/// Stateless qwen.ai OAuth endpoints for the Qwen Code device flow.
+pub(crate) struct QwenOauthEndpoints;
+
+/// Fixed Qwen Code OAuth client id (public client, no secret).
+const QWEN_CLIENT_ID: &str = "f0304373b74a44d2b584a3fb70ca9e56";
+
+const QWEN_SCOPE: &str = "openid profile email model.completion";
+
+const DEVICE_GRANT_TYPE: &str = "urn:ietf:params:oauth:grant-type:device_code";
+
+/// A pending device authorization, as returned by the device-code endpoint.
+#[derive(Debug, Clone, Deserialize)]
+pub(crate) struct DeviceAuthorization {
+    pub device_code: String,
+    pub user_code: String,
+    pub verification_uri: String,
+    /// Verification page with the user code already filled in.
+    #[serde(default)]
+    pub verification_uri_complete: Option<String>,
+    /// Seconds until the device code expires.
+    #[serde(default)]
+    pub expires_in: Option<u64>,
+    #[serde(default)]
+    interval: Option<u64>,
+}
+
+impl DeviceAuthorization {
+    pub(crate) fn interval(&self) -> Duration {
+        Duration::from_secs(self.interval.unwrap_or(5).max(1))
+    }
+}
+
+fn endpoint(path: &str) -> Url {
+    CONFIG
+        .providers
+        .qwen
+        .oauth_base_url
+        .join(path)
+        .expect("valid Qwen OAuth endpoint path")
+}
+
+/// Read a token endpoint response, mapping an OAuth `error` on a 4xx to
+/// [`OauthError::ServerResponse`].
+async fn token_response(resp: reqwest::Response) -> Result<OauthTokenResponse, OauthError> {
+    let status = resp.status();
+    let body = resp.text().await?;
+    if !status.is_success() {
+        let error = serde_json::from_str::<Value>(&body)
+            .ok()
+            .and_then(|v| v.get("error").and_then(Value::as_str).map(str::to_string));
+        return Err(match error {
+            Some(error) if status.is_client_error() => OauthError::ServerResponse { error },
+            _ => OauthError::UpstreamStatus(status),
+        });
+    }
+    serde_json::from_str(&body).map_err(|e| OauthError::Parse {
+        message: e.to_string(),
+        body,
+    })
+}
+
+impl QwenOauthEndpoints {
+    /// Start a device authorization bound to `challenge`; the user then
+    /// approves it at `verification_uri`.
+    pub(crate) async fn request_device_code(
+        challenge: &PkceCodeChallenge,
+        http_client: &reqwest::Client,
+    ) -> Result<DeviceAuthorization, OauthError> {
+        let resp = http_client
+            .post(endpoint("./api/v1/oauth2/device/code"))
+            .form(&[
+                ("client_id", QWEN_CLIENT_ID),
+                ("scope", QWEN_SCOPE),
+                ("code_challenge", challenge.as_str()),
+                ("code_challenge_method", challenge.method().as_str()),
+            ])
+            .send()
+            .await?;
+        if !resp.status().is_success() {
+            return Err(OauthError::UpstreamStatus(resp.status()));
+        }
+        let body = resp.text().await?;
+        serde_json::from_str(&body).map_err(|e| OauthError::Parse {
+            message: e.to_string(),
+            body,
+        })
+    }
+
+    /// One poll of a device authorization; `None` while the user has not
+    /// approved it yet.
+    pub(crate) async fn poll_device_token(
+        device: &DeviceAuthorization,
+        verifier: &PkceCodeVerifier,
+        http_client: &reqwest::Client,
+    ) -> Result<Option<OauthTokenResponse>, OauthError> {
+        let resp = http_client
+            .post(endpoint("./api/v1/oauth2/token"))
+            .form(&[
+                ("grant_type", DEVICE_GRANT_TYPE),
+                ("client_id", QWEN_CLIENT_ID),
+                ("device_code", device.device_code.as_str()),
+                ("code_verifier", verifier.secret().as_str()),
+            ])
+            .send()
+            .await?;
+        if resp.status() == StatusCode::TOO_MANY_REQUESTS {
+            return Ok(None);
+        }
+        match token_response(resp).await {
+            Ok(tokens) => {
+                info!("Qwen OAuth2 device authorization completed successfully");
+                Ok(Some(tokens))
+            }
+            Err(OauthError::ServerResponse { error })
+                if error == "authorization_pending" || error == "slow_down" =>
+            {
+                Ok(None)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    pub(crate) async fn refresh_access_token(
+        refresh_token: &str,
+        http_client: reqwest::Client,
+    ) -> Result<OauthTokenResponse, OauthError> {
+        let resp = http_client
+            .post(endpoint("./api/v1/oauth2/token"))
+            .form(&[
+                ("grant_type", "refresh_token"),
+                ("refresh_token", refresh_token),
+                ("client_id", QWEN_CLIENT_ID),
+            ])
+            .send()
+            .await?;
+        token_response(resp).await
+    }
+}
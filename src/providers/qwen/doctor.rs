@@ -0,0 +1,120 @@
+//! Qwen probes for `pollux doctor`.
+
+use super::QWEN_USER_AGENT;
+use super::client::QwenClient;
+use super::client::oauth::OAUTH_RETRY_POLICY;
+use super::manager::{CredentialOps, Qwen};
+use super::resource::QwenResource;
+use crate::config::QwenResolvedConfig;
+use crate::db::DbActorHandle;
+use crate::providers::doctor::{
+    DoctorArgs, DoctorReport, PROBE_PROMPT, SseProbe, db_id, expect_json, http_client, probe_sse,
+    token_validity,
+};
+use crate::providers::manifest::QwenLease;
+use crate::providers::traits::oauth_worker::refresh_credential;
+use crate::providers::traits::provider::{CredentialStore, ProviderResource};
+use crate::providers::traits::scheduler::Schedulable;
+use serde_json::{Value, json};
+use std::time::Instant;
+use url::Url;
+
+pub(crate) async fn diagnose(
+    report: &mut DoctorReport,
+    cfg: &QwenResolvedConfig,
+    db: &DbActorHandle,
+    args: &DoctorArgs,
+) {
+    let id = args.id;
+    let Some(mut cred) = report
+        .step("load", async {
+            let row = db
+                .get_qwen_by_id(db_id(id)?)
+                .await
+                .map_err(|e| e.to_string())?;
+            let cred = QwenResource::from(row);
+            let detail = format!(
+                "resource {}, {}",
+                cred.identifier(),
+                token_validity(cred.expiry())
+            );
+            Ok((cred, detail))
+        })
+        .await
+    else {
+        return;
+    };
+
+    if args.refresh {
+        let client = http_client(cfg.proxy.as_ref(), None);
+        report
+            .step("refresh", async {
+                refresh_credential(&Qwen, client, *OAUTH_RETRY_POLICY, &mut cred, None)
+                    .await
+                    .map_err(|e| e.to_string())?;
+                CredentialOps::new(db.clone())
+                    .save_refreshed(id, &cred)
+                    .await
+                    .map_err(|e| format!("refreshed but not saved: {e}"))?;
+                Ok(((), format!("{}, saved", token_validity(cred.expiry()))))
+            })
+            .await;
+    } else {
+        report.skip("refresh");
+    }
+
+    let lease = cred.make_lease(id);
+    let client = http_client(cfg.proxy.as_ref(), Some(QWEN_USER_AGENT));
+    let base = cfg.custom_api_url.as_ref();
+    let model = args
+        .model
+        .clone()
+        .or_else(|| cfg.model_list.first().cloned())
+        .unwrap_or_default();
+
+    report
+        .step("generate", async {
+            let resp = chat(&client, base, &lease, &model, false).await?;
+            let body = expect_json(resp).await?;
+            let tokens = body
+                .pointer("/usage/completion_tokens")
+                .and_then(Value::as_u64)
+                .unwrap_or(0);
+            let finish = body
+                .pointer("/choices/0/finish_reason")
+                .and_then(Value::as_str)
+                .unwrap_or("unknown");
+            Ok(((), format!("{model}: {finish}, {tokens} completion tokens")))
+        })
+        .await;
+
+    report
+        .step("stream", async {
+            let sent = Instant::now();
+            let resp = chat(&client, base, &lease, &model, true).await?;
+            let probe: SseProbe = probe_sse(resp, sent).await?;
+            Ok(((), probe.summary()))
+        })
+        .await;
+}
+
+async fn chat(
+    client: &reqwest::Client,
+    base: Option<&Url>,
+    lease: &QwenLease,
+    model: &str,
+    stream: bool,
+) -> Result<reqwest::Response, String> {
+    client
+        .post(QwenClient::chat_completions_url(base, lease))
+        .headers(QwenClient::upstream_headers(lease))
+        .json(&json!({
+            "model": model,
+            "max_tokens": 64,
+            "stream": stream,
+            "messages": [{"role": "user", "content": PROBE_PROMPT}],
+        }))
+        .send()
+        .await
+        .map_err(|e| e.to_string())
+}
@@ -0,0 +1,91 @@
+use crate::providers::{ActionForError, MappingAction};
+use chrono::{DateTime, Days, FixedOffset, Utc};
+use pollux_schema::QwenErrorBody;
+use reqwest::StatusCode;
+use std::time::Duration;
+
+/// Qwen's free quota resets at midnight Beijing time (UTC+8).
+const QUOTA_RESET_OFFSET_SECS: i32 = 8 * 60 * 60;
+
+impl MappingAction for QwenErrorBody {
+    fn try_match_rule(&self, status: StatusCode) -> Option<ActionForError> {
+        match (status, self) {
+            // 429: free daily quota used up; park the account until the quota resets.
+            (StatusCode::TOO_MANY_REQUESTS | StatusCode::FORBIDDEN, body)
+                if body.is_quota_exhausted() =>
+            {
+                Some(ActionForError::RateLimit(until_quota_reset(Utc::now())))
+            }
+
+            // 404: model not served for this account.
+            (StatusCode::NOT_FOUND, body) if body.code() == Some("model_not_found") => {
+                Some(ActionForError::ModelUnsupported)
+            }
+
+            _ => None,
+        }
+    }
+
+    fn action_from_status(status: StatusCode) -> ActionForError {
+        match status {
+            StatusCode::UNAUTHORIZED => ActionForError::Invalid,
+            StatusCode::FORBIDDEN => ActionForError::Ban,
+            // Request-rate throttling without a quota code clears quickly.
+            StatusCode::TOO_MANY_REQUESTS => ActionForError::RateLimit(Duration::from_mins(1)),
+            _ => ActionForError::None,
+        }
+    }
+}
+
+/// Time from `now` until the next midnight in UTC+8, at least one second.
+fn until_quota_reset(now: DateTime<Utc>) -> Duration {
+    let offset = FixedOffset::east_opt(QUOTA_RESET_OFFSET_SECS).expect("valid UTC offset");
+    let local = now.with_timezone(&offset);
+    let next_midnight = local
+        .date_naive()
+        .checked_add_days(Days::new(1))
+        .and_then(|day| day.and_hms_opt(0, 0, 0))
+        .and_then(|midnight| midnight.and_local_timezone(offset).single());
+    next_midnight
+        .and_then(|reset| (reset.with_timezone(&Utc) - now).to_std().ok())
+        .unwrap_or(Duration::from_hours(1))
+        .max(Duration::from_secs(1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quota_exhausted_rate_limits_until_beijing_midnight() {
+        let now = DateTime::parse_from_rfc3339("2026-03-01T15:30:00Z")
+            .expect("valid timestamp")
+            .with_timezone(&Utc);
+        // 23:30 in UTC+8, so the quota resets in 30 minutes.
+        assert_eq!(until_quota_reset(now), Duration::from_mins(30));
+
+        let raw = r#"{"error":{"code":"insufficient_quota","message":"Free allocated quota exceeded.","type":"insufficient_quota"}}"#;
+        let parsed = serde_json::from_str::<QwenErrorBody>(raw).expect("parse sample");
+        assert!(matches!(
+            parsed.try_match_rule(StatusCode::TOO_MANY_REQUESTS),
+            Some(ActionForError::RateLimit(d)) if d <= Duration::from_hours(24)
+        ));
+    }
+
+    #[test]
+    fn plain_throttling_falls_back_to_status_mapping() {
+        let raw =
+            r#"{"error":{"code":"rate_limit_exceeded","message":"Requests rate limit exceeded"}}"#;
+        let parsed = serde_json::from_str::<QwenErrorBody>(raw).expect("parse sample");
+
+        assert_eq!(parsed.try_match_rule(StatusCode::TOO_MANY_REQUESTS), None);
+        assert_eq!(
+            QwenErrorBody::action_from_status(StatusCode::TOO_MANY_REQUESTS),
+            ActionForError::RateLimit(Duration::from_mins(1))
+        );
+        assert_eq!(
+            QwenErrorBody::action_from_status(StatusCode::UNAUTHORIZED),
+            ActionForError::Invalid
+        );
+    }
+}
@@ -0,0 +1,142 @@
+mod ops;
+
+use super::client::oauth::{OAUTH_RETRY_POLICY, endpoints::QwenOauthEndpoints};
+use super::resource::QwenResource;
+use super::{SUPPORTED_MODEL_MASK, SUPPORTED_MODEL_NAMES};
+use crate::config::QwenResolvedConfig;
+use crate::error::OauthError;
+use crate::model_catalog::ModelCapabilities;
+use crate::oauth_utils::OauthTokenResponse;
+use crate::providers::manifest::ProviderKind;
+use crate::providers::traits::actor::ProviderActorHandle;
+use crate::providers::traits::provider::{OauthWorkerSettings, PoolSettings, Provider};
+use backon::ExponentialBuilder;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+
+pub(crate) use ops::CredentialOps;
+
+/// Qwen Code accounts on the shared pool actor.
+pub struct Qwen;
+
+impl Provider for Qwen {
+    const NAME: &'static str = "Qwen";
+    const KIND: ProviderKind = ProviderKind::Qwen;
+
+    type Resource = QwenResource;
+    type Store = CredentialOps;
+
+    fn supported_models(&self) -> (ModelCapabilities, Vec<String>) {
+        (SUPPORTED_MODEL_MASK.clone(), SUPPORTED_MODEL_NAMES.clone())
+    }
+
+    fn oauth_retry_policy(&self) -> ExponentialBuilder {
+        *OAUTH_RETRY_POLICY
+    }
+
+    fn refresh_access_token(
+        &self,
+        refresh_token: &str,
+        http_client: reqwest::Client,
+    ) -> impl Future<Output = Result<OauthTokenResponse, OauthError>> + Send {
+        QwenOauthEndpoints::refresh_access_token(refresh_token, http_client)
+    }
+}
+
+pub type QwenActorHandle = ProviderActorHandle<Qwen>;
+
+pub(in crate::providers) async fn spawn(
+    db: crate::db::DbActorHandle,
+    cfg: Arc<QwenResolvedConfig>,
+) -> QwenActorHandle {
+    let settings = PoolSettings {
+        endpoint: cfg.custom_api_url.as_ref().map_or(
+            "<per-account resource_url>".to_string(),
+            ToString::to_string,
+        ),
+        retry_max_times: cfg.retry_max_times,
+        auto_disable: cfg.auto_disable,
+        health: cfg.health,
+        min_token_validity: Duration::from_secs(cfg.min_token_validity_secs),
+        stale_grace: Duration::from_secs(cfg.stale_grace_secs),
+        capability_restore: Duration::from_secs(cfg.capability_restore_secs),
+        max_concurrent_per_credential: cfg.max_concurrent_per_credential,
+        tier_weights: HashMap::new(),
+        scheduling_policy: cfg.scheduling_policy,
+        priority_reserve: cfg.priority_reserve,
+        lease_wait: Duration::from_millis(cfg.lease_wait_ms),
+        oauth: OauthWorkerSettings {
+            proxy: cfg.proxy.clone(),
+            user_agent: None,
+            enable_multiplexing: cfg.enable_multiplexing,
+            http_client: cfg.http_client,
+            oauth_tps: cfg.oauth_tps,
+        },
+    };
+    crate::providers::traits::actor::spawn(db, Qwen, settings).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::RefreshTokenSeed;
+    use crate::providers::traits::oauth_worker::{CredentialJob, CredentialJobKind};
+    use crate::providers::traits::provider::ProviderResource;
+    use governor::{Quota, RateLimiter};
+    use serde_json::json;
+    use std::num::NonZeroU32;
+
+    #[test]
+    fn refresh_job_keeps_refresh_id() {
+        let cred = QwenResource::from_payload(json!({
+            "refresh_token": "rt-1",
+            "access_token": "at-1",
+            "resource_url": "portal.qwen.ai",
+            "expiry": "2026-01-01T00:00:00Z",
+        }))
+        .expect("valid credential payload");
+
+        let job = CredentialJob::refresh(42, cred);
+
+        assert_eq!(job.kind.credential_id(), Some(42));
+    }
+
+    #[test]
+    fn untrusted_ingest_job_sets_refresh_token() {
+        let seed = RefreshTokenSeed::new("seed-rt").expect("valid seed");
+
+        let job = CredentialJob::<QwenResource>::ingest_untrusted_seed(&seed).expect("ingest job");
+
+        assert!(matches!(job.kind, CredentialJobKind::IngestUntrusted));
+        assert_eq!(job.cred.refresh_token(), "seed-rt");
+        assert_eq!(job.kind.credential_id(), None);
+    }
+
+    #[test]
+    fn trusted_ingest_job_skips_network_work() {
+        let token_response: OauthTokenResponse = serde_json::from_value(json!({
+            "access_token": "trusted-at",
+            "token_type": "Bearer",
+            "expires_in": 3600,
+            "refresh_token": "trusted-rt",
+            "resource_url": "portal.qwen.ai",
+        }))
+        .expect("token response deserializes");
+
+        let job = CredentialJob::<QwenResource>::ingest_trusted_oauth(&token_response)
+            .expect("trusted oauth ingest job");
+
+        let limiter = RateLimiter::direct(Quota::per_second(NonZeroU32::MIN));
+        let runtime = tokio::runtime::Runtime::new().expect("tokio runtime");
+        let completed = runtime
+            .block_on(job.execute(&Qwen, reqwest::Client::new(), &limiter))
+            .expect("trusted oauth ingest succeeds without refresh");
+
+        assert!(matches!(completed.kind, CredentialJobKind::IngestTrusted));
+        assert_eq!(completed.cred.access_token(), "trusted-at");
+        assert_eq!(completed.cred.refresh_token(), "trusted-rt");
+        assert_eq!(completed.cred.resource_url(), Some("portal.qwen.ai"));
+    }
+}
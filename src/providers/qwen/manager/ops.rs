@@ -0,0 +1,145 @@
+use crate::db::{
+    DbActorHandle, ProviderCreate, ProviderDelete, ProviderIdentity, ProviderPatch, QwenCreate,
+    QwenPatch, synthetic_sub_from_refresh_token,
+};
+use crate::error::PolluxError;
+use crate::providers::credential_view::CredentialView;
+use crate::providers::qwen::resource::QwenResource;
+use crate::providers::seed::StoredSeed;
+use crate::providers::traits::provider::{CredentialStore, ProviderResource};
+use crate::providers::traits::scheduler::CredentialId;
+
+#[derive(Clone)]
+pub struct CredentialOps {
+    db: DbActorHandle,
+}
+
+impl CredentialOps {
+    async fn upsert(&self, cred: QwenResource) -> Result<CredentialId, PolluxError> {
+        let create: QwenCreate = cred.into();
+        let id = self.db.create(ProviderCreate::Qwen(create)).await?;
+
+        u64::try_from(id)
+            .map_err(|_| PolluxError::UnexpectedError(format!("Invalid credential id {id}")))
+    }
+
+    async fn update_by_id(&self, id: CredentialId, patch: QwenPatch) -> Result<(), PolluxError> {
+        let _ = i64::try_from(id)
+            .map_err(|_| PolluxError::UnexpectedError(format!("Invalid credential id {id}")))?;
+
+        self.db.patch(ProviderPatch::Qwen { id, patch }).await?;
+        Ok(())
+    }
+}
+
+impl CredentialStore<QwenResource> for CredentialOps {
+    fn new(db: DbActorHandle) -> Self {
+        Self { db }
+    }
+
+    async fn load_active(&self) -> Result<Vec<(CredentialId, QwenResource)>, PolluxError> {
+        let rows = self.db.list_active_qwen().await?;
+        let mut result = Vec::with_capacity(rows.len());
+        for row in rows {
+            let id = u64::try_from(row.id).map_err(|_| {
+                PolluxError::UnexpectedError(format!("Invalid credential id {}", row.id))
+            })?;
+            result.push((id, row.into()));
+        }
+        Ok(result)
+    }
+
+    /// Store a freshly onboarded credential. Qwen tokens name no account, so
+    /// rows are keyed by a hash of the refresh token (as the DB layer does):
+    /// submitting the same credential again updates its row and re-enables it.
+    async fn store_onboarded(
+        &self,
+        cred: QwenResource,
+    ) -> Result<StoredSeed<QwenResource>, PolluxError> {
+        let sub = synthetic_sub_from_refresh_token(cred.refresh_token());
+        let existing = self
+            .db
+            .find_by_identity(ProviderIdentity::Qwen { sub })
+            .await?;
+        let Some(id) = existing
+            .map(|id| {
+                u64::try_from(id).map_err(|_| {
+                    PolluxError::UnexpectedError(format!("Invalid credential id {id}"))
+                })
+            })
+            .transpose()?
+        else {
+            let id = self.upsert(cred.clone()).await?;
+            return Ok(StoredSeed {
+                id,
+                credential: cred,
+                existing: false,
+            });
+        };
+
+        let create = QwenCreate::from(cred);
+        let patch = QwenPatch {
+            refresh_token: Some(create.refresh_token),
+            access_token: Some(create.access_token),
+            resource_url: create.resource_url,
+            expiry: Some(create.expiry),
+            status: Some(true),
+            labels: (!create.labels.is_empty()).then_some(create.labels),
+        };
+        self.update_by_id(id, patch).await?;
+        Ok(StoredSeed {
+            id,
+            credential: self.get_by_id(id).await?,
+            existing: true,
+        })
+    }
+
+    async fn save_refreshed(
+        &self,
+        id: CredentialId,
+        cred: &QwenResource,
+    ) -> Result<(), PolluxError> {
+        let patch = QwenPatch {
+            refresh_token: Some(cred.refresh_token().to_string()),
+            access_token: Some(cred.access_token().to_string()),
+            resource_url: cred.resource_url().map(ToString::to_string),
+            expiry: Some(cred.expiry()),
+            ..Default::default()
+        };
+        self.update_by_id(id, patch).await
+    }
+
+    async fn set_status(&self, id: CredentialId, status: bool) -> Result<(), PolluxError> {
+        let _ = i64::try_from(id)
+            .map_err(|_| PolluxError::UnexpectedError(format!("Invalid credential id {id}")))?;
+        let patch = QwenPatch {
+            status: Some(status),
+            ..Default::default()
+        };
+        self.db.patch(ProviderPatch::Qwen { id, patch }).await
+    }
+
+    /// All stored rows (any status), for admin listing.
+    async fn load_all_views(&self) -> Result<Vec<CredentialView>, PolluxError> {
+        let rows = self.db.list_qwen().await?;
+        Ok(rows.into_iter().map(CredentialView::from).collect())
+    }
+
+    async fn get_by_id(&self, id: CredentialId) -> Result<QwenResource, PolluxError> {
+        let db_id = i64::try_from(id)
+            .map_err(|_| PolluxError::UnexpectedError(format!("Invalid credential id {id}")))?;
+        match self.db.get_qwen_by_id(db_id).await {
+            Ok(row) => Ok(row.into()),
+            Err(PolluxError::DatabaseError(sqlx::Error::RowNotFound)) => {
+                Err(PolluxError::NotFound(format!("qwen record id={id}")))
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    async fn delete(&self, id: CredentialId) -> Result<(), PolluxError> {
+        let db_id = i64::try_from(id)
+            .map_err(|_| PolluxError::UnexpectedError(format!("Invalid credential id {id}")))?;
+        self.db.delete(ProviderDelete::Qwen(db_id)).await
+    }
+}
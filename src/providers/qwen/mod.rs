@@ -0,0 +1,17 @@
+pub mod client;
+pub(crate) mod doctor;
+mod errors;
+mod manager;
+mod model_mask;
+pub(crate) mod oauth;
+mod resource;
+
+pub use manager::QwenActorHandle;
+pub(in crate::providers) use manager::spawn;
+pub(crate) use model_mask::{SUPPORTED_MODEL_MASK, SUPPORTED_MODEL_NAMES, model_mask};
+
+/// Qwen Code-style User-Agent, also sent as `X-DashScope-UserAgent`.
+pub(crate) const QWEN_USER_AGENT: &str = "QwenCode/0.0.14 (linux; x64)";
+
+/// API host for accounts whose token response named none.
+pub(crate) const DEFAULT_RESOURCE_HOST: &str = "portal.qwen.ai";
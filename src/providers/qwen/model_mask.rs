@@ -0,0 +1,26 @@
+use crate::config::CONFIG;
+use crate::model_catalog::{self, MODEL_REGISTRY, ModelCapabilities};
+use std::collections::HashSet;
+use std::sync::LazyLock;
+
+pub(crate) static SUPPORTED_MODEL_NAMES: LazyLock<Vec<String>> = LazyLock::new(|| {
+    let cfg = CONFIG.qwen();
+
+    let mut seen = HashSet::<String>::new();
+    cfg.model_list
+        .into_iter()
+        .filter(|name| seen.insert(name.clone()))
+        .collect()
+});
+
+pub(crate) static SUPPORTED_MODEL_MASK: LazyLock<ModelCapabilities> = LazyLock::new(|| {
+    SUPPORTED_MODEL_NAMES
+        .iter()
+        .filter_map(|name| MODEL_REGISTRY.get_index(name))
+        .collect()
+});
+
+pub(crate) fn model_mask(name: &str) -> Option<ModelCapabilities> {
+    let bit = model_catalog::mask(name)?;
+    SUPPORTED_MODEL_MASK.intersects(&bit).then_some(bit)
+}
@@ -0,0 +1 @@
+pub(crate) use crate::oauth_utils::OauthTokenResponse;
@@ -0,0 +1,300 @@
+use crate::db::{DbQwenResource, QwenCreate, split_labels};
+use crate::error::PolluxError;
+use crate::providers::RefreshTokenSeed;
+use crate::providers::manifest::{QwenLease, QwenProfile};
+use crate::providers::qwen::DEFAULT_RESOURCE_HOST;
+use crate::providers::qwen::oauth::OauthTokenResponse;
+use crate::providers::traits::provider::ProviderResource;
+use crate::providers::traits::scheduler::{CooldownScope, CredentialId, Schedulable};
+use chrono::{DateTime, Duration, Utc};
+use oauth2::TokenResponse;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tracing::debug;
+use url::Url;
+
+/// In-memory credential state for the Qwen provider.
+///
+/// Qwen tokens carry no account identity; the only per-account detail is
+/// `resource_url`, the host serving that account's API.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QwenResource {
+    refresh_token: String,
+    access_token: String,
+    resource_url: Option<String>,
+    expiry: DateTime<Utc>,
+    #[serde(default)]
+    labels: Vec<String>,
+    #[serde(default)]
+    proxy_url: Option<Url>,
+}
+
+impl Default for QwenResource {
+    fn default() -> Self {
+        Self {
+            refresh_token: String::new(),
+            access_token: String::new(),
+            resource_url: None,
+            expiry: Utc::now(),
+            labels: Vec::new(),
+            proxy_url: None,
+        }
+    }
+}
+
+impl QwenResource {
+    /// Return true if current time is within 5 minutes of expiry (inclusive).
+    #[allow(dead_code)]
+    pub fn is_expired(&self) -> bool {
+        Utc::now() + Duration::minutes(5) >= self.expiry
+    }
+
+    pub fn resource_url(&self) -> Option<&str> {
+        self.resource_url.as_deref()
+    }
+
+    /// Build a resource from any JSON-like payload by applying updates to a default struct.
+    #[cfg(test)]
+    pub fn from_payload(payload: impl Serialize) -> Result<Self, PolluxError> {
+        let mut cred = QwenResource::default();
+        cred.update_credential(payload)?;
+        Ok(cred)
+    }
+}
+
+impl ProviderResource for QwenResource {
+    fn refresh_token(&self) -> &str {
+        &self.refresh_token
+    }
+
+    fn access_token(&self) -> &str {
+        &self.access_token
+    }
+
+    fn expiry(&self) -> DateTime<Utc> {
+        self.expiry
+    }
+
+    fn set_labels(&mut self, labels: Vec<String>) {
+        self.labels = labels;
+    }
+
+    fn proxy_url(&self) -> Option<&Url> {
+        self.proxy_url.as_ref()
+    }
+
+    fn set_proxy_url(&mut self, proxy_url: Option<Url>) {
+        self.proxy_url = proxy_url;
+    }
+
+    /// Merge updates from any JSON-serializable payload into this resource.
+    ///
+    /// This accepts both:
+    /// - full credential JSON (`refresh_token`, `resource_url`, `expiry`, etc.)
+    /// - OAuth token refresh payloads (`access_token`, `expires_in`, `resource_url`)
+    ///
+    /// Only updates fields present in the JSON; others remain unchanged.
+    fn update_credential(&mut self, payload: impl Serialize) -> Result<(), PolluxError> {
+        use crate::providers::credential_update::{apply_expiry, parse_patch, set_opt, set_plain};
+
+        #[derive(Debug, Default, Deserialize)]
+        struct CredentialPatch {
+            refresh_token: Option<String>,
+            access_token: Option<String>,
+            resource_url: Option<String>,
+            expiry: Option<DateTime<Utc>>,
+            expires_in: Option<i64>,
+        }
+
+        let patch: CredentialPatch = parse_patch(payload)?;
+
+        set_plain(&mut self.refresh_token, patch.refresh_token);
+        set_plain(&mut self.access_token, patch.access_token);
+        set_opt(&mut self.resource_url, patch.resource_url);
+        apply_expiry(&mut self.expiry, patch.expires_in, patch.expiry);
+
+        debug!(
+            resource = %self.identifier(),
+            "Qwen resource updated successfully"
+        );
+
+        Ok(())
+    }
+
+    fn try_from_oauth_token_response(
+        token_response: &OauthTokenResponse,
+        refresh_seed: Option<&RefreshTokenSeed>,
+    ) -> Result<Self, PolluxError> {
+        let access_token = Some(token_response.access_token().secret().trim())
+            .filter(|&s| !s.is_empty())
+            .map(ToString::to_string)
+            .ok_or_else(|| {
+                PolluxError::UnexpectedError("Missing access_token in OAuth token response".into())
+            })?;
+
+        let expiry = Utc::now()
+            + token_response
+                .expires_in()
+                .unwrap_or(std::time::Duration::from_hours(1));
+
+        // qwen.ai rotates refresh tokens, but keep the seed if none came back.
+        let refresh_token = token_response
+            .refresh_token()
+            .map(|t| t.secret().trim())
+            .filter(|&s| !s.is_empty())
+            .or_else(|| refresh_seed.map(RefreshTokenSeed::refresh_token))
+            .map(ToString::to_string)
+            .ok_or_else(|| {
+                PolluxError::UnexpectedError("Missing refresh_token in OAuth token response".into())
+            })?;
+
+        let resource_url = token_response
+            .extra_fields()
+            .extra
+            .get("resource_url")
+            .and_then(Value::as_str)
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(ToString::to_string);
+
+        Ok(QwenResource {
+            refresh_token,
+            access_token,
+            resource_url,
+            expiry,
+            labels: Vec::new(),
+            proxy_url: None,
+        })
+    }
+}
+
+impl Schedulable for QwenResource {
+    type Lease = QwenLease;
+    const COOLDOWN_GRANULARITY: CooldownScope = CooldownScope::PerCredential;
+
+    /// Resource host, for logs only; several accounts usually share it.
+    fn identifier(&self) -> &str {
+        self.resource_url
+            .as_deref()
+            .unwrap_or(DEFAULT_RESOURCE_HOST)
+    }
+
+    fn expires_within(&self, min_validity: std::time::Duration) -> bool {
+        Duration::from_std(min_validity)
+            .ok()
+            .and_then(|margin| Utc::now().checked_add_signed(margin))
+            .is_none_or(|deadline| deadline >= self.expiry)
+    }
+
+    fn expired_beyond(&self, grace: std::time::Duration) -> bool {
+        Duration::from_std(grace)
+            .ok()
+            .and_then(|grace| self.expiry.checked_add_signed(grace))
+            .is_none_or(|deadline| Utc::now() > deadline)
+    }
+
+    fn make_lease(&self, id: CredentialId) -> QwenLease {
+        QwenLease {
+            id,
+            access_token: self.access_token.clone(),
+            resource_url: self.resource_url.clone(),
+            proxy_url: self.proxy_url.clone(),
+        }
+    }
+
+    fn labels(&self) -> &[String] {
+        &self.labels
+    }
+}
+
+impl TryFrom<QwenProfile> for QwenResource {
+    type Error = PolluxError;
+
+    fn try_from(profile: QwenProfile) -> Result<Self, Self::Error> {
+        let access_token = profile
+            .access_token
+            .ok_or(PolluxError::MissingAccessToken)?;
+        let expiry = profile.expiry.ok_or(PolluxError::MissingExpiry)?;
+
+        Ok(QwenResource {
+            refresh_token: profile.refresh_token,
+            access_token,
+            resource_url: profile.resource_url,
+            expiry,
+            labels: Vec::new(),
+            proxy_url: None,
+        })
+    }
+}
+
+impl From<DbQwenResource> for QwenResource {
+    fn from(d: DbQwenResource) -> Self {
+        QwenResource {
+            refresh_token: d.refresh_token,
+            access_token: d.access_token,
+            resource_url: d.resource_url,
+            expiry: d.expiry,
+            labels: split_labels(&d.labels),
+            proxy_url: d.proxy_url.and_then(|url| url.parse().ok()),
+        }
+    }
+}
+
+impl From<QwenResource> for QwenCreate {
+    fn from(cred: QwenResource) -> Self {
+        QwenCreate {
+            refresh_token: cred.refresh_token,
+            access_token: cred.access_token,
+            resource_url: cred.resource_url,
+            expiry: cred.expiry,
+            labels: cred.labels,
+            proxy_url: cred.proxy_url,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn token_response_carries_resource_url() {
+        let token_response: OauthTokenResponse = serde_json::from_value(json!({
+            "access_token": "at",
+            "token_type": "Bearer",
+            "expires_in": 21600,
+            "refresh_token": "rt",
+            "resource_url": "portal.qwen.ai",
+        }))
+        .expect("token response deserializes");
+
+        let cred = QwenResource::try_from_oauth_token_response(&token_response, None)
+            .expect("tokens present");
+
+        assert_eq!(cred.resource_url(), Some("portal.qwen.ai"));
+        assert_eq!(cred.refresh_token(), "rt");
+        assert!(!cred.expires_within(std::time::Duration::from_hours(5)));
+    }
+
+    #[test]
+    fn refresh_keeps_resource_url_unless_sent() {
+        let mut cred = QwenResource::from_payload(json!({
+            "refresh_token": "rt",
+            "access_token": "at0",
+            "resource_url": "portal.qwen.ai",
+            "expiry": Utc::now() - chrono::Duration::minutes(10),
+        }))
+        .expect("valid payload");
+
+        cred.update_credential(json!({
+            "access_token": "at1",
+            "expires_in": 3600,
+        }))
+        .expect("valid update");
+
+        assert_eq!(cred.access_token(), "at1");
+        assert_eq!(cred.resource_url(), Some("portal.qwen.ai"));
+        assert!(!cred.is_expired());
+    }
+}
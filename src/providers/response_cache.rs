@@ -0,0 +1,218 @@
+//! Response cache for deterministic requests (`providers.<p>.response_cache`).
+//!
+//! Only non-streaming requests with `temperature = 0` are cached, keyed by the
+//! canonical hash of the route, model and request body. Only `200` bodies are
+//! stored. Entries live in memory and, with `disk_path`, also in one file per
+//! key so a restart does not spend quota on prompts that were already answered.
+
+use crate::config::ResponseCacheConfig;
+use crate::utils::request_hash::canonical_request_hash;
+use axum::{
+    body::{Bytes, to_bytes},
+    http::{HeaderValue, StatusCode, header::CONTENT_TYPE},
+    response::{IntoResponse, Response},
+};
+use moka::sync::Cache;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use tracing::{debug, warn};
+
+/// Response header telling clients whether the cache answered.
+pub const CACHE_HEADER: &str = "x-pollux-cache";
+
+/// Largest response body that will be cached.
+const MAX_CACHED_BODY: usize = 8 * 1024 * 1024;
+
+#[derive(Clone)]
+pub struct ResponseCache {
+    memory: Cache<String, Bytes>,
+    disk: Option<PathBuf>,
+    ttl: Duration,
+}
+
+impl ResponseCache {
+    pub fn new(cfg: &ResponseCacheConfig) -> Self {
+        let ttl = Duration::from_secs(cfg.ttl_secs.max(1));
+        if let Some(dir) = &cfg.disk_path
+            && let Err(e) = std::fs::create_dir_all(dir)
+        {
+            warn!(path = %dir.display(), error = %e, "Response cache directory unavailable");
+        }
+        Self {
+            memory: Cache::builder()
+                .time_to_live(ttl)
+                .max_capacity(cfg.max_entries.max(1))
+                .build(),
+            disk: cfg.disk_path.clone(),
+            ttl,
+        }
+    }
+
+    /// Cache entry for a request, or `None` when caching is off for the
+    /// provider or the request is not deterministic (streaming, or a
+    /// `temperature` other than `0`).
+    pub fn entry<'a, T: Serialize>(
+        cache: Option<&'a Self>,
+        route: &str,
+        model: &str,
+        body: &T,
+        stream: bool,
+        temperature: Option<f64>,
+    ) -> Option<CacheEntry<'a>> {
+        let cache = cache?;
+        if stream || !temperature.is_some_and(|t| t <= 0.0) {
+            return None;
+        }
+        let hash = canonical_request_hash(&format!("{route}:{model}"), body)?;
+        Some(CacheEntry {
+            cache,
+            key: hash.trim_start_matches("sha256:").to_string(),
+        })
+    }
+
+    /// A `200` answered from the cache, marked `x-pollux-cache: hit`.
+    async fn lookup(&self, key: &str) -> Option<Response> {
+        let body = if let Some(body) = self.memory.get(key) {
+            body
+        } else {
+            let body = self.read_disk(key).await?;
+            self.memory.insert(key.to_string(), body.clone());
+            body
+        };
+        debug!(key, "Response cache hit");
+        let mut resp = (
+            StatusCode::OK,
+            [(CONTENT_TYPE, HeaderValue::from_static("application/json"))],
+            body,
+        )
+            .into_response();
+        resp.headers_mut()
+            .insert(CACHE_HEADER, HeaderValue::from_static("hit"));
+        Some(resp)
+    }
+
+    async fn store(&self, key: String, resp: Response) -> Response {
+        if resp.status() != StatusCode::OK {
+            return resp;
+        }
+        let (mut parts, body) = resp.into_parts();
+        let body = match to_bytes(body, MAX_CACHED_BODY).await {
+            Ok(body) => body,
+            Err(e) => {
+                warn!(error = %e, "Response too large or unreadable; not cached");
+                return (StatusCode::BAD_GATEWAY, e.to_string()).into_response();
+            }
+        };
+        self.memory.insert(key.clone(), body.clone());
+        self.write_disk(key, body.clone());
+        parts
+            .headers
+            .insert(CACHE_HEADER, HeaderValue::from_static("miss"));
+        Response::from_parts(parts, body.into())
+    }
+
+    async fn read_disk(&self, key: &str) -> Option<Bytes> {
+        let path = entry_path(self.disk.as_deref()?, key);
+        let ttl = self.ttl;
+        tokio::task::spawn_blocking(move || {
+            let age = std::fs::metadata(&path)
+                .and_then(|m| m.modified())
+                .ok()
+                .and_then(|modified| SystemTime::now().duration_since(modified).ok())?;
+            if age >= ttl {
+                let _ = std::fs::remove_file(&path);
+                return None;
+            }
+            std::fs::read(&path).ok().map(Bytes::from)
+        })
+        .await
+        .ok()
+        .flatten()
+    }
+
+    fn write_disk(&self, key: String, body: Bytes) {
+        let Some(dir) = self.disk.clone() else {
+            return;
+        };
+        tokio::task::spawn_blocking(move || {
+            let path = entry_path(&dir, &key);
+            let tmp = path.with_extension("tmp");
+            if let Err(e) = std::fs::write(&tmp, &body).and_then(|()| std::fs::rename(&tmp, &path))
+            {
+                warn!(path = %path.display(), error = %e, "Response cache write failed");
+            }
+        });
+    }
+}
+
+/// One cacheable request, from [`ResponseCache::entry`].
+pub struct CacheEntry<'a> {
+    cache: &'a ResponseCache,
+    key: String,
+}
+
+impl CacheEntry<'_> {
+    /// The stored response, marked `x-pollux-cache: hit`.
+    pub async fn hit(&self) -> Option<Response> {
+        self.cache.lookup(&self.key).await
+    }
+
+    /// Store a successful response and hand it back marked
+    /// `x-pollux-cache: miss`; other statuses pass through untouched.
+    pub async fn store(self, resp: Response) -> Response {
+        self.cache.store(self.key, resp).await
+    }
+}
+
+fn entry_path(dir: &Path, key: &str) -> PathBuf {
+    dir.join(format!("{key}.json"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn deterministic_responses_are_stored_and_served_from_disk() {
+        let dir =
+            std::env::temp_dir().join(format!("pollux-response-cache-{}", std::process::id()));
+        let cfg = ResponseCacheConfig {
+            disk_path: Some(dir.clone()),
+            ..Default::default()
+        };
+        let body = json!({"contents": [{"parts": [{"text": "hi"}]}]});
+        let cache = ResponseCache::new(&cfg);
+        let entry = |stream, temperature| {
+            ResponseCache::entry(Some(&cache), "gemini", "m", &body, stream, temperature)
+        };
+        assert!(entry(true, Some(0.0)).is_none());
+        assert!(entry(false, Some(0.7)).is_none());
+        assert!(entry(false, None).is_none());
+        assert!(ResponseCache::entry(None, "gemini", "m", &body, false, Some(0.0)).is_none());
+
+        let miss = entry(false, Some(0.0)).unwrap();
+        let key = miss.key.clone();
+        assert!(miss.hit().await.is_none());
+        let stored = miss
+            .store((StatusCode::OK, "{\"ok\":true}").into_response())
+            .await;
+        assert_eq!(stored.headers()[CACHE_HEADER], "miss");
+
+        // Disk writes are off the request path; wait for the file to land.
+        for _ in 0..50 {
+            if entry_path(&dir, &key).exists() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        let restarted = ResponseCache::new(&cfg);
+        let hit = restarted.lookup(&key).await.expect("served from disk");
+        assert_eq!(hit.headers()[CACHE_HEADER], "hit");
+        let bytes = to_bytes(hit.into_body(), usize::MAX).await.unwrap();
+        assert_eq!(&bytes[..], b"{\"ok\":true}");
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
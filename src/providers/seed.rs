@@ -5,16 +5,27 @@
 //! not derive `Debug`: refresh tokens are long-lived secrets and any accidental
 //! `{:?}` formatting (tracing events, panic messages, error chains) must not
 //! leak them.
+//!
+//! `resource:add?validate=true` onboards each seed synchronously; the outcome
+//! travels back to the waiting request as a [`SeedReport`].
 
+use crate::db::normalize_labels;
+use crate::utils::http::redact_proxy;
+use ractor::RpcReplyPort;
+use serde::Serialize;
+use std::collections::HashMap;
 use std::fmt;
+use url::Url;
 
 /// Untrusted input: a refresh token submitted from an external source.
 ///
 /// Construct via [`RefreshTokenSeed::new`], which trims whitespace and rejects
 /// empty values. The contained token is never exposed through `Debug`.
 #[derive(Clone)]
-pub(crate) struct RefreshTokenSeed {
+pub struct RefreshTokenSeed {
     refresh_token: String,
+    labels: Vec<String>,
+    proxy_url: Option<Url>,
 }
 
 impl RefreshTokenSeed {
@@ -25,7 +36,23 @@ impl RefreshTokenSeed {
         if refresh_token.is_empty() {
             return None;
         }
-        Some(Self { refresh_token })
+        Some(Self {
+            refresh_token,
+            labels: Vec::new(),
+            proxy_url: None,
+        })
+    }
+
+    /// Attach the pool labels submitted alongside the token.
+    pub(crate) fn with_labels(mut self, labels: &[String]) -> Self {
+        self.labels = normalize_labels(labels);
+        self
+    }
+
+    /// Bind the credential to its own egress proxy.
+    pub(crate) fn with_proxy_url(mut self, proxy_url: Option<Url>) -> Self {
+        self.proxy_url = proxy_url;
+        self
     }
 
     /// Borrow the underlying token. Callers are responsible for not logging
@@ -33,12 +60,143 @@ impl RefreshTokenSeed {
     pub(crate) fn refresh_token(&self) -> &str {
         &self.refresh_token
     }
+
+    pub(crate) fn labels(&self) -> &[String] {
+        &self.labels
+    }
+
+    pub(crate) fn proxy_url(&self) -> Option<&Url> {
+        self.proxy_url.as_ref()
+    }
 }
 
 impl fmt::Debug for RefreshTokenSeed {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("RefreshTokenSeed")
             .field("refresh_token", &"<redacted>")
+            .field("labels", &self.labels)
+            .field("proxy", &self.proxy_url.as_ref().map(redact_proxy))
             .finish()
     }
 }
+
+/// Outcome of validating one submitted seed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SeedStatus {
+    /// Onboarded and activated.
+    Ok,
+    /// Already active, or repeated earlier in the same request.
+    Duplicate,
+    /// Missing token, or refresh/onboarding failed.
+    Invalid,
+}
+
+/// Per-item result of `resource:add?validate=true`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SeedReport {
+    pub status: SeedStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub credential_id: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    /// Project id (Gemini CLI, Antigravity) or account id (Codex).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub project: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl SeedReport {
+    pub fn invalid(error: impl fmt::Display) -> Self {
+        Self {
+            status: SeedStatus::Invalid,
+            credential_id: None,
+            email: None,
+            project: None,
+            error: Some(error.to_string()),
+        }
+    }
+
+    pub fn duplicate() -> Self {
+        Self {
+            status: SeedStatus::Duplicate,
+            credential_id: None,
+            email: None,
+            project: None,
+            error: None,
+        }
+    }
+
+    pub(crate) fn onboarded(id: u64, email: Option<String>, project: String) -> Self {
+        Self {
+            status: SeedStatus::Ok,
+            credential_id: Some(id),
+            email,
+            project: Some(project),
+            error: None,
+        }
+    }
+
+    /// Mark an onboarded report as a duplicate of a stored account.
+    pub(crate) fn duplicate_if(mut self, duplicate: bool) -> Self {
+        if duplicate {
+            self.status = SeedStatus::Duplicate;
+        }
+        self
+    }
+}
+
+/// A validation waiting for its credential to be activated; sent with
+/// `ActivateCredential` so the actor can tell new from already-active ids.
+#[derive(Debug)]
+pub(crate) struct PendingSeedReport {
+    pub report: SeedReport,
+    pub reply: RpcReplyPort<SeedReport>,
+}
+
+impl PendingSeedReport {
+    pub(crate) fn send(mut self, already_active: bool) {
+        if already_active {
+            self.report.status = SeedStatus::Duplicate;
+        }
+        let _ = self.reply.send(self.report);
+    }
+}
+
+/// Where an onboarded credential ended up in the database.
+#[derive(Debug)]
+pub struct StoredSeed<R> {
+    pub id: u64,
+    /// The credential as it should run: the stored row when `existing`.
+    pub credential: R,
+    /// The account already had a row, which was updated in place.
+    pub existing: bool,
+}
+
+/// Reply ports of in-flight validations, keyed by the ticket carried on the
+/// onboarding job.
+#[derive(Default)]
+pub(crate) struct SeedValidations {
+    next: u64,
+    pending: HashMap<u64, RpcReplyPort<SeedReport>>,
+}
+
+impl SeedValidations {
+    pub(crate) fn register(&mut self, reply: RpcReplyPort<SeedReport>) -> u64 {
+        self.next += 1;
+        self.pending.insert(self.next, reply);
+        self.next
+    }
+
+    pub(crate) fn take(&mut self, ticket: u64) -> Option<RpcReplyPort<SeedReport>> {
+        self.pending.remove(&ticket)
+    }
+
+    /// Answer `ticket` right away, e.g. when onboarding failed.
+    pub(crate) fn fail(&mut self, ticket: u64, error: impl fmt::Display) {
+        if let Some(reply) = self.take(ticket) {
+            let _ = reply.send(SeedReport::invalid(error));
+        }
+    }
+}
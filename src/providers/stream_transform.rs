@@ -0,0 +1,664 @@
+//! Pluggable transformations of generated text.
+//!
+//! Respond modules hand every chunk to a per-request [`StreamPipeline`], which
+//! extracts text deltas, runs them through the configured [`StreamTransformer`]
+//! stages and writes the result back. Stages may hold text back across chunks;
+//! it is released at message boundaries (non-text parts, `finishReason`).
+//!
+//! The `repair` stage also fixes raw payloads before they are parsed; see
+//! [`StreamPipeline::repair_payload`].
+
+use crate::config::StreamTransformerConfig;
+use pollux_schema::gemini::{Content, GeminiResponseBody, Part};
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashSet};
+use std::fmt::Write as _;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeltaKind {
+    Text,
+    Thought,
+}
+
+/// A piece of generated text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Delta {
+    pub kind: DeltaKind,
+    pub text: String,
+}
+
+impl Delta {
+    pub fn text(text: impl Into<String>) -> Self {
+        Self {
+            kind: DeltaKind::Text,
+            text: text.into(),
+        }
+    }
+
+    pub fn thought(text: impl Into<String>) -> Self {
+        Self {
+            kind: DeltaKind::Thought,
+            text: text.into(),
+        }
+    }
+}
+
+/// One stage of a stream pipeline: deltas in, deltas out.
+pub trait StreamTransformer: Send {
+    /// Transform deltas as they arrive. A stage may return fewer deltas than it
+    /// received; anything held back must be returned from [`Self::flush`].
+    fn transform(&mut self, deltas: Vec<Delta>) -> Vec<Delta>;
+
+    /// Release held-back text. Called at message boundaries and stream end.
+    fn flush(&mut self) -> Vec<Delta> {
+        Vec::new()
+    }
+
+    /// Emit anything needed to close out the response. Called once, after
+    /// [`Self::flush`], when the response ends; never at other boundaries.
+    fn finish(&mut self) -> Vec<Delta> {
+        Vec::new()
+    }
+}
+
+/// Drops thought deltas.
+#[derive(Debug, Default)]
+pub struct StripThoughts;
+
+impl StreamTransformer for StripThoughts {
+    fn transform(&mut self, mut deltas: Vec<Delta>) -> Vec<Delta> {
+        deltas.retain(|d| d.kind != DeltaKind::Thought);
+        deltas
+    }
+}
+
+/// Replaces listed words, matched as whole words and case-insensitively.
+///
+/// A trailing partial word is held back until the next delta so words split
+/// across chunks are still caught.
+#[derive(Debug)]
+pub struct Redact {
+    words: HashSet<String>,
+    replacement: String,
+    pending: Option<Delta>,
+}
+
+impl Redact {
+    pub fn new(words: &[String], replacement: &str) -> Self {
+        Self {
+            words: words
+                .iter()
+                .map(|w| w.trim().to_lowercase())
+                .filter(|w| !w.is_empty())
+                .collect(),
+            replacement: replacement.to_string(),
+            pending: None,
+        }
+    }
+
+    fn redact(&self, text: &str) -> String {
+        let mut out = String::with_capacity(text.len());
+        let mut word_start = None;
+        for (i, c) in text.char_indices() {
+            if c.is_alphanumeric() {
+                word_start.get_or_insert(i);
+            } else {
+                if let Some(start) = word_start.take() {
+                    self.push_word(&mut out, &text[start..i]);
+                }
+                out.push(c);
+            }
+        }
+        if let Some(start) = word_start {
+            self.push_word(&mut out, &text[start..]);
+        }
+        out
+    }
+
+    fn push_word(&self, out: &mut String, word: &str) {
+        if self.words.contains(&word.to_lowercase()) {
+            out.push_str(&self.replacement);
+        } else {
+            out.push_str(word);
+        }
+    }
+
+    fn release(&self, mut delta: Delta) -> Delta {
+        delta.text = self.redact(&delta.text);
+        delta
+    }
+}
+
+impl StreamTransformer for Redact {
+    fn transform(&mut self, deltas: Vec<Delta>) -> Vec<Delta> {
+        let mut out = Vec::new();
+        for delta in deltas {
+            let mut text = match self.pending.take() {
+                Some(pending) if pending.kind == delta.kind => pending.text,
+                Some(pending) => {
+                    out.push(self.release(pending));
+                    String::new()
+                }
+                None => String::new(),
+            };
+            text.push_str(&delta.text);
+
+            let split = text
+                .char_indices()
+                .rfind(|(_, c)| !c.is_alphanumeric())
+                .map_or(0, |(i, c)| i + c.len_utf8());
+            let tail = text.split_off(split);
+            if !text.is_empty() {
+                out.push(Delta {
+                    kind: delta.kind,
+                    text: self.redact(&text),
+                });
+            }
+            if !tail.is_empty() {
+                self.pending = Some(Delta {
+                    kind: delta.kind,
+                    text: tail,
+                });
+            }
+        }
+        out
+    }
+
+    fn flush(&mut self) -> Vec<Delta> {
+        self.pending
+            .take()
+            .map(|pending| self.release(pending))
+            .into_iter()
+            .collect()
+    }
+}
+
+/// Buffers consecutive deltas of the same kind until `min_chars` bytes are ready.
+#[derive(Debug)]
+pub struct Coalesce {
+    min_chars: usize,
+    buffer: Option<Delta>,
+}
+
+impl Coalesce {
+    pub fn new(min_chars: usize) -> Self {
+        Self {
+            min_chars,
+            buffer: None,
+        }
+    }
+}
+
+impl StreamTransformer for Coalesce {
+    fn transform(&mut self, deltas: Vec<Delta>) -> Vec<Delta> {
+        let mut out = Vec::new();
+        for delta in deltas {
+            match &mut self.buffer {
+                Some(buffer) if buffer.kind == delta.kind => buffer.text.push_str(&delta.text),
+                _ => out.extend(self.buffer.replace(delta)),
+            }
+            if self
+                .buffer
+                .as_ref()
+                .is_some_and(|b| b.text.len() >= self.min_chars)
+            {
+                out.extend(self.buffer.take());
+            }
+        }
+        out
+    }
+
+    fn flush(&mut self) -> Vec<Delta> {
+        self.buffer.take().into_iter().collect()
+    }
+}
+
+/// Longest line prefix kept while looking for fence markers.
+const MAX_FENCE_LINE: usize = 256;
+
+/// Closes a Markdown code fence that is still open when the response ends,
+/// so truncated output does not leave the client rendering everything after
+/// it as code. Text passes through unchanged; thoughts are not tracked.
+#[derive(Debug, Default)]
+pub struct RepairFences {
+    /// Marker character and run length of the open fence.
+    open: Option<(char, usize)>,
+    line: String,
+}
+
+impl RepairFences {
+    fn observe(&mut self, text: &str) {
+        for c in text.chars() {
+            if c == '\n' {
+                self.end_line();
+            } else if self.line.len() < MAX_FENCE_LINE {
+                self.line.push(c);
+            }
+        }
+    }
+
+    fn end_line(&mut self) {
+        let line = std::mem::take(&mut self.line);
+        let Some((marker, len, rest)) = fence_marker(&line) else {
+            return;
+        };
+        match self.open {
+            None => self.open = Some((marker, len)),
+            Some((open, open_len))
+                if marker == open && len >= open_len && rest.trim().is_empty() =>
+            {
+                self.open = None;
+            }
+            Some(_) => {}
+        }
+    }
+}
+
+/// `(marker, run length, rest of line)` for a `CommonMark` fence line.
+fn fence_marker(line: &str) -> Option<(char, usize, &str)> {
+    let body = line.trim_start_matches(' ');
+    if line.len() - body.len() > 3 {
+        return None;
+    }
+    let marker = body.chars().next().filter(|c| matches!(c, '`' | '~'))?;
+    let rest = body.trim_start_matches(marker);
+    let len = body.len() - rest.len();
+    if len < 3 || (marker == '`' && rest.contains('`')) {
+        return None;
+    }
+    Some((marker, len, rest))
+}
+
+impl StreamTransformer for RepairFences {
+    fn transform(&mut self, deltas: Vec<Delta>) -> Vec<Delta> {
+        for delta in deltas.iter().filter(|d| d.kind == DeltaKind::Text) {
+            self.observe(&delta.text);
+        }
+        deltas
+    }
+
+    fn finish(&mut self) -> Vec<Delta> {
+        let mid_line = !self.line.is_empty();
+        self.end_line();
+        let Some((marker, len)) = self.open.take() else {
+            return Vec::new();
+        };
+        let mut close = String::from(if mid_line { "\n" } else { "" });
+        close.extend(std::iter::repeat_n(marker, len));
+        vec![Delta::text(close)]
+    }
+}
+
+const REPLACEMENT_ESCAPE: &str = "\\ufffd";
+
+/// Repairs UTF-16 surrogate escapes in raw upstream JSON, which `serde_json`
+/// rejects when unpaired. A high surrogate that ends one chunk's string is
+/// carried into the next chunk when that chunk's string starts with the low
+/// half; any other unpaired half becomes U+FFFD.
+#[derive(Debug, Default)]
+pub struct SurrogateRepair {
+    carried: Option<u16>,
+}
+
+impl SurrogateRepair {
+    pub fn repair<'a>(&mut self, data: &'a str) -> Cow<'a, str> {
+        let mut carried = self.carried.take();
+        if carried.is_none() && !data.contains("\\ud") && !data.contains("\\uD") {
+            return Cow::Borrowed(data);
+        }
+        let bytes = data.as_bytes();
+        let mut out = String::with_capacity(data.len() + 12);
+        let (mut copied, mut i) = (0, 0);
+        while i < bytes.len() {
+            if bytes[i] != b'\\' {
+                i += 1;
+                continue;
+            }
+            let Some(unit) = escape_unit(bytes, i) else {
+                i += 2;
+                continue;
+            };
+            let next = i + 6;
+            if !(0xD800..=0xDFFF).contains(&unit) {
+                i = next;
+                continue;
+            }
+            out.push_str(&data[copied..i]);
+            let mut end = next;
+            if unit <= 0xDBFF {
+                match escape_unit(bytes, next) {
+                    Some(low) if (0xDC00..=0xDFFF).contains(&low) => {
+                        end = next + 6;
+                        out.push_str(&data[i..end]);
+                    }
+                    _ if bytes.get(next) == Some(&b'"') => self.carried = Some(unit),
+                    _ => out.push_str(REPLACEMENT_ESCAPE),
+                }
+            } else {
+                match carried.take() {
+                    Some(high) if out.ends_with('"') => {
+                        let _ = write!(out, "\\u{high:04x}");
+                        out.push_str(&data[i..next]);
+                    }
+                    _ => out.push_str(REPLACEMENT_ESCAPE),
+                }
+            }
+            copied = end;
+            i = end;
+        }
+        out.push_str(&data[copied..]);
+        Cow::Owned(out)
+    }
+}
+
+/// Code unit of the `\uXXXX` escape starting at `at`, if there is one.
+fn escape_unit(bytes: &[u8], at: usize) -> Option<u16> {
+    if bytes.get(at..at + 2)? != b"\\u" {
+        return None;
+    }
+    let hex = std::str::from_utf8(bytes.get(at + 2..at + 6)?).ok()?;
+    u16::from_str_radix(hex, 16).ok()
+}
+
+/// Ordered transformer stages for one response.
+#[derive(Default)]
+pub struct StreamPipeline {
+    stages: Vec<Box<dyn StreamTransformer>>,
+    surrogates: Option<SurrogateRepair>,
+}
+
+impl StreamPipeline {
+    pub fn from_config(configs: &[StreamTransformerConfig]) -> Self {
+        let mut pipeline = Self::default();
+        for cfg in configs {
+            let stage: Box<dyn StreamTransformer> = match cfg {
+                StreamTransformerConfig::StripThoughts => Box::new(StripThoughts),
+                StreamTransformerConfig::Redact { words, replacement } => {
+                    Box::new(Redact::new(words, replacement))
+                }
+                StreamTransformerConfig::Coalesce { min_chars } => {
+                    Box::new(Coalesce::new(*min_chars))
+                }
+                StreamTransformerConfig::Repair { fences, surrogates } => {
+                    if *surrogates {
+                        pipeline.surrogates = Some(SurrogateRepair::default());
+                    }
+                    if !*fences {
+                        continue;
+                    }
+                    Box::new(RepairFences::default())
+                }
+            };
+            pipeline.stages.push(stage);
+        }
+        pipeline
+    }
+
+    /// Fix a raw upstream payload before it is parsed. Borrows the input
+    /// unchanged unless a `repair` stage with `surrogates` is configured.
+    pub fn repair_payload<'a>(&mut self, data: &'a str) -> Cow<'a, str> {
+        match self.surrogates.as_mut() {
+            Some(repair) => repair.repair(data),
+            None => Cow::Borrowed(data),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.stages.is_empty()
+    }
+
+    pub fn push(&mut self, deltas: Vec<Delta>) -> Vec<Delta> {
+        self.stages
+            .iter_mut()
+            .fold(deltas, |carried, stage| stage.transform(carried))
+    }
+
+    /// Flush every stage, feeding each stage's output through the stages after it.
+    pub fn flush(&mut self) -> Vec<Delta> {
+        self.stages.iter_mut().fold(Vec::new(), |carried, stage| {
+            let mut out = stage.transform(carried);
+            out.extend(stage.flush());
+            out
+        })
+    }
+
+    /// Like [`Self::flush`], but also lets every stage close out the response.
+    pub fn finish(&mut self) -> Vec<Delta> {
+        self.stages.iter_mut().fold(Vec::new(), |carried, stage| {
+            let mut out = stage.transform(carried);
+            out.extend(stage.flush());
+            out.extend(stage.finish());
+            out
+        })
+    }
+
+    /// Apply the pipeline to one Gemini response chunk (or a whole JSON response).
+    ///
+    /// Plain text parts become deltas; other parts act as boundaries and keep
+    /// their position. Text parts that carry a signature or metadata are
+    /// transformed in isolation so the attached data stays with its text.
+    /// Held-back text is flushed, and the response finished, when the
+    /// candidate has a `finishReason`, or always when `complete` is set
+    /// (non-streaming responses).
+    ///
+    /// Returns `false` when the chunk ended up with nothing to send.
+    pub fn apply_gemini(&mut self, body: &mut GeminiResponseBody, complete: bool) -> bool {
+        if self.is_empty() {
+            return true;
+        }
+        let Some(candidate) = body.candidates.first_mut() else {
+            return true;
+        };
+        let terminal = complete || candidate.finish_reason.is_some();
+        let had_parts = candidate
+            .content
+            .as_ref()
+            .is_some_and(|c| !c.parts.is_empty());
+
+        let parts = candidate
+            .content
+            .as_mut()
+            .map(|c| std::mem::take(&mut c.parts))
+            .unwrap_or_default();
+        let mut out = Vec::with_capacity(parts.len());
+        let mut run = Vec::new();
+        for part in parts {
+            if let Some(delta) = plain_delta(&part) {
+                run.push(delta);
+                continue;
+            }
+            out.extend(
+                self.push(std::mem::take(&mut run))
+                    .into_iter()
+                    .map(delta_part),
+            );
+            out.extend(self.flush().into_iter().map(delta_part));
+            out.extend(self.isolated(part));
+        }
+        out.extend(self.push(run).into_iter().map(delta_part));
+        if terminal {
+            out.extend(self.finish().into_iter().map(delta_part));
+        }
+
+        if out.is_empty() {
+            if let Some(content) = candidate.content.as_mut() {
+                content.parts = out;
+            }
+            return terminal || !had_parts;
+        }
+        match candidate.content.as_mut() {
+            Some(content) => content.parts = out,
+            None => {
+                candidate.content = Some(Content {
+                    role: Some("model".to_string()),
+                    parts: out,
+                    extra: BTreeMap::default(),
+                });
+            }
+        }
+        true
+    }
+
+    /// Run a boundary part's own text (if any) through every stage.
+    fn isolated(&mut self, mut part: Part) -> Option<Part> {
+        let Some(text) = part.text.take() else {
+            return Some(part);
+        };
+        let kind = delta_kind(&part);
+        let mut deltas = self.push(vec![Delta { kind, text }]);
+        deltas.extend(self.flush());
+        if deltas.is_empty() {
+            return None;
+        }
+        part.text = Some(deltas.into_iter().map(|d| d.text).collect());
+        Some(part)
+    }
+}
+
+fn delta_kind(part: &Part) -> DeltaKind {
+    if part.thought == Some(true) {
+        DeltaKind::Thought
+    } else {
+        DeltaKind::Text
+    }
+}
+
+fn plain_delta(part: &Part) -> Option<Delta> {
+    let plain = part.thought_signature.is_none()
+        && part.part_metadata.is_none()
+        && part.video_metadata.is_none()
+        && part.extra.is_empty();
+    if !plain {
+        return None;
+    }
+    part.text.as_ref().map(|text| Delta {
+        kind: delta_kind(part),
+        text: text.clone(),
+    })
+}
+
+fn delta_part(delta: Delta) -> Part {
+    Part {
+        thought: (delta.kind == DeltaKind::Thought).then_some(true),
+        text: Some(delta.text),
+        ..Part::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn chunk(value: serde_json::Value) -> GeminiResponseBody {
+        serde_json::from_value(value).unwrap()
+    }
+
+    fn parts(body: &GeminiResponseBody) -> serde_json::Value {
+        serde_json::to_value(&body.candidates[0].content.as_ref().unwrap().parts).unwrap()
+    }
+
+    #[test]
+    fn redact_catches_words_split_across_deltas() {
+        let mut redact = Redact::new(&["Darn".to_string()], "***");
+        let mut out = redact.transform(vec![Delta::text("oh da")]);
+        out.extend(redact.transform(vec![Delta::text("rn it, DARN")]));
+        out.extend(redact.flush());
+        let text: String = out.into_iter().map(|d| d.text).collect();
+        assert_eq!(text, "oh *** it, ***");
+    }
+
+    #[test]
+    fn coalesce_merges_until_threshold_and_splits_on_kind_change() {
+        let mut coalesce = Coalesce::new(4);
+        assert!(coalesce.transform(vec![Delta::text("ab")]).is_empty());
+        assert_eq!(
+            coalesce.transform(vec![Delta::text("cd"), Delta::thought("x")]),
+            vec![Delta::text("abcd")]
+        );
+        assert_eq!(
+            coalesce.transform(vec![Delta::text("e")]),
+            vec![Delta::thought("x")]
+        );
+        assert_eq!(coalesce.flush(), vec![Delta::text("e")]);
+    }
+
+    #[test]
+    fn gemini_chunks_strip_thoughts_and_drop_empty_chunks() {
+        let mut pipeline = StreamPipeline::from_config(&[
+            StreamTransformerConfig::StripThoughts,
+            StreamTransformerConfig::Coalesce { min_chars: 100 },
+        ]);
+
+        let mut thinking = chunk(json!({"candidates": [{"content": {"role": "model",
+            "parts": [{"text": "hmm", "thought": true}]}}]}));
+        assert!(!pipeline.apply_gemini(&mut thinking, false));
+
+        let mut first = chunk(json!({"candidates": [{"content": {"role": "model",
+            "parts": [{"text": "Hello "}]}}]}));
+        assert!(!pipeline.apply_gemini(&mut first, false));
+
+        let mut last = chunk(json!({"candidates": [{"content": {"role": "model",
+            "parts": [{"text": "world"}, {"functionCall": {"name": "f", "args": {}}}]},
+            "finishReason": "STOP"}]}));
+        assert!(pipeline.apply_gemini(&mut last, false));
+        assert_eq!(
+            parts(&last),
+            json!([{"text": "Hello world"}, {"functionCall": {"name": "f", "args": {}}}])
+        );
+    }
+
+    #[test]
+    fn gemini_signed_parts_keep_their_signature() {
+        let mut pipeline = StreamPipeline::from_config(&[StreamTransformerConfig::Redact {
+            words: vec!["secret".to_string()],
+            replacement: "[x]".to_string(),
+        }]);
+        let mut body = chunk(json!({"candidates": [{"content": {"role": "model",
+            "parts": [{"text": "a secret", "thoughtSignature": "sig"}]}}]}));
+        assert!(pipeline.apply_gemini(&mut body, false));
+        assert_eq!(
+            parts(&body),
+            json!([{"text": "a [x]", "thoughtSignature": "sig"}])
+        );
+    }
+
+    #[test]
+    fn repair_closes_fence_left_open_at_truncation() {
+        let mut pipeline = StreamPipeline::from_config(&[StreamTransformerConfig::Repair {
+            fences: true,
+            surrogates: false,
+        }]);
+        let mut first = chunk(json!({"candidates": [{"content": {"role": "model",
+            "parts": [{"text": "Done:\n```rust\nfn main() {}\n```\n\n~~~~py\nprint("}]}}]}));
+        assert!(pipeline.apply_gemini(&mut first, false));
+        let mut last = chunk(json!({"candidates": [{"content": {"role": "model",
+            "parts": [{"text": "1)"}]}, "finishReason": "MAX_TOKENS"}]}));
+        assert!(pipeline.apply_gemini(&mut last, false));
+        assert_eq!(parts(&last), json!([{"text": "1)"}, {"text": "\n~~~~"}]));
+
+        let mut balanced = StreamPipeline::from_config(&[StreamTransformerConfig::Repair {
+            fences: true,
+            surrogates: false,
+        }]);
+        let mut body = chunk(json!({"candidates": [{"content": {"role": "model",
+            "parts": [{"text": "```\na\n```"}]}, "finishReason": "STOP"}]}));
+        assert!(balanced.apply_gemini(&mut body, false));
+        assert_eq!(parts(&body), json!([{"text": "```\na\n```"}]));
+    }
+
+    #[test]
+    fn surrogate_halves_are_rejoined_across_payloads() {
+        let mut repair = SurrogateRepair::default();
+        let first = repair.repair(r#"{"text":"smile \ud83d"}"#);
+        assert_eq!(first, r#"{"text":"smile "}"#);
+        let second = repair.repair(r#"{"text":"\ude00 ok \udc00 \\ud800"}"#);
+        assert_eq!(second, r#"{"text":"\ud83d\ude00 ok \ufffd \\ud800"}"#);
+        let text: serde_json::Value = serde_json::from_str(&second).unwrap();
+        assert_eq!(text["text"], "\u{1f600} ok \u{fffd} \\ud800");
+        assert!(matches!(
+            repair.repair(r#"{"text":"plain"}"#),
+            Cow::Borrowed(_)
+        ));
+    }
+}
@@ -0,0 +1,116 @@
+//! Server-side `systemInstruction` policy for Gemini-shaped upstream payloads.
+
+use crate::config::{SystemInstructionConfig, SystemInstructionMode};
+use pollux_schema::gemini::{Content, GeminiGenerateContentRequest, Part};
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+
+/// Merge the configured text into `body.systemInstruction`.
+pub(crate) fn apply(
+    cfg: Option<&SystemInstructionConfig>,
+    body: &mut GeminiGenerateContentRequest,
+) {
+    let Some(cfg) = cfg else {
+        return;
+    };
+    let part = || Part {
+        text: Some(cfg.text.clone()),
+        ..Part::default()
+    };
+    let slot = body.system_instruction_mut();
+    match cfg.mode {
+        SystemInstructionMode::Off => {}
+        SystemInstructionMode::Replace if cfg.text.is_empty() => *slot = None,
+        SystemInstructionMode::Replace => *slot = Some(instruction(vec![part()])),
+        _ if cfg.text.is_empty() => {}
+        SystemInstructionMode::Prepend => match slot {
+            Some(content) => content.parts.insert(0, part()),
+            None => *slot = Some(instruction(vec![part()])),
+        },
+        SystemInstructionMode::Append => match slot {
+            Some(content) => content.parts.push(part()),
+            None => *slot = Some(instruction(vec![part()])),
+        },
+    }
+}
+
+/// `body` with the policy applied, cloned only when it changes anything.
+pub(crate) fn merged<'a>(
+    cfg: Option<&SystemInstructionConfig>,
+    body: &'a GeminiGenerateContentRequest,
+) -> Cow<'a, GeminiGenerateContentRequest> {
+    match cfg {
+        Some(cfg) if cfg.mode != SystemInstructionMode::Off => {
+            let mut body = body.clone();
+            apply(Some(cfg), &mut body);
+            Cow::Owned(body)
+        }
+        _ => Cow::Borrowed(body),
+    }
+}
+
+fn instruction(parts: Vec<Part>) -> Content {
+    Content {
+        role: None,
+        parts,
+        extra: BTreeMap::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn request(system: Option<&str>) -> GeminiGenerateContentRequest {
+        let mut body = json!({ "contents": [{ "role": "user", "parts": [{ "text": "hi" }] }] });
+        if let Some(system) = system {
+            body["systemInstruction"] = json!({ "parts": [{ "text": system }] });
+        }
+        serde_json::from_value(body).unwrap()
+    }
+
+    fn texts(body: &GeminiGenerateContentRequest) -> Vec<&str> {
+        body.system_instruction
+            .iter()
+            .flat_map(|c| &c.parts)
+            .filter_map(|p| p.text.as_deref())
+            .collect()
+    }
+
+    fn cfg(mode: SystemInstructionMode, text: &str) -> SystemInstructionConfig {
+        SystemInstructionConfig {
+            mode,
+            text: text.to_string(),
+        }
+    }
+
+    #[test]
+    fn modes_merge_with_client_instruction() {
+        let client = request(Some("client"));
+        let run = |mode, text| merged(Some(&cfg(mode, text)), &client).into_owned();
+
+        assert_eq!(
+            texts(&run(SystemInstructionMode::Prepend, "srv")),
+            ["srv", "client"]
+        );
+        assert_eq!(
+            texts(&run(SystemInstructionMode::Append, "srv")),
+            ["client", "srv"]
+        );
+        assert_eq!(texts(&run(SystemInstructionMode::Replace, "srv")), ["srv"]);
+        assert!(texts(&run(SystemInstructionMode::Replace, "")).is_empty());
+        assert_eq!(texts(&run(SystemInstructionMode::Off, "srv")), ["client"]);
+        assert_eq!(texts(&run(SystemInstructionMode::Prepend, "")), ["client"]);
+    }
+
+    #[test]
+    fn missing_instruction_is_created_and_off_borrows() {
+        let mut body = request(None);
+        apply(Some(&cfg(SystemInstructionMode::Append, "srv")), &mut body);
+        assert_eq!(texts(&body), ["srv"]);
+
+        let client = request(Some("client"));
+        assert!(matches!(merged(None, &client), Cow::Borrowed(_)));
+    }
+}
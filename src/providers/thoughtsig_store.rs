@@ -0,0 +1,231 @@
+//! Thought-signature storage selected by `providers.<p>.thoughtsig.storage`.
+//!
+//! The persistent store still answers lookups from memory; every captured
+//! signature is also upserted through the `DbActor`, and the newest unexpired
+//! rows are reloaded at startup so clients replaying a conversation after a
+//! restart keep getting real signatures instead of the fallback.
+//!
+//! Rows carry the fingerprint version of their key. Only the configured
+//! `fingerprint` and `migrate_from` versions are reloaded; rows of any other
+//! version can never be hit again and are deleted by the pruner.
+
+use crate::config::{ThoughtSigConfig, ThoughtSigStorage};
+use crate::db::{DbActorHandle, ThoughtSignatureRow};
+use crate::providers::manifest::ProviderKind;
+use chrono::Utc;
+use moka::sync::Cache;
+use pollux_thoughtsig_core::{
+    CacheCounters, CacheKey, FingerprintVersion, Invalidation, MemorySignatureStore,
+    SignatureCacheStats, SignatureCacheStore, ThoughtSignature,
+};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{debug, info, warn};
+
+const PRUNE_INTERVAL: Duration = Duration::from_mins(10);
+
+/// Build the store configured for `provider`.
+pub async fn signature_store(
+    db: &DbActorHandle,
+    provider: ProviderKind,
+    cfg: &ThoughtSigConfig,
+) -> Arc<dyn SignatureCacheStore> {
+    match cfg.storage {
+        ThoughtSigStorage::Memory => {
+            Arc::new(MemorySignatureStore::new(cfg.ttl_secs, cfg.max_capacity))
+        }
+        ThoughtSigStorage::Persistent => {
+            let store = PersistentSignatureStore::load(db.clone(), provider, cfg).await;
+            store.spawn_pruner();
+            Arc::new(store)
+        }
+    }
+}
+
+#[derive(Clone)]
+struct Entry {
+    signature: ThoughtSignature,
+    /// Capture time, so reloaded rows expire on the original schedule
+    /// rather than a fresh TTL from startup.
+    created_at: i64,
+    model: Arc<str>,
+}
+
+/// In-memory signatures written through to the `thought_signatures` table.
+#[derive(Clone)]
+pub struct PersistentSignatureStore {
+    memory: Cache<CacheKey, Entry>,
+    counters: Arc<CacheCounters>,
+    db: DbActorHandle,
+    provider: &'static str,
+    ttl_secs: i64,
+    max_capacity: i64,
+    /// Version bytes worth keeping: the current one and the one migrated from.
+    versions: [i16; 2],
+}
+
+impl PersistentSignatureStore {
+    /// Prune expired rows, then warm memory with what is left.
+    ///
+    /// A database failure is logged and the store starts empty; signatures
+    /// are an optimisation, not a reason to refuse to start.
+    pub async fn load(db: DbActorHandle, provider: ProviderKind, cfg: &ThoughtSigConfig) -> Self {
+        let ttl_secs = cfg.ttl_secs.max(1);
+        let counters = Arc::new(CacheCounters::default());
+        let listener = counters.clone();
+        let store = Self {
+            memory: Cache::builder()
+                .time_to_live(Duration::from_secs(ttl_secs))
+                .max_capacity(cfg.max_capacity.max(1))
+                .support_invalidation_closures()
+                .eviction_listener(move |_, _, cause| listener.record_removal(cause))
+                .build(),
+            counters,
+            db,
+            provider: provider.label(),
+            ttl_secs: i64::try_from(ttl_secs).unwrap_or(i64::MAX),
+            max_capacity: i64::try_from(cfg.max_capacity.max(1)).unwrap_or(i64::MAX),
+            versions: [cfg.fingerprint, cfg.migrate_from.unwrap_or(cfg.fingerprint)]
+                .map(|version| i16::from(version.byte())),
+        };
+
+        store.prune().await;
+        let since = Utc::now().timestamp().saturating_sub(store.ttl_secs);
+        match store
+            .db
+            .load_thought_signatures(store.provider, since, store.max_capacity)
+            .await
+        {
+            Ok(rows) => {
+                let count = rows.len();
+                for row in rows {
+                    let Some(version) = u8::try_from(row.key_version)
+                        .ok()
+                        .and_then(FingerprintVersion::from_byte)
+                        .filter(|version| store.versions.contains(&i16::from(version.byte())))
+                    else {
+                        continue;
+                    };
+                    store.memory.insert(
+                        CacheKey::new(version, row.cache_key.cast_unsigned()),
+                        Entry {
+                            signature: Arc::from(row.signature),
+                            created_at: row.created_at,
+                            model: Arc::from(row.model),
+                        },
+                    );
+                }
+                info!(
+                    provider = store.provider,
+                    count, "Restored persisted thought signatures"
+                );
+            }
+            Err(e) => {
+                warn!(provider = store.provider, error = %e, "Failed to load thought signatures");
+            }
+        }
+        store
+    }
+
+    async fn prune(&self) {
+        let before = Utc::now().timestamp().saturating_sub(self.ttl_secs);
+        match self
+            .db
+            .prune_thought_signatures(self.provider, before, self.max_capacity, self.versions)
+            .await
+        {
+            Ok(removed) => debug!(
+                provider = self.provider,
+                removed, "Pruned thought signatures"
+            ),
+            Err(e) => {
+                warn!(provider = self.provider, error = %e, "Failed to prune thought signatures");
+            }
+        }
+    }
+
+    /// Put `entry` in memory and upsert its row.
+    fn write(&self, key: CacheKey, entry: Entry) {
+        self.db.record_thought_signature(ThoughtSignatureRow {
+            provider: self.provider.to_string(),
+            cache_key: key.hash.cast_signed(),
+            key_version: i16::from(key.version.byte()),
+            signature: entry.signature.to_string(),
+            model: entry.model.to_string(),
+            created_at: entry.created_at,
+        });
+        self.memory.insert(key, entry);
+    }
+
+    /// Apply the TTL and size bound to the table until the runtime shuts down.
+    fn spawn_pruner(&self) {
+        let store = self.clone();
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(PRUNE_INTERVAL);
+            ticker.tick().await;
+            loop {
+                ticker.tick().await;
+                store.prune().await;
+            }
+        });
+    }
+}
+
+impl SignatureCacheStore for PersistentSignatureStore {
+    fn get(&self, key: &CacheKey) -> Option<ThoughtSignature> {
+        let signature = self.memory.get(key).and_then(|entry| {
+            let age = Utc::now().timestamp().saturating_sub(entry.created_at);
+            (age < self.ttl_secs).then_some(entry.signature)
+        });
+        self.counters.record_lookup(signature.is_some());
+        signature
+    }
+
+    fn insert(&self, key: CacheKey, signature: ThoughtSignature, model: Option<&str>) {
+        self.counters.record_insert();
+        self.write(
+            key,
+            Entry {
+                signature,
+                created_at: Utc::now().timestamp(),
+                model: Arc::from(model.unwrap_or_default()),
+            },
+        );
+    }
+
+    fn stats(&self) -> SignatureCacheStats {
+        self.memory.run_pending_tasks();
+        self.counters.snapshot(self.memory.entry_count())
+    }
+
+    /// Drops matching entries from memory and, asynchronously, from the table.
+    fn invalidate(&self, scope: &Invalidation) {
+        match scope {
+            Invalidation::Key(key) => self.memory.invalidate(key),
+            Invalidation::Model(model) => {
+                let model: Arc<str> = Arc::from(model.as_str());
+                let _ = self
+                    .memory
+                    .invalidate_entries_if(move |_, entry| entry.model == model);
+            }
+            Invalidation::All => self.memory.invalidate_all(),
+        }
+        self.memory.run_pending_tasks();
+        self.db
+            .delete_thought_signatures(self.provider, scope.clone());
+    }
+
+    /// Keeps the capture time and model tag, and deletes the old row.
+    fn rekey(&self, from: &CacheKey, to: CacheKey) -> Option<ThoughtSignature> {
+        let entry = self.memory.remove(from).filter(|entry| {
+            Utc::now().timestamp().saturating_sub(entry.created_at) < self.ttl_secs
+        });
+        self.counters.record_lookup(entry.is_some());
+        let entry = entry?;
+        let signature = entry.signature.clone();
+        self.write(to, entry);
+        self.db
+            .delete_thought_signatures(self.provider, Invalidation::Key(*from));
+        Some(signature)
+    }
+}
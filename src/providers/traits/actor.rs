@@ -0,0 +1,1560 @@
+//! Pool actor shared by the [`Provider`] implementations: schedules leases,
+//! tracks cooldowns and refreshes, parks waiting requests and serves the
+//! admin API.
+
+use super::oauth_worker::{
+    CredentialJob, CredentialJobKind, CredentialProcessError, CredentialProcessResult,
+    OauthWorkerHandle,
+};
+use super::provider::{CredentialStore, PoolLease, PoolSettings, Provider, ProviderResource};
+use super::route_table::RouteTable;
+use super::scheduler::{
+    AssignmentStats, CredentialId, HealthEvent, HealthVerdict, ResourceScheduler, Schedulable,
+};
+use super::waiters::LeaseWaiters;
+use crate::config::Priority;
+use crate::db::DbActorHandle;
+use crate::error::{OauthError, PolluxError};
+use crate::model_catalog::{MODEL_REGISTRY, ModelCapabilities};
+use crate::oauth_utils::OauthTokenResponse;
+use crate::providers::credential_view::{CredentialView, merge_runtime};
+use crate::providers::pool_status::{PoolErrorKind, PoolStatus, RecentErrors};
+use crate::providers::{Lease, PendingSeedReport, RefreshTokenSeed, SeedReport, SeedValidations};
+use crate::server::alerts::{AlertEvent, alert};
+use crate::server::coordination::is_leader;
+use ractor::{Actor, ActorProcessingErr, ActorRef, RpcReplyPort};
+use std::marker::PhantomData;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tracing::{debug, error, info, warn};
+
+/// Lease handed out by `P`'s pool.
+pub type LeaseOf<P> = <<P as Provider>::Resource as Schedulable>::Lease;
+
+/// Public messages handled by a provider pool actor.
+pub enum ProviderActorMessage<P: Provider> {
+    /// Request one available credential for the given model mask.
+    /// The optional `u64` is the session `route_key` (see `crate::server::session`) for affinity.
+    /// `pool` limits the choice to credentials with that label (see `crate::server::pool`).
+    /// `priority` orders parked callers and gates the reserve (see `crate::server::priority`).
+    /// Returns `None` if none available.
+    GetCredential {
+        model_mask: ModelCapabilities,
+        route_key: Option<u64>,
+        pool: Option<String>,
+        priority: Priority,
+        reply: RpcReplyPort<Option<LeaseOf<P>>>,
+    },
+
+    /// Time until the first credential cooling on this model (within `pool`) frees up.
+    RetryAfter {
+        model_mask: ModelCapabilities,
+        pool: Option<String>,
+        reply: RpcReplyPort<Option<Duration>>,
+    },
+
+    /// Report rate limiting; start a per-model cooldown for this credential.
+    ReportRateLimit {
+        id: CredentialId,
+        model_mask: ModelCapabilities,
+        cooldown: Duration,
+    },
+
+    /// Report unsupported model (e.g. 400/404); clear capability bits for this credential.
+    ReportModelUnsupported {
+        id: CredentialId,
+        model_mask: ModelCapabilities,
+    },
+    /// Report the outcome of a request that was not rate limited, banned or
+    /// invalid; feeds success-rate driven auto-disable.
+    ReportOutcome {
+        id: CredentialId,
+        model_mask: ModelCapabilities,
+        success: bool,
+    },
+    /// Report a successful request and how long upstream took to answer;
+    /// feeds auto-disable and the latency/error scheduling policies.
+    ReportSuccess {
+        id: CredentialId,
+        model_mask: ModelCapabilities,
+        latency: Duration,
+    },
+
+    /// Report account quota left after a response; an exhausted quota cools
+    /// the credential down until it resets.
+    ReportQuota {
+        id: CredentialId,
+        remaining_percent: u8,
+        exhausted_for: Option<Duration>,
+    },
+
+    /// Report invalid/expired access (e.g. 401); refresh then re-enqueue.
+    ReportInvalid { id: CredentialId },
+
+    /// Report a credential as banned/unusable; remove from queues and storage.
+    ReportBanned { id: CredentialId },
+
+    /// A lease handed out by `GetCredential` is no longer in use.
+    ReleaseCredential { id: CredentialId },
+
+    /// Submit a trusted OAuth token response (from an approved login).
+    ///
+    /// This should already contain `access_token` + `expiry`. The actor will convert it
+    /// into a trusted ingest job, then persist+activate it through the same completion path as
+    /// other credential ingest flows.
+    SubmitTrustedOauth(OauthTokenResponse),
+
+    /// Submit untrusted refresh token seeds and trigger zero-trust ingestion for each.
+    ///
+    /// This is intended for 0-trust ingestion (e.g. an add-credentials endpoint). The actor will
+    /// only persist+activate after a refresh succeeds and identity can be derived.
+    SubmitUntrustedSeeds(Vec<RefreshTokenSeed>),
+
+    /// Ingest one untrusted seed and reply with the outcome once it is
+    /// activated or fails.
+    ValidateSeed {
+        seed: RefreshTokenSeed,
+        reply: RpcReplyPort<SeedReport>,
+    },
+
+    /// Admin: list stored credentials merged with live scheduler state.
+    ListCredentials {
+        reply: RpcReplyPort<Result<Vec<CredentialView>, PolluxError>>,
+    },
+
+    /// Admin: scheduler snapshot (queues, cooldowns, refreshes, recent errors).
+    GetStatus { reply: RpcReplyPort<PoolStatus> },
+
+    /// Admin: enable or disable a credential in storage and in the scheduler.
+    SetCredentialStatus {
+        id: CredentialId,
+        enabled: bool,
+        reply: RpcReplyPort<Result<(), PolluxError>>,
+    },
+
+    /// Admin: drop a credential from the scheduler and delete it from storage.
+    DeleteCredential {
+        id: CredentialId,
+        reply: RpcReplyPort<Result<(), PolluxError>>,
+    },
+
+    /// Admin: force one model on (pinned against auto-disable) or off for a credential.
+    SetModelOverride {
+        id: CredentialId,
+        model_mask: ModelCapabilities,
+        enabled: bool,
+        reply: RpcReplyPort<Result<(), PolluxError>>,
+    },
+
+    /// Admin: cool a credential on some models (every provider model for an
+    /// empty mask) for a while, or lift the cooldown with `None`.
+    SetCooldown {
+        id: CredentialId,
+        model_mask: ModelCapabilities,
+        cooldown: Option<Duration>,
+        reply: RpcReplyPort<Result<(), PolluxError>>,
+    },
+
+    /// Admin: replace the set of models a credential is scheduled for.
+    SetCapabilities {
+        id: CredentialId,
+        model_mask: ModelCapabilities,
+        reply: RpcReplyPort<Result<(), PolluxError>>,
+    },
+
+    // Internal messages (sent by the actor itself / workers)
+    /// Background credential processing has completed.
+    ProcessComplete {
+        result: CredentialProcessResult<P::Resource>,
+    },
+    /// A credential has been processed and stored; activate it in memory queues.
+    ActivateCredential {
+        id: CredentialId,
+        credential: P::Resource,
+        /// Models the onboarding probe found unsupported; left out of its queues.
+        unsupported: ModelCapabilities,
+        report: Option<PendingSeedReport>,
+    },
+    /// Retry parked `GetCredential` calls (cooldown ended or a wait deadline hit).
+    ServeWaiters,
+    /// Periodic: give back models lost longer than `capability_restore_secs` ago.
+    RestoreLostModels,
+}
+
+impl<P: Provider> ProviderActorMessage<P> {
+    /// Messages after which a parked `GetCredential` may now be served.
+    fn frees_capacity(&self) -> bool {
+        matches!(
+            self,
+            Self::ReleaseCredential { .. }
+                | Self::ProcessComplete { .. }
+                | Self::ActivateCredential { .. }
+                | Self::ServeWaiters
+                | Self::RestoreLostModels
+                | Self::SetCooldown { cooldown: None, .. }
+                | Self::SetCapabilities { .. }
+        )
+    }
+}
+
+/// Handle for interacting with a provider pool actor.
+pub struct ProviderActorHandle<P: Provider> {
+    actor: ActorRef<ProviderActorMessage<P>>,
+}
+
+impl<P: Provider> Clone for ProviderActorHandle<P> {
+    fn clone(&self) -> Self {
+        Self {
+            actor: self.actor.clone(),
+        }
+    }
+}
+
+impl<P: Provider> ProviderActorHandle<P> {
+    /// Request a credential based on target model mask.
+    /// If `route_key` is provided, the actor will attempt session-affinity routing first.
+    /// The current request's priority class is sent along.
+    pub async fn get_credential(
+        &self,
+        model_mask: ModelCapabilities,
+        route_key: Option<u64>,
+        pool: Option<String>,
+    ) -> Result<Option<Lease<LeaseOf<P>>>, PolluxError> {
+        let priority = crate::server::priority::current();
+        let lease = ractor::call!(self.actor, |reply| ProviderActorMessage::GetCredential {
+            model_mask,
+            route_key,
+            pool,
+            priority,
+            reply,
+        })
+        .map_err(|e| PolluxError::RactorError(format!("GetCredential RPC failed: {e}")))?;
+        let actor = self.actor.clone();
+        Ok(lease.map(|lease| {
+            let id = lease.credential_id();
+            Lease::new(id, lease, move || {
+                let _ = ractor::cast!(actor, ProviderActorMessage::ReleaseCredential { id });
+            })
+        }))
+    }
+
+    /// Shortest remaining cooldown for `model_mask`, as a hint for clients
+    /// once retries are exhausted. `None` when nothing is cooling.
+    pub async fn retry_after(
+        &self,
+        model_mask: ModelCapabilities,
+        pool: Option<String>,
+    ) -> Option<Duration> {
+        ractor::call!(self.actor, |reply| ProviderActorMessage::RetryAfter {
+            model_mask,
+            pool,
+            reply
+        })
+        .ok()
+        .flatten()
+    }
+
+    /// Report rate limit; the actor will cool down this credential before reuse.
+    pub fn report_rate_limit(
+        &self,
+        id: CredentialId,
+        model_mask: ModelCapabilities,
+        cooldown: Duration,
+    ) {
+        let _ = ractor::cast!(
+            self.actor,
+            ProviderActorMessage::ReportRateLimit {
+                id,
+                model_mask,
+                cooldown
+            }
+        );
+    }
+
+    /// Report invalid/expired access (401); the actor will refresh before reuse.
+    pub fn report_invalid(&self, id: CredentialId) {
+        let _ = ractor::cast!(self.actor, ProviderActorMessage::ReportInvalid { id });
+    }
+
+    /// Report that a credential does not support a model (e.g. 404).
+    pub fn report_model_unsupported(&self, id: CredentialId, model_mask: ModelCapabilities) {
+        let _ = ractor::cast!(
+            self.actor,
+            ProviderActorMessage::ReportModelUnsupported { id, model_mask }
+        );
+    }
+
+    /// Report a success or generic upstream failure for auto-disable bookkeeping.
+    pub fn report_outcome(&self, id: CredentialId, model_mask: ModelCapabilities, success: bool) {
+        let _ = ractor::cast!(
+            self.actor,
+            ProviderActorMessage::ReportOutcome {
+                id,
+                model_mask,
+                success
+            }
+        );
+    }
+
+    /// Report a successful request that took `latency` until upstream answered.
+    pub fn report_success(
+        &self,
+        id: CredentialId,
+        model_mask: ModelCapabilities,
+        latency: Duration,
+    ) {
+        let _ = ractor::cast!(
+            self.actor,
+            ProviderActorMessage::ReportSuccess {
+                id,
+                model_mask,
+                latency
+            }
+        );
+    }
+
+    /// Report the account quota upstream says `id` has left.
+    pub fn report_quota(
+        &self,
+        id: CredentialId,
+        remaining_percent: u8,
+        exhausted_for: Option<Duration>,
+    ) {
+        let _ = ractor::cast!(
+            self.actor,
+            ProviderActorMessage::ReportQuota {
+                id,
+                remaining_percent,
+                exhausted_for
+            }
+        );
+    }
+
+    /// Report a credential as permanently banned/unusable; remove it entirely.
+    pub fn report_banned(&self, id: CredentialId) {
+        let _ = ractor::cast!(self.actor, ProviderActorMessage::ReportBanned { id });
+    }
+
+    /// Submit a trusted OAuth token response to the actor for trusted ingest + persistence.
+    pub(crate) fn submit_trusted_oauth(&self, token_response: OauthTokenResponse) {
+        let _ = ractor::cast!(
+            self.actor,
+            ProviderActorMessage::SubmitTrustedOauth(token_response)
+        );
+    }
+
+    /// Submit refresh tokens as 0-trust seeds. The actor will verify, then persist+activate.
+    pub(crate) fn submit_seeds(&self, seeds: Vec<RefreshTokenSeed>) {
+        if seeds.is_empty() {
+            return;
+        }
+
+        let _ = ractor::cast!(
+            self.actor,
+            ProviderActorMessage::SubmitUntrustedSeeds(seeds)
+        );
+    }
+
+    /// Ingest a refresh token synchronously, reporting the outcome.
+    pub(crate) async fn validate_seed(&self, seed: RefreshTokenSeed) -> SeedReport {
+        ractor::call!(self.actor, |reply| ProviderActorMessage::ValidateSeed {
+            seed,
+            reply
+        })
+        .unwrap_or_else(|e| SeedReport::invalid(format!("ValidateSeed RPC failed: {e}")))
+    }
+
+    pub(super) fn send_process_complete(
+        &self,
+        result: CredentialProcessResult<P::Resource>,
+    ) -> Result<(), PolluxError> {
+        ractor::cast!(self.actor, ProviderActorMessage::ProcessComplete { result })
+            .map_err(|e| PolluxError::RactorError(format!("ProcessComplete cast failed: {e}")))
+    }
+
+    /// Admin: list stored credentials merged with live scheduler state.
+    pub async fn list_credentials(&self) -> Result<Vec<CredentialView>, PolluxError> {
+        ractor::call!(self.actor, |reply| ProviderActorMessage::ListCredentials {
+            reply
+        })
+        .map_err(|e| PolluxError::RactorError(format!("ListCredentials RPC failed: {e}")))?
+    }
+
+    /// Admin: live scheduler snapshot for the dashboard.
+    pub async fn status(&self) -> Result<PoolStatus, PolluxError> {
+        ractor::call!(self.actor, |reply| ProviderActorMessage::GetStatus {
+            reply
+        })
+        .map_err(|e| PolluxError::RactorError(format!("GetStatus RPC failed: {e}")))
+    }
+
+    /// Admin: enable or disable a credential. Disabling takes it out of rotation immediately.
+    pub async fn set_credential_status(
+        &self,
+        id: CredentialId,
+        enabled: bool,
+    ) -> Result<(), PolluxError> {
+        ractor::call!(self.actor, |reply| {
+            ProviderActorMessage::SetCredentialStatus { id, enabled, reply }
+        })
+        .map_err(|e| PolluxError::RactorError(format!("SetCredentialStatus RPC failed: {e}")))?
+    }
+
+    /// Admin: remove a credential from rotation and delete it from storage.
+    pub async fn delete_credential(&self, id: CredentialId) -> Result<(), PolluxError> {
+        ractor::call!(self.actor, |reply| ProviderActorMessage::DeleteCredential {
+            id,
+            reply
+        })
+        .map_err(|e| PolluxError::RactorError(format!("DeleteCredential RPC failed: {e}")))?
+    }
+
+    /// Admin: force a model on or off for one credential, overriding auto-disable.
+    pub async fn set_model_override(
+        &self,
+        id: CredentialId,
+        model_mask: ModelCapabilities,
+        enabled: bool,
+    ) -> Result<(), PolluxError> {
+        ractor::call!(self.actor, |reply| ProviderActorMessage::SetModelOverride {
+            id,
+            model_mask,
+            enabled,
+            reply
+        })
+        .map_err(|e| PolluxError::RactorError(format!("SetModelOverride RPC failed: {e}")))?
+    }
+
+    /// Admin: cool a credential on `model_mask` (all models when empty), or
+    /// lift its cooldown with `None`.
+    pub async fn set_cooldown(
+        &self,
+        id: CredentialId,
+        model_mask: ModelCapabilities,
+        cooldown: Option<Duration>,
+    ) -> Result<(), PolluxError> {
+        ractor::call!(self.actor, |reply| ProviderActorMessage::SetCooldown {
+            id,
+            model_mask,
+            cooldown,
+            reply
+        })
+        .map_err(|e| PolluxError::RactorError(format!("SetCooldown RPC failed: {e}")))?
+    }
+
+    /// Admin: replace the models a credential is scheduled for.
+    pub async fn set_capabilities(
+        &self,
+        id: CredentialId,
+        model_mask: ModelCapabilities,
+    ) -> Result<(), PolluxError> {
+        ractor::call!(self.actor, |reply| ProviderActorMessage::SetCapabilities {
+            id,
+            model_mask,
+            reply
+        })
+        .map_err(|e| PolluxError::RactorError(format!("SetCapabilities RPC failed: {e}")))?
+    }
+}
+
+struct ProviderActorState<P: Provider> {
+    ops: P::Store,
+    recent_errors: RecentErrors,
+    manager: ResourceScheduler<P::Resource>,
+    router: RouteTable,
+    provider_supported_mask: ModelCapabilities,
+    processor_handle: OauthWorkerHandle<P>,
+    waiters: LeaseWaiters<LeaseOf<P>>,
+    validations: SeedValidations,
+}
+
+struct ProviderActor<P>(PhantomData<fn() -> P>);
+
+#[ractor::async_trait]
+impl<P: Provider> Actor for ProviderActor<P> {
+    type Msg = ProviderActorMessage<P>;
+    type State = ProviderActorState<P>;
+    type Arguments = (P::Store, Arc<P>, PoolSettings);
+
+    async fn pre_start(
+        &self,
+        myself: ActorRef<Self::Msg>,
+        (ops, provider, settings): Self::Arguments,
+    ) -> Result<Self::State, ActorProcessingErr> {
+        let (provider_supported_mask, model_names) = provider.supported_models();
+        let processor_handle = OauthWorkerHandle::spawn(
+            ProviderActorHandle {
+                actor: myself.clone(),
+            },
+            provider,
+            settings.oauth.clone(),
+        )
+        .await?;
+
+        let model_count = MODEL_REGISTRY.len();
+
+        let mut manager = ResourceScheduler::new(model_count)
+            .with_auto_disable(settings.auto_disable)
+            .with_health(settings.health)
+            .with_min_token_validity(settings.min_token_validity)
+            .with_stale_grace(settings.stale_grace)
+            .with_capability_restore(settings.capability_restore)
+            .with_tier_weights(settings.tier_weights)
+            .with_policy(settings.scheduling_policy)
+            .with_max_concurrent(settings.max_concurrent_per_credential)
+            .with_priority_reserve(settings.priority_reserve);
+
+        info!(
+            "{}Actor initializing with supported models: {:?}",
+            P::NAME,
+            model_names
+        );
+
+        let rows = ops.load_active().await.map_err(|e| {
+            ActorProcessingErr::from(format!("DB load active {} creds failed: {e}", P::NAME))
+        })?;
+        for (id, cred) in rows {
+            manager.add_credential(id, cred, provider_supported_mask.clone());
+        }
+
+        info!(
+            "{}Actor started from DB: {} active creds loaded into {} queues",
+            P::NAME,
+            manager.stats(&ModelCapabilities::none()).total_creds,
+            model_count
+        );
+
+        info!(
+            endpoint = %settings.endpoint,
+            proxy = %settings.oauth.proxy.as_ref().map_or("<none>", |u| u.as_str()),
+            enable_multiplexing = settings.oauth.enable_multiplexing,
+            retry_max_times = settings.retry_max_times,
+            oauth_tps = settings.oauth.oauth_tps,
+            "{}Actor runtime config loaded",
+            P::NAME
+        );
+
+        if let Some(period) = manager.restore_check_interval() {
+            myself.send_interval(period, || ProviderActorMessage::RestoreLostModels);
+        }
+
+        Ok(ProviderActorState {
+            ops,
+            recent_errors: RecentErrors::default(),
+            manager,
+            router: RouteTable::default(),
+            provider_supported_mask,
+            processor_handle,
+            waiters: LeaseWaiters::new(settings.lease_wait),
+            validations: SeedValidations::default(),
+        })
+    }
+
+    #[allow(clippy::too_many_lines)]
+    async fn handle(
+        &self,
+        myself: ActorRef<Self::Msg>,
+        message: Self::Msg,
+        state: &mut Self::State,
+    ) -> Result<(), ActorProcessingErr> {
+        let frees_capacity = message.frees_capacity();
+        match message {
+            ProviderActorMessage::GetCredential {
+                model_mask,
+                route_key,
+                pool,
+                priority,
+                reply,
+            } => {
+                Self::handle_get_credential(
+                    &myself,
+                    state,
+                    reply,
+                    &model_mask,
+                    route_key,
+                    pool,
+                    priority,
+                );
+            }
+
+            ProviderActorMessage::RetryAfter {
+                model_mask,
+                pool,
+                reply,
+            } => {
+                let _ = reply.send(state.manager.retry_after(&model_mask, pool.as_deref()));
+            }
+
+            ProviderActorMessage::ReportRateLimit {
+                id,
+                model_mask,
+                cooldown,
+            } => {
+                state
+                    .recent_errors
+                    .push(id, PoolErrorKind::RateLimited, &model_mask);
+                Self::handle_report_rate_limit(state, id, &model_mask, cooldown);
+            }
+
+            ProviderActorMessage::ReportModelUnsupported { id, model_mask } => {
+                state
+                    .recent_errors
+                    .push(id, PoolErrorKind::ModelUnsupported, &model_mask);
+                Self::handle_report_model_unsupported(state, id, &model_mask);
+            }
+            ProviderActorMessage::ReportOutcome {
+                id,
+                model_mask,
+                success,
+            } => {
+                if !success {
+                    state
+                        .recent_errors
+                        .push(id, PoolErrorKind::Failed, &model_mask);
+                }
+                Self::handle_report_outcome(state, id, &model_mask, success, None);
+            }
+            ProviderActorMessage::ReportSuccess {
+                id,
+                model_mask,
+                latency,
+            } => {
+                Self::handle_report_outcome(state, id, &model_mask, true, Some(latency));
+            }
+            ProviderActorMessage::ReportQuota {
+                id,
+                remaining_percent,
+                exhausted_for,
+            } => {
+                state
+                    .manager
+                    .report_quota(id, remaining_percent, exhausted_for);
+                if let Some(cooldown) = exhausted_for {
+                    info!(
+                        id,
+                        cooldown_secs = cooldown.as_secs(),
+                        "[{}] Account quota exhausted; cooling credential until reset",
+                        P::NAME
+                    );
+                }
+            }
+
+            ProviderActorMessage::ReportInvalid { id } => {
+                state
+                    .recent_errors
+                    .push(id, PoolErrorKind::Invalid, &ModelCapabilities::none());
+                state.manager.revoke_stale(id);
+                Self::handle_report_invalid(myself.clone(), state, vec![id]);
+            }
+
+            ProviderActorMessage::ReportBanned { id } => {
+                state
+                    .recent_errors
+                    .push(id, PoolErrorKind::Banned, &ModelCapabilities::none());
+                if state.manager.health_policy().is_some() {
+                    Self::handle_health_event(state, id, HealthEvent::Forbidden);
+                } else {
+                    Self::handle_report_banned(state, id);
+                }
+            }
+
+            ProviderActorMessage::ReleaseCredential { id } => state.manager.release(id),
+
+            ProviderActorMessage::SubmitTrustedOauth(token_response) => {
+                Self::handle_submit_trusted_oauth(state, token_response);
+            }
+
+            ProviderActorMessage::SubmitUntrustedSeeds(seeds) => {
+                Self::handle_submit_untrusted_seeds(state, seeds);
+            }
+
+            ProviderActorMessage::ValidateSeed { seed, reply } => {
+                Self::handle_validate_seed(state, &seed, reply);
+            }
+
+            ProviderActorMessage::ProcessComplete { result } => {
+                Self::handle_process_complete(&myself, state, result);
+            }
+
+            ProviderActorMessage::ListCredentials { reply } => {
+                Self::handle_list_credentials(state, reply);
+            }
+            ProviderActorMessage::GetStatus { reply } => {
+                let _ = reply.send(PoolStatus::collect(
+                    P::KIND,
+                    &state.manager,
+                    &state.provider_supported_mask,
+                    &state.recent_errors,
+                ));
+            }
+            ProviderActorMessage::SetCredentialStatus { id, enabled, reply } => {
+                Self::handle_set_credential_status(&myself, state, id, enabled, reply);
+            }
+            ProviderActorMessage::DeleteCredential { id, reply } => {
+                Self::handle_delete_credential(state, id, reply);
+            }
+            ProviderActorMessage::SetModelOverride {
+                id,
+                model_mask,
+                enabled,
+                reply,
+            } => {
+                Self::handle_set_model_override(state, id, &model_mask, enabled, reply);
+            }
+            ProviderActorMessage::SetCooldown {
+                id,
+                model_mask,
+                cooldown,
+                reply,
+            } => {
+                Self::handle_set_cooldown(state, id, &model_mask, cooldown, reply);
+            }
+            ProviderActorMessage::SetCapabilities {
+                id,
+                model_mask,
+                reply,
+            } => {
+                Self::handle_set_capabilities(state, id, &model_mask, reply);
+            }
+            ProviderActorMessage::ActivateCredential {
+                id,
+                credential,
+                unsupported,
+                report,
+            } => {
+                Self::handle_activate_credential(state, id, credential, &unsupported, report);
+            }
+
+            ProviderActorMessage::ServeWaiters => {}
+            ProviderActorMessage::RestoreLostModels => Self::handle_restore_lost_models(state),
+        }
+        if frees_capacity {
+            Self::serve_waiters(&myself, state);
+        }
+        Ok(())
+    }
+}
+
+impl<P: Provider> ProviderActor<P> {
+    fn handle_report_model_unsupported(
+        state: &mut ProviderActorState<P>,
+        id: CredentialId,
+        model_mask: &ModelCapabilities,
+    ) {
+        if model_mask.is_empty() || !state.manager.contains(id) {
+            return;
+        }
+
+        let ident = state.manager.get_identifier(id).to_owned();
+
+        let disabled_names = crate::model_catalog::format_model_mask(model_mask);
+
+        // Scheduler is pure logic; log the state transition at the actor boundary.
+        let Some((before_bits, after_bits)) = state.manager.mark_model_unsupported(id, model_mask)
+        else {
+            return;
+        };
+        if before_bits == after_bits {
+            return;
+        }
+
+        if after_bits.is_empty() {
+            warn!(
+                "{} credential id={} account={} now supports no models after disabling {} (mask={}); caps {} -> {}",
+                P::NAME,
+                id,
+                ident,
+                disabled_names,
+                model_mask,
+                before_bits,
+                after_bits
+            );
+        } else {
+            info!(
+                "{} credential id={} account={} disabled models {} (mask={}); caps {} -> {}",
+                P::NAME,
+                id,
+                ident,
+                disabled_names,
+                model_mask,
+                before_bits,
+                after_bits
+            );
+        }
+    }
+
+    fn handle_report_outcome(
+        state: &mut ProviderActorState<P>,
+        id: CredentialId,
+        model_mask: &ModelCapabilities,
+        success: bool,
+        latency: Option<Duration>,
+    ) {
+        let success_rate = match latency {
+            Some(latency) => state.manager.report_success(id, model_mask, latency),
+            None => state.manager.report_outcome(id, model_mask, success),
+        };
+        let event = if success {
+            HealthEvent::Success
+        } else {
+            HealthEvent::ServerError
+        };
+        Self::handle_health_event(state, id, event);
+        let Some(success_rate) = success_rate else {
+            return;
+        };
+        warn!(
+            id,
+            account = %state.manager.get_identifier(id),
+            model = %crate::model_catalog::format_model_mask(model_mask),
+            success_rate,
+            "[{}] Model auto-disabled for credential after repeated failures",
+            P::NAME
+        );
+    }
+
+    fn handle_health_event(
+        state: &mut ProviderActorState<P>,
+        id: CredentialId,
+        event: HealthEvent,
+    ) {
+        match state.manager.record_health(id, event) {
+            None => {}
+            Some(HealthVerdict::Parked { score, duration }) => {
+                warn!(
+                    id,
+                    account = %state.manager.get_identifier(id),
+                    score,
+                    park_secs = duration.as_secs(),
+                    "[{}] Credential parked after its health score dropped",
+                    P::NAME
+                );
+            }
+            Some(HealthVerdict::Ban { score }) => {
+                warn!(
+                    id,
+                    account = %state.manager.get_identifier(id),
+                    score,
+                    "[{}] Credential banned after its health score dropped",
+                    P::NAME
+                );
+                Self::handle_report_banned(state, id);
+            }
+        }
+    }
+
+    fn handle_restore_lost_models(state: &mut ProviderActorState<P>) {
+        for (id, models) in state.manager.restore_lost_models() {
+            info!(
+                id,
+                account = %state.manager.get_identifier(id),
+                models = %crate::model_catalog::format_model_mask(&models),
+                "[{}] Lost models restored; the next request re-validates them",
+                P::NAME
+            );
+        }
+    }
+
+    fn handle_set_model_override(
+        state: &mut ProviderActorState<P>,
+        id: CredentialId,
+        model_mask: &ModelCapabilities,
+        enabled: bool,
+        reply: RpcReplyPort<Result<(), PolluxError>>,
+    ) {
+        let model = crate::model_catalog::format_model_mask(model_mask);
+        let res = if state.provider_supported_mask.contains_all(model_mask) {
+            state
+                .manager
+                .set_model_override(id, model_mask, enabled)
+                .ok_or_else(|| PolluxError::NotFound(format!("credential {id} is not loaded")))
+        } else {
+            Err(PolluxError::NotFound(format!(
+                "model {model} is not served by this provider"
+            )))
+        };
+        if let Ok((before, after)) = &res {
+            info!(id, model = %model, enabled, caps.before = %before, caps.after = %after, "[{}] Model override set via admin API", P::NAME);
+        }
+        let _ = reply.send(res.map(|_| ()));
+    }
+
+    fn handle_set_cooldown(
+        state: &mut ProviderActorState<P>,
+        id: CredentialId,
+        model_mask: &ModelCapabilities,
+        cooldown: Option<Duration>,
+        reply: RpcReplyPort<Result<(), PolluxError>>,
+    ) {
+        let model_mask = if model_mask.is_empty() {
+            &state.provider_supported_mask
+        } else {
+            model_mask
+        };
+        let model = crate::model_catalog::format_model_mask(model_mask);
+        let res = if !state.provider_supported_mask.contains_all(model_mask) {
+            Err(PolluxError::NotFound(format!(
+                "model {model} is not served by this provider"
+            )))
+        } else if state.manager.set_manual_cooldown(id, model_mask, cooldown) {
+            Ok(())
+        } else {
+            Err(PolluxError::NotFound(format!(
+                "credential {id} is not loaded"
+            )))
+        };
+        if res.is_ok() {
+            info!(id, model = %model, cooldown_secs = cooldown.map(|c| c.as_secs()), "[{}] Cooldown set via admin API", P::NAME);
+        }
+        let _ = reply.send(res);
+    }
+
+    fn handle_set_capabilities(
+        state: &mut ProviderActorState<P>,
+        id: CredentialId,
+        model_mask: &ModelCapabilities,
+        reply: RpcReplyPort<Result<(), PolluxError>>,
+    ) {
+        let res = if state.provider_supported_mask.contains_all(model_mask) {
+            state
+                .manager
+                .set_capabilities(id, model_mask)
+                .ok_or_else(|| PolluxError::NotFound(format!("credential {id} is not loaded")))
+        } else {
+            Err(PolluxError::NotFound(format!(
+                "model {} is not served by this provider",
+                crate::model_catalog::format_model_mask(model_mask)
+            )))
+        };
+        if let Ok((before, after)) = &res {
+            info!(id, caps.before = %before, caps.after = %after, "[{}] Capabilities set via admin API", P::NAME);
+        }
+        let _ = reply.send(res.map(|_| ()));
+    }
+
+    fn handle_get_credential(
+        myself: &ActorRef<ProviderActorMessage<P>>,
+        state: &mut ProviderActorState<P>,
+        reply_port: RpcReplyPort<Option<LeaseOf<P>>>,
+        model_mask: &ModelCapabilities,
+        route_key: Option<u64>,
+        pool: Option<String>,
+        priority: Priority,
+    ) {
+        let sched_stats = match Self::try_assign(
+            myself,
+            state,
+            model_mask,
+            route_key,
+            pool.as_deref(),
+            priority,
+        ) {
+            Ok(assigned) => {
+                Self::send_lease(state, reply_port, assigned);
+                return;
+            }
+            Err(miss) => miss,
+        };
+
+        let Err(reply_port) =
+            state
+                .waiters
+                .park(model_mask.clone(), route_key, pool, priority, reply_port)
+        else {
+            debug!(model_mask = %model_mask, "[{}] No credential available; request queued", P::NAME);
+            Self::schedule_waiters(myself, state);
+            return;
+        };
+        warn!(
+            model_mask = %model_mask,
+            queue = sched_stats.queue_len,
+            total = sched_stats.total_creds,
+            cooling = sched_stats.cooldowns,
+            refreshing = sched_stats.refreshing,
+            skipped.cooling = sched_stats.skipped_cooling,
+            skipped.refreshing = sched_stats.skipped_refreshing,
+            skipped.expired = sched_stats.skipped_expired,
+            skipped.busy = sched_stats.skipped_busy,
+            skipped.other_pool = sched_stats.skipped_other_pool,
+            held_in_reserve = sched_stats.held_in_reserve,
+            ?priority,
+            "[{}] No credential available",
+            P::NAME
+        );
+        alert(AlertEvent::PoolEmpty {
+            provider: P::KIND,
+            model: crate::model_catalog::format_model_mask(model_mask),
+        });
+        let _ = reply_port.send(None);
+    }
+
+    /// One scheduling attempt; the stats explain a miss.
+    fn try_assign(
+        myself: &ActorRef<ProviderActorMessage<P>>,
+        state: &mut ProviderActorState<P>,
+        model_mask: &ModelCapabilities,
+        route_key: Option<u64>,
+        pool: Option<&str>,
+        priority: Priority,
+    ) -> Result<LeaseOf<P>, AssignmentStats> {
+        let sticky_id = route_key.and_then(|rk| state.router.get(rk, model_mask));
+        let start = Instant::now();
+        let assignment = state
+            .manager
+            .get_assigned(model_mask, sticky_id, pool, priority);
+        let sched_us = start.elapsed().as_micros();
+        let sched_stats = assignment.stats;
+
+        if !assignment.refresh_ids.is_empty() {
+            Self::handle_report_invalid(myself.clone(), state, assignment.refresh_ids);
+        }
+
+        let Some(assigned) = assignment.assigned else {
+            return Err(sched_stats);
+        };
+        if let Some(rk) = route_key
+            && !assignment.route_hit
+        {
+            state
+                .router
+                .insert(rk, model_mask, assigned.credential_id());
+        }
+
+        info!(
+            sched_us,
+            id = assigned.credential_id(),
+            account = %state.manager.get_identifier(assigned.credential_id()),
+            model_mask = %model_mask,
+            sticky = assignment.route_hit,
+            queue = sched_stats.queue_len,
+            total = sched_stats.total_creds,
+            cooling = sched_stats.cooldowns,
+            refreshing = sched_stats.refreshing,
+            "[{}] Credential assigned",
+            P::NAME
+        );
+        Ok(assigned)
+    }
+
+    /// Hand a lease to its caller, taking it back if the caller is gone.
+    fn send_lease(
+        state: &mut ProviderActorState<P>,
+        reply_port: RpcReplyPort<Option<LeaseOf<P>>>,
+        lease: LeaseOf<P>,
+    ) {
+        let id = lease.credential_id();
+        if reply_port.send(Some(lease)).is_err() {
+            state.manager.release(id);
+        }
+    }
+
+    /// Retry parked requests, highest priority first.
+    fn serve_waiters(
+        myself: &ActorRef<ProviderActorMessage<P>>,
+        state: &mut ProviderActorState<P>,
+    ) {
+        if state.waiters.is_empty() {
+            return;
+        }
+        for waiter in state.waiters.take_live(Instant::now()) {
+            match Self::try_assign(
+                myself,
+                state,
+                &waiter.model_mask,
+                waiter.route_key,
+                waiter.pool.as_deref(),
+                waiter.priority,
+            ) {
+                Ok(assigned) => Self::send_lease(state, waiter.reply, assigned),
+                Err(_) => state.waiters.requeue(waiter),
+            }
+        }
+        Self::schedule_waiters(myself, state);
+    }
+
+    fn schedule_waiters(
+        myself: &ActorRef<ProviderActorMessage<P>>,
+        state: &mut ProviderActorState<P>,
+    ) {
+        let next_cooldown = state.manager.next_cooldown_expiry();
+        if let Some(delay) = state.waiters.schedule_wake(Instant::now(), next_cooldown) {
+            myself.send_after(delay, || ProviderActorMessage::ServeWaiters);
+        }
+    }
+
+    fn handle_report_rate_limit(
+        state: &mut ProviderActorState<P>,
+        id: CredentialId,
+        model_mask: &ModelCapabilities,
+        cooldown: Duration,
+    ) {
+        if !state.manager.contains(id) {
+            return;
+        }
+        state.manager.report_rate_limit(id, model_mask, cooldown);
+        info!(
+            "ID: {id}, Credential starting cooldown, model_mask={}, re-enqueue after {} secs",
+            model_mask,
+            cooldown.as_secs(),
+        );
+    }
+
+    fn handle_report_invalid(
+        myself: ActorRef<ProviderActorMessage<P>>,
+        state: &mut ProviderActorState<P>,
+        ids: Vec<CredentialId>,
+    ) {
+        let mut jobs_to_send = Vec::new();
+        for id in ids {
+            if state.manager.is_refreshing(id) {
+                debug!("ID: {id} already refreshing, skipping.");
+                continue;
+            }
+            if let Some(current) = state.manager.get_credential_clone(id) {
+                state.manager.mark_refreshing(id);
+
+                info!(
+                    "ID: {}, Resource: {}, invalid/expired reported.",
+                    id,
+                    current.identifier()
+                );
+                jobs_to_send.push((id, current));
+            }
+        }
+        if jobs_to_send.is_empty() {
+            return;
+        }
+        if !is_leader() {
+            Self::reload_from_leader(myself, state.ops.clone(), jobs_to_send);
+            return;
+        }
+
+        let processor_handle = state.processor_handle.clone();
+        tokio::spawn(async move {
+            for (id, cred) in jobs_to_send {
+                let job = CredentialJob::refresh(id, cred);
+                if let Err(e) = processor_handle.submit(job.clone()) {
+                    warn!("ID: {id} credential refresh enqueue failed. Rolling back.");
+                    let _ = myself.cast(ProviderActorMessage::ProcessComplete {
+                        result: Err(CredentialProcessError {
+                            original_job: job,
+                            error: e,
+                        }),
+                    });
+                } else {
+                    debug!("ID: {id} refresh enqueued.");
+                }
+            }
+        });
+    }
+
+    /// Follower side of `basic.coordination`: the leader process owns OAuth
+    /// refreshes, so pick up the token it has stored instead. A stored token
+    /// no newer than ours completes as a transient failure and is retried on
+    /// the next report.
+    fn reload_from_leader(
+        myself: ActorRef<ProviderActorMessage<P>>,
+        ops: P::Store,
+        jobs: Vec<(CredentialId, P::Resource)>,
+    ) {
+        tokio::spawn(async move {
+            for (id, cred) in jobs {
+                let job = CredentialJob::refresh(id, cred);
+                let result = match ops.get_by_id(id).await {
+                    Ok(stored) if stored.expiry() > job.cred.expiry() => {
+                        debug!("ID: {id} picked up token refreshed by the leader.");
+                        Ok(CredentialJob::refresh(id, stored))
+                    }
+                    Ok(_) => Err(CredentialProcessError {
+                        original_job: job,
+                        error: PolluxError::UnexpectedError(
+                            "awaiting refresh by the leader process".to_string(),
+                        ),
+                    }),
+                    Err(error) => Err(CredentialProcessError {
+                        original_job: job,
+                        error,
+                    }),
+                };
+                let _ = myself.cast(ProviderActorMessage::ProcessComplete { result });
+            }
+        });
+    }
+
+    fn handle_list_credentials(
+        state: &ProviderActorState<P>,
+        reply: RpcReplyPort<Result<Vec<CredentialView>, PolluxError>>,
+    ) {
+        let runtime = state.manager.runtime_snapshot();
+        let ops = state.ops.clone();
+        tokio::spawn(async move {
+            let res = ops
+                .load_all_views()
+                .await
+                .map(|views| merge_runtime(views, &runtime));
+            let _ = reply.send(res);
+        });
+    }
+
+    fn handle_set_credential_status(
+        myself: &ActorRef<ProviderActorMessage<P>>,
+        state: &mut ProviderActorState<P>,
+        id: CredentialId,
+        enabled: bool,
+        reply: RpcReplyPort<Result<(), PolluxError>>,
+    ) {
+        let ops = state.ops.clone();
+        let loaded = state.manager.contains(id);
+        if !enabled {
+            state.manager.delete_credential(id);
+            info!("ID: {id}, disabled via admin API. removed_from_mem={loaded}");
+        }
+
+        let myself = myself.clone();
+        tokio::spawn(async move {
+            let res = async {
+                // Resolve first so unknown ids surface as 404 rather than a failed patch.
+                let credential = ops.get_by_id(id).await?;
+                ops.set_status(id, enabled).await?;
+                if enabled && !loaded {
+                    myself
+                        .cast(ProviderActorMessage::ActivateCredential {
+                            id,
+                            credential,
+                            unsupported: ModelCapabilities::none(),
+                            report: None,
+                        })
+                        .map_err(|e| {
+                            PolluxError::RactorError(format!("ActivateCredential cast failed: {e}"))
+                        })?;
+                }
+                Ok(())
+            }
+            .await;
+            let _ = reply.send(res);
+        });
+    }
+
+    fn handle_delete_credential(
+        state: &mut ProviderActorState<P>,
+        id: CredentialId,
+        reply: RpcReplyPort<Result<(), PolluxError>>,
+    ) {
+        let removed = state.manager.contains(id);
+        state.manager.delete_credential(id);
+        info!("ID: {id}, deleted via admin API. removed_from_mem={removed}");
+
+        let ops = state.ops.clone();
+        tokio::spawn(async move {
+            let _ = reply.send(ops.delete(id).await);
+        });
+    }
+
+    fn handle_report_banned(state: &mut ProviderActorState<P>, id: CredentialId) {
+        let ident = state.manager.get_identifier(id).to_owned();
+        let removed = state.manager.contains(id);
+
+        state.manager.delete_credential(id);
+
+        info!("ID: {id}, Resource: {ident}, banned. removed_from_mem={removed}");
+        if removed {
+            alert(AlertEvent::CredentialBanned {
+                provider: P::KIND,
+                id,
+                account: ident.clone(),
+            });
+        }
+
+        let ops = state.ops.clone();
+        tokio::spawn(async move {
+            if let Err(e) = ops.set_status(id, false).await {
+                warn!("ID: {id}, Resource: {ident}, ban report failed to update DB status: {e}");
+            }
+        });
+    }
+
+    fn handle_submit_untrusted_seeds(
+        state: &mut ProviderActorState<P>,
+        seeds: Vec<RefreshTokenSeed>,
+    ) {
+        let count = seeds.len();
+        info!(count, "Batch submit received, dispatching...");
+        let processor_handle = state.processor_handle.clone();
+
+        tokio::spawn(async move {
+            for seed in seeds {
+                let job = match CredentialJob::ingest_untrusted_seed(&seed) {
+                    Ok(job) => job,
+                    Err(e) => {
+                        warn!(
+                            "Failed to build untrusted {} ingest job from seed: {}",
+                            P::NAME,
+                            e
+                        );
+                        continue;
+                    }
+                };
+
+                if let Err(e) = processor_handle.submit(job) {
+                    warn!("Failed to enqueue untrusted {} ingest job: {}", P::NAME, e);
+                    break;
+                }
+            }
+        });
+    }
+
+    fn handle_activate_credential(
+        state: &mut ProviderActorState<P>,
+        id: CredentialId,
+        credential: P::Resource,
+        unsupported: &ModelCapabilities,
+        report: Option<PendingSeedReport>,
+    ) {
+        let already_active = state.manager.contains(id);
+        if let Some(report) = report {
+            report.send(already_active);
+        }
+        let ident = credential.identifier().to_owned();
+        if already_active {
+            info!("ID: {id}, Resource: {ident}, already active; keeping runtime state");
+            state.manager.replace_resource(id, credential);
+            return;
+        }
+        state
+            .manager
+            .add_credential(id, credential, state.provider_supported_mask.clone());
+        // Recorded as lost rather than never granted, so `capability_restore_secs` retries them.
+        state.manager.mark_model_unsupported(id, unsupported);
+        if unsupported.is_empty() {
+            info!("ID: {id}, Resource: {ident}, submitted and activated");
+        } else {
+            info!(
+                "ID: {id}, Resource: {ident}, submitted and activated without {}",
+                crate::model_catalog::format_model_mask(unsupported)
+            );
+        }
+    }
+
+    /// Store an onboarded credential, then activate it (answering a pending
+    /// validation, if any).
+    fn persist_onboarded(
+        myself: ActorRef<ProviderActorMessage<P>>,
+        ops: P::Store,
+        cred: P::Resource,
+        unsupported: ModelCapabilities,
+        ident: String,
+        reply: Option<RpcReplyPort<SeedReport>>,
+    ) {
+        tokio::spawn(async move {
+            match ops.store_onboarded(cred).await {
+                Ok(stored) => {
+                    let id = stored.id;
+                    if stored.existing {
+                        info!(
+                            "ID: {id}, Resource: {ident}, account already stored; updated in place"
+                        );
+                    }
+                    let report = reply.map(|reply| PendingSeedReport {
+                        report: SeedReport::onboarded(
+                            id,
+                            stored.credential.email().map(str::to_string),
+                            stored.credential.seed_account().to_string(),
+                        )
+                        .duplicate_if(stored.existing),
+                        reply,
+                    });
+                    if let Err(e) = myself.cast(ProviderActorMessage::ActivateCredential {
+                        id,
+                        credential: stored.credential,
+                        unsupported,
+                        report,
+                    }) {
+                        warn!("Resource: {ident} ActivateCredential failed: {}", e);
+                    }
+                }
+                Err(e) => {
+                    warn!("Resource: {ident} DB upsert failed: {}", e);
+                    if let Some(reply) = reply {
+                        let _ = reply.send(SeedReport::invalid(e));
+                    }
+                }
+            }
+        });
+    }
+
+    fn handle_validate_seed(
+        state: &mut ProviderActorState<P>,
+        seed: &RefreshTokenSeed,
+        reply: RpcReplyPort<SeedReport>,
+    ) {
+        let ticket = state.validations.register(reply);
+        let submitted = CredentialJob::validate_untrusted_seed(seed, ticket)
+            .and_then(|job| state.processor_handle.submit(job));
+        if let Err(e) = submitted {
+            state.validations.fail(ticket, e);
+        }
+    }
+
+    fn handle_submit_trusted_oauth(
+        state: &mut ProviderActorState<P>,
+        token_response: OauthTokenResponse,
+    ) {
+        info!("Trusted OAuth submit received, dispatching trusted ingest...");
+        let processor_handle = state.processor_handle.clone();
+        tokio::spawn(async move {
+            let job = match CredentialJob::ingest_trusted_oauth(&token_response) {
+                Ok(job) => job,
+                Err(e) => {
+                    warn!("Trusted OAuth submit ignored: {}", e);
+                    return;
+                }
+            };
+
+            if let Err(e) = processor_handle.submit(job) {
+                warn!("Trusted OAuth submit enqueue failed: {}", e);
+            }
+        });
+    }
+
+    fn handle_process_complete(
+        myself: &ActorRef<ProviderActorMessage<P>>,
+        state: &mut ProviderActorState<P>,
+        result: CredentialProcessResult<P::Resource>,
+    ) {
+        let kind = match &result {
+            Ok(success) => &success.kind,
+            Err(failed) => &failed.original_job.kind,
+        };
+        if let Some(id) = kind.credential_id()
+            && !state.manager.is_refreshing(id)
+        {
+            debug!("ID: {id} credential processing completed/failed after removal; skipping.");
+            return;
+        }
+
+        match result {
+            Ok(success) => {
+                let ident = success.cred.identifier().to_owned();
+                let cred = success.cred;
+                match success.kind {
+                    CredentialJobKind::Refresh(id) => {
+                        debug!("ID: {id} refresh success. Updating manager and persisting.");
+                        state.manager.complete_refresh(id, cred.clone());
+                        Self::handle_health_event(state, id, HealthEvent::Refreshed);
+
+                        let ops = state.ops.clone();
+                        tokio::spawn(async move {
+                            if let Err(e) = ops.save_refreshed(id, &cred).await {
+                                warn!("ID: {id} DB update failed: {}", e);
+                            }
+                        });
+                    }
+                    CredentialJobKind::IngestUntrusted
+                    | CredentialJobKind::IngestTrusted
+                    | CredentialJobKind::ValidateUntrusted(_) => {
+                        info!(
+                            "Resource: {ident} {} ingest success. Inserting to DB.",
+                            P::NAME
+                        );
+                        let reply = match success.kind {
+                            CredentialJobKind::ValidateUntrusted(ticket) => {
+                                state.validations.take(ticket)
+                            }
+                            _ => None,
+                        };
+                        Self::persist_onboarded(
+                            myself.clone(),
+                            state.ops.clone(),
+                            cred,
+                            success.unsupported,
+                            ident,
+                            reply,
+                        );
+                    }
+                }
+            }
+            Err(failed) => {
+                let job = failed.original_job;
+                let err = failed.error;
+                let ident = job.cred.identifier().to_owned();
+                warn!("CredentialJob failed for account {}: {}", ident, err);
+
+                match job.kind {
+                    CredentialJobKind::Refresh(id) => {
+                        if let PolluxError::Oauth(OauthError::ServerResponse { .. }) = err {
+                            Self::handle_refresh_revoked(state, id, ident, &err);
+                        } else {
+                            warn!(
+                                "ID: {id} refresh failed due to transient error: {}. Keeping credential.",
+                                err
+                            );
+                            state.manager.complete_refresh(id, job.cred);
+                            Self::handle_health_event(state, id, HealthEvent::RefreshFailed);
+                        }
+                    }
+                    CredentialJobKind::IngestUntrusted
+                    | CredentialJobKind::ValidateUntrusted(_) => {
+                        warn!(
+                            "Untrusted {} credential ingest failed; discarding job. Details: {}",
+                            P::NAME,
+                            err
+                        );
+                        if let CredentialJobKind::ValidateUntrusted(ticket) = job.kind {
+                            state.validations.fail(ticket, err);
+                        }
+                    }
+                    CredentialJobKind::IngestTrusted => {
+                        warn!(
+                            "Trusted {} OAuth ingest failed; discarding job. Details: {}",
+                            P::NAME,
+                            err
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    /// Disable a credential whose refresh token upstream no longer accepts.
+    fn handle_refresh_revoked(
+        state: &mut ProviderActorState<P>,
+        id: CredentialId,
+        ident: String,
+        err: &PolluxError,
+    ) {
+        error!("ID: {id} refresh failed permanently: {}. Removing.", err);
+        state.manager.delete_credential(id);
+        alert(AlertEvent::RefreshFailed {
+            provider: P::KIND,
+            id,
+            account: ident,
+            error: err.to_string(),
+        });
+
+        let ops = state.ops.clone();
+        tokio::spawn(async move {
+            if let Err(e) = ops.set_status(id, false).await {
+                warn!("ID: {id} DB set_status failed: {}", e);
+            }
+        });
+    }
+}
+
+/// Load `P`'s active credentials and start its pool actor.
+pub(crate) async fn spawn<P: Provider>(
+    db: DbActorHandle,
+    provider: P,
+    settings: PoolSettings,
+) -> ProviderActorHandle<P> {
+    let ops = P::Store::new(db);
+
+    let (actor, _jh) = Actor::spawn(
+        Some(format!("{}Main", P::NAME)),
+        ProviderActor::<P>(PhantomData),
+        (ops, Arc::new(provider), settings),
+    )
+    .await
+    .unwrap_or_else(|e| panic!("failed to spawn {}Actor: {e}", P::NAME));
+
+    ProviderActorHandle { actor }
+}
@@ -14,14 +14,20 @@ pub trait LeaseLabel {
 pub(crate) enum LeaseStatus<L> {
     /// Credential is usable — here is the lease.
     Ready(L),
+    /// Credential needs refreshing but is still usable within the stale grace.
+    Stale(L),
     /// Credential has expired and needs refreshing.
     Expired,
     /// Credential is in a rate-limit cooldown for this model.
     Cooling,
     /// Credential is already being refreshed.
     Refreshing,
+    /// Credential is serving its maximum number of concurrent requests.
+    Busy,
     /// Credential does not support the requested model.
     Unsupported,
+    /// Credential lacks the label of the requested pool.
+    OtherPool,
     /// Credential ID not found in the manager.
     Missing,
 }
@@ -34,10 +40,17 @@ impl<L: LeaseLabel> fmt::Display for LeaseStatus<L> {
                 lease.fmt_label(f)?;
                 f.write_str(")")
             }
+            LeaseStatus::Stale(lease) => {
+                f.write_str("stale(")?;
+                lease.fmt_label(f)?;
+                f.write_str(")")
+            }
             LeaseStatus::Expired => f.write_str("expired"),
             LeaseStatus::Cooling => f.write_str("cooling"),
             LeaseStatus::Refreshing => f.write_str("refreshing"),
+            LeaseStatus::Busy => f.write_str("busy"),
             LeaseStatus::Unsupported => f.write_str("unsupported"),
+            LeaseStatus::OtherPool => f.write_str("other_pool"),
             LeaseStatus::Missing => f.write_str("missing"),
         }
     }
@@ -1,5 +1,10 @@
+pub(crate) mod actor;
 pub(crate) mod lease_status;
+pub(crate) mod oauth_worker;
+pub(crate) mod provider;
+pub(crate) mod route_table;
 #[cfg(not(feature = "bench"))]
 pub(crate) mod scheduler;
 #[cfg(feature = "bench")]
 pub mod scheduler;
+pub(crate) mod waiters;
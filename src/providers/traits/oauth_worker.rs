@@ -0,0 +1,457 @@
+//! Background OAuth worker shared by the [`Provider`] pools: refreshes
+//! access tokens and onboards refresh-token seeds, rate limited to
+//! `oauth_tps` upstream calls per second.
+
+use super::actor::ProviderActorHandle;
+use super::provider::{OauthWorkerSettings, Provider, ProviderResource};
+use super::scheduler::{CredentialId, Schedulable};
+use crate::error::{IsRetryable, OauthError, PolluxError};
+use crate::model_catalog::ModelCapabilities;
+use crate::oauth_utils::OauthTokenResponse;
+use crate::providers::RefreshTokenSeed;
+use crate::utils::dns::with_resolver;
+use crate::utils::http::BoundClients;
+use backon::{ExponentialBuilder, Retryable};
+use futures::stream::StreamExt;
+use governor::{DefaultDirectRateLimiter, Quota, RateLimiter, state::StreamRateLimitExt};
+use ractor::{Actor, ActorProcessingErr, ActorRef};
+use reqwest::header::{CONNECTION, HeaderMap, HeaderValue};
+use serde_json::json;
+use std::marker::PhantomData;
+use std::{sync::Arc, time::Duration};
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+use tracing::{debug, error, info, warn};
+use url::Url;
+
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum CredentialJobKind {
+    Refresh(CredentialId),
+    IngestUntrusted,
+    IngestTrusted,
+    /// `IngestUntrusted` whose outcome is reported back to a waiting
+    /// `resource:add?validate=true` request under this ticket.
+    ValidateUntrusted(u64),
+}
+
+impl CredentialJobKind {
+    pub fn credential_id(&self) -> Option<CredentialId> {
+        match self {
+            Self::Refresh(id) => Some(*id),
+            Self::IngestUntrusted | Self::IngestTrusted | Self::ValidateUntrusted(_) => None,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct CredentialProcessError<R> {
+    pub original_job: CredentialJob<R>,
+    pub error: PolluxError,
+}
+
+pub(crate) type CredentialProcessResult<R> = Result<CredentialJob<R>, CredentialProcessError<R>>;
+
+/// Handle for submitting credential jobs to a provider's OAuth worker.
+pub(crate) struct OauthWorkerHandle<P: Provider> {
+    actor: ActorRef<OauthWorkerMessage<P::Resource>>,
+}
+
+impl<P: Provider> Clone for OauthWorkerHandle<P> {
+    fn clone(&self) -> Self {
+        Self {
+            actor: self.actor.clone(),
+        }
+    }
+}
+
+impl<P: Provider> OauthWorkerHandle<P> {
+    pub async fn spawn(
+        handle: ProviderActorHandle<P>,
+        provider: Arc<P>,
+        settings: OauthWorkerSettings,
+    ) -> Result<Self, ActorProcessingErr> {
+        let (actor, _jh) = Actor::spawn(
+            Some(format!("{}OauthWorker", P::NAME)),
+            OauthWorkerActor::<P>(PhantomData),
+            (handle, provider, settings),
+        )
+        .await
+        .map_err(|e| {
+            ActorProcessingErr::from(format!("{}OauthWorkerActor spawn failed: {e}", P::NAME))
+        })?;
+        Ok(Self { actor })
+    }
+
+    /// Submit a credential job (refresh, untrusted ingest, or trusted ingest) for processing.
+    pub fn submit(&self, job: CredentialJob<P::Resource>) -> Result<(), PolluxError> {
+        ractor::cast!(self.actor, OauthWorkerMessage(job)).map_err(|e| {
+            PolluxError::RactorError(format!("{}OauthWorkerActor cast failed: {e}", P::NAME))
+        })
+    }
+}
+
+/// Actor message wrapping a single credential job.
+///
+/// Job dispatch is driven by [`CredentialJobKind`] inside the job itself,
+/// so a single message variant is sufficient.
+#[derive(Debug)]
+struct OauthWorkerMessage<R>(CredentialJob<R>);
+
+struct OauthWorkerState<P: Provider> {
+    job_tx: mpsc::Sender<CredentialJob<P::Resource>>,
+    handle: ProviderActorHandle<P>,
+}
+
+struct OauthWorkerActor<P>(PhantomData<fn() -> P>);
+
+/// OAuth refresh client, egressing through `proxy`.
+fn oauth_client(settings: &OauthWorkerSettings, proxy: Option<&Url>) -> reqwest::Client {
+    let mut headers = HeaderMap::new();
+    let mut builder = with_resolver(reqwest::Client::builder())
+        .connect_timeout(Duration::from_secs(5))
+        .timeout(Duration::from_secs(30));
+
+    if let Some(user_agent) = settings.user_agent {
+        builder = builder.user_agent(user_agent);
+    }
+
+    if let Some(proxy_url) = proxy {
+        let proxy =
+            reqwest::Proxy::all(proxy_url.as_str()).expect("invalid proxy url for reqwest client");
+        builder = builder.proxy(proxy);
+    }
+
+    if settings.enable_multiplexing {
+        builder = builder.http2_adaptive_window(true);
+    } else {
+        headers.insert(CONNECTION, HeaderValue::from_static("close"));
+
+        builder = builder
+            .http1_only()
+            .pool_max_idle_per_host(0)
+            .pool_idle_timeout(Duration::from_secs(0));
+    }
+
+    builder =
+        crate::utils::http::tune(builder, &settings.http_client, settings.enable_multiplexing);
+
+    builder
+        .default_headers(headers)
+        .build()
+        .expect("FATAL: initialize credential processor HTTP client failed")
+}
+
+#[ractor::async_trait]
+impl<P: Provider> Actor for OauthWorkerActor<P> {
+    type Msg = OauthWorkerMessage<P::Resource>;
+    type State = OauthWorkerState<P>;
+    type Arguments = (ProviderActorHandle<P>, Arc<P>, OauthWorkerSettings);
+
+    async fn pre_start(
+        &self,
+        _myself: ActorRef<Self::Msg>,
+        (handle, provider, settings): Self::Arguments,
+    ) -> Result<Self::State, ActorProcessingErr> {
+        let client = oauth_client(&settings, settings.proxy.as_ref());
+        let bound = {
+            let settings = settings.clone();
+            BoundClients::new(move |proxy| oauth_client(&settings, Some(proxy)))
+        };
+
+        let oauth_tps = settings.oauth_tps.max(1);
+        let oauth_tps_u32 = u32::try_from(oauth_tps).unwrap_or(u32::MAX);
+        let burst_u32 = u32::try_from(oauth_tps.saturating_mul(2)).unwrap_or(u32::MAX);
+        let limiter = Arc::new(RateLimiter::direct(
+            Quota::per_second(std::num::NonZeroU32::new(oauth_tps_u32).unwrap())
+                .allow_burst(std::num::NonZeroU32::new(burst_u32).unwrap()),
+        ));
+
+        let (job_tx, job_rx) = mpsc::channel::<CredentialJob<P::Resource>>(1000);
+        let pipeline_handle = handle.clone();
+
+        let buffer_unordered = oauth_tps.saturating_mul(2).max(1);
+        tokio::spawn(async move {
+            info!(
+                "{} Credential Pipeline Started: BufferUnordered={}, RateLimit={}/s, Burst={}",
+                P::NAME,
+                buffer_unordered,
+                oauth_tps_u32,
+                burst_u32
+            );
+
+            let provider = &*provider;
+            let job_limiter = &*limiter;
+            let mut pipeline = ReceiverStream::new(job_rx)
+                .ratelimit_stream(&limiter)
+                .map(|job| {
+                    let http = job
+                        .cred
+                        .proxy_url()
+                        .map_or_else(|| client.clone(), |proxy| bound.get(proxy));
+                    async move { job.execute(provider, http, job_limiter).await }
+                })
+                .buffer_unordered(buffer_unordered);
+
+            while let Some(result) = pipeline.next().await {
+                if let Err(e) = pipeline_handle.send_process_complete(result) {
+                    warn!("Actor unreachable (channel closed), worker stopping: {}", e);
+                    break;
+                }
+            }
+
+            info!("{} Credential Pipeline Stopped", P::NAME);
+        });
+
+        info!(
+            proxy = %settings.proxy.as_ref().map_or("<none>", |u| u.as_str()),
+            enable_multiplexing = settings.enable_multiplexing,
+            oauth_tps = settings.oauth_tps,
+            "{}CredentialProcessor runtime config loaded",
+            P::NAME
+        );
+
+        Ok(OauthWorkerState { job_tx, handle })
+    }
+
+    async fn handle(
+        &self,
+        _myself: ActorRef<Self::Msg>,
+        OauthWorkerMessage(job): Self::Msg,
+        state: &mut Self::State,
+    ) -> Result<(), ActorProcessingErr> {
+        let tx = state.job_tx.clone();
+        let handle = state.handle.clone();
+
+        tokio::spawn(async move {
+            send_job(tx, handle, job).await;
+        });
+
+        Ok(())
+    }
+}
+
+async fn send_job<P: Provider>(
+    tx: mpsc::Sender<CredentialJob<P::Resource>>,
+    handle: ProviderActorHandle<P>,
+    job: CredentialJob<P::Resource>,
+) {
+    if let Err(e) = tx.send(job).await {
+        warn!(
+            "Failed to submit credential job (channel closed/full): {}",
+            e
+        );
+        let result = Err(CredentialProcessError {
+            original_job: e.0,
+            error: PolluxError::RactorError(format!("{}OauthWorker job queue is closed", P::NAME)),
+        });
+        if let Err(e) = handle.send_process_complete(result) {
+            warn!(
+                "Actor unreachable (channel closed), dropping credential process result: {}",
+                e
+            );
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct CredentialJob<R> {
+    pub cred: R,
+    pub kind: CredentialJobKind,
+    /// Models [`Provider::onboard`] found unsupported; empty for refreshes.
+    pub unsupported: ModelCapabilities,
+}
+
+impl<R: ProviderResource> CredentialJob<R> {
+    pub(crate) fn refresh(id: CredentialId, cred: R) -> Self {
+        Self {
+            cred,
+            kind: CredentialJobKind::Refresh(id),
+            unsupported: ModelCapabilities::none(),
+        }
+    }
+
+    pub(crate) fn ingest_untrusted_seed(seed: &RefreshTokenSeed) -> Result<Self, PolluxError> {
+        let mut cred = R::default();
+        cred.update_credential(json!({ "refresh_token": seed.refresh_token() }))?;
+        cred.set_labels(seed.labels().to_vec());
+        cred.set_proxy_url(seed.proxy_url().cloned());
+        Ok(Self {
+            cred,
+            kind: CredentialJobKind::IngestUntrusted,
+            unsupported: ModelCapabilities::none(),
+        })
+    }
+
+    /// Like [`Self::ingest_untrusted_seed`], reporting under `ticket`.
+    pub(crate) fn validate_untrusted_seed(
+        seed: &RefreshTokenSeed,
+        ticket: u64,
+    ) -> Result<Self, PolluxError> {
+        let mut job = Self::ingest_untrusted_seed(seed)?;
+        job.kind = CredentialJobKind::ValidateUntrusted(ticket);
+        Ok(job)
+    }
+
+    pub(crate) fn ingest_trusted_oauth(
+        token_response: &OauthTokenResponse,
+    ) -> Result<Self, PolluxError> {
+        let cred = R::try_from_oauth_token_response(token_response, None)?;
+        Ok(Self {
+            cred,
+            kind: CredentialJobKind::IngestTrusted,
+            unsupported: ModelCapabilities::none(),
+        })
+    }
+
+    /// Execute the credential job (refresh or ingest) and return the updated job on success.
+    ///
+    /// Wraps [`Self::execute_inner`] so that all `PolluxError` variants are uniformly
+    /// converted into `CredentialProcessError` carrying the original job.
+    pub(crate) async fn execute<P: Provider<Resource = R>>(
+        mut self,
+        provider: &P,
+        client: reqwest::Client,
+        limiter: &DefaultDirectRateLimiter,
+    ) -> CredentialProcessResult<R> {
+        match self.execute_inner(provider, client, limiter).await {
+            Ok(()) => Ok(self),
+            Err(error) => Err(CredentialProcessError {
+                original_job: self,
+                error,
+            }),
+        }
+    }
+
+    /// Core processing logic for a single credential job.
+    ///
+    /// Dispatches to the appropriate refresh/ingest path, runs
+    /// [`Provider::onboard`] for ingests, then validates that the resulting
+    /// credential contains the required fields (access token, identity, and
+    /// refresh token).
+    async fn execute_inner<P: Provider<Resource = R>>(
+        &mut self,
+        provider: &P,
+        client: reqwest::Client,
+        limiter: &DefaultDirectRateLimiter,
+    ) -> Result<(), PolluxError> {
+        match self.kind {
+            CredentialJobKind::Refresh(_) => {
+                refresh_credential(
+                    provider,
+                    client.clone(),
+                    provider.oauth_retry_policy(),
+                    &mut self.cred,
+                    None,
+                )
+                .await?;
+            }
+            CredentialJobKind::IngestUntrusted | CredentialJobKind::ValidateUntrusted(_) => {
+                let refresh_token = self.cred.refresh_token().trim().to_string();
+                let refresh_seed = RefreshTokenSeed::new(&refresh_token).ok_or_else(|| {
+                    PolluxError::UnexpectedError(format!(
+                        "Missing refresh_token for untrusted {} credential ingest",
+                        P::NAME
+                    ))
+                })?;
+
+                refresh_credential(
+                    provider,
+                    client.clone(),
+                    provider.oauth_retry_policy(),
+                    &mut self.cred,
+                    Some(refresh_seed),
+                )
+                .await?;
+            }
+            CredentialJobKind::IngestTrusted => {}
+        }
+
+        if self.cred.access_token().trim().is_empty() {
+            return Err(PolluxError::MissingAccessToken);
+        }
+
+        if self.kind.credential_id().is_none() {
+            self.unsupported = provider.onboard(&mut self.cred, client, limiter).await?;
+        }
+
+        if !self.cred.has_identity() {
+            let context = match self.kind {
+                CredentialJobKind::Refresh(_) => "after refresh",
+                CredentialJobKind::IngestUntrusted | CredentialJobKind::ValidateUntrusted(_) => {
+                    "after untrusted credential ingest"
+                }
+                CredentialJobKind::IngestTrusted => "in trusted OAuth response",
+            };
+            return Err(PolluxError::UnexpectedError(format!(
+                "Missing {} identity {context}",
+                P::NAME
+            )));
+        }
+
+        if self.cred.refresh_token().trim().is_empty() {
+            return Err(PolluxError::UnexpectedError(format!(
+                "Missing refresh_token in {} credential",
+                P::NAME
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+/// Refresh a credential via the provider's OAuth token endpoint.
+///
+/// When `refresh_seed` is `Some`, the credential is rebuilt from scratch using
+/// the full token response (untrusted ingest path). When `None`, only the
+/// token-related fields are patched in place, preserving existing identity.
+pub(crate) async fn refresh_credential<P: Provider>(
+    provider: &P,
+    client: reqwest::Client,
+    retry_policy: ExponentialBuilder,
+    creds: &mut P::Resource,
+    refresh_seed: Option<RefreshTokenSeed>,
+) -> Result<(), PolluxError> {
+    let refresh_token = match &refresh_seed {
+        Some(seed) => seed.refresh_token(),
+        None => creds.refresh_token(),
+    };
+    let token_response =
+        request_token_refresh(provider, client, retry_policy, refresh_token).await?;
+
+    if let Some(seed) = refresh_seed {
+        let labels = creds.labels().to_vec();
+        let proxy_url = creds.proxy_url().cloned();
+        *creds = P::Resource::try_from_oauth_token_response(&token_response, Some(&seed))?;
+        creds.set_labels(labels);
+        creds.set_proxy_url(proxy_url);
+    } else {
+        creds.update_credential(&token_response)?;
+        debug!(account = %creds.identifier(), "Access token refreshed successfully");
+    }
+    Ok(())
+}
+
+async fn request_token_refresh<P: Provider>(
+    provider: &P,
+    client: reqwest::Client,
+    retry_policy: ExponentialBuilder,
+    refresh_token: &str,
+) -> Result<OauthTokenResponse, PolluxError> {
+    (|| async {
+        provider
+            .refresh_access_token(refresh_token, client.clone())
+            .await
+    })
+    .retry(retry_policy)
+    .when(|e: &OauthError| e.is_retryable())
+    .notify(|err, dur: Duration| {
+        error!(
+            "{} OAuth2 refresh retrying error {} with sleeping {:?}",
+            P::NAME,
+            err.to_string(),
+            dur
+        );
+    })
+    .await
+    .map_err(Into::into)
+}
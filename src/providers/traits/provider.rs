@@ -0,0 +1,186 @@
+//! Extension points of the shared OAuth credential pool.
+//!
+//! A provider whose accounts are OAuth refresh tokens implements
+//! [`Provider`] on a small handle type, [`ProviderResource`] on its
+//! credential and [`CredentialStore`] on its storage ops. The pool actor
+//! ([`super::actor::ProviderActor`]), the refresh worker
+//! ([`super::oauth_worker`]), lease waiters and the admin plumbing are then
+//! shared; the provider keeps only its OAuth endpoints, any onboarding past
+//! the token grant (see [`Provider::onboard`]) and its request/response
+//! adapters.
+
+use super::lease_status::LeaseLabel;
+use super::scheduler::{CredentialId, Schedulable};
+use crate::config::{AutoDisableConfig, HealthConfig, HttpClientConfig, SchedulingPolicy};
+use crate::db::DbActorHandle;
+use crate::error::{OauthError, PolluxError};
+use crate::model_catalog::ModelCapabilities;
+use crate::oauth_utils::OauthTokenResponse;
+use crate::providers::RefreshTokenSeed;
+use crate::providers::credential_view::CredentialView;
+use crate::providers::manifest::ProviderKind;
+use crate::providers::seed::StoredSeed;
+use backon::ExponentialBuilder;
+use chrono::{DateTime, Utc};
+use governor::DefaultDirectRateLimiter;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::future::Future;
+use std::time::Duration;
+use url::Url;
+
+/// A provider served by the shared pool actor.
+pub trait Provider: Send + Sync + 'static {
+    /// Display name used in logs and actor names, e.g. `Codex`.
+    const NAME: &'static str;
+    const KIND: ProviderKind;
+
+    type Resource: ProviderResource;
+    type Store: CredentialStore<Self::Resource>;
+
+    /// Models this provider serves, as a mask and as names (for logs).
+    fn supported_models(&self) -> (ModelCapabilities, Vec<String>);
+
+    /// Backoff for transient failures of [`Self::refresh_access_token`].
+    fn oauth_retry_policy(&self) -> ExponentialBuilder;
+
+    /// One refresh-token grant against the provider's token endpoint.
+    fn refresh_access_token(
+        &self,
+        refresh_token: &str,
+        http_client: reqwest::Client,
+    ) -> impl Future<Output = Result<OauthTokenResponse, OauthError>> + Send;
+
+    /// Finish ingesting a credential whose token grant succeeded, e.g. by
+    /// resolving the account's GCP project; runs before the identity check,
+    /// so it may fill in the identity. Returns the models the
+    /// credential turned out not to serve; upstream calls made here should
+    /// wait on `limiter`, which is shared with the token grants.
+    fn onboard(
+        &self,
+        _cred: &mut Self::Resource,
+        _http_client: reqwest::Client,
+        _limiter: &DefaultDirectRateLimiter,
+    ) -> impl Future<Output = Result<ModelCapabilities, PolluxError>> + Send {
+        async { Ok(ModelCapabilities::none()) }
+    }
+}
+
+/// Lease of a [`ProviderResource`], tagged with the credential it came from.
+pub trait PoolLease: LeaseLabel + Debug + Send + 'static {
+    fn credential_id(&self) -> CredentialId;
+}
+
+/// A credential built from, and refreshed by, an OAuth token response.
+pub trait ProviderResource:
+    Schedulable<Lease: PoolLease> + Default + Send + Sync + Unpin + 'static
+{
+    fn refresh_token(&self) -> &str;
+
+    fn access_token(&self) -> &str;
+
+    fn expiry(&self) -> DateTime<Utc>;
+
+    fn proxy_url(&self) -> Option<&Url>;
+
+    fn set_labels(&mut self, labels: Vec<String>);
+
+    fn set_proxy_url(&mut self, proxy_url: Option<Url>);
+
+    /// Merge a token response (or any payload with the same field names)
+    /// into this credential, keeping fields it does not carry.
+    fn update_credential(&mut self, payload: impl Serialize) -> Result<(), PolluxError>;
+
+    /// Build a fresh credential from a token response. `seed`, when given,
+    /// supplies the refresh token if the response omits one.
+    fn try_from_oauth_token_response(
+        token_response: &OauthTokenResponse,
+        seed: Option<&RefreshTokenSeed>,
+    ) -> Result<Self, PolluxError>;
+
+    /// Whether the account identity the provider needs is present. Checked
+    /// after every refresh and ingest.
+    fn has_identity(&self) -> bool {
+        true
+    }
+
+    /// Email reported back to `resource:add?validate=true`.
+    fn email(&self) -> Option<&str> {
+        None
+    }
+
+    /// Account reported back to `resource:add?validate=true`.
+    fn seed_account(&self) -> &str {
+        self.identifier()
+    }
+}
+
+/// Persistence of one provider's credentials.
+pub trait CredentialStore<R>: Clone + Send + Sync + 'static {
+    fn new(db: DbActorHandle) -> Self;
+
+    fn load_active(
+        &self,
+    ) -> impl Future<Output = Result<Vec<(CredentialId, R)>, PolluxError>> + Send;
+
+    /// Store a freshly onboarded credential, updating the row of an account
+    /// that is already stored.
+    fn store_onboarded(
+        &self,
+        cred: R,
+    ) -> impl Future<Output = Result<StoredSeed<R>, PolluxError>> + Send;
+
+    /// Persist the token fields of a refreshed credential.
+    fn save_refreshed(
+        &self,
+        id: CredentialId,
+        cred: &R,
+    ) -> impl Future<Output = Result<(), PolluxError>> + Send;
+
+    fn set_status(
+        &self,
+        id: CredentialId,
+        status: bool,
+    ) -> impl Future<Output = Result<(), PolluxError>> + Send;
+
+    /// All stored rows (any status), for admin listing.
+    fn load_all_views(
+        &self,
+    ) -> impl Future<Output = Result<Vec<CredentialView>, PolluxError>> + Send;
+
+    fn get_by_id(&self, id: CredentialId) -> impl Future<Output = Result<R, PolluxError>> + Send;
+
+    fn delete(&self, id: CredentialId) -> impl Future<Output = Result<(), PolluxError>> + Send;
+}
+
+/// Runtime settings of one pool, taken from the provider's resolved config.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct PoolSettings {
+    /// Upstream API base, for the startup log only.
+    pub endpoint: String,
+    pub retry_max_times: usize,
+    pub auto_disable: Option<AutoDisableConfig>,
+    pub health: Option<HealthConfig>,
+    pub min_token_validity: Duration,
+    pub stale_grace: Duration,
+    pub capability_restore: Duration,
+    pub max_concurrent_per_credential: Option<u32>,
+    pub tier_weights: HashMap<String, u32>,
+    pub scheduling_policy: SchedulingPolicy,
+    pub priority_reserve: f64,
+    pub lease_wait: Duration,
+    pub oauth: OauthWorkerSettings,
+}
+
+/// HTTP settings of the refresh worker.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct OauthWorkerSettings {
+    pub proxy: Option<Url>,
+    /// User-Agent of token and onboarding calls; reqwest's when unset.
+    pub user_agent: Option<&'static str>,
+    pub enable_multiplexing: bool,
+    pub http_client: HttpClientConfig,
+    /// Token endpoint calls per second.
+    pub oauth_tps: usize,
+}
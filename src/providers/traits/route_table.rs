@@ -1,3 +1,4 @@
+use crate::model_catalog::ModelCapabilities;
 use crate::providers::traits::scheduler::CredentialId;
 use moka::sync::Cache;
 use std::time::Duration;
@@ -11,7 +12,14 @@ use std::time::Duration;
 /// Stale entries are handled lazily — the scheduler evaluates each hinted ID
 /// via [`ResourceScheduler::get_assigned`] and falls back to queue selection on miss.
 pub struct RouteTable {
-    cache: Cache<(u64, u64), CredentialId>,
+    cache: Cache<(u64, ModelCapabilities), CredentialId>,
+}
+
+impl Default for RouteTable {
+    /// 10k live sessions, released after an hour without traffic.
+    fn default() -> Self {
+        Self::new(10_000, Duration::from_hours(1))
+    }
 }
 
 impl RouteTable {
@@ -26,14 +34,20 @@ impl RouteTable {
 
     /// Returns the cached credential for this `(session, model)` pair, if any.
     #[inline]
-    pub fn get(&self, route_key: u64, model_mask: u64) -> Option<CredentialId> {
-        self.cache.get(&(route_key, model_mask))
+    pub fn get(&self, route_key: u64, model_mask: &ModelCapabilities) -> Option<CredentialId> {
+        self.cache.get(&(route_key, model_mask.clone()))
     }
 
     /// Binds a `(session, model)` pair to the given credential.
     #[inline]
-    pub fn insert(&self, route_key: u64, model_mask: u64, credential_id: CredentialId) {
-        self.cache.insert((route_key, model_mask), credential_id);
+    pub fn insert(
+        &self,
+        route_key: u64,
+        model_mask: &ModelCapabilities,
+        credential_id: CredentialId,
+    ) {
+        self.cache
+            .insert((route_key, model_mask.clone()), credential_id);
     }
 }
 
@@ -44,18 +58,21 @@ mod tests {
     #[test]
     fn insert_and_get() {
         let rt = RouteTable::new(100, Duration::from_mins(1));
-        rt.insert(0xABCD, 0x01, 42);
-        assert_eq!(rt.get(0xABCD, 0x01), Some(42));
-        assert_eq!(rt.get(0xABCD, 0x02), None);
-        assert_eq!(rt.get(0x1234, 0x01), None);
+        rt.insert(0xABCD, &ModelCapabilities::from_bits(0x01), 42);
+        assert_eq!(
+            rt.get(0xABCD, &ModelCapabilities::from_bits(0x01)),
+            Some(42)
+        );
+        assert_eq!(rt.get(0xABCD, &ModelCapabilities::from_bits(0x02)), None);
+        assert_eq!(rt.get(0x1234, &ModelCapabilities::from_bits(0x01)), None);
     }
 
     #[test]
     fn different_model_mask_same_session() {
         let rt = RouteTable::new(100, Duration::from_mins(1));
-        rt.insert(0xAA, 0x01, 100);
-        rt.insert(0xAA, 0x02, 200);
-        assert_eq!(rt.get(0xAA, 0x01), Some(100));
-        assert_eq!(rt.get(0xAA, 0x02), Some(200));
+        rt.insert(0xAA, &ModelCapabilities::from_bits(0x01), 100);
+        rt.insert(0xAA, &ModelCapabilities::from_bits(0x02), 200);
+        assert_eq!(rt.get(0xAA, &ModelCapabilities::from_bits(0x01)), Some(100));
+        assert_eq!(rt.get(0xAA, &ModelCapabilities::from_bits(0x02)), Some(200));
     }
 }
@@ -4,12 +4,27 @@ use std::fmt::Debug;
 use std::time::{Duration, Instant};
 
 use super::lease_status::{LeaseLabel, LeaseStatus};
+use crate::config::{AutoDisableConfig, HealthAction, HealthConfig, Priority, SchedulingPolicy};
 use crate::model_catalog::ModelCapabilities;
 use tracing::error;
 
 pub type CredentialId = u64;
 pub type ModelIndex = usize;
 
+/// Lease-time validity margin used unless configured otherwise.
+const DEFAULT_MIN_TOKEN_VALIDITY: Duration = Duration::from_mins(5);
+
+/// Upper bound on how late a lost model is restored past its delay.
+const MAX_RESTORE_CHECK_INTERVAL: Duration = Duration::from_mins(1);
+
+/// Weight of the newest sample in the latency and error moving averages.
+const FEEDBACK_ALPHA: f64 = 0.2;
+
+/// Health score lost per consecutive failure of each kind.
+const FORBIDDEN_PENALTY: u32 = 40;
+const SERVER_ERROR_PENALTY: u32 = 10;
+const REFRESH_FAILURE_PENALTY: u32 = 25;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum CooldownScope {
     /// Cooldown applies only to the model that triggered the 429.
@@ -37,11 +52,91 @@ pub trait Schedulable: Clone + Debug {
     /// output — never for scheduling decisions.
     fn identifier(&self) -> &str;
 
-    /// Check if the credential has expired and needs token refresh.
-    fn is_expired(&self) -> bool;
+    /// Check if the access token has less than `min_validity` left and must
+    /// be refreshed before it can be leased.
+    fn expires_within(&self, min_validity: Duration) -> bool;
+
+    /// Check if the access token expired more than `grace` ago. Resources
+    /// that cannot tell treat any expiry as final.
+    fn expired_beyond(&self, grace: Duration) -> bool {
+        let _ = grace;
+        self.expires_within(Duration::ZERO)
+    }
 
     /// Build a lease from this resource for the given credential ID.
     fn make_lease(&self, id: CredentialId) -> Self::Lease;
+
+    /// Plan or tier name looked up in the scheduler's tier weights.
+    fn tier(&self) -> Option<&str> {
+        None
+    }
+
+    /// Pool labels; a request pinned to a pool only leases credentials
+    /// carrying its label.
+    fn labels(&self) -> &[String] {
+        &[]
+    }
+}
+
+/// Most recent request outcomes of one credential on one model.
+#[derive(Debug, Clone, Copy, Default)]
+struct OutcomeWindow {
+    /// Newest outcome in bit 0; a set bit is a failure.
+    failures: u64,
+    len: u32,
+}
+
+impl OutcomeWindow {
+    /// Records an outcome and returns the success rate once `window` outcomes
+    /// have been seen.
+    #[allow(clippy::cast_possible_truncation)]
+    fn record(&mut self, success: bool, window: usize) -> Option<f64> {
+        let window = window.clamp(1, 64) as u32;
+        self.failures = (self.failures << 1) | u64::from(!success);
+        if window < 64 {
+            self.failures &= (1u64 << window) - 1;
+        }
+        self.len = (self.len + 1).min(window);
+        (self.len == window)
+            .then(|| f64::from(window - self.failures.count_ones()) / f64::from(window))
+    }
+}
+
+/// Moving averages of one credential's recent requests on one model, fed by
+/// [`ResourceScheduler::report_success`] and failure reports.
+#[derive(Debug, Clone, Copy, Default)]
+struct ModelFeedback {
+    /// Time to response headers in milliseconds; `None` until a success.
+    latency_ms: Option<f64>,
+    /// Share of recent requests that failed, `0.0..=1.0`.
+    error_rate: f64,
+}
+
+impl ModelFeedback {
+    fn record_latency(&mut self, latency: Duration) {
+        let sample = latency.as_secs_f64() * 1000.0;
+        self.latency_ms = Some(
+            self.latency_ms
+                .map_or(sample, |avg| avg + FEEDBACK_ALPHA * (sample - avg)),
+        );
+    }
+
+    fn record_outcome(&mut self, success: bool) {
+        let sample = if success { 0.0 } else { 1.0 };
+        self.error_rate += FEEDBACK_ALPHA * (sample - self.error_rate);
+    }
+
+    /// Ranking key under `policy`; lower is preferred. Credentials without a
+    /// latency sample rank first so they get measured.
+    fn score(&self, policy: SchedulingPolicy) -> f64 {
+        match policy {
+            SchedulingPolicy::RoundRobin | SchedulingPolicy::LeastRecentlyUsed => 0.0,
+            SchedulingPolicy::LeastLatency | SchedulingPolicy::P2cLatency => {
+                self.latency_ms.unwrap_or(0.0)
+            }
+            SchedulingPolicy::LeastErrors => self.error_rate,
+        }
+    }
 }
 
 /// Runtime credential = base resource data + dynamic capability bitset.
@@ -49,8 +144,26 @@ pub trait Schedulable: Clone + Debug {
 struct ResourceEntry<R> {
     inner: R,
     caps: ModelCapabilities,
+    /// Models an admin force-enabled; exempt from auto-disable.
+    pinned: ModelCapabilities,
     refreshing: bool,
+    /// The pending refresh is for expiry only, so the old token may still
+    /// be served within the stale grace.
+    stale_ok: bool,
     cooldowns: Vec<Option<Instant>>,
+    outcomes: Vec<OutcomeWindow>,
+    feedback: Vec<ModelFeedback>,
+    /// When each model was lost to an unsupported report or auto-disable.
+    lost_at: Vec<Option<Instant>>,
+    /// Smooth weighted round-robin balance (see `assign_weighted`).
+    credit: i64,
+    /// Leases handed out and not yet released.
+    in_flight: u32,
+    /// When the last lease on any model was handed out.
+    last_leased: Option<Instant>,
+    /// Percent of account quota left, as last reported by upstream.
+    quota_remaining: Option<u8>,
+    health: CredentialHealth,
 }
 
 impl<R> ResourceEntry<R> {
@@ -58,8 +171,18 @@ impl<R> ResourceEntry<R> {
         Self {
             inner,
             caps: initial_caps,
+            pinned: ModelCapabilities::none(),
             refreshing: false,
+            stale_ok: false,
             cooldowns: vec![None; model_count],
+            outcomes: vec![OutcomeWindow::default(); model_count],
+            feedback: vec![ModelFeedback::default(); model_count],
+            lost_at: vec![None; model_count],
+            credit: 0,
+            in_flight: 0,
+            last_leased: None,
+            quota_remaining: None,
+            health: CredentialHealth::default(),
         }
     }
 
@@ -85,7 +208,8 @@ impl<R> ResourceEntry<R> {
         }
         self.inner = inner;
         self.refreshing = false;
-        self.caps
+        self.stale_ok = false;
+        self.caps.clone()
     }
 
     /// Adjusts status counters for an entry that is about to be dropped
@@ -212,6 +336,13 @@ pub struct AssignmentStats {
     pub skipped_refreshing: usize,
     pub skipped_unsupported: usize,
     pub skipped_expired: usize,
+    pub skipped_busy: usize,
+    /// Credentials outside the requested pool.
+    pub skipped_other_pool: usize,
+    /// The model is down to the credentials reserved for `high` priority.
+    pub held_in_reserve: bool,
+    /// Leases handed out on a token awaiting refresh.
+    pub served_stale: usize,
 }
 
 #[derive(Debug)]
@@ -233,6 +364,81 @@ impl<L> Default for AssignmentResult<L> {
     }
 }
 
+/// Point-in-time scheduler state for a single credential, for admin views.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CredentialRuntime {
+    pub caps: ModelCapabilities,
+    pub refreshing: bool,
+    /// Requests currently holding a lease on this credential.
+    pub in_flight: u32,
+    /// Cooldowns still in effect, as `(model index, remaining)`.
+    pub cooldowns: Vec<(ModelIndex, Duration)>,
+    /// Percent of account quota left, when upstream reports it.
+    pub quota_remaining: Option<u8>,
+    pub health: CredentialHealth,
+}
+
+/// Something that moves a credential's health score.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HealthEvent {
+    /// A request succeeded; clears the request failures.
+    Success,
+    /// Upstream rejected the account (`403` and other ban reports).
+    Forbidden,
+    /// A generic upstream failure, e.g. `500`.
+    ServerError,
+    /// A token refresh failed, but not permanently.
+    RefreshFailed,
+    /// A token refresh succeeded; clears the refresh failures.
+    Refreshed,
+}
+
+/// Consecutive failures of one credential, and the score they add up to.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CredentialHealth {
+    pub forbidden: u32,
+    pub server_errors: u32,
+    pub refresh_failures: u32,
+}
+
+impl CredentialHealth {
+    /// `100` minus the penalty of every consecutive failure, floored at `0`.
+    pub fn score(&self) -> u8 {
+        let penalty = self
+            .forbidden
+            .saturating_mul(FORBIDDEN_PENALTY)
+            .saturating_add(self.server_errors.saturating_mul(SERVER_ERROR_PENALTY))
+            .saturating_add(
+                self.refresh_failures
+                    .saturating_mul(REFRESH_FAILURE_PENALTY),
+            );
+        u8::try_from(100u32.saturating_sub(penalty)).unwrap_or_default()
+    }
+
+    fn record(&mut self, event: HealthEvent) {
+        match event {
+            HealthEvent::Success => {
+                self.forbidden = 0;
+                self.server_errors = 0;
+            }
+            HealthEvent::Forbidden => self.forbidden += 1,
+            HealthEvent::ServerError => self.server_errors += 1,
+            HealthEvent::RefreshFailed => self.refresh_failures += 1,
+            HealthEvent::Refreshed => self.refresh_failures = 0,
+        }
+    }
+}
+
+/// What [`ResourceScheduler::record_health`] decided for a credential whose
+/// score fell below the threshold.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HealthVerdict {
+    /// Every model was cooled down for `duration` and the score reset.
+    Parked { score: u8, duration: Duration },
+    /// The caller should ban the credential.
+    Ban { score: u8 },
+}
+
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
 struct CooldownTicket(Reverse<Instant>, CredentialId, ModelIndex);
 
@@ -258,6 +464,11 @@ impl ModelQueue {
     fn len(&self) -> usize {
         self.order.len()
     }
+
+    fn drain(&mut self) -> Vec<CredentialId> {
+        self.members.clear();
+        self.order.drain(..).collect()
+    }
 }
 
 /// Generic resource scheduler. Pure logic, no IO, no locks.
@@ -271,6 +482,15 @@ pub struct ResourceScheduler<R: Schedulable> {
     waiting_room: BinaryHeap<CooldownTicket>,
     model_count: usize,
     status: SchedulerStatus,
+    auto_disable: Option<AutoDisableConfig>,
+    health: Option<HealthConfig>,
+    min_token_validity: Duration,
+    stale_grace: Duration,
+    capability_restore: Duration,
+    tier_weights: HashMap<String, u32>,
+    policy: SchedulingPolicy,
+    max_concurrent: Option<u32>,
+    priority_reserve: f64,
 }
 
 impl<R: Schedulable> ResourceScheduler<R> {
@@ -281,29 +501,130 @@ impl<R: Schedulable> ResourceScheduler<R> {
             waiting_room: BinaryHeap::new(),
             model_count,
             status: SchedulerStatus::new(model_count),
+            auto_disable: None,
+            health: None,
+            min_token_validity: DEFAULT_MIN_TOKEN_VALIDITY,
+            stale_grace: Duration::ZERO,
+            capability_restore: Duration::ZERO,
+            tier_weights: HashMap::new(),
+            policy: SchedulingPolicy::RoundRobin,
+            max_concurrent: None,
+            priority_reserve: 0.0,
         }
     }
 
+    /// Enables success-rate driven auto-disable (see [`Self::report_outcome`]).
+    #[must_use]
+    pub fn with_auto_disable(mut self, policy: Option<AutoDisableConfig>) -> Self {
+        self.auto_disable = policy;
+        self
+    }
+
+    /// Enables health-score driven parking and banning (see
+    /// [`Self::record_health`]).
+    #[must_use]
+    pub fn with_health(mut self, policy: Option<HealthConfig>) -> Self {
+        self.health = policy;
+        self
+    }
+
+    /// The health policy, if one is set.
+    pub fn health_policy(&self) -> Option<HealthConfig> {
+        self.health
+    }
+
+    /// Tokens with less than `min_validity` left are sent for refresh
+    /// instead of being leased.
+    #[must_use]
+    pub fn with_min_token_validity(mut self, min_validity: Duration) -> Self {
+        self.min_token_validity = min_validity;
+        self
+    }
+
+    /// Keep leasing a credential whose token needs refreshing, until it has
+    /// been expired for longer than `grace`, instead of skipping it while the
+    /// refresh runs. Zero disables this.
+    #[must_use]
+    pub fn with_stale_grace(mut self, grace: Duration) -> Self {
+        self.stale_grace = grace;
+        self
+    }
+
+    /// Give back models a credential lost through
+    /// [`Self::mark_model_unsupported`] or auto-disable once `after` has
+    /// passed (see [`Self::restore_lost_models`]). Zero disables this.
+    #[must_use]
+    pub fn with_capability_restore(mut self, after: Duration) -> Self {
+        self.capability_restore = after;
+        self
+    }
+
+    /// Share of traffic per [`Schedulable::tier`]; unlisted tiers weigh 1.
+    ///
+    /// Empty keeps plain round-robin. Weight 0 marks overflow credentials,
+    /// used only while no weighted credential is ready.
+    #[must_use]
+    pub fn with_tier_weights(mut self, weights: HashMap<String, u32>) -> Self {
+        self.tier_weights = weights;
+        self
+    }
+
+    /// How ready credentials are ranked when no tier weights are set.
+    ///
+    /// Round-robin rotates through the queue; the other policies lease the
+    /// best-scoring ready credential (see [`Self::report_success`]), queue
+    /// order breaking ties.
+    #[must_use]
+    pub fn with_policy(mut self, policy: SchedulingPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Caps the leases a credential may have outstanding; a credential at the
+    /// cap is skipped until one is [released](Self::release). `None` or `0`
+    /// leaves it unlimited.
+    #[must_use]
+    pub fn with_max_concurrent(mut self, max: Option<u32>) -> Self {
+        self.max_concurrent = max.filter(|&n| n > 0);
+        self
+    }
+
+    /// Holds back this fraction of each model's credentials (in the requested
+    /// pool) for [`Priority::High`]: other requests get nothing once no more
+    /// than that many are ready.
+    #[must_use]
+    pub fn with_priority_reserve(mut self, fraction: f64) -> Self {
+        self.priority_reserve = fraction.clamp(0.0, 1.0);
+        self
+    }
+
     /// Adds a credential to the scheduler.
     ///
     /// Re-adding an existing `id` is treated as an external replacement:
     /// all runtime state tracked by the scheduler (refreshing, cooldowns,
     /// dynamically disabled capabilities) is discarded and rebuilt from the
-    /// supplied resource plus `initial_caps_bits`.
-    pub fn add_credential(&mut self, id: CredentialId, resource: R, initial_caps_bits: u64) {
+    /// supplied resource plus `initial_caps`.
+    pub fn add_credential(
+        &mut self,
+        id: CredentialId,
+        resource: R,
+        initial_caps: ModelCapabilities,
+    ) {
+        // Leases of the replaced resource are still out and will be released.
+        let mut in_flight = 0;
         if let Some(mut old) = self.creds.remove(&id) {
             old.detach(&mut self.status);
+            in_flight = old.in_flight;
         }
 
-        let caps = ModelCapabilities::from_bits(initial_caps_bits);
-        self.creds
-            .insert(id, ResourceEntry::new(resource, caps, self.model_count));
-
         for (index, queue) in self.queues.iter_mut().enumerate() {
-            if caps.supports(index) {
+            if initial_caps.supports(index) {
                 queue.push_back(id);
             }
         }
+        let mut entry = ResourceEntry::new(resource, initial_caps, self.model_count);
+        entry.in_flight = in_flight;
+        self.creds.insert(id, entry);
     }
 
     /// Applies a completed refresh by updating the inner resource for an
@@ -333,16 +654,32 @@ impl<R: Schedulable> ResourceScheduler<R> {
         }
     }
 
+    /// Swap in a newer copy of an active credential, e.g. one re-submitted
+    /// with different labels, keeping its runtime state.
+    pub fn replace_resource(&mut self, id: CredentialId, resource: R) {
+        if let Some(cred) = self.creds.get_mut(&id) {
+            cred.inner = resource;
+        }
+    }
+
     /// Selects a credential for `model_mask`.
     ///
     /// When `sticky_id` is provided, it is evaluated first; on any non-ready
-    /// status the method falls back to round-robin queue selection.
+    /// status the method falls back to queue selection under the configured
+    /// tier weights or [`SchedulingPolicy`].
     /// Expired credentials encountered along either path are collected in
     /// [`AssignmentResult::refresh_ids`].
+    ///
+    /// With a `pool`, only credentials carrying that label are considered;
+    /// the others stay queued for other requests. Below
+    /// [`Priority::High`], nothing is assigned while the model is down to its
+    /// reserve (see [`Self::with_priority_reserve`]).
     pub fn get_assigned(
         &mut self,
-        model_mask: u64,
+        model_mask: &ModelCapabilities,
         sticky_id: Option<CredentialId>,
+        pool: Option<&str>,
+        priority: Priority,
     ) -> AssignmentResult<R::Lease> {
         let now = Instant::now();
         self.process_waiting_room(now);
@@ -354,12 +691,24 @@ impl<R: Schedulable> ResourceScheduler<R> {
         };
 
         result.stats = self.stats(model_mask);
+        if priority != Priority::High && self.in_reserve(model_index, now, pool) {
+            result.stats.held_in_reserve = true;
+            return result;
+        }
 
         // Evaluate sticky hint first if provided.
         if let Some(id) = sticky_id {
-            let status = self.check_lease(id, model_index, now);
+            let status = self.check_lease(id, model_index, now, pool);
             match status {
                 LeaseStatus::Ready(lease) => {
+                    self.acquire(id);
+                    result.assigned = Some(lease);
+                    result.route_hit = true;
+                    return result;
+                }
+                LeaseStatus::Stale(lease) => {
+                    self.serve_stale(id, &mut result);
+                    self.acquire(id);
                     result.assigned = Some(lease);
                     result.route_hit = true;
                     return result;
@@ -369,18 +718,31 @@ impl<R: Schedulable> ResourceScheduler<R> {
             }
         }
 
-        // Round-robin from queue.
+        if !self.tier_weights.is_empty() {
+            return self.assign_weighted(model_index, now, pool, result);
+        }
+        if self.policy != SchedulingPolicy::RoundRobin {
+            return self.assign_ranked(model_index, now, pool, result);
+        }
+
+        // Round-robin from queue. Busy and other-pool credentials stay
+        // queued; they are put back once the scan ends so it cannot loop
+        // over them.
+        let mut busy = Vec::new();
         while let Some(id) = self
             .queues
             .get_mut(model_index)
             .and_then(ModelQueue::pop_front)
         {
-            let status = self.check_lease(id, model_index, now);
+            let status = self.check_lease(id, model_index, now, pool);
+            if matches!(status, LeaseStatus::Stale(_)) {
+                self.serve_stale(id, &mut result);
+            }
             match status {
-                LeaseStatus::Ready(lease) => {
-                    if let Some(queue) = self.queues.get_mut(model_index) {
-                        queue.push_back(id);
-                    }
+                LeaseStatus::Ready(lease) | LeaseStatus::Stale(lease) => {
+                    self.requeue(model_index, busy);
+                    self.requeue(model_index, [id]);
+                    self.acquire(id);
                     result.assigned = Some(lease);
                     return result;
                 }
@@ -390,29 +752,281 @@ impl<R: Schedulable> ResourceScheduler<R> {
                 }
                 LeaseStatus::Cooling => result.stats.skipped_cooling += 1,
                 LeaseStatus::Refreshing => result.stats.skipped_refreshing += 1,
+                LeaseStatus::Busy => {
+                    busy.push(id);
+                    result.stats.skipped_busy += 1;
+                }
+                LeaseStatus::OtherPool => {
+                    busy.push(id);
+                    result.stats.skipped_other_pool += 1;
+                }
                 LeaseStatus::Unsupported => result.stats.skipped_unsupported += 1,
                 LeaseStatus::Missing => {}
             }
         }
+        self.requeue(model_index, busy);
+        result
+    }
+
+    /// Whether no more of the model's credentials are ready than
+    /// `priority_reserve` holds back.
+    #[allow(
+        clippy::cast_precision_loss,
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss
+    )]
+    fn in_reserve(&self, model_index: ModelIndex, now: Instant, pool: Option<&str>) -> bool {
+        if self.priority_reserve <= 0.0 {
+            return false;
+        }
+        let mut serving = 0usize;
+        let mut ready = 0usize;
+        for &id in self.creds.keys() {
+            match self.check_lease(id, model_index, now, pool) {
+                LeaseStatus::Ready(_) | LeaseStatus::Stale(_) => ready += 1,
+                LeaseStatus::OtherPool | LeaseStatus::Unsupported | LeaseStatus::Missing => {
+                    continue;
+                }
+                _ => {}
+            }
+            serving += 1;
+        }
+        let reserved = (serving as f64 * self.priority_reserve).floor() as usize;
+        reserved > 0 && ready <= reserved
+    }
+
+    fn requeue(&mut self, model_index: ModelIndex, ids: impl IntoIterator<Item = CredentialId>) {
+        if let Some(queue) = self.queues.get_mut(model_index) {
+            for id in ids {
+                queue.push_back(id);
+            }
+        }
+    }
+
+    fn acquire(&mut self, id: CredentialId) {
+        if let Some(cred) = self.creds.get_mut(&id) {
+            cred.in_flight = cred.in_flight.saturating_add(1);
+            cred.last_leased = Some(Instant::now());
+        }
+    }
+
+    /// Earliest moment a cooling credential re-enters a queue.
+    pub fn next_cooldown_expiry(&self) -> Option<Instant> {
+        self.waiting_room.peek().map(|ticket| ticket.0.0)
+    }
+
+    /// How long until the first credential cooling on `model_mask` (within
+    /// `pool`) can serve it again; `None` when nothing is cooling.
+    pub fn retry_after(
+        &self,
+        model_mask: &ModelCapabilities,
+        pool: Option<&str>,
+    ) -> Option<Duration> {
+        let model_index = self.index_from_mask(model_mask)?;
+        let now = Instant::now();
+        self.creds
+            .values()
+            .filter(|cred| cred.caps.supports(model_index))
+            .filter(|cred| pool.is_none_or(|pool| cred.inner.labels().iter().any(|l| l == pool)))
+            .filter_map(|cred| cred.cooldowns.get(model_index).copied().flatten())
+            .filter(|&deadline| deadline > now)
+            .min()
+            .map(|deadline| deadline.duration_since(now))
+    }
+
+    /// Returns a lease handed out by [`Self::get_assigned`].
+    pub fn release(&mut self, id: CredentialId) {
+        if let Some(cred) = self.creds.get_mut(&id) {
+            cred.in_flight = cred.in_flight.saturating_sub(1);
+        }
+    }
+
+    /// Weighted pick among every ready credential in the model's queue.
+    ///
+    /// Smooth weighted round-robin: each ready credential earns its weight in
+    /// credit and the richest one is leased and pays back the round's total,
+    /// so shares converge to the weight ratios without bursts. Non-ready
+    /// credentials leave the queue exactly as in plain round-robin.
+    fn assign_weighted(
+        &mut self,
+        model_index: ModelIndex,
+        now: Instant,
+        pool: Option<&str>,
+        mut result: AssignmentResult<R::Lease>,
+    ) -> AssignmentResult<R::Lease> {
+        let (ready, busy) = self.scan_queue(model_index, now, pool, &mut result);
+
+        let weighted: Vec<(CredentialId, i64)> = ready
+            .iter()
+            .map(|&id| (id, self.weight_of(id)))
+            .filter(|&(_, weight)| weight > 0)
+            .collect();
+        let chosen = if weighted.is_empty() {
+            ready.first().copied()
+        } else {
+            let total: i64 = weighted.iter().map(|&(_, w)| w).sum();
+            let mut best: Option<(CredentialId, i64)> = None;
+            for &(id, weight) in &weighted {
+                let Some(cred) = self.creds.get_mut(&id) else {
+                    continue;
+                };
+                cred.credit += weight;
+                if best.is_none_or(|(_, credit)| cred.credit > credit) {
+                    best = Some((id, cred.credit));
+                }
+            }
+            best.map(|(id, _)| {
+                if let Some(cred) = self.creds.get_mut(&id) {
+                    cred.credit -= total;
+                }
+                id
+            })
+        };
+
+        self.lease_chosen(model_index, &ready, &busy, chosen, &mut result);
         result
     }
 
+    /// Leases the ready credential preferred by the scheduling policy for
+    /// this model; earlier queue positions win ties.
+    fn assign_ranked(
+        &mut self,
+        model_index: ModelIndex,
+        now: Instant,
+        pool: Option<&str>,
+        mut result: AssignmentResult<R::Lease>,
+    ) -> AssignmentResult<R::Lease> {
+        let (ready, busy) = self.scan_queue(model_index, now, pool, &mut result);
+        let chosen = match self.policy {
+            SchedulingPolicy::LeastRecentlyUsed => ready
+                .iter()
+                .copied()
+                .min_by_key(|id| self.creds.get(id).and_then(|c| c.last_leased)),
+            SchedulingPolicy::P2cLatency if ready.len() > 2 => {
+                let picks = rand::seq::index::sample(&mut rand::rng(), ready.len(), 2);
+                let pair: Vec<_> = picks.iter().map(|i| ready[i]).collect();
+                self.lowest_score(&pair, model_index)
+            }
+            _ => self.lowest_score(&ready, model_index),
+        };
+        self.lease_chosen(model_index, &ready, &busy, chosen, &mut result);
+        result
+    }
+
+    fn lowest_score(&self, ids: &[CredentialId], model_index: ModelIndex) -> Option<CredentialId> {
+        ids.iter()
+            .filter_map(|&id| {
+                let score = self.creds.get(&id)?.feedback[model_index].score(self.policy);
+                Some((id, score))
+            })
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(id, _)| id)
+    }
+
+    /// Drains the model's queue and sorts its credentials into ready and
+    /// busy (or other-pool) ones; the rest leave the queue exactly as in
+    /// plain round-robin.
+    fn scan_queue(
+        &mut self,
+        model_index: ModelIndex,
+        now: Instant,
+        pool: Option<&str>,
+        result: &mut AssignmentResult<R::Lease>,
+    ) -> (Vec<CredentialId>, Vec<CredentialId>) {
+        let Some(queued) = self.queues.get_mut(model_index).map(ModelQueue::drain) else {
+            return (Vec::new(), Vec::new());
+        };
+
+        let mut ready = Vec::with_capacity(queued.len());
+        let mut busy = Vec::new();
+        for id in queued {
+            match self.check_lease(id, model_index, now, pool) {
+                LeaseStatus::Ready(_) => ready.push(id),
+                LeaseStatus::Stale(_) => {
+                    self.serve_stale(id, result);
+                    ready.push(id);
+                }
+                LeaseStatus::Expired => {
+                    result.refresh_ids.push(id);
+                    result.stats.skipped_expired += 1;
+                }
+                LeaseStatus::Cooling => result.stats.skipped_cooling += 1,
+                LeaseStatus::Refreshing => result.stats.skipped_refreshing += 1,
+                LeaseStatus::Busy => {
+                    busy.push(id);
+                    result.stats.skipped_busy += 1;
+                }
+                LeaseStatus::OtherPool => {
+                    busy.push(id);
+                    result.stats.skipped_other_pool += 1;
+                }
+                LeaseStatus::Unsupported => result.stats.skipped_unsupported += 1,
+                LeaseStatus::Missing => {}
+            }
+        }
+        (ready, busy)
+    }
+
+    /// Requeues the scanned credentials, `chosen` last, and leases it.
+    fn lease_chosen(
+        &mut self,
+        model_index: ModelIndex,
+        ready: &[CredentialId],
+        busy: &[CredentialId],
+        chosen: Option<CredentialId>,
+        result: &mut AssignmentResult<R::Lease>,
+    ) {
+        let queue = &mut self.queues[model_index];
+        for &id in ready.iter().chain(busy).filter(|&&id| Some(id) != chosen) {
+            queue.push_back(id);
+        }
+        if let Some(id) = chosen {
+            queue.push_back(id);
+            result.assigned = self.creds.get(&id).map(|c| c.inner.make_lease(id));
+            self.acquire(id);
+        }
+    }
+
+    fn weight_of(&self, id: CredentialId) -> i64 {
+        self.creds
+            .get(&id)
+            .and_then(|c| c.inner.tier())
+            .and_then(|tier| self.tier_weights.get(tier))
+            .map_or(1, |&w| i64::from(w))
+    }
+
     /// Single evaluation path for any credential candidate against a model index.
     fn check_lease(
         &self,
         id: CredentialId,
         model_index: ModelIndex,
         now: Instant,
+        pool: Option<&str>,
     ) -> LeaseStatus<R::Lease> {
         let Some(cred) = self.creds.get(&id) else {
             return LeaseStatus::Missing;
         };
 
+        if pool.is_some_and(|pool| !cred.inner.labels().iter().any(|l| l == pool)) {
+            return LeaseStatus::OtherPool;
+        }
+
         if !cred.caps.supports(model_index) {
             return LeaseStatus::Unsupported;
         }
 
+        let busy = self.max_concurrent.is_some_and(|max| cred.in_flight >= max);
+
         if cred.is_refreshing() {
+            if cred.stale_ok
+                && self.within_stale_grace(&cred.inner)
+                && !cred.is_cooling(model_index, now)
+            {
+                if busy {
+                    return LeaseStatus::Busy;
+                }
+                return LeaseStatus::Stale(cred.inner.make_lease(id));
+            }
             return LeaseStatus::Refreshing;
         }
 
@@ -420,15 +1034,50 @@ impl<R: Schedulable> ResourceScheduler<R> {
             return LeaseStatus::Cooling;
         }
 
-        if cred.inner.is_expired() {
-            return LeaseStatus::Expired;
+        if cred.inner.expires_within(self.min_token_validity) {
+            if !self.within_stale_grace(&cred.inner) {
+                return LeaseStatus::Expired;
+            }
+            if !busy {
+                return LeaseStatus::Stale(cred.inner.make_lease(id));
+            }
         }
 
+        if busy {
+            return LeaseStatus::Busy;
+        }
         LeaseStatus::Ready(cred.inner.make_lease(id))
     }
 
-    pub fn report_rate_limit(&mut self, id: CredentialId, model_mask: u64, cooldown: Duration) {
+    fn within_stale_grace(&self, resource: &R) -> bool {
+        !self.stale_grace.is_zero() && !resource.expired_beyond(self.stale_grace)
+    }
+
+    /// Bookkeeping for a [`LeaseStatus::Stale`] lease: the first one asks for
+    /// the refresh and allows stale leases until it completes.
+    fn serve_stale(&mut self, id: CredentialId, result: &mut AssignmentResult<R::Lease>) {
+        let Some(cred) = self.creds.get_mut(&id) else {
+            return;
+        };
+        if !cred.is_refreshing() {
+            cred.stale_ok = true;
+            result.refresh_ids.push(id);
+        }
+        result.stats.served_stale += 1;
+    }
+
+    pub fn report_rate_limit(
+        &mut self,
+        id: CredentialId,
+        model_mask: &ModelCapabilities,
+        cooldown: Duration,
+    ) {
         let now = Instant::now();
+        if let Some(model_index) = self.index_from_mask(model_mask)
+            && let Some(cred) = self.creds.get_mut(&id)
+        {
+            cred.feedback[model_index].record_outcome(false);
+        }
         match R::COOLDOWN_GRANULARITY {
             CooldownScope::PerModel => {
                 let Some(model_index) = self.index_from_mask(model_mask) else {
@@ -444,6 +1093,30 @@ impl<R: Schedulable> ResourceScheduler<R> {
         }
     }
 
+    /// Record upstream-reported account quota for `id`. When the quota is
+    /// used up, every model cools down for `exhausted_for` without counting
+    /// as a failure, so the next request goes elsewhere instead of into a 429.
+    pub fn report_quota(
+        &mut self,
+        id: CredentialId,
+        remaining_percent: u8,
+        exhausted_for: Option<Duration>,
+    ) {
+        let Some(cred) = self.creds.get_mut(&id) else {
+            return;
+        };
+        cred.quota_remaining = Some(remaining_percent);
+        let Some(cooldown) = exhausted_for else {
+            return;
+        };
+        let now = Instant::now();
+        for index in 0..self.queues.len() {
+            if self.creds[&id].cooldowns[index].is_none_or(|d| d < now + cooldown) {
+                self.insert_cooldown(id, index, cooldown, now);
+            }
+        }
+    }
+
     fn insert_cooldown(
         &mut self,
         id: CredentialId,
@@ -465,6 +1138,13 @@ impl<R: Schedulable> ResourceScheduler<R> {
         waiting_room.push(CooldownTicket(Reverse(deadline), id, model_index));
     }
 
+    /// Stops stale leases of `id`, e.g. once upstream rejected its token.
+    pub fn revoke_stale(&mut self, id: CredentialId) {
+        if let Some(cred) = self.creds.get_mut(&id) {
+            cred.stale_ok = false;
+        }
+    }
+
     pub fn mark_refreshing(&mut self, id: CredentialId) {
         let Self { creds, status, .. } = self;
         if let Some(cred) = creds.get_mut(&id) {
@@ -476,16 +1156,224 @@ impl<R: Schedulable> ResourceScheduler<R> {
     pub fn mark_model_unsupported(
         &mut self,
         id: CredentialId,
-        model_mask: u64,
-    ) -> Option<(u64, u64)> {
-        if model_mask == 0 {
+        model_mask: &ModelCapabilities,
+    ) -> Option<(ModelCapabilities, ModelCapabilities)> {
+        if model_mask.is_empty() {
             return None;
         }
         let cred = self.creds.get_mut(&id)?;
-        let before = cred.caps.bits();
+        let before = cred.caps.clone();
         cred.caps.disable_mask(model_mask);
-        let after = cred.caps.bits();
-        Some((before, after))
+        let now = Instant::now();
+        for index in before.iter().filter(|&i| !cred.caps.supports(i)) {
+            if let Some(slot) = cred.lost_at.get_mut(index) {
+                *slot = Some(now);
+            }
+        }
+        Some((before, cred.caps.clone()))
+    }
+
+    /// How often [`Self::restore_lost_models`] should run, if restoring is on.
+    pub fn restore_check_interval(&self) -> Option<Duration> {
+        (!self.capability_restore.is_zero())
+            .then(|| self.capability_restore.min(MAX_RESTORE_CHECK_INTERVAL))
+    }
+
+    /// Re-enables every model lost at least `capability_restore` ago and
+    /// queues the credential for it again; the next request re-validates it.
+    ///
+    /// Admin overrides are not touched. Returns the restored models per
+    /// credential.
+    pub fn restore_lost_models(&mut self) -> Vec<(CredentialId, ModelCapabilities)> {
+        if self.capability_restore.is_zero() {
+            return Vec::new();
+        }
+        let Some(cutoff) = Instant::now().checked_sub(self.capability_restore) else {
+            return Vec::new();
+        };
+        let mut restored = Vec::new();
+        for (&id, cred) in &mut self.creds {
+            let mut models = ModelCapabilities::none();
+            for (index, slot) in cred.lost_at.iter_mut().enumerate() {
+                if slot.is_some_and(|at| at <= cutoff) {
+                    *slot = None;
+                    cred.outcomes[index] = OutcomeWindow::default();
+                    cred.caps.enable(index);
+                    models.enable(index);
+                    self.queues[index].push_back(id);
+                }
+            }
+            if !models.is_empty() {
+                restored.push((id, models));
+            }
+        }
+        restored
+    }
+
+    /// Records a success or a generic failure of `id` on the model in `model_mask`.
+    ///
+    /// The outcome always feeds the error rate ranked by
+    /// [`SchedulingPolicy::LeastErrors`]. Without an auto-disable policy
+    /// nothing else happens. With one, a full window whose success rate is
+    /// below the threshold clears the model's capability bit (unless it is
+    /// pinned) and the observed success rate is returned.
+    pub fn report_outcome(
+        &mut self,
+        id: CredentialId,
+        model_mask: &ModelCapabilities,
+        success: bool,
+    ) -> Option<f64> {
+        let model_index = self.index_from_mask(model_mask)?;
+        let cred = self.creds.get_mut(&id)?;
+        cred.feedback[model_index].record_outcome(success);
+        let policy = self.auto_disable?;
+        if !cred.caps.supports(model_index) || cred.pinned.supports(model_index) {
+            return None;
+        }
+        let window = &mut cred.outcomes[model_index];
+        let rate = window.record(success, policy.window)?;
+        if rate >= policy.min_success_rate {
+            return None;
+        }
+        *window = OutcomeWindow::default();
+        cred.caps.disable(model_index);
+        cred.lost_at[model_index] = Some(Instant::now());
+        Some(rate)
+    }
+
+    /// Records a successful request of `id` on the model in `model_mask` that
+    /// took `latency` to answer, then handles it like
+    /// [`Self::report_outcome`].
+    pub fn report_success(
+        &mut self,
+        id: CredentialId,
+        model_mask: &ModelCapabilities,
+        latency: Duration,
+    ) -> Option<f64> {
+        let model_index = self.index_from_mask(model_mask)?;
+        self.creds.get_mut(&id)?.feedback[model_index].record_latency(latency);
+        self.report_outcome(id, model_mask, true)
+    }
+
+    /// Counts `event` towards the health score of `id`.
+    ///
+    /// Without a health policy the score is only tracked. With one, a score
+    /// below the threshold either parks the credential (every model cools
+    /// down and the score starts over) or asks the caller to ban it.
+    pub fn record_health(&mut self, id: CredentialId, event: HealthEvent) -> Option<HealthVerdict> {
+        let cred = self.creds.get_mut(&id)?;
+        cred.health.record(event);
+        let policy = self.health?;
+        let score = cred.health.score();
+        if score >= policy.min_score {
+            return None;
+        }
+        match policy.action {
+            HealthAction::Ban => Some(HealthVerdict::Ban { score }),
+            HealthAction::Park => {
+                cred.health = CredentialHealth::default();
+                let duration = Duration::from_secs(policy.park_secs);
+                let now = Instant::now();
+                for index in 0..self.queues.len() {
+                    self.insert_cooldown(id, index, duration, now);
+                }
+                Some(HealthVerdict::Parked { score, duration })
+            }
+        }
+    }
+
+    /// Admin override for one model of one credential.
+    ///
+    /// Enabling sets the capability bit, pins it against auto-disable and puts
+    /// the credential back in that model's queue; disabling clears both.
+    /// Returns the `(before, after)` capabilities.
+    pub fn set_model_override(
+        &mut self,
+        id: CredentialId,
+        model_mask: &ModelCapabilities,
+        enabled: bool,
+    ) -> Option<(ModelCapabilities, ModelCapabilities)> {
+        let model_index = self.index_from_mask(model_mask)?;
+        let cred = self.creds.get_mut(&id)?;
+        let before = cred.caps.clone();
+        cred.outcomes[model_index] = OutcomeWindow::default();
+        cred.lost_at[model_index] = None;
+        if enabled {
+            cred.caps.enable(model_index);
+            cred.pinned.enable(model_index);
+            self.queues[model_index].push_back(id);
+        } else {
+            cred.caps.disable(model_index);
+            cred.pinned.disable(model_index);
+        }
+        Some((before, cred.caps.clone()))
+    }
+
+    /// Admin cooldown for the models in `model_mask` of one credential.
+    ///
+    /// `Some(duration)` cools them for `duration` without counting as a
+    /// failure; `None` lifts their cooldowns early and queues the credential
+    /// for the ones it supports. Returns `false` if `id` is not loaded.
+    pub fn set_manual_cooldown(
+        &mut self,
+        id: CredentialId,
+        model_mask: &ModelCapabilities,
+        cooldown: Option<Duration>,
+    ) -> bool {
+        if !self.creds.contains_key(&id) {
+            return false;
+        }
+        let indexes: Vec<ModelIndex> = model_mask
+            .iter()
+            .filter(|&index| index < self.queues.len())
+            .collect();
+        let Some(cooldown) = cooldown else {
+            let Self {
+                creds,
+                status,
+                queues,
+                ..
+            } = self;
+            let cred = creds.get_mut(&id).expect("checked above");
+            for index in indexes {
+                if cred.cooldowns[index].take().is_some() {
+                    status.dec_cooldown_count(index);
+                    if cred.caps.supports(index) {
+                        queues[index].push_back(id);
+                    }
+                }
+            }
+            return true;
+        };
+        let now = Instant::now();
+        for index in indexes {
+            self.insert_cooldown(id, index, cooldown, now);
+        }
+        true
+    }
+
+    /// Admin replacement of one credential's capability mask.
+    ///
+    /// Models in `caps` are queued again; models outside it lose their pin
+    /// and are not given back by [`Self::restore_lost_models`]. Returns the
+    /// `(before, after)` capabilities.
+    pub fn set_capabilities(
+        &mut self,
+        id: CredentialId,
+        caps: &ModelCapabilities,
+    ) -> Option<(ModelCapabilities, ModelCapabilities)> {
+        let cred = self.creds.get_mut(&id)?;
+        let before = cred.caps.clone();
+        cred.caps = caps.clone();
+        cred.pinned = cred.pinned.intersection(caps);
+        for (index, queue) in self.queues.iter_mut().enumerate() {
+            cred.outcomes[index] = OutcomeWindow::default();
+            cred.lost_at[index] = None;
+            if caps.supports(index) {
+                queue.push_back(id);
+            }
+        }
+        Some((before, cred.caps.clone()))
     }
 
     pub fn delete_credential(&mut self, id: CredentialId) {
@@ -519,11 +1407,53 @@ impl<R: Schedulable> ResourceScheduler<R> {
             .is_some_and(ResourceEntry::is_refreshing)
     }
 
-    pub fn total_creds(&self) -> usize {
-        self.creds.len()
+    /// Loaded credentials that could serve at least one model right now:
+    /// not refreshing and not cooling down on every model they support.
+    pub fn usable_creds(&self) -> usize {
+        let now = Instant::now();
+        self.creds
+            .values()
+            .filter(|entry| {
+                !entry.is_refreshing()
+                    && entry
+                        .caps
+                        .iter()
+                        .take_while(|&idx| idx < entry.cooldowns.len())
+                        .any(|idx| !entry.is_cooling(idx, now))
+            })
+            .count()
+    }
+
+    /// Snapshot of the runtime state of every loaded credential.
+    pub fn runtime_snapshot(&self) -> HashMap<CredentialId, CredentialRuntime> {
+        let now = Instant::now();
+        self.creds
+            .iter()
+            .map(|(&id, entry)| {
+                let cooldowns = entry
+                    .cooldowns
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(idx, deadline)| {
+                        deadline
+                            .filter(|d| *d > now)
+                            .map(|d| (idx, d.duration_since(now)))
+                    })
+                    .collect();
+                let runtime = CredentialRuntime {
+                    caps: entry.caps.clone(),
+                    refreshing: entry.refreshing,
+                    in_flight: entry.in_flight,
+                    cooldowns,
+                    quota_remaining: entry.quota_remaining,
+                    health: entry.health,
+                };
+                (id, runtime)
+            })
+            .collect()
     }
 
-    pub fn stats(&self, model_mask: u64) -> AssignmentStats {
+    pub fn stats(&self, model_mask: &ModelCapabilities) -> AssignmentStats {
         let model_index = self.index_from_mask(model_mask);
         let queue_len = model_index
             .and_then(|i| self.queues.get(i).map(ModelQueue::len))
@@ -538,15 +1468,10 @@ impl<R: Schedulable> ResourceScheduler<R> {
         }
     }
 
-    fn index_from_mask(&self, model_mask: u64) -> Option<ModelIndex> {
-        if model_mask == 0 || (model_mask & (model_mask - 1)) != 0 {
-            return None;
-        }
-        let index = model_mask.trailing_zeros() as usize;
-        if index >= self.queues.len() {
-            return None;
-        }
-        Some(index)
+    fn index_from_mask(&self, model_mask: &ModelCapabilities) -> Option<ModelIndex> {
+        model_mask
+            .single_index()
+            .filter(|&index| index < self.queues.len())
     }
 
     fn process_waiting_room(&mut self, now: Instant) {
@@ -609,7 +1534,7 @@ mod tests {
             "mock"
         }
 
-        fn is_expired(&self) -> bool {
+        fn expires_within(&self, _min_validity: Duration) -> bool {
             self.0
         }
 
@@ -630,7 +1555,7 @@ mod tests {
             "mock-per-cred"
         }
 
-        fn is_expired(&self) -> bool {
+        fn expires_within(&self, _min_validity: Duration) -> bool {
             self.0
         }
 
@@ -639,22 +1564,90 @@ mod tests {
         }
     }
 
-    type Mgr = ResourceScheduler<MockResource>;
+    /// Tiered variant for weighted selection.
+    #[derive(Debug, Clone)]
+    struct MockTieredResource(&'static str);
 
-    fn mask(index: usize) -> u64 {
-        1u64 << index
+    impl Schedulable for MockTieredResource {
+        type Lease = MockLease;
+
+        fn identifier(&self) -> &'static str {
+            self.0
+        }
+
+        fn expires_within(&self, _min_validity: Duration) -> bool {
+            false
+        }
+
+        fn make_lease(&self, id: CredentialId) -> MockLease {
+            MockLease(id)
+        }
+
+        fn tier(&self) -> Option<&str> {
+            Some(self.0)
+        }
     }
 
-    fn all_caps() -> u64 {
-        ModelCapabilities::all().bits()
+    /// Token awaiting refresh; `true` once it is past any stale grace.
+    #[derive(Debug, Clone)]
+    struct MockStaleResource(bool);
+
+    impl Schedulable for MockStaleResource {
+        type Lease = MockLease;
+
+        fn identifier(&self) -> &'static str {
+            "mock-stale"
+        }
+
+        fn expires_within(&self, _min_validity: Duration) -> bool {
+            true
+        }
+
+        fn expired_beyond(&self, _grace: Duration) -> bool {
+            self.0
+        }
+
+        fn make_lease(&self, id: CredentialId) -> MockLease {
+            MockLease(id)
+        }
     }
 
-    fn caps_for(indices: &[usize]) -> u64 {
-        let mut c = ModelCapabilities::none();
-        for &i in indices {
-            c.enable(i);
+    /// Labeled variant for pool pinning.
+    #[derive(Debug, Clone)]
+    struct MockLabeledResource(Vec<String>);
+
+    impl Schedulable for MockLabeledResource {
+        type Lease = MockLease;
+
+        fn identifier(&self) -> &'static str {
+            "mock-labeled"
+        }
+
+        fn expires_within(&self, _min_validity: Duration) -> bool {
+            false
+        }
+
+        fn make_lease(&self, id: CredentialId) -> MockLease {
+            MockLease(id)
+        }
+
+        fn labels(&self) -> &[String] {
+            &self.0
         }
-        c.bits()
+    }
+
+    type Mgr = ResourceScheduler<MockResource>;
+
+    fn mask(index: usize) -> ModelCapabilities {
+        ModelCapabilities::single(index)
+    }
+
+    fn all_caps() -> ModelCapabilities {
+        ModelCapabilities::all(4)
+    }
+
+    fn caps_for(indices: &[usize]) -> ModelCapabilities {
+        indices.iter().copied().collect()
     }
 
     // ── Core scheduling ─────────────────────────────────────────────
@@ -664,8 +1657,82 @@ mod tests {
         let mut mgr = Mgr::new(2);
         mgr.add_credential(1, MockResource(false), caps_for(&[0]));
 
-        assert!(mgr.get_assigned(mask(0), None).assigned.is_some());
-        assert!(mgr.get_assigned(mask(1), None).assigned.is_none());
+        assert!(
+            mgr.get_assigned(&mask(0), None, None, Priority::Normal)
+                .assigned
+                .is_some()
+        );
+        assert!(
+            mgr.get_assigned(&mask(1), None, None, Priority::Normal)
+                .assigned
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn usable_creds_excludes_refreshing_and_fully_cooling_credentials() {
+        let mut mgr = Mgr::new(2);
+        mgr.add_credential(1, MockResource(false), all_caps());
+        mgr.add_credential(2, MockResource(false), caps_for(&[0]));
+        mgr.add_credential(3, MockResource(false), all_caps());
+        assert_eq!(mgr.usable_creds(), 3);
+
+        mgr.report_rate_limit(1, &mask(1), Duration::from_secs(30));
+        mgr.report_rate_limit(2, &mask(0), Duration::from_secs(30));
+        mgr.mark_refreshing(3);
+        assert_eq!(mgr.usable_creds(), 1);
+    }
+
+    #[test]
+    fn runtime_snapshot_reports_caps_and_active_cooldowns() {
+        let mut mgr = Mgr::new(2);
+        mgr.add_credential(1, MockResource(false), all_caps());
+        mgr.add_credential(2, MockResource(false), caps_for(&[0]));
+        mgr.report_rate_limit(1, &mask(1), Duration::from_secs(30));
+        mgr.mark_refreshing(2);
+
+        let snap = mgr.runtime_snapshot();
+        let one = &snap[&1];
+        assert_eq!(one.caps, all_caps());
+        assert!(!one.refreshing);
+        assert_eq!(one.cooldowns.len(), 1);
+        assert_eq!(one.cooldowns[0].0, 1);
+
+        let two = &snap[&2];
+        assert_eq!(two.caps, caps_for(&[0]));
+        assert!(two.refreshing);
+        assert!(two.cooldowns.is_empty());
+    }
+
+    #[test]
+    fn models_beyond_index_63_are_schedulable() {
+        let mut mgr = Mgr::new(70);
+        mgr.add_credential(1, MockResource(false), caps_for(&[3, 66]));
+
+        assert_eq!(
+            mgr.get_assigned(&mask(66), None, None, Priority::Normal)
+                .assigned
+                .unwrap()
+                .0,
+            1
+        );
+        assert!(
+            mgr.get_assigned(&mask(69), None, None, Priority::Normal)
+                .assigned
+                .is_none()
+        );
+
+        mgr.mark_model_unsupported(1, &mask(66));
+        assert!(
+            mgr.get_assigned(&mask(66), None, None, Priority::Normal)
+                .assigned
+                .is_none()
+        );
+        assert!(
+            mgr.get_assigned(&mask(3), None, None, Priority::Normal)
+                .assigned
+                .is_some()
+        );
     }
 
     #[test]
@@ -674,8 +1741,14 @@ mod tests {
         mgr.add_credential(1, MockResource(false), caps_for(&[0]));
         mgr.add_credential(2, MockResource(false), caps_for(&[0]));
 
-        let first = mgr.get_assigned(mask(0), None).assigned.unwrap();
-        let second = mgr.get_assigned(mask(0), None).assigned.unwrap();
+        let first = mgr
+            .get_assigned(&mask(0), None, None, Priority::Normal)
+            .assigned
+            .unwrap();
+        let second = mgr
+            .get_assigned(&mask(0), None, None, Priority::Normal)
+            .assigned
+            .unwrap();
         assert_eq!(first.0, 1);
         assert_eq!(second.0, 2);
     }
@@ -684,23 +1757,66 @@ mod tests {
     fn mark_model_unsupported_disables_capability() {
         let mut mgr = Mgr::new(2);
         mgr.add_credential(1, MockResource(false), all_caps());
-        mgr.mark_model_unsupported(1, mask(1));
+        mgr.mark_model_unsupported(1, &mask(1));
+
+        assert!(
+            mgr.get_assigned(&mask(1), None, None, Priority::Normal)
+                .assigned
+                .is_none()
+        );
+        assert!(
+            mgr.get_assigned(&mask(0), None, None, Priority::Normal)
+                .assigned
+                .is_some()
+        );
+    }
+
+    #[test]
+    fn lost_models_are_restored_after_delay() {
+        let mut mgr = Mgr::new(2).with_capability_restore(Duration::from_millis(20));
+        mgr.add_credential(1, MockResource(false), all_caps());
+        mgr.add_credential(2, MockResource(false), all_caps());
+        mgr.mark_model_unsupported(1, &mask(1));
+        mgr.set_model_override(2, &mask(1), false);
 
-        assert!(mgr.get_assigned(mask(1), None).assigned.is_none());
-        assert!(mgr.get_assigned(mask(0), None).assigned.is_some());
+        assert!(mgr.restore_lost_models().is_empty());
+        std::thread::sleep(Duration::from_millis(30));
+
+        // Admin overrides stay off; only the reported loss comes back.
+        assert_eq!(mgr.restore_lost_models(), vec![(1, mask(1))]);
+        assert_eq!(
+            mgr.get_assigned(&mask(1), None, None, Priority::Normal)
+                .assigned
+                .unwrap()
+                .0,
+            1
+        );
+        assert!(mgr.restore_lost_models().is_empty());
     }
 
     #[test]
     fn readd_same_id_resets_disabled_caps() {
         let mut mgr = Mgr::new(2);
         mgr.add_credential(1, MockResource(false), all_caps());
-        mgr.mark_model_unsupported(1, mask(1));
+        mgr.mark_model_unsupported(1, &mask(1));
 
         // re-add with full caps — runtime-disabled bit should be reset
         mgr.add_credential(1, MockResource(false), all_caps());
 
-        assert_eq!(mgr.get_assigned(mask(1), None).assigned.unwrap().0, 1);
-        assert_eq!(mgr.get_assigned(mask(0), None).assigned.unwrap().0, 1);
+        assert_eq!(
+            mgr.get_assigned(&mask(1), None, None, Priority::Normal)
+                .assigned
+                .unwrap()
+                .0,
+            1
+        );
+        assert_eq!(
+            mgr.get_assigned(&mask(0), None, None, Priority::Normal)
+                .assigned
+                .unwrap()
+                .0,
+            1
+        );
     }
 
     // ── Expiry & refresh ────────────────────────────────────────────
@@ -710,9 +1826,51 @@ mod tests {
         let mut mgr = Mgr::new(1);
         mgr.add_credential(1, MockResource(true), caps_for(&[0]));
 
-        let result = mgr.get_assigned(mask(0), None);
+        let result = mgr.get_assigned(&mask(0), None, None, Priority::Normal);
+        assert!(result.assigned.is_none());
+        assert_eq!(result.refresh_ids, vec![1]);
+    }
+
+    #[test]
+    fn stale_token_is_served_while_its_refresh_runs() {
+        let mut mgr = ResourceScheduler::<MockStaleResource>::new(1)
+            .with_stale_grace(Duration::from_secs(30));
+        mgr.add_credential(1, MockStaleResource(false), caps_for(&[0]));
+
+        let result = mgr.get_assigned(&mask(0), None, None, Priority::Normal);
+        assert_eq!(result.assigned.unwrap().0, 1);
+        assert_eq!(result.refresh_ids, vec![1]);
+
+        // In flight: still leased, refresh not requested again.
+        mgr.mark_refreshing(1);
+        let result = mgr.get_assigned(&mask(0), None, None, Priority::Normal);
+        assert_eq!(result.assigned.unwrap().0, 1);
+        assert!(result.refresh_ids.is_empty());
+        assert_eq!(result.stats.served_stale, 1);
+
+        // Upstream rejected the token: no more stale leases.
+        mgr.revoke_stale(1);
+        assert!(
+            mgr.get_assigned(&mask(0), None, None, Priority::Normal)
+                .assigned
+                .is_none()
+        );
+
+        // Past the grace, or without one, the credential waits for its refresh.
+        let mut mgr = ResourceScheduler::<MockStaleResource>::new(1)
+            .with_stale_grace(Duration::from_secs(30));
+        mgr.add_credential(1, MockStaleResource(true), caps_for(&[0]));
+        let result = mgr.get_assigned(&mask(0), None, None, Priority::Normal);
         assert!(result.assigned.is_none());
         assert_eq!(result.refresh_ids, vec![1]);
+
+        let mut mgr = ResourceScheduler::<MockStaleResource>::new(1);
+        mgr.add_credential(1, MockStaleResource(false), caps_for(&[0]));
+        assert!(
+            mgr.get_assigned(&mask(0), None, None, Priority::Normal)
+                .assigned
+                .is_none()
+        );
     }
 
     #[test]
@@ -722,33 +1880,55 @@ mod tests {
         mgr.add_credential(2, MockResource(false), caps_for(&[0]));
         mgr.mark_refreshing(1);
 
-        assert_eq!(mgr.get_assigned(mask(0), None).assigned.unwrap().0, 2);
+        assert_eq!(
+            mgr.get_assigned(&mask(0), None, None, Priority::Normal)
+                .assigned
+                .unwrap()
+                .0,
+            2
+        );
     }
 
     #[test]
     fn complete_refresh_clears_refreshing_and_requeues() {
         let mut mgr = Mgr::new(1);
         mgr.add_credential(1, MockResource(true), caps_for(&[0]));
-        let result = mgr.get_assigned(mask(0), None);
+        let result = mgr.get_assigned(&mask(0), None, None, Priority::Normal);
         assert_eq!(result.refresh_ids, vec![1]);
 
         mgr.mark_refreshing(1);
         mgr.complete_refresh(1, MockResource(false));
         assert!(!mgr.get_credential(1).unwrap().0);
         assert!(!mgr.is_refreshing(1));
-        assert_eq!(mgr.get_assigned(mask(0), None).assigned.unwrap().0, 1);
+        assert_eq!(
+            mgr.get_assigned(&mask(0), None, None, Priority::Normal)
+                .assigned
+                .unwrap()
+                .0,
+            1
+        );
     }
 
     #[test]
     fn complete_refresh_preserves_disabled_capabilities() {
         let mut mgr = Mgr::new(2);
         mgr.add_credential(1, MockResource(true), all_caps());
-        mgr.mark_model_unsupported(1, mask(1));
+        mgr.mark_model_unsupported(1, &mask(1));
         mgr.mark_refreshing(1);
 
         mgr.complete_refresh(1, MockResource(false));
-        assert!(mgr.get_assigned(mask(1), None).assigned.is_none());
-        assert_eq!(mgr.get_assigned(mask(0), None).assigned.unwrap().0, 1);
+        assert!(
+            mgr.get_assigned(&mask(1), None, None, Priority::Normal)
+                .assigned
+                .is_none()
+        );
+        assert_eq!(
+            mgr.get_assigned(&mask(0), None, None, Priority::Normal)
+                .assigned
+                .unwrap()
+                .0,
+            1
+        );
     }
 
     #[test]
@@ -757,20 +1937,20 @@ mod tests {
         mgr.add_credential(1, MockResource(false), caps_for(&[0]));
         mgr.add_credential(2, MockResource(false), caps_for(&[0]));
 
-        assert_eq!(mgr.stats(mask(0)).refreshing, 0);
+        assert_eq!(mgr.stats(&mask(0)).refreshing, 0);
 
         mgr.mark_refreshing(1);
         mgr.mark_refreshing(1);
-        assert_eq!(mgr.stats(mask(0)).refreshing, 1);
+        assert_eq!(mgr.stats(&mask(0)).refreshing, 1);
 
         mgr.mark_refreshing(2);
-        assert_eq!(mgr.stats(mask(0)).refreshing, 2);
+        assert_eq!(mgr.stats(&mask(0)).refreshing, 2);
 
         mgr.complete_refresh(1, MockResource(false));
-        assert_eq!(mgr.stats(mask(0)).refreshing, 1);
+        assert_eq!(mgr.stats(&mask(0)).refreshing, 1);
 
         mgr.delete_credential(2);
-        assert_eq!(mgr.stats(mask(0)).refreshing, 0);
+        assert_eq!(mgr.stats(&mask(0)).refreshing, 0);
     }
 
     #[test]
@@ -784,8 +1964,14 @@ mod tests {
         mgr.add_credential(1, MockResource(false), caps_for(&[0]));
 
         assert!(!mgr.is_refreshing(1));
-        assert_eq!(mgr.stats(mask(0)).refreshing, 0);
-        assert_eq!(mgr.get_assigned(mask(0), None).assigned.unwrap().0, 1);
+        assert_eq!(mgr.stats(&mask(0)).refreshing, 0);
+        assert_eq!(
+            mgr.get_assigned(&mask(0), None, None, Priority::Normal)
+                .assigned
+                .unwrap()
+                .0,
+            1
+        );
     }
 
     // ── PerModel cooldown ─────────────────────────────────────────
@@ -795,11 +1981,19 @@ mod tests {
         let mut mgr = Mgr::new(1);
         mgr.add_credential(1, MockResource(false), caps_for(&[0]));
 
-        mgr.report_rate_limit(1, mask(0), Duration::from_millis(10));
-        assert!(mgr.get_assigned(mask(0), None).assigned.is_none());
+        mgr.report_rate_limit(1, &mask(0), Duration::from_millis(10));
+        assert!(
+            mgr.get_assigned(&mask(0), None, None, Priority::Normal)
+                .assigned
+                .is_none()
+        );
 
         std::thread::sleep(Duration::from_millis(20));
-        assert!(mgr.get_assigned(mask(0), None).assigned.is_some());
+        assert!(
+            mgr.get_assigned(&mask(0), None, None, Priority::Normal)
+                .assigned
+                .is_some()
+        );
     }
 
     #[test]
@@ -807,10 +2001,18 @@ mod tests {
         let mut mgr = Mgr::new(2);
         mgr.add_credential(1, MockResource(false), caps_for(&[0, 1]));
 
-        mgr.report_rate_limit(1, mask(0), Duration::from_mins(1));
+        mgr.report_rate_limit(1, &mask(0), Duration::from_mins(1));
 
-        assert!(mgr.get_assigned(mask(0), None).assigned.is_none());
-        assert!(mgr.get_assigned(mask(1), None).assigned.is_some());
+        assert!(
+            mgr.get_assigned(&mask(0), None, None, Priority::Normal)
+                .assigned
+                .is_none()
+        );
+        assert!(
+            mgr.get_assigned(&mask(1), None, None, Priority::Normal)
+                .assigned
+                .is_some()
+        );
     }
 
     #[test]
@@ -819,12 +2021,16 @@ mod tests {
         mgr.add_credential(1, MockResource(false), caps_for(&[0]));
 
         // pop + push_back (credential stays in queue)
-        assert!(mgr.get_assigned(mask(0), None).assigned.is_some());
+        assert!(
+            mgr.get_assigned(&mask(0), None, None, Priority::Normal)
+                .assigned
+                .is_some()
+        );
 
-        mgr.report_rate_limit(1, mask(0), Duration::from_millis(10));
+        mgr.report_rate_limit(1, &mask(0), Duration::from_millis(10));
         std::thread::sleep(Duration::from_millis(20));
 
-        let result = mgr.get_assigned(mask(0), None);
+        let result = mgr.get_assigned(&mask(0), None, None, Priority::Normal);
         assert_eq!(result.stats.queue_len, 1, "credential duplicated in queue");
     }
 
@@ -832,18 +2038,28 @@ mod tests {
     fn readd_same_id_resets_cooldown_state() {
         let mut mgr = Mgr::new(1);
         mgr.add_credential(1, MockResource(false), caps_for(&[0]));
-        mgr.report_rate_limit(1, mask(0), Duration::from_millis(10));
+        mgr.report_rate_limit(1, &mask(0), Duration::from_millis(10));
 
-        assert_eq!(mgr.stats(mask(0)).cooldowns, 1);
-        assert!(mgr.get_assigned(mask(0), None).assigned.is_none());
+        assert_eq!(mgr.stats(&mask(0)).cooldowns, 1);
+        assert!(
+            mgr.get_assigned(&mask(0), None, None, Priority::Normal)
+                .assigned
+                .is_none()
+        );
 
         mgr.add_credential(1, MockResource(false), caps_for(&[0]));
 
-        assert_eq!(mgr.stats(mask(0)).cooldowns, 0);
-        assert_eq!(mgr.get_assigned(mask(0), None).assigned.unwrap().0, 1);
+        assert_eq!(mgr.stats(&mask(0)).cooldowns, 0);
+        assert_eq!(
+            mgr.get_assigned(&mask(0), None, None, Priority::Normal)
+                .assigned
+                .unwrap()
+                .0,
+            1
+        );
 
         std::thread::sleep(Duration::from_millis(20));
-        let result = mgr.get_assigned(mask(0), None);
+        let result = mgr.get_assigned(&mask(0), None, None, Priority::Normal);
         assert_eq!(result.stats.cooldowns, 0);
     }
 
@@ -856,11 +2072,23 @@ mod tests {
         let mut mgr = PerCredMgr::new(3);
         mgr.add_credential(1, MockPerCredResource(false), caps_for(&[0, 1, 2]));
 
-        mgr.report_rate_limit(1, mask(0), Duration::from_mins(1));
-
-        assert!(mgr.get_assigned(mask(0), None).assigned.is_none());
-        assert!(mgr.get_assigned(mask(1), None).assigned.is_none());
-        assert!(mgr.get_assigned(mask(2), None).assigned.is_none());
+        mgr.report_rate_limit(1, &mask(0), Duration::from_mins(1));
+
+        assert!(
+            mgr.get_assigned(&mask(0), None, None, Priority::Normal)
+                .assigned
+                .is_none()
+        );
+        assert!(
+            mgr.get_assigned(&mask(1), None, None, Priority::Normal)
+                .assigned
+                .is_none()
+        );
+        assert!(
+            mgr.get_assigned(&mask(2), None, None, Priority::Normal)
+                .assigned
+                .is_none()
+        );
     }
 
     #[test]
@@ -868,13 +2096,29 @@ mod tests {
         let mut mgr = PerCredMgr::new(2);
         mgr.add_credential(1, MockPerCredResource(false), caps_for(&[0, 1]));
 
-        mgr.report_rate_limit(1, mask(0), Duration::from_millis(10));
-        assert!(mgr.get_assigned(mask(0), None).assigned.is_none());
-        assert!(mgr.get_assigned(mask(1), None).assigned.is_none());
+        mgr.report_rate_limit(1, &mask(0), Duration::from_millis(10));
+        assert!(
+            mgr.get_assigned(&mask(0), None, None, Priority::Normal)
+                .assigned
+                .is_none()
+        );
+        assert!(
+            mgr.get_assigned(&mask(1), None, None, Priority::Normal)
+                .assigned
+                .is_none()
+        );
 
         std::thread::sleep(Duration::from_millis(20));
-        assert!(mgr.get_assigned(mask(0), None).assigned.is_some());
-        assert!(mgr.get_assigned(mask(1), None).assigned.is_some());
+        assert!(
+            mgr.get_assigned(&mask(0), None, None, Priority::Normal)
+                .assigned
+                .is_some()
+        );
+        assert!(
+            mgr.get_assigned(&mask(1), None, None, Priority::Normal)
+                .assigned
+                .is_some()
+        );
     }
 
     #[test]
@@ -883,8 +2127,54 @@ mod tests {
         mgr.add_credential(1, MockPerCredResource(false), caps_for(&[0]));
         mgr.add_credential(2, MockPerCredResource(false), caps_for(&[0]));
 
-        mgr.report_rate_limit(1, mask(0), Duration::from_mins(1));
-        assert_eq!(mgr.get_assigned(mask(0), None).assigned.unwrap().0, 2);
+        mgr.report_rate_limit(1, &mask(0), Duration::from_mins(1));
+        assert_eq!(
+            mgr.get_assigned(&mask(0), None, None, Priority::Normal)
+                .assigned
+                .unwrap()
+                .0,
+            2
+        );
+    }
+
+    #[test]
+    fn exhausted_quota_cools_every_model_without_counting_a_failure() {
+        let mut mgr = PerCredMgr::new(2);
+        mgr.add_credential(1, MockPerCredResource(false), caps_for(&[0, 1]));
+        mgr.add_credential(2, MockPerCredResource(false), caps_for(&[0, 1]));
+
+        mgr.report_quota(2, 35, None);
+        mgr.report_quota(1, 0, Some(Duration::from_mins(30)));
+
+        let snap = mgr.runtime_snapshot();
+        assert_eq!(snap[&1].quota_remaining, Some(0));
+        assert_eq!(snap[&1].cooldowns.len(), 2);
+        assert_eq!(snap[&2].quota_remaining, Some(35));
+        assert!(snap[&2].cooldowns.is_empty());
+        assert!(mgr.creds[&1].feedback[0].error_rate.abs() < f64::EPSILON);
+        for model in 0..2 {
+            let lease = mgr
+                .get_assigned(&mask(model), None, None, Priority::Normal)
+                .assigned
+                .unwrap();
+            assert_eq!(lease.0, 2);
+            mgr.release(lease.0);
+        }
+    }
+
+    #[test]
+    fn retry_after_reports_shortest_cooldown_for_model() {
+        let mut mgr = Mgr::new(2);
+        mgr.add_credential(1, MockResource(false), caps_for(&[0, 1]));
+        mgr.add_credential(2, MockResource(false), caps_for(&[0, 1]));
+        assert_eq!(mgr.retry_after(&mask(0), None), None);
+
+        mgr.report_rate_limit(1, &mask(0), Duration::from_mins(5));
+        mgr.report_rate_limit(2, &mask(0), Duration::from_secs(30));
+
+        let wait = mgr.retry_after(&mask(0), None).unwrap();
+        assert!(wait <= Duration::from_secs(30) && wait > Duration::from_secs(25));
+        assert_eq!(mgr.retry_after(&mask(1), None), None);
     }
 
     // ── Sticky / route-hit ──────────────────────────────────────────
@@ -894,7 +2184,7 @@ mod tests {
         let mut mgr = Mgr::new(1);
         mgr.add_credential(1, MockResource(false), caps_for(&[0]));
 
-        let result = mgr.get_assigned(mask(0), Some(1));
+        let result = mgr.get_assigned(&mask(0), Some(1), None, Priority::Normal);
         assert!(result.route_hit);
         assert_eq!(result.assigned.unwrap().0, 1);
     }
@@ -905,7 +2195,7 @@ mod tests {
         mgr.add_credential(1, MockResource(true), caps_for(&[0]));
         mgr.add_credential(2, MockResource(false), caps_for(&[0]));
 
-        let result = mgr.get_assigned(mask(0), Some(1));
+        let result = mgr.get_assigned(&mask(0), Some(1), None, Priority::Normal);
         assert!(!result.route_hit);
         assert!(result.refresh_ids.contains(&1));
         assert_eq!(result.assigned.unwrap().0, 2);
@@ -917,9 +2207,9 @@ mod tests {
         mgr.add_credential(1, MockResource(false), caps_for(&[0]));
         mgr.add_credential(2, MockResource(false), caps_for(&[0]));
 
-        mgr.report_rate_limit(1, mask(0), Duration::from_mins(1));
+        mgr.report_rate_limit(1, &mask(0), Duration::from_mins(1));
 
-        let result = mgr.get_assigned(mask(0), Some(1));
+        let result = mgr.get_assigned(&mask(0), Some(1), None, Priority::Normal);
         assert!(!result.route_hit);
         assert_eq!(result.assigned.unwrap().0, 2);
     }
@@ -931,7 +2221,7 @@ mod tests {
         mgr.add_credential(2, MockResource(false), caps_for(&[0]));
         mgr.mark_refreshing(1);
 
-        let result = mgr.get_assigned(mask(0), Some(1));
+        let result = mgr.get_assigned(&mask(0), Some(1), None, Priority::Normal);
         assert!(!result.route_hit);
         assert!(!result.refresh_ids.contains(&1));
         assert_eq!(result.assigned.unwrap().0, 2);
@@ -942,7 +2232,7 @@ mod tests {
         let mut mgr = Mgr::new(1);
         mgr.add_credential(2, MockResource(false), caps_for(&[0]));
 
-        let result = mgr.get_assigned(mask(0), Some(999));
+        let result = mgr.get_assigned(&mask(0), Some(999), None, Priority::Normal);
         assert!(!result.route_hit);
         assert_eq!(result.assigned.unwrap().0, 2);
     }
@@ -953,7 +2243,7 @@ mod tests {
         mgr.add_credential(1, MockResource(false), caps_for(&[0]));
         mgr.add_credential(2, MockResource(false), caps_for(&[1]));
 
-        let result = mgr.get_assigned(mask(1), Some(1));
+        let result = mgr.get_assigned(&mask(1), Some(1), None, Priority::Normal);
         assert!(!result.route_hit);
         assert_eq!(result.assigned.unwrap().0, 2);
     }
@@ -966,8 +2256,478 @@ mod tests {
         mgr.add_credential(1, MockResource(false), caps_for(&[0, 1]));
         mgr.add_credential(2, MockResource(false), caps_for(&[0]));
 
-        assert_eq!(mgr.stats(mask(0)).queue_len, 2);
-        assert_eq!(mgr.stats(mask(0)).total_creds, 2);
-        assert_eq!(mgr.stats(mask(1)).queue_len, 1);
+        assert_eq!(mgr.stats(&mask(0)).queue_len, 2);
+        assert_eq!(mgr.stats(&mask(0)).total_creds, 2);
+        assert_eq!(mgr.stats(&mask(1)).queue_len, 1);
+    }
+
+    // ── Auto-disable ────────────────────────────────────────────────
+
+    fn auto_disable(window: usize) -> AutoDisableConfig {
+        AutoDisableConfig {
+            window,
+            min_success_rate: 0.5,
+        }
+    }
+
+    #[test]
+    fn low_success_rate_disables_only_that_model() {
+        let mut mgr = Mgr::new(2).with_auto_disable(Some(auto_disable(4)));
+        mgr.add_credential(1, MockResource(false), caps_for(&[0, 1]));
+
+        assert_eq!(mgr.report_outcome(1, &mask(0), true), None);
+        assert_eq!(mgr.report_outcome(1, &mask(0), false), None);
+        assert_eq!(mgr.report_outcome(1, &mask(0), false), None);
+        assert_eq!(mgr.report_outcome(1, &mask(0), false), Some(0.25));
+
+        assert!(
+            mgr.get_assigned(&mask(0), None, None, Priority::Normal)
+                .assigned
+                .is_none()
+        );
+        assert!(
+            mgr.get_assigned(&mask(1), None, None, Priority::Normal)
+                .assigned
+                .is_some()
+        );
+    }
+
+    #[test]
+    fn outcomes_are_ignored_without_policy() {
+        let mut mgr = Mgr::new(1);
+        mgr.add_credential(1, MockResource(false), all_caps());
+        for _ in 0..100 {
+            assert_eq!(mgr.report_outcome(1, &mask(0), false), None);
+        }
+        assert!(
+            mgr.get_assigned(&mask(0), None, None, Priority::Normal)
+                .assigned
+                .is_some()
+        );
+    }
+
+    #[test]
+    fn admin_override_reenables_and_pins_model() {
+        let mut mgr = Mgr::new(1).with_auto_disable(Some(auto_disable(1)));
+        mgr.add_credential(1, MockResource(false), all_caps());
+        assert!(mgr.report_outcome(1, &mask(0), false).is_some());
+        assert!(
+            mgr.get_assigned(&mask(0), None, None, Priority::Normal)
+                .assigned
+                .is_none()
+        );
+
+        let (before, after) = mgr.set_model_override(1, &mask(0), true).unwrap();
+        assert!(!before.supports(0) && after.supports(0));
+        assert_eq!(mgr.report_outcome(1, &mask(0), false), None);
+        assert_eq!(
+            mgr.get_assigned(&mask(0), None, None, Priority::Normal)
+                .assigned
+                .unwrap()
+                .0,
+            1
+        );
+
+        mgr.set_model_override(1, &mask(0), false).unwrap();
+        assert!(
+            mgr.get_assigned(&mask(0), None, None, Priority::Normal)
+                .assigned
+                .is_none()
+        );
+        assert!(mgr.set_model_override(9, &mask(0), true).is_none());
+    }
+
+    // ── Health ──────────────────────────────────────────────────────
+
+    fn health(action: HealthAction) -> HealthConfig {
+        HealthConfig {
+            min_score: 50,
+            action,
+            park_secs: 600,
+        }
+    }
+
+    #[test]
+    fn health_score_tracks_consecutive_failures_without_policy() {
+        let mut mgr = Mgr::new(1);
+        mgr.add_credential(1, MockResource(false), all_caps());
+        for event in [
+            HealthEvent::Forbidden,
+            HealthEvent::ServerError,
+            HealthEvent::RefreshFailed,
+            HealthEvent::RefreshFailed,
+        ] {
+            assert_eq!(mgr.record_health(1, event), None);
+        }
+        let health = mgr.runtime_snapshot()[&1].health;
+        assert_eq!(health.score(), 0);
+
+        mgr.record_health(1, HealthEvent::Success);
+        assert_eq!(mgr.runtime_snapshot()[&1].health.score(), 50);
+        mgr.record_health(1, HealthEvent::Refreshed);
+        assert_eq!(
+            mgr.runtime_snapshot()[&1].health,
+            CredentialHealth::default()
+        );
+        assert_eq!(mgr.record_health(9, HealthEvent::Forbidden), None);
+    }
+
+    #[test]
+    fn low_health_parks_every_model_and_resets_the_score() {
+        let mut mgr = Mgr::new(2).with_health(Some(health(HealthAction::Park)));
+        mgr.add_credential(1, MockResource(false), all_caps());
+
+        for _ in 0..5 {
+            assert_eq!(mgr.record_health(1, HealthEvent::ServerError), None);
+        }
+        assert_eq!(
+            mgr.record_health(1, HealthEvent::ServerError),
+            Some(HealthVerdict::Parked {
+                score: 40,
+                duration: Duration::from_mins(10),
+            })
+        );
+        for model in 0..2 {
+            assert!(
+                mgr.get_assigned(&mask(model), None, None, Priority::Normal)
+                    .assigned
+                    .is_none()
+            );
+        }
+        let runtime = &mgr.runtime_snapshot()[&1];
+        assert_eq!(runtime.health.score(), 100);
+        assert_eq!(runtime.cooldowns.len(), 2);
+    }
+
+    #[test]
+    fn low_health_asks_for_a_ban_when_configured() {
+        let mut mgr = Mgr::new(1).with_health(Some(health(HealthAction::Ban)));
+        mgr.add_credential(1, MockResource(false), all_caps());
+
+        assert_eq!(mgr.record_health(1, HealthEvent::Forbidden), None);
+        assert_eq!(mgr.record_health(1, HealthEvent::ServerError), None);
+        assert_eq!(
+            mgr.record_health(1, HealthEvent::Forbidden),
+            Some(HealthVerdict::Ban { score: 10 })
+        );
+        assert!(
+            mgr.get_assigned(&mask(0), None, None, Priority::Normal)
+                .assigned
+                .is_some()
+        );
+    }
+
+    #[test]
+    fn manual_cooldown_is_imposed_and_lifted_without_a_failure() {
+        let mut mgr = Mgr::new(2);
+        mgr.add_credential(1, MockResource(false), all_caps());
+
+        assert!(mgr.set_manual_cooldown(1, &mask(0), Some(Duration::from_mins(10))));
+        assert!(
+            mgr.get_assigned(&mask(0), None, None, Priority::Normal)
+                .assigned
+                .is_none()
+        );
+        assert!(
+            mgr.get_assigned(&mask(1), None, None, Priority::Normal)
+                .assigned
+                .is_some()
+        );
+        assert!(mgr.retry_after(&mask(0), None).is_some());
+
+        assert!(mgr.set_manual_cooldown(1, &mask(0), None));
+        assert_eq!(
+            mgr.get_assigned(&mask(0), None, None, Priority::Normal)
+                .assigned
+                .unwrap()
+                .0,
+            1
+        );
+        assert!(mgr.runtime_snapshot()[&1].cooldowns.is_empty());
+        assert!(!mgr.set_manual_cooldown(9, &mask(0), None));
+    }
+
+    #[test]
+    fn set_capabilities_replaces_mask_and_requeues() {
+        let mut mgr = Mgr::new(2);
+        mgr.add_credential(1, MockResource(false), caps_for(&[0]));
+
+        let (before, after) = mgr.set_capabilities(1, &caps_for(&[1])).unwrap();
+        assert!(before.supports(0) && !before.supports(1));
+        assert!(!after.supports(0) && after.supports(1));
+        assert!(
+            mgr.get_assigned(&mask(0), None, None, Priority::Normal)
+                .assigned
+                .is_none()
+        );
+        assert!(
+            mgr.get_assigned(&mask(1), None, None, Priority::Normal)
+                .assigned
+                .is_some()
+        );
+        assert!(mgr.set_capabilities(9, &all_caps()).is_none());
+    }
+
+    #[test]
+    fn priority_reserve_keeps_last_credentials_for_high_priority() {
+        let mut mgr = Mgr::new(1).with_priority_reserve(0.5);
+        mgr.add_credential(1, MockResource(false), all_caps());
+        mgr.add_credential(2, MockResource(false), all_caps());
+
+        assert!(
+            mgr.get_assigned(&mask(0), None, None, Priority::Low)
+                .assigned
+                .is_some()
+        );
+        mgr.report_rate_limit(1, &mask(0), Duration::from_mins(1));
+
+        let result = mgr.get_assigned(&mask(0), None, None, Priority::Normal);
+        assert!(result.assigned.is_none());
+        assert!(result.stats.held_in_reserve);
+        assert_eq!(
+            mgr.get_assigned(&mask(0), None, None, Priority::High)
+                .assigned
+                .unwrap()
+                .0,
+            2
+        );
+    }
+
+    #[test]
+    fn tier_weights_split_traffic_and_keep_zero_weight_as_overflow() {
+        let weights = [("pro", 3), ("plus", 1), ("free", 0)]
+            .into_iter()
+            .map(|(tier, w)| (tier.to_string(), w))
+            .collect();
+        let mut mgr = ResourceScheduler::new(1).with_tier_weights(weights);
+        mgr.add_credential(1, MockTieredResource("pro"), all_caps());
+        mgr.add_credential(2, MockTieredResource("plus"), all_caps());
+        mgr.add_credential(3, MockTieredResource("free"), all_caps());
+
+        let mut picks = HashMap::<CredentialId, usize>::new();
+        for _ in 0..8 {
+            let lease = mgr
+                .get_assigned(&mask(0), None, None, Priority::Normal)
+                .assigned
+                .unwrap();
+            *picks.entry(lease.0).or_default() += 1;
+        }
+        assert_eq!(picks.get(&1), Some(&6));
+        assert_eq!(picks.get(&2), Some(&2));
+        assert_eq!(picks.get(&3), None);
+
+        mgr.report_rate_limit(1, &mask(0), Duration::from_secs(30));
+        mgr.report_rate_limit(2, &mask(0), Duration::from_secs(30));
+        assert_eq!(
+            mgr.get_assigned(&mask(0), None, None, Priority::Normal)
+                .assigned
+                .unwrap()
+                .0,
+            3
+        );
+    }
+
+    #[test]
+    fn least_latency_prefers_unsampled_then_fastest_credential() {
+        let mut mgr = Mgr::new(1).with_policy(SchedulingPolicy::LeastLatency);
+        mgr.add_credential(1, MockResource(false), all_caps());
+        mgr.add_credential(2, MockResource(false), all_caps());
+        mgr.add_credential(3, MockResource(false), all_caps());
+
+        mgr.report_success(1, &mask(0), Duration::from_millis(300));
+        mgr.report_success(2, &mask(0), Duration::from_millis(100));
+
+        assert_eq!(
+            mgr.get_assigned(&mask(0), None, None, Priority::Normal)
+                .assigned
+                .unwrap()
+                .0,
+            3
+        );
+        mgr.report_success(3, &mask(0), Duration::from_millis(500));
+        for _ in 0..3 {
+            assert_eq!(
+                mgr.get_assigned(&mask(0), None, None, Priority::Normal)
+                    .assigned
+                    .unwrap()
+                    .0,
+                2
+            );
+        }
+
+        mgr.report_rate_limit(2, &mask(0), Duration::from_secs(30));
+        assert_eq!(
+            mgr.get_assigned(&mask(0), None, None, Priority::Normal)
+                .assigned
+                .unwrap()
+                .0,
+            1
+        );
+    }
+
+    #[test]
+    fn least_recently_used_spreads_leases_across_models() {
+        let mut mgr = Mgr::new(2).with_policy(SchedulingPolicy::LeastRecentlyUsed);
+        mgr.add_credential(1, MockResource(false), all_caps());
+        mgr.add_credential(2, MockResource(false), all_caps());
+
+        assert_eq!(
+            mgr.get_assigned(&mask(0), None, None, Priority::Normal)
+                .assigned
+                .unwrap()
+                .0,
+            1
+        );
+        // Round-robin would start model 1 on credential 1 again.
+        assert_eq!(
+            mgr.get_assigned(&mask(1), None, None, Priority::Normal)
+                .assigned
+                .unwrap()
+                .0,
+            2
+        );
+        assert_eq!(
+            mgr.get_assigned(&mask(1), None, None, Priority::Normal)
+                .assigned
+                .unwrap()
+                .0,
+            1
+        );
+    }
+
+    #[test]
+    fn p2c_latency_never_picks_the_slowest_of_three() {
+        let mut mgr = Mgr::new(1).with_policy(SchedulingPolicy::P2cLatency);
+        for id in 1..=3 {
+            mgr.add_credential(id, MockResource(false), all_caps());
+        }
+        mgr.report_success(1, &mask(0), Duration::from_millis(100));
+        mgr.report_success(2, &mask(0), Duration::from_millis(200));
+        mgr.report_success(3, &mask(0), Duration::from_millis(900));
+
+        for _ in 0..50 {
+            let lease = mgr
+                .get_assigned(&mask(0), None, None, Priority::Normal)
+                .assigned
+                .unwrap();
+            assert_ne!(lease.0, 3);
+            mgr.release(lease.0);
+        }
+    }
+
+    #[test]
+    fn least_errors_prefers_healthiest_credential_and_rotates_ties() {
+        let mut mgr = Mgr::new(1).with_policy(SchedulingPolicy::LeastErrors);
+        mgr.add_credential(1, MockResource(false), all_caps());
+        mgr.add_credential(2, MockResource(false), all_caps());
+        mgr.add_credential(3, MockResource(false), all_caps());
+
+        let picks: Vec<_> = (0..3)
+            .map(|_| {
+                mgr.get_assigned(&mask(0), None, None, Priority::Normal)
+                    .assigned
+                    .unwrap()
+                    .0
+            })
+            .collect();
+        assert_eq!(picks, [1, 2, 3]);
+
+        assert_eq!(mgr.report_outcome(1, &mask(0), false), None);
+        mgr.report_outcome(2, &mask(0), false);
+        mgr.report_outcome(2, &mask(0), false);
+        for _ in 0..3 {
+            assert_eq!(
+                mgr.get_assigned(&mask(0), None, None, Priority::Normal)
+                    .assigned
+                    .unwrap()
+                    .0,
+                3
+            );
+        }
+
+        mgr.report_outcome(3, &mask(0), false);
+        mgr.report_outcome(3, &mask(0), false);
+        mgr.report_outcome(3, &mask(0), false);
+        assert_eq!(
+            mgr.get_assigned(&mask(0), None, None, Priority::Normal)
+                .assigned
+                .unwrap()
+                .0,
+            1
+        );
+    }
+
+    #[test]
+    fn credentials_at_max_concurrency_are_skipped_until_released() {
+        let mut mgr = Mgr::new(1).with_max_concurrent(Some(1));
+        mgr.add_credential(1, MockResource(false), all_caps());
+        mgr.add_credential(2, MockResource(false), all_caps());
+
+        assert_eq!(
+            mgr.get_assigned(&mask(0), Some(1), None, Priority::Normal)
+                .assigned
+                .unwrap()
+                .0,
+            1
+        );
+        // Sticky hint on a busy credential falls back to the queue.
+        assert_eq!(
+            mgr.get_assigned(&mask(0), Some(1), None, Priority::Normal)
+                .assigned
+                .unwrap()
+                .0,
+            2
+        );
+        let result = mgr.get_assigned(&mask(0), None, None, Priority::Normal);
+        assert!(result.assigned.is_none());
+        assert_eq!(result.stats.skipped_busy, 2);
+        assert_eq!(mgr.runtime_snapshot()[&1].in_flight, 1);
+
+        mgr.release(2);
+        assert_eq!(
+            mgr.get_assigned(&mask(0), None, None, Priority::Normal)
+                .assigned
+                .unwrap()
+                .0,
+            2
+        );
+        mgr.release(1);
+        mgr.release(1);
+        assert_eq!(mgr.runtime_snapshot()[&1].in_flight, 0);
+        assert_eq!(
+            mgr.get_assigned(&mask(0), None, None, Priority::Normal)
+                .assigned
+                .unwrap()
+                .0,
+            1
+        );
+    }
+
+    #[test]
+    fn pool_limits_assignment_to_labeled_credentials() {
+        let mut mgr = ResourceScheduler::new(1);
+        mgr.add_credential(1, MockLabeledResource(Vec::new()), all_caps());
+        mgr.add_credential(2, MockLabeledResource(vec!["team-a".into()]), all_caps());
+
+        for _ in 0..3 {
+            let result = mgr.get_assigned(&mask(0), Some(1), Some("team-a"), Priority::Normal);
+            assert_eq!(result.assigned.unwrap().0, 2);
+        }
+        let result = mgr.get_assigned(&mask(0), None, Some("team-b"), Priority::Normal);
+        assert!(result.assigned.is_none());
+        assert_eq!(result.stats.skipped_other_pool, 2);
+
+        // Unpinned requests still rotate over every credential.
+        let first = mgr
+            .get_assigned(&mask(0), None, None, Priority::Normal)
+            .assigned
+            .unwrap()
+            .0;
+        let second = mgr
+            .get_assigned(&mask(0), None, None, Priority::Normal)
+            .assigned
+            .unwrap()
+            .0;
+        assert_ne!(first, second);
     }
 }
@@ -0,0 +1,220 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use crate::config::Priority;
+use crate::model_catalog::ModelCapabilities;
+use ractor::RpcReplyPort;
+use tracing::warn;
+
+/// Upper bound on parked `GetCredential` calls per actor; beyond it callers
+/// get `None` straight away.
+const MAX_WAITERS: usize = 512;
+
+/// A `GetCredential` call parked until a credential frees up.
+pub(crate) struct Waiter<L> {
+    pub model_mask: ModelCapabilities,
+    pub route_key: Option<u64>,
+    pub pool: Option<String>,
+    pub priority: Priority,
+    pub reply: RpcReplyPort<Option<L>>,
+    deadline: Instant,
+}
+
+/// Callers waiting for a lease (`providers.*.lease_wait_ms`), highest
+/// [`Priority`] first and FIFO within a class.
+///
+/// The owning actor retries the queue in that order whenever a credential
+/// may have become available: a cooldown expiring, a refresh completing, a
+/// lease being released. Waiters still unserved at their deadline get `None`.
+pub(crate) struct LeaseWaiters<L> {
+    queue: VecDeque<Waiter<L>>,
+    wait: Duration,
+    /// Earliest wake-up already scheduled, to avoid piling up timers.
+    wake_at: Option<Instant>,
+}
+
+impl<L> LeaseWaiters<L> {
+    pub fn new(wait: Duration) -> Self {
+        Self {
+            queue: VecDeque::new(),
+            wait,
+            wake_at: None,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.queue.is_empty()
+    }
+
+    /// Park a caller behind everyone of its priority or higher; hands the
+    /// reply port back when waiting is disabled or the queue is full.
+    pub fn park(
+        &mut self,
+        model_mask: ModelCapabilities,
+        route_key: Option<u64>,
+        pool: Option<String>,
+        priority: Priority,
+        reply: RpcReplyPort<Option<L>>,
+    ) -> Result<(), RpcReplyPort<Option<L>>> {
+        if self.wait.is_zero() || self.queue.len() >= MAX_WAITERS {
+            return Err(reply);
+        }
+        let at = self
+            .queue
+            .iter()
+            .position(|w| w.priority > priority)
+            .unwrap_or(self.queue.len());
+        self.queue.insert(
+            at,
+            Waiter {
+                model_mask,
+                route_key,
+                pool,
+                priority,
+                reply,
+                deadline: Instant::now() + self.wait,
+            },
+        );
+        Ok(())
+    }
+
+    /// Remove every waiter for a retry pass, answering `None` to the ones
+    /// past their deadline and dropping the ones whose caller went away.
+    pub fn take_live(&mut self, now: Instant) -> Vec<Waiter<L>> {
+        let mut live = Vec::with_capacity(self.queue.len());
+        let mut expired = 0usize;
+        for waiter in self.queue.drain(..) {
+            if waiter.reply.is_closed() {
+                continue;
+            }
+            if waiter.deadline <= now {
+                expired += 1;
+                let _ = waiter.reply.send(None);
+                continue;
+            }
+            live.push(waiter);
+        }
+        if expired > 0 {
+            warn!(
+                expired,
+                "No credential became available within lease_wait_ms"
+            );
+        }
+        live
+    }
+
+    /// Put an unserved waiter back, keeping its place in line.
+    pub fn requeue(&mut self, waiter: Waiter<L>) {
+        self.queue.push_back(waiter);
+    }
+
+    /// Delay until the next retry pass, if one is needed and not already
+    /// scheduled. `next_event` is the earliest time a credential could free
+    /// up on its own (e.g. a cooldown ending).
+    pub fn schedule_wake(&mut self, now: Instant, next_event: Option<Instant>) -> Option<Duration> {
+        let deadline = self.queue.iter().map(|w| w.deadline).min()?;
+        let at = next_event.map_or(deadline, |event| event.min(deadline));
+        if self
+            .wake_at
+            .is_some_and(|pending| pending > now && pending <= at)
+        {
+            return None;
+        }
+        self.wake_at = Some(at);
+        Some(at.saturating_duration_since(now))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ractor::concurrency::oneshot;
+
+    #[test]
+    fn expired_waiters_get_none_and_the_rest_keep_their_order() {
+        let mut waiters = LeaseWaiters::<u64>::new(Duration::from_secs(5));
+        let (tx_a, mut rx_a) = oneshot();
+        let (tx_b, _rx_b) = oneshot();
+        waiters
+            .park(
+                ModelCapabilities::none(),
+                Some(1),
+                None,
+                Priority::Normal,
+                tx_a.into(),
+            )
+            .unwrap();
+        waiters
+            .park(
+                ModelCapabilities::none(),
+                Some(2),
+                None,
+                Priority::Normal,
+                tx_b.into(),
+            )
+            .unwrap();
+
+        let now = Instant::now();
+        let live = waiters.take_live(now);
+        assert_eq!(
+            live.iter().map(|w| w.route_key).collect::<Vec<_>>(),
+            [Some(1), Some(2)]
+        );
+        for waiter in live {
+            waiters.requeue(waiter);
+        }
+        assert!(waiters.schedule_wake(now, None).is_some());
+        assert!(waiters.schedule_wake(now, None).is_none());
+
+        assert!(waiters.take_live(now + Duration::from_secs(6)).is_empty());
+        assert_eq!(rx_a.try_recv().unwrap(), None);
+        assert!(waiters.is_empty());
+    }
+
+    #[test]
+    fn higher_priority_waiters_jump_the_queue() {
+        let mut waiters = LeaseWaiters::<u64>::new(Duration::from_secs(5));
+        let mut receivers = Vec::new();
+        for (key, priority) in [
+            (1, Priority::Low),
+            (2, Priority::Normal),
+            (3, Priority::High),
+            (4, Priority::Normal),
+            (5, Priority::High),
+        ] {
+            let (tx, rx) = oneshot();
+            receivers.push(rx);
+            waiters
+                .park(
+                    ModelCapabilities::none(),
+                    Some(key),
+                    None,
+                    priority,
+                    tx.into(),
+                )
+                .unwrap();
+        }
+        let order: Vec<_> = waiters
+            .take_live(Instant::now())
+            .iter()
+            .map(|w| w.route_key)
+            .collect();
+        assert_eq!(order, [Some(3), Some(5), Some(2), Some(4), Some(1)]);
+    }
+
+    #[test]
+    fn callers_are_not_parked_when_waiting_is_disabled() {
+        let mut off = LeaseWaiters::<u64>::new(Duration::ZERO);
+        let (tx, _rx) = oneshot();
+        assert!(
+            off.park(
+                ModelCapabilities::none(),
+                None,
+                None,
+                Priority::Normal,
+                tx.into()
+            )
+            .is_err()
+        );
+    }
+}
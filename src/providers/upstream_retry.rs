@@ -6,6 +6,7 @@ use std::time::Duration;
 use url::Url;
 
 use crate::providers::UPSTREAM_BODY_PREVIEW_CHARS;
+use crate::utils::logging::body_preview;
 
 static NETWORK_RETRY_POLICY: LazyLock<ExponentialBuilder> = LazyLock::new(|| {
     ExponentialBuilder::default()
@@ -44,10 +45,10 @@ pub(crate) async fn post_json_bytes_with_retry(
                 let err = resp.error_for_status_ref().unwrap_err();
 
                 let body_preview = match resp.bytes().await {
-                    Ok(bytes) => {
-                        let raw_body = String::from_utf8_lossy(&bytes);
-                        format!("{raw_body:.UPSTREAM_BODY_PREVIEW_CHARS$}")
-                    }
+                    Ok(bytes) => body_preview(
+                        &String::from_utf8_lossy(&bytes),
+                        UPSTREAM_BODY_PREVIEW_CHARS,
+                    ),
                     Err(e) => format!("<failed to read body: {e}>"),
                 };
 
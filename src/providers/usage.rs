@@ -0,0 +1,286 @@
+//! Per-request usage accounting.
+//!
+//! A [`UsageTracker`] is created by a route handler and shared (by clone) with
+//! the response path. Token counts are picked up from upstream `usageMetadata`
+//! (Gemini) or `usage` (`OpenAI`, Anthropic) as they pass through, and a single row is
+//! written to the `usage` table once the last clone is dropped — for streams
+//! that is when the client stream ends.
+
+use crate::db::{DbActorHandle, UsageRecord};
+use crate::providers::HeldLease;
+use crate::providers::quota::DailyQuota;
+use crate::providers::traits::scheduler::CredentialId;
+use crate::server::audit_log::{AUDIT_SCOPE, AuditRecord, AuditScope};
+use crate::server::guards::rate_limit::{TOKEN_BUDGET, TokenBudget};
+use axum::http::StatusCode;
+use chrono::Utc;
+use serde_json::Value;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+/// Attached to successful upstream responses (`reqwest::Response::extensions`)
+/// so callers can tell which credential served the request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LeasedCredential(pub CredentialId);
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+struct TokenCounts {
+    prompt: i64,
+    output: i64,
+}
+
+impl TokenCounts {
+    /// Gemini `usageMetadata`; thinking tokens are billed as output.
+    fn from_gemini(usage: &Value) -> Option<Self> {
+        let field = |name: &str| usage.get(name).and_then(Value::as_i64);
+        let prompt = field("promptTokenCount");
+        let candidates = field("candidatesTokenCount");
+        let thoughts = field("thoughtsTokenCount");
+        if prompt.is_none() && candidates.is_none() && thoughts.is_none() {
+            return None;
+        }
+        Some(Self {
+            prompt: prompt.unwrap_or(0),
+            output: candidates.unwrap_or(0) + thoughts.unwrap_or(0),
+        })
+    }
+
+    /// `OpenAI` `usage` (Responses `input/output_tokens` or Chat `prompt/completion_tokens`).
+    fn from_openai(usage: &Value) -> Option<Self> {
+        let field = |a: &str, b: &str| {
+            usage
+                .get(a)
+                .or_else(|| usage.get(b))
+                .and_then(Value::as_i64)
+        };
+        let prompt = field("input_tokens", "prompt_tokens");
+        let output = field("output_tokens", "completion_tokens");
+        if prompt.is_none() && output.is_none() {
+            return None;
+        }
+        Some(Self {
+            prompt: prompt.unwrap_or(0),
+            output: output.unwrap_or(0),
+        })
+    }
+}
+
+/// Anthropic `usage`, split across stream events: `message_start` carries the
+/// input counts and `message_delta` the running output count.
+fn merge_anthropic(tokens: &mut TokenCounts, usage: &Value) {
+    let field = |name: &str| usage.get(name).and_then(Value::as_i64);
+    let input = [
+        "input_tokens",
+        "cache_creation_input_tokens",
+        "cache_read_input_tokens",
+    ]
+    .into_iter()
+    .filter_map(field)
+    .reduce(i64::saturating_add);
+    if let Some(input) = input.filter(|&n| n > 0) {
+        tokens.prompt = input;
+    }
+    if let Some(output) = field("output_tokens") {
+        tokens.output = output;
+    }
+}
+
+struct UsageEntry {
+    db: DbActorHandle,
+    provider: &'static str,
+    model: String,
+    start: Instant,
+    credential_id: Option<CredentialId>,
+    /// Released with the entry, i.e. when the response (or stream) is done.
+    lease: Option<HeldLease>,
+    tokens: TokenCounts,
+    status: StatusCode,
+    /// Client key's per-minute budget, charged with the final counts.
+    budget: Option<TokenBudget>,
+    /// Set when the audit log is on; gets one record with the final counts.
+    audit: Option<AuditScope>,
+    /// Provider's daily quota, charged unless upstream rate-limited the request.
+    quota: Option<DailyQuota>,
+}
+
+impl Drop for UsageEntry {
+    fn drop(&mut self) {
+        let latency_ms = i64::try_from(self.start.elapsed().as_millis()).unwrap_or(i64::MAX);
+        if let Some(budget) = &self.budget {
+            let spent = self.tokens.prompt.saturating_add(self.tokens.output);
+            budget.charge(u64::try_from(spent).unwrap_or(0));
+        }
+        if let Some(audit) = self.audit.take() {
+            let now = Utc::now();
+            let elapsed = (now - audit.started_at).num_milliseconds();
+            audit.log.record(AuditRecord {
+                ts: now,
+                request_id: audit.request_id.clone(),
+                provider: self.provider,
+                model: self.model.clone(),
+                request_hash: audit.request_hash(),
+                credential_id: self.credential_id,
+                key_fingerprint: audit.key_fingerprint.clone(),
+                status: self.status.as_u16(),
+                latency_ms: u64::try_from(elapsed).unwrap_or(0),
+                retries: audit.retries(),
+                prompt_tokens: self.tokens.prompt,
+                output_tokens: self.tokens.output,
+            });
+        }
+        if let (Some(quota), Some(id)) = (&self.quota, self.credential_id)
+            && self.status != StatusCode::TOO_MANY_REQUESTS
+        {
+            let spent = self.tokens.prompt.saturating_add(self.tokens.output);
+            quota.record(id, &self.model, u64::try_from(spent).unwrap_or(0));
+        }
+        self.db.record_usage(UsageRecord {
+            created_at: Utc::now(),
+            provider: self.provider.to_string(),
+            model: std::mem::take(&mut self.model),
+            credential_id: self.credential_id.map(u64::cast_signed),
+            prompt_tokens: self.tokens.prompt,
+            output_tokens: self.tokens.output,
+            latency_ms,
+            status: i32::from(self.status.as_u16()),
+        });
+    }
+}
+
+/// Cheap-to-clone handle; the usage row is written when the last clone drops.
+#[derive(Clone)]
+pub struct UsageTracker {
+    entry: Arc<Mutex<UsageEntry>>,
+}
+
+impl UsageTracker {
+    pub fn start(db: DbActorHandle, provider: &'static str, model: &str) -> Self {
+        Self {
+            entry: Arc::new(Mutex::new(UsageEntry {
+                db,
+                provider,
+                model: model.to_string(),
+                start: Instant::now(),
+                credential_id: None,
+                lease: None,
+                tokens: TokenCounts::default(),
+                // Overwritten by the handler; only seen if it panics first.
+                status: StatusCode::INTERNAL_SERVER_ERROR,
+                budget: TOKEN_BUDGET.try_with(Clone::clone).ok(),
+                audit: AUDIT_SCOPE.try_with(Clone::clone).ok(),
+                quota: None,
+            })),
+        }
+    }
+
+    /// Count the request against `quota` once it completes.
+    #[must_use]
+    pub fn with_quota(self, quota: Option<DailyQuota>) -> Self {
+        self.with_entry(|e| e.quota = quota);
+        self
+    }
+
+    fn with_entry(&self, f: impl FnOnce(&mut UsageEntry)) {
+        if let Ok(mut entry) = self.entry.lock() {
+            f(&mut entry);
+        }
+    }
+
+    /// Pick up the serving credential from an upstream response, holding its
+    /// lease until the tracker is dropped.
+    pub fn observe_response(&self, resp: &reqwest::Response) {
+        if let Some(LeasedCredential(id)) = resp.extensions().get::<LeasedCredential>().copied() {
+            self.with_entry(|e| e.credential_id = Some(id));
+        }
+        if let Some(lease) = resp.extensions().get::<HeldLease>().cloned() {
+            self.with_entry(|e| e.lease = Some(lease));
+        }
+    }
+
+    /// Model the request was made for.
+    pub fn model(&self) -> String {
+        self.entry
+            .lock()
+            .map(|e| e.model.clone())
+            .unwrap_or_default()
+    }
+
+    pub fn set_status(&self, status: StatusCode) {
+        self.with_entry(|e| e.status = status);
+    }
+
+    /// Record Gemini `usageMetadata`. Streams repeat cumulative totals, so the
+    /// latest chunk wins.
+    pub fn observe_gemini(&self, usage_metadata: Option<&Value>) {
+        if let Some(tokens) = usage_metadata.and_then(TokenCounts::from_gemini) {
+            self.with_entry(|e| e.tokens = tokens);
+        }
+    }
+
+    /// Record OpenAI-style `usage`.
+    pub fn observe_openai(&self, usage: Option<&Value>) {
+        if let Some(tokens) = usage.and_then(TokenCounts::from_openai) {
+            self.with_entry(|e| e.tokens = tokens);
+        }
+    }
+
+    /// Record Anthropic `usage` from a message or one of its stream events.
+    pub fn observe_anthropic(&self, usage: Option<&Value>) {
+        if let Some(usage) = usage {
+            self.with_entry(|e| merge_anthropic(&mut e.tokens, usage));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{TokenCounts, merge_anthropic};
+    use serde_json::json;
+
+    #[test]
+    fn gemini_usage_counts_thoughts_as_output() {
+        let usage = json!({
+            "promptTokenCount": 12,
+            "candidatesTokenCount": 30,
+            "thoughtsTokenCount": 8,
+            "totalTokenCount": 50
+        });
+        assert_eq!(
+            TokenCounts::from_gemini(&usage),
+            Some(TokenCounts {
+                prompt: 12,
+                output: 38
+            })
+        );
+        assert_eq!(TokenCounts::from_gemini(&json!({})), None);
+    }
+
+    #[test]
+    fn openai_usage_accepts_responses_and_chat_shapes() {
+        let responses = json!({"input_tokens": 5, "output_tokens": 7, "total_tokens": 12});
+        let chat = json!({"prompt_tokens": 5, "completion_tokens": 7});
+        let expected = Some(TokenCounts {
+            prompt: 5,
+            output: 7,
+        });
+        assert_eq!(TokenCounts::from_openai(&responses), expected);
+        assert_eq!(TokenCounts::from_openai(&chat), expected);
+    }
+
+    #[test]
+    fn anthropic_stream_usage_merges_start_and_delta() {
+        let mut tokens = TokenCounts::default();
+        merge_anthropic(
+            &mut tokens,
+            &json!({"input_tokens": 3, "cache_read_input_tokens": 9, "output_tokens": 1}),
+        );
+        merge_anthropic(&mut tokens, &json!({"output_tokens": 42}));
+        assert_eq!(
+            tokens,
+            TokenCounts {
+                prompt: 12,
+                output: 42
+            }
+        );
+    }
+}
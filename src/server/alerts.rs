@@ -0,0 +1,342 @@
+//! Webhook alerts on pool events (`alerts`).
+//!
+//! Provider actors and the database actor raise an [`AlertEvent`] with
+//! [`alert`]; a dedicated actor posts it to `alerts.webhook_url`. Delivery is
+//! rate limited so a flapping pool cannot flood the channel: repeats of the
+//! same alert within `min_interval_secs` are only counted, and reported with
+//! the next one sent, and at most `max_per_minute` alerts go out in total.
+//! Until [`spawn`] has started the actor, [`alert`] does nothing.
+
+use crate::config::{AlertFormat, AlertsConfig};
+use crate::providers::manifest::ProviderKind;
+use crate::providers::traits::scheduler::CredentialId;
+use crate::utils::dns::with_resolver;
+use chrono::Utc;
+use ractor::{Actor, ActorProcessingErr, ActorRef};
+use serde::Serialize;
+use serde_json::{Value, json};
+use std::collections::{HashMap, VecDeque};
+use std::fmt::{self, Write as _};
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+use tracing::{debug, info, warn};
+use url::Url;
+
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Window of `alerts.max_per_minute`.
+const RATE_WINDOW: Duration = Duration::from_mins(1);
+
+/// Past this many alert keys, the ones no longer held back are forgotten.
+const MAX_TRACKED_KEYS: usize = 1024;
+
+static ALERTS: OnceLock<ActorRef<AlertEvent>> = OnceLock::new();
+
+/// Something an operator should hear about.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum AlertEvent {
+    /// Disabled after upstream rejected it or its health score dropped.
+    CredentialBanned {
+        provider: ProviderKind,
+        id: CredentialId,
+        account: String,
+    },
+    /// Disabled because its refresh token no longer works.
+    RefreshFailed {
+        provider: ProviderKind,
+        id: CredentialId,
+        account: String,
+        error: String,
+    },
+    /// A request found no credential for its model.
+    PoolEmpty {
+        provider: ProviderKind,
+        model: String,
+    },
+    DatabaseError {
+        error: String,
+    },
+}
+
+impl AlertEvent {
+    /// What repeats are recognized by: the event and what it is about.
+    fn key(&self) -> String {
+        match self {
+            Self::CredentialBanned { provider, id, .. } => {
+                format!("credential_banned:{}:{id}", provider.label())
+            }
+            Self::RefreshFailed { provider, id, .. } => {
+                format!("refresh_failed:{}:{id}", provider.label())
+            }
+            Self::PoolEmpty { provider, model } => {
+                format!("pool_empty:{}:{model}", provider.label())
+            }
+            Self::DatabaseError { .. } => "database_error".to_string(),
+        }
+    }
+}
+
+impl fmt::Display for AlertEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::CredentialBanned {
+                provider,
+                id,
+                account,
+            } => write!(f, "{} credential {id} ({account}) banned", provider.label()),
+            Self::RefreshFailed {
+                provider,
+                id,
+                account,
+                error,
+            } => write!(
+                f,
+                "{} credential {id} ({account}) disabled, token refresh failed: {error}",
+                provider.label()
+            ),
+            Self::PoolEmpty { provider, model } => {
+                write!(
+                    f,
+                    "{} has no credential available for {model}",
+                    provider.label()
+                )
+            }
+            Self::DatabaseError { error } => write!(f, "database error: {error}"),
+        }
+    }
+}
+
+/// Raise `event`; a no-op while alerts are off.
+pub fn alert(event: AlertEvent) {
+    if let Some(actor) = ALERTS.get() {
+        let _ = actor.cast(event);
+    }
+}
+
+/// Start the alert actor, if `alerts.webhook_url` is set.
+pub async fn spawn(cfg: &AlertsConfig) {
+    let Some(url) = cfg.webhook_url.clone() else {
+        return;
+    };
+    let (actor, _jh) = Actor::spawn(Some("Alerts".to_string()), AlertActor, (cfg.clone(), url))
+        .await
+        .expect("failed to spawn alert actor");
+    if ALERTS.set(actor).is_ok() {
+        info!(format = ?cfg.format, "[Alerts] Webhook alerts enabled");
+    }
+}
+
+/// Repeat suppression and the per-minute cap.
+#[derive(Debug)]
+struct AlertLimiter {
+    min_interval: Duration,
+    max_per_minute: usize,
+    /// Per alert key: when it was last sent, and repeats held back since.
+    repeats: HashMap<String, (Option<Instant>, u32)>,
+    sent: VecDeque<Instant>,
+}
+
+impl AlertLimiter {
+    fn new(cfg: &AlertsConfig) -> Self {
+        Self {
+            min_interval: Duration::from_secs(cfg.min_interval_secs),
+            max_per_minute: usize::try_from(cfg.max_per_minute).unwrap_or(usize::MAX),
+            repeats: HashMap::new(),
+            sent: VecDeque::new(),
+        }
+    }
+
+    /// Whether the alert under `key` may go out at `now`, and if so how many
+    /// of its repeats were held back before it.
+    fn admit(&mut self, key: String, now: Instant) -> Option<u32> {
+        while self
+            .sent
+            .front()
+            .is_some_and(|&at| now.duration_since(at) >= RATE_WINDOW)
+        {
+            self.sent.pop_front();
+        }
+        if self.repeats.len() >= MAX_TRACKED_KEYS {
+            let min_interval = self.min_interval;
+            self.repeats.retain(|_, (last, held)| {
+                *held > 0 || last.is_some_and(|at| now.duration_since(at) < min_interval)
+            });
+        }
+        let (last, held) = self.repeats.entry(key).or_default();
+        if last.is_some_and(|at| now.duration_since(at) < self.min_interval)
+            || self.sent.len() >= self.max_per_minute
+        {
+            *held += 1;
+            return None;
+        }
+        *last = Some(now);
+        self.sent.push_back(now);
+        Some(std::mem::take(held))
+    }
+}
+
+/// Webhook body for `event` in the configured format.
+fn payload(cfg: &AlertsConfig, event: &AlertEvent, held: u32) -> Value {
+    let mut text = format!("[pollux] {event}");
+    if held > 0 {
+        let _ = write!(text, " ({held} repeats held back)");
+    }
+    match cfg.format {
+        AlertFormat::Json => {
+            let mut body = json!(event);
+            body["text"] = json!(text);
+            body["suppressed"] = json!(held);
+            body["at"] = json!(Utc::now());
+            body
+        }
+        AlertFormat::Slack => json!({ "text": text }),
+        AlertFormat::Telegram => json!({ "chat_id": cfg.telegram_chat_id, "text": text }),
+    }
+}
+
+struct AlertActor;
+
+struct AlertState {
+    cfg: AlertsConfig,
+    url: Url,
+    client: reqwest::Client,
+    limiter: AlertLimiter,
+}
+
+#[ractor::async_trait]
+impl Actor for AlertActor {
+    type Msg = AlertEvent;
+    type State = AlertState;
+    type Arguments = (AlertsConfig, Url);
+
+    async fn pre_start(
+        &self,
+        _myself: ActorRef<Self::Msg>,
+        (cfg, url): Self::Arguments,
+    ) -> Result<Self::State, ActorProcessingErr> {
+        let client = with_resolver(reqwest::Client::builder())
+            .timeout(WEBHOOK_TIMEOUT)
+            .build()
+            .map_err(|e| ActorProcessingErr::from(format!("alert HTTP client: {e}")))?;
+        Ok(AlertState {
+            limiter: AlertLimiter::new(&cfg),
+            cfg,
+            url,
+            client,
+        })
+    }
+
+    async fn handle(
+        &self,
+        _myself: ActorRef<Self::Msg>,
+        event: Self::Msg,
+        state: &mut Self::State,
+    ) -> Result<(), ActorProcessingErr> {
+        let Some(held) = state.limiter.admit(event.key(), Instant::now()) else {
+            debug!(%event, "[Alerts] Rate limited; held back");
+            return Ok(());
+        };
+        let body = payload(&state.cfg, &event, held);
+        let res = state
+            .client
+            .post(state.url.clone())
+            .json(&body)
+            .send()
+            .await
+            .and_then(reqwest::Response::error_for_status);
+        if let Err(e) = res {
+            warn!(error = %e.without_url(), %event, "[Alerts] Webhook delivery failed");
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pool_empty(model: &str) -> AlertEvent {
+        AlertEvent::PoolEmpty {
+            provider: ProviderKind::Codex,
+            model: model.to_string(),
+        }
+    }
+
+    #[test]
+    fn repeats_are_held_back_and_counted() {
+        let cfg = AlertsConfig {
+            min_interval_secs: 60,
+            ..AlertsConfig::default()
+        };
+        let mut limiter = AlertLimiter::new(&cfg);
+        let start = Instant::now();
+        let key = pool_empty("gpt-5").key();
+
+        assert_eq!(limiter.admit(key.clone(), start), Some(0));
+        assert_eq!(
+            limiter.admit(key.clone(), start + Duration::from_secs(1)),
+            None
+        );
+        assert_eq!(
+            limiter.admit(key.clone(), start + Duration::from_secs(2)),
+            None
+        );
+        assert_eq!(
+            limiter.admit(pool_empty("o3").key(), start + Duration::from_secs(3)),
+            Some(0)
+        );
+        assert_eq!(limiter.admit(key, start + Duration::from_secs(61)), Some(2));
+    }
+
+    #[test]
+    fn alerts_beyond_the_per_minute_cap_are_dropped() {
+        let cfg = AlertsConfig {
+            max_per_minute: 2,
+            ..AlertsConfig::default()
+        };
+        let mut limiter = AlertLimiter::new(&cfg);
+        let start = Instant::now();
+
+        assert!(limiter.admit("a".to_string(), start).is_some());
+        assert!(limiter.admit("b".to_string(), start).is_some());
+        assert_eq!(limiter.admit("c".to_string(), start), None);
+        assert_eq!(limiter.admit("c".to_string(), start + RATE_WINDOW), Some(1));
+    }
+
+    #[test]
+    fn payload_follows_the_configured_format() {
+        let event = AlertEvent::CredentialBanned {
+            provider: ProviderKind::GeminiCli,
+            id: 7,
+            account: "a@example.com".to_string(),
+        };
+        let text = "[pollux] geminicli credential 7 (a@example.com) banned (3 repeats held back)";
+
+        let body = payload(&AlertsConfig::default(), &event, 3);
+        assert_eq!(body["event"], "credential_banned");
+        assert_eq!(body["provider"], "gemini_cli");
+        assert_eq!(body["id"], 7);
+        assert_eq!(body["suppressed"], 3);
+        assert_eq!(body["text"], text);
+
+        let slack = AlertsConfig {
+            format: AlertFormat::Slack,
+            ..AlertsConfig::default()
+        };
+        assert_eq!(payload(&slack, &event, 3), json!({ "text": text }));
+
+        let telegram = AlertsConfig {
+            format: AlertFormat::Telegram,
+            telegram_chat_id: Some("-100".to_string()),
+            ..AlertsConfig::default()
+        };
+        assert_eq!(
+            payload(&telegram, &event, 0),
+            json!({
+                "chat_id": "-100",
+                "text": "[pollux] geminicli credential 7 (a@example.com) banned",
+            })
+        );
+    }
+}
@@ -0,0 +1,280 @@
+//! JSON-lines audit log of proxied requests (`basic.audit_log_path`).
+//!
+//! `access_log` runs each request inside an [`AUDIT_SCOPE`]; the
+//! [`UsageTracker`](crate::providers::usage::UsageTracker) started there
+//! captures it and emits one [`AuditRecord`] when it drops, i.e. once the
+//! final status and token counts are known. Lines are written by a dedicated
+//! thread so file IO never blocks the runtime; if it falls behind, records
+//! are dropped with a warning rather than buffered without bound.
+
+use crate::server::request_events::RequestMeta;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::fmt::Write as _;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write as _};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::mpsc::{self, Receiver, SyncSender, TrySendError};
+use tracing::warn;
+
+const AUDIT_QUEUE_CAPACITY: usize = 8192;
+
+tokio::task_local! {
+    /// Audit context of the request being handled, when the audit log is on.
+    pub(crate) static AUDIT_SCOPE: AuditScope;
+}
+
+/// One proxied request.
+#[derive(Debug, Clone, Serialize)]
+pub struct AuditRecord {
+    pub ts: DateTime<Utc>,
+    pub request_id: String,
+    pub provider: &'static str,
+    pub model: String,
+    /// Canonical payload hash, as in `x-pollux-request-hash`.
+    pub request_hash: Option<String>,
+    pub credential_id: Option<u64>,
+    /// First 12 hex digits of the client key's SHA-256; never the key.
+    pub key_fingerprint: Option<String>,
+    pub status: u16,
+    /// Until the response finished, including the whole stream.
+    pub latency_ms: u64,
+    /// Upstream attempts beyond the first.
+    pub retries: u32,
+    pub prompt_tokens: i64,
+    pub output_tokens: i64,
+}
+
+/// Request-scoped audit state set up by `access_log`.
+#[derive(Debug, Clone)]
+pub(crate) struct AuditScope {
+    pub(crate) log: AuditLog,
+    pub(crate) request_id: String,
+    pub(crate) key_fingerprint: Option<String>,
+    pub(crate) started_at: DateTime<Utc>,
+    meta: RequestMeta,
+    retries: Arc<AtomicU32>,
+}
+
+impl AuditScope {
+    pub(crate) fn new(
+        log: AuditLog,
+        request_id: String,
+        key: Option<&str>,
+        meta: RequestMeta,
+    ) -> Self {
+        Self {
+            log,
+            request_id,
+            key_fingerprint: key.map(key_fingerprint),
+            started_at: Utc::now(),
+            meta,
+            retries: Arc::default(),
+        }
+    }
+
+    pub(crate) fn request_hash(&self) -> Option<String> {
+        self.meta.request_hash().map(ToString::to_string)
+    }
+
+    pub(crate) fn retries(&self) -> u32 {
+        self.retries.load(Ordering::Relaxed)
+    }
+}
+
+/// Count an upstream retry against the current request, if it is audited.
+pub(crate) fn note_retry() {
+    let _ = AUDIT_SCOPE.try_with(|scope| scope.retries.fetch_add(1, Ordering::Relaxed));
+}
+
+fn key_fingerprint(key: &str) -> String {
+    let digest = Sha256::digest(key.as_bytes());
+    let mut out = String::with_capacity(12);
+    for byte in &digest[..6] {
+        let _ = write!(out, "{byte:02x}");
+    }
+    out
+}
+
+/// Handle to the writer thread.
+#[derive(Debug, Clone)]
+pub struct AuditLog {
+    tx: SyncSender<AuditRecord>,
+}
+
+impl AuditLog {
+    /// Open (or create) `path` for appending and start the writer thread.
+    ///
+    /// # Errors
+    /// Returns the IO error if the file cannot be opened.
+    pub fn open(path: PathBuf, max_bytes: u64, max_files: usize) -> io::Result<Self> {
+        let writer = RotatingFile::open(path, max_bytes, max_files)?;
+        let (tx, rx) = mpsc::sync_channel(AUDIT_QUEUE_CAPACITY);
+        std::thread::Builder::new()
+            .name("pollux-audit-log".to_string())
+            .spawn(move || write_loop(&rx, writer))?;
+        Ok(Self { tx })
+    }
+
+    pub fn record(&self, record: AuditRecord) {
+        match self.tx.try_send(record) {
+            Ok(()) => {}
+            Err(TrySendError::Full(record)) => {
+                warn!(
+                    request_id = %record.request_id,
+                    "[AuditLog] Writer is behind, record dropped"
+                );
+            }
+            Err(TrySendError::Disconnected(_)) => {
+                warn!("[AuditLog] Writer stopped, record dropped");
+            }
+        }
+    }
+}
+
+fn write_loop(rx: &Receiver<AuditRecord>, mut writer: RotatingFile) {
+    while let Ok(record) = rx.recv() {
+        let mut line = match serde_json::to_vec(&record) {
+            Ok(line) => line,
+            Err(e) => {
+                warn!(error = %e, "[AuditLog] Failed to serialize record");
+                continue;
+            }
+        };
+        line.push(b'\n');
+        if let Err(e) = writer.write_line(&line) {
+            warn!(error = %e, path = %writer.path.display(), "[AuditLog] Write failed");
+        }
+    }
+}
+
+/// Append-only file rotated to `<path>.1` … `<path>.<max_files>` by size.
+struct RotatingFile {
+    path: PathBuf,
+    file: File,
+    len: u64,
+    max_bytes: u64,
+    max_files: usize,
+}
+
+impl RotatingFile {
+    fn open(path: PathBuf, max_bytes: u64, max_files: usize) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let len = file.metadata()?.len();
+        Ok(Self {
+            path,
+            file,
+            len,
+            max_bytes,
+            max_files,
+        })
+    }
+
+    fn write_line(&mut self, line: &[u8]) -> io::Result<()> {
+        if self.len > 0 && self.len + line.len() as u64 > self.max_bytes {
+            self.rotate()?;
+        }
+        self.file.write_all(line)?;
+        self.len += line.len() as u64;
+        Ok(())
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        self.file.flush()?;
+        if self.max_files == 0 {
+            fs::remove_file(&self.path)?;
+        } else {
+            let _ = fs::remove_file(rotated(&self.path, self.max_files));
+            for n in (1..self.max_files).rev() {
+                let from = rotated(&self.path, n);
+                if from.exists() {
+                    fs::rename(&from, rotated(&self.path, n + 1))?;
+                }
+            }
+            fs::rename(&self.path, rotated(&self.path, 1))?;
+        }
+        self.file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        self.len = 0;
+        Ok(())
+    }
+}
+
+fn rotated(path: &Path, n: usize) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(format!(".{n}"));
+    PathBuf::from(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rotates_by_size_and_keeps_max_files() {
+        let dir = std::env::temp_dir().join(format!(
+            "pollux-audit-{}-{}",
+            std::process::id(),
+            Utc::now().timestamp_nanos_opt().unwrap_or_default()
+        ));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("audit.jsonl");
+
+        let mut writer = RotatingFile::open(path.clone(), 10, 2).unwrap();
+        for line in ["aaaaaaa\n", "bbbbbbb\n", "ccccccc\n", "ddddddd\n"] {
+            writer.write_line(line.as_bytes()).unwrap();
+        }
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "ddddddd\n");
+        assert_eq!(fs::read_to_string(rotated(&path, 1)).unwrap(), "ccccccc\n");
+        assert_eq!(fs::read_to_string(rotated(&path, 2)).unwrap(), "bbbbbbb\n");
+        assert!(!rotated(&path, 3).exists());
+        assert_eq!(key_fingerprint("pwd").len(), 12);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn records_carry_the_request_hash() {
+        let dir = std::env::temp_dir().join(format!(
+            "pollux-audit-hash-{}-{}",
+            std::process::id(),
+            Utc::now().timestamp_nanos_opt().unwrap_or_default()
+        ));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("audit.jsonl");
+
+        let meta = RequestMeta::default();
+        meta.hash_request("m", &serde_json::json!({"input": "hi"}));
+        let (tx, rx) = mpsc::sync_channel(1);
+        let scope = AuditScope::new(AuditLog { tx }, "rid".to_string(), Some("pwd"), meta);
+        scope.log.record(AuditRecord {
+            ts: Utc::now(),
+            request_id: scope.request_id.clone(),
+            provider: "codex",
+            model: "m".to_string(),
+            request_hash: scope.request_hash(),
+            credential_id: Some(1),
+            key_fingerprint: scope.key_fingerprint.clone(),
+            status: 200,
+            latency_ms: 5,
+            retries: scope.retries(),
+            prompt_tokens: 3,
+            output_tokens: 4,
+        });
+        drop(scope);
+        write_loop(&rx, RotatingFile::open(path.clone(), 1 << 20, 1).unwrap());
+
+        let line: serde_json::Value =
+            serde_json::from_str(fs::read_to_string(&path).unwrap().trim()).unwrap();
+        let hash = line["request_hash"].as_str().unwrap();
+        assert!(hash.starts_with("sha256:"), "{hash}");
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
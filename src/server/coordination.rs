@@ -0,0 +1,117 @@
+//! Leader election between Pollux processes sharing one database
+//! (`basic.coordination`).
+//!
+//! Both the outgoing and the incoming binary of an upgrade serve traffic, but
+//! two processes refreshing the same refresh token race each other and can
+//! invalidate it. The process holding an exclusive lock on `lock_path` is the
+//! leader: it runs OAuth refreshes and `resource:add` onboarding. Followers
+//! reload tokens the leader has written to the database and keep polling the
+//! lock, so the new binary takes over as soon as the old one exits.
+
+use crate::config::CoordinationConfig;
+use std::fs::{File, OpenOptions, TryLockError};
+use std::io::{self, Write as _};
+use std::net::SocketAddr;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use tokio::net::{TcpListener, TcpSocket};
+use tracing::{info, warn};
+
+/// Without coordination every process is its own leader.
+static IS_LEADER: AtomicBool = AtomicBool::new(true);
+
+/// Whether this process refreshes and onboards credentials.
+pub fn is_leader() -> bool {
+    IS_LEADER.load(Ordering::Relaxed)
+}
+
+/// Try to take the lock; `Ok(None)` while another process holds it.
+fn try_acquire(path: &Path) -> io::Result<Option<File>> {
+    let mut file = OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(path)?;
+    match file.try_lock() {
+        Ok(()) => {
+            file.set_len(0)?;
+            writeln!(file, "{}", std::process::id())?;
+            Ok(Some(file))
+        }
+        Err(TryLockError::WouldBlock) => Ok(None),
+        Err(TryLockError::Error(e)) => Err(e),
+    }
+}
+
+/// Join the election. Returns immediately; a follower keeps polling the lock
+/// in the background and is promoted once it gets it.
+///
+/// The lock is held until the process exits, which is also how it is released.
+pub fn start(cfg: &CoordinationConfig) -> io::Result<()> {
+    let Some(path) = cfg.lock_path.clone() else {
+        return Ok(());
+    };
+    if let Some(file) = try_acquire(&path)? {
+        info!(path = %path.display(), "[Coordination] Holding the lock; this process is the leader");
+        std::mem::forget(file);
+        return Ok(());
+    }
+
+    IS_LEADER.store(false, Ordering::Relaxed);
+    info!(
+        path = %path.display(),
+        "[Coordination] Lock held by another process; serving as follower"
+    );
+    let poll = Duration::from_secs(cfg.poll_secs.max(1));
+    tokio::spawn(async move {
+        loop {
+            tokio::time::sleep(poll).await;
+            match try_acquire(&path) {
+                Ok(Some(file)) => {
+                    std::mem::forget(file);
+                    IS_LEADER.store(true, Ordering::Relaxed);
+                    info!("[Coordination] Lock acquired; promoted to leader");
+                    return;
+                }
+                Ok(None) => {}
+                Err(e) => warn!(error = %e, "[Coordination] Lock poll failed"),
+            }
+        }
+    });
+    Ok(())
+}
+
+/// Bind the HTTP listener, with `SO_REUSEPORT` when `reuse_port` is set so
+/// the next binary can bind the same address while this one drains.
+pub fn bind_listener(addr: SocketAddr, reuse_port: bool) -> io::Result<TcpListener> {
+    let socket = if addr.is_ipv4() {
+        TcpSocket::new_v4()?
+    } else {
+        TcpSocket::new_v6()?
+    };
+    socket.set_reuseaddr(true)?;
+    #[cfg(unix)]
+    socket.set_reuseport(reuse_port)?;
+    #[cfg(not(unix))]
+    if reuse_port {
+        warn!("[Coordination] reuse_port is only supported on Unix; ignoring");
+    }
+    socket.bind(addr)?;
+    socket.listen(1024)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::try_acquire;
+
+    #[test]
+    fn second_holder_is_refused_until_the_first_releases() {
+        let path = std::env::temp_dir().join(format!("pollux-leader-{}.lock", std::process::id()));
+        let leader = try_acquire(&path).unwrap().expect("first process leads");
+        assert!(try_acquire(&path).unwrap().is_none());
+        drop(leader);
+        assert!(try_acquire(&path).unwrap().is_some());
+        let _ = std::fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,244 @@
+//! In-flight request tracking for graceful shutdown.
+//!
+//! Every response body carries a guard that keeps the in-flight count up
+//! until the body is fully sent or dropped, so a long SSE stream counts as
+//! in flight for its whole lifetime. On shutdown the server waits for the count
+//! to reach zero; when the drain timeout fires first, [`ShutdownDrain::force_close`]
+//! makes every open event stream emit a final `event: error` frame and end.
+
+use axum::{
+    body::{Body, Bytes},
+    extract::{Request, State},
+    http::header::CONTENT_TYPE,
+    middleware::Next,
+    response::Response,
+};
+use futures::{Stream, StreamExt};
+use http_body::{Frame, SizeHint};
+use std::pin::Pin;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::task::{Context, Poll};
+use std::time::Duration;
+use tokio::sync::{Notify, watch};
+
+/// Final frame sent to SSE clients whose stream outlived the drain timeout.
+const SHUTDOWN_FRAME: &str = "event: error\ndata: {\"error\":{\"code\":503,\"message\":\"server is shutting down\",\"status\":\"UNAVAILABLE\"}}\n\n";
+
+#[derive(Debug)]
+struct DrainInner {
+    in_flight: AtomicUsize,
+    idle: Notify,
+    /// `true` once shutdown has started.
+    draining: watch::Sender<bool>,
+    /// `true` once open streams must terminate.
+    closing: watch::Sender<bool>,
+}
+
+/// Shared drain state; cheap to clone.
+#[derive(Debug, Clone)]
+pub struct ShutdownDrain {
+    inner: Arc<DrainInner>,
+}
+
+impl Default for ShutdownDrain {
+    fn default() -> Self {
+        Self {
+            inner: Arc::new(DrainInner {
+                in_flight: AtomicUsize::new(0),
+                idle: Notify::new(),
+                draining: watch::Sender::new(false),
+                closing: watch::Sender::new(false),
+            }),
+        }
+    }
+}
+
+/// Held by a response body; decrements the in-flight count on drop.
+struct InFlightGuard(Arc<DrainInner>);
+
+impl Drop for InFlightGuard {
+    fn drop(&mut self) {
+        if self.0.in_flight.fetch_sub(1, Ordering::AcqRel) == 1 {
+            self.0.idle.notify_waiters();
+        }
+    }
+}
+
+impl ShutdownDrain {
+    /// Requests whose response has not finished streaming yet.
+    #[must_use]
+    pub fn in_flight(&self) -> usize {
+        self.inner.in_flight.load(Ordering::Acquire)
+    }
+
+    fn enter(&self) -> InFlightGuard {
+        self.inner.in_flight.fetch_add(1, Ordering::AcqRel);
+        InFlightGuard(self.inner.clone())
+    }
+
+    /// Mark the start of shutdown.
+    pub fn begin(&self) {
+        self.inner.draining.send_replace(true);
+    }
+
+    /// Terminate every open event stream with a final error frame.
+    pub fn force_close(&self) {
+        self.inner.closing.send_replace(true);
+    }
+
+    /// Resolves `timeout` after [`Self::begin`] unless all requests finish
+    /// first, in which case it never resolves.
+    pub async fn expired(&self, timeout: Duration) {
+        let mut draining = self.inner.draining.subscribe();
+        let _ = draining.wait_for(|d| *d).await;
+        let idle = async {
+            loop {
+                let notified = self.inner.idle.notified();
+                if self.in_flight() == 0 {
+                    break;
+                }
+                notified.await;
+            }
+        };
+        if tokio::time::timeout(timeout, idle).await.is_ok() {
+            std::future::pending::<()>().await;
+        }
+    }
+}
+
+pub(crate) fn is_event_stream(resp: &Response) -> bool {
+    resp.headers()
+        .get(CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.starts_with("text/event-stream"))
+}
+
+/// Body wrapper holding `guard` until the body is sent or dropped; keeps size
+/// hints (and `Content-Length`) intact.
+pub(crate) struct GuardedBody<G> {
+    inner: Body,
+    _guard: G,
+}
+
+impl<G: Send + Unpin + 'static> GuardedBody<G> {
+    pub(crate) fn wrap(inner: Body, guard: G) -> Body {
+        Body::new(Self {
+            inner,
+            _guard: guard,
+        })
+    }
+}
+
+impl<G: Unpin> http_body::Body for GuardedBody<G> {
+    type Data = Bytes;
+    type Error = axum::Error;
+
+    fn poll_frame(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        Pin::new(&mut self.inner).poll_frame(cx)
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        self.inner.size_hint()
+    }
+}
+
+/// Event stream body that ends with [`SHUTDOWN_FRAME`] once `closing` flips.
+fn closable_sse<S>(
+    body: S,
+    closing: watch::Receiver<bool>,
+    guard: InFlightGuard,
+) -> impl Stream<Item = Result<Bytes, axum::Error>>
+where
+    S: Stream<Item = Result<Bytes, axum::Error>> + Send + 'static,
+{
+    futures::stream::unfold(
+        (Box::pin(body), closing, Some(guard)),
+        |(mut body, mut closing, guard)| async move {
+            guard.as_ref()?;
+            let next = tokio::select! {
+                biased;
+                chunk = body.next() => Some(chunk?),
+                _ = closing.wait_for(|c| *c) => None,
+            };
+            if let Some(chunk) = next {
+                return Some((chunk, (body, closing, guard)));
+            }
+            let frame = Bytes::from_static(SHUTDOWN_FRAME.as_bytes());
+            Some((Ok(frame), (body, closing, None)))
+        },
+    )
+}
+
+/// Middleware: count the request as in flight until its body is done.
+pub async fn track_in_flight(
+    State(drain): State<ShutdownDrain>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let guard = drain.enter();
+    let resp = next.run(req).await;
+    let sse = is_event_stream(&resp);
+    let (parts, body) = resp.into_parts();
+
+    let body = if sse {
+        let closing = drain.inner.closing.subscribe();
+        Body::from_stream(closable_sse(body.into_data_stream(), closing, guard))
+    } else {
+        GuardedBody::wrap(body, guard)
+    };
+    Response::from_parts(parts, body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{Router, body::to_bytes, routing::get};
+    use tower::ServiceExt;
+
+    #[tokio::test]
+    async fn forced_close_ends_open_streams_with_error_frame() {
+        let drain = ShutdownDrain::default();
+        let app = Router::new()
+            .route(
+                "/sse",
+                get(|| async {
+                    let pending = futures::stream::pending::<Result<Bytes, std::io::Error>>();
+                    let first =
+                        futures::stream::once(async { Ok(Bytes::from_static(b"data: 1\n\n")) });
+                    Response::builder()
+                        .header(CONTENT_TYPE, "text/event-stream")
+                        .body(Body::from_stream(first.chain(pending)))
+                        .unwrap()
+                }),
+            )
+            .layer(axum::middleware::from_fn_with_state(
+                drain.clone(),
+                track_in_flight,
+            ));
+
+        let resp = app
+            .oneshot(Request::get("/sse").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(drain.in_flight(), 1);
+
+        drain.begin();
+        let expired = tokio::time::timeout(Duration::from_secs(1), drain.expired(Duration::ZERO));
+        assert!(expired.await.is_ok());
+        drain.force_close();
+
+        let body = to_bytes(resp.into_body(), usize::MAX).await.unwrap();
+        let text = String::from_utf8(body.to_vec()).unwrap();
+        assert!(text.starts_with("data: 1\n\n"));
+        assert!(text.ends_with(SHUTDOWN_FRAME));
+        assert_eq!(drain.in_flight(), 0);
+    }
+}
@@ -1,4 +1,5 @@
 use crate::server::router::PolluxState;
+use crate::server::routes::geminicli::canonical_path;
 use axum::{
     Json,
     extract::FromRequestParts,
@@ -8,9 +9,14 @@ use axum::{
 use axum_extra::headers::{Authorization, HeaderMapExt, authorization::Bearer};
 use serde_json::json;
 use subtle::ConstantTimeEq;
+use tracing::warn;
 
 fn extract_header_token(headers: &axum::http::HeaderMap) -> Option<String> {
-    if let Some(k) = headers.get("x-goog-api-key").and_then(|v| v.to_str().ok()) {
+    // `x-api-key` is what Anthropic clients send.
+    if let Some(k) = ["x-goog-api-key", "x-api-key"]
+        .iter()
+        .find_map(|name| headers.get(*name).and_then(|v| v.to_str().ok()))
+    {
         return Some(k.to_string());
     }
     headers
@@ -26,6 +32,14 @@ fn extract_query_token(query: Option<&str>) -> Option<String> {
     })
 }
 
+/// The client key from `x-goog-api-key`, `x-api-key`, a bearer token or `?key=`.
+pub(crate) fn presented_key(
+    headers: &axum::http::HeaderMap,
+    query: Option<&str>,
+) -> Option<String> {
+    extract_header_token(headers).or_else(|| extract_query_token(query))
+}
+
 #[derive(Debug, Clone, Copy)]
 pub struct RequireKeyAuth;
 
@@ -36,26 +50,102 @@ impl FromRequestParts<PolluxState> for RequireKeyAuth {
         parts: &mut Parts,
         state: &PolluxState,
     ) -> Result<Self, Self::Rejection> {
-        let token =
-            extract_header_token(&parts.headers).or_else(|| extract_query_token(parts.uri.query()));
-
-        match token {
-            Some(key) => {
-                let expected = state.pollux_key.as_ref();
-                if key.as_bytes().ct_eq(expected.as_bytes()).into() {
-                    Ok(RequireKeyAuth)
-                } else {
-                    Err(AuthError::InvalidKey)
-                }
+        let Some(key) = presented_key(&parts.headers, parts.uri.query()) else {
+            return Err(AuthError::MissingKey);
+        };
+
+        let expected = state.pollux_key.as_ref();
+        if key.as_bytes().ct_eq(expected.as_bytes()).into() {
+            return Ok(RequireKeyAuth);
+        }
+
+        let Some(scoped) = state
+            .api_keys
+            .iter()
+            .find(|k| bool::from(key.as_bytes().ct_eq(k.key.as_bytes())))
+        else {
+            return Err(AuthError::InvalidKey);
+        };
+
+        let path = parts.uri.path();
+        let canonical = canonical_path(path);
+        if scoped
+            .routes
+            .iter()
+            .any(|p| route_matches(p, path) || route_matches(p, &canonical))
+        {
+            Ok(RequireKeyAuth)
+        } else {
+            warn!(key = %scoped.name, path, "[Auth] Route not allowed for scoped key");
+            Err(AuthError::RouteNotAllowed)
+        }
+    }
+}
+
+/// Accepts only the master `pollux_key`; guards the admin routes, which no
+/// scoped key can reach whatever its route patterns.
+#[derive(Debug, Clone, Copy)]
+pub struct RequireMasterKey;
+
+impl FromRequestParts<PolluxState> for RequireMasterKey {
+    type Rejection = AuthError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &PolluxState,
+    ) -> Result<Self, Self::Rejection> {
+        let Some(key) = presented_key(&parts.headers, parts.uri.query()) else {
+            return Err(AuthError::MissingKey);
+        };
+
+        let expected = state.pollux_key.as_ref();
+        if key.as_bytes().ct_eq(expected.as_bytes()).into() {
+            return Ok(RequireMasterKey);
+        }
+
+        match state
+            .api_keys
+            .iter()
+            .find(|k| bool::from(key.as_bytes().ct_eq(k.key.as_bytes())))
+        {
+            Some(scoped) => {
+                let path = parts.uri.path();
+                warn!(key = %scoped.name, path, "[Auth] Admin route needs the master key");
+                Err(AuthError::RouteNotAllowed)
             }
-            None => Err(AuthError::MissingKey),
+            None => Err(AuthError::InvalidKey),
+        }
+    }
+}
+
+/// Glob-style match of a request path against a route pattern; `*` matches any
+/// run of characters (including `/`), everything else is literal.
+fn route_matches(pattern: &str, path: &str) -> bool {
+    let Some((prefix, rest)) = pattern.split_once('*') else {
+        return pattern == path;
+    };
+    let Some(mut remaining) = path.strip_prefix(prefix) else {
+        return false;
+    };
+
+    let mut segments = rest.split('*').peekable();
+    while let Some(segment) = segments.next() {
+        if segments.peek().is_none() {
+            // Last literal must anchor at the end of the path.
+            return remaining.ends_with(segment);
+        }
+        match remaining.find(segment) {
+            Some(at) => remaining = &remaining[at + segment.len()..],
+            None => return false,
         }
     }
+    true
 }
 
 pub enum AuthError {
     MissingKey,
     InvalidKey,
+    RouteNotAllowed,
 }
 
 impl IntoResponse for AuthError {
@@ -63,6 +153,10 @@ impl IntoResponse for AuthError {
         let (status, reason) = match self {
             AuthError::MissingKey => (StatusCode::UNAUTHORIZED, "Missing API key"),
             AuthError::InvalidKey => (StatusCode::UNAUTHORIZED, "Invalid API key"),
+            AuthError::RouteNotAllowed => (
+                StatusCode::FORBIDDEN,
+                "API key is not allowed to call this route",
+            ),
         };
         (
             status,
@@ -71,3 +165,30 @@ impl IntoResponse for AuthError {
             .into_response()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::route_matches;
+
+    #[test]
+    fn route_patterns_match_literally_or_by_wildcard() {
+        assert!(route_matches("/codex/v1/models", "/codex/v1/models"));
+        assert!(!route_matches("/codex/v1/models", "/codex/v1/models/x"));
+
+        assert!(route_matches("/codex/*", "/codex/v1/responses"));
+        assert!(!route_matches("/codex/*", "/geminicli/v1beta/models"));
+
+        let generate = "/geminicli/v1beta/models/*:generateContent";
+        assert!(route_matches(
+            generate,
+            "/geminicli/v1beta/models/gemini-2.5-pro:generateContent"
+        ));
+        assert!(!route_matches(
+            generate,
+            "/geminicli/v1beta/models/gemini-2.5-pro:streamGenerateContent"
+        ));
+
+        assert!(route_matches("/*/v1/*", "/codex/v1/responses"));
+        assert!(route_matches("*", "/anything"));
+    }
+}
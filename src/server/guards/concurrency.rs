@@ -0,0 +1,168 @@
+//! Server-wide cap on proxied requests in flight (`basic.concurrency_limit`).
+//!
+//! A slot is held until the response body is fully sent or dropped, so an
+//! open SSE stream keeps its slot for its whole lifetime; counting only until
+//! the handler returns would let buffered streams pile up unbounded. Requests
+//! over the cap queue for up to `queue_timeout_ms` and are then rejected with
+//! a `429` whose code (`server_overloaded`) tells Pollux's own saturation
+//! apart from an exhausted credential pool.
+
+use super::rate_limit::too_many_requests;
+use crate::config::ConcurrencyLimitConfig;
+use crate::server::drain::GuardedBody;
+use axum::{
+    extract::{Request, State},
+    middleware::Next,
+    response::Response,
+};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Semaphore;
+use tracing::warn;
+
+/// `Retry-After` sent with an overload rejection.
+const OVERLOAD_RETRY_AFTER: Duration = Duration::from_secs(1);
+
+/// Shared in-flight slots; the default is unlimited.
+#[derive(Debug, Clone, Default)]
+pub struct ConcurrencyLimit {
+    slots: Option<Arc<Semaphore>>,
+    max_in_flight: usize,
+    queue_timeout: Duration,
+}
+
+impl ConcurrencyLimit {
+    #[must_use]
+    pub fn new(cfg: Option<ConcurrencyLimitConfig>) -> Self {
+        let Some(cfg) = cfg.filter(|cfg| cfg.max_in_flight > 0) else {
+            return Self::default();
+        };
+        Self {
+            slots: Some(Arc::new(Semaphore::new(cfg.max_in_flight))),
+            max_in_flight: cfg.max_in_flight,
+            queue_timeout: Duration::from_millis(cfg.queue_timeout_ms),
+        }
+    }
+
+    /// Requests currently holding a slot; `0` when unlimited.
+    #[must_use]
+    pub fn in_flight(&self) -> usize {
+        self.slots.as_ref().map_or(0, |slots| {
+            self.max_in_flight.saturating_sub(slots.available_permits())
+        })
+    }
+}
+
+pub async fn limit_concurrency(
+    State(limit): State<ConcurrencyLimit>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let Some(slots) = limit.slots.clone() else {
+        return next.run(req).await;
+    };
+
+    let permit = match slots.clone().try_acquire_owned() {
+        Ok(permit) => Some(permit),
+        Err(_) if limit.queue_timeout.is_zero() => None,
+        Err(_) => tokio::time::timeout(limit.queue_timeout, slots.acquire_owned())
+            .await
+            .ok()
+            .and_then(Result::ok),
+    };
+    let Some(permit) = permit else {
+        let path = req.uri().path();
+        warn!(
+            path,
+            max_in_flight = limit.max_in_flight,
+            "[Concurrency] Server at its in-flight limit; request rejected"
+        );
+        return too_many_requests(
+            path,
+            "server_overloaded",
+            "Pollux is at its concurrent request limit; retry shortly",
+            OVERLOAD_RETRY_AFTER,
+        );
+    };
+
+    let resp = next.run(req).await;
+    let (parts, body) = resp.into_parts();
+    Response::from_parts(parts, GuardedBody::wrap(body, permit))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{
+        Router,
+        body::{Body, Bytes},
+        http::StatusCode,
+        routing::get,
+    };
+    use futures::StreamExt;
+    use tower::ServiceExt;
+
+    fn app(limit: ConcurrencyLimit) -> Router {
+        Router::new()
+            .route(
+                "/sse",
+                get(|| async {
+                    let first =
+                        futures::stream::once(async { Ok(Bytes::from_static(b"data: 1\n\n")) });
+                    let pending = futures::stream::pending::<Result<Bytes, std::io::Error>>();
+                    Body::from_stream(first.chain(pending))
+                }),
+            )
+            .layer(axum::middleware::from_fn_with_state(
+                limit,
+                limit_concurrency,
+            ))
+    }
+
+    fn get_sse() -> Request {
+        Request::get("/sse").body(Body::empty()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn open_stream_holds_its_slot_until_dropped() {
+        let limit = ConcurrencyLimit::new(Some(ConcurrencyLimitConfig {
+            max_in_flight: 1,
+            queue_timeout_ms: 0,
+        }));
+        let app = app(limit.clone());
+
+        let open = app.clone().oneshot(get_sse()).await.unwrap();
+        assert_eq!(open.status(), StatusCode::OK);
+        assert_eq!(limit.in_flight(), 1);
+
+        let rejected = app.clone().oneshot(get_sse()).await.unwrap();
+        assert_eq!(rejected.status(), StatusCode::TOO_MANY_REQUESTS);
+        let body = axum::body::to_bytes(rejected.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert!(String::from_utf8_lossy(&body).contains("concurrent request limit"));
+
+        drop(open);
+        assert_eq!(limit.in_flight(), 0);
+        let admitted = app.oneshot(get_sse()).await.unwrap();
+        assert_eq!(admitted.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn queued_request_gets_the_slot_once_freed() {
+        let limit = ConcurrencyLimit::new(Some(ConcurrencyLimitConfig {
+            max_in_flight: 1,
+            queue_timeout_ms: 5_000,
+        }));
+        let app = app(limit);
+
+        let open = app.clone().oneshot(get_sse()).await.unwrap();
+        let queued = tokio::spawn(app.oneshot(get_sse()));
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert!(!queued.is_finished());
+
+        drop(open);
+        let admitted = queued.await.unwrap().unwrap();
+        assert_eq!(admitted.status(), StatusCode::OK);
+    }
+}
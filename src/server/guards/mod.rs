@@ -1 +1,5 @@
 pub mod auth;
+pub mod concurrency;
+pub mod policy;
+pub mod rate_limit;
+pub mod resource_add;
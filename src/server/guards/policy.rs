@@ -0,0 +1,230 @@
+//! Per-key request policy (`basic.api_keys[].policy`).
+//!
+//! Checked by the extractors once the body is parsed, so a violation comes
+//! back as a 400 in the route's own error shape before any credential is
+//! leased. Keys without a policy, including `pollux_key`, are unrestricted.
+
+use super::auth::presented_key;
+use crate::config::RequestPolicyConfig;
+use crate::server::router::PolluxState;
+use axum::http::HeaderMap;
+use pollux_schema::OpenaiRequestBody;
+use pollux_schema::anthropic::AnthropicMessagesRequest;
+use pollux_schema::gemini::GeminiGenerateContentRequest;
+use pollux_schema::openai::ChatCompletionRequest;
+use serde_json::Value;
+use subtle::ConstantTimeEq;
+
+/// The policy of the scoped key that sent this request, if it has one.
+pub(crate) fn for_request<'a>(
+    state: &'a PolluxState,
+    headers: &HeaderMap,
+    query: Option<&str>,
+) -> Option<&'a RequestPolicyConfig> {
+    let key = presented_key(headers, query)?;
+    state
+        .api_keys
+        .iter()
+        .find(|k| bool::from(key.as_bytes().ct_eq(k.key.as_bytes())))?
+        .policy
+        .as_ref()
+}
+
+/// Enforce `policy` on a Responses request; `include` is filtered in place.
+pub(crate) fn check_responses(
+    policy: &RequestPolicyConfig,
+    body: &mut OpenaiRequestBody,
+) -> Result<(), String> {
+    check_max_output_tokens(policy, body.max_output_tokens, "max_output_tokens")?;
+    if policy.forbid_store && body.store == Some(true) {
+        return Err("store=true is not allowed for this API key".to_string());
+    }
+    let tools = body
+        .extra
+        .get("tools")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(|tool| tool.get("type").and_then(Value::as_str));
+    check_tools(policy, tools)?;
+    if let Some(include) = &mut body.include {
+        include.retain(|value| !policy.strip_include.contains(value));
+    }
+    Ok(())
+}
+
+/// Enforce `policy` on a Gemini request; a missing `maxOutputTokens` is set
+/// to the key's limit.
+pub(crate) fn check_gemini(
+    policy: &RequestPolicyConfig,
+    body: &mut GeminiGenerateContentRequest,
+) -> Result<(), String> {
+    let sent = body
+        .generation_config
+        .as_ref()
+        .and_then(|g| g.max_output_tokens);
+    check_max_output_tokens(policy, sent, "maxOutputTokens")?;
+    if let Some(max) = policy.max_output_tokens {
+        body.generation_config
+            .get_or_insert_with(Default::default)
+            .max_output_tokens
+            .get_or_insert(max);
+    }
+    let tools = body.tools.iter().flatten().flat_map(|tool| {
+        tool.function_declarations
+            .as_ref()
+            .map(|_| "functionDeclarations")
+            .into_iter()
+            .chain(tool.extra.keys().map(String::as_str))
+    });
+    check_tools(policy, tools)
+}
+
+/// Enforce `policy` on an Anthropic Messages request. Server tools are
+/// matched by their versioned `type`, e.g. `web_search_20250305`.
+pub(crate) fn check_messages(
+    policy: &RequestPolicyConfig,
+    body: &AnthropicMessagesRequest,
+) -> Result<(), String> {
+    check_max_output_tokens(policy, body.max_tokens, "max_tokens")?;
+    let tools = body
+        .extra
+        .get("tools")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(|tool| tool.get("type").and_then(Value::as_str));
+    check_tools(policy, tools)
+}
+
+/// Enforce `policy` on a Chat Completions request; `max_completion_tokens`
+/// wins over the deprecated `max_tokens` when both are sent.
+pub(crate) fn check_chat(
+    policy: &RequestPolicyConfig,
+    body: &ChatCompletionRequest,
+) -> Result<(), String> {
+    match body.max_completion_tokens {
+        Some(sent) => check_max_output_tokens(policy, Some(sent), "max_completion_tokens")?,
+        None => check_max_output_tokens(policy, body.max_tokens, "max_tokens")?,
+    }
+    if policy.forbid_store && body.extra.get("store").and_then(Value::as_bool) == Some(true) {
+        return Err("store=true is not allowed for this API key".to_string());
+    }
+    let tools = body.tools.iter().flatten().map(|tool| tool.kind.as_str());
+    check_tools(policy, tools)
+}
+
+fn check_max_output_tokens(
+    policy: &RequestPolicyConfig,
+    sent: Option<u32>,
+    field: &str,
+) -> Result<(), String> {
+    match (sent, policy.max_output_tokens) {
+        (Some(sent), Some(max)) if sent > max => Err(format!(
+            "{field}={sent} exceeds this API key's limit of {max}"
+        )),
+        _ => Ok(()),
+    }
+}
+
+fn check_tools<'a>(
+    policy: &RequestPolicyConfig,
+    mut tools: impl Iterator<Item = &'a str>,
+) -> Result<(), String> {
+    match tools.find(|tool| policy.forbid_tools.iter().any(|f| f == tool)) {
+        Some(tool) => Err(format!("tool `{tool}` is not allowed for this API key")),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn policy() -> RequestPolicyConfig {
+        RequestPolicyConfig {
+            max_output_tokens: Some(1024),
+            forbid_store: true,
+            strip_include: vec!["reasoning.encrypted_content".to_string()],
+            forbid_tools: vec!["web_search".to_string(), "googleSearch".to_string()],
+        }
+    }
+
+    #[test]
+    fn responses_violations_are_rejected_and_include_is_stripped() {
+        let parse = |value: Value| -> OpenaiRequestBody { serde_json::from_value(value).unwrap() };
+
+        let mut ok = parse(json!({
+            "model": "gpt-5",
+            "include": ["reasoning.encrypted_content", "file_search_call.results"],
+            "tools": [{"type": "function", "name": "f"}]
+        }));
+        assert_eq!(check_responses(&policy(), &mut ok), Ok(()));
+        assert_eq!(
+            ok.include.as_deref(),
+            Some(&["file_search_call.results".to_string()][..])
+        );
+
+        for value in [
+            json!({"model": "gpt-5", "store": true}),
+            json!({"model": "gpt-5", "max_output_tokens": 4096}),
+            json!({"model": "gpt-5", "tools": [{"type": "web_search"}]}),
+        ] {
+            assert!(check_responses(&policy(), &mut parse(value)).is_err());
+        }
+    }
+
+    #[test]
+    fn gemini_tools_are_checked_and_output_is_capped() {
+        let parse = |value: Value| -> GeminiGenerateContentRequest {
+            serde_json::from_value(value).unwrap()
+        };
+
+        let mut ok = parse(json!({"contents": [], "tools": [{"codeExecution": {}}]}));
+        assert_eq!(check_gemini(&policy(), &mut ok), Ok(()));
+        assert_eq!(ok.generation_config.unwrap().max_output_tokens, Some(1024));
+
+        let mut search = parse(json!({"contents": [], "tools": [{"googleSearch": {}}]}));
+        assert_eq!(
+            check_gemini(&policy(), &mut search),
+            Err("tool `googleSearch` is not allowed for this API key".to_string())
+        );
+    }
+
+    #[test]
+    fn messages_max_tokens_and_server_tools_are_checked() {
+        let parse =
+            |value: Value| -> AnthropicMessagesRequest { serde_json::from_value(value).unwrap() };
+        let mut policy = policy();
+        policy.forbid_tools.push("web_search_20250305".to_string());
+
+        let ok = parse(json!({"model": "m", "messages": [], "max_tokens": 1024}));
+        assert_eq!(check_messages(&policy, &ok), Ok(()));
+
+        for value in [
+            json!({"model": "m", "messages": [], "max_tokens": 4096}),
+            json!({"model": "m", "messages": [], "tools": [{"type": "web_search_20250305", "name": "web_search"}]}),
+        ] {
+            assert!(check_messages(&policy, &parse(value)).is_err());
+        }
+    }
+
+    #[test]
+    fn chat_token_limits_and_tools_are_checked() {
+        let parse =
+            |value: Value| -> ChatCompletionRequest { serde_json::from_value(value).unwrap() };
+
+        let ok = parse(json!({"model": "m", "messages": [], "max_tokens": 1024}));
+        assert_eq!(check_chat(&policy(), &ok), Ok(()));
+
+        for value in [
+            json!({"model": "m", "messages": [], "max_completion_tokens": 4096}),
+            json!({"model": "m", "messages": [], "max_tokens": 4096}),
+            json!({"model": "m", "messages": [], "store": true}),
+            json!({"model": "m", "messages": [], "tools": [{"type": "web_search"}]}),
+        ] {
+            assert!(check_chat(&policy(), &parse(value)).is_err());
+        }
+    }
+}
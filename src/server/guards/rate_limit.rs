@@ -0,0 +1,225 @@
+//! Per-client-key request limits (`basic.rate_limit`, `api_keys[].rate_limit`).
+//!
+//! Sits inside `RequireKeyAuth` on the provider routers, so only accepted keys
+//! get here. Requests per second go through a governor limiter per key.
+//! Token usage is only known once a response ends, so the handler runs inside
+//! a [`TOKEN_BUDGET`] scope; the [`UsageTracker`](crate::providers::usage::UsageTracker)
+//! started there charges its final counts, and further requests are turned
+//! away until the minute rolls over.
+
+use super::auth::presented_key;
+use crate::config::{BasicConfig, RateLimitConfig};
+use crate::error::{CodexError, GeminiCliError, GeminiErrorObject, set_retry_after};
+use axum::{
+    extract::{Request, State},
+    http::StatusCode,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use governor::clock::{Clock, DefaultClock};
+use governor::{DefaultDirectRateLimiter, Quota, RateLimiter};
+use pollux_schema::OpenaiResponsesErrorObject;
+use std::num::NonZeroU32;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use subtle::ConstantTimeEq;
+use tracing::warn;
+
+const TOKEN_WINDOW: Duration = Duration::from_mins(1);
+
+tokio::task_local! {
+    /// Token budget of the key that made the current request, if it has one.
+    pub(crate) static TOKEN_BUDGET: TokenBudget;
+}
+
+#[derive(Debug)]
+struct TokenWindow {
+    started: Instant,
+    used: u64,
+}
+
+impl TokenWindow {
+    fn roll(&mut self) {
+        if self.started.elapsed() >= TOKEN_WINDOW {
+            self.started = Instant::now();
+            self.used = 0;
+        }
+    }
+}
+
+/// Tokens spent by one key in the current fixed one-minute window.
+#[derive(Debug, Clone)]
+pub(crate) struct TokenBudget {
+    limit: u64,
+    window: Arc<Mutex<TokenWindow>>,
+}
+
+impl TokenBudget {
+    fn new(limit: u64) -> Self {
+        Self {
+            limit,
+            window: Arc::new(Mutex::new(TokenWindow {
+                started: Instant::now(),
+                used: 0,
+            })),
+        }
+    }
+
+    /// Time until the window resets, or `None` while tokens remain.
+    fn exhausted_for(&self) -> Option<Duration> {
+        let mut window = self.window.lock().ok()?;
+        window.roll();
+        (window.used >= self.limit).then(|| TOKEN_WINDOW.saturating_sub(window.started.elapsed()))
+    }
+
+    pub(crate) fn charge(&self, tokens: u64) {
+        if let Ok(mut window) = self.window.lock() {
+            window.roll();
+            window.used = window.used.saturating_add(tokens);
+        }
+    }
+}
+
+struct KeyLimiter {
+    key: String,
+    name: String,
+    rps: Option<DefaultDirectRateLimiter>,
+    tokens: Option<TokenBudget>,
+}
+
+impl KeyLimiter {
+    fn new(key: &str, name: &str, cfg: RateLimitConfig) -> Option<Self> {
+        let rps = cfg
+            .rps
+            .and_then(NonZeroU32::new)
+            .map(|rps| RateLimiter::direct(Quota::per_second(rps)));
+        let tokens = cfg.tpm.map(TokenBudget::new);
+        if rps.is_none() && tokens.is_none() {
+            return None;
+        }
+        Some(Self {
+            key: key.to_string(),
+            name: name.to_string(),
+            rps,
+            tokens,
+        })
+    }
+
+    /// How long the caller should wait, or `None` to admit the request.
+    ///
+    /// The token budget is checked first so a rejected request does not also
+    /// use up a request slot.
+    fn check(&self) -> Option<Duration> {
+        if let Some(wait) = self.tokens.as_ref().and_then(TokenBudget::exhausted_for) {
+            return Some(wait);
+        }
+        let rps = self.rps.as_ref()?;
+        rps.check()
+            .err()
+            .map(|not_until| not_until.wait_time_from(DefaultClock::default().now()))
+    }
+}
+
+/// Limiters for every client key that has a limit configured.
+#[derive(Clone, Default)]
+pub struct KeyRateLimits {
+    keys: Arc<[KeyLimiter]>,
+}
+
+impl KeyRateLimits {
+    /// `pollux_key` uses `basic.rate_limit`; scoped keys use their own
+    /// `rate_limit`, falling back to the same default.
+    #[must_use]
+    pub fn new(basic: &BasicConfig) -> Self {
+        let master = basic
+            .rate_limit
+            .and_then(|cfg| KeyLimiter::new(&basic.pollux_key, "pollux_key", cfg));
+        let scoped = basic.api_keys.iter().filter_map(|k| {
+            k.rate_limit
+                .or(basic.rate_limit)
+                .and_then(|cfg| KeyLimiter::new(&k.key, &k.name, cfg))
+        });
+        Self {
+            keys: master.into_iter().chain(scoped).collect(),
+        }
+    }
+
+    fn find(&self, key: &str) -> Option<&KeyLimiter> {
+        self.keys
+            .iter()
+            .find(|l| bool::from(key.as_bytes().ct_eq(l.key.as_bytes())))
+    }
+}
+
+pub async fn enforce_rate_limit(
+    State(limits): State<KeyRateLimits>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let Some(limiter) =
+        presented_key(req.headers(), req.uri().query()).and_then(|key| limits.find(&key))
+    else {
+        return next.run(req).await;
+    };
+
+    if let Some(wait) = limiter.check() {
+        let path = req.uri().path();
+        warn!(key = %limiter.name, path, "[RateLimit] Client key over its limit");
+        return too_many_requests(
+            path,
+            "rate_limit_exceeded",
+            "Rate limit exceeded for this API key",
+            wait,
+        );
+    }
+
+    match limiter.tokens.clone() {
+        Some(budget) => TOKEN_BUDGET.scope(budget, next.run(req)).await,
+        None => next.run(req).await,
+    }
+}
+
+/// 429 in the error shape the route's clients expect, with `Retry-After`.
+/// `code` only reaches Responses-shaped bodies; Gemini ones carry
+/// `RESOURCE_EXHAUSTED`.
+pub(super) fn too_many_requests(path: &str, code: &str, message: &str, wait: Duration) -> Response {
+    let status = StatusCode::TOO_MANY_REQUESTS;
+    let message = message.to_string();
+    let mut resp = if path.starts_with("/codex/") {
+        CodexError::RequestRejected {
+            status,
+            body: OpenaiResponsesErrorObject {
+                code: Some(code.to_string()),
+                message,
+                r#type: "rate_limit_error".to_string(),
+                param: None,
+            },
+            debug_message: None,
+        }
+        .into_response()
+    } else {
+        GeminiCliError::RequestRejected {
+            status,
+            body: GeminiErrorObject::for_status(status, "RESOURCE_EXHAUSTED", message),
+            debug_message: None,
+        }
+        .into_response()
+    };
+    set_retry_after(&mut resp, wait);
+    resp
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TokenBudget;
+
+    #[test]
+    fn token_budget_rejects_once_spent() {
+        let budget = TokenBudget::new(100);
+        assert!(budget.exhausted_for().is_none());
+        budget.charge(60);
+        assert!(budget.exhausted_for().is_none());
+        budget.charge(60);
+        assert!(budget.exhausted_for().is_some());
+    }
+}
@@ -0,0 +1,111 @@
+//! Guard for the `/<provider>/resource:add` routes (`basic.resource_add`).
+//!
+//! These routes take refresh tokens and start an onboarding job per token,
+//! so they are mounted outside the proxy routers: they check their own key
+//! instead of `RequireKeyAuth`, share one upload rate across providers, and
+//! cap the batch size. Under `basic.coordination` only the leader process
+//! accepts uploads.
+
+use super::auth::{AuthError, presented_key};
+use crate::config::ResourceAddConfig;
+use crate::server::coordination::is_leader;
+use axum::{
+    extract::{Request, State},
+    http::{HeaderValue, StatusCode, header::RETRY_AFTER},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use governor::clock::{Clock, DefaultClock};
+use governor::{DefaultDirectRateLimiter, Quota, RateLimiter};
+use std::num::NonZeroU32;
+use std::sync::Arc;
+use subtle::ConstantTimeEq;
+use tracing::warn;
+
+#[derive(Clone)]
+pub struct ResourceAddGuard {
+    key: Arc<str>,
+    limiter: Option<Arc<DefaultDirectRateLimiter>>,
+    max_batch: usize,
+}
+
+impl ResourceAddGuard {
+    /// `pollux_key` is used when `admin_key` is unset.
+    #[must_use]
+    pub fn new(pollux_key: Arc<str>, cfg: &ResourceAddConfig) -> Self {
+        Self {
+            key: cfg.admin_key.as_deref().map_or(pollux_key, Arc::from),
+            limiter: NonZeroU32::new(cfg.requests_per_minute)
+                .map(|rpm| Arc::new(RateLimiter::direct(Quota::per_minute(rpm)))),
+            max_batch: cfg.max_batch,
+        }
+    }
+
+    /// The `413` for an upload with more entries than `max_batch`, if any.
+    pub(crate) fn reject_batch(&self, len: usize) -> Option<Response> {
+        if len <= self.max_batch {
+            return None;
+        }
+        warn!(
+            entries = len,
+            max_batch = self.max_batch,
+            "[ResourceAdd] Batch too large"
+        );
+        Some(
+            (
+                StatusCode::PAYLOAD_TOO_LARGE,
+                format!(
+                    "Too many entries: at most {} are accepted per request",
+                    self.max_batch
+                ),
+            )
+                .into_response(),
+        )
+    }
+}
+
+pub async fn guard_resource_add(
+    State(guard): State<ResourceAddGuard>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let Some(key) = presented_key(req.headers(), req.uri().query()) else {
+        return AuthError::MissingKey.into_response();
+    };
+    if !bool::from(key.as_bytes().ct_eq(guard.key.as_bytes())) {
+        warn!(path = req.uri().path(), "[ResourceAdd] Rejected key");
+        return AuthError::InvalidKey.into_response();
+    }
+
+    if let Some(limiter) = &guard.limiter
+        && let Err(not_until) = limiter.check()
+    {
+        let wait = not_until.wait_time_from(DefaultClock::default().now());
+        let secs = (wait.as_secs() + u64::from(wait.subsec_nanos() > 0)).max(1);
+        warn!(
+            path = req.uri().path(),
+            "[ResourceAdd] Upload rate exceeded"
+        );
+        return (
+            StatusCode::TOO_MANY_REQUESTS,
+            [(RETRY_AFTER, HeaderValue::from(secs))],
+            "Too many resource:add requests",
+        )
+            .into_response();
+    }
+
+    if !is_leader() {
+        warn!(
+            path = req.uri().path(),
+            "[ResourceAdd] Follower process; onboarding is left to the leader"
+        );
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            [(RETRY_AFTER, HeaderValue::from(5))],
+            "This instance is not the coordination leader; retry shortly",
+        )
+            .into_response();
+    }
+
+    next.run(req).await
+}
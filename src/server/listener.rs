@@ -0,0 +1,184 @@
+//! HTTP listener selection: systemd socket activation, a Unix domain socket
+//! (`basic.listen = "unix:..."`) or TCP.
+//!
+//! A socket handed over by systemd (`LISTEN_PID`/`LISTEN_FDS`) wins over the
+//! configuration, so a unit with `Sockets=` needs no `listen` setting at all.
+
+use crate::config::{BasicConfig, ListenTarget};
+use crate::server::coordination;
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+use tokio::net::TcpListener;
+#[cfg(unix)]
+use tokio::net::UnixListener;
+use tracing::warn;
+
+pub enum HttpListener {
+    Tcp(TcpListener),
+    /// `path` is set when this process created the socket file and should
+    /// remove it on exit; inherited sockets belong to systemd.
+    #[cfg(unix)]
+    Unix {
+        listener: UnixListener,
+        path: Option<PathBuf>,
+    },
+}
+
+impl HttpListener {
+    /// Socket file to remove once the server has stopped.
+    pub fn socket_path(&self) -> Option<PathBuf> {
+        match self {
+            Self::Tcp(_) => None,
+            #[cfg(unix)]
+            Self::Unix { path, .. } => path.clone(),
+        }
+    }
+}
+
+impl fmt::Display for HttpListener {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Tcp(listener) => match listener.local_addr() {
+                Ok(addr) => write!(f, "{addr}"),
+                Err(_) => write!(f, "tcp"),
+            },
+            #[cfg(unix)]
+            Self::Unix { listener, .. } => match listener
+                .local_addr()
+                .ok()
+                .and_then(|addr| addr.as_pathname().map(|p| p.display().to_string()))
+            {
+                Some(path) => write!(f, "unix:{path}"),
+                None => write!(f, "unix socket"),
+            },
+        }
+    }
+}
+
+/// Take the systemd-activated socket if there is one, else bind per `basic`.
+pub fn bind(basic: &BasicConfig) -> io::Result<HttpListener> {
+    #[cfg(unix)]
+    if let Some(listener) = systemd::inherited()? {
+        return Ok(listener);
+    }
+    let target = basic
+        .listen_target()
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+    match target {
+        ListenTarget::Tcp(addr) => Ok(HttpListener::Tcp(coordination::bind_listener(
+            addr,
+            basic.coordination.reuse_port,
+        )?)),
+        #[cfg(unix)]
+        ListenTarget::Unix(path) => {
+            if basic.coordination.reuse_port {
+                warn!("[Listener] reuse_port does not apply to Unix sockets; ignoring");
+            }
+            remove_stale_socket(&path)?;
+            Ok(HttpListener::Unix {
+                listener: UnixListener::bind(&path)?,
+                path: Some(path),
+            })
+        }
+        #[cfg(not(unix))]
+        ListenTarget::Unix(_) => Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "basic.listen: Unix sockets are only supported on Unix",
+        )),
+    }
+}
+
+/// A socket file left behind by a previous run would make `bind` fail.
+#[cfg(unix)]
+fn remove_stale_socket(path: &std::path::Path) -> io::Result<()> {
+    use std::os::unix::fs::FileTypeExt as _;
+
+    match std::fs::symlink_metadata(path) {
+        Ok(meta) if meta.file_type().is_socket() => std::fs::remove_file(path),
+        Ok(_) => Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("{} exists and is not a socket", path.display()),
+        )),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(e),
+    }
+}
+
+#[cfg(unix)]
+mod systemd {
+    use super::HttpListener;
+    use std::io;
+    use std::os::fd::{FromRawFd as _, OwnedFd, RawFd};
+    use tracing::{info, warn};
+
+    /// First descriptor passed by `sd_listen_fds(3)`.
+    const SD_LISTEN_FDS_START: RawFd = 3;
+
+    /// The socket systemd passed to this process, if any.
+    pub(super) fn inherited() -> io::Result<Option<HttpListener>> {
+        let ours = std::env::var("LISTEN_PID")
+            .ok()
+            .and_then(|pid| pid.parse::<u32>().ok())
+            == Some(std::process::id());
+        let count = std::env::var("LISTEN_FDS")
+            .ok()
+            .and_then(|n| n.parse::<u32>().ok())
+            .unwrap_or(0);
+        if !ours || count == 0 {
+            return Ok(None);
+        }
+        if count > 1 {
+            warn!(
+                count,
+                "[Listener] systemd passed several sockets; using the first"
+            );
+        }
+
+        // SAFETY: LISTEN_PID names this process, so systemd handed it
+        // ownership of descriptor 3 and nothing else in Pollux opens it.
+        let fd = unsafe { OwnedFd::from_raw_fd(SD_LISTEN_FDS_START) };
+        let unix = std::os::unix::net::UnixListener::from(fd);
+        let listener = if unix.local_addr().is_ok() {
+            unix.set_nonblocking(true)?;
+            HttpListener::Unix {
+                listener: tokio::net::UnixListener::from_std(unix)?,
+                path: None,
+            }
+        } else {
+            let tcp = std::net::TcpListener::from(OwnedFd::from(unix));
+            tcp.set_nonblocking(true)?;
+            HttpListener::Tcp(tokio::net::TcpListener::from_std(tcp)?)
+        };
+        info!("[Listener] Using socket from systemd activation");
+        Ok(Some(listener))
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn binds_unix_socket_over_a_stale_file() {
+        let path = std::env::temp_dir().join(format!("pollux-{}.sock", uuid::Uuid::new_v4()));
+        let basic = BasicConfig {
+            listen: Some(format!("unix:{}", path.display())),
+            ..BasicConfig::default()
+        };
+
+        let first = bind(&basic).unwrap();
+        assert_eq!(first.socket_path().as_deref(), Some(path.as_path()));
+        assert_eq!(first.to_string(), format!("unix:{}", path.display()));
+        drop(first);
+
+        // The file outlives the listener; binding again must replace it.
+        let second = bind(&basic).unwrap();
+        drop(second);
+        std::fs::remove_file(&path).unwrap();
+
+        std::fs::write(&path, b"not a socket").unwrap();
+        assert!(bind(&basic).is_err());
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,154 @@
+//! Runtime log filter behind `/admin/v1/loglevel`.
+//!
+//! The process-wide `EnvFilter` sits in a [`reload::Layer`], so operators can
+//! raise verbosity for one target (`pollux::providers::codex=debug`) without a
+//! restart. A change may carry a revert delay, after which the startup filter
+//! comes back unless another change was made in the meantime.
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tracing::info;
+use tracing_subscriber::{EnvFilter, Registry, reload};
+
+/// The reloadable filter layer to install on the registry.
+pub type LogFilterLayer = reload::Layer<EnvFilter, Registry>;
+
+#[derive(Debug, Default)]
+struct Pending {
+    /// Bumped on every change so a stale revert does not undo a newer one.
+    generation: u64,
+    reverts_at: Option<DateTime<Utc>>,
+}
+
+/// Handle to the installed filter; clones share state.
+#[derive(Clone)]
+pub struct LogLevelControl {
+    handle: reload::Handle<EnvFilter, Registry>,
+    startup: Arc<str>,
+    pending: Arc<Mutex<Pending>>,
+}
+
+/// Current filter as served by the admin API.
+#[derive(Debug, Clone, Serialize)]
+pub struct LogLevelView {
+    pub filter: String,
+    pub startup_filter: String,
+    /// When the startup filter is restored, if a change is temporary.
+    pub reverts_at: Option<DateTime<Utc>>,
+}
+
+impl LogLevelControl {
+    /// Wrap `filter` for installation and return the control for it.
+    #[must_use]
+    pub fn new(filter: EnvFilter) -> (LogFilterLayer, Self) {
+        let startup = Arc::from(filter.to_string());
+        let (layer, handle) = reload::Layer::new(filter);
+        let control = Self {
+            handle,
+            startup,
+            pending: Arc::default(),
+        };
+        (layer, control)
+    }
+
+    pub fn view(&self) -> LogLevelView {
+        let filter = self
+            .handle
+            .with_current(ToString::to_string)
+            .unwrap_or_default();
+        let reverts_at = self.pending.lock().ok().and_then(|p| p.reverts_at);
+        LogLevelView {
+            filter,
+            startup_filter: self.startup.to_string(),
+            reverts_at,
+        }
+    }
+
+    /// Replace the filter with `directives` (`RUST_LOG` syntax). With
+    /// `revert_after`, the startup filter is restored once it elapses.
+    ///
+    /// Must be called within a Tokio runtime when `revert_after` is set.
+    pub fn set(&self, directives: &str, revert_after: Option<Duration>) -> Result<(), String> {
+        let filter = EnvFilter::try_new(directives).map_err(|e| e.to_string())?;
+        self.handle.reload(filter).map_err(|e| e.to_string())?;
+
+        let generation = {
+            let mut pending = self.pending.lock().map_err(|e| e.to_string())?;
+            pending.generation += 1;
+            pending.reverts_at = revert_after
+                .and_then(|d| chrono::Duration::from_std(d).ok())
+                .map(|d| Utc::now() + d);
+            pending.generation
+        };
+        info!(filter = directives, revert_after = ?revert_after, "Log filter changed");
+
+        if let Some(delay) = revert_after {
+            let control = self.clone();
+            tokio::spawn(async move {
+                tokio::time::sleep(delay).await;
+                control.revert(generation);
+            });
+        }
+        Ok(())
+    }
+
+    /// Restore the startup filter, unless a newer change has been made since
+    /// `generation`.
+    fn revert(&self, generation: u64) {
+        let Ok(mut pending) = self.pending.lock() else {
+            return;
+        };
+        if pending.generation != generation {
+            return;
+        }
+        pending.reverts_at = None;
+        if let Ok(filter) = EnvFilter::try_new(&*self.startup)
+            && self.handle.reload(filter).is_ok()
+        {
+            info!(
+                filter = &*self.startup,
+                "Log filter reverted to startup value"
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tracing_subscriber::layer::SubscriberExt;
+
+    #[tokio::test]
+    async fn temporary_filter_reverts_unless_replaced() {
+        let (layer, control) = LogLevelControl::new(EnvFilter::new("info"));
+        let _subscriber = Registry::default().with(layer);
+
+        assert!(control.set("pollux=loud", None).is_err());
+        assert_eq!(control.view().filter, "info");
+
+        control
+            .set(
+                "info,pollux::providers::codex=debug",
+                Some(Duration::from_millis(50)),
+            )
+            .expect("valid filter");
+        let view = control.view();
+        assert!(view.filter.contains("pollux::providers::codex=debug"));
+        assert!(view.reverts_at.is_some());
+
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        let view = control.view();
+        assert_eq!(view.filter, "info");
+        assert_eq!(view.reverts_at, None);
+
+        // A later change outlives the earlier change's timer.
+        control
+            .set("debug", Some(Duration::from_millis(50)))
+            .expect("valid filter");
+        control.set("warn", None).expect("valid filter");
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        assert_eq!(control.view().filter, "warn");
+    }
+}
@@ -1,5 +1,19 @@
+pub mod alerts;
+pub mod audit_log;
+pub mod coordination;
+pub mod drain;
 pub mod guards;
+pub mod listener;
+pub mod log_level;
+pub mod pool;
+pub mod priority;
+pub mod replay;
+pub mod request_counters;
+pub mod request_events;
 pub mod router;
 pub mod routes;
+pub mod session;
+pub mod sse_flush;
+pub mod stream_capture;
 
 const DEFAULT_API_BODY_LIMIT_BYTES: usize = 50 * 1024 * 1024;
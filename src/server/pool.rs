@@ -0,0 +1,33 @@
+//! Client-selected credential pools.
+//!
+//! Credentials may carry labels (set through `resource:add`). A request
+//! sending `x-pollux-pool: <label>` is only served by credentials carrying
+//! that label; requests without the header may use any credential.
+
+use axum::http::{HeaderMap, HeaderName};
+
+pub static X_POLLUX_POOL: HeaderName = HeaderName::from_static("x-pollux-pool");
+
+/// Label from `x-pollux-pool`, if the client sent a non-empty one.
+pub fn requested_pool(headers: &HeaderMap) -> Option<String> {
+    let value = headers.get(&X_POLLUX_POOL)?.to_str().ok()?.trim();
+    (!value.is_empty()).then(|| value.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    #[test]
+    fn pool_header_is_trimmed_and_blank_means_any() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(requested_pool(&headers), None);
+
+        headers.insert(&X_POLLUX_POOL, HeaderValue::from_static("  "));
+        assert_eq!(requested_pool(&headers), None);
+
+        headers.insert(&X_POLLUX_POOL, HeaderValue::from_static(" team-a "));
+        assert_eq!(requested_pool(&headers).as_deref(), Some("team-a"));
+    }
+}
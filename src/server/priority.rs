@@ -0,0 +1,121 @@
+//! Request priority classes.
+//!
+//! A request sending `x-pollux-priority: high|normal|low` gets that class;
+//! otherwise its key's `api_keys[].priority` applies, and `normal` after that.
+//! A scoped key's class is also a ceiling: its header may lower the class but
+//! not raise it, so only `high` keys reach the reserved credentials.
+//! The class is scoped to the handler through [`REQUEST_PRIORITY`] and read
+//! by the provider pools when they lease a credential.
+
+use super::guards::auth::presented_key;
+use crate::config::{ApiKeyConfig, Priority};
+use axum::{
+    extract::{Request, State},
+    http::{HeaderMap, HeaderName},
+    middleware::Next,
+    response::Response,
+};
+use std::sync::Arc;
+use subtle::ConstantTimeEq;
+
+pub static X_POLLUX_PRIORITY: HeaderName = HeaderName::from_static("x-pollux-priority");
+
+tokio::task_local! {
+    /// Priority class of the request being handled.
+    pub(crate) static REQUEST_PRIORITY: Priority;
+}
+
+/// Class from `x-pollux-priority`, if the client sent a known one.
+pub fn requested_priority(headers: &HeaderMap) -> Option<Priority> {
+    Priority::parse(headers.get(&X_POLLUX_PRIORITY)?.to_str().ok()?)
+}
+
+/// Priority of the current request; `normal` outside a request scope, e.g.
+/// for background work.
+pub(crate) fn current() -> Priority {
+    REQUEST_PRIORITY.try_with(|p| *p).unwrap_or_default()
+}
+
+/// Class of a request sending `requested`, presented with `key`, or with the
+/// `pollux_key` when `key` is `None`.
+fn effective_priority(requested: Option<Priority>, key: Option<&ApiKeyConfig>) -> Priority {
+    let Some(key) = key else {
+        return requested.unwrap_or_default();
+    };
+    // `High` orders first, so the lower class is the greater one.
+    let ceiling = key.priority.unwrap_or_default();
+    requested.map_or(ceiling, |p| p.max(ceiling))
+}
+
+pub async fn scope_priority(
+    State(api_keys): State<Arc<[ApiKeyConfig]>>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let key = presented_key(req.headers(), req.uri().query()).and_then(|key| {
+        api_keys
+            .iter()
+            .find(|k| bool::from(key.as_bytes().ct_eq(k.key.as_bytes())))
+    });
+    let priority = effective_priority(requested_priority(req.headers()), key);
+    REQUEST_PRIORITY.scope(priority, next.run(req)).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    #[test]
+    fn priority_header_is_case_insensitive_and_unknown_means_unset() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(requested_priority(&headers), None);
+
+        headers.insert(&X_POLLUX_PRIORITY, HeaderValue::from_static("urgent"));
+        assert_eq!(requested_priority(&headers), None);
+
+        headers.insert(&X_POLLUX_PRIORITY, HeaderValue::from_static(" High "));
+        assert_eq!(requested_priority(&headers), Some(Priority::High));
+    }
+
+    fn key(priority: Option<Priority>) -> ApiKeyConfig {
+        ApiKeyConfig {
+            name: "ci".to_string(),
+            key: "sk-ci".to_string(),
+            routes: vec!["*".to_string()],
+            rate_limit: None,
+            policy: None,
+            priority,
+        }
+    }
+
+    #[test]
+    fn scoped_keys_may_lower_but_not_raise_their_priority() {
+        let low = key(Some(Priority::Low));
+        assert_eq!(
+            effective_priority(Some(Priority::High), Some(&low)),
+            Priority::Low
+        );
+        assert_eq!(effective_priority(None, Some(&low)), Priority::Low);
+
+        let unset = key(None);
+        assert_eq!(
+            effective_priority(Some(Priority::High), Some(&unset)),
+            Priority::Normal
+        );
+        assert_eq!(
+            effective_priority(Some(Priority::Low), Some(&unset)),
+            Priority::Low
+        );
+
+        let high = key(Some(Priority::High));
+        assert_eq!(
+            effective_priority(Some(Priority::Normal), Some(&high)),
+            Priority::Normal
+        );
+        assert_eq!(
+            effective_priority(Some(Priority::High), None),
+            Priority::High
+        );
+    }
+}
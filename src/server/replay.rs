@@ -0,0 +1,187 @@
+//! `pollux replay`: re-run a captured upstream SSE stream offline.
+//!
+//! Feeds a `.sse` file written by [`stream_capture`](super::stream_capture)
+//! (or any hand-written Gemini CLI envelope stream) through the Gemini CLI or
+//! Antigravity streaming respond pipeline and prints the client-facing event
+//! stream, so translation bugs reproduce without an account or network.
+//! Nothing is recorded: usage rows and thought signatures are skipped.
+
+use crate::config::{Config, StreamTransformerConfig};
+use crate::providers::manifest::ProviderKind;
+use crate::server::routes::{antigravity, geminicli};
+use crate::server::sse_flush::close_with_error_frame;
+use crate::server::stream_capture::parse_header;
+use axum::body::Bytes;
+use axum::response::IntoResponse;
+use axum::response::sse::{Event, Sse};
+use eventsource_stream::Eventsource;
+use futures::{Stream, StreamExt};
+use std::convert::Infallible;
+use std::path::PathBuf;
+
+pub const USAGE: &str = "\
+Usage: pollux replay <FILE> [--provider <geminicli|antigravity>] [--no-transformers]
+
+Runs a captured upstream SSE stream (see basic.stream_capture_dir) through
+the provider's streaming respond pipeline and prints the events a client
+would receive. The provider defaults to the one named in the capture header.
+The provider's configured stream_transformers apply unless --no-transformers
+is given.";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReplayArgs {
+    pub path: PathBuf,
+    pub provider: Option<ProviderKind>,
+    pub transformers: bool,
+}
+
+impl ReplayArgs {
+    /// Parse the arguments following `replay`.
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Self, String> {
+        let mut path = None;
+        let mut provider = None;
+        let mut transformers = true;
+
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--provider" => {
+                    let raw = args
+                        .next()
+                        .ok_or_else(|| "--provider requires a value".to_string())?;
+                    provider = Some(parse_provider(&raw)?);
+                }
+                "--no-transformers" => transformers = false,
+                other if other.starts_with("--") => {
+                    return Err(format!("unexpected argument `{other}`"));
+                }
+                other if path.is_none() => path = Some(PathBuf::from(other)),
+                other => return Err(format!("unexpected argument `{other}`")),
+            }
+        }
+        Ok(Self {
+            path: path.ok_or("a capture file is required")?,
+            provider,
+            transformers,
+        })
+    }
+}
+
+fn parse_provider(raw: &str) -> Result<ProviderKind, String> {
+    match raw {
+        "geminicli" => Ok(ProviderKind::GeminiCli),
+        "antigravity" => Ok(ProviderKind::Antigravity),
+        other => Err(format!(
+            "replay supports geminicli and antigravity, not `{other}`"
+        )),
+    }
+}
+
+/// Replay `args.path` and return the rendered client event stream.
+pub async fn run(cfg: &Config, args: &ReplayArgs) -> Result<String, String> {
+    let capture = std::fs::read_to_string(&args.path)
+        .map_err(|e| format!("cannot read {}: {e}", args.path.display()))?;
+    let provider = if let Some(provider) = args.provider {
+        provider
+    } else {
+        let (provider, _) = parse_header(&capture)
+            .ok_or("no capture header; pass --provider geminicli|antigravity")?;
+        parse_provider(provider.label())?
+    };
+    let transformers = match (args.transformers, provider) {
+        (false, _) => Vec::new(),
+        (true, ProviderKind::Antigravity) => cfg.antigravity().stream_transformers,
+        (true, _) => cfg.geminicli().stream_transformers,
+    };
+    let replayer = Replayer {
+        provider,
+        transformers,
+    };
+    Ok(replayer.render(capture).await)
+}
+
+struct Replayer {
+    provider: ProviderKind,
+    transformers: Vec<StreamTransformerConfig>,
+}
+
+impl Replayer {
+    async fn render(&self, capture: String) -> String {
+        let upstream =
+            futures::stream::iter([Ok::<_, Infallible>(Bytes::from(capture))]).eventsource();
+        let events = match self.provider {
+            ProviderKind::Antigravity => {
+                antigravity::respond::replay_stream(upstream, &self.transformers).boxed()
+            }
+            _ => geminicli::respond::replay_stream(upstream, &self.transformers).boxed(),
+        };
+        render(events).await
+    }
+}
+
+async fn render<E: std::fmt::Display + Send + 'static>(
+    events: impl Stream<Item = Result<Event, E>> + Send + 'static,
+) -> String {
+    let events = close_with_error_frame(events, |e| {
+        Event::default().event("error").data(e.to_string())
+    });
+    let body = Sse::new(events).into_response().into_body();
+    let bytes = axum::body::to_bytes(body, usize::MAX)
+        .await
+        .unwrap_or_default();
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    fn replayer(provider: ProviderKind) -> Replayer {
+        Replayer {
+            provider,
+            transformers: Vec::new(),
+        }
+    }
+
+    /// Every `tests/fixtures/replay/<provider>/*.sse` must translate to the
+    /// `.out` file next to it. Regenerate one with
+    /// `pollux replay <file> --no-transformers > <file minus .sse>.out`.
+    #[tokio::test]
+    async fn fixture_corpus_replays_to_expected_output() {
+        let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/replay");
+        let mut checked = 0;
+        for provider in [ProviderKind::GeminiCli, ProviderKind::Antigravity] {
+            let dir = root.join(provider.label());
+            let mut captures: Vec<_> = std::fs::read_dir(&dir)
+                .unwrap()
+                .map(|entry| entry.unwrap().path())
+                .filter(|path| path.extension().is_some_and(|ext| ext == "sse"))
+                .collect();
+            captures.sort();
+            for capture in captures {
+                let input = std::fs::read_to_string(&capture).unwrap();
+                let expected = std::fs::read_to_string(capture.with_extension("out"))
+                    .unwrap_or_else(|e| panic!("{}: missing .out: {e}", capture.display()));
+                let actual = replayer(provider).render(input).await;
+                assert_eq!(actual, expected, "{}", capture.display());
+                checked += 1;
+            }
+        }
+        assert!(checked >= 2, "fixture corpus is empty");
+    }
+
+    #[test]
+    fn args_take_a_file_and_optional_provider() {
+        let args = ReplayArgs::parse(
+            ["cap.sse", "--provider", "antigravity", "--no-transformers"].map(String::from),
+        )
+        .unwrap();
+        assert_eq!(args.path, PathBuf::from("cap.sse"));
+        assert_eq!(args.provider, Some(ProviderKind::Antigravity));
+        assert!(!args.transformers);
+
+        assert!(ReplayArgs::parse(Vec::new()).is_err());
+        assert!(ReplayArgs::parse(["a", "--provider", "codex"].map(String::from)).is_err());
+    }
+}
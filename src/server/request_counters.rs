@@ -0,0 +1,152 @@
+//! Cumulative request/error counters per (provider, model).
+//!
+//! `access_log` bumps the counters for every request routed to a provider.
+//! Totals are loaded from the `request_counters` table at startup and written
+//! back on an interval (and once more on shutdown), so they keep counting
+//! across deploys instead of starting from zero.
+
+use crate::db::{DbActorHandle, RequestCounterRow};
+use crate::error::PolluxError;
+use axum::http::StatusCode;
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tracing::warn;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+struct Totals {
+    requests: i64,
+    errors: i64,
+}
+
+#[derive(Debug, Default)]
+struct CountersInner {
+    totals: BTreeMap<(String, String), Totals>,
+    /// Set on every change; cleared once the totals are saved.
+    dirty: bool,
+}
+
+/// Shared counters; cheap to clone.
+#[derive(Debug, Clone, Default)]
+pub struct RequestCounters {
+    inner: Arc<Mutex<CountersInner>>,
+}
+
+impl RequestCounters {
+    /// Start from previously persisted totals.
+    #[must_use]
+    pub fn restore(rows: Vec<RequestCounterRow>) -> Self {
+        let totals = rows
+            .into_iter()
+            .map(|row| {
+                let totals = Totals {
+                    requests: row.requests,
+                    errors: row.errors,
+                };
+                ((row.provider, row.model), totals)
+            })
+            .collect();
+        Self {
+            inner: Arc::new(Mutex::new(CountersInner {
+                totals,
+                dirty: false,
+            })),
+        }
+    }
+
+    pub fn record(&self, provider: &str, model: &str, status: StatusCode) {
+        let Ok(mut inner) = self.inner.lock() else {
+            return;
+        };
+        let totals = inner
+            .totals
+            .entry((provider.to_string(), model.to_string()))
+            .or_default();
+        totals.requests += 1;
+        if status.is_client_error() || status.is_server_error() {
+            totals.errors += 1;
+        }
+        inner.dirty = true;
+    }
+
+    /// Current totals, ordered by provider then model.
+    #[must_use]
+    pub fn snapshot(&self) -> Vec<RequestCounterRow> {
+        self.inner
+            .lock()
+            .map(|inner| rows(&inner.totals))
+            .unwrap_or_default()
+    }
+
+    /// Save the totals if anything changed since the last save.
+    pub async fn flush(&self, db: &DbActorHandle) -> Result<(), PolluxError> {
+        let pending = {
+            let Ok(mut inner) = self.inner.lock() else {
+                return Ok(());
+            };
+            if !inner.dirty {
+                return Ok(());
+            }
+            inner.dirty = false;
+            rows(&inner.totals)
+        };
+        let result = db.save_request_counters(pending).await;
+        if result.is_err()
+            && let Ok(mut inner) = self.inner.lock()
+        {
+            inner.dirty = true;
+        }
+        result
+    }
+
+    /// Flush every `interval` until the runtime shuts down.
+    pub fn spawn_flusher(&self, db: DbActorHandle, interval: Duration) {
+        let counters = self.clone();
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.tick().await;
+            loop {
+                ticker.tick().await;
+                if let Err(e) = counters.flush(&db).await {
+                    warn!(error = %e, "Failed to persist request counters");
+                }
+            }
+        });
+    }
+}
+
+fn rows(totals: &BTreeMap<(String, String), Totals>) -> Vec<RequestCounterRow> {
+    totals
+        .iter()
+        .map(|((provider, model), t)| RequestCounterRow {
+            provider: provider.clone(),
+            model: model.clone(),
+            requests: t.requests,
+            errors: t.errors,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn restored_totals_keep_counting() {
+        let counters = RequestCounters::restore(vec![RequestCounterRow {
+            provider: "codex".to_string(),
+            model: "gpt-5".to_string(),
+            requests: 10,
+            errors: 2,
+        }]);
+        counters.record("codex", "gpt-5", StatusCode::OK);
+        counters.record("codex", "gpt-5", StatusCode::TOO_MANY_REQUESTS);
+        counters.record("geminicli", "gemini-2.5-pro", StatusCode::OK);
+
+        let snapshot = counters.snapshot();
+        assert_eq!(snapshot.len(), 2);
+        assert_eq!((snapshot[0].requests, snapshot[0].errors), (12, 3));
+        assert_eq!(snapshot[1].provider, "geminicli");
+        assert_eq!((snapshot[1].requests, snapshot[1].errors), (1, 0));
+    }
+}
@@ -0,0 +1,227 @@
+//! Live request/outcome events for operators (`/admin/v1/logs/stream`).
+//!
+//! `access_log` publishes one [`RequestEvent`] per completed request onto a
+//! broadcast bus. Nothing is buffered for absent subscribers; slow subscribers
+//! are told how many events they missed instead of back-pressuring requests.
+
+use crate::utils::request_hash::canonical_request_hash;
+use axum::http::StatusCode;
+use chrono::{DateTime, Utc};
+use pollux_thoughtsig_core::PatchSummary;
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, OnceLock};
+use tokio::sync::broadcast;
+
+const EVENT_BUS_CAPACITY: usize = 1024;
+
+/// One completed request, as seen by the access log.
+#[derive(Debug, Clone, Serialize)]
+pub struct RequestEvent {
+    pub ts: DateTime<Utc>,
+    pub request_id: String,
+    pub method: String,
+    pub path: String,
+    pub provider: Option<&'static str>,
+    pub model: Option<String>,
+    /// Canonical payload hash, also returned as `x-pollux-request-hash`.
+    pub request_hash: Option<String>,
+    pub status: u16,
+    /// Time to response headers; for SSE this excludes the stream body.
+    pub latency_ms: u64,
+}
+
+/// Request-scoped slots that extractors fill once the body is parsed.
+///
+/// Inserted into request extensions by `access_log` and read back once the
+/// response is ready, so the event carries the model without re-parsing bodies.
+#[derive(Debug, Clone, Default)]
+pub struct RequestMeta {
+    model: Arc<OnceLock<String>>,
+    request_hash: Arc<OnceLock<String>>,
+    thoughtsig: Arc<OnceLock<PatchSummary>>,
+}
+
+impl RequestMeta {
+    pub fn set_model(&self, model: &str) {
+        let _ = self.model.set(model.to_string());
+    }
+
+    pub fn model(&self) -> Option<String> {
+        self.model.get().cloned()
+    }
+
+    /// Record the canonical hash of the client payload (see `x-pollux-request-hash`).
+    pub fn hash_request<T: Serialize>(&self, model: &str, body: &T) {
+        if let Some(hash) = canonical_request_hash(model, body) {
+            let _ = self.request_hash.set(hash);
+        }
+    }
+
+    pub fn request_hash(&self) -> Option<&str> {
+        self.request_hash.get().map(String::as_str)
+    }
+
+    /// Record thought-signature patch counts (see `x-pollux-thoughtsig`).
+    pub fn set_thoughtsig(&self, summary: PatchSummary) {
+        let _ = self.thoughtsig.set(summary);
+    }
+
+    pub fn thoughtsig(&self) -> Option<PatchSummary> {
+        self.thoughtsig.get().copied()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RequestEventBus {
+    tx: broadcast::Sender<RequestEvent>,
+}
+
+impl Default for RequestEventBus {
+    fn default() -> Self {
+        let (tx, _) = broadcast::channel(EVENT_BUS_CAPACITY);
+        Self { tx }
+    }
+}
+
+impl RequestEventBus {
+    /// Whether anyone is listening; lets callers skip building events.
+    pub fn has_subscribers(&self) -> bool {
+        self.tx.receiver_count() > 0
+    }
+
+    pub fn publish(&self, event: RequestEvent) {
+        let _ = self.tx.send(event);
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<RequestEvent> {
+        self.tx.subscribe()
+    }
+}
+
+/// Provider label derived from the route prefix.
+pub fn provider_from_path(path: &str) -> Option<&'static str> {
+    let first = path.trim_start_matches('/').split('/').next()?;
+    match first {
+        "geminicli" => Some("geminicli"),
+        "codex" => Some("codex"),
+        "antigravity" => Some("antigravity"),
+        "claude" => Some("claude"),
+        "qwen" => Some("qwen"),
+        "admin" => Some("admin"),
+        _ => None,
+    }
+}
+
+/// Query filters for the live stream. All set filters must match.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct RequestEventFilter {
+    pub provider: Option<String>,
+    pub model: Option<String>,
+    /// Only emit events with `status >= min_status` (e.g. `400` for failures only).
+    pub min_status: Option<u16>,
+}
+
+impl RequestEventFilter {
+    pub fn matches(&self, event: &RequestEvent) -> bool {
+        if let Some(provider) = self.provider.as_deref()
+            && event.provider != Some(provider)
+        {
+            return false;
+        }
+        if let Some(model) = self.model.as_deref()
+            && event.model.as_deref() != Some(model)
+        {
+            return false;
+        }
+        self.min_status.is_none_or(|min| event.status >= min)
+    }
+}
+
+impl RequestEvent {
+    pub fn new(
+        request_id: String,
+        method: &str,
+        path: &str,
+        meta: &RequestMeta,
+        status: StatusCode,
+        latency_ms: u64,
+    ) -> Self {
+        Self {
+            ts: Utc::now(),
+            request_id,
+            method: method.to_string(),
+            path: path.to_string(),
+            provider: provider_from_path(path),
+            model: meta.model(),
+            request_hash: meta.request_hash().map(ToString::to_string),
+            status: status.as_u16(),
+            latency_ms,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(path: &str, model: Option<&str>, status: StatusCode) -> RequestEvent {
+        let meta = RequestMeta::default();
+        if let Some(model) = model {
+            meta.set_model(model);
+        }
+        RequestEvent::new("rid".to_string(), "POST", path, &meta, status, 5)
+    }
+
+    #[test]
+    fn provider_is_taken_from_route_prefix() {
+        assert_eq!(provider_from_path("/codex/v1/responses"), Some("codex"));
+        assert_eq!(
+            provider_from_path("/geminicli/v1beta/models/x:generateContent"),
+            Some("geminicli")
+        );
+        assert_eq!(provider_from_path("/claude/v1/messages"), Some("claude"));
+        assert_eq!(provider_from_path("/oauth2callback"), None);
+    }
+
+    #[test]
+    fn filter_combines_provider_model_and_min_status() {
+        let ok = event("/codex/v1/responses", Some("gpt-5"), StatusCode::OK);
+        let failed = event(
+            "/codex/v1/responses",
+            Some("gpt-5"),
+            StatusCode::SERVICE_UNAVAILABLE,
+        );
+        let other = event(
+            "/antigravity/v1beta/models/m:generateContent",
+            Some("m"),
+            StatusCode::BAD_REQUEST,
+        );
+
+        assert!(RequestEventFilter::default().matches(&ok));
+
+        let errors_only = RequestEventFilter {
+            min_status: Some(400),
+            ..Default::default()
+        };
+        assert!(!errors_only.matches(&ok));
+        assert!(errors_only.matches(&failed));
+
+        let codex_errors = RequestEventFilter {
+            provider: Some("codex".to_string()),
+            model: Some("gpt-5".to_string()),
+            min_status: Some(400),
+        };
+        assert!(codex_errors.matches(&failed));
+        assert!(!codex_errors.matches(&other));
+        assert!(!codex_errors.matches(&ok));
+    }
+
+    #[test]
+    fn request_meta_keeps_first_model() {
+        let meta = RequestMeta::default();
+        assert_eq!(meta.model(), None);
+        meta.set_model("a");
+        meta.clone().set_model("b");
+        assert_eq!(meta.model().as_deref(), Some("a"));
+    }
+}
@@ -1,21 +1,56 @@
+use crate::config::{
+    ApiKeyConfig, HttpClientConfig, ResourceAddConfig, RoutingConfig, SseFlushConfig,
+};
+use crate::model_catalog::consistency::ModelConsistencyReport;
 use crate::providers::Providers;
 use crate::providers::antigravity::ANTIGRAVITY_USER_AGENT;
+use crate::providers::antigravity::discovery::ModelDiscovery;
+use crate::providers::claude::CLAUDE_USER_AGENT;
+use crate::providers::claude::client::ClaudeClient;
 use crate::providers::codex::CODEX_USER_AGENT;
 use crate::providers::codex::client::CodexClient;
 use crate::providers::geminicli::client::GeminiClient;
 use crate::providers::geminicli::{GEMINICLI_USER_AGENT, GOOGLE_AUTH_LIB_USER_AGENT};
-use crate::server::guards::auth::RequireKeyAuth;
+use crate::providers::manifest::ProviderKind;
+use crate::providers::qwen::QWEN_USER_AGENT;
+use crate::providers::qwen::client::QwenClient;
+use crate::server::audit_log::{AUDIT_SCOPE, AuditLog, AuditScope};
+use crate::server::drain::{ShutdownDrain, track_in_flight};
+use crate::server::guards::auth::{RequireKeyAuth, RequireMasterKey, presented_key};
+use crate::server::guards::concurrency::{ConcurrencyLimit, limit_concurrency};
+use crate::server::guards::rate_limit::{KeyRateLimits, enforce_rate_limit};
+use crate::server::guards::resource_add::{ResourceAddGuard, guard_resource_add};
+use crate::server::log_level::LogLevelControl;
+use crate::server::priority::scope_priority;
+use crate::server::request_counters::RequestCounters;
+use crate::server::request_events::{
+    RequestEvent, RequestEventBus, RequestMeta, provider_from_path,
+};
 use crate::server::routes::antigravity::oauth::{
     antigravity_oauth_callback_root, antigravity_oauth_entry,
 };
-use crate::server::routes::codex::oauth::{codex_oauth_callback, codex_oauth_entry};
+use crate::server::routes::codex::device::DeviceFlows;
+use crate::server::routes::codex::oauth::{
+    codex_device_status, codex_oauth_callback, codex_oauth_entry,
+};
 use crate::server::routes::geminicli::oauth::{google_oauth_callback, google_oauth_entry};
-use crate::server::routes::{antigravity, codex, geminicli};
+use crate::server::routes::mirror::Mirror;
+use crate::server::routes::qwen::device::DeviceFlows as QwenDeviceFlows;
+use crate::server::routes::qwen::oauth::qwen_device_status;
+use crate::server::routes::{admin, antigravity, claude, codex, geminicli, health, qwen, unified};
+use crate::server::sse_flush::sse_flush;
+use crate::server::stream_capture::StreamCapture;
+use crate::utils::dns::with_resolver;
+use crate::utils::http::{BoundClients, EgressClients, tune};
 
 use axum::{
     Router,
-    extract::{FromRef, Request},
-    http::{HeaderName, StatusCode, Version, header::USER_AGENT},
+    body::{Body, HttpBody},
+    extract::{FromRef, Request, State},
+    http::{
+        HeaderName, StatusCode, Version,
+        header::{CONTENT_LENGTH, CONTENT_TYPE, USER_AGENT},
+    },
     middleware::{self, Next},
     response::Response,
     routing::get,
@@ -27,13 +62,21 @@ use reqwest::header::{CONNECTION, HeaderMap, HeaderValue};
 use std::time::Instant;
 use std::{sync::Arc, sync::LazyLock, time::Duration};
 use tower_http::decompression::RequestDecompressionLayer;
-use tracing::{error, info, warn};
+use tracing::{Instrument, error, info, info_span, warn};
 
 /// Global cookie signing/encryption key for `PrivateCookieJar`.
 static COOKIE_KEY: LazyLock<Key> = LazyLock::new(Key::generate);
 
 const MAX_REQUEST_ID_LEN: usize = 128;
+/// Total timeout for unary upstream calls; streams have none.
+const REQUEST_TIMEOUT: Duration = Duration::from_mins(10);
 const X_REQUEST_ID: HeaderName = HeaderName::from_static("x-request-id");
+const X_POLLUX_REQUEST_ID: HeaderName = HeaderName::from_static("x-pollux-request-id");
+/// Error bodies larger than this are passed on without a `request_id`.
+const MAX_TAGGED_ERROR_BODY: u64 = 64 * 1024;
+const X_POLLUX_REQUEST_HASH: HeaderName = HeaderName::from_static("x-pollux-request-hash");
+/// Set when `providers.<p>.thoughtsig.report_outcomes` is on.
+const X_POLLUX_THOUGHTSIG: HeaderName = HeaderName::from_static("x-pollux-thoughtsig");
 
 fn generate_request_id() -> String {
     // 96 bits => 16 chars base64url (no padding).
@@ -42,6 +85,62 @@ fn generate_request_id() -> String {
     base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(bytes)
 }
 
+/// Headers derived from what the extractors recorded in [`RequestMeta`].
+fn insert_meta_headers(resp: &mut Response, meta: &RequestMeta) {
+    if let Some(value) = meta
+        .request_hash()
+        .and_then(|h| HeaderValue::from_str(h).ok())
+    {
+        resp.headers_mut().insert(X_POLLUX_REQUEST_HASH, value);
+    }
+    if let Some(value) = meta
+        .thoughtsig()
+        .and_then(|summary| HeaderValue::from_str(&summary.to_string()).ok())
+    {
+        resp.headers_mut().insert(X_POLLUX_THOUGHTSIG, value);
+    }
+}
+
+/// Stamp `request_id` into a JSON `{"error": {...}}` body, so a user can
+/// quote the failing request and an operator can grep for it.
+async fn tag_error_body(resp: Response, request_id: &str) -> Response {
+    let is_json = resp
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.starts_with("application/json"));
+    let small = resp
+        .body()
+        .size_hint()
+        .exact()
+        .is_some_and(|len| len <= MAX_TAGGED_ERROR_BODY);
+    if !is_json || !small {
+        return resp;
+    }
+
+    let (mut parts, body) = resp.into_parts();
+    let Ok(bytes) = axum::body::to_bytes(body, usize::MAX).await else {
+        return Response::from_parts(parts, Body::empty());
+    };
+    let Ok(mut value) = serde_json::from_slice::<serde_json::Value>(&bytes) else {
+        return Response::from_parts(parts, Body::from(bytes));
+    };
+    let Some(error) = value
+        .get_mut("error")
+        .and_then(serde_json::Value::as_object_mut)
+    else {
+        return Response::from_parts(parts, Body::from(bytes));
+    };
+    error.insert("request_id".to_string(), request_id.into());
+    match serde_json::to_vec(&value) {
+        Ok(tagged) => {
+            parts.headers.remove(CONTENT_LENGTH);
+            Response::from_parts(parts, Body::from(tagged))
+        }
+        Err(_) => Response::from_parts(parts, Body::from(bytes)),
+    }
+}
+
 fn format_http_version(version: Version) -> &'static str {
     match version {
         Version::HTTP_09 => "HTTP/0.9",
@@ -59,11 +158,50 @@ pub struct PolluxState {
     pub geminicli_client: reqwest::Client,
     pub codex_client: reqwest::Client,
     pub antigravity_client: reqwest::Client,
-    pub antigravity_stream_client: reqwest::Client,
+    /// Qwen OAuth client for the device-code and token endpoints.
+    pub qwen_client: reqwest::Client,
+    /// Antigravity API callers, one pair per egress proxy.
+    pub(crate) antigravity_egress: EgressClients,
     pub(crate) geminicli_caller: GeminiClient,
     pub(crate) codex_caller: CodexClient,
+    pub(crate) claude_caller: ClaudeClient,
+    pub(crate) qwen_caller: QwenClient,
     pub pollux_key: Arc<str>,
     pub insecure_cookie: bool,
+    /// Route-scoped keys accepted alongside `pollux_key`.
+    pub api_keys: Arc<[ApiKeyConfig]>,
+    /// Per-key request and token limits on the provider routes.
+    pub rate_limits: KeyRateLimits,
+    /// Server-wide cap on proxied requests in flight.
+    pub concurrency_limit: ConcurrencyLimit,
+    /// Key, rate and batch limits for `/<provider>/resource:add`.
+    pub resource_add: ResourceAddGuard,
+    /// Completed-request events for `/admin/v1/logs/stream`.
+    pub request_events: RequestEventBus,
+    /// Cumulative per-model request totals, persisted across restarts.
+    pub request_counters: RequestCounters,
+    /// Write batching for streamed responses.
+    pub sse_flush: SseFlushConfig,
+    /// Startup comparison of configured models with the previous boot.
+    pub model_report: Arc<ModelConsistencyReport>,
+    /// JSON-lines record of proxied requests (`basic.audit_log_path`).
+    pub audit_log: Option<AuditLog>,
+    /// Raw upstream SSE copies for `pollux replay` (`basic.stream_capture_dir`).
+    pub stream_capture: Option<StreamCapture>,
+    /// In-flight tracking used to drain requests on shutdown.
+    pub drain: ShutdownDrain,
+    /// Codex device-code logins started via `/codex/oauth/start`.
+    pub codex_device_flows: DeviceFlows,
+    /// Qwen device-code logins started via `/qwen/oauth/start`.
+    pub qwen_device_flows: QwenDeviceFlows,
+    /// Cross-provider failover order (`routing`).
+    pub routing: Arc<RoutingConfig>,
+    /// Shadow traffic and its outcome counters (`routing.mirror`).
+    pub mirror: Mirror,
+    /// Live Antigravity model availability (`providers.antigravity.model_discovery`).
+    pub antigravity_models: ModelDiscovery,
+    /// Reloadable tracing filter for `/admin/v1/loglevel`, when installed.
+    pub log_level: Option<LogLevelControl>,
 }
 
 impl PolluxState {
@@ -71,11 +209,12 @@ impl PolluxState {
         user_agent: Option<&str>,
         proxy: Option<url::Url>,
         enable_multiplexing: bool,
+        http: &HttpClientConfig,
         total_timeout: Option<Duration>,
     ) -> reqwest::Client {
         let mut headers = HeaderMap::new();
 
-        let mut builder = reqwest::Client::builder()
+        let mut builder = with_resolver(reqwest::Client::builder())
             .redirect(reqwest::redirect::Policy::none())
             .connect_timeout(Duration::from_secs(10));
 
@@ -103,6 +242,7 @@ impl PolluxState {
                 .pool_max_idle_per_host(0)
                 .pool_idle_timeout(Duration::from_secs(0));
         }
+        builder = tune(builder, http, enable_multiplexing);
 
         builder
             .default_headers(headers)
@@ -110,26 +250,114 @@ impl PolluxState {
             .expect("failed to build reqwest client")
     }
 
+    /// Unary and streaming API caller clients through `proxy`.
+    fn build_pair(
+        user_agent: Option<&str>,
+        proxy: Option<&url::Url>,
+        enable_multiplexing: bool,
+        http: &HttpClientConfig,
+    ) -> (reqwest::Client, reqwest::Client) {
+        (
+            Self::build_client(
+                user_agent,
+                proxy.cloned(),
+                enable_multiplexing,
+                http,
+                Some(REQUEST_TIMEOUT),
+            ),
+            Self::build_client(user_agent, proxy.cloned(), enable_multiplexing, http, None),
+        )
+    }
+
+    /// API caller clients for a provider: one pair per `proxy_pool` entry, or
+    /// a single pair through `proxy` without a pool. Credentials bound to
+    /// their own proxy get a pair built on first use.
+    fn build_egress(
+        user_agent: Option<&str>,
+        proxy: Option<&url::Url>,
+        proxy_pool: &[url::Url],
+        enable_multiplexing: bool,
+        http: &HttpClientConfig,
+    ) -> EgressClients {
+        let egress = if proxy_pool.is_empty() {
+            let (client, stream_client) =
+                Self::build_pair(user_agent, proxy, enable_multiplexing, http);
+            EgressClients::single(client, stream_client)
+        } else {
+            EgressClients::new(
+                proxy_pool
+                    .iter()
+                    .map(|url| Self::build_pair(user_agent, Some(url), enable_multiplexing, http))
+                    .collect(),
+            )
+        };
+        let user_agent = user_agent.map(str::to_owned);
+        let http = *http;
+        egress.with_bound(BoundClients::new(move |url| {
+            Self::build_pair(user_agent.as_deref(), Some(url), enable_multiplexing, &http)
+        }))
+    }
+
+    /// API caller clients that connect directly, ignoring every proxy.
+    fn build_direct(
+        user_agent: Option<&str>,
+        enable_multiplexing: bool,
+        http: &HttpClientConfig,
+    ) -> EgressClients {
+        let (client, stream_client) = Self::build_pair(user_agent, None, enable_multiplexing, http);
+        EgressClients::single(client, stream_client)
+    }
+
     #[must_use]
     #[allow(clippy::too_many_lines)]
     pub fn new(providers: Providers, pollux_key: Arc<str>, insecure_cookie: bool) -> Self {
         let geminicli_cfg = providers.geminicli_cfg.clone();
         let codex_cfg = providers.codex_cfg.clone();
         let antigravity_cfg = providers.antigravity_cfg.clone();
+        let claude_cfg = providers.claude_cfg.clone();
+        let qwen_cfg = providers.qwen_cfg.clone();
 
         let geminicli_default_url: url::Url =
             "https://cloudcode-pa.googleapis.com".parse().unwrap();
         let codex_default_url: url::Url = "https://chatgpt.com".parse().unwrap();
+        let claude_default_url: url::Url = "https://api.anthropic.com".parse().unwrap();
 
         let geminicli_has_custom_url = geminicli_cfg.custom_api_url != geminicli_default_url;
         let codex_has_custom_url = codex_cfg.custom_api_url != codex_default_url;
-        let request_timeout = Some(Duration::from_mins(10));
-        let stream_timeout = None;
+        let claude_has_custom_url = claude_cfg.custom_api_url != claude_default_url;
+        let request_timeout = Some(REQUEST_TIMEOUT);
+
+        for (provider, multiplexing, http) in [
+            (
+                "geminicli",
+                geminicli_cfg.enable_multiplexing,
+                &geminicli_cfg.http_client,
+            ),
+            (
+                "codex",
+                codex_cfg.enable_multiplexing,
+                &codex_cfg.http_client,
+            ),
+            (
+                "antigravity",
+                antigravity_cfg.enable_multiplexing,
+                &antigravity_cfg.http_client,
+            ),
+            (
+                "claude",
+                claude_cfg.enable_multiplexing,
+                &claude_cfg.http_client,
+            ),
+            ("qwen", qwen_cfg.enable_multiplexing, &qwen_cfg.http_client),
+        ] {
+            info!(provider, multiplexing, http_client = ?http, "Upstream HTTP client settings");
+        }
 
         let geminicli_client = Self::build_client(
             Some(GOOGLE_AUTH_LIB_USER_AGENT),
             geminicli_cfg.proxy.clone(),
             geminicli_cfg.enable_multiplexing,
+            &geminicli_cfg.http_client,
             request_timeout,
         );
         // Codex OAuth client: no User-Agent, matching upstream codex-rs which
@@ -138,110 +366,270 @@ impl PolluxState {
             None,
             codex_cfg.proxy.clone(),
             codex_cfg.enable_multiplexing,
+            &codex_cfg.http_client,
             request_timeout,
         );
         let antigravity_client = Self::build_client(
             Some(ANTIGRAVITY_USER_AGENT),
             antigravity_cfg.proxy.clone(),
             antigravity_cfg.enable_multiplexing,
+            &antigravity_cfg.http_client,
+            request_timeout,
+        );
+        let qwen_client = Self::build_client(
+            Some(QWEN_USER_AGENT),
+            qwen_cfg.proxy.clone(),
+            qwen_cfg.enable_multiplexing,
+            &qwen_cfg.http_client,
             request_timeout,
         );
-        let antigravity_stream_client = Self::build_client(
+        let antigravity_egress = Self::build_egress(
             Some(ANTIGRAVITY_USER_AGENT),
-            antigravity_cfg.proxy.clone(),
+            antigravity_cfg.proxy.as_ref(),
+            &antigravity_cfg.proxy_pool,
             antigravity_cfg.enable_multiplexing,
-            stream_timeout,
+            &antigravity_cfg.http_client,
         );
 
         // When a custom_api_url is set it acts as a reverse proxy, so the
-        // caller should bypass the configured proxy and connect directly.
-        let geminicli_caller_client = if geminicli_has_custom_url {
-            Self::build_client(
+        // caller should bypass the configured proxies (including per-credential
+        // bindings) and connect directly.
+        let geminicli_caller_egress = if geminicli_has_custom_url {
+            Self::build_direct(
                 Some(GEMINICLI_USER_AGENT),
-                None,
                 geminicli_cfg.enable_multiplexing,
-                request_timeout,
+                &geminicli_cfg.http_client,
             )
         } else {
-            Self::build_client(
+            Self::build_egress(
                 Some(GEMINICLI_USER_AGENT),
-                geminicli_cfg.proxy.clone(),
+                geminicli_cfg.proxy.as_ref(),
+                &geminicli_cfg.proxy_pool,
                 geminicli_cfg.enable_multiplexing,
-                request_timeout,
-            )
-        };
-        let geminicli_caller_stream_client = if geminicli_has_custom_url {
-            Self::build_client(
-                Some(GEMINICLI_USER_AGENT),
-                None,
-                geminicli_cfg.enable_multiplexing,
-                stream_timeout,
-            )
-        } else {
-            Self::build_client(
-                Some(GEMINICLI_USER_AGENT),
-                geminicli_cfg.proxy.clone(),
-                geminicli_cfg.enable_multiplexing,
-                stream_timeout,
+                &geminicli_cfg.http_client,
             )
         };
         // API caller always uses the full Codex UA regardless of custom URL.
-        let codex_caller_client = if codex_has_custom_url {
-            Self::build_client(
+        let codex_caller_egress = if codex_has_custom_url {
+            Self::build_direct(
                 Some(CODEX_USER_AGENT),
-                None,
                 codex_cfg.enable_multiplexing,
-                request_timeout,
+                &codex_cfg.http_client,
             )
         } else {
-            Self::build_client(
+            Self::build_egress(
                 Some(CODEX_USER_AGENT),
-                codex_cfg.proxy.clone(),
+                codex_cfg.proxy.as_ref(),
+                &codex_cfg.proxy_pool,
                 codex_cfg.enable_multiplexing,
-                request_timeout,
+                &codex_cfg.http_client,
             )
         };
-        let codex_caller_stream_client = if codex_has_custom_url {
-            Self::build_client(
-                Some(CODEX_USER_AGENT),
-                None,
-                codex_cfg.enable_multiplexing,
-                stream_timeout,
+
+        let claude_caller_egress = if claude_has_custom_url {
+            Self::build_direct(
+                Some(CLAUDE_USER_AGENT),
+                claude_cfg.enable_multiplexing,
+                &claude_cfg.http_client,
             )
         } else {
-            Self::build_client(
-                Some(CODEX_USER_AGENT),
-                codex_cfg.proxy.clone(),
-                codex_cfg.enable_multiplexing,
-                stream_timeout,
+            Self::build_egress(
+                Some(CLAUDE_USER_AGENT),
+                claude_cfg.proxy.as_ref(),
+                &claude_cfg.proxy_pool,
+                claude_cfg.enable_multiplexing,
+                &claude_cfg.http_client,
+            )
+        };
+
+        let qwen_caller_egress = if qwen_cfg.custom_api_url.is_some() {
+            Self::build_direct(
+                Some(QWEN_USER_AGENT),
+                qwen_cfg.enable_multiplexing,
+                &qwen_cfg.http_client,
+            )
+        } else {
+            Self::build_egress(
+                Some(QWEN_USER_AGENT),
+                qwen_cfg.proxy.as_ref(),
+                &qwen_cfg.proxy_pool,
+                qwen_cfg.enable_multiplexing,
+                &qwen_cfg.http_client,
             )
         };
 
         let geminicli_caller = GeminiClient::new(
-            geminicli_caller_client,
-            geminicli_caller_stream_client,
+            geminicli_caller_egress,
             &geminicli_cfg.custom_api_url,
             geminicli_cfg.retry_max_times,
             geminicli_cfg.trace_header.clone(),
-        );
+        )
+        .with_error_clusters(providers.error_clusters.clone())
+        .with_system_instruction(geminicli_cfg.system_instruction.clone())
+        .with_stream_only_models(geminicli_cfg.stream_only_models.clone())
+        .with_model_overrides(geminicli_cfg.model_overrides.clone());
         let codex_caller = CodexClient::new(
-            codex_caller_client,
-            codex_caller_stream_client,
+            codex_caller_egress,
             &codex_cfg.custom_api_url,
             codex_cfg.retry_max_times,
             codex_cfg.trace_header.clone(),
-        );
+        )
+        .with_error_clusters(providers.error_clusters.clone());
+        let claude_caller = ClaudeClient::new(
+            claude_caller_egress,
+            &claude_cfg.custom_api_url,
+            claude_cfg.retry_max_times,
+            claude_cfg.trace_header.clone(),
+        )
+        .with_error_clusters(providers.error_clusters.clone());
+        let qwen_caller = QwenClient::new(
+            qwen_caller_egress,
+            qwen_cfg.custom_api_url.as_ref(),
+            qwen_cfg.retry_max_times,
+            qwen_cfg.trace_header.clone(),
+        )
+        .with_error_clusters(providers.error_clusters.clone());
 
         Self {
             providers,
             geminicli_client,
             codex_client,
             antigravity_client,
-            antigravity_stream_client,
+            qwen_client,
+            antigravity_egress,
             geminicli_caller,
             codex_caller,
+            claude_caller,
+            qwen_caller,
+            resource_add: ResourceAddGuard::new(pollux_key.clone(), &ResourceAddConfig::default()),
             pollux_key,
             insecure_cookie,
+            api_keys: Arc::from([]),
+            rate_limits: KeyRateLimits::default(),
+            concurrency_limit: ConcurrencyLimit::default(),
+            request_events: RequestEventBus::default(),
+            request_counters: RequestCounters::default(),
+            sse_flush: SseFlushConfig::default(),
+            model_report: Arc::default(),
+            audit_log: None,
+            stream_capture: None,
+            drain: ShutdownDrain::default(),
+            codex_device_flows: DeviceFlows::default(),
+            qwen_device_flows: QwenDeviceFlows::default(),
+            routing: Arc::default(),
+            mirror: Mirror::default(),
+            antigravity_models: ModelDiscovery::default(),
+            log_level: None,
+        }
+    }
+
+    /// Accept additional route-scoped keys (`basic.api_keys`).
+    #[must_use]
+    pub fn with_api_keys(mut self, api_keys: Vec<ApiKeyConfig>) -> Self {
+        self.api_keys = Arc::from(api_keys);
+        self
+    }
+
+    /// Enforce `basic.rate_limit` / `api_keys[].rate_limit`.
+    #[must_use]
+    pub fn with_rate_limits(mut self, rate_limits: KeyRateLimits) -> Self {
+        self.rate_limits = rate_limits;
+        self
+    }
+
+    /// Enforce `basic.concurrency_limit`.
+    #[must_use]
+    pub fn with_concurrency_limit(mut self, limit: ConcurrencyLimit) -> Self {
+        self.concurrency_limit = limit;
+        self
+    }
+
+    /// Apply `basic.resource_add`.
+    #[must_use]
+    pub fn with_resource_add(mut self, cfg: &ResourceAddConfig) -> Self {
+        self.resource_add = ResourceAddGuard::new(self.pollux_key.clone(), cfg);
+        self
+    }
+
+    /// Apply `routing`.
+    #[must_use]
+    pub fn with_routing(mut self, cfg: RoutingConfig) -> Self {
+        self.mirror = Mirror::new(cfg.mirror.clone());
+        self.routing = Arc::new(cfg);
+        self
+    }
+
+    /// Apply `providers.sse`.
+    #[must_use]
+    pub fn with_sse_flush(mut self, cfg: SseFlushConfig) -> Self {
+        self.sse_flush = cfg;
+        self
+    }
+
+    /// Serve the report produced by `model_catalog::consistency::check`.
+    #[must_use]
+    pub fn with_model_report(mut self, report: ModelConsistencyReport) -> Self {
+        self.model_report = Arc::new(report);
+        self
+    }
+
+    /// Continue counting from totals restored at startup.
+    #[must_use]
+    pub fn with_request_counters(mut self, counters: RequestCounters) -> Self {
+        self.request_counters = counters;
+        self
+    }
+
+    /// Expose the process log filter on `/admin/v1/loglevel`.
+    #[must_use]
+    pub fn with_log_level(mut self, control: LogLevelControl) -> Self {
+        self.log_level = Some(control);
+        self
+    }
+
+    /// Write an audit record for every proxied request.
+    #[must_use]
+    pub fn with_audit_log(mut self, audit_log: AuditLog) -> Self {
+        self.audit_log = Some(audit_log);
+        self
+    }
+
+    /// Copy streamed Gemini responses to disk for `pollux replay`.
+    #[must_use]
+    pub fn with_stream_capture(mut self, capture: StreamCapture) -> Self {
+        self.stream_capture = Some(capture);
+        self
+    }
+
+    /// Start polling upstream for Antigravity model availability when
+    /// `providers.antigravity.model_discovery` is set.
+    pub fn spawn_model_discovery(&self) {
+        let cfg = &self.providers.antigravity_cfg;
+        let Some(discovery) = cfg.model_discovery else {
+            return;
+        };
+        info!(
+            interval_secs = discovery.interval_secs,
+            "Antigravity model discovery enabled"
+        );
+        self.antigravity_models.spawn_poller(
+            self.providers.db.clone(),
+            self.antigravity_egress.clone(),
+            &cfg.api_url,
+            Duration::from_secs(discovery.interval_secs),
+        );
+    }
+
+    /// `resp`, teed into a capture file when stream capture is on.
+    pub(crate) fn capture(
+        &self,
+        provider: ProviderKind,
+        model: &str,
+        resp: reqwest::Response,
+    ) -> reqwest::Response {
+        match &self.stream_capture {
+            Some(capture) => capture.tee(provider, model, resp),
+            None => resp,
         }
     }
 }
@@ -257,7 +645,15 @@ async fn not_found_handler() -> StatusCode {
     StatusCode::NOT_FOUND
 }
 
-async fn access_log(req: Request, next: Next) -> Response {
+async fn access_log(
+    State((events, counters, audit_log)): State<(
+        RequestEventBus,
+        RequestCounters,
+        Option<AuditLog>,
+    )>,
+    mut req: Request,
+    next: Next,
+) -> Response {
     // Capture request metadata before moving `req` into the handler stack.
     let method = req.method().clone();
     let uri = req.uri().clone();
@@ -277,13 +673,34 @@ async fn access_log(req: Request, next: Next) -> Response {
         .unwrap_or("-")
         .to_string();
 
+    let meta = RequestMeta::default();
+    req.extensions_mut().insert(meta.clone());
+
+    // Every log line emitted while handling the request, upstream calls
+    // included, carries the id through this span.
+    let span = info_span!("request", id = %request_id);
     let start = Instant::now();
-    let mut resp = next.run(req).await;
+    let mut resp = match audit_log {
+        Some(log) => {
+            let key = presented_key(req.headers(), req.uri().query());
+            let scope = AuditScope::new(log, request_id.clone(), key.as_deref(), meta.clone());
+            AUDIT_SCOPE
+                .scope(scope, next.run(req))
+                .instrument(span)
+                .await
+        }
+        None => next.run(req).instrument(span).await,
+    };
 
-    // Always reflect `x-request-id` for easier correlation, even if the client didn't send one.
+    // Always reflect the id for easier correlation, even if the client didn't send one.
     if let Ok(value) = HeaderValue::from_str(&request_id) {
-        resp.headers_mut().insert(X_REQUEST_ID, value);
+        resp.headers_mut().insert(X_REQUEST_ID, value.clone());
+        resp.headers_mut().insert(X_POLLUX_REQUEST_ID, value);
+    }
+    if resp.status().is_client_error() || resp.status().is_server_error() {
+        resp = tag_error_body(resp, &request_id).await;
     }
+    insert_meta_headers(&mut resp, &meta);
 
     let status = resp.status();
     let latency_ms = start.elapsed().as_millis();
@@ -327,44 +744,135 @@ async fn access_log(req: Request, next: Next) -> Response {
         );
     }
 
+    if let (Some(provider), Some(model)) = (provider_from_path(path), meta.model())
+        && provider != "admin"
+    {
+        counters.record(provider, &model, status);
+    }
+
+    if events.has_subscribers() {
+        events.publish(RequestEvent::new(
+            request_id,
+            method.as_str(),
+            path,
+            &meta,
+            status,
+            u64::try_from(latency_ms).unwrap_or(u64::MAX),
+        ));
+    }
+
     resp
 }
 
 pub fn pollux_router(state: PolluxState) -> Router {
+    let access_log_state = (
+        state.request_events.clone(),
+        state.request_counters.clone(),
+        state.audit_log.clone(),
+    );
+    let drain = state.drain.clone();
+    let sse_flush_cfg = state.sse_flush;
+    // Added before auth so auth runs first and only accepted keys are counted.
+    let rate_limit = middleware::from_fn_with_state(state.rate_limits.clone(), enforce_rate_limit);
+    // Innermost, so rejected and rate-limited requests never take a slot.
+    let concurrency =
+        middleware::from_fn_with_state(state.concurrency_limit.clone(), limit_concurrency);
+    let priority = middleware::from_fn_with_state(state.api_keys.clone(), scope_priority);
+
     let gemini = geminicli::router()
+        .layer(concurrency.clone())
+        .layer(priority.clone())
+        .layer(rate_limit.clone())
         .layer(middleware::from_extractor_with_state::<RequireKeyAuth, _>(
             state.clone(),
         ));
 
     let codex = codex::router()
+        .layer(concurrency.clone())
+        .layer(priority.clone())
         .layer(RequestDecompressionLayer::new().zstd(true))
+        .layer(rate_limit.clone())
         .layer(middleware::from_extractor_with_state::<RequireKeyAuth, _>(
             state.clone(),
         ));
 
     let antigravity = antigravity::router()
+        .layer(concurrency.clone())
+        .layer(priority.clone())
+        .layer(rate_limit.clone())
+        .layer(middleware::from_extractor_with_state::<RequireKeyAuth, _>(
+            state.clone(),
+        ));
+
+    let claude = claude::router()
+        .layer(concurrency.clone())
+        .layer(priority.clone())
+        .layer(rate_limit.clone())
+        .layer(middleware::from_extractor_with_state::<RequireKeyAuth, _>(
+            state.clone(),
+        ));
+
+    let qwen = qwen::router()
+        .layer(concurrency.clone())
+        .layer(priority.clone())
+        .layer(rate_limit.clone())
         .layer(middleware::from_extractor_with_state::<RequireKeyAuth, _>(
             state.clone(),
         ));
 
+    let unified = unified::router()
+        .layer(concurrency)
+        .layer(priority)
+        .layer(RequestDecompressionLayer::new().zstd(true))
+        .layer(rate_limit)
+        .layer(middleware::from_extractor_with_state::<RequireKeyAuth, _>(
+            state.clone(),
+        ));
+
+    let resource_add = geminicli::resource_router()
+        .merge(codex::resource_router())
+        .merge(antigravity::resource_router())
+        .merge(claude::resource_router())
+        .merge(qwen::resource_router())
+        .layer(middleware::from_fn_with_state(
+            state.resource_add.clone(),
+            guard_resource_add,
+        ));
+
+    let admin = admin::router()
+        .layer(middleware::from_extractor_with_state::<RequireMasterKey, _>(state.clone()));
+
     let oauth = Router::new()
         // Oauth Redirect path
         .route("/geminicli/auth", get(google_oauth_entry))
+        .route("/geminicli/oauth/login", get(google_oauth_entry))
         .route("/codex/auth", get(codex_oauth_entry))
         .route("/antigravity/auth", get(antigravity_oauth_entry))
+        .route("/antigravity/oauth/login", get(antigravity_oauth_entry))
         // GeminiCli Callback paths
         .route("/oauth2callback", get(google_oauth_callback))
         // Codex Callback paths
         .route("/auth/callback", get(codex_oauth_callback))
+        // Codex device-code progress (the poll id is unguessable)
+        .route("/codex/oauth/{poll_id}", get(codex_device_status))
+        .route("/qwen/oauth/{poll_id}", get(qwen_device_status))
         // Antigravity callback path (guarded)
         .route("/", get(antigravity_oauth_callback_root));
 
     Router::new()
+        .merge(health::router())
         .merge(oauth)
         .merge(gemini)
         .merge(codex)
         .merge(antigravity)
+        .merge(claude)
+        .merge(qwen)
+        .merge(unified)
+        .merge(resource_add)
+        .merge(admin)
         .fallback(not_found_handler)
         .with_state(state)
-        .layer(middleware::from_fn(access_log))
+        .layer(middleware::from_fn_with_state(sse_flush_cfg, sse_flush))
+        .layer(middleware::from_fn_with_state(access_log_state, access_log))
+        .layer(middleware::from_fn_with_state(drain, track_in_flight))
 }
@@ -0,0 +1,666 @@
+use crate::PolluxError;
+use crate::db::{RequestCounterRow, UsageAggregate, UsageQuery};
+use crate::model_catalog::consistency::ModelConsistencyReport;
+use crate::model_catalog::{self, ModelCapabilities};
+use crate::providers::antigravity::discovery::DiscoveryReport;
+use crate::providers::capacity::{self, Recommendation};
+use crate::providers::codex::usage::{self, CodexUsageSnapshot};
+use crate::providers::error_clusters::{ERROR_CLUSTER_RETENTION_MINS, ErrorClusterView};
+use crate::providers::experiment::ExperimentReport;
+use crate::providers::manifest::ProviderKind;
+use crate::providers::pool_status::{CredentialCounts, PoolStatus};
+use crate::providers::quota::{DailyQuota, QuotaView};
+use crate::providers::{CredentialView, Providers};
+use crate::server::log_level::LogLevelView;
+use crate::server::request_events::RequestEventFilter;
+use crate::server::router::PolluxState;
+use crate::server::routes::mirror::MirrorReport;
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{
+        Html, IntoResponse,
+        sse::{Event, KeepAlive, Sse},
+    },
+};
+use futures::Stream;
+use pollux_thoughtsig_core::{CacheKey, FingerprintVersion, Invalidation, SignatureCacheStats};
+use serde::{Deserialize, Serialize};
+use std::convert::Infallible;
+use std::num::NonZeroU64;
+use std::time::Duration;
+use tokio_stream::StreamExt;
+use tokio_stream::wrappers::{BroadcastStream, errors::BroadcastStreamRecvError};
+use tracing::info;
+
+#[derive(Debug, Serialize)]
+pub struct CredentialListResponse {
+    pub credentials: Vec<CredentialView>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ExperimentsResponse {
+    pub geminicli: Option<ExperimentReport>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct UsageResponse {
+    pub usage: Vec<UsageAggregate>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RecommendationsResponse {
+    pub window_hours: u32,
+    pub target_unavailable_rate: f64,
+    pub recommendations: Vec<Recommendation>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ProviderStatusView {
+    #[serde(flatten)]
+    pub pool: PoolStatus,
+    pub credentials: CredentialCounts,
+    /// Cumulative totals per model, carried across restarts.
+    pub request_totals: Vec<RequestCounterRow>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct StatusResponse {
+    pub generated_at: chrono::DateTime<chrono::Utc>,
+    pub providers: Vec<ProviderStatusView>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RecommendationsQuery {
+    /// Look-back window over recorded usage. Default: 24.
+    pub window_hours: Option<u32>,
+    /// Acceptable share of `503` responses. Default: 0.01.
+    pub target_unavailable_rate: Option<f64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ErrorClustersResponse {
+    pub window_mins: u32,
+    pub clusters: Vec<ErrorClusterView>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ErrorClustersQuery {
+    /// Minutes to count over, at most six hours. Default: 15.
+    pub window_mins: Option<u32>,
+    pub provider: Option<String>,
+    /// Default: 20.
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LogLevelUpdate {
+    /// `RUST_LOG` syntax, e.g. `info,pollux::providers::codex=debug`.
+    pub filter: String,
+    /// Restore the startup filter after this many seconds. Default: never.
+    pub revert_after_secs: Option<u64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ThoughtSigCacheResponse {
+    pub geminicli: SignatureCacheStats,
+    pub antigravity: SignatureCacheStats,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ThoughtSigFlushQuery {
+    /// `geminicli` or `antigravity`. Default: both.
+    pub provider: Option<ProviderKind>,
+    /// Drop only signatures captured from this model's responses.
+    pub model: Option<String>,
+    /// Drop only this conversation fingerprint, as logged by the patcher.
+    pub key: Option<u64>,
+    /// Fingerprint version of `key`. Default: `v1`.
+    #[serde(default)]
+    pub key_version: FingerprintVersion,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CredentialStatusPatch {
+    pub enabled: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CooldownRequest {
+    /// Model to cool. Default: every model the provider serves.
+    pub model: Option<String>,
+    pub secs: NonZeroU64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CooldownQuery {
+    /// Model to lift the cooldown for. Default: every model.
+    pub model: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CapabilitiesRequest {
+    pub models: Vec<String>,
+}
+
+fn models_mask<'a>(
+    models: impl IntoIterator<Item = &'a str>,
+) -> Result<ModelCapabilities, PolluxError> {
+    models
+        .into_iter()
+        .try_fold(ModelCapabilities::none(), |acc, model| {
+            let mask = model_catalog::mask(model)
+                .ok_or_else(|| PolluxError::NotFound(format!("unknown model: {model}")))?;
+            Ok(acc.merge(&mask))
+        })
+}
+
+async fn set_cooldown_for(
+    providers: &Providers,
+    kind: ProviderKind,
+    id: u64,
+    model_mask: ModelCapabilities,
+    cooldown: Option<Duration>,
+) -> Result<(), PolluxError> {
+    match kind {
+        ProviderKind::GeminiCli => {
+            providers
+                .geminicli
+                .set_cooldown(id, model_mask, cooldown)
+                .await
+        }
+        ProviderKind::Codex => providers.codex.set_cooldown(id, model_mask, cooldown).await,
+        ProviderKind::Antigravity => {
+            providers
+                .antigravity
+                .set_cooldown(id, model_mask, cooldown)
+                .await
+        }
+        ProviderKind::Claude => {
+            providers
+                .claude
+                .set_cooldown(id, model_mask, cooldown)
+                .await
+        }
+        ProviderKind::Qwen => providers.qwen.set_cooldown(id, model_mask, cooldown).await,
+    }
+}
+
+async fn list_for(
+    providers: &Providers,
+    kind: ProviderKind,
+) -> Result<Vec<CredentialView>, PolluxError> {
+    match kind {
+        ProviderKind::GeminiCli => providers.geminicli.list_credentials().await,
+        ProviderKind::Codex => providers.codex.list_credentials().await,
+        ProviderKind::Antigravity => providers.antigravity.list_credentials().await,
+        ProviderKind::Claude => providers.claude.list_credentials().await,
+        ProviderKind::Qwen => providers.qwen.list_credentials().await,
+    }
+}
+
+/// GET /admin/v1/status
+///
+/// Scheduler snapshot per provider, stored credential counts and cumulative
+/// request totals; backs the `/admin/ui` dashboard.
+pub async fn admin_status(
+    State(state): State<PolluxState>,
+) -> Result<Json<StatusResponse>, PolluxError> {
+    let providers = &state.providers;
+    let totals = state.request_counters.snapshot();
+    let mut out = Vec::with_capacity(ProviderKind::ALL.len());
+    for kind in ProviderKind::ALL {
+        let pool = match kind {
+            ProviderKind::GeminiCli => providers.geminicli.status().await,
+            ProviderKind::Codex => providers.codex.status().await,
+            ProviderKind::Antigravity => providers.antigravity.status().await,
+            ProviderKind::Claude => providers.claude.status().await,
+            ProviderKind::Qwen => providers.qwen.status().await,
+        }?;
+        let views = list_for(providers, kind).await?;
+        out.push(ProviderStatusView {
+            pool,
+            credentials: CredentialCounts::from_views(&views),
+            request_totals: totals
+                .iter()
+                .filter(|row| row.provider == kind.label())
+                .cloned()
+                .collect(),
+        });
+    }
+    Ok(Json(StatusResponse {
+        generated_at: chrono::Utc::now(),
+        providers: out,
+    }))
+}
+
+/// GET /admin/ui
+///
+/// Static dashboard page; it polls `/admin/v1/status` with the same key.
+pub async fn admin_ui() -> Html<&'static str> {
+    Html(include_str!("dashboard.html"))
+}
+
+/// GET /admin/v1/credentials
+pub async fn admin_list_credentials(
+    State(state): State<PolluxState>,
+) -> Result<Json<CredentialListResponse>, PolluxError> {
+    let mut credentials = Vec::new();
+    for kind in ProviderKind::ALL {
+        credentials.extend(list_for(&state.providers, kind).await?);
+    }
+    Ok(Json(CredentialListResponse { credentials }))
+}
+
+/// GET /admin/v1/credentials/{provider}
+pub async fn admin_list_provider_credentials(
+    State(state): State<PolluxState>,
+    Path(kind): Path<ProviderKind>,
+) -> Result<Json<CredentialListResponse>, PolluxError> {
+    let credentials = list_for(&state.providers, kind).await?;
+    Ok(Json(CredentialListResponse { credentials }))
+}
+
+/// PATCH /admin/v1/credentials/{provider}/{id}
+///
+/// Body: `{"enabled": false}` takes the credential out of rotation and marks it
+/// disabled in storage; `{"enabled": true}` re-enables and reloads it.
+pub async fn admin_patch_credential(
+    State(state): State<PolluxState>,
+    Path((kind, id)): Path<(ProviderKind, u64)>,
+    Json(body): Json<CredentialStatusPatch>,
+) -> Result<StatusCode, PolluxError> {
+    let providers = &state.providers;
+    let enabled = body.enabled;
+    match kind {
+        ProviderKind::GeminiCli => providers.geminicli.set_credential_status(id, enabled).await,
+        ProviderKind::Codex => providers.codex.set_credential_status(id, enabled).await,
+        ProviderKind::Antigravity => {
+            providers
+                .antigravity
+                .set_credential_status(id, enabled)
+                .await
+        }
+        ProviderKind::Claude => providers.claude.set_credential_status(id, enabled).await,
+        ProviderKind::Qwen => providers.qwen.set_credential_status(id, enabled).await,
+    }?;
+    info!(provider = ?kind, id, enabled = body.enabled, "[Admin] Credential status updated");
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// PATCH /admin/v1/credentials/{provider}/{id}/models/{model}
+///
+/// Body: `{"enabled": true}` turns the model back on for this credential and
+/// exempts it from success-rate auto-disable; `{"enabled": false}` takes the
+/// credential out of that model's rotation. Runtime only; re-adding or
+/// re-enabling the credential resets it.
+pub async fn admin_patch_credential_model(
+    State(state): State<PolluxState>,
+    Path((kind, id, model)): Path<(ProviderKind, u64, String)>,
+    Json(body): Json<CredentialStatusPatch>,
+) -> Result<StatusCode, PolluxError> {
+    let model_mask = model_catalog::mask(&model)
+        .ok_or_else(|| PolluxError::NotFound(format!("unknown model: {model}")))?;
+    let providers = &state.providers;
+    let enabled = body.enabled;
+    match kind {
+        ProviderKind::GeminiCli => {
+            providers
+                .geminicli
+                .set_model_override(id, model_mask, enabled)
+                .await
+        }
+        ProviderKind::Codex => {
+            providers
+                .codex
+                .set_model_override(id, model_mask, enabled)
+                .await
+        }
+        ProviderKind::Antigravity => {
+            providers
+                .antigravity
+                .set_model_override(id, model_mask, enabled)
+                .await
+        }
+        ProviderKind::Claude => {
+            providers
+                .claude
+                .set_model_override(id, model_mask, enabled)
+                .await
+        }
+        ProviderKind::Qwen => {
+            providers
+                .qwen
+                .set_model_override(id, model_mask, enabled)
+                .await
+        }
+    }?;
+    info!(provider = ?kind, id, model = %model, enabled, "[Admin] Credential model override updated");
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// POST /admin/v1/{provider}/credentials/{id}/cooldown
+///
+/// Body: `{"model": "gemini-2.5-pro", "secs": 600}` keeps the credential out
+/// of that model's rotation for ten minutes; without `model` every model
+/// cools. Nothing is counted as a failure and nothing is stored, so the
+/// account stays enabled.
+pub async fn admin_set_cooldown(
+    State(state): State<PolluxState>,
+    Path((kind, id)): Path<(ProviderKind, u64)>,
+    Json(body): Json<CooldownRequest>,
+) -> Result<StatusCode, PolluxError> {
+    let model_mask = models_mask(body.model.as_deref())?;
+    let cooldown = Duration::from_secs(body.secs.get());
+    set_cooldown_for(&state.providers, kind, id, model_mask, Some(cooldown)).await?;
+    info!(provider = ?kind, id, model = ?body.model, secs = body.secs.get(), "[Admin] Credential cooldown imposed");
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// DELETE /admin/v1/{provider}/credentials/{id}/cooldown?model=
+///
+/// Lifts the cooldown early, whether imposed here or by an upstream 429.
+pub async fn admin_clear_cooldown(
+    State(state): State<PolluxState>,
+    Path((kind, id)): Path<(ProviderKind, u64)>,
+    Query(query): Query<CooldownQuery>,
+) -> Result<StatusCode, PolluxError> {
+    let model_mask = models_mask(query.model.as_deref())?;
+    set_cooldown_for(&state.providers, kind, id, model_mask, None).await?;
+    info!(provider = ?kind, id, model = ?query.model, "[Admin] Credential cooldown cleared");
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// PUT /admin/v1/{provider}/credentials/{id}/capabilities
+///
+/// Body: `{"models": ["gemini-2.5-pro"]}` replaces the set of models the
+/// credential is scheduled for. Models left out are not restored by
+/// `capability_restore_secs`; like the per-model override this is runtime
+/// only.
+pub async fn admin_set_capabilities(
+    State(state): State<PolluxState>,
+    Path((kind, id)): Path<(ProviderKind, u64)>,
+    Json(body): Json<CapabilitiesRequest>,
+) -> Result<StatusCode, PolluxError> {
+    let model_mask = models_mask(body.models.iter().map(String::as_str))?;
+    let providers = &state.providers;
+    match kind {
+        ProviderKind::GeminiCli => providers.geminicli.set_capabilities(id, model_mask).await,
+        ProviderKind::Codex => providers.codex.set_capabilities(id, model_mask).await,
+        ProviderKind::Antigravity => providers.antigravity.set_capabilities(id, model_mask).await,
+        ProviderKind::Claude => providers.claude.set_capabilities(id, model_mask).await,
+        ProviderKind::Qwen => providers.qwen.set_capabilities(id, model_mask).await,
+    }?;
+    info!(provider = ?kind, id, models = ?body.models, "[Admin] Credential capabilities replaced");
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// DELETE /admin/v1/credentials/{provider}/{id}
+pub async fn admin_delete_credential(
+    State(state): State<PolluxState>,
+    Path((kind, id)): Path<(ProviderKind, u64)>,
+) -> Result<StatusCode, PolluxError> {
+    let providers = &state.providers;
+    match kind {
+        ProviderKind::GeminiCli => providers.geminicli.delete_credential(id).await,
+        ProviderKind::Codex => providers.codex.delete_credential(id).await,
+        ProviderKind::Antigravity => providers.antigravity.delete_credential(id).await,
+        ProviderKind::Claude => providers.claude.delete_credential(id).await,
+        ProviderKind::Qwen => providers.qwen.delete_credential(id).await,
+    }?;
+    info!(provider = ?kind, id, "[Admin] Credential deleted");
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// GET /admin/v1/codex/credentials/{id}/quota
+///
+/// Plan type and rolling-window usage of one Codex account, fetched live with
+/// its stored access token. Nothing is leased, so cooling or disabled
+/// accounts can be audited too; an expired token surfaces as upstream `401`.
+pub async fn admin_codex_quota(
+    State(state): State<PolluxState>,
+    Path(id): Path<u64>,
+) -> Result<Json<CodexUsageSnapshot>, PolluxError> {
+    let snapshot = usage::snapshot(&state.codex_caller, &state.providers.db, id).await?;
+    Ok(Json(snapshot))
+}
+
+/// GET /admin/v1/experiments
+///
+/// Per-arm outcome counters for configured request-shape experiments.
+pub async fn admin_list_experiments(State(state): State<PolluxState>) -> Json<ExperimentsResponse> {
+    Json(ExperimentsResponse {
+        geminicli: state.providers.geminicli_experiment.report(),
+    })
+}
+
+/// GET /admin/v1/mirror
+///
+/// Primary and mirror outcome counters for `routing.mirror`; `null` when off.
+pub async fn admin_mirror(State(state): State<PolluxState>) -> Json<Option<MirrorReport>> {
+    Json(state.mirror.report())
+}
+
+/// GET /admin/v1/models/consistency
+///
+/// Configured model lists compared with the previous boot, as computed at startup.
+pub async fn admin_model_consistency(
+    State(state): State<PolluxState>,
+) -> Json<ModelConsistencyReport> {
+    Json(state.model_report.as_ref().clone())
+}
+
+/// GET /admin/v1/antigravity/models
+///
+/// Credentials upstream currently offers each model to, from the last
+/// `model_discovery` pass; empty with `enabled: false` when discovery is off.
+pub async fn admin_antigravity_models(State(state): State<PolluxState>) -> Json<DiscoveryReport> {
+    let cfg = &state.providers.antigravity_cfg;
+    Json(
+        state
+            .antigravity_models
+            .report(&cfg.model_list, cfg.model_discovery.is_some()),
+    )
+}
+
+/// GET /admin/v1/usage?since=2026-01-01T00:00:00Z&provider=codex
+///
+/// Request and token totals per (provider, model, credential), heaviest first.
+pub async fn admin_usage(
+    State(state): State<PolluxState>,
+    Query(query): Query<UsageQuery>,
+) -> Result<Json<UsageResponse>, PolluxError> {
+    let usage = state.providers.db.usage_summary(query).await?;
+    Ok(Json(UsageResponse { usage }))
+}
+
+#[derive(Debug, Serialize)]
+pub struct QuotaResponse {
+    pub quotas: Vec<QuotaView>,
+}
+
+/// GET /admin/v1/quota
+///
+/// Today's per-credential counters for providers with `daily_quota` set.
+pub async fn admin_quota(State(state): State<PolluxState>) -> Json<QuotaResponse> {
+    let quotas = ProviderKind::ALL
+        .into_iter()
+        .filter_map(|kind| state.providers.quota(kind).map(DailyQuota::view))
+        .collect();
+    Json(QuotaResponse { quotas })
+}
+
+/// GET /admin/v1/recommendations
+///
+/// Capacity guidance derived from recorded usage and the current pool.
+/// Query: `window_hours` (default 24), `target_unavailable_rate` (default 0.01).
+pub async fn admin_recommendations(
+    State(state): State<PolluxState>,
+    Query(query): Query<RecommendationsQuery>,
+) -> Result<Json<RecommendationsResponse>, PolluxError> {
+    let window_hours = query.window_hours.unwrap_or(24);
+    let target = query.target_unavailable_rate.unwrap_or(0.01);
+    let since = chrono::Utc::now() - chrono::Duration::hours(i64::from(window_hours));
+
+    let model_stats = state.providers.db.model_usage_stats(since).await?;
+    let mut views = Vec::new();
+    for kind in ProviderKind::ALL {
+        views.extend(list_for(&state.providers, kind).await?);
+    }
+
+    Ok(Json(RecommendationsResponse {
+        window_hours,
+        target_unavailable_rate: target,
+        recommendations: capacity::recommend(&model_stats, &views, target),
+    }))
+}
+
+/// GET /admin/v1/errors?provider=geminicli&limit=20
+///
+/// Upstream error clusters seen in the last `window_mins` (default 15),
+/// most frequent first.
+pub async fn admin_error_clusters(
+    State(state): State<PolluxState>,
+    Query(query): Query<ErrorClustersQuery>,
+) -> Json<ErrorClustersResponse> {
+    let window_mins = query
+        .window_mins
+        .unwrap_or(15)
+        .clamp(1, ERROR_CLUSTER_RETENTION_MINS);
+    Json(ErrorClustersResponse {
+        window_mins,
+        clusters: state.providers.error_clusters.top(
+            window_mins,
+            query.provider.as_deref(),
+            query.limit.unwrap_or(20),
+        ),
+    })
+}
+
+/// GET /admin/v1/logs/stream?provider=codex&model=gpt-5&min_status=400
+///
+/// Live SSE feed of completed requests (`event: request`). Subscribers that
+/// fall behind get an `event: lagged` frame with the number of dropped events.
+pub async fn admin_logs_stream(
+    State(state): State<PolluxState>,
+    Query(filter): Query<RequestEventFilter>,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let rx = state.request_events.subscribe();
+    info!(?filter, "[Admin] Log stream subscriber attached");
+
+    let stream = BroadcastStream::new(rx).filter_map(move |item| match item {
+        Ok(event) if filter.matches(&event) => Event::default()
+            .event("request")
+            .json_data(&event)
+            .ok()
+            .map(Ok),
+        Ok(_) => None,
+        Err(BroadcastStreamRecvError::Lagged(skipped)) => Some(Ok(Event::default()
+            .event("lagged")
+            .data(skipped.to_string()))),
+    });
+
+    Sse::new(stream).keep_alive(KeepAlive::default())
+}
+
+/// GET /admin/v1/loglevel
+///
+/// The active tracing filter, the one set at startup, and when a temporary
+/// change reverts.
+pub async fn admin_log_level(
+    State(state): State<PolluxState>,
+) -> Result<Json<LogLevelView>, PolluxError> {
+    let control = state
+        .log_level
+        .as_ref()
+        .ok_or_else(|| PolluxError::NotFound("runtime log level control".to_string()))?;
+    Ok(Json(control.view()))
+}
+
+/// PUT /admin/v1/loglevel
+///
+/// Body: `{"filter": "info,pollux::providers::codex=debug", "revert_after_secs": 900}`.
+/// Applies immediately; an invalid filter is rejected with `400` and leaves
+/// the current one in place.
+pub async fn admin_set_log_level(
+    State(state): State<PolluxState>,
+    Json(body): Json<LogLevelUpdate>,
+) -> Result<Json<LogLevelView>, axum::response::Response> {
+    let control = state.log_level.as_ref().ok_or_else(|| {
+        PolluxError::NotFound("runtime log level control".to_string()).into_response()
+    })?;
+    control
+        .set(
+            body.filter.trim(),
+            body.revert_after_secs.map(Duration::from_secs),
+        )
+        .map_err(|e| {
+            (StatusCode::BAD_REQUEST, format!("Invalid log filter: {e}")).into_response()
+        })?;
+    info!(filter = %body.filter.trim(), revert_after_secs = ?body.revert_after_secs, "[Admin] Log level updated");
+    Ok(Json(control.view()))
+}
+
+fn thoughtsig_cache_stats(providers: &Providers) -> ThoughtSigCacheResponse {
+    ThoughtSigCacheResponse {
+        geminicli: providers.geminicli_thoughtsig.cache_stats(),
+        antigravity: providers.antigravity_thoughtsig.cache_stats(),
+    }
+}
+
+/// GET /admin/v1/thoughtsig/cache
+///
+/// Entry count and hit/miss/insert/eviction counters of each thought-signature
+/// cache. A high miss rate means thought parts are being dummy-filled
+/// (Gemini CLI) or dropped (Antigravity).
+pub async fn admin_thoughtsig_cache(
+    State(state): State<PolluxState>,
+) -> Json<ThoughtSigCacheResponse> {
+    Json(thoughtsig_cache_stats(&state.providers))
+}
+
+/// DELETE /admin/v1/thoughtsig/cache
+///
+/// Query: `provider`, plus at most one of `model` or `key`; with neither,
+/// the whole cache is flushed. Persisted signatures are deleted too.
+pub async fn admin_flush_thoughtsig_cache(
+    State(state): State<PolluxState>,
+    Query(query): Query<ThoughtSigFlushQuery>,
+) -> Result<Json<ThoughtSigCacheResponse>, axum::response::Response> {
+    let scope = match (query.model.as_deref().map(str::trim), query.key) {
+        (Some(_), Some(_)) => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                "Pass either model or key, not both",
+            )
+                .into_response());
+        }
+        (Some(model), None) if !model.is_empty() => Invalidation::Model(model.to_string()),
+        (Some(_), None) => {
+            return Err((StatusCode::BAD_REQUEST, "model must not be empty").into_response());
+        }
+        (None, Some(key)) => Invalidation::Key(CacheKey::new(query.key_version, key)),
+        (None, None) => Invalidation::All,
+    };
+    let providers = &state.providers;
+    match query.provider {
+        Some(ProviderKind::GeminiCli) => providers.geminicli_thoughtsig.invalidate_cache(&scope),
+        Some(ProviderKind::Antigravity) => {
+            providers.antigravity_thoughtsig.invalidate_cache(&scope);
+        }
+        Some(kind @ (ProviderKind::Codex | ProviderKind::Claude | ProviderKind::Qwen)) => {
+            return Err(PolluxError::NotFound(format!(
+                "thought-signature cache for {}",
+                kind.label()
+            ))
+            .into_response());
+        }
+        None => {
+            providers.geminicli_thoughtsig.invalidate_cache(&scope);
+            providers.antigravity_thoughtsig.invalidate_cache(&scope);
+        }
+    }
+    info!(provider = ?query.provider, scope = ?scope, "[Admin] Thought-signature cache flushed");
+    Ok(Json(thoughtsig_cache_stats(providers)))
+}
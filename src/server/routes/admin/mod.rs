@@ -0,0 +1,66 @@
+pub mod handlers;
+
+use crate::server::router::PolluxState;
+use axum::{
+    Router,
+    routing::{get, patch, post, put},
+};
+use handlers::{
+    admin_antigravity_models, admin_clear_cooldown, admin_codex_quota, admin_delete_credential,
+    admin_error_clusters, admin_flush_thoughtsig_cache, admin_list_credentials,
+    admin_list_experiments, admin_list_provider_credentials, admin_log_level, admin_logs_stream,
+    admin_mirror, admin_model_consistency, admin_patch_credential, admin_patch_credential_model,
+    admin_quota, admin_recommendations, admin_set_capabilities, admin_set_cooldown,
+    admin_set_log_level, admin_status, admin_thoughtsig_cache, admin_ui, admin_usage,
+};
+
+pub fn router() -> Router<PolluxState> {
+    Router::new()
+        .route("/admin/ui", get(admin_ui))
+        .route("/admin/v1/status", get(admin_status))
+        .route("/admin/v1/credentials", get(admin_list_credentials))
+        .route(
+            "/admin/v1/credentials/{provider}",
+            get(admin_list_provider_credentials),
+        )
+        .route(
+            "/admin/v1/credentials/{provider}/{id}",
+            patch(admin_patch_credential).delete(admin_delete_credential),
+        )
+        .route(
+            "/admin/v1/credentials/{provider}/{id}/models/{model}",
+            patch(admin_patch_credential_model),
+        )
+        .route(
+            "/admin/v1/codex/credentials/{id}/quota",
+            get(admin_codex_quota),
+        )
+        .route(
+            "/admin/v1/{provider}/credentials/{id}/cooldown",
+            post(admin_set_cooldown).delete(admin_clear_cooldown),
+        )
+        .route(
+            "/admin/v1/{provider}/credentials/{id}/capabilities",
+            put(admin_set_capabilities),
+        )
+        .route("/admin/v1/experiments", get(admin_list_experiments))
+        .route("/admin/v1/mirror", get(admin_mirror))
+        .route("/admin/v1/errors", get(admin_error_clusters))
+        .route("/admin/v1/logs/stream", get(admin_logs_stream))
+        .route(
+            "/admin/v1/loglevel",
+            get(admin_log_level).put(admin_set_log_level),
+        )
+        .route(
+            "/admin/v1/thoughtsig/cache",
+            get(admin_thoughtsig_cache).delete(admin_flush_thoughtsig_cache),
+        )
+        .route("/admin/v1/models/consistency", get(admin_model_consistency))
+        .route(
+            "/admin/v1/antigravity/models",
+            get(admin_antigravity_models),
+        )
+        .route("/admin/v1/usage", get(admin_usage))
+        .route("/admin/v1/quota", get(admin_quota))
+        .route("/admin/v1/recommendations", get(admin_recommendations))
+}
@@ -1,13 +1,17 @@
 use crate::error::{GeminiCliError, GeminiErrorObject};
 use crate::providers::antigravity::AntigravityContext;
+use crate::server::guards::policy;
+use crate::server::pool::requested_pool;
+use crate::server::request_events::RequestMeta;
 use crate::server::router::PolluxState;
+use crate::server::session::session_route_key;
 use crate::utils::logging::with_pretty_json_debug;
 use axum::{
     Json, RequestExt,
     extract::{FromRequest, Path, Request},
     http::StatusCode,
 };
-use pollux_schema::gemini::GeminiGenerateContentRequest;
+use pollux_schema::gemini::{GeminiCountTokensRequest, GeminiGenerateContentRequest};
 use std::borrow::Borrow;
 use tracing::{debug, warn};
 
@@ -19,6 +23,7 @@ where
 {
     type Rejection = GeminiCliError;
 
+    #[allow(clippy::too_many_lines)]
     async fn from_request(mut req: Request, state: &S) -> Result<Self, Self::Rejection> {
         let Path(path) = req
             .extract_parts::<Path<String>>()
@@ -46,13 +51,27 @@ where
                 debug_message: None,
             });
         };
-        let model = if let Some((m, _r)) = last_seg.split_once(':') {
-            m.to_string()
-        } else {
-            last_seg
-        };
-
+        let requested = last_seg
+            .split_once(':')
+            .map_or(last_seg.as_str(), |(m, _r)| m);
         let state = state.borrow();
+        let model = state
+            .providers
+            .antigravity_cfg
+            .model_aliases
+            .resolve(requested)
+            .into_owned();
+        if model != requested {
+            debug!(from = %requested, to = %model, "[Antigravity] Model alias applied");
+        }
+        let meta = req.extensions().get::<RequestMeta>().cloned();
+        let route_key = session_route_key(req.headers());
+        let pool = requested_pool(req.headers());
+        let policy = policy::for_request(state, req.headers(), req.uri().query());
+        if let Some(meta) = &meta {
+            meta.set_model(&model);
+        }
+
         let is_allowed = state
             .providers
             .antigravity_cfg
@@ -94,14 +113,54 @@ where
         };
 
         let stream = path.contains("streamGenerateContent");
-        let Json(mut body) = req
-            .extract::<Json<GeminiGenerateContentRequest>, _>()
-            .await?;
+        let mut body = if last_seg.ends_with(":countTokens") {
+            let Json(body) = req.extract::<Json<GeminiCountTokensRequest>, _>().await?;
+            body.into_generate_request()
+        } else {
+            let Json(mut body) = req
+                .extract::<Json<GeminiGenerateContentRequest>, _>()
+                .await?;
+            if let Some(policy) = policy {
+                policy::check_gemini(policy, &mut body).map_err(|message| {
+                    GeminiCliError::RequestRejected {
+                        status: StatusCode::BAD_REQUEST,
+                        body: GeminiErrorObject::for_status(
+                            StatusCode::BAD_REQUEST,
+                            "INVALID_ARGUMENT",
+                            message,
+                        ),
+                        debug_message: None,
+                    }
+                })?;
+            }
+            body
+        };
+        let max = state.providers.antigravity_cfg.max_inline_data_bytes;
+        let len = body.inline_data_len();
+        if max > 0 && len > max {
+            return Err(GeminiCliError::RequestRejected {
+                status: StatusCode::BAD_REQUEST,
+                body: GeminiErrorObject::for_status(
+                    StatusCode::BAD_REQUEST,
+                    "INVALID_ARGUMENT",
+                    format!("inline data totals {len} bytes, above the limit of {max} bytes"),
+                ),
+                debug_message: None,
+            });
+        }
+        if let Some(meta) = &meta {
+            meta.hash_request(&model, &body);
+        }
 
-        state
+        let summary = state
             .providers
             .antigravity_thoughtsig
             .patch_request(&mut body);
+        if let Some(meta) = &meta
+            && state.providers.antigravity_cfg.thoughtsig.report_outcomes
+        {
+            meta.set_thoughtsig(summary);
+        }
 
         with_pretty_json_debug(&body, |pretty_body| {
             debug!(
@@ -119,6 +178,8 @@ where
             stream,
             path,
             model_mask,
+            route_key,
+            pool,
         };
         Ok(AntigravityPreprocess(body, ctx))
     }
@@ -3,46 +3,210 @@ use super::{
     respond::{build_json_response, build_stream_response},
 };
 use crate::error::GeminiCliError;
-use crate::providers::antigravity::AntigravityClient;
+use crate::providers::UsageTracker;
+use crate::providers::anthropic_compat::{count_tokens_contents, estimate_tokens};
+use crate::providers::antigravity::{AntigravityClient, AntigravityContext};
+use crate::providers::manifest::ProviderKind;
+use crate::providers::response_cache::ResponseCache;
+use crate::server::pool::requested_pool;
 use crate::server::router::PolluxState;
+use crate::server::routes::failover::{self, GeminiRoute};
+use crate::server::routes::resume::StreamResume;
+use crate::server::session::session_route_key;
 use axum::{
     Json,
     extract::State,
+    http::HeaderMap,
     response::{IntoResponse, Response},
 };
-use pollux_schema::gemini::GeminiModelList;
+use pollux_schema::anthropic::{AnthropicCountTokensRequest, AnthropicCountTokensResponse};
+use pollux_schema::gemini::{
+    Content, GeminiCountTokensResponse, GeminiGenerateContentRequest, GeminiModelList,
+};
+use tracing::warn;
 
 pub async fn antigravity_proxy_handler(
     State(state): State<PolluxState>,
     AntigravityPreprocess(body, ctx): AntigravityPreprocess,
-) -> Result<Response, GeminiCliError> {
-    let caller = AntigravityClient::new(
+) -> Response {
+    if ctx.is_count_tokens() {
+        return count_tokens(&state, &body, &ctx).await.into_response();
+    }
+    let cached = ResponseCache::entry(
+        state.providers.antigravity_response_cache.as_ref(),
+        "antigravity:generateContent",
+        &ctx.model,
+        &body,
+        ctx.stream,
+        body.generation_config.as_ref().and_then(|g| g.temperature),
+    );
+    if let Some(entry) = &cached
+        && let Some(hit) = entry.hit().await
+    {
+        return hit;
+    }
+    let usage = state
+        .providers
+        .track_usage(ProviderKind::Antigravity, &ctx.model);
+    let mirrored = state.mirror.copy(
+        &state,
+        ProviderKind::Antigravity,
+        &body,
+        &GeminiRoute::from(&ctx),
+    );
+    let result = forward(&state, &body, &ctx, &usage).await;
+    let resp = failover::settle(
+        &state,
+        ProviderKind::Antigravity,
+        result,
+        &usage,
+        &body,
+        GeminiRoute::from(&ctx),
+    )
+    .await;
+    if let Some(mirrored) = mirrored {
+        mirrored.finish(resp.status());
+    }
+    match cached {
+        Some(entry) => entry.store(resp).await,
+        None => resp,
+    }
+}
+
+fn caller(state: &PolluxState) -> AntigravityClient {
+    AntigravityClient::new(
         state.providers.antigravity_cfg.as_ref(),
-        state.antigravity_client.clone(),
-        state.antigravity_stream_client.clone(),
+        state.antigravity_egress.clone(),
         Some(state.providers.antigravity_cfg.api_url.clone()),
-    );
+    )
+    .with_error_clusters(state.providers.error_clusters.clone())
+}
 
-    let upstream_resp = caller
-        .call_antigravity(&state.providers.antigravity, &ctx, &body)
+pub(crate) async fn forward(
+    state: &PolluxState,
+    body: &GeminiGenerateContentRequest,
+    ctx: &AntigravityContext,
+    usage: &UsageTracker,
+) -> Result<Response, GeminiCliError> {
+    let upstream_resp = caller(state)
+        .call_antigravity(&state.providers.antigravity, ctx, body)
         .await
         .map_err(map_antigravity_error)?;
+    usage.observe_response(&upstream_resp);
 
     if ctx.stream {
-        Ok(build_stream_response(upstream_resp, &state).into_response())
+        let resume = stream_resume(state, body, ctx, usage);
+        Ok(build_stream_response(upstream_resp, state, usage.clone(), resume).into_response())
     } else {
-        Ok(build_json_response(upstream_resp, &state)
+        Ok(build_json_response(upstream_resp, state, usage)
             .await?
             .into_response())
     }
 }
 
+/// Replay a stream cut short through a fresh lease (`stream_resume_max_times`).
+fn stream_resume(
+    state: &PolluxState,
+    body: &GeminiGenerateContentRequest,
+    ctx: &AntigravityContext,
+    usage: &UsageTracker,
+) -> Option<StreamResume> {
+    let max_times = state.providers.antigravity_cfg.stream_resume_max_times;
+    let (state, ctx, usage) = (state.clone(), ctx.clone(), usage.clone());
+    StreamResume::new(max_times, body, move |body| {
+        let (state, ctx, usage) = (state.clone(), ctx.clone(), usage.clone());
+        Box::pin(async move {
+            let resp = caller(&state)
+                .call_antigravity(&state.providers.antigravity, &ctx, &body)
+                .await
+                .map_err(map_antigravity_error)?;
+            usage.observe_response(&resp);
+            Ok(resp)
+        })
+    })
+}
+
+async fn count_tokens(
+    state: &PolluxState,
+    body: &GeminiGenerateContentRequest,
+    ctx: &AntigravityContext,
+) -> Result<Json<GeminiCountTokensResponse>, GeminiCliError> {
+    let upstream_resp = caller(state)
+        .count_tokens(&state.providers.antigravity, ctx, &body.contents)
+        .await
+        .map_err(map_antigravity_error)?;
+    Ok(Json(upstream_resp.json().await?))
+}
+
+/// `POST /antigravity/v1/messages/count_tokens`
+///
+/// Anthropic `count_tokens`, which Claude Code calls before sending a
+/// request. Counted by Cloud Code when the model is served here; otherwise,
+/// or when that fails, estimated locally so the client is never blocked.
+pub async fn antigravity_messages_count_tokens_handler(
+    State(state): State<PolluxState>,
+    headers: HeaderMap,
+    Json(body): Json<AnthropicCountTokensRequest>,
+) -> Json<AnthropicCountTokensResponse> {
+    let contents = count_tokens_contents(&body);
+    let cfg = &state.providers.antigravity_cfg;
+    let model = cfg.model_aliases.resolve(&body.model).into_owned();
+    let model_mask = cfg
+        .model_list
+        .iter()
+        .any(|m| m == &model)
+        .then(|| crate::model_catalog::mask(&model))
+        .flatten();
+
+    let upstream = match model_mask {
+        Some(model_mask) => {
+            let ctx = AntigravityContext {
+                path: format!("models/{model}:countTokens"),
+                model,
+                stream: false,
+                model_mask,
+                route_key: session_route_key(&headers),
+                pool: requested_pool(&headers),
+            };
+            upstream_token_count(&state, &ctx, &contents).await
+        }
+        None => None,
+    };
+    let input_tokens = upstream.unwrap_or_else(|| estimate_tokens(&contents));
+    Json(AnthropicCountTokensResponse { input_tokens })
+}
+
+async fn upstream_token_count(
+    state: &PolluxState,
+    ctx: &AntigravityContext,
+    contents: &[Content],
+) -> Option<u64> {
+    let counted = async {
+        let resp = caller(state)
+            .count_tokens(&state.providers.antigravity, ctx, contents)
+            .await?;
+        Ok::<_, crate::PolluxError>(resp.json::<GeminiCountTokensResponse>().await?)
+    };
+    match counted.await {
+        Ok(counted) => Some(counted.total_tokens),
+        Err(e) => {
+            warn!(model = %ctx.model, error = %e, "[Antigravity] countTokens failed; estimating locally");
+            None
+        }
+    }
+}
+
+/// Configured models, narrowed to those some credential currently offers
+/// once model discovery has completed a pass.
 pub async fn antigravity_models_handler(
     State(state): State<PolluxState>,
 ) -> Result<Json<GeminiModelList>, GeminiCliError> {
-    Ok(Json(GeminiModelList::from_model_names(
-        state.providers.antigravity_cfg.model_list.iter().cloned(),
-    )))
+    let model_list = &state.providers.antigravity_cfg.model_list;
+    let models = state
+        .antigravity_models
+        .served(model_list)
+        .unwrap_or_else(|| model_list.clone());
+    Ok(Json(GeminiModelList::from_model_names(models)))
 }
 
 fn map_antigravity_error(err: crate::PolluxError) -> GeminiCliError {
@@ -51,6 +215,9 @@ fn map_antigravity_error(err: crate::PolluxError) -> GeminiCliError {
             status,
             body: String::new(),
         },
+        crate::PolluxError::RetryAfter { error, after } => {
+            map_antigravity_error(*error).with_retry_after(Some(after))
+        }
         other => other.into(),
     }
 }
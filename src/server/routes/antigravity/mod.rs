@@ -10,7 +10,10 @@ use axum::{
     routing::{get, post},
 };
 
-use handlers::{antigravity_models_handler, antigravity_proxy_handler};
+use handlers::{
+    antigravity_messages_count_tokens_handler, antigravity_models_handler,
+    antigravity_proxy_handler,
+};
 use resource::antigravity_resource_add;
 
 pub fn router() -> Router<PolluxState> {
@@ -23,5 +26,13 @@ pub fn router() -> Router<PolluxState> {
             "/antigravity/v1beta/models/{*path}",
             post(antigravity_proxy_handler),
         )
-        .route("/antigravity/resource:add", post(antigravity_resource_add))
+        .route(
+            "/antigravity/v1/messages/count_tokens",
+            post(antigravity_messages_count_tokens_handler),
+        )
+}
+
+/// Credential upload, mounted behind `ResourceAddGuard` instead of key auth.
+pub fn resource_router() -> Router<PolluxState> {
+    Router::new().route("/antigravity/resource:add", post(antigravity_resource_add))
 }
@@ -2,21 +2,22 @@ use crate::PolluxError;
 use crate::error::OauthError;
 use crate::providers::antigravity::client::oauth::endpoints::AntigravityOauthEndpoints;
 use crate::server::router::PolluxState;
+use crate::server::routes::oauth_page;
 use axum::{
     extract::{Query, State},
-    http::StatusCode,
+    http::{HeaderMap, StatusCode},
     response::{IntoResponse, Redirect},
 };
 use axum_extra::extract::cookie::{Cookie, PrivateCookieJar, SameSite};
 use oauth2::{AuthorizationCode, PkceCodeChallenge, PkceCodeVerifier, TokenResponse};
 use std::collections::HashMap;
 use time::Duration;
-use tracing::{error, info};
+use tracing::{error, info, warn};
 
 const CSRF_COOKIE: &str = "antigravity_oauth_csrf_token";
 const PKCE_COOKIE: &str = "antigravity_oauth_pkce_verifier";
 
-/// GET /antigravity/auth
+/// GET /antigravity/oauth/login (also `/antigravity/auth`)
 ///
 /// Starts the Antigravity `OAuth2` PKCE flow and redirects the browser to the configured auth URL.
 pub async fn antigravity_oauth_entry(
@@ -49,11 +50,13 @@ pub async fn antigravity_oauth_entry(
 ///
 /// Antigravity OAuth callback handler.
 ///
-/// This handler is intentionally **guarded**: it only activates when both `code` and `state`
-/// query parameters are present. Otherwise it returns 404, keeping `/` effectively not-found.
+/// This handler is intentionally **guarded**: it only activates when `state` and either
+/// `code` or `error` (declined consent) are present. Otherwise it returns 404, keeping `/`
+/// effectively not-found.
 pub async fn antigravity_oauth_callback_root(
     State(state): State<PolluxState>,
     Query(params): Query<HashMap<String, String>>,
+    headers: HeaderMap,
     jar: PrivateCookieJar,
 ) -> impl IntoResponse {
     let code = params
@@ -69,7 +72,14 @@ pub async fn antigravity_oauth_callback_root(
         .filter(|s| !s.is_empty())
         .map(str::to_string);
 
+    let has_state = state_param.is_some();
     let (Some(code), Some(state_param)) = (code, state_param) else {
+        if let Some(reason) = params.get("error").filter(|_| has_state) {
+            warn!(reason, "Antigravity OAuth callback without a code");
+            let (jar, _) = take_oauth_cookies(jar);
+            let resp = oauth_page::not_completed(&headers, reason, "/antigravity/oauth/login");
+            return (jar, resp).into_response();
+        }
         return StatusCode::NOT_FOUND.into_response();
     };
 
@@ -83,11 +93,11 @@ pub async fn antigravity_oauth_callback_root(
                 .antigravity
                 .submit_trusted_oauth(token_response);
             info!("Antigravity OAuth callback accepted");
-            (jar, (StatusCode::ACCEPTED, "Success")).into_response()
+            (jar, oauth_page::accepted(&headers, "Antigravity")).into_response()
         }
         Err(err) => {
             error!("Antigravity OAuth failure: {:?}", err);
-            (jar, err.into_response()).into_response()
+            (jar, oauth_page::failed(&headers, err)).into_response()
         }
     }
 }
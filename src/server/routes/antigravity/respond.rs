@@ -1,11 +1,18 @@
+use crate::config::StreamTransformerConfig;
 use crate::error::GeminiCliError;
+use crate::providers::UsageTracker;
+use crate::providers::gemini_aggregate::{GeminiAggregator, is_event_stream};
+use crate::providers::manifest::ProviderKind;
+use crate::providers::stream_transform::StreamPipeline;
 use crate::server::router::PolluxState;
+use crate::server::routes::resume::{StreamResume, resumable};
+use crate::server::sse_flush::{close_with_error_frame, with_heartbeat};
 use axum::{
     Json,
     http::StatusCode,
     response::{
         IntoResponse,
-        sse::{Event, KeepAlive, Sse},
+        sse::{Event, Sse},
     },
 };
 use eventsource_stream::Eventsource;
@@ -18,14 +25,26 @@ use tracing::{error, warn};
 pub async fn build_json_response(
     upstream_resp: reqwest::Response,
     state: &PolluxState,
+    usage: &UsageTracker,
 ) -> Result<(StatusCode, Json<GeminiResponseBody>), GeminiCliError> {
     let status = upstream_resp.status();
-    let response_body = transform_nostream(upstream_resp).await?;
-    let mut sniffer = state.providers.antigravity_thoughtsig.build_sniffer();
+    let mut pipeline =
+        StreamPipeline::from_config(&state.providers.antigravity_cfg.stream_transformers);
+    let mut response_body = if is_event_stream(&upstream_resp) {
+        transform_aggregated(upstream_resp, &mut pipeline).await?
+    } else {
+        transform_nostream(upstream_resp).await?
+    };
+    usage.observe_gemini(response_body.usageMetadata.as_ref());
+    let mut sniffer = state
+        .providers
+        .antigravity_thoughtsig
+        .build_sniffer(&usage.model());
     state
         .providers
         .antigravity_thoughtsig
         .sniff_response(&response_body, &mut sniffer);
+    pipeline.apply_gemini(&mut response_body, true);
     Ok((status, Json(response_body)))
 }
 
@@ -33,10 +52,19 @@ pub async fn build_json_response(
 pub fn build_stream_response(
     upstream_resp: reqwest::Response,
     state: &PolluxState,
+    usage: UsageTracker,
+    resume: Option<StreamResume>,
 ) -> impl IntoResponse {
-    let sniffer = state.providers.antigravity_thoughtsig.build_sniffer();
-    let raw_stream = upstream_resp.bytes_stream().eventsource();
-    let timed_stream = transform_stream(raw_stream, state.clone(), sniffer)
+    let sniffer = state
+        .providers
+        .antigravity_thoughtsig
+        .build_sniffer(&usage.model());
+    let pipeline =
+        StreamPipeline::from_config(&state.providers.antigravity_cfg.stream_transformers);
+    let upstream_resp = state.capture(ProviderKind::Antigravity, &usage.model(), upstream_resp);
+    let raw_stream = resumable(upstream_resp, resume);
+    let observe = observer(state.clone(), sniffer, usage);
+    let timed_stream = transform_stream(raw_stream, pipeline, observe)
         .timeout(Duration::from_mins(1))
         .map(|item| match item {
             Ok(Ok(event)) => Ok(event),
@@ -49,20 +77,46 @@ pub fn build_stream_response(
             }
         });
 
-    Sse::new(timed_stream).keep_alive(KeepAlive::default())
+    let timed_stream = close_with_error_frame(timed_stream, GeminiCliError::into_sse_event);
+    with_heartbeat(Sse::new(timed_stream), state.sse_flush)
 }
 
-fn transform_stream<I, E>(
-    s: I,
+/// Records usage and thought signatures of each parsed chunk.
+fn observer(
     state: PolluxState,
     mut sniffer: pollux_thoughtsig_core::SignatureSniffer,
+    usage: UsageTracker,
+) -> impl FnMut(&GeminiResponseBody) {
+    move |gemini_resp| {
+        usage.observe_gemini(gemini_resp.usageMetadata.as_ref());
+        state
+            .providers
+            .antigravity_thoughtsig
+            .sniff_response(gemini_resp, &mut sniffer);
+    }
+}
+
+/// Replay a captured upstream stream through the same translation as
+/// [`build_stream_response`], without usage or signature side effects.
+pub(crate) fn replay_stream<I, E>(
+    s: I,
+    transformers: &[StreamTransformerConfig],
+) -> impl Stream<Item = Result<Event, E>> + use<I, E>
+where
+    I: Stream<Item = Result<eventsource_stream::Event, E>>,
+{
+    transform_stream(s, StreamPipeline::from_config(transformers), |_| {})
+}
+
+fn transform_stream<I, E>(
+    s: I,
+    mut pipeline: StreamPipeline,
+    mut observe: impl FnMut(&GeminiResponseBody),
 ) -> impl Stream<Item = Result<Event, E>>
 where
     I: Stream<Item = Result<eventsource_stream::Event, E>>,
 {
     s.try_filter_map(move |upstream_event| {
-        let state = state.clone();
-
         let out = {
             if upstream_event.data.is_empty()
                 || upstream_event.data == "[DONE]"
@@ -70,14 +124,15 @@ where
             {
                 Ok(None)
             } else {
-                let Some(gemini_resp) = parse_sse_payload(&upstream_event.data) else {
+                let Some(mut gemini_resp) =
+                    parse_sse_payload(&pipeline.repair_payload(&upstream_event.data))
+                else {
                     return future::ready(Ok(None));
                 };
-
-                state
-                    .providers
-                    .antigravity_thoughtsig
-                    .sniff_response(&gemini_resp, &mut sniffer);
+                observe(&gemini_resp);
+                if !pipeline.apply_gemini(&mut gemini_resp, false) {
+                    return future::ready(Ok(None));
+                }
 
                 match Event::default().json_data(gemini_resp) {
                     Ok(ev) => Ok(Some(ev)),
@@ -102,6 +157,45 @@ fn parse_sse_payload(data: &str) -> Option<GeminiResponseBody> {
     Some(cli_resp.into())
 }
 
+/// Merge the SSE stream of a `stream_only_models` call into one response.
+async fn transform_aggregated(
+    upstream_resp: reqwest::Response,
+    pipeline: &mut StreamPipeline,
+) -> Result<GeminiResponseBody, GeminiCliError> {
+    let mut aggregator = GeminiAggregator::default();
+    let events = upstream_resp
+        .bytes_stream()
+        .eventsource()
+        .timeout(Duration::from_mins(1));
+    tokio::pin!(events);
+
+    while let Some(item) = events.next().await {
+        let upstream_event = match item {
+            Ok(Ok(event)) => event,
+            Ok(Err(e)) => return Err(GeminiCliError::StreamProtocolError(e.to_string())),
+            Err(_) => {
+                error!("Upstream SSE stream timed out (idle > 60s)");
+                return Err(GeminiCliError::StreamProtocolError(
+                    "Stream idle timeout".to_string(),
+                ));
+            }
+        };
+        if upstream_event.data.is_empty()
+            || upstream_event.data == "[DONE]"
+            || upstream_event.event == "done"
+        {
+            continue;
+        }
+        if let Some(chunk) = parse_sse_payload(&pipeline.repair_payload(&upstream_event.data)) {
+            aggregator.push(chunk);
+        }
+    }
+
+    aggregator.finish().ok_or_else(|| {
+        GeminiCliError::StreamProtocolError("Stream ended without a response".to_string())
+    })
+}
+
 async fn transform_nostream(
     upstream_resp: reqwest::Response,
 ) -> Result<GeminiResponseBody, GeminiCliError> {
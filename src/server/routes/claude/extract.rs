@@ -0,0 +1,106 @@
+use crate::error::ClaudeError;
+use crate::providers::claude::model_mask;
+use crate::server::guards::policy;
+use crate::server::pool::requested_pool;
+use crate::server::request_events::RequestMeta;
+use crate::server::router::PolluxState;
+use crate::server::session::session_route_key;
+use crate::utils::logging::with_pretty_json_debug;
+use axum::{
+    Json,
+    extract::{FromRequest, Request},
+    http::HeaderMap,
+};
+use pollux_schema::anthropic::AnthropicMessagesRequest;
+use std::borrow::Cow;
+use tracing::debug;
+
+use super::ClaudeContext;
+
+pub(crate) struct ClaudePreprocess {
+    pub body: AnthropicMessagesRequest,
+    pub ctx: ClaudeContext,
+}
+
+impl<S> FromRequest<S> for ClaudePreprocess
+where
+    S: Send + Sync + std::borrow::Borrow<PolluxState>,
+{
+    type Rejection = ClaudeError;
+
+    /// Extract and validate a `/claude/v1/messages` request.
+    ///
+    /// - `providers.claude.model_aliases` is applied first and the canonical
+    ///   name is written back into the body.
+    /// - Missing or unknown models are rejected with `invalid_request_error`
+    ///   before any credential is leased.
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let (parts, body) = req.into_parts();
+
+        let meta = parts.extensions.get::<RequestMeta>().cloned();
+        let route_key = session_route_key(&parts.headers);
+        let pool = requested_pool(&parts.headers);
+        let anthropic_version = header_string(&parts.headers, "anthropic-version");
+        let anthropic_beta = header_string(&parts.headers, "anthropic-beta");
+        let policy = policy::for_request(state.borrow(), &parts.headers, parts.uri.query());
+
+        let req = Request::from_parts(parts, body);
+        let Json(mut body) = Json::<AnthropicMessagesRequest>::from_request(req, state).await?;
+
+        let aliases = &state.borrow().providers.claude_cfg.model_aliases;
+        if let Cow::Owned(canonical) = aliases.resolve(&body.model) {
+            debug!(from = %body.model, to = %canonical, "[Claude] Model alias applied");
+            body.model = canonical;
+        }
+        let model = body.model.as_str();
+        if let Some(meta) = &meta {
+            meta.set_model(model);
+        }
+        if model.is_empty() {
+            return Err(ClaudeError::invalid_request("model: Field required"));
+        }
+        let Some(model_mask) = model_mask(model) else {
+            return Err(ClaudeError::invalid_request(format!(
+                "model: {model} is not served by this deployment"
+            )));
+        };
+
+        if let Some(policy) = policy {
+            policy::check_messages(policy, &body).map_err(ClaudeError::invalid_request)?;
+        }
+        if let Some(meta) = &meta {
+            meta.hash_request(model, &body);
+        }
+
+        with_pretty_json_debug(&body, |pretty_body| {
+            debug!(
+                channel = "claude",
+                req.model = %model,
+                req.stream = body.stream,
+                body = %pretty_body,
+                "[Claude] Extracted request body"
+            );
+        });
+
+        let ctx = ClaudeContext {
+            model: body.model.clone(),
+            stream: body.stream,
+            model_mask,
+            route_key,
+            pool,
+            anthropic_version,
+            anthropic_beta,
+        };
+
+        Ok(Self { body, ctx })
+    }
+}
+
+fn header_string(headers: &HeaderMap, name: &str) -> Option<String> {
+    headers
+        .get(name)
+        .and_then(|v| v.to_str().ok())
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .map(ToString::to_string)
+}
@@ -0,0 +1,60 @@
+use super::{extract::ClaudePreprocess, respond};
+use crate::error::ClaudeError;
+use crate::providers::UsageTracker;
+use crate::providers::manifest::ProviderKind;
+use crate::server::router::PolluxState;
+use axum::{
+    Json,
+    extract::State,
+    response::{IntoResponse, Response},
+};
+use pollux_schema::openai::OpenaiModelList;
+use tracing::debug;
+
+pub(crate) async fn claude_messages_handler(
+    State(state): State<PolluxState>,
+    preprocess: ClaudePreprocess,
+) -> Response {
+    let usage = state
+        .providers
+        .track_usage(ProviderKind::Claude, &preprocess.ctx.model);
+    let resp = forward_messages(&state, preprocess, &usage)
+        .await
+        .into_response();
+    usage.set_status(resp.status());
+    resp
+}
+
+async fn forward_messages(
+    state: &PolluxState,
+    ClaudePreprocess { body, ctx }: ClaudePreprocess,
+    usage: &UsageTracker,
+) -> Result<Response, ClaudeError> {
+    debug!(
+        model = %ctx.model,
+        stream = ctx.stream,
+        model_mask = %ctx.model_mask,
+        "Incoming Claude messages request"
+    );
+
+    let upstream_resp = state
+        .claude_caller
+        .call_claude(&state.providers.claude, &ctx, &body)
+        .await?;
+    usage.observe_response(&upstream_resp);
+
+    if ctx.stream {
+        Ok(
+            respond::build_stream_response(upstream_resp, usage.clone(), state.sse_flush)
+                .into_response(),
+        )
+    } else {
+        Ok(respond::build_json_response(upstream_resp, usage)
+            .await?
+            .into_response())
+    }
+}
+
+pub(super) async fn claude_models_handler() -> Result<Json<OpenaiModelList>, ClaudeError> {
+    Ok(Json(super::CLAUDE_MODEL_LIST.clone()))
+}
@@ -0,0 +1,51 @@
+use crate::model_catalog::ModelCapabilities;
+use crate::server::router::PolluxState;
+use axum::{
+    Router,
+    extract::DefaultBodyLimit,
+    routing::{get, post},
+};
+
+pub mod extract;
+pub mod handlers;
+pub mod resource;
+pub mod respond;
+
+use crate::providers::claude::SUPPORTED_MODEL_NAMES;
+use pollux_schema::openai::OpenaiModelList;
+use std::sync::LazyLock;
+
+pub static CLAUDE_MODEL_LIST: LazyLock<OpenaiModelList> = LazyLock::new(|| {
+    OpenaiModelList::from_model_names(SUPPORTED_MODEL_NAMES.iter().cloned(), "claude".to_string())
+});
+
+#[derive(Debug, Clone)]
+pub struct ClaudeContext {
+    pub model: String,
+    pub stream: bool,
+    pub model_mask: ModelCapabilities,
+    /// Hash of `x-pollux-session`, used to pin a session to the same account.
+    pub route_key: Option<u64>,
+    /// Label from `x-pollux-pool`; only credentials carrying it are leased.
+    pub pool: Option<String>,
+    /// Client `anthropic-version`, forwarded as-is when present.
+    pub anthropic_version: Option<String>,
+    /// Client `anthropic-beta` flags, merged with the OAuth flag upstream.
+    pub anthropic_beta: Option<String>,
+}
+
+pub fn router() -> Router<PolluxState> {
+    Router::new()
+        .route(
+            "/claude/v1/messages",
+            post(handlers::claude_messages_handler).layer(DefaultBodyLimit::max(
+                crate::server::DEFAULT_API_BODY_LIMIT_BYTES,
+            )),
+        )
+        .route("/claude/v1/models", get(handlers::claude_models_handler))
+}
+
+/// Credential upload, mounted behind `ResourceAddGuard` instead of key auth.
+pub fn resource_router() -> Router<PolluxState> {
+    Router::new().route("/claude/resource:add", post(resource::claude_resource_add))
+}
@@ -0,0 +1,80 @@
+use crate::config::SseFlushConfig;
+use crate::error::ClaudeError;
+use crate::providers::UsageTracker;
+use crate::server::sse_flush::{close_with_error_frame, with_heartbeat};
+use axum::{
+    Json,
+    http::StatusCode,
+    response::{
+        IntoResponse,
+        sse::{Event, Sse},
+    },
+};
+use eventsource_stream::Eventsource;
+use serde_json::Value;
+use std::time::Duration;
+use tokio_stream::StreamExt;
+use tracing::error;
+
+const SSE_IDLE_TIMEOUT: Duration = Duration::from_mins(1);
+
+/// Relay the upstream Messages SSE stream event by event, picking up usage
+/// from `message_start` and `message_delta` on the way.
+pub(super) fn build_stream_response(
+    upstream_resp: reqwest::Response,
+    usage: UsageTracker,
+    sse: SseFlushConfig,
+) -> impl IntoResponse {
+    let stream = upstream_resp
+        .bytes_stream()
+        .eventsource()
+        .timeout(SSE_IDLE_TIMEOUT)
+        .map(move |item| -> Result<Event, Box<ClaudeError>> {
+            match item {
+                Ok(Ok(event)) => {
+                    observe_event_usage(&usage, &event.data);
+                    let relayed = Event::default().data(event.data);
+                    Ok(if event.event.is_empty() || event.event == "message" {
+                        relayed
+                    } else {
+                        relayed.event(event.event)
+                    })
+                }
+                Ok(Err(e)) => Err(Box::new(ClaudeError::StreamProtocolError(e.to_string()))),
+                Err(_) => {
+                    error!("Upstream Claude SSE stream timed out (idle > 60s)");
+                    Err(Box::new(ClaudeError::StreamProtocolError(
+                        "Stream idle timeout".to_string(),
+                    )))
+                }
+            }
+        });
+
+    let stream = close_with_error_frame(stream, |e| e.into_sse_event());
+    with_heartbeat(Sse::new(stream), sse)
+}
+
+fn observe_event_usage(usage: &UsageTracker, data: &str) {
+    if !data.contains("\"usage\"") {
+        return;
+    }
+    let Ok(event) = serde_json::from_str::<Value>(data) else {
+        return;
+    };
+    let found = event
+        .get("message")
+        .and_then(|m| m.get("usage"))
+        .or_else(|| event.get("usage"));
+    usage.observe_anthropic(found);
+}
+
+/// Relay a non-streaming Messages response as JSON.
+pub(super) async fn build_json_response(
+    upstream_resp: reqwest::Response,
+    usage: &UsageTracker,
+) -> Result<(StatusCode, Json<Value>), ClaudeError> {
+    let status = upstream_resp.status();
+    let body: Value = upstream_resp.json().await?;
+    usage.observe_anthropic(body.get("usage"));
+    Ok((status, Json(body)))
+}
@@ -0,0 +1,153 @@
+//! Device-code login for Codex (`POST /codex/oauth/start`).
+//!
+//! Starting a flow returns the verification URL, the code the user types
+//! there and a poll id. A background task polls `OpenAI` until the user
+//! approves, then hands the tokens to the Codex actor like the browser
+//! callback does. `GET /codex/oauth/{poll_id}` reports progress; the random
+//! poll id is the only thing that identifies a flow.
+
+use super::oauth::check_token_response;
+use crate::PolluxError;
+use crate::error::IsRetryable;
+use crate::providers::codex::CodexActorHandle;
+use crate::providers::codex::client::oauth::endpoints::{
+    CodexOauthEndpoints, DEVICE_VERIFICATION_URL, DeviceUserCode,
+};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+/// How long a user has to approve a device code.
+const DEVICE_CODE_TTL: Duration = Duration::from_mins(15);
+/// Finished flows stay queryable for this long after they expire.
+const FLOW_RETENTION: Duration = Duration::from_mins(30);
+
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum DeviceFlowStatus {
+    Pending,
+    Completed,
+    Failed { message: String },
+    Expired,
+}
+
+/// Response of `POST /codex/oauth/start`.
+#[derive(Debug, Serialize)]
+pub struct DeviceFlowStarted {
+    pub poll_id: String,
+    pub verification_url: &'static str,
+    pub user_code: String,
+    pub expires_in: u64,
+}
+
+#[derive(Debug)]
+struct DeviceFlow {
+    started: Instant,
+    status: DeviceFlowStatus,
+}
+
+/// Device flows started on this process, keyed by poll id.
+#[derive(Debug, Clone, Default)]
+pub struct DeviceFlows {
+    flows: Arc<Mutex<HashMap<String, DeviceFlow>>>,
+}
+
+impl DeviceFlows {
+    /// Request a device code and start polling for its approval.
+    pub(crate) async fn start(
+        &self,
+        http_client: reqwest::Client,
+        codex: CodexActorHandle,
+    ) -> Result<DeviceFlowStarted, PolluxError> {
+        let device = CodexOauthEndpoints::request_device_code(&http_client).await?;
+        let poll_id = uuid::Uuid::new_v4().simple().to_string();
+        if let Ok(mut flows) = self.flows.lock() {
+            flows.retain(|_, flow| flow.started.elapsed() < FLOW_RETENTION);
+            flows.insert(
+                poll_id.clone(),
+                DeviceFlow {
+                    started: Instant::now(),
+                    status: DeviceFlowStatus::Pending,
+                },
+            );
+        }
+        info!(poll_id, "[Codex] Device authorization started");
+
+        let started = DeviceFlowStarted {
+            poll_id: poll_id.clone(),
+            verification_url: DEVICE_VERIFICATION_URL,
+            user_code: device.user_code.clone(),
+            expires_in: DEVICE_CODE_TTL.as_secs(),
+        };
+        let flows = self.clone();
+        tokio::spawn(async move {
+            let status = poll_until_done(&device, http_client, &codex).await;
+            flows.finish(&poll_id, status);
+        });
+        Ok(started)
+    }
+
+    pub(crate) fn status(&self, poll_id: &str) -> Option<DeviceFlowStatus> {
+        let flows = self.flows.lock().ok()?;
+        flows.get(poll_id).map(|flow| flow.status.clone())
+    }
+
+    fn finish(&self, poll_id: &str, status: DeviceFlowStatus) {
+        match &status {
+            DeviceFlowStatus::Failed { message } => {
+                warn!(poll_id, message, "[Codex] Device authorization failed");
+            }
+            other => info!(poll_id, status = ?other, "[Codex] Device authorization finished"),
+        }
+        if let Ok(mut flows) = self.flows.lock()
+            && let Some(flow) = flows.get_mut(poll_id)
+        {
+            flow.status = status;
+        }
+    }
+}
+
+async fn poll_until_done(
+    device: &DeviceUserCode,
+    http_client: reqwest::Client,
+    codex: &CodexActorHandle,
+) -> DeviceFlowStatus {
+    let deadline = Instant::now() + DEVICE_CODE_TTL;
+    let interval = device.interval();
+    loop {
+        if Instant::now() >= deadline {
+            return DeviceFlowStatus::Expired;
+        }
+        tokio::time::sleep(interval).await;
+        let authorization = match CodexOauthEndpoints::poll_device_code(device, &http_client).await
+        {
+            Ok(Some(authorization)) => authorization,
+            Ok(None) => continue,
+            Err(e) if e.is_retryable() => {
+                warn!(error = %e, "[Codex] Device authorization poll failed; retrying");
+                continue;
+            }
+            Err(e) => {
+                return DeviceFlowStatus::Failed {
+                    message: e.to_string(),
+                };
+            }
+        };
+        let tokens =
+            CodexOauthEndpoints::exchange_device_authorization(authorization, http_client.clone())
+                .await
+                .map_err(PolluxError::from)
+                .and_then(check_token_response);
+        return match tokens {
+            Ok(tokens) => {
+                codex.submit_trusted_oauth(tokens);
+                DeviceFlowStatus::Completed
+            }
+            Err(e) => DeviceFlowStatus::Failed {
+                message: e.to_string(),
+            },
+        };
+    }
+}
@@ -1,5 +1,12 @@
+use crate::config::RequestPolicyConfig;
 use crate::error::CodexError;
 use crate::providers::codex::model_mask;
+use crate::providers::codex::reasoning::apply_reasoning_defaults;
+use crate::server::guards::policy;
+use crate::server::pool::requested_pool;
+use crate::server::request_events::RequestMeta;
+use crate::server::router::PolluxState;
+use crate::server::session::{route_key, session_route_key};
 use crate::utils::logging::with_pretty_json_debug;
 use axum::{
     Json,
@@ -8,9 +15,12 @@ use axum::{
 };
 use pollux_schema::OpenaiResponsesErrorObject;
 use serde_json::Value;
+use std::borrow::Cow;
 use tracing::debug;
 
+use crate::providers::codex::chat_compat::chat_request_to_responses;
 use pollux_schema::OpenaiRequestBody;
+use pollux_schema::openai::ChatCompletionRequest;
 
 use super::CodexContext;
 use super::headers::OpenaiRequestHeaders;
@@ -23,7 +33,7 @@ pub(crate) struct CodexPreprocess {
 
 impl<S> FromRequest<S> for CodexPreprocess
 where
-    S: Send + Sync,
+    S: Send + Sync + std::borrow::Borrow<PolluxState>,
 {
     type Rejection = CodexError;
 
@@ -42,6 +52,8 @@ where
     ///
     /// Notes:
     /// - We intentionally do not `trim()` or otherwise normalize `model`; matching is exact.
+    /// - `providers.codex.model_aliases` is applied first and the canonical name is written
+    ///   back into the body, so upstream only ever sees configured model names.
     async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
         // Split request so we can extract headers from Parts, then reassemble for Json.
         let (mut parts, body) = req.into_parts();
@@ -51,69 +63,170 @@ where
         let codex_headers = OpenaiRequestHeaders::from_request_parts(&mut parts, state)
             .await
             .unwrap();
+        let meta = parts.extensions.get::<RequestMeta>().cloned();
+        let route_key = session_route_key(&parts.headers)
+            .unwrap_or_else(|| route_key(&codex_headers.session_id));
+        let pool = requested_pool(&parts.headers);
+        let policy = policy::for_request(state.borrow(), &parts.headers, parts.uri.query());
 
         let req = Request::from_parts(parts, body);
         let Json(body) = Json::<OpenaiRequestBody>::from_request(req, state).await?;
+        let (body, ctx) = prepare(state.borrow(), body, meta.as_ref(), policy, route_key, pool)?;
 
-        let model = body.model.as_str();
-        if model.is_empty() {
-            return Err(CodexError::RequestRejected {
+        Ok(Self {
+            body,
+            ctx,
+            headers: codex_headers,
+        })
+    }
+}
+
+/// `OpenAI` Chat Completions request translated into a Responses body.
+pub(crate) struct CodexChatPreprocess {
+    pub body: OpenaiRequestBody,
+    pub ctx: CodexContext,
+    pub headers: OpenaiRequestHeaders,
+    /// `stream_options.include_usage`.
+    pub include_usage: bool,
+}
+
+impl<S> FromRequest<S> for CodexChatPreprocess
+where
+    S: Send + Sync + std::borrow::Borrow<PolluxState>,
+{
+    type Rejection = CodexError;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let (mut parts, body) = req.into_parts();
+
+        let codex_headers = OpenaiRequestHeaders::from_request_parts(&mut parts, state)
+            .await
+            .unwrap();
+        let meta = parts.extensions.get::<RequestMeta>().cloned();
+        let route_key = session_route_key(&parts.headers)
+            .unwrap_or_else(|| route_key(&codex_headers.session_id));
+        let pool = requested_pool(&parts.headers);
+        let policy = policy::for_request(state.borrow(), &parts.headers, parts.uri.query());
+
+        let req = Request::from_parts(parts, body);
+        let Json(chat) = Json::<ChatCompletionRequest>::from_request(req, state).await?;
+        let include_usage = chat
+            .stream_options
+            .as_ref()
+            .is_some_and(|o| o.include_usage);
+        let body =
+            chat_request_to_responses(chat).map_err(|message| CodexError::RequestRejected {
                 status: StatusCode::BAD_REQUEST,
                 body: OpenaiResponsesErrorObject {
-                    code: Some("INVALID_MODEL".to_string()),
-                    message: "missing or empty model".to_string(),
-                    r#type: "INVALID_MODEL".to_string(),
+                    code: Some("INVALID_REQUEST".to_string()),
+                    message,
+                    r#type: "INVALID_REQUEST".to_string(),
                     param: None,
                 },
                 debug_message: None,
-            });
-        }
+            })?;
+        let (body, ctx) = prepare(state.borrow(), body, meta.as_ref(), policy, route_key, pool)?;
+
+        Ok(Self {
+            body,
+            ctx,
+            headers: codex_headers,
+            include_usage,
+        })
+    }
+}
+
+/// Shared tail of the Responses and Chat Completions extractors: alias
+/// resolution, model validation, key policy, reasoning defaults and request hashing.
+#[allow(clippy::result_large_err)] // rejection type of both extractors
+fn prepare(
+    state: &PolluxState,
+    mut body: OpenaiRequestBody,
+    meta: Option<&RequestMeta>,
+    policy: Option<&RequestPolicyConfig>,
+    route_key: u64,
+    pool: Option<String>,
+) -> Result<(OpenaiRequestBody, CodexContext), CodexError> {
+    if let Cow::Owned(canonical) = state.providers.codex_cfg.model_aliases.resolve(&body.model) {
+        debug!(from = %body.model, to = %canonical, "[Codex] Model alias applied");
+        body.model = canonical;
+    }
+    let model = body.model.as_str();
+    if let Some(meta) = meta {
+        meta.set_model(model);
+    }
+    if model.is_empty() {
+        return Err(CodexError::RequestRejected {
+            status: StatusCode::BAD_REQUEST,
+            body: OpenaiResponsesErrorObject {
+                code: Some("INVALID_MODEL".to_string()),
+                message: "missing or empty model".to_string(),
+                r#type: "INVALID_MODEL".to_string(),
+                param: None,
+            },
+            debug_message: None,
+        });
+    }
 
-        let stream = body.stream;
+    let stream = body.stream;
 
-        let Some(model_mask) = model_mask(model) else {
-            return Err(CodexError::RequestRejected {
+    let Some(model_mask) = model_mask(model) else {
+        return Err(CodexError::RequestRejected {
+            status: StatusCode::BAD_REQUEST,
+            body: OpenaiResponsesErrorObject {
+                code: Some("UNSUPPORTED_MODEL".to_string()),
+                message: "unsupported model (exact match required)".to_string(),
+                r#type: "UNSUPPORTED_MODEL".to_string(),
+                param: None,
+            },
+            debug_message: None,
+        });
+    };
+
+    if let Some(policy) = policy {
+        policy::check_responses(policy, &mut body).map_err(|message| {
+            CodexError::RequestRejected {
                 status: StatusCode::BAD_REQUEST,
                 body: OpenaiResponsesErrorObject {
-                    code: Some("UNSUPPORTED_MODEL".to_string()),
-                    message: "unsupported model (exact match required)".to_string(),
-                    r#type: "UNSUPPORTED_MODEL".to_string(),
+                    code: Some("POLICY_VIOLATION".to_string()),
+                    message,
+                    r#type: "INVALID_REQUEST".to_string(),
                     param: None,
                 },
                 debug_message: None,
-            });
-        };
+            }
+        })?;
+    }
 
-        with_pretty_json_debug(&body, |pretty_body| {
-            debug!(
-                channel = "codex",
-                req.model = %model,
-                req.stream = stream,
-                body = %pretty_body,
-                "[Codex] Extracted normalized request body"
-            );
-        });
+    apply_reasoning_defaults(
+        &state.providers.codex_cfg.reasoning,
+        &body.model,
+        &mut body.reasoning,
+    );
+    let model = body.model.as_str();
+    if let Some(meta) = meta {
+        meta.hash_request(model, &body);
+    }
 
-        let route_key = {
-            use std::hash::Hasher;
-            let mut hasher = ahash::AHasher::default();
-            hasher.write(codex_headers.session_id.as_bytes());
-            hasher.finish()
-        };
+    with_pretty_json_debug(&body, |pretty_body| {
+        debug!(
+            channel = "codex",
+            req.model = %model,
+            req.stream = stream,
+            body = %pretty_body,
+            "[Codex] Extracted normalized request body"
+        );
+    });
 
-        let ctx = CodexContext {
-            model: body.model.clone(),
-            stream,
-            model_mask,
-            route_key: Some(route_key),
-        };
+    let ctx = CodexContext {
+        model: body.model.clone(),
+        stream,
+        model_mask,
+        route_key: Some(route_key),
+        pool,
+    };
 
-        Ok(Self {
-            body,
-            ctx,
-            headers: codex_headers,
-        })
-    }
+    Ok((body, ctx))
 }
 
 /// Lightweight extractor for `/codex/v1/responses/compact`.
@@ -128,7 +241,7 @@ pub(crate) struct CodexCompactPreprocess {
 
 impl<S> FromRequest<S> for CodexCompactPreprocess
 where
-    S: Send + Sync,
+    S: Send + Sync + std::borrow::Borrow<PolluxState>,
 {
     type Rejection = CodexError;
 
@@ -138,14 +251,41 @@ where
         let codex_headers = OpenaiRequestHeaders::from_request_parts(&mut parts, state)
             .await
             .unwrap();
+        let meta = parts.extensions.get::<RequestMeta>().cloned();
+        let route_key = session_route_key(&parts.headers)
+            .unwrap_or_else(|| route_key(&codex_headers.session_id));
+        let pool = requested_pool(&parts.headers);
 
         let req = Request::from_parts(parts, body);
-        let Json(value) = Json::<Value>::from_request(req, state).await?;
+        let Json(mut value) = Json::<Value>::from_request(req, state).await?;
+
+        let canonical = value
+            .get("model")
+            .and_then(Value::as_str)
+            .and_then(|model| {
+                match state
+                    .borrow()
+                    .providers
+                    .codex_cfg
+                    .model_aliases
+                    .resolve(model)
+                {
+                    Cow::Owned(canonical) => Some(canonical),
+                    Cow::Borrowed(_) => None,
+                }
+            });
+        if let Some(canonical) = canonical {
+            debug!(to = %canonical, "[Codex] Model alias applied");
+            value["model"] = Value::String(canonical);
+        }
 
         let model = value
             .get("model")
             .and_then(Value::as_str)
             .unwrap_or_default();
+        if let Some(meta) = &meta {
+            meta.set_model(model);
+        }
 
         if model.is_empty() {
             return Err(CodexError::RequestRejected {
@@ -173,18 +313,16 @@ where
             });
         };
 
-        let route_key = {
-            use std::hash::Hasher;
-            let mut hasher = ahash::AHasher::default();
-            hasher.write(codex_headers.session_id.as_bytes());
-            hasher.finish()
-        };
+        if let Some(meta) = &meta {
+            meta.hash_request(model, &value);
+        }
 
         let ctx = CodexContext {
             model: model.to_string(),
             stream: false,
             model_mask,
             route_key: Some(route_key),
+            pool,
         };
 
         Ok(Self {
@@ -1,7 +1,13 @@
 use super::{extract::CodexPreprocess, respond};
 use crate::error::CodexError;
+use crate::providers::UsageTracker;
+use crate::providers::chat_compat::ChatResponseMeta;
+use crate::providers::codex::chat_compat::ResponsesChatStream;
+use crate::providers::manifest::ProviderKind;
+use crate::providers::response_cache::{CacheEntry, ResponseCache};
 use crate::server::router::PolluxState;
-use crate::server::routes::codex::extract::CodexCompactPreprocess;
+use crate::server::routes::codex::CodexContext;
+use crate::server::routes::codex::extract::{CodexChatPreprocess, CodexCompactPreprocess};
 use axum::{
     Json,
     extract::State,
@@ -9,11 +15,52 @@ use axum::{
 };
 use pollux_schema::CodexRequestBody;
 use pollux_schema::openai::OpenaiModelList;
+use pollux_schema::openai::OpenaiRequestBody;
 use tracing::debug;
 
-pub(super) async fn codex_response_handler(
+pub(crate) async fn codex_response_handler(
     State(state): State<PolluxState>,
+    preprocess: CodexPreprocess,
+) -> Response {
+    let cached = cache_entry(&state, "responses", &preprocess.body, &preprocess.ctx);
+    if let Some(entry) = &cached
+        && let Some(hit) = entry.hit().await
+    {
+        return hit;
+    }
+    let usage = state
+        .providers
+        .track_usage(ProviderKind::Codex, &preprocess.ctx.model);
+    let resp = forward_response(&state, preprocess, &usage)
+        .await
+        .into_response();
+    usage.set_status(resp.status());
+    match cached {
+        Some(entry) => entry.store(resp).await,
+        None => resp,
+    }
+}
+
+fn cache_entry<'a>(
+    state: &'a PolluxState,
+    route: &str,
+    body: &OpenaiRequestBody,
+    ctx: &CodexContext,
+) -> Option<CacheEntry<'a>> {
+    ResponseCache::entry(
+        state.providers.codex_response_cache.as_ref(),
+        &format!("codex:{route}"),
+        &ctx.model,
+        body,
+        ctx.stream,
+        body.temperature.map(f64::from),
+    )
+}
+
+async fn forward_response(
+    state: &PolluxState,
     CodexPreprocess { body, ctx, headers }: CodexPreprocess,
+    usage: &UsageTracker,
 ) -> Result<Response, CodexError> {
     let codex_body: CodexRequestBody = body.into();
 
@@ -21,7 +68,7 @@ pub(super) async fn codex_response_handler(
         model = %ctx.model,
         client_stream = ctx.stream,
         upstream_stream = codex_body.stream,
-        model_mask = format_args!("0x{:016x}", ctx.model_mask),
+        model_mask = %ctx.model_mask,
         "Incoming Codex request"
     );
 
@@ -29,26 +76,115 @@ pub(super) async fn codex_response_handler(
         .codex_caller
         .call_codex(&state.providers.codex, &ctx, &codex_body, &headers)
         .await?;
+    usage.observe_response(&upstream_resp);
 
     if ctx.stream {
-        Ok(respond::build_stream_response(upstream_resp).into_response())
+        Ok(
+            respond::build_stream_response(upstream_resp, usage.clone(), state.sse_flush)
+                .into_response(),
+        )
     } else {
         let (status, body) = respond::build_json_response_from_stream(upstream_resp).await?;
+        usage.observe_openai(body.get("usage"));
         Ok((status, body).into_response())
     }
 }
 
+pub(crate) async fn codex_chat_completions_handler(
+    State(state): State<PolluxState>,
+    preprocess: CodexChatPreprocess,
+) -> Response {
+    let cached = cache_entry(&state, "chat", &preprocess.body, &preprocess.ctx);
+    if let Some(entry) = &cached
+        && let Some(hit) = entry.hit().await
+    {
+        return hit;
+    }
+    let usage = state
+        .providers
+        .track_usage(ProviderKind::Codex, &preprocess.ctx.model);
+    let resp = forward_chat(&state, preprocess, &usage)
+        .await
+        .into_response();
+    usage.set_status(resp.status());
+    match cached {
+        Some(entry) => entry.store(resp).await,
+        None => resp,
+    }
+}
+
+async fn forward_chat(
+    state: &PolluxState,
+    CodexChatPreprocess {
+        body,
+        ctx,
+        headers,
+        include_usage,
+    }: CodexChatPreprocess,
+    usage: &UsageTracker,
+) -> Result<Response, CodexError> {
+    let codex_body: CodexRequestBody = body.into();
+
+    debug!(
+        model = %ctx.model,
+        client_stream = ctx.stream,
+        model_mask = %ctx.model_mask,
+        "Incoming Codex chat completions request"
+    );
+
+    let upstream_resp = state
+        .codex_caller
+        .call_codex(&state.providers.codex, &ctx, &codex_body, &headers)
+        .await?;
+    usage.observe_response(&upstream_resp);
+
+    let meta = ChatResponseMeta::new(&ctx.model);
+    if ctx.stream {
+        let chat = ResponsesChatStream::new(meta, include_usage);
+        Ok(
+            respond::build_chat_stream_response(
+                upstream_resp,
+                usage.clone(),
+                chat,
+                state.sse_flush,
+            )
+            .into_response(),
+        )
+    } else {
+        Ok(
+            respond::build_chat_json_response(upstream_resp, usage, &meta)
+                .await
+                .into_response(),
+        )
+    }
+}
+
 pub(super) async fn codex_models_handler() -> Result<Json<OpenaiModelList>, CodexError> {
     Ok(Json(super::CODEX_MODEL_LIST.clone()))
 }
 
 pub(super) async fn codex_compact_handler(
     State(state): State<PolluxState>,
+    preprocess: CodexCompactPreprocess,
+) -> Response {
+    let usage = state
+        .providers
+        .track_usage(ProviderKind::Codex, &preprocess.ctx.model);
+    let resp = forward_compact(&state, preprocess, &usage)
+        .await
+        .into_response();
+    usage.set_status(resp.status());
+    resp
+}
+
+async fn forward_compact(
+    state: &PolluxState,
     CodexCompactPreprocess { body, ctx, headers }: CodexCompactPreprocess,
+    usage: &UsageTracker,
 ) -> Result<Response, CodexError> {
     debug!(
         model = %ctx.model,
-        model_mask = format_args!("0x{:016x}", ctx.model_mask),
+        model_mask = %ctx.model_mask,
         "Incoming Codex compact request"
     );
 
@@ -56,12 +192,16 @@ pub(super) async fn codex_compact_handler(
         .codex_caller
         .call_codex_compact(&state.providers.codex, &ctx, &body, &headers)
         .await?;
+    usage.observe_response(&upstream_resp);
 
     let status = upstream_resp.status();
     let body = upstream_resp
         .bytes()
         .await
         .map_err(|e| CodexError::StreamProtocolError(e.to_string()))?;
+    if let Ok(value) = serde_json::from_slice::<serde_json::Value>(&body) {
+        usage.observe_openai(value.get("usage"));
+    }
 
     Ok((status, body).into_response())
 }
@@ -253,6 +253,7 @@ mod tests {
             access_token: "at-test".to_string(),
             account_id: "acct-test".to_string(),
             email: None,
+            proxy_url: None,
         };
 
         let map = CodexRequestHeaders::build(&inbound, &lease).into_header_map();
@@ -1,3 +1,4 @@
+use crate::model_catalog::ModelCapabilities;
 use crate::server::router::PolluxState;
 use axum::{
     Router,
@@ -5,6 +6,7 @@ use axum::{
     routing::{get, post},
 };
 
+pub mod device;
 pub mod extract;
 pub mod handlers;
 pub mod headers;
@@ -24,9 +26,11 @@ pub static CODEX_MODEL_LIST: LazyLock<OpenaiModelList> = LazyLock::new(|| {
 pub struct CodexContext {
     pub model: String,
     pub stream: bool,
-    pub model_mask: u64,
-    /// `AHash` of `session_id`, used as a routing/cache key to pin a session to the same account.
+    pub model_mask: ModelCapabilities,
+    /// Hash of `x-pollux-session` (or `session_id`), used to pin a session to the same account.
     pub route_key: Option<u64>,
+    /// Label from `x-pollux-pool`; only credentials carrying it are leased.
+    pub pool: Option<String>,
 }
 
 pub fn router() -> Router<PolluxState> {
@@ -43,6 +47,19 @@ pub fn router() -> Router<PolluxState> {
                 crate::server::DEFAULT_API_BODY_LIMIT_BYTES,
             )),
         )
+        .route(
+            "/codex/v1/chat/completions",
+            post(handlers::codex_chat_completions_handler).layer(DefaultBodyLimit::max(
+                crate::server::DEFAULT_API_BODY_LIMIT_BYTES,
+            )),
+        )
         .route("/codex/v1/models", get(handlers::codex_models_handler))
+}
+
+/// Credential upload and device-code login, mounted behind `ResourceAddGuard`
+/// instead of key auth.
+pub fn resource_router() -> Router<PolluxState> {
+    Router::new()
         .route("/codex/resource:add", post(resource::codex_resource_add))
+        .route("/codex/oauth/start", post(oauth::codex_device_start))
 }
@@ -1,12 +1,14 @@
+use super::device::DeviceFlowStarted;
 use crate::PolluxError;
 use crate::error::OauthError;
 use crate::providers::codex::client::oauth::endpoints::CodexOauthEndpoints;
 use crate::providers::codex::oauth::OauthTokenResponse;
 use crate::server::router::PolluxState;
 use axum::{
-    extract::{Query, State},
+    Json,
+    extract::{Path, Query, State},
     http::StatusCode,
-    response::{IntoResponse, Redirect},
+    response::{IntoResponse, Redirect, Response},
 };
 use axum_extra::extract::cookie::{Cookie, PrivateCookieJar, SameSite};
 use oauth2::{AuthorizationCode, PkceCodeChallenge, PkceCodeVerifier, TokenResponse};
@@ -105,6 +107,13 @@ async fn process_oauth_exchange(
         details: None,
     })?;
 
+    check_token_response(token_response)
+}
+
+/// Reject token responses the Codex actor cannot onboard.
+pub(super) fn check_token_response(
+    token_response: OauthTokenResponse,
+) -> Result<OauthTokenResponse, PolluxError> {
     if token_response.refresh_token().is_none() {
         return Err(OauthError::Flow {
             code: "MISSING_REFRESH_TOKEN".to_string(),
@@ -132,6 +141,30 @@ async fn process_oauth_exchange(
     Ok(token_response)
 }
 
+/// POST /codex/oauth/start
+///
+/// Starts a device-code login for a machine without a browser callback.
+pub async fn codex_device_start(
+    State(state): State<PolluxState>,
+) -> Result<Json<DeviceFlowStarted>, PolluxError> {
+    let started = state
+        .codex_device_flows
+        .start(state.codex_client.clone(), state.providers.codex.clone())
+        .await?;
+    Ok(Json(started))
+}
+
+/// GET `/codex/oauth/{poll_id}`
+pub async fn codex_device_status(
+    State(state): State<PolluxState>,
+    Path(poll_id): Path<String>,
+) -> Response {
+    match state.codex_device_flows.status(&poll_id) {
+        Some(status) => Json(status).into_response(),
+        None => (StatusCode::NOT_FOUND, "Unknown poll id").into_response(),
+    }
+}
+
 fn take_oauth_cookies(jar: PrivateCookieJar) -> (PrivateCookieJar, Option<(String, String)>) {
     let csrf = jar.get(CSRF_COOKIE).map(|c| c.value().to_string());
     let pkce = jar.get(PKCE_COOKIE).map(|c| c.value().to_string());
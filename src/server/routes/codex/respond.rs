@@ -1,27 +1,39 @@
+use crate::config::SseFlushConfig;
 use crate::error::CodexError;
+use crate::providers::UsageTracker;
+use crate::providers::chat_compat::ChatResponseMeta;
+use crate::providers::codex::chat_compat::{ResponsesChatStream, responses_to_chat_completion};
+use crate::server::sse_flush::{close_with_error_frame, with_heartbeat};
 use axum::{
     Json,
     body::Bytes,
     http::StatusCode,
     response::{
         IntoResponse,
-        sse::{Event, KeepAlive, Sse},
+        sse::{Event, Sse},
     },
 };
 use eventsource_stream::Eventsource;
-use futures::{Stream, TryStreamExt};
+use futures::{Stream, TryStreamExt, future};
+use pollux_schema::openai::{ChatCompletion, ChatCompletionChunk};
 use serde_json::Value;
+use std::collections::{BTreeMap, VecDeque};
 use std::time::Duration;
 use tokio_stream::StreamExt;
-use tracing::error;
+use tracing::{error, warn};
 
 const SSE_IDLE_TIMEOUT: Duration = Duration::from_mins(1);
 
 /// Build SSE stream response.
-pub(super) fn build_stream_response(upstream_resp: reqwest::Response) -> impl IntoResponse {
+pub(super) fn build_stream_response(
+    upstream_resp: reqwest::Response,
+    usage: UsageTracker,
+    sse: SseFlushConfig,
+) -> impl IntoResponse {
     let raw_stream = upstream_resp.bytes_stream().eventsource();
-    let timed_stream = transform_stream(raw_stream).timeout(SSE_IDLE_TIMEOUT).map(
-        |item| -> Result<_, Box<CodexError>> {
+    let timed_stream = transform_stream(raw_stream, usage)
+        .timeout(SSE_IDLE_TIMEOUT)
+        .map(|item| -> Result<_, Box<CodexError>> {
             match item {
                 Ok(Ok(event)) => Ok(event),
                 Ok(Err(e)) => Err(Box::new(CodexError::StreamProtocolError(e.to_string()))),
@@ -32,17 +44,17 @@ pub(super) fn build_stream_response(upstream_resp: reqwest::Response) -> impl In
                     )))
                 }
             }
-        },
-    );
+        });
 
-    Sse::new(timed_stream).keep_alive(KeepAlive::default())
+    let timed_stream = close_with_error_frame(timed_stream, |e| e.into_failed_event());
+    with_heartbeat(Sse::new(timed_stream), sse)
 }
 
 /// Build JSON response from a streaming upstream response.
 ///
 /// Codex upstream can be forced into SSE mode (e.g. `stream=true`) even when the client
-/// asked for a non-streaming response. In that case we buffer the SSE stream until the
-/// final `response.completed` event and return the embedded `response` as JSON.
+/// asked for a non-streaming response. In that case we buffer the SSE stream until its
+/// terminal event and return the aggregated `response` as JSON.
 pub(super) async fn build_json_response_from_stream(
     upstream_resp: reqwest::Response,
 ) -> Result<(StatusCode, Json<Value>), CodexError> {
@@ -58,8 +70,8 @@ where
     E: std::error::Error + Send + Sync + 'static,
 {
     // Codex upstream is always forced to `stream=true` (SSE). For non-stream clients we buffer the
-    // SSE stream until `response.completed` and return the embedded `response` as JSON.
-    let mut last_json: Option<Value> = None;
+    // SSE stream and rebuild the final `response` object from its events.
+    let mut aggregator = ResponsesAggregator::default();
 
     let raw_stream = stream.eventsource();
     let timed_stream = raw_stream.timeout(SSE_IDLE_TIMEOUT);
@@ -84,46 +96,219 @@ where
             break;
         }
 
-        let value: Value = match serde_json::from_str(&upstream_event.data) {
-            Ok(v) => v,
-            Err(_) => continue,
+        let Ok(value) = serde_json::from_str::<Value>(&upstream_event.data) else {
+            continue;
         };
-
-        if value.get("type").and_then(Value::as_str) == Some("response.completed")
-            && let Some(resp) = value.get("response")
+        if aggregator
+            .on_event(&value)
+            .map_err(CodexError::StreamProtocolError)?
         {
-            return Ok(resp.clone());
+            break;
         }
-
-        last_json = Some(value);
     }
 
-    // Best-effort fallback: return the last JSON event we saw.
-    if let Some(root) = last_json {
-        if root.get("type").and_then(Value::as_str) == Some("response.completed")
-            && let Some(resp) = root.get("response")
-        {
-            return Ok(resp.clone());
+    aggregator.finish().ok_or_else(|| {
+        CodexError::StreamProtocolError("Stream ended without a response".to_string())
+    })
+}
+
+/// Rebuilds the final Responses object from Codex's event stream.
+///
+/// The terminal event carries the response, but Codex may leave its `output`
+/// empty; the items are then taken from the `response.output_item.*` events.
+#[derive(Debug, Default)]
+struct ResponsesAggregator {
+    response: Option<Value>,
+    items: BTreeMap<u64, Value>,
+    terminal: bool,
+}
+
+impl ResponsesAggregator {
+    /// Feed one event; `Ok(true)` once the response reached a terminal state,
+    /// `Err` with the upstream message on an `error` event.
+    fn on_event(&mut self, event: &Value) -> Result<bool, String> {
+        match event.get("type").and_then(Value::as_str) {
+            Some("response.created" | "response.in_progress") => {
+                if let Some(response) = event.get("response") {
+                    self.response = Some(response.clone());
+                }
+                Ok(false)
+            }
+            Some("response.output_item.added" | "response.output_item.done") => {
+                if let (Some(index), Some(item)) = (
+                    event.get("output_index").and_then(Value::as_u64),
+                    event.get("item"),
+                ) {
+                    self.items.insert(index, item.clone());
+                }
+                Ok(false)
+            }
+            Some("response.completed" | "response.incomplete" | "response.failed") => {
+                if let Some(response) = event.get("response") {
+                    self.response = Some(response.clone());
+                }
+                self.terminal = true;
+                Ok(true)
+            }
+            Some("error") => {
+                let message = event
+                    .get("message")
+                    .or_else(|| event.pointer("/error/message"))
+                    .and_then(Value::as_str)
+                    .unwrap_or("upstream stream error");
+                Err(message.to_string())
+            }
+            _ => Ok(false),
         }
-        return Ok(root);
     }
 
-    Ok(Value::Null)
+    /// The final response, marked `incomplete` when the stream stopped early;
+    /// `None` if upstream never sent one.
+    fn finish(self) -> Option<Value> {
+        let mut response = self.response?;
+        let Some(object) = response.as_object_mut() else {
+            return Some(response);
+        };
+        let output_empty = object
+            .get("output")
+            .and_then(Value::as_array)
+            .is_none_or(Vec::is_empty);
+        if output_empty && !self.items.is_empty() {
+            object.insert(
+                "output".to_string(),
+                Value::Array(self.items.into_values().collect()),
+            );
+        }
+        if !self.terminal {
+            warn!("Upstream Codex stream ended before a terminal response event");
+            object.insert("status".to_string(), Value::from("incomplete"));
+        }
+        Some(response)
+    }
 }
 
 /// Convert upstream SSE events into SSE `Event`s for clients.
-pub fn transform_stream<I, E>(s: I) -> impl Stream<Item = Result<Event, E>>
+///
+/// Token usage is read from the terminal `response.completed` event.
+pub fn transform_stream<I, E>(s: I, usage: UsageTracker) -> impl Stream<Item = Result<Event, E>>
 where
     I: Stream<Item = Result<eventsource_stream::Event, E>>,
 {
-    s.try_filter_map(move |upstream_event| async move {
-        if upstream_event.data.is_empty() {
-            return Ok(None);
+    s.try_filter_map(move |upstream_event| {
+        if upstream_event.data.contains("\"response.completed\"")
+            && let Ok(value) = serde_json::from_str::<Value>(&upstream_event.data)
+        {
+            usage.observe_openai(value.pointer("/response/usage"));
+        }
+        async move {
+            if upstream_event.data.is_empty() {
+                return Ok(None);
+            }
+            Ok(Some(Event::default().data(upstream_event.data)))
         }
-        Ok(Some(Event::default().data(upstream_event.data)))
     })
 }
 
+/// Non-streaming `OpenAI` Chat Completions response.
+pub(super) async fn build_chat_json_response(
+    upstream_resp: reqwest::Response,
+    usage: &UsageTracker,
+    meta: &ChatResponseMeta,
+) -> Result<(StatusCode, Json<ChatCompletion>), CodexError> {
+    let status = upstream_resp.status();
+    let response = parse_upstream_sse_to_json(upstream_resp.bytes_stream()).await?;
+    usage.observe_openai(response.get("usage"));
+    Ok((status, Json(responses_to_chat_completion(&response, meta))))
+}
+
+/// Streaming `OpenAI` Chat Completions response, terminated by `data: [DONE]`.
+pub(super) fn build_chat_stream_response(
+    upstream_resp: reqwest::Response,
+    usage: UsageTracker,
+    chat: ResponsesChatStream,
+    sse: SseFlushConfig,
+) -> impl IntoResponse {
+    let raw_stream = upstream_resp.bytes_stream().eventsource();
+    let events = chat_chunk_stream(raw_stream, chat, usage)
+        .filter_map(|item| match item {
+            Ok(chunk) => match Event::default().json_data(chunk) {
+                Ok(ev) => Some(Ok(ev)),
+                Err(e) => {
+                    warn!("Failed to serialize chat completion chunk: {}", e);
+                    None
+                }
+            },
+            Err(e) => Some(Err(e)),
+        })
+        .chain(futures::stream::once(future::ready(Ok(
+            Event::default().data("[DONE]")
+        ))));
+
+    let timed_stream = events
+        .timeout(SSE_IDLE_TIMEOUT)
+        .map(|item| -> Result<_, Box<CodexError>> {
+            match item {
+                Ok(Ok(event)) => Ok(event),
+                Ok(Err(e)) => Err(Box::new(CodexError::StreamProtocolError(e.to_string()))),
+                Err(_) => {
+                    error!("Upstream Codex SSE stream timed out (idle > 60s)");
+                    Err(Box::new(CodexError::StreamProtocolError(
+                        "Stream idle timeout".to_string(),
+                    )))
+                }
+            }
+        });
+
+    let timed_stream = close_with_error_frame(timed_stream, |e| e.into_chat_error_event());
+    with_heartbeat(Sse::new(timed_stream), sse)
+}
+
+/// Re-chunk Responses events as `chat.completion.chunk`s, appending the
+/// trailing finish/usage chunks once upstream ends.
+fn chat_chunk_stream<I, E>(
+    inner: I,
+    chat: ResponsesChatStream,
+    usage: UsageTracker,
+) -> impl Stream<Item = Result<ChatCompletionChunk, E>>
+where
+    I: Stream<Item = Result<eventsource_stream::Event, E>>,
+{
+    futures::stream::unfold(
+        (Box::pin(inner), chat, VecDeque::new(), false),
+        move |(mut inner, mut chat, mut pending, mut done)| {
+            let usage = usage.clone();
+            async move {
+                loop {
+                    if let Some(chunk) = pending.pop_front() {
+                        return Some((Ok(chunk), (inner, chat, pending, done)));
+                    }
+                    if done {
+                        return None;
+                    }
+                    match inner.next().await {
+                        Some(Ok(event)) => {
+                            let Ok(value) = serde_json::from_str::<Value>(&event.data) else {
+                                continue;
+                            };
+                            if value.get("type").and_then(Value::as_str)
+                                == Some("response.completed")
+                            {
+                                usage.observe_openai(value.pointer("/response/usage"));
+                            }
+                            pending.extend(chat.on_event(&value));
+                        }
+                        Some(Err(e)) => return Some((Err(e), (inner, chat, pending, done))),
+                        None => {
+                            done = true;
+                            pending.extend(chat.finish());
+                        }
+                    }
+                }
+            }
+        },
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -160,4 +345,42 @@ mod tests {
         let body = parse_upstream_sse_to_json(stream).await.unwrap();
         assert_eq!(body, json!({"id":"r2"}));
     }
+
+    #[tokio::test]
+    async fn parse_upstream_sse_to_json_fills_empty_output_from_items() {
+        let sse_body = concat!(
+            "data: {\"type\":\"response.created\",\"response\":{\"id\":\"r3\",\"status\":\"in_progress\",\"output\":[]}}\n\n",
+            "data: {\"type\":\"response.output_item.done\",\"output_index\":1,\"item\":{\"type\":\"message\",\"id\":\"m1\"}}\n\n",
+            "data: {\"type\":\"response.output_item.done\",\"output_index\":0,\"item\":{\"type\":\"reasoning\",\"id\":\"rs1\"}}\n\n",
+            "data: {\"type\":\"response.completed\",\"response\":{\"id\":\"r3\",\"status\":\"completed\",\"output\":[]}}\n\n",
+        );
+
+        let stream = stream::iter([Ok::<_, std::convert::Infallible>(Bytes::from_static(
+            sse_body.as_bytes(),
+        ))]);
+        let body = parse_upstream_sse_to_json(stream).await.unwrap();
+        assert_eq!(body["status"], "completed");
+        assert_eq!(body["output"][0]["id"], "rs1");
+        assert_eq!(body["output"][1]["id"], "m1");
+    }
+
+    #[tokio::test]
+    async fn parse_upstream_sse_to_json_marks_truncated_stream_incomplete() {
+        let sse_body = concat!(
+            "data: {\"type\":\"response.created\",\"response\":{\"id\":\"r4\",\"status\":\"in_progress\"}}\n\n",
+            "data: {\"type\":\"response.output_item.added\",\"output_index\":0,\"item\":{\"type\":\"message\",\"id\":\"m1\"}}\n\n",
+        );
+
+        let stream = stream::iter([Ok::<_, std::convert::Infallible>(Bytes::from_static(
+            sse_body.as_bytes(),
+        ))]);
+        let body = parse_upstream_sse_to_json(stream).await.unwrap();
+        assert_eq!(body["status"], "incomplete");
+        assert_eq!(body["output"][0]["id"], "m1");
+
+        let empty = stream::iter([Ok::<_, std::convert::Infallible>(Bytes::from_static(
+            b"data: [DONE]\n\n",
+        ))]);
+        assert!(parse_upstream_sse_to_json(empty).await.is_err());
+    }
 }
@@ -0,0 +1,148 @@
+//! Cross-provider failover for native Gemini requests (`routing.fallback`).
+//!
+//! Antigravity and Gemini CLI both take the native `generateContent` body, so
+//! a request one pool cannot serve is replayed through the other provider's
+//! own translation path, with its own usage accounting.
+
+use super::{antigravity, geminicli};
+use crate::error::GeminiCliError;
+use crate::providers::ExperimentArm;
+use crate::providers::UsageTracker;
+use crate::providers::antigravity::AntigravityContext;
+use crate::providers::geminicli::{GeminiContext, model_mask};
+use crate::providers::manifest::ProviderKind;
+use crate::server::router::PolluxState;
+use axum::response::{IntoResponse, Response};
+use pollux_schema::gemini::GeminiGenerateContentRequest;
+use tracing::info;
+
+/// The provider-independent part of a native Gemini request.
+pub(crate) struct GeminiRoute<'a> {
+    pub model: &'a str,
+    pub stream: bool,
+    pub path: &'a str,
+    pub route_key: Option<u64>,
+    pub pool: Option<&'a str>,
+}
+
+impl<'a> From<&'a GeminiContext> for GeminiRoute<'a> {
+    fn from(ctx: &'a GeminiContext) -> Self {
+        Self {
+            model: &ctx.model,
+            stream: ctx.stream,
+            path: &ctx.path,
+            route_key: ctx.route_key,
+            pool: ctx.pool.as_deref(),
+        }
+    }
+}
+
+impl<'a> From<&'a AntigravityContext> for GeminiRoute<'a> {
+    fn from(ctx: &'a AntigravityContext) -> Self {
+        Self {
+            model: &ctx.model,
+            stream: ctx.stream,
+            path: &ctx.path,
+            route_key: ctx.route_key,
+            pool: ctx.pool.as_deref(),
+        }
+    }
+}
+
+/// Turn `from`'s result into the response, trying the configured fallback
+/// providers first when its pool gave up. `usage` keeps `from`'s status.
+pub(crate) async fn settle(
+    state: &PolluxState,
+    from: ProviderKind,
+    result: Result<Response, GeminiCliError>,
+    usage: &UsageTracker,
+    body: &GeminiGenerateContentRequest,
+    route: GeminiRoute<'_>,
+) -> Response {
+    let err = match result {
+        Ok(resp) => {
+            usage.set_status(resp.status());
+            return resp;
+        }
+        Err(err) => err,
+    };
+    let gave_up = err.pool_gave_up();
+    let resp = err.into_response();
+    usage.set_status(resp.status());
+    if !gave_up {
+        return resp;
+    }
+
+    for &target in state.routing.fallbacks_after(from) {
+        let Some((gave_up, fallback)) = forward(state, target, body, &route).await else {
+            continue;
+        };
+        info!(
+            from = from.label(),
+            to = target.label(),
+            model = route.model,
+            status = %fallback.status(),
+            "Request failed over to another provider"
+        );
+        if !gave_up {
+            return fallback;
+        }
+    }
+    resp
+}
+
+/// Send the request through `target`, reporting whether its pool gave up
+/// too; `None` when `target` does not serve the model.
+pub(crate) async fn forward(
+    state: &PolluxState,
+    target: ProviderKind,
+    body: &GeminiGenerateContentRequest,
+    route: &GeminiRoute<'_>,
+) -> Option<(bool, Response)> {
+    let result = match target {
+        ProviderKind::GeminiCli => {
+            let ctx = GeminiContext {
+                model: route.model.to_string(),
+                stream: route.stream,
+                path: route.path.to_string(),
+                model_mask: model_mask(route.model)?,
+                experiment_arm: ExperimentArm::Control,
+                user_agent_override: None,
+                route_key: route.route_key,
+                pool: route.pool.map(ToString::to_string),
+            };
+            let usage = state.providers.track_usage(target, route.model);
+            (
+                geminicli::handlers::forward(state, body, &ctx, &usage).await,
+                usage,
+            )
+        }
+        ProviderKind::Antigravity => {
+            let cfg = &state.providers.antigravity_cfg;
+            if !cfg.model_list.iter().any(|m| m == route.model) {
+                return None;
+            }
+            let ctx = AntigravityContext {
+                model: route.model.to_string(),
+                stream: route.stream,
+                path: route.path.to_string(),
+                model_mask: crate::model_catalog::mask(route.model)?,
+                route_key: route.route_key,
+                pool: route.pool.map(ToString::to_string),
+            };
+            let usage = state.providers.track_usage(target, route.model);
+            (
+                antigravity::handlers::forward(state, body, &ctx, &usage).await,
+                usage,
+            )
+        }
+        ProviderKind::Codex | ProviderKind::Claude | ProviderKind::Qwen => return None,
+    };
+    let (result, usage) = result;
+    let (gave_up, resp) = match result {
+        Ok(resp) => (false, resp),
+        Err(err) => (err.pool_gave_up(), err.into_response()),
+    };
+    usage.set_status(resp.status());
+    Some((gave_up, resp))
+}
@@ -1,5 +1,14 @@
-use crate::providers::geminicli::{GeminiContext, model_mask};
+use crate::model_catalog::ModelCapabilities;
+use crate::providers::ExperimentArm;
+use crate::providers::chat_compat::chat_request_to_gemini;
+use crate::providers::geminicli::{
+    GeminiContext, embedding_model_mask, image_model_mask, model_mask,
+};
+use crate::server::guards::policy;
+use crate::server::pool::requested_pool;
+use crate::server::request_events::RequestMeta;
 use crate::server::router::PolluxState;
+use crate::server::session::session_route_key;
 use crate::utils::logging::with_pretty_json_debug;
 use crate::{error::GeminiCliError, error::GeminiErrorObject};
 use axum::{
@@ -7,7 +16,11 @@ use axum::{
     extract::{FromRequest, Path, Request},
     http::StatusCode,
 };
-use pollux_schema::gemini::GeminiGenerateContentRequest;
+use pollux_schema::gemini::{
+    GeminiBatchEmbedContentsRequest, GeminiCountTokensRequest, GeminiEmbedContentRequest,
+    GeminiEmbedRequest, GeminiGenerateContentRequest, GeminiPredictRequest,
+};
+use pollux_schema::openai::ChatCompletionRequest;
 use tracing::{debug, warn};
 
 pub struct GeminiPreprocess(pub GeminiGenerateContentRequest, pub GeminiContext);
@@ -19,79 +32,338 @@ where
     type Rejection = GeminiCliError;
 
     async fn from_request(mut req: Request, state: &S) -> Result<Self, Self::Rejection> {
-        let Path(path) = req
-            .extract_parts::<Path<String>>()
-            .await
-            .map_err(|rejection| GeminiCliError::RequestRejected {
-                status: StatusCode::BAD_REQUEST,
-                body: GeminiErrorObject::for_status(
-                    StatusCode::BAD_REQUEST,
-                    "INVALID_ARGUMENT",
-                    "invalid path",
-                ),
-                debug_message: Some(rejection.to_string()),
-            })?;
-
-        // Determine model and optional rpc from the last path segment
-        let last_seg = path.split('/').next_back().map(ToString::to_string);
-        let Some(last_seg) = last_seg else {
-            return Err(GeminiCliError::RequestRejected {
-                status: StatusCode::BAD_REQUEST,
-                body: GeminiErrorObject::for_status(
-                    StatusCode::BAD_REQUEST,
-                    "INVALID_ARGUMENT",
-                    "model not found in path",
-                ),
-                debug_message: None,
-            });
+        let (path, requested, rpc) = model_path(&mut req).await?;
+        let state = state.borrow();
+        let meta = req.extensions().get::<RequestMeta>().cloned();
+        let route_key = session_route_key(req.headers());
+        let pool = requested_pool(req.headers());
+        let policy = policy::for_request(state, req.headers(), req.uri().query());
+        // Gemini image models generate on the same RPCs as text models.
+        let (model, model_mask) = resolve_model(state, &requested, meta.as_ref(), |name| {
+            model_mask(name).or_else(|| image_model_mask(name))
+        })?;
+
+        let stream = path.contains("streamGenerateContent");
+
+        let body = if rpc.as_deref() == Some("countTokens") {
+            let Json(body) = Json::<GeminiCountTokensRequest>::from_request(req, &()).await?;
+            body.into_generate_request()
+        } else {
+            let Json(mut body) =
+                Json::<GeminiGenerateContentRequest>::from_request(req, &()).await?;
+            if let Some(policy) = policy {
+                policy::check_gemini(policy, &mut body).map_err(invalid_argument)?;
+            }
+            body
         };
-        let model = if let Some((m, _r)) = last_seg.split_once(':') {
-            m.to_string()
+        check_inline_data(state, &body)?;
+        let (body, ctx) = finalize(
+            state,
+            body,
+            GeminiContext {
+                model,
+                stream,
+                path,
+                model_mask,
+                experiment_arm: ExperimentArm::Control,
+                user_agent_override: None,
+                route_key,
+                pool,
+            },
+            meta.as_ref(),
+        );
+        Ok(GeminiPreprocess(body, ctx))
+    }
+}
+
+/// `:embedContent` or `:batchEmbedContents` on an embedding model.
+pub struct GeminiEmbedPreprocess(pub GeminiEmbedRequest, pub GeminiContext);
+
+impl GeminiEmbedPreprocess {
+    /// Whether `path` names one of the embedding RPCs.
+    pub fn matches(path: &str) -> bool {
+        path.ends_with(":embedContent") || path.ends_with(":batchEmbedContents")
+    }
+}
+
+impl<S> FromRequest<S> for GeminiEmbedPreprocess
+where
+    S: Send + Sync + std::borrow::Borrow<PolluxState>,
+{
+    type Rejection = GeminiCliError;
+
+    async fn from_request(mut req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let (path, requested, rpc) = model_path(&mut req).await?;
+        let state = state.borrow();
+        let meta = req.extensions().get::<RequestMeta>().cloned();
+        let route_key = session_route_key(req.headers());
+        let pool = requested_pool(req.headers());
+        let (model, model_mask) =
+            resolve_model(state, &requested, meta.as_ref(), embedding_model_mask)?;
+
+        let mut body = if rpc.as_deref() == Some("batchEmbedContents") {
+            let Json(body) =
+                Json::<GeminiBatchEmbedContentsRequest>::from_request(req, &()).await?;
+            if body.requests.is_empty() {
+                return Err(invalid_argument("requests must not be empty".to_string()));
+            }
+            GeminiEmbedRequest::Batch(body)
         } else {
-            last_seg
+            let Json(body) = Json::<GeminiEmbedContentRequest>::from_request(req, &()).await?;
+            GeminiEmbedRequest::Single(body)
         };
+        body.set_model(&model);
 
-        let Some(model_mask) = model_mask(model.as_str()) else {
-            warn!("Rejected request for unsupported model: {}", model);
-            let body = GeminiErrorObject::for_status(
-                StatusCode::BAD_REQUEST,
-                "INVALID_ARGUMENT",
-                format!("unsupported model: {model}"),
-            );
-            return Err(GeminiCliError::RequestRejected {
-                status: StatusCode::BAD_REQUEST,
-                body,
-                debug_message: None,
-            });
+        debug!(
+            channel = "geminicli",
+            req.model = %model,
+            req.path = %path,
+            "[GeminiCLI] Extracted embedding request"
+        );
+        let ctx = GeminiContext {
+            model,
+            stream: false,
+            path,
+            model_mask,
+            experiment_arm: ExperimentArm::Control,
+            user_agent_override: None,
+            route_key,
+            pool,
         };
+        Ok(GeminiEmbedPreprocess(body, ctx))
+    }
+}
 
-        let stream = path.contains("streamGenerateContent");
+/// Imagen `:predict` on an image generation model.
+pub struct GeminiPredictPreprocess(pub GeminiPredictRequest, pub GeminiContext);
 
-        let Json(mut body) = Json::<GeminiGenerateContentRequest>::from_request(req, &()).await?;
+impl GeminiPredictPreprocess {
+    /// Whether `path` names the `predict` RPC.
+    pub fn matches(path: &str) -> bool {
+        path.ends_with(":predict")
+    }
+}
+
+impl<S> FromRequest<S> for GeminiPredictPreprocess
+where
+    S: Send + Sync + std::borrow::Borrow<PolluxState>,
+{
+    type Rejection = GeminiCliError;
 
+    async fn from_request(mut req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let (path, requested, _) = model_path(&mut req).await?;
         let state = state.borrow();
-        state
-            .providers
-            .geminicli_thoughtsig
-            .patch_request(&mut body);
-
-        with_pretty_json_debug(&body, |pretty_body| {
-            debug!(
-                channel = "geminicli",
-                req.model = %model,
-                req.stream = stream,
-                req.path = %path,
-                body = %pretty_body,
-                "[GeminiCLI] Extracted normalized request body"
-            );
-        });
+        let meta = req.extensions().get::<RequestMeta>().cloned();
+        let route_key = session_route_key(req.headers());
+        let pool = requested_pool(req.headers());
+        let (model, model_mask) =
+            resolve_model(state, &requested, meta.as_ref(), image_model_mask)?;
 
+        let Json(body) = Json::<GeminiPredictRequest>::from_request(req, &()).await?;
+        if body.instances.is_empty() {
+            return Err(invalid_argument("instances must not be empty".to_string()));
+        }
+
+        debug!(
+            channel = "geminicli",
+            req.model = %model,
+            req.path = %path,
+            "[GeminiCLI] Extracted predict request"
+        );
         let ctx = GeminiContext {
             model,
-            stream,
+            stream: false,
             path,
             model_mask,
+            experiment_arm: ExperimentArm::Control,
+            user_agent_override: None,
+            route_key,
+            pool,
         };
-        Ok(GeminiPreprocess(body, ctx))
+        Ok(GeminiPredictPreprocess(body, ctx))
+    }
+}
+
+/// `OpenAI` Chat Completions request translated into a native Gemini body.
+///
+/// The third field is `stream_options.include_usage`.
+pub struct GeminiChatPreprocess(
+    pub GeminiGenerateContentRequest,
+    pub GeminiContext,
+    pub bool,
+);
+
+impl<S> FromRequest<S> for GeminiChatPreprocess
+where
+    S: Send + Sync + std::borrow::Borrow<PolluxState>,
+{
+    type Rejection = GeminiCliError;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let state = state.borrow();
+        let meta = req.extensions().get::<RequestMeta>().cloned();
+        let route_key = session_route_key(req.headers());
+        let pool = requested_pool(req.headers());
+        let policy = policy::for_request(state, req.headers(), req.uri().query());
+
+        let Json(chat) = Json::<ChatCompletionRequest>::from_request(req, &()).await?;
+        if chat.model.is_empty() {
+            return Err(invalid_argument("model is required".to_string()));
+        }
+        let (model, model_mask) = resolve_model(state, &chat.model, meta.as_ref(), model_mask)?;
+        let stream = chat.stream;
+        let include_usage = chat
+            .stream_options
+            .as_ref()
+            .is_some_and(|o| o.include_usage);
+
+        let mut body = chat_request_to_gemini(chat).map_err(invalid_argument)?;
+        if let Some(policy) = policy {
+            policy::check_gemini(policy, &mut body).map_err(invalid_argument)?;
+        }
+        check_inline_data(state, &body)?;
+        let (body, ctx) = finalize(
+            state,
+            body,
+            GeminiContext {
+                model,
+                stream,
+                path: "openai/chat/completions".to_string(),
+                model_mask,
+                experiment_arm: ExperimentArm::Control,
+                user_agent_override: None,
+                route_key,
+                pool,
+            },
+            meta.as_ref(),
+        );
+        Ok(GeminiChatPreprocess(body, ctx, include_usage))
+    }
+}
+
+fn invalid_argument(message: String) -> GeminiCliError {
+    GeminiCliError::RequestRejected {
+        status: StatusCode::BAD_REQUEST,
+        body: GeminiErrorObject::for_status(StatusCode::BAD_REQUEST, "INVALID_ARGUMENT", message),
+        debug_message: None,
+    }
+}
+
+/// Reject bodies whose inline media exceeds `max_inline_data_bytes`.
+fn check_inline_data(
+    state: &PolluxState,
+    body: &GeminiGenerateContentRequest,
+) -> Result<(), GeminiCliError> {
+    let max = state.providers.geminicli_cfg.max_inline_data_bytes;
+    let len = body.inline_data_len();
+    if max > 0 && len > max {
+        return Err(invalid_argument(format!(
+            "inline data totals {len} bytes, above the limit of {max} bytes"
+        )));
     }
+    Ok(())
+}
+
+/// The `{*path}` capture with the requested model and RPC of its last segment.
+async fn model_path(req: &mut Request) -> Result<(String, String, Option<String>), GeminiCliError> {
+    let Path(path) = req
+        .extract_parts::<Path<String>>()
+        .await
+        .map_err(|rejection| GeminiCliError::RequestRejected {
+            status: StatusCode::BAD_REQUEST,
+            body: GeminiErrorObject::for_status(
+                StatusCode::BAD_REQUEST,
+                "INVALID_ARGUMENT",
+                "invalid path",
+            ),
+            debug_message: Some(rejection.to_string()),
+        })?;
+
+    // Determine model and optional rpc from the last path segment
+    let Some(last_seg) = path.split('/').next_back() else {
+        return Err(GeminiCliError::RequestRejected {
+            status: StatusCode::BAD_REQUEST,
+            body: GeminiErrorObject::for_status(
+                StatusCode::BAD_REQUEST,
+                "INVALID_ARGUMENT",
+                "model not found in path",
+            ),
+            debug_message: None,
+        });
+    };
+    let (requested, rpc) = match last_seg.split_once(':') {
+        Some((model, rpc)) => (model.to_string(), Some(rpc.to_string())),
+        None => (last_seg.to_string(), None),
+    };
+    Ok((path, requested, rpc))
+}
+
+/// Apply model aliases and map the canonical name to its capability bit.
+///
+/// `lookup` decides which family of models the route accepts.
+fn resolve_model(
+    state: &PolluxState,
+    requested: &str,
+    meta: Option<&RequestMeta>,
+    lookup: fn(&str) -> Option<ModelCapabilities>,
+) -> Result<(String, ModelCapabilities), GeminiCliError> {
+    let model = state
+        .providers
+        .geminicli_cfg
+        .model_aliases
+        .resolve(requested)
+        .into_owned();
+    if model != requested {
+        debug!(from = %requested, to = %model, "[GeminiCLI] Model alias applied");
+    }
+    if let Some(meta) = meta {
+        meta.set_model(&model);
+    }
+
+    let Some(model_mask) = lookup(model.as_str()) else {
+        warn!("Rejected request for unsupported model: {}", model);
+        return Err(invalid_argument(format!("unsupported model: {model}")));
+    };
+    Ok((model, model_mask))
+}
+
+/// Shared tail of both extractors: request hashing, thought signature
+/// patching and experiment assignment.
+fn finalize(
+    state: &PolluxState,
+    mut body: GeminiGenerateContentRequest,
+    mut ctx: GeminiContext,
+    meta: Option<&RequestMeta>,
+) -> (GeminiGenerateContentRequest, GeminiContext) {
+    if let Some(meta) = meta {
+        meta.hash_request(&ctx.model, &body);
+    }
+
+    let summary = state
+        .providers
+        .geminicli_thoughtsig
+        .patch_request(&mut body);
+    if let Some(meta) = meta
+        && state.providers.geminicli_cfg.thoughtsig.report_outcomes
+    {
+        meta.set_thoughtsig(summary);
+    }
+
+    let experiment = &state.providers.geminicli_experiment;
+    ctx.experiment_arm = experiment.assign();
+    experiment.apply(ctx.experiment_arm, &mut body);
+    ctx.user_agent_override = experiment.user_agent(ctx.experiment_arm, &ctx.model);
+
+    with_pretty_json_debug(&body, |pretty_body| {
+        debug!(
+            channel = "geminicli",
+            req.model = %ctx.model,
+            req.stream = ctx.stream,
+            req.path = %ctx.path,
+            req.experiment_arm = ?ctx.experiment_arm,
+            body = %pretty_body,
+            "[GeminiCLI] Extracted normalized request body"
+        );
+    });
+    (body, ctx)
 }
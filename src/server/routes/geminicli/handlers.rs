@@ -1,29 +1,297 @@
 use super::{
-    extract::GeminiPreprocess,
-    respond::{build_json_response, build_stream_response},
+    extract::{
+        GeminiChatPreprocess, GeminiEmbedPreprocess, GeminiPredictPreprocess, GeminiPreprocess,
+    },
+    respond::{
+        build_chat_json_response, build_chat_stream_response, build_json_response,
+        build_stream_response,
+    },
 };
 use crate::error::GeminiCliError;
+use crate::providers::UsageTracker;
+use crate::providers::chat_compat::{ChatResponseMeta, ChatStreamState};
+use crate::providers::geminicli::GeminiContext;
+use crate::providers::manifest::ProviderKind;
+use crate::providers::response_cache::{CacheEntry, ResponseCache};
 use crate::server::router::PolluxState;
+use crate::server::routes::failover::{self, GeminiRoute};
+use crate::server::routes::resume::StreamResume;
 use axum::{
     Json,
-    extract::State,
+    extract::{FromRequest, Request, State},
+    http::StatusCode,
     response::{IntoResponse, Response},
 };
-use pollux_schema::{gemini::GeminiModelList, openai::OpenaiModelList};
+use pollux_schema::{
+    gemini::{
+        GeminiCountTokensResponse, GeminiEmbedRequest, GeminiGenerateContentRequest,
+        GeminiModelList, GeminiPredictRequest,
+    },
+    openai::OpenaiModelList,
+};
+use serde_json::Value;
+use std::time::Instant;
+
+/// `POST models/{model}:{rpc}`; the embedding and predict RPCs have their
+/// own body shapes.
+pub async fn gemini_models_rpc_handler(State(state): State<PolluxState>, req: Request) -> Response {
+    if GeminiEmbedPreprocess::matches(req.uri().path()) {
+        return match GeminiEmbedPreprocess::from_request(req, &state).await {
+            Ok(GeminiEmbedPreprocess(body, ctx)) => gemini_embed_handler(&state, &body, &ctx).await,
+            Err(e) => e.into_response(),
+        };
+    }
+    if GeminiPredictPreprocess::matches(req.uri().path()) {
+        return match GeminiPredictPreprocess::from_request(req, &state).await {
+            Ok(GeminiPredictPreprocess(body, ctx)) => {
+                gemini_predict_handler(&state, &body, &ctx).await
+            }
+            Err(e) => e.into_response(),
+        };
+    }
+    match GeminiPreprocess::from_request(req, &state).await {
+        Ok(preprocess) => gemini_cli_handler(State(state), preprocess).await,
+        Err(e) => e.into_response(),
+    }
+}
 
 pub async fn gemini_cli_handler(
     State(state): State<PolluxState>,
     GeminiPreprocess(body, ctx): GeminiPreprocess,
+) -> Response {
+    if ctx.is_count_tokens() {
+        return count_tokens(&state, &body, &ctx).await.into_response();
+    }
+    let cached = cache_entry(&state, "generateContent", &body, &ctx);
+    if let Some(entry) = &cached
+        && let Some(hit) = entry.hit().await
+    {
+        return hit;
+    }
+    let start = Instant::now();
+    let usage = state
+        .providers
+        .track_usage(ProviderKind::GeminiCli, &ctx.model);
+    let mirrored = state.mirror.copy(
+        &state,
+        ProviderKind::GeminiCli,
+        &body,
+        &GeminiRoute::from(&ctx),
+    );
+    let result = forward(&state, &body, &ctx, &usage).await;
+    let resp = failover::settle(
+        &state,
+        ProviderKind::GeminiCli,
+        result,
+        &usage,
+        &body,
+        GeminiRoute::from(&ctx),
+    )
+    .await;
+    if let Some(mirrored) = mirrored {
+        mirrored.finish(resp.status());
+    }
+    state
+        .providers
+        .geminicli_experiment
+        .record(ctx.experiment_arm, resp.status(), start.elapsed());
+    match cached {
+        Some(entry) => entry.store(resp).await,
+        None => resp,
+    }
+}
+
+fn cache_entry<'a>(
+    state: &'a PolluxState,
+    route: &str,
+    body: &GeminiGenerateContentRequest,
+    ctx: &GeminiContext,
+) -> Option<CacheEntry<'a>> {
+    ResponseCache::entry(
+        state.providers.geminicli_response_cache.as_ref(),
+        &format!("geminicli:{route}"),
+        &ctx.model,
+        body,
+        ctx.stream,
+        body.generation_config.as_ref().and_then(|g| g.temperature),
+    )
+}
+
+pub(crate) async fn forward(
+    state: &PolluxState,
+    body: &GeminiGenerateContentRequest,
+    ctx: &GeminiContext,
+    usage: &UsageTracker,
+) -> Result<Response, GeminiCliError> {
+    let upstream_resp = state
+        .geminicli_caller
+        .call_gemini_cli(&state.providers.geminicli, ctx, body)
+        .await?;
+    usage.observe_response(&upstream_resp);
+
+    if ctx.stream {
+        let resume = stream_resume(state, body, ctx, usage);
+        Ok(build_stream_response(upstream_resp, state, usage.clone(), resume).into_response())
+    } else {
+        Ok(build_json_response(upstream_resp, state, usage)
+            .await
+            .into_response())
+    }
+}
+
+/// Replay a stream cut short through a fresh lease (`stream_resume_max_times`).
+fn stream_resume(
+    state: &PolluxState,
+    body: &GeminiGenerateContentRequest,
+    ctx: &GeminiContext,
+    usage: &UsageTracker,
+) -> Option<StreamResume> {
+    let max_times = state.providers.geminicli_cfg.stream_resume_max_times;
+    let (state, ctx, usage) = (state.clone(), ctx.clone(), usage.clone());
+    StreamResume::new(max_times, body, move |body| {
+        let (state, ctx, usage) = (state.clone(), ctx.clone(), usage.clone());
+        Box::pin(async move {
+            let resp = state
+                .geminicli_caller
+                .call_gemini_cli(&state.providers.geminicli, &ctx, &body)
+                .await?;
+            usage.observe_response(&resp);
+            Ok(resp)
+        })
+    })
+}
+
+/// `countTokens` is not a generation: no usage or experiment accounting.
+async fn count_tokens(
+    state: &PolluxState,
+    body: &GeminiGenerateContentRequest,
+    ctx: &GeminiContext,
+) -> Result<Json<GeminiCountTokensResponse>, GeminiCliError> {
+    let upstream_resp = state
+        .geminicli_caller
+        .count_tokens(&state.providers.geminicli, ctx, &body.contents)
+        .await?;
+    Ok(Json(upstream_resp.json().await?))
+}
+
+async fn gemini_embed_handler(
+    state: &PolluxState,
+    body: &GeminiEmbedRequest,
+    ctx: &GeminiContext,
+) -> Response {
+    let usage = state
+        .providers
+        .track_usage(ProviderKind::GeminiCli, &ctx.model);
+    let resp = embed(state, body, ctx, &usage).await.into_response();
+    usage.set_status(resp.status());
+    resp
+}
+
+async fn embed(
+    state: &PolluxState,
+    body: &GeminiEmbedRequest,
+    ctx: &GeminiContext,
+    usage: &UsageTracker,
+) -> Result<(StatusCode, Json<Value>), GeminiCliError> {
+    let upstream_resp = state
+        .geminicli_caller
+        .embed(&state.providers.geminicli, ctx, body)
+        .await?;
+    usage.observe_response(&upstream_resp);
+    unwrap_response(upstream_resp).await
+}
+
+async fn gemini_predict_handler(
+    state: &PolluxState,
+    body: &GeminiPredictRequest,
+    ctx: &GeminiContext,
+) -> Response {
+    let usage = state
+        .providers
+        .track_usage(ProviderKind::GeminiCli, &ctx.model);
+    let resp = predict(state, body, ctx, &usage).await.into_response();
+    usage.set_status(resp.status());
+    resp
+}
+
+async fn predict(
+    state: &PolluxState,
+    body: &GeminiPredictRequest,
+    ctx: &GeminiContext,
+    usage: &UsageTracker,
+) -> Result<(StatusCode, Json<Value>), GeminiCliError> {
+    let upstream_resp = state
+        .geminicli_caller
+        .predict(&state.providers.geminicli, ctx, body)
+        .await?;
+    usage.observe_response(&upstream_resp);
+    unwrap_response(upstream_resp).await
+}
+
+/// Cloud Code wraps results in `response` like generateContent.
+async fn unwrap_response(
+    upstream_resp: reqwest::Response,
+) -> Result<(StatusCode, Json<Value>), GeminiCliError> {
+    let status = upstream_resp.status();
+    let mut json: Value = upstream_resp.json().await?;
+    if let Some(inner) = json.get_mut("response").map(Value::take) {
+        json = inner;
+    }
+    Ok((status, Json(json)))
+}
+
+/// `OpenAI` Chat Completions on top of Gemini CLI, including tool calls.
+pub async fn gemini_chat_completions_handler(
+    State(state): State<PolluxState>,
+    GeminiChatPreprocess(body, ctx, include_usage): GeminiChatPreprocess,
+) -> Response {
+    let cached = cache_entry(&state, "chat", &body, &ctx);
+    if let Some(entry) = &cached
+        && let Some(hit) = entry.hit().await
+    {
+        return hit;
+    }
+    let start = Instant::now();
+    let usage = state
+        .providers
+        .track_usage(ProviderKind::GeminiCli, &ctx.model);
+    let resp = forward_chat(&state, &body, &ctx, &usage, include_usage)
+        .await
+        .into_response();
+    usage.set_status(resp.status());
+    state
+        .providers
+        .geminicli_experiment
+        .record(ctx.experiment_arm, resp.status(), start.elapsed());
+    match cached {
+        Some(entry) => entry.store(resp).await,
+        None => resp,
+    }
+}
+
+async fn forward_chat(
+    state: &PolluxState,
+    body: &GeminiGenerateContentRequest,
+    ctx: &GeminiContext,
+    usage: &UsageTracker,
+    include_usage: bool,
 ) -> Result<Response, GeminiCliError> {
     let upstream_resp = state
         .geminicli_caller
-        .call_gemini_cli(&state.providers.geminicli, &ctx, &body)
+        .call_gemini_cli(&state.providers.geminicli, ctx, body)
         .await?;
+    usage.observe_response(&upstream_resp);
 
+    let meta = ChatResponseMeta::new(&ctx.model);
     if ctx.stream {
-        Ok(build_stream_response(upstream_resp, &state).into_response())
+        let chat = ChatStreamState::new(meta, include_usage);
+        let resume = stream_resume(state, body, ctx, usage);
+        Ok(
+            build_chat_stream_response(upstream_resp, state, usage.clone(), chat, resume)
+                .into_response(),
+        )
     } else {
-        Ok(build_json_response(upstream_resp, &state)
+        Ok(build_chat_json_response(upstream_resp, state, usage, &meta)
             .await
             .into_response())
     }
@@ -4,9 +4,14 @@ pub mod oauth;
 pub mod resource;
 pub mod respond;
 
-use crate::providers::geminicli::SUPPORTED_MODEL_NAMES;
+use crate::providers::geminicli::{
+    EMBEDDING_MODEL_NAMES, IMAGE_MODEL_NAMES, SUPPORTED_MODEL_NAMES,
+};
 use crate::server::router::PolluxState;
-use handlers::{gemini_cli_handler, gemini_models_handler, gemini_openai_models_handler};
+use handlers::{
+    gemini_chat_completions_handler, gemini_models_handler, gemini_models_rpc_handler,
+    gemini_openai_models_handler,
+};
 use pollux_schema::{gemini::GeminiModelList, openai::OpenaiModelList};
 use resource::geminicli_resource_add;
 
@@ -15,10 +20,13 @@ use axum::{
     extract::DefaultBodyLimit,
     routing::{get, post},
 };
-use std::sync::LazyLock;
+use std::{borrow::Cow, sync::LazyLock};
 
-pub static GEMINI_MODEL_LIST: LazyLock<GeminiModelList> =
-    LazyLock::new(|| GeminiModelList::from_model_names(SUPPORTED_MODEL_NAMES.iter().cloned()));
+pub static GEMINI_MODEL_LIST: LazyLock<GeminiModelList> = LazyLock::new(|| {
+    GeminiModelList::from_model_names(SUPPORTED_MODEL_NAMES.iter().cloned())
+        .with_embedding_models(EMBEDDING_MODEL_NAMES.iter().cloned())
+        .with_image_models(IMAGE_MODEL_NAMES.iter().cloned())
+});
 
 pub static GEMINI_OPENAI_MODEL_LIST: LazyLock<OpenaiModelList> = LazyLock::new(|| {
     OpenaiModelList::from_model_names(
@@ -27,18 +35,75 @@ pub static GEMINI_OPENAI_MODEL_LIST: LazyLock<OpenaiModelList> = LazyLock::new(|
     )
 });
 
+/// API versions Google SDKs put after the base URL. `v1beta` is canonical;
+/// AI Studio clients pinned to `v1alpha` or `v1` reach the same handlers.
+pub const API_VERSIONS: [&str; 3] = ["v1beta", "v1alpha", "v1"];
+
+/// `path` with a `v1alpha`/`v1` alias rewritten to `v1beta`, so route
+/// patterns written against the canonical surface cover the aliases too.
+pub(crate) fn canonical_path(path: &str) -> Cow<'_, str> {
+    for alias in &API_VERSIONS[1..] {
+        let prefix = format!("/geminicli/{alias}/");
+        if let Some(rest) = path.strip_prefix(&prefix) {
+            return Cow::Owned(format!("/geminicli/v1beta/{rest}"));
+        }
+        if path == prefix.trim_end_matches('/') {
+            return Cow::Borrowed("/geminicli/v1beta");
+        }
+    }
+    Cow::Borrowed(path)
+}
+
 pub fn router() -> Router<PolluxState> {
-    Router::new()
-        .route("/geminicli/v1beta/models", get(gemini_models_handler))
+    let router = Router::new()
         .route(
             "/geminicli/v1beta/openai/models",
             get(gemini_openai_models_handler),
         )
         .route(
-            "/geminicli/v1beta/models/{*path}",
-            post(gemini_cli_handler).layer(DefaultBodyLimit::max(
+            "/geminicli/v1beta/openai/chat/completions",
+            post(gemini_chat_completions_handler).layer(DefaultBodyLimit::max(
                 crate::server::DEFAULT_API_BODY_LIMIT_BYTES,
             )),
-        )
-        .route("/geminicli/resource:add", post(geminicli_resource_add))
+        );
+    API_VERSIONS.iter().fold(router, |router, version| {
+        router
+            .route(
+                &format!("/geminicli/{version}/models"),
+                get(gemini_models_handler),
+            )
+            .route(
+                &format!("/geminicli/{version}/models/{{*path}}"),
+                post(gemini_models_rpc_handler).layer(DefaultBodyLimit::max(
+                    crate::server::DEFAULT_API_BODY_LIMIT_BYTES,
+                )),
+            )
+    })
+}
+
+/// Credential upload, mounted behind `ResourceAddGuard` instead of key auth.
+pub fn resource_router() -> Router<PolluxState> {
+    Router::new().route("/geminicli/resource:add", post(geminicli_resource_add))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::canonical_path;
+
+    #[test]
+    fn version_aliases_canonicalize_to_v1beta() {
+        assert_eq!(
+            canonical_path("/geminicli/v1alpha/models/m:generateContent"),
+            "/geminicli/v1beta/models/m:generateContent"
+        );
+        assert_eq!(
+            canonical_path("/geminicli/v1/models"),
+            "/geminicli/v1beta/models"
+        );
+        assert_eq!(
+            canonical_path("/geminicli/v1beta/models"),
+            "/geminicli/v1beta/models"
+        );
+        assert_eq!(canonical_path("/codex/v1/models"), "/codex/v1/models");
+    }
 }
@@ -1,11 +1,12 @@
 use crate::server::router::PolluxState;
+use crate::server::routes::oauth_page;
 use crate::{
     PolluxError, error::OauthError, providers::geminicli::GeminiCliActorHandle,
     providers::geminicli::client::oauth::endpoints::GoogleOauthEndpoints,
 };
 use axum::{
     extract::{Query, State},
-    http::StatusCode,
+    http::HeaderMap,
     response::{IntoResponse, Redirect},
 };
 use axum_extra::extract::cookie::{Cookie, PrivateCookieJar, SameSite};
@@ -13,17 +14,27 @@ use oauth2::{AuthorizationCode, PkceCodeChallenge, PkceCodeVerifier, TokenRespon
 use reqwest::Client;
 use serde::Deserialize;
 use time::Duration;
-use tracing::{error, info};
+use tracing::{error, info, warn};
 
 const CSRF_COOKIE: &str = "oauth_csrf_token";
 const PKCE_COOKIE: &str = "oauth_pkce_verifier";
 
+/// Google redirects back with either `code` + `state` or, when the user
+/// declines consent, `error`.
 #[derive(Debug, Deserialize)]
 pub struct AuthCallbackQuery {
-    pub code: String,
-    pub state: String,
+    #[serde(default)]
+    pub code: Option<String>,
+    #[serde(default)]
+    pub state: Option<String>,
+    #[serde(default)]
+    pub error: Option<String>,
 }
-/// GET /auth
+
+/// GET /geminicli/oauth/login (also `/geminicli/auth`)
+///
+/// Redirects the browser to Google with the Gemini CLI scopes; the callback
+/// adds the account, so onboarding needs nothing but a browser.
 pub async fn google_oauth_entry(
     State(state): State<PolluxState>,
     jar: PrivateCookieJar,
@@ -52,24 +63,35 @@ pub async fn google_oauth_entry(
 pub async fn google_oauth_callback(
     State(state): State<PolluxState>,
     Query(query): Query<AuthCallbackQuery>,
+    headers: HeaderMap,
     jar: PrivateCookieJar,
 ) -> impl IntoResponse {
     let (jar, session_data) = take_oauth_cookies(jar);
 
+    let (Some(code), Some(csrf_state)) = (query.code, query.state) else {
+        let reason = query
+            .error
+            .as_deref()
+            .unwrap_or("missing authorization code");
+        warn!(reason, "Google OAuth callback without a code");
+        let resp = oauth_page::not_completed(&headers, reason, "/geminicli/oauth/login");
+        return (jar, resp).into_response();
+    };
+
     let result = process_oauth_exchange(
         &state.providers.geminicli,
         &state.geminicli_client,
-        &query.code,
-        &query.state,
+        &code,
+        &csrf_state,
         session_data,
     )
     .await;
 
     match result {
-        Ok(()) => (jar, (StatusCode::ACCEPTED, "Success")).into_response(),
+        Ok(()) => (jar, oauth_page::accepted(&headers, "Gemini CLI")).into_response(),
         Err(err) => {
             error!("OAuth failure: {:?}", err);
-            (jar, err.into_response()).into_response()
+            (jar, oauth_page::failed(&headers, err)).into_response()
         }
     }
 }
@@ -1,27 +1,53 @@
+use crate::providers::RefreshTokenSeed;
 use crate::server::router::PolluxState;
+use crate::server::routes::seed_validation::{ResourceAddQuery, validate_seeds};
+use crate::utils::http::is_supported_proxy;
 use axum::extract::rejection::JsonRejection;
-use axum::{Json, extract::State, http::StatusCode, response::IntoResponse};
+use axum::{
+    Json,
+    extract::{Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
 use serde::Deserialize;
 use std::collections::HashSet;
+use tracing::warn;
+use url::Url;
 
 #[derive(Debug, Deserialize)]
 pub struct GeminiCliResourceSeed {
-    /// Only this field is used; all other fields are ignored.
+    /// Only this field, `labels` and `proxy_url` are used; all other fields
+    /// are ignored.
     ///
     /// Aliases support common naming across other tools.
     #[serde(alias = "refreshToken")]
     pub refresh_token: Option<String>,
+
+    /// Pool labels; requests sent with `x-pollux-pool: <label>` only use
+    /// credentials carrying that label.
+    #[serde(default)]
+    pub labels: Vec<String>,
+
+    /// Egress proxy this credential always uses (`http`, `https`, `socks5`
+    /// or `socks5h`), overriding the provider's `proxy` and `proxy_pool`.
+    #[serde(default, alias = "proxyUrl")]
+    pub proxy_url: Option<Url>,
 }
 
 /// POST /geminicli/resource:add
 ///
 /// 0-trust credential ingestion. This endpoint is intentionally a black box:
-/// - It accepts a wide shape for easier migration, but only uses `refresh_token`.
-/// - It returns 400 for invalid payload shapes (non-array).
+/// - It accepts a wide shape for easier migration, but only uses `refresh_token`, `labels`
+///   and `proxy_url`.
+/// - It returns 400 for invalid payload shapes (non-array) and 413 above `max_batch` entries.
 /// - It returns 202 + "Success" once accepted, regardless of internal validation outcomes.
 /// - Detailed outcomes are only recorded in local logs.
+///
+/// With `?validate=true` each item is onboarded before responding, and the
+/// `200` body reports `ok`/`duplicate`/`invalid` per item.
 pub async fn geminicli_resource_add(
     State(state): State<PolluxState>,
+    Query(query): Query<ResourceAddQuery>,
     payload: Result<Json<Vec<GeminiCliResourceSeed>>, JsonRejection>,
 ) -> axum::response::Response {
     let Ok(Json(seeds)) = payload else {
@@ -31,20 +57,36 @@ pub async fn geminicli_resource_add(
         )
             .into_response();
     };
+    if let Some(resp) = state.resource_add.reject_batch(seeds.len()) {
+        return resp;
+    }
+    if query.validate {
+        let handle = &state.providers.geminicli;
+        let items = seeds
+            .into_iter()
+            .map(|s| (s.refresh_token, s.labels, s.proxy_url))
+            .collect();
+        return validate_seeds(items, |seed| handle.validate_seed(seed)).await;
+    }
 
     let mut seen: HashSet<String> = HashSet::new();
-    let refresh_tokens: Vec<String> = seeds
+    let seeds: Vec<RefreshTokenSeed> = seeds
         .into_iter()
-        .filter_map(|s| s.refresh_token)
-        .map(|t| t.trim().to_string())
-        .filter(|t| !t.is_empty())
+        .filter_map(|s| {
+            if s.proxy_url
+                .as_ref()
+                .is_some_and(|url| !is_supported_proxy(url))
+            {
+                warn!("Skipping seed with unsupported proxy_url scheme");
+                return None;
+            }
+            RefreshTokenSeed::new(s.refresh_token.as_deref()?)
+                .map(|seed| seed.with_labels(&s.labels).with_proxy_url(s.proxy_url))
+        })
         // Deduplicate within this request to avoid redundant refresh work.
-        .filter(|t| seen.insert(t.clone()))
+        .filter(|seed| seen.insert(seed.refresh_token().to_string()))
         .collect();
 
-    state
-        .providers
-        .geminicli
-        .submit_refresh_tokens(refresh_tokens);
+    state.providers.geminicli.submit_seeds(seeds);
     (StatusCode::ACCEPTED, "Success").into_response()
 }
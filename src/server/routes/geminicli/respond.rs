@@ -1,16 +1,26 @@
+use crate::config::StreamTransformerConfig;
 use crate::error::GeminiCliError;
+use crate::providers::UsageTracker;
+use crate::providers::chat_compat::{ChatResponseMeta, ChatStreamState, gemini_to_chat_completion};
+use crate::providers::gemini_aggregate::{GeminiAggregator, is_event_stream};
+use crate::providers::manifest::ProviderKind;
+use crate::providers::stream_transform::StreamPipeline;
 use crate::server::router::PolluxState;
+use crate::server::routes::resume::{StreamResume, resumable};
+use crate::server::sse_flush::{close_with_error_frame, with_heartbeat};
 use axum::{
     Json,
     http::StatusCode,
     response::{
         IntoResponse,
-        sse::{Event, KeepAlive, Sse},
+        sse::{Event, Sse},
     },
 };
 use eventsource_stream::Eventsource;
 use futures::{Stream, TryStreamExt, future};
+use pollux_schema::openai::{ChatCompletion, ChatCompletionChunk};
 use pollux_schema::{gemini::GeminiResponseBody, geminicli::GeminiCliResponseBody};
+use std::collections::VecDeque;
 use std::time::Duration;
 use tokio_stream::StreamExt;
 use tracing::{error, warn};
@@ -19,14 +29,26 @@ use tracing::{error, warn};
 pub async fn build_json_response(
     upstream_resp: reqwest::Response,
     state: &PolluxState,
+    usage: &UsageTracker,
 ) -> Result<(StatusCode, Json<GeminiResponseBody>), GeminiCliError> {
     let status = upstream_resp.status();
-    let response_body = transform_nostream(upstream_resp).await?;
-    let mut sniffer = state.providers.geminicli_thoughtsig.build_sniffer();
+    let mut pipeline =
+        StreamPipeline::from_config(&state.providers.geminicli_cfg.stream_transformers);
+    let mut response_body = if is_event_stream(&upstream_resp) {
+        transform_aggregated(upstream_resp, &mut pipeline).await?
+    } else {
+        transform_nostream(upstream_resp).await?
+    };
+    usage.observe_gemini(response_body.usageMetadata.as_ref());
+    let mut sniffer = state
+        .providers
+        .geminicli_thoughtsig
+        .build_sniffer(&usage.model());
     state
         .providers
         .geminicli_thoughtsig
         .sniff_response(&response_body, &mut sniffer);
+    pipeline.apply_gemini(&mut response_body, true);
     Ok((status, Json(response_body)))
 }
 
@@ -35,10 +57,18 @@ pub async fn build_json_response(
 pub fn build_stream_response(
     upstream_resp: reqwest::Response,
     state: &PolluxState,
+    usage: UsageTracker,
+    resume: Option<StreamResume>,
 ) -> impl IntoResponse {
-    let sniffer = state.providers.geminicli_thoughtsig.build_sniffer();
-    let raw_stream = upstream_resp.bytes_stream().eventsource();
-    let record_stream = transform_stream(raw_stream, state.clone(), sniffer);
+    let sniffer = state
+        .providers
+        .geminicli_thoughtsig
+        .build_sniffer(&usage.model());
+    let pipeline = StreamPipeline::from_config(&state.providers.geminicli_cfg.stream_transformers);
+    let upstream_resp = state.capture(ProviderKind::GeminiCli, &usage.model(), upstream_resp);
+    let raw_stream = resumable(upstream_resp, resume);
+    let observe = observer(state.clone(), sniffer, usage);
+    let record_stream = transform_stream(raw_stream, pipeline, observe);
     let timed_stream = record_stream
         .timeout(Duration::from_mins(1))
         .map(move |item| match item {
@@ -52,49 +82,182 @@ pub fn build_stream_response(
             }
         });
 
-    Sse::new(timed_stream).keep_alive(KeepAlive::default())
+    let timed_stream = close_with_error_frame(timed_stream, GeminiCliError::into_sse_event);
+    with_heartbeat(Sse::new(timed_stream), state.sse_flush)
 }
 
-/// Convert upstream SSE events into SSE `Event`s and record thought signatures.
+/// Convert upstream SSE events into SSE `Event`s, record thought signatures and
+/// apply the route's stream transformers.
 fn transform_stream<I, E>(
     s: I,
+    pipeline: StreamPipeline,
+    observe: impl FnMut(&GeminiResponseBody),
+) -> impl Stream<Item = Result<Event, E>>
+where
+    I: Stream<Item = Result<eventsource_stream::Event, E>>,
+{
+    gemini_stream(s, pipeline, observe).try_filter_map(|gemini_resp| {
+        future::ready(match Event::default().json_data(gemini_resp) {
+            Ok(ev) => Ok(Some(ev)),
+            Err(e) => {
+                warn!("Failed to serialize GeminiResponse: {}", e);
+                Ok(None)
+            }
+        })
+    })
+}
+
+/// Records usage and thought signatures of each parsed chunk.
+fn observer(
     state: PolluxState,
     mut sniffer: pollux_thoughtsig_core::SignatureSniffer,
-) -> impl Stream<Item = Result<Event, E>>
+    usage: UsageTracker,
+) -> impl FnMut(&GeminiResponseBody) {
+    move |gemini_resp| {
+        usage.observe_gemini(gemini_resp.usageMetadata.as_ref());
+        state
+            .providers
+            .geminicli_thoughtsig
+            .sniff_response(gemini_resp, &mut sniffer);
+    }
+}
+
+/// Replay a captured upstream stream through the same translation as
+/// [`build_stream_response`], without usage or signature side effects.
+pub(crate) fn replay_stream<I, E>(
+    s: I,
+    transformers: &[StreamTransformerConfig],
+) -> impl Stream<Item = Result<Event, E>> + use<I, E>
+where
+    I: Stream<Item = Result<eventsource_stream::Event, E>>,
+{
+    transform_stream(s, StreamPipeline::from_config(transformers), |_| {})
+}
+
+/// Parse upstream SSE events into Gemini responses, passing each to
+/// `observe` and applying the route's stream transformers.
+fn gemini_stream<I, E>(
+    s: I,
+    mut pipeline: StreamPipeline,
+    mut observe: impl FnMut(&GeminiResponseBody),
+) -> impl Stream<Item = Result<GeminiResponseBody, E>>
 where
     I: Stream<Item = Result<eventsource_stream::Event, E>>,
 {
     s.try_filter_map(move |upstream_event| {
-        let state = state.clone();
+        if upstream_event.data.is_empty()
+            || upstream_event.data == "[DONE]"
+            || upstream_event.event == "done"
+        {
+            return future::ready(Ok(None));
+        }
+        let Some(mut gemini_resp) =
+            parse_sse_payload(&pipeline.repair_payload(&upstream_event.data))
+        else {
+            return future::ready(Ok(None));
+        };
+        observe(&gemini_resp);
+        if !pipeline.apply_gemini(&mut gemini_resp, false) {
+            return future::ready(Ok(None));
+        }
+        future::ready(Ok(Some(gemini_resp)))
+    })
+}
 
-        let out = {
-            if upstream_event.data.is_empty()
-                || upstream_event.data == "[DONE]"
-                || upstream_event.event == "done"
-            {
-                Ok(None)
-            } else {
-                let Some(gemini_resp) = parse_sse_payload(&upstream_event.data) else {
-                    return future::ready(Ok(None));
-                };
-
-                state
-                    .providers
-                    .geminicli_thoughtsig
-                    .sniff_response(&gemini_resp, &mut sniffer);
-
-                match Event::default().json_data(gemini_resp) {
-                    Ok(ev) => Ok(Some(ev)),
-                    Err(e) => {
-                        warn!("Failed to serialize GeminiResponse: {}", e);
-                        Ok(None)
+/// Re-chunk Gemini responses as `chat.completion.chunk`s, appending the
+/// trailing finish/usage chunks once upstream ends.
+fn chat_chunk_stream<I, E>(
+    inner: I,
+    chat: ChatStreamState,
+) -> impl Stream<Item = Result<ChatCompletionChunk, E>>
+where
+    I: Stream<Item = Result<GeminiResponseBody, E>>,
+{
+    futures::stream::unfold(
+        (Box::pin(inner), chat, VecDeque::new(), false),
+        |(mut inner, mut chat, mut pending, mut done)| async move {
+            loop {
+                if let Some(chunk) = pending.pop_front() {
+                    return Some((Ok(chunk), (inner, chat, pending, done)));
+                }
+                if done {
+                    return None;
+                }
+                match inner.next().await {
+                    Some(Ok(resp)) => pending.extend(chat.on_gemini(&resp)),
+                    Some(Err(e)) => return Some((Err(e), (inner, chat, pending, done))),
+                    None => {
+                        done = true;
+                        pending.extend(chat.finish());
                     }
                 }
             }
-        };
+        },
+    )
+}
 
-        future::ready(out)
-    })
+/// Non-streaming `OpenAI` Chat Completions response.
+pub async fn build_chat_json_response(
+    upstream_resp: reqwest::Response,
+    state: &PolluxState,
+    usage: &UsageTracker,
+    meta: &ChatResponseMeta,
+) -> Result<(StatusCode, Json<ChatCompletion>), GeminiCliError> {
+    let (status, Json(response_body)) = build_json_response(upstream_resp, state, usage).await?;
+    Ok((
+        status,
+        Json(gemini_to_chat_completion(&response_body, meta)),
+    ))
+}
+
+/// Streaming `OpenAI` Chat Completions response, terminated by `data: [DONE]`.
+#[must_use]
+pub fn build_chat_stream_response(
+    upstream_resp: reqwest::Response,
+    state: &PolluxState,
+    usage: UsageTracker,
+    chat: ChatStreamState,
+    resume: Option<StreamResume>,
+) -> impl IntoResponse {
+    let sniffer = state
+        .providers
+        .geminicli_thoughtsig
+        .build_sniffer(&usage.model());
+    let pipeline = StreamPipeline::from_config(&state.providers.geminicli_cfg.stream_transformers);
+    let upstream_resp = state.capture(ProviderKind::GeminiCli, &usage.model(), upstream_resp);
+    let raw_stream = resumable(upstream_resp, resume);
+    let observe = observer(state.clone(), sniffer, usage);
+    let gemini = gemini_stream(raw_stream, pipeline, observe);
+    let events = chat_chunk_stream(gemini, chat)
+        .filter_map(|item| match item {
+            Ok(chunk) => match Event::default().json_data(chunk) {
+                Ok(ev) => Some(Ok(ev)),
+                Err(e) => {
+                    warn!("Failed to serialize chat completion chunk: {}", e);
+                    None
+                }
+            },
+            Err(e) => Some(Err(e)),
+        })
+        .chain(futures::stream::once(future::ready(Ok(
+            Event::default().data("[DONE]")
+        ))));
+
+    let timed_stream = events
+        .timeout(Duration::from_mins(1))
+        .map(move |item| match item {
+            Ok(Ok(event)) => Ok(event),
+            Ok(Err(e)) => Err(GeminiCliError::StreamProtocolError(e.to_string())),
+            Err(_) => {
+                error!("Upstream SSE stream timed out (idle > 60s)");
+                Err(GeminiCliError::StreamProtocolError(
+                    "Stream idle timeout".to_string(),
+                ))
+            }
+        });
+
+    let timed_stream = close_with_error_frame(timed_stream, GeminiCliError::into_sse_event);
+    with_heartbeat(Sse::new(timed_stream), state.sse_flush)
 }
 
 fn parse_sse_payload(data: &str) -> Option<GeminiResponseBody> {
@@ -106,6 +269,45 @@ fn parse_sse_payload(data: &str) -> Option<GeminiResponseBody> {
     Some(cli_resp.into())
 }
 
+/// Merge the SSE stream of a `stream_only_models` call into one response.
+async fn transform_aggregated(
+    upstream_resp: reqwest::Response,
+    pipeline: &mut StreamPipeline,
+) -> Result<GeminiResponseBody, GeminiCliError> {
+    let mut aggregator = GeminiAggregator::default();
+    let events = upstream_resp
+        .bytes_stream()
+        .eventsource()
+        .timeout(Duration::from_mins(1));
+    tokio::pin!(events);
+
+    while let Some(item) = events.next().await {
+        let upstream_event = match item {
+            Ok(Ok(event)) => event,
+            Ok(Err(e)) => return Err(GeminiCliError::StreamProtocolError(e.to_string())),
+            Err(_) => {
+                error!("Upstream SSE stream timed out (idle > 60s)");
+                return Err(GeminiCliError::StreamProtocolError(
+                    "Stream idle timeout".to_string(),
+                ));
+            }
+        };
+        if upstream_event.data.is_empty()
+            || upstream_event.data == "[DONE]"
+            || upstream_event.event == "done"
+        {
+            continue;
+        }
+        if let Some(chunk) = parse_sse_payload(&pipeline.repair_payload(&upstream_event.data)) {
+            aggregator.push(chunk);
+        }
+    }
+
+    aggregator.finish().ok_or_else(|| {
+        GeminiCliError::StreamProtocolError("Stream ended without a response".to_string())
+    })
+}
+
 /// Convert non-streaming CLI envelope into `GeminiResponse`.
 pub async fn transform_nostream(
     upstream_resp: reqwest::Response,
@@ -0,0 +1,79 @@
+//! Unauthenticated probes for load balancers and orchestrators.
+
+use crate::providers::manifest::ProviderKind;
+use crate::server::router::PolluxState;
+use axum::{
+    Json, Router,
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::get,
+};
+use serde::Serialize;
+
+#[derive(Debug, Serialize)]
+pub struct ProviderReadiness {
+    pub provider: ProviderKind,
+    pub ready: bool,
+    /// Credentials loaded in the scheduler; `null` when the actor did not answer.
+    pub active: Option<usize>,
+    /// Credentials able to serve at least one model right now.
+    pub usable: Option<usize>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReadinessResponse {
+    pub ready: bool,
+    pub providers: Vec<ProviderReadiness>,
+}
+
+pub fn router() -> Router<PolluxState> {
+    Router::new()
+        .route("/healthz", get(healthz))
+        .route("/readyz", get(readyz))
+}
+
+/// GET /healthz
+///
+/// Liveness: the process is up and serving HTTP.
+async fn healthz() -> &'static str {
+    "ok"
+}
+
+/// GET /readyz
+///
+/// Readiness: `503` unless every provider has a usable credential.
+async fn readyz(State(state): State<PolluxState>) -> Response {
+    let providers = &state.providers;
+    let mut out = Vec::with_capacity(ProviderKind::ALL.len());
+    for kind in ProviderKind::ALL {
+        let pool = match kind {
+            ProviderKind::GeminiCli => providers.geminicli.status().await,
+            ProviderKind::Codex => providers.codex.status().await,
+            ProviderKind::Antigravity => providers.antigravity.status().await,
+            ProviderKind::Claude => providers.claude.status().await,
+            ProviderKind::Qwen => providers.qwen.status().await,
+        }
+        .ok();
+        out.push(ProviderReadiness {
+            provider: kind,
+            ready: pool.as_ref().is_some_and(|p| p.usable > 0),
+            active: pool.as_ref().map(|p| p.loaded),
+            usable: pool.as_ref().map(|p| p.usable),
+        });
+    }
+    let ready = out.iter().all(|p| p.ready);
+    let status = if ready {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    (
+        status,
+        Json(ReadinessResponse {
+            ready,
+            providers: out,
+        }),
+    )
+        .into_response()
+}
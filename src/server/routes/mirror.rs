@@ -0,0 +1,243 @@
+//! Shadow traffic for native Gemini requests (`routing.mirror`).
+//!
+//! A sampled request is copied to a second provider (through its own pool and
+//! translation path) or to a Gemini-compatible URL in the background. The
+//! copy's response is read to the end and dropped; only its status and
+//! latency are kept, next to the primary's for the same requests, so two
+//! backends can be compared on live traffic before switching.
+
+use super::failover::{self, GeminiRoute};
+use crate::config::{MirrorConfig, Priority};
+use crate::providers::experiment::{ArmCounters, ArmStats};
+use crate::providers::manifest::ProviderKind;
+use crate::server::priority::REQUEST_PRIORITY;
+use crate::server::router::PolluxState;
+use crate::utils::dns::with_resolver;
+use axum::http::StatusCode;
+use futures::StreamExt as _;
+use pollux_schema::gemini::GeminiGenerateContentRequest;
+use rand::Rng;
+use serde::Serialize;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+use tracing::debug;
+
+const MIRROR_URL_TIMEOUT: Duration = Duration::from_mins(10);
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MirrorReport {
+    pub from: ProviderKind,
+    /// Provider label or URL receiving the copies.
+    pub target: String,
+    pub fraction: f64,
+    /// Outcomes of the copied requests as served to clients.
+    pub primary: ArmStats,
+    /// Outcomes of the copies.
+    pub mirror: ArmStats,
+    /// Copies not sent because the target does not serve the model.
+    pub skipped: u64,
+}
+
+#[derive(Debug)]
+struct MirrorInner {
+    cfg: MirrorConfig,
+    client: reqwest::Client,
+    primary: ArmCounters,
+    mirror: ArmCounters,
+    skipped: AtomicU64,
+}
+
+/// Cheap-to-clone handle; a disabled mirror copies nothing.
+#[derive(Debug, Clone, Default)]
+pub struct Mirror {
+    inner: Option<Arc<MirrorInner>>,
+}
+
+/// Times the primary side of a mirrored request.
+pub(crate) struct Mirrored {
+    inner: Arc<MirrorInner>,
+    start: Instant,
+}
+
+impl Mirrored {
+    pub(crate) fn finish(self, status: StatusCode) {
+        self.inner.primary.record(status, self.start.elapsed());
+    }
+}
+
+impl Mirror {
+    pub fn new(cfg: Option<MirrorConfig>) -> Self {
+        let inner = cfg.map(|mut cfg| {
+            cfg.fraction = if cfg.fraction.is_finite() {
+                cfg.fraction.clamp(0.0, 1.0)
+            } else {
+                0.0
+            };
+            let client = with_resolver(reqwest::Client::builder())
+                .timeout(MIRROR_URL_TIMEOUT)
+                .build()
+                .expect("failed to build mirror HTTP client");
+            Arc::new(MirrorInner {
+                cfg,
+                client,
+                primary: ArmCounters::default(),
+                mirror: ArmCounters::default(),
+                skipped: AtomicU64::default(),
+            })
+        });
+        Self { inner }
+    }
+
+    /// Copy a sampled request from `from` in the background; the returned
+    /// handle records the primary's outcome.
+    pub(crate) fn copy(
+        &self,
+        state: &PolluxState,
+        from: ProviderKind,
+        body: &GeminiGenerateContentRequest,
+        route: &GeminiRoute<'_>,
+    ) -> Option<Mirrored> {
+        let inner = self.inner.as_ref()?;
+        if inner.cfg.from != from || !rand::rng().random_bool(inner.cfg.fraction) {
+            return None;
+        }
+        let copy = MirrorCopy {
+            inner: inner.clone(),
+            state: state.clone(),
+            body: body.clone(),
+            model: route.model.to_string(),
+            stream: route.stream,
+            path: route.path.to_string(),
+            route_key: route.route_key,
+            pool: route.pool.map(ToString::to_string),
+        };
+        // Shadow traffic never jumps ahead of the requests it copies.
+        tokio::spawn(REQUEST_PRIORITY.scope(Priority::Low, copy.run()));
+        Some(Mirrored {
+            inner: inner.clone(),
+            start: Instant::now(),
+        })
+    }
+
+    pub fn report(&self) -> Option<MirrorReport> {
+        let inner = self.inner.as_ref()?;
+        let cfg = &inner.cfg;
+        let target = match (cfg.to, &cfg.url) {
+            (Some(to), _) => to.label().to_string(),
+            (None, Some(url)) => url.to_string(),
+            (None, None) => String::new(),
+        };
+        Some(MirrorReport {
+            from: cfg.from,
+            target,
+            fraction: cfg.fraction,
+            primary: inner.primary.stats(),
+            mirror: inner.mirror.stats(),
+            skipped: inner.skipped.load(Ordering::Relaxed),
+        })
+    }
+}
+
+/// An owned copy of the request for the background task.
+struct MirrorCopy {
+    inner: Arc<MirrorInner>,
+    state: PolluxState,
+    body: GeminiGenerateContentRequest,
+    model: String,
+    stream: bool,
+    path: String,
+    route_key: Option<u64>,
+    pool: Option<String>,
+}
+
+impl MirrorCopy {
+    async fn run(self) {
+        let start = Instant::now();
+        let cfg = &self.inner.cfg;
+        let status = match (cfg.to, &cfg.url) {
+            (Some(to), _) => self.to_provider(to, start).await,
+            (None, Some(url)) => self.to_url(url, start).await,
+            (None, None) => None,
+        };
+        match status {
+            Some(status) => debug!(
+                model = %self.model,
+                status = %status,
+                elapsed_ms = start.elapsed().as_millis(),
+                "[Mirror] Copy finished"
+            ),
+            None => {
+                self.inner.skipped.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+    }
+
+    async fn to_provider(&self, to: ProviderKind, start: Instant) -> Option<StatusCode> {
+        let route = GeminiRoute {
+            model: &self.model,
+            stream: self.stream,
+            path: &self.path,
+            route_key: self.route_key,
+            pool: self.pool.as_deref(),
+        };
+        let (_, resp) = failover::forward(&self.state, to, &self.body, &route).await?;
+        let status = resp.status();
+        self.inner.mirror.record(status, start.elapsed());
+        let mut body = resp.into_body().into_data_stream();
+        while let Some(Ok(_)) = body.next().await {}
+        Some(status)
+    }
+
+    async fn to_url(&self, base: &url::Url, start: Instant) -> Option<StatusCode> {
+        let url = format!(
+            "{}/models/{}",
+            base.as_str().trim_end_matches('/'),
+            self.path
+        );
+        let mut req = self.inner.client.post(url).json(&self.body);
+        if self.stream {
+            req = req.query(&[("alt", "sse")]);
+        }
+        if let Some(key) = &self.inner.cfg.key {
+            req = req.header("x-goog-api-key", key);
+        }
+        let resp = match req.send().await {
+            Ok(resp) => resp,
+            Err(e) => {
+                debug!(error = %e, "[Mirror] Copy to URL failed");
+                self.inner
+                    .mirror
+                    .record(StatusCode::BAD_GATEWAY, start.elapsed());
+                return Some(StatusCode::BAD_GATEWAY);
+            }
+        };
+        let status = resp.status();
+        self.inner.mirror.record(status, start.elapsed());
+        let mut body = resp.bytes_stream();
+        while let Some(Ok(_)) = body.next().await {}
+        Some(status)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn report_names_the_target() {
+        assert!(Mirror::default().report().is_none());
+
+        let mirror = Mirror::new(Some(MirrorConfig {
+            from: ProviderKind::Antigravity,
+            to: None,
+            url: Some("https://staging.example/geminicli/v1beta".parse().unwrap()),
+            key: None,
+            fraction: 0.25,
+        }));
+        let report = mirror.report().unwrap();
+        assert_eq!(report.target, "https://staging.example/geminicli/v1beta");
+        assert_eq!(report.primary.requests, 0);
+        assert_eq!(report.skipped, 0);
+    }
+}
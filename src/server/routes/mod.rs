@@ -1,3 +1,13 @@
+pub mod admin;
 pub mod antigravity;
+pub mod claude;
 pub mod codex;
+pub(crate) mod failover;
 pub mod geminicli;
+pub mod health;
+pub mod mirror;
+pub(crate) mod oauth_page;
+pub mod qwen;
+pub mod resume;
+pub mod seed_validation;
+pub mod unified;
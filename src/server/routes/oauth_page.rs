@@ -0,0 +1,76 @@
+//! Responses for the end of the browser OAuth flows.
+//!
+//! A browser (`Accept: text/html`) gets a short page saying what happened;
+//! other clients keep the plain `202 Success` and JSON error bodies.
+
+use crate::PolluxError;
+use axum::{
+    http::{HeaderMap, StatusCode, header::ACCEPT},
+    response::{Html, IntoResponse, Response},
+};
+
+fn wants_html(headers: &HeaderMap) -> bool {
+    headers
+        .get(ACCEPT)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|accept| accept.contains("text/html"))
+}
+
+/// The account was handed to the provider actor for onboarding.
+pub(crate) fn accepted(headers: &HeaderMap, provider: &str) -> Response {
+    if !wants_html(headers) {
+        return (StatusCode::ACCEPTED, "Success").into_response();
+    }
+    let detail = format!(
+        "The {provider} account is being verified and will join the pool shortly. \
+         You can close this tab."
+    );
+    (StatusCode::ACCEPTED, page("Account submitted", &detail)).into_response()
+}
+
+/// The code exchange or one of the flow checks failed.
+pub(crate) fn failed(headers: &HeaderMap, err: PolluxError) -> Response {
+    if !wants_html(headers) {
+        return err.into_response();
+    }
+    let detail = err.to_string();
+    let status = err.into_response().status();
+    (status, page("Adding the account failed", &detail)).into_response()
+}
+
+/// The provider redirected back without a code, e.g. consent was declined.
+pub(crate) fn not_completed(headers: &HeaderMap, reason: &str, login_path: &str) -> Response {
+    let detail = format!("The provider returned: {reason}. Start again from {login_path}.");
+    if !wants_html(headers) {
+        return (StatusCode::BAD_REQUEST, detail).into_response();
+    }
+    (
+        StatusCode::BAD_REQUEST,
+        page("Authorization was not completed", &detail),
+    )
+        .into_response()
+}
+
+fn page(title: &str, detail: &str) -> Html<String> {
+    Html(format!(
+        "<!doctype html><html><head><meta charset=\"utf-8\"><title>Pollux</title></head>\
+         <body><h1>{}</h1><p>{}</p></body></html>",
+        escape_html(title),
+        escape_html(detail)
+    ))
+}
+
+fn escape_html(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
@@ -0,0 +1,157 @@
+//! Device-code login for Qwen Code (`POST /qwen/oauth/start`).
+//!
+//! qwen.ai has no browser callback for the Qwen Code client, so this is the
+//! only way to onboard an account besides uploading a refresh token. A
+//! background task polls the token endpoint until the user approves, then
+//! hands the tokens to the Qwen actor. `GET /qwen/oauth/{poll_id}` reports
+//! progress; the random poll id is the only thing that identifies a flow.
+
+use crate::PolluxError;
+use crate::error::{IsRetryable, OauthError};
+use crate::providers::qwen::QwenActorHandle;
+use crate::providers::qwen::client::oauth::endpoints::{DeviceAuthorization, QwenOauthEndpoints};
+use oauth2::{PkceCodeChallenge, PkceCodeVerifier, TokenResponse};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+/// Used when the device-code response carries no `expires_in`.
+const DEFAULT_DEVICE_CODE_TTL: Duration = Duration::from_mins(15);
+/// Finished flows stay queryable for this long after they start.
+const FLOW_RETENTION: Duration = Duration::from_mins(30);
+
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum DeviceFlowStatus {
+    Pending,
+    Completed,
+    Failed { message: String },
+    Expired,
+}
+
+/// Response of `POST /qwen/oauth/start`.
+#[derive(Debug, Serialize)]
+pub struct DeviceFlowStarted {
+    pub poll_id: String,
+    /// Verification page, with the user code prefilled when qwen.ai offers it.
+    pub verification_url: String,
+    pub user_code: String,
+    pub expires_in: u64,
+}
+
+#[derive(Debug)]
+struct DeviceFlow {
+    started: Instant,
+    status: DeviceFlowStatus,
+}
+
+/// Device flows started on this process, keyed by poll id.
+#[derive(Debug, Clone, Default)]
+pub struct DeviceFlows {
+    flows: Arc<Mutex<HashMap<String, DeviceFlow>>>,
+}
+
+impl DeviceFlows {
+    /// Request a device code and start polling for its approval.
+    pub(crate) async fn start(
+        &self,
+        http_client: reqwest::Client,
+        qwen: QwenActorHandle,
+    ) -> Result<DeviceFlowStarted, PolluxError> {
+        let (challenge, verifier) = PkceCodeChallenge::new_random_sha256();
+        let device = QwenOauthEndpoints::request_device_code(&challenge, &http_client).await?;
+        let ttl = device
+            .expires_in
+            .map_or(DEFAULT_DEVICE_CODE_TTL, Duration::from_secs);
+        let poll_id = uuid::Uuid::new_v4().simple().to_string();
+        if let Ok(mut flows) = self.flows.lock() {
+            flows.retain(|_, flow| flow.started.elapsed() < FLOW_RETENTION);
+            flows.insert(
+                poll_id.clone(),
+                DeviceFlow {
+                    started: Instant::now(),
+                    status: DeviceFlowStatus::Pending,
+                },
+            );
+        }
+        info!(poll_id, "[Qwen] Device authorization started");
+
+        let started = DeviceFlowStarted {
+            poll_id: poll_id.clone(),
+            verification_url: device
+                .verification_uri_complete
+                .clone()
+                .unwrap_or_else(|| device.verification_uri.clone()),
+            user_code: device.user_code.clone(),
+            expires_in: ttl.as_secs(),
+        };
+        let flows = self.clone();
+        tokio::spawn(async move {
+            let status = poll_until_done(&device, &verifier, ttl, &http_client, &qwen).await;
+            flows.finish(&poll_id, status);
+        });
+        Ok(started)
+    }
+
+    pub(crate) fn status(&self, poll_id: &str) -> Option<DeviceFlowStatus> {
+        let flows = self.flows.lock().ok()?;
+        flows.get(poll_id).map(|flow| flow.status.clone())
+    }
+
+    fn finish(&self, poll_id: &str, status: DeviceFlowStatus) {
+        match &status {
+            DeviceFlowStatus::Failed { message } => {
+                warn!(poll_id, message, "[Qwen] Device authorization failed");
+            }
+            other => info!(poll_id, status = ?other, "[Qwen] Device authorization finished"),
+        }
+        if let Ok(mut flows) = self.flows.lock()
+            && let Some(flow) = flows.get_mut(poll_id)
+        {
+            flow.status = status;
+        }
+    }
+}
+
+async fn poll_until_done(
+    device: &DeviceAuthorization,
+    verifier: &PkceCodeVerifier,
+    ttl: Duration,
+    http_client: &reqwest::Client,
+    qwen: &QwenActorHandle,
+) -> DeviceFlowStatus {
+    let deadline = Instant::now() + ttl;
+    let interval = device.interval();
+    loop {
+        if Instant::now() >= deadline {
+            return DeviceFlowStatus::Expired;
+        }
+        tokio::time::sleep(interval).await;
+        let tokens =
+            match QwenOauthEndpoints::poll_device_token(device, verifier, http_client).await {
+                Ok(Some(tokens)) => tokens,
+                Ok(None) => continue,
+                Err(e) if e.is_retryable() => {
+                    warn!(error = %e, "[Qwen] Device authorization poll failed; retrying");
+                    continue;
+                }
+                Err(OauthError::ServerResponse { error }) if error == "expired_token" => {
+                    return DeviceFlowStatus::Expired;
+                }
+                Err(e) => {
+                    return DeviceFlowStatus::Failed {
+                        message: e.to_string(),
+                    };
+                }
+            };
+        if tokens.refresh_token().is_none() {
+            return DeviceFlowStatus::Failed {
+                message: "Missing refresh_token in token response".to_string(),
+            };
+        }
+        qwen.submit_trusted_oauth(tokens);
+        return DeviceFlowStatus::Completed;
+    }
+}
@@ -0,0 +1,115 @@
+use crate::error::QwenError;
+use crate::providers::qwen::model_mask;
+use crate::server::guards::policy;
+use crate::server::pool::requested_pool;
+use crate::server::request_events::RequestMeta;
+use crate::server::router::PolluxState;
+use crate::server::session::session_route_key;
+use crate::utils::logging::with_pretty_json_debug;
+use axum::{
+    Json,
+    extract::{FromRequest, Request},
+    http::StatusCode,
+};
+use pollux_schema::OpenaiResponsesErrorObject;
+use pollux_schema::openai::ChatCompletionRequest;
+use std::borrow::Cow;
+use tracing::debug;
+
+use super::QwenContext;
+
+pub(crate) struct QwenPreprocess {
+    pub body: ChatCompletionRequest,
+    pub ctx: QwenContext,
+}
+
+impl<S> FromRequest<S> for QwenPreprocess
+where
+    S: Send + Sync + std::borrow::Borrow<PolluxState>,
+{
+    type Rejection = QwenError;
+
+    /// Extract and validate a `/qwen/v1/chat/completions` request.
+    ///
+    /// - `providers.qwen.model_aliases` is applied first and the canonical
+    ///   name is written back into the body.
+    /// - Missing/empty `model` => `INVALID_MODEL`; a model outside
+    ///   `providers.qwen.model_list` => `UNSUPPORTED_MODEL`, both before any
+    ///   credential is leased.
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let (parts, body) = req.into_parts();
+
+        let meta = parts.extensions.get::<RequestMeta>().cloned();
+        let route_key = session_route_key(&parts.headers);
+        let pool = requested_pool(&parts.headers);
+        let policy = policy::for_request(state.borrow(), &parts.headers, parts.uri.query());
+
+        let req = Request::from_parts(parts, body);
+        let Json(mut body) = Json::<ChatCompletionRequest>::from_request(req, state).await?;
+
+        let aliases = &state.borrow().providers.qwen_cfg.model_aliases;
+        if let Cow::Owned(canonical) = aliases.resolve(&body.model) {
+            debug!(from = %body.model, to = %canonical, "[Qwen] Model alias applied");
+            body.model = canonical;
+        }
+        let model = body.model.as_str();
+        if let Some(meta) = &meta {
+            meta.set_model(model);
+        }
+        if model.is_empty() {
+            return Err(rejected(
+                "INVALID_MODEL",
+                "INVALID_MODEL",
+                "missing or empty model".to_string(),
+            ));
+        }
+        let Some(model_mask) = model_mask(model) else {
+            return Err(rejected(
+                "UNSUPPORTED_MODEL",
+                "UNSUPPORTED_MODEL",
+                "unsupported model (exact match required)".to_string(),
+            ));
+        };
+
+        if let Some(policy) = policy {
+            policy::check_chat(policy, &body)
+                .map_err(|message| rejected("POLICY_VIOLATION", "INVALID_REQUEST", message))?;
+        }
+        if let Some(meta) = &meta {
+            meta.hash_request(model, &body);
+        }
+
+        with_pretty_json_debug(&body, |pretty_body| {
+            debug!(
+                channel = "qwen",
+                req.model = %model,
+                req.stream = body.stream,
+                body = %pretty_body,
+                "[Qwen] Extracted request body"
+            );
+        });
+
+        let ctx = QwenContext {
+            model: body.model.clone(),
+            stream: body.stream,
+            model_mask,
+            route_key,
+            pool,
+        };
+
+        Ok(Self { body, ctx })
+    }
+}
+
+fn rejected(code: &str, r#type: &str, message: String) -> QwenError {
+    QwenError::RequestRejected {
+        status: StatusCode::BAD_REQUEST,
+        body: OpenaiResponsesErrorObject {
+            code: Some(code.to_string()),
+            message,
+            r#type: r#type.to_string(),
+            param: None,
+        },
+        debug_message: None,
+    }
+}
@@ -0,0 +1,60 @@
+use super::{extract::QwenPreprocess, respond};
+use crate::error::QwenError;
+use crate::providers::UsageTracker;
+use crate::providers::manifest::ProviderKind;
+use crate::server::router::PolluxState;
+use axum::{
+    Json,
+    extract::State,
+    response::{IntoResponse, Response},
+};
+use pollux_schema::openai::OpenaiModelList;
+use tracing::debug;
+
+pub(crate) async fn qwen_chat_completions_handler(
+    State(state): State<PolluxState>,
+    preprocess: QwenPreprocess,
+) -> Response {
+    let usage = state
+        .providers
+        .track_usage(ProviderKind::Qwen, &preprocess.ctx.model);
+    let resp = forward_chat(&state, preprocess, &usage)
+        .await
+        .into_response();
+    usage.set_status(resp.status());
+    resp
+}
+
+async fn forward_chat(
+    state: &PolluxState,
+    QwenPreprocess { body, ctx }: QwenPreprocess,
+    usage: &UsageTracker,
+) -> Result<Response, QwenError> {
+    debug!(
+        model = %ctx.model,
+        stream = ctx.stream,
+        model_mask = %ctx.model_mask,
+        "Incoming Qwen chat completions request"
+    );
+
+    let upstream_resp = state
+        .qwen_caller
+        .call_qwen(&state.providers.qwen, &ctx, &body)
+        .await?;
+    usage.observe_response(&upstream_resp);
+
+    if ctx.stream {
+        Ok(
+            respond::build_stream_response(upstream_resp, usage.clone(), state.sse_flush)
+                .into_response(),
+        )
+    } else {
+        Ok(respond::build_json_response(upstream_resp, usage)
+            .await?
+            .into_response())
+    }
+}
+
+pub(super) async fn qwen_models_handler() -> Result<Json<OpenaiModelList>, QwenError> {
+    Ok(Json(super::QWEN_MODEL_LIST.clone()))
+}
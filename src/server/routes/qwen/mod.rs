@@ -0,0 +1,52 @@
+use crate::model_catalog::ModelCapabilities;
+use crate::server::router::PolluxState;
+use axum::{
+    Router,
+    extract::DefaultBodyLimit,
+    routing::{get, post},
+};
+
+pub mod device;
+pub mod extract;
+pub mod handlers;
+pub mod oauth;
+pub mod resource;
+pub mod respond;
+
+use crate::providers::qwen::SUPPORTED_MODEL_NAMES;
+use pollux_schema::openai::OpenaiModelList;
+use std::sync::LazyLock;
+
+pub static QWEN_MODEL_LIST: LazyLock<OpenaiModelList> = LazyLock::new(|| {
+    OpenaiModelList::from_model_names(SUPPORTED_MODEL_NAMES.iter().cloned(), "qwen".to_string())
+});
+
+#[derive(Debug, Clone)]
+pub struct QwenContext {
+    pub model: String,
+    pub stream: bool,
+    pub model_mask: ModelCapabilities,
+    /// Hash of `x-pollux-session`, used to pin a session to the same account.
+    pub route_key: Option<u64>,
+    /// Label from `x-pollux-pool`; only credentials carrying it are leased.
+    pub pool: Option<String>,
+}
+
+pub fn router() -> Router<PolluxState> {
+    Router::new()
+        .route(
+            "/qwen/v1/chat/completions",
+            post(handlers::qwen_chat_completions_handler).layer(DefaultBodyLimit::max(
+                crate::server::DEFAULT_API_BODY_LIMIT_BYTES,
+            )),
+        )
+        .route("/qwen/v1/models", get(handlers::qwen_models_handler))
+}
+
+/// Credential upload and device-code login, mounted behind `ResourceAddGuard`
+/// instead of key auth.
+pub fn resource_router() -> Router<PolluxState> {
+    Router::new()
+        .route("/qwen/resource:add", post(resource::qwen_resource_add))
+        .route("/qwen/oauth/start", post(oauth::qwen_device_start))
+}
@@ -0,0 +1,33 @@
+use super::device::DeviceFlowStarted;
+use crate::PolluxError;
+use crate::server::router::PolluxState;
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+
+/// POST /qwen/oauth/start
+///
+/// Starts a Qwen Code device-code login.
+pub async fn qwen_device_start(
+    State(state): State<PolluxState>,
+) -> Result<Json<DeviceFlowStarted>, PolluxError> {
+    let started = state
+        .qwen_device_flows
+        .start(state.qwen_client.clone(), state.providers.qwen.clone())
+        .await?;
+    Ok(Json(started))
+}
+
+/// GET `/qwen/oauth/{poll_id}`
+pub async fn qwen_device_status(
+    State(state): State<PolluxState>,
+    Path(poll_id): Path<String>,
+) -> Response {
+    match state.qwen_device_flows.status(&poll_id) {
+        Some(status) => Json(status).into_response(),
+        None => (StatusCode::NOT_FOUND, "Unknown poll id").into_response(),
+    }
+}
@@ -0,0 +1,71 @@
+use crate::config::SseFlushConfig;
+use crate::error::QwenError;
+use crate::providers::UsageTracker;
+use crate::server::sse_flush::{close_with_error_frame, with_heartbeat};
+use axum::{
+    Json,
+    http::StatusCode,
+    response::{
+        IntoResponse,
+        sse::{Event, Sse},
+    },
+};
+use eventsource_stream::Eventsource;
+use serde_json::Value;
+use std::time::Duration;
+use tokio_stream::StreamExt;
+use tracing::error;
+
+const SSE_IDLE_TIMEOUT: Duration = Duration::from_mins(1);
+
+/// Relay the upstream chat completion chunks as-is, including the final
+/// `[DONE]`, picking up `usage` from the chunk that carries it.
+pub(super) fn build_stream_response(
+    upstream_resp: reqwest::Response,
+    usage: UsageTracker,
+    sse: SseFlushConfig,
+) -> impl IntoResponse {
+    let stream = upstream_resp
+        .bytes_stream()
+        .eventsource()
+        .timeout(SSE_IDLE_TIMEOUT)
+        .map(move |item| -> Result<Event, Box<QwenError>> {
+            match item {
+                Ok(Ok(event)) => {
+                    observe_chunk_usage(&usage, &event.data);
+                    Ok(Event::default().data(event.data))
+                }
+                Ok(Err(e)) => Err(Box::new(QwenError::StreamProtocolError(e.to_string()))),
+                Err(_) => {
+                    error!("Upstream Qwen SSE stream timed out (idle > 60s)");
+                    Err(Box::new(QwenError::StreamProtocolError(
+                        "Stream idle timeout".to_string(),
+                    )))
+                }
+            }
+        });
+
+    let stream = close_with_error_frame(stream, |e| e.into_sse_event());
+    with_heartbeat(Sse::new(stream), sse)
+}
+
+fn observe_chunk_usage(usage: &UsageTracker, data: &str) {
+    if !data.contains("\"usage\"") {
+        return;
+    }
+    let Ok(chunk) = serde_json::from_str::<Value>(data) else {
+        return;
+    };
+    usage.observe_openai(chunk.get("usage").filter(|u| !u.is_null()));
+}
+
+/// Relay a non-streaming chat completion as JSON.
+pub(super) async fn build_json_response(
+    upstream_resp: reqwest::Response,
+    usage: &UsageTracker,
+) -> Result<(StatusCode, Json<Value>), QwenError> {
+    let status = upstream_resp.status();
+    let body: Value = upstream_resp.json().await?;
+    usage.observe_openai(body.get("usage"));
+    Ok((status, Json(body)))
+}
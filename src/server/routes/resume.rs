@@ -0,0 +1,285 @@
+//! Mid-stream resumption for Gemini-protocol streams
+//! (`providers.*.stream_resume_max_times`).
+//!
+//! When an upstream stream ends or fails before any candidate reports a
+//! `finishReason`, the request is replayed on a freshly leased credential with
+//! the text generated so far appended as a trailing `model` turn, and the
+//! continuation's events are spliced into the client stream. Thoughts in a
+//! continuation are dropped since the client already saw the first attempt's.
+//! Streams with function calls, non-text parts or several candidates cannot
+//! be continued this way and keep ending as before.
+
+use crate::error::GeminiCliError;
+use eventsource_stream::{Event, EventStreamError, Eventsource};
+use futures::future::BoxFuture;
+use futures::stream::{BoxStream, StreamExt};
+use pollux_schema::gemini::{Content, GeminiGenerateContentRequest, Part};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use tracing::{info, warn};
+
+type EventResult = Result<Event, EventStreamError<reqwest::Error>>;
+
+type Replay = Box<
+    dyn FnMut(
+            GeminiGenerateContentRequest,
+        ) -> BoxFuture<'static, Result<reqwest::Response, GeminiCliError>>
+        + Send,
+>;
+
+/// How to replay a request whose stream was cut short.
+pub struct StreamResume {
+    max_times: usize,
+    body: GeminiGenerateContentRequest,
+    replay: Replay,
+}
+
+impl StreamResume {
+    /// `None` when resumption is off (`max_times == 0`). `replay` sends the
+    /// continuation request, leasing a credential as a first attempt would.
+    pub(crate) fn new<F>(
+        max_times: usize,
+        body: &GeminiGenerateContentRequest,
+        replay: F,
+    ) -> Option<Self>
+    where
+        F: FnMut(
+                GeminiGenerateContentRequest,
+            ) -> BoxFuture<'static, Result<reqwest::Response, GeminiCliError>>
+            + Send
+            + 'static,
+    {
+        (max_times > 0).then(|| Self {
+            max_times,
+            body: body.clone(),
+            replay: Box::new(replay),
+        })
+    }
+
+    /// The original request with `text` appended to (or as) a final `model` turn.
+    fn continuation(&self, text: &str) -> GeminiGenerateContentRequest {
+        let mut body = self.body.clone();
+        if text.is_empty() {
+            return body;
+        }
+        let part = Part {
+            text: Some(text.to_string()),
+            ..Part::default()
+        };
+        match body.contents.last_mut() {
+            Some(last) if last.role.as_deref() == Some("model") => last.parts.push(part),
+            _ => body.contents.push(Content {
+                role: Some("model".to_string()),
+                parts: vec![part],
+                extra: BTreeMap::new(),
+            }),
+        }
+        body
+    }
+}
+
+/// What the client has been sent so far.
+struct Progress {
+    text: String,
+    finished: bool,
+    resumable: bool,
+    continuation: bool,
+}
+
+impl Progress {
+    /// Record `event`, dropping thought parts from continuations. Returns
+    /// `false` when nothing is left to forward.
+    fn observe(&mut self, event: &mut Event) -> bool {
+        let Ok(mut payload) = serde_json::from_str::<Value>(&event.data) else {
+            return true;
+        };
+        let Some(candidates) = payload
+            .pointer_mut("/response/candidates")
+            .and_then(Value::as_array_mut)
+        else {
+            return true;
+        };
+        let mut emptied = false;
+        for candidate in candidates.iter_mut() {
+            if candidate.get("index").and_then(Value::as_u64).unwrap_or(0) != 0 {
+                self.resumable = false;
+            }
+            if candidate.get("finishReason").is_some_and(|r| !r.is_null()) {
+                self.finished = true;
+            }
+            let Some(parts) = candidate
+                .pointer_mut("/content/parts")
+                .and_then(Value::as_array_mut)
+            else {
+                continue;
+            };
+            if self.continuation && parts.iter().any(is_thought) {
+                parts.retain(|p| !is_thought(p));
+                emptied |= parts.is_empty();
+            }
+            for part in parts.iter().filter(|p| !is_thought(p)) {
+                match part.get("text").and_then(Value::as_str) {
+                    Some(text) if part.get("functionCall").is_none() => self.text.push_str(text),
+                    _ => self.resumable = false,
+                }
+            }
+        }
+        if !self.continuation {
+            return true;
+        }
+        if emptied && !self.finished {
+            return false;
+        }
+        event.data = payload.to_string();
+        true
+    }
+}
+
+fn is_thought(part: &Value) -> bool {
+    part.get("thought").and_then(Value::as_bool) == Some(true)
+}
+
+fn events(resp: reqwest::Response) -> BoxStream<'static, EventResult> {
+    resp.bytes_stream().eventsource().boxed()
+}
+
+/// Upstream SSE events of `upstream`, continued through `resume` if the
+/// stream is cut short.
+pub(crate) fn resumable(
+    upstream: reqwest::Response,
+    resume: Option<StreamResume>,
+) -> BoxStream<'static, EventResult> {
+    let Some(resume) = resume else {
+        return events(upstream);
+    };
+    let progress = Progress {
+        text: String::new(),
+        finished: false,
+        resumable: true,
+        continuation: false,
+    };
+    futures::stream::unfold(
+        (events(upstream), resume, progress, 0, false),
+        |(mut inner, mut resume, mut progress, mut attempts, done)| async move {
+            if done {
+                return None;
+            }
+            loop {
+                let end = match inner.next().await {
+                    Some(Ok(mut event)) => {
+                        if progress.observe(&mut event) {
+                            return Some((Ok(event), (inner, resume, progress, attempts, false)));
+                        }
+                        continue;
+                    }
+                    end => end,
+                };
+                if !progress.finished && progress.resumable && attempts < resume.max_times {
+                    attempts += 1;
+                    let body = resume.continuation(&progress.text);
+                    match (resume.replay)(body).await {
+                        Ok(resp) => {
+                            info!(
+                                attempt = attempts,
+                                resumed_chars = progress.text.len(),
+                                "Upstream stream ended early; resumed on a new credential"
+                            );
+                            inner = events(resp);
+                            progress.continuation = true;
+                            continue;
+                        }
+                        Err(e) => warn!("Stream resumption failed: {}", e),
+                    }
+                }
+                return end.map(|e| (e, (inner, resume, progress, attempts, true)));
+            }
+        },
+    )
+    .boxed()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    fn sse(chunks: &[Value]) -> reqwest::Response {
+        let body = chunks
+            .iter()
+            .map(|c| format!("data: {c}\n\n"))
+            .collect::<Vec<_>>()
+            .concat();
+        reqwest::Response::from(axum::http::Response::new(body))
+    }
+
+    fn chunk(parts: &Value, finish: Option<&str>) -> Value {
+        let mut candidate = serde_json::json!({ "content": { "role": "model", "parts": parts } });
+        if let Some(reason) = finish {
+            candidate["finishReason"] = reason.into();
+        }
+        serde_json::json!({ "response": { "candidates": [candidate] } })
+    }
+
+    #[tokio::test]
+    async fn truncated_stream_is_continued_from_the_text_sent_so_far() {
+        let body: GeminiGenerateContentRequest = serde_json::from_value(serde_json::json!({
+            "contents": [{ "role": "user", "parts": [{ "text": "hi" }] }]
+        }))
+        .unwrap();
+        let replayed = Arc::new(Mutex::new(Vec::new()));
+        let seen = replayed.clone();
+        let resume = StreamResume::new(1, &body, move |body| {
+            seen.lock().unwrap().push(body);
+            Box::pin(async {
+                Ok(sse(&[
+                    chunk(
+                        &serde_json::json!([{ "text": "again", "thought": true }]),
+                        None,
+                    ),
+                    chunk(&serde_json::json!([{ "text": "lo" }]), Some("STOP")),
+                ]))
+            })
+        });
+
+        let first = sse(&[chunk(&serde_json::json!([{ "text": "Hel" }]), None)]);
+        let out: Vec<Value> = resumable(first, resume)
+            .map(|e| serde_json::from_str(&e.unwrap().data).unwrap())
+            .collect()
+            .await;
+
+        let texts: Vec<&str> = out
+            .iter()
+            .map(|c| {
+                c["response"]["candidates"][0]["content"]["parts"][0]["text"]
+                    .as_str()
+                    .unwrap()
+            })
+            .collect();
+        assert_eq!(texts, ["Hel", "lo"]);
+        let replayed = replayed.lock().unwrap();
+        assert_eq!(replayed.len(), 1);
+        let last = replayed[0].contents.last().unwrap();
+        assert_eq!(last.role.as_deref(), Some("model"));
+        assert_eq!(last.parts[0].text.as_deref(), Some("Hel"));
+    }
+
+    #[tokio::test]
+    async fn finished_or_tool_call_streams_are_not_replayed() {
+        let body: GeminiGenerateContentRequest =
+            serde_json::from_value(serde_json::json!({ "contents": [] })).unwrap();
+        for first in [
+            sse(&[chunk(
+                &serde_json::json!([{ "text": "done" }]),
+                Some("STOP"),
+            )]),
+            sse(&[chunk(
+                &serde_json::json!([{ "functionCall": { "name": "f", "args": {} } }]),
+                None,
+            )]),
+        ] {
+            let resume =
+                StreamResume::new(3, &body, |_| Box::pin(async { panic!("must not replay") }));
+            assert_eq!(resumable(first, resume).count().await, 1);
+        }
+    }
+}
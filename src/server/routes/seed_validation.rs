@@ -0,0 +1,67 @@
+//! `?validate=true` mode shared by the `/<provider>/resource:add` routes.
+//!
+//! Instead of queueing seeds and answering `202`, each submitted item is
+//! refreshed and onboarded before the response, which lists one
+//! [`SeedReport`] per item in submission order.
+
+use crate::providers::{RefreshTokenSeed, SeedReport};
+use crate::utils::http::is_supported_proxy;
+use axum::{Json, response::IntoResponse};
+use futures::future::join_all;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::future::Future;
+use url::Url;
+
+#[derive(Debug, Default, Deserialize)]
+pub struct ResourceAddQuery {
+    /// Onboard synchronously and report per item.
+    #[serde(default)]
+    pub validate: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SeedValidationResponse {
+    pub results: Vec<SeedReport>,
+}
+
+/// Report on each submitted `(refresh_token, labels, proxy_url)` item. Empty
+/// items and unsupported proxies are invalid, and repeats of an earlier item are duplicates; the rest go through
+/// `validate` concurrently.
+pub(crate) async fn validate_seeds<F, Fut>(
+    items: Vec<(Option<String>, Vec<String>, Option<Url>)>,
+    validate: F,
+) -> axum::response::Response
+where
+    F: Fn(RefreshTokenSeed) -> Fut,
+    Fut: Future<Output = SeedReport>,
+{
+    let mut submitted = HashSet::new();
+    let results = join_all(items.into_iter().map(|(token, labels, proxy_url)| {
+        let seed = token.as_deref().and_then(RefreshTokenSeed::new);
+        let checked = match seed {
+            None => Err(SeedReport::invalid("missing refresh_token")),
+            Some(_)
+                if proxy_url
+                    .as_ref()
+                    .is_some_and(|url| !is_supported_proxy(url)) =>
+            {
+                Err(SeedReport::invalid("unsupported proxy_url scheme"))
+            }
+            Some(seed) if !submitted.insert(seed.refresh_token().to_string()) => {
+                Err(SeedReport::duplicate())
+            }
+            Some(seed) => Ok(validate(
+                seed.with_labels(&labels).with_proxy_url(proxy_url),
+            )),
+        };
+        async move {
+            match checked {
+                Ok(pending) => pending.await,
+                Err(report) => report,
+            }
+        }
+    }))
+    .await;
+    Json(SeedValidationResponse { results }).into_response()
+}
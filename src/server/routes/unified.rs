@@ -0,0 +1,195 @@
+//! Provider-agnostic `OpenAI` routes.
+//!
+//! `/v1/models` lists every provider's models, and `/v1/chat/completions` and
+//! `/v1/responses` are dispatched to the first provider serving the request's
+//! `model` (after that provider's `model_aliases`), in `ProviderKind::ALL`
+//! order. The provider handlers run unchanged, so behavior matches their own
+//! prefixed routes.
+
+use super::{codex, geminicli, qwen};
+use crate::config::ModelAliases;
+use crate::error::CodexError;
+use crate::providers::Providers;
+use crate::providers::manifest::ProviderKind;
+use crate::server::DEFAULT_API_BODY_LIMIT_BYTES;
+use crate::server::router::PolluxState;
+use axum::{
+    Json, Router,
+    body::{Body, to_bytes},
+    extract::{DefaultBodyLimit, FromRequest, Request, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::{get, post},
+};
+use pollux_schema::OpenaiResponsesErrorObject;
+use pollux_schema::openai::{OpenaiModel, OpenaiModelList};
+use serde::Deserialize;
+use std::collections::HashSet;
+
+/// Providers that implement each unified endpoint.
+const CHAT_PROVIDERS: &[ProviderKind] = &[
+    ProviderKind::GeminiCli,
+    ProviderKind::Codex,
+    ProviderKind::Qwen,
+];
+const RESPONSES_PROVIDERS: &[ProviderKind] = &[ProviderKind::Codex];
+
+pub fn router() -> Router<PolluxState> {
+    Router::new()
+        .route("/v1/models", get(models_handler))
+        .route(
+            "/v1/chat/completions",
+            post(chat_completions_handler)
+                .layer(DefaultBodyLimit::max(DEFAULT_API_BODY_LIMIT_BYTES)),
+        )
+        .route(
+            "/v1/responses",
+            post(responses_handler).layer(DefaultBodyLimit::max(DEFAULT_API_BODY_LIMIT_BYTES)),
+        )
+}
+
+fn served_models(providers: &Providers, kind: ProviderKind) -> (&[String], &ModelAliases) {
+    match kind {
+        ProviderKind::GeminiCli => (
+            &providers.geminicli_cfg.model_list,
+            &providers.geminicli_cfg.model_aliases,
+        ),
+        ProviderKind::Codex => (
+            &providers.codex_cfg.model_list,
+            &providers.codex_cfg.model_aliases,
+        ),
+        ProviderKind::Antigravity => (
+            &providers.antigravity_cfg.model_list,
+            &providers.antigravity_cfg.model_aliases,
+        ),
+        ProviderKind::Claude => (
+            &providers.claude_cfg.model_list,
+            &providers.claude_cfg.model_aliases,
+        ),
+        ProviderKind::Qwen => (
+            &providers.qwen_cfg.model_list,
+            &providers.qwen_cfg.model_aliases,
+        ),
+    }
+}
+
+/// First of `candidates` whose model list has `model`.
+pub fn route(
+    providers: &Providers,
+    model: &str,
+    candidates: &[ProviderKind],
+) -> Option<ProviderKind> {
+    candidates.iter().copied().find(|&kind| {
+        let (models, aliases) = served_models(providers, kind);
+        let canonical = aliases.resolve(model);
+        models.iter().any(|m| *m == *canonical)
+    })
+}
+
+/// All configured models, each listed once under the first provider serving it.
+pub fn model_list(providers: &Providers) -> OpenaiModelList {
+    let mut seen = HashSet::new();
+    let mut list = OpenaiModelList::default();
+    for kind in ProviderKind::ALL {
+        let (models, _) = served_models(providers, kind);
+        list.data.extend(
+            models
+                .iter()
+                .filter(|id| seen.insert(id.as_str()))
+                .map(|id| OpenaiModel {
+                    id: id.clone(),
+                    owned_by: kind.label().to_string(),
+                    display_name: id.clone(),
+                    ..OpenaiModel::default()
+                }),
+        );
+    }
+    list
+}
+
+/// GET /v1/models
+async fn models_handler(State(state): State<PolluxState>) -> Json<OpenaiModelList> {
+    Json(model_list(&state.providers))
+}
+
+#[derive(Deserialize)]
+struct ModelField {
+    #[serde(default)]
+    model: String,
+}
+
+/// Read the body's `model` and hand back an equivalent request. An unreadable
+/// body yields an empty model, leaving the rejection to the provider extractor.
+async fn peek_model(req: Request) -> Result<(Request, String), Response> {
+    let (parts, body) = req.into_parts();
+    let bytes = to_bytes(body, DEFAULT_API_BODY_LIMIT_BYTES)
+        .await
+        .map_err(|e| (StatusCode::PAYLOAD_TOO_LARGE, e.to_string()).into_response())?;
+    let model = serde_json::from_slice::<ModelField>(&bytes)
+        .map(|f| f.model)
+        .unwrap_or_default();
+    Ok((Request::from_parts(parts, Body::from(bytes)), model))
+}
+
+fn unsupported_model(model: &str) -> Response {
+    CodexError::RequestRejected {
+        status: StatusCode::BAD_REQUEST,
+        body: OpenaiResponsesErrorObject {
+            code: Some("UNSUPPORTED_MODEL".to_string()),
+            message: format!("no provider serves model `{model}`"),
+            r#type: "UNSUPPORTED_MODEL".to_string(),
+            param: None,
+        },
+        debug_message: None,
+    }
+    .into_response()
+}
+
+/// POST /v1/chat/completions
+async fn chat_completions_handler(State(state): State<PolluxState>, req: Request) -> Response {
+    let (req, model) = match peek_model(req).await {
+        Ok(peeked) => peeked,
+        Err(resp) => return resp,
+    };
+    match route(&state.providers, &model, CHAT_PROVIDERS) {
+        Some(ProviderKind::GeminiCli) => {
+            match geminicli::extract::GeminiChatPreprocess::from_request(req, &state).await {
+                Ok(preprocess) => {
+                    geminicli::handlers::gemini_chat_completions_handler(State(state), preprocess)
+                        .await
+                }
+                Err(e) => e.into_response(),
+            }
+        }
+        Some(ProviderKind::Qwen) => {
+            match qwen::extract::QwenPreprocess::from_request(req, &state).await {
+                Ok(preprocess) => {
+                    qwen::handlers::qwen_chat_completions_handler(State(state), preprocess).await
+                }
+                Err(e) => e.into_response(),
+            }
+        }
+        None if !model.is_empty() => unsupported_model(&model),
+        _ => match codex::extract::CodexChatPreprocess::from_request(req, &state).await {
+            Ok(preprocess) => {
+                codex::handlers::codex_chat_completions_handler(State(state), preprocess).await
+            }
+            Err(e) => e.into_response(),
+        },
+    }
+}
+
+/// POST /v1/responses
+async fn responses_handler(State(state): State<PolluxState>, req: Request) -> Response {
+    let (req, model) = match peek_model(req).await {
+        Ok(peeked) => peeked,
+        Err(resp) => return resp,
+    };
+    if !model.is_empty() && route(&state.providers, &model, RESPONSES_PROVIDERS).is_none() {
+        return unsupported_model(&model);
+    }
+    match codex::extract::CodexPreprocess::from_request(req, &state).await {
+        Ok(preprocess) => codex::handlers::codex_response_handler(State(state), preprocess).await,
+        Err(e) => e.into_response(),
+    }
+}
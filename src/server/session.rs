@@ -0,0 +1,42 @@
+//! Client-declared session affinity.
+//!
+//! Requests carrying `x-pollux-session` are hashed into a `route_key`; the
+//! provider actors use it to keep handing the same conversation to the same
+//! credential while it stays healthy (see `RouteTable`).
+
+use axum::http::{HeaderMap, HeaderName};
+use std::hash::Hasher;
+
+pub static X_POLLUX_SESSION: HeaderName = HeaderName::from_static("x-pollux-session");
+
+/// Stable routing key for a session identifier.
+pub fn route_key(session: &str) -> u64 {
+    let mut hasher = ahash::AHasher::default();
+    hasher.write(session.as_bytes());
+    hasher.finish()
+}
+
+/// Routing key from `x-pollux-session`, if the client sent a non-empty one.
+pub fn session_route_key(headers: &HeaderMap) -> Option<u64> {
+    let value = headers.get(&X_POLLUX_SESSION)?.to_str().ok()?.trim();
+    (!value.is_empty()).then(|| route_key(value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    #[test]
+    fn session_header_maps_to_stable_key() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(session_route_key(&headers), None);
+
+        headers.insert(&X_POLLUX_SESSION, HeaderValue::from_static("   "));
+        assert_eq!(session_route_key(&headers), None);
+
+        headers.insert(&X_POLLUX_SESSION, HeaderValue::from_static(" conv-1 "));
+        assert_eq!(session_route_key(&headers), Some(route_key("conv-1")));
+        assert_ne!(route_key("conv-1"), route_key("conv-2"));
+    }
+}
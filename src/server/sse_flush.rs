@@ -0,0 +1,243 @@
+//! Write batching for event-stream responses (`providers.sse`).
+//!
+//! Axum writes each SSE event as its own body chunk. When buffering is
+//! configured, chunks are held until `max_buffer_bytes` have accumulated or
+//! the oldest buffered byte has waited `max_latency_ms`, whichever comes first.
+//! Streaming routes also get their idle heartbeat from here.
+
+use crate::config::SseFlushConfig;
+use crate::server::drain::is_event_stream;
+use axum::{
+    BoxError,
+    body::{Body, Bytes},
+    extract::{Request, State},
+    middleware::Next,
+    response::{
+        IntoResponse, Response,
+        sse::{Event, KeepAlive, Sse},
+    },
+};
+use futures::{Stream, StreamExt};
+use std::collections::VecDeque;
+use std::convert::Infallible;
+use std::time::Duration;
+use tokio::time::Instant;
+
+struct FlushState<S> {
+    body: std::pin::Pin<Box<S>>,
+    buf: Vec<u8>,
+    ready: VecDeque<Bytes>,
+    deadline: Option<Instant>,
+    done: bool,
+}
+
+impl<S> FlushState<S> {
+    fn take_buf(&mut self) -> Option<Bytes> {
+        self.deadline = None;
+        (!self.buf.is_empty()).then(|| Bytes::from(std::mem::take(&mut self.buf)))
+    }
+}
+
+/// Re-chunk `body` according to `cfg`; `cfg.max_buffer_bytes` must be non-zero.
+fn batched<S>(body: S, cfg: SseFlushConfig) -> impl Stream<Item = Result<Bytes, axum::Error>>
+where
+    S: Stream<Item = Result<Bytes, axum::Error>> + Send + 'static,
+{
+    let max = cfg.max_buffer_bytes;
+    let latency = Duration::from_millis(cfg.max_latency_ms);
+    let state = FlushState {
+        body: Box::pin(body),
+        buf: Vec::with_capacity(max),
+        ready: VecDeque::new(),
+        deadline: None,
+        done: false,
+    };
+
+    futures::stream::unfold(state, move |mut st| async move {
+        loop {
+            if let Some(chunk) = st.ready.pop_front() {
+                return Some((Ok(chunk), st));
+            }
+            if st.done {
+                let rest = st.take_buf()?;
+                return Some((Ok(rest), st));
+            }
+
+            let next = match st.deadline {
+                Some(deadline) => tokio::select! {
+                    chunk = st.body.next() => Some(chunk),
+                    () = tokio::time::sleep_until(deadline) => None,
+                },
+                None => Some(st.body.next().await),
+            };
+            match next {
+                // Latency budget spent: write whatever is buffered.
+                None => {
+                    if let Some(buffered) = st.take_buf() {
+                        return Some((Ok(buffered), st));
+                    }
+                }
+                Some(None) => st.done = true,
+                Some(Some(Err(e))) => return Some((Err(e), st)),
+                Some(Some(Ok(mut chunk))) => {
+                    if cfg.split_oversized && chunk.len() > max {
+                        if let Some(buffered) = st.take_buf() {
+                            st.ready.push_back(buffered);
+                        }
+                        while !chunk.is_empty() {
+                            st.ready.push_back(chunk.split_to(max.min(chunk.len())));
+                        }
+                        continue;
+                    }
+                    st.buf.extend_from_slice(&chunk);
+                    if st.buf.len() >= max {
+                        if let Some(buffered) = st.take_buf() {
+                            return Some((Ok(buffered), st));
+                        }
+                    } else if st.deadline.is_none() {
+                        st.deadline = Some(Instant::now() + latency);
+                    }
+                }
+            }
+        }
+    })
+}
+
+/// Send `: ping` comments on `sse` after `heartbeat_secs` without an event.
+pub(crate) fn with_heartbeat<S, E>(sse: Sse<S>, cfg: SseFlushConfig) -> Response
+where
+    S: Stream<Item = Result<Event, E>> + Send + 'static,
+    E: Into<BoxError>,
+{
+    if cfg.heartbeat_secs == 0 {
+        return sse.into_response();
+    }
+    sse.keep_alive(
+        KeepAlive::new()
+            .interval(Duration::from_secs(cfg.heartbeat_secs))
+            .text("ping"),
+    )
+    .into_response()
+}
+
+/// End `events` at its first error with the event `to_frame` renders for it.
+///
+/// A stream error would otherwise abort the body mid-chunk; clients see a cut
+/// connection instead of the provider-native error object they can parse.
+pub(crate) fn close_with_error_frame<S, E, F>(
+    events: S,
+    to_frame: F,
+) -> impl Stream<Item = Result<Event, Infallible>>
+where
+    S: Stream<Item = Result<Event, E>>,
+    F: FnOnce(E) -> Event,
+{
+    futures::stream::unfold(
+        (Box::pin(events), Some(to_frame)),
+        |(mut events, to_frame)| async move {
+            let to_frame = to_frame?;
+            match events.next().await? {
+                Ok(event) => Some((Ok(event), (events, Some(to_frame)))),
+                Err(e) => Some((Ok(to_frame(e)), (events, None))),
+            }
+        },
+    )
+}
+
+/// Middleware: apply the flush policy to event-stream responses.
+pub async fn sse_flush(State(cfg): State<SseFlushConfig>, req: Request, next: Next) -> Response {
+    let resp = next.run(req).await;
+    if cfg.max_buffer_bytes == 0 || !is_event_stream(&resp) {
+        return resp;
+    }
+    let (parts, body) = resp.into_parts();
+    Response::from_parts(
+        parts,
+        Body::from_stream(batched(body.into_data_stream(), cfg)),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn events(chunks: &[&'static str]) -> impl Stream<Item = Result<Bytes, axum::Error>> + Send {
+        futures::stream::iter(
+            chunks
+                .iter()
+                .map(|c| Ok(Bytes::from_static(c.as_bytes())))
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    async fn collect(stream: impl Stream<Item = Result<Bytes, axum::Error>>) -> Vec<String> {
+        stream
+            .map(|c| String::from_utf8(c.unwrap().to_vec()).unwrap())
+            .collect()
+            .await
+    }
+
+    #[tokio::test]
+    async fn small_events_are_merged_and_large_ones_split() {
+        let cfg = SseFlushConfig {
+            max_buffer_bytes: 8,
+            max_latency_ms: 1_000,
+            split_oversized: true,
+            ..Default::default()
+        };
+        let out = collect(batched(
+            events(&["ab", "cd", "efgh", "0123456789", "z"]),
+            cfg,
+        ))
+        .await;
+        assert_eq!(out, vec!["abcdefgh", "01234567", "89", "z"]);
+    }
+
+    #[tokio::test]
+    async fn partial_buffer_is_flushed_after_max_latency() {
+        let cfg = SseFlushConfig {
+            max_buffer_bytes: 1024,
+            max_latency_ms: 20,
+            split_oversized: false,
+            ..Default::default()
+        };
+        let body = events(&["data: 1\n\n"]).chain(futures::stream::pending());
+        let mut out = Box::pin(batched(body, cfg));
+        let first = tokio::time::timeout(Duration::from_secs(1), out.next())
+            .await
+            .expect("flushed by latency, not end of stream");
+        assert_eq!(first.unwrap().unwrap(), Bytes::from_static(b"data: 1\n\n"));
+    }
+
+    #[tokio::test]
+    async fn stream_error_becomes_a_final_event() {
+        let upstream = futures::stream::iter(vec![
+            Ok(Event::default().data("1")),
+            Err("boom"),
+            Ok(Event::default().data("never sent")),
+        ]);
+        let framed = close_with_error_frame(upstream, |e| Event::default().event("error").data(e));
+        let body = Sse::new(framed).into_response().into_body();
+        let bytes = axum::body::to_bytes(body, usize::MAX).await.unwrap();
+        assert_eq!(
+            String::from_utf8_lossy(&bytes),
+            "data: 1\n\nevent: error\ndata: boom\n\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn idle_stream_gets_ping_comments() {
+        let cfg = SseFlushConfig {
+            heartbeat_secs: 1,
+            ..Default::default()
+        };
+        let idle = futures::stream::pending::<Result<Event, std::convert::Infallible>>();
+        let mut body = with_heartbeat(Sse::new(idle), cfg)
+            .into_body()
+            .into_data_stream();
+        let first = tokio::time::timeout(Duration::from_secs(3), body.next())
+            .await
+            .expect("heartbeat before the upstream ever spoke");
+        assert_eq!(first.unwrap().unwrap(), Bytes::from_static(b": ping\n\n"));
+    }
+}
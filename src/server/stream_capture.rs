@@ -0,0 +1,173 @@
+//! Raw upstream SSE capture for `pollux replay` (`basic.stream_capture_dir`).
+//!
+//! Each streamed Gemini CLI or Antigravity response is copied byte for byte
+//! into its own `.sse` file, led by a `: pollux-capture ...` comment naming
+//! the provider and model. SSE parsers skip comments, so a capture is itself a
+//! valid event stream and can be replayed through the respond pipeline as-is.
+//! Continuations fetched by stream resume are not captured.
+
+use crate::providers::manifest::ProviderKind;
+use crate::utils::logging::payload_logging_enabled;
+use chrono::Utc;
+use futures::StreamExt;
+use std::fs::File;
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use tracing::warn;
+
+const HEADER_PREFIX: &str = ": pollux-capture ";
+
+#[derive(Debug, Clone)]
+pub struct StreamCapture {
+    dir: Arc<PathBuf>,
+    seq: Arc<AtomicU64>,
+}
+
+impl StreamCapture {
+    /// Capture into `dir`, creating it if missing.
+    pub fn new(dir: PathBuf) -> std::io::Result<Self> {
+        std::fs::create_dir_all(&dir)?;
+        Ok(Self {
+            dir: Arc::new(dir),
+            seq: Arc::default(),
+        })
+    }
+
+    /// `resp` with its body copied to a new capture file as it is read.
+    /// Returns `resp` untouched if the file cannot be created, or in
+    /// compliance mode, where payloads never reach disk.
+    pub(crate) fn tee(
+        &self,
+        provider: ProviderKind,
+        model: &str,
+        resp: reqwest::Response,
+    ) -> reqwest::Response {
+        if !payload_logging_enabled() {
+            return resp;
+        }
+        let seq = self.seq.fetch_add(1, Ordering::Relaxed);
+        let name = format!(
+            "{}-{}-{}-{seq}.sse",
+            Utc::now().format("%Y%m%dT%H%M%S"),
+            provider.label(),
+            sanitize(model),
+        );
+        let path = self.dir.join(name);
+        let mut file = match File::create(&path) {
+            Ok(file) => file,
+            Err(e) => {
+                warn!(path = %path.display(), error = %e, "[Capture] Cannot create capture file");
+                return resp;
+            }
+        };
+        let _ = file.write_all(header(provider, model).as_bytes());
+
+        let mut builder = axum::http::Response::builder()
+            .status(resp.status())
+            .version(resp.version());
+        if let Some(headers) = builder.headers_mut() {
+            headers.clone_from(resp.headers());
+        }
+        let body = resp.bytes_stream().inspect(move |chunk| {
+            if let Ok(bytes) = chunk {
+                let _ = file.write_all(bytes);
+            }
+        });
+        match builder.body(reqwest::Body::wrap_stream(body)) {
+            Ok(copy) => reqwest::Response::from(copy),
+            Err(e) => unreachable!("status and headers come from a valid response: {e}"),
+        }
+    }
+}
+
+fn header(provider: ProviderKind, model: &str) -> String {
+    format!(
+        "{HEADER_PREFIX}provider={} model={}\n\n",
+        provider.label(),
+        sanitize(model)
+    )
+}
+
+/// Provider and model named by a capture's leading comment.
+pub(crate) fn parse_header(capture: &str) -> Option<(ProviderKind, String)> {
+    let line = capture.lines().next()?.strip_prefix(HEADER_PREFIX)?;
+    let mut provider = None;
+    let mut model = String::new();
+    for field in line.split_whitespace() {
+        match field.split_once('=') {
+            Some(("provider", label)) => {
+                provider = ProviderKind::ALL.into_iter().find(|k| k.label() == label);
+            }
+            Some(("model", name)) => model = name.to_string(),
+            _ => {}
+        }
+    }
+    Some((provider?, model))
+}
+
+fn sanitize(model: &str) -> String {
+    model
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(not(feature = "compliance"))]
+    #[tokio::test]
+    async fn tee_copies_the_body_behind_a_header_comment() {
+        let dir = std::env::temp_dir().join(format!("pollux-capture-{}", uuid::Uuid::new_v4()));
+        let capture = StreamCapture::new(dir.clone()).unwrap();
+        let upstream = axum::http::Response::builder()
+            .header("content-type", "text/event-stream")
+            .body("data: {\"a\":1}\n\n")
+            .unwrap();
+
+        let resp = capture.tee(
+            ProviderKind::Antigravity,
+            "models/gemini 3",
+            reqwest::Response::from(upstream),
+        );
+        assert_eq!(resp.headers()["content-type"], "text/event-stream");
+        assert_eq!(resp.text().await.unwrap(), "data: {\"a\":1}\n\n");
+
+        let file = std::fs::read_dir(&dir).unwrap().next().unwrap().unwrap();
+        let text = std::fs::read_to_string(file.path()).unwrap();
+        assert_eq!(
+            parse_header(&text),
+            Some((ProviderKind::Antigravity, "models_gemini_3".to_string()))
+        );
+        assert!(text.ends_with("\n\ndata: {\"a\":1}\n\n"));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[cfg(feature = "compliance")]
+    #[tokio::test]
+    async fn tee_writes_nothing_in_compliance_mode() {
+        let dir = std::env::temp_dir().join(format!("pollux-capture-{}", uuid::Uuid::new_v4()));
+        let capture = StreamCapture::new(dir.clone()).unwrap();
+        let upstream = axum::http::Response::builder()
+            .body("data: {\"a\":1}\n\n")
+            .unwrap();
+
+        let resp = capture.tee(
+            ProviderKind::GeminiCli,
+            "gemini-2.5-pro",
+            reqwest::Response::from(upstream),
+        );
+        assert_eq!(resp.text().await.unwrap(), "data: {\"a\":1}\n\n");
+        assert!(std::fs::read_dir(&dir).unwrap().next().is_none());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
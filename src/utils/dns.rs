@@ -0,0 +1,107 @@
+use crate::config::{DnsConfig, IpPreference};
+use crate::error::PolluxError;
+use hickory_resolver::TokioResolver;
+use hickory_resolver::config::LookupIpStrategy;
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+use std::net::SocketAddr;
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+/// Lookups slower than this are logged; they count against request latency.
+const SLOW_LOOKUP: Duration = Duration::from_secs(1);
+
+/// Set once at startup when `providers.dns.enabled`; clients built before
+/// that (or with it disabled) use reqwest's system resolver.
+static RESOLVER: OnceLock<Arc<CachingResolver>> = OnceLock::new();
+
+/// Caching resolver that honours record TTLs (capped by `max_ttl_secs`).
+#[derive(Clone)]
+pub(crate) struct CachingResolver {
+    inner: TokioResolver,
+}
+
+impl CachingResolver {
+    fn new(cfg: &DnsConfig) -> Result<Self, PolluxError> {
+        let mut builder = TokioResolver::builder_tokio().map_err(|e| {
+            PolluxError::UnexpectedError(format!("failed to read system DNS config: {e}"))
+        })?;
+        let opts = builder.options_mut();
+        opts.ip_strategy = match cfg.ip_preference {
+            IpPreference::Ipv4First => LookupIpStrategy::Ipv4AndIpv6,
+            IpPreference::Ipv6First => LookupIpStrategy::Ipv6AndIpv4,
+            IpPreference::Ipv4Only => LookupIpStrategy::Ipv4Only,
+            IpPreference::Ipv6Only => LookupIpStrategy::Ipv6Only,
+        };
+        opts.cache_size = cfg.cache_size.max(1);
+        opts.positive_max_ttl = Some(Duration::from_secs(cfg.max_ttl_secs));
+        opts.timeout = Duration::from_secs(cfg.timeout_secs.max(1));
+        let inner = builder.build().map_err(|e| {
+            PolluxError::UnexpectedError(format!("failed to build DNS resolver: {e}"))
+        })?;
+        Ok(Self { inner })
+    }
+}
+
+impl Resolve for CachingResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        let resolver = self.inner.clone();
+        Box::pin(async move {
+            let started = Instant::now();
+            let lookup = resolver.lookup_ip(name.as_str()).await?;
+            let elapsed = started.elapsed();
+            if elapsed >= SLOW_LOOKUP {
+                warn!(
+                    host = name.as_str(),
+                    elapsed_ms = elapsed.as_millis(),
+                    "Slow DNS lookup"
+                );
+            }
+            let addrs: Vec<SocketAddr> = lookup.iter().map(|ip| SocketAddr::new(ip, 0)).collect();
+            Ok(Box::new(addrs.into_iter()) as Addrs)
+        })
+    }
+}
+
+/// Installs the caching resolver for every upstream client built afterwards.
+/// A no-op when `providers.dns.enabled` is false or a resolver is already set.
+pub fn install_dns_resolver(cfg: &DnsConfig) -> Result<(), PolluxError> {
+    if !cfg.enabled || RESOLVER.get().is_some() {
+        return Ok(());
+    }
+    let resolver = CachingResolver::new(cfg)?;
+    if RESOLVER.set(Arc::new(resolver)).is_ok() {
+        info!(
+            ip_preference = ?cfg.ip_preference,
+            max_ttl_secs = cfg.max_ttl_secs,
+            "Caching DNS resolver enabled"
+        );
+    }
+    Ok(())
+}
+
+/// Attaches the installed resolver, if any, to a client builder.
+pub(crate) fn with_resolver(builder: reqwest::ClientBuilder) -> reqwest::ClientBuilder {
+    match RESOLVER.get() {
+        Some(resolver) => builder.dns_resolver(resolver.clone()),
+        None => builder,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn ip_literals_resolve_without_a_nameserver() {
+        let cfg = DnsConfig {
+            enabled: true,
+            ip_preference: IpPreference::Ipv4Only,
+            ..DnsConfig::default()
+        };
+        let resolver = CachingResolver::new(&cfg).expect("resolver builds");
+        let name: Name = "127.0.0.1".parse().expect("valid name");
+        let addrs: Vec<SocketAddr> = resolver.resolve(name).await.expect("resolves").collect();
+        assert_eq!(addrs, vec!["127.0.0.1:0".parse().unwrap()]);
+    }
+}
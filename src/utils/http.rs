@@ -0,0 +1,125 @@
+use crate::config::HttpClientConfig;
+use reqwest::{Client, ClientBuilder};
+use std::sync::Arc;
+use std::time::Duration;
+use url::Url;
+
+/// Applies the configured overrides on top of a provider client's builder.
+///
+/// Pool and HTTP/2 keepalive settings are skipped without multiplexing,
+/// where the builder already disables pooling.
+pub(crate) fn tune(
+    mut builder: ClientBuilder,
+    cfg: &HttpClientConfig,
+    enable_multiplexing: bool,
+) -> ClientBuilder {
+    if let Some(secs) = cfg.connect_timeout_secs {
+        builder = builder.connect_timeout(Duration::from_secs(secs));
+    }
+    if let Some(secs) = cfg.read_timeout_secs {
+        builder = builder.read_timeout(Duration::from_secs(secs));
+    }
+    if let Some(secs) = cfg.tcp_keepalive_secs {
+        builder = builder.tcp_keepalive(Duration::from_secs(secs));
+    }
+    if !enable_multiplexing {
+        return builder;
+    }
+    if let Some(max) = cfg.pool_max_idle_per_host {
+        builder = builder.pool_max_idle_per_host(max);
+    }
+    if let Some(secs) = cfg.pool_idle_timeout_secs {
+        builder = builder.pool_idle_timeout(Duration::from_secs(secs));
+    }
+    if let Some(secs) = cfg.http2_keep_alive_interval_secs {
+        builder = builder
+            .http2_keep_alive_interval(Duration::from_secs(secs))
+            .http2_keep_alive_while_idle(true);
+    }
+    builder
+}
+
+/// Clients for credentials bound to their own proxy, built on first use.
+///
+/// Entries sit in a bounded LRU so bindings that stop being leased release
+/// their connection pools.
+#[derive(Clone)]
+pub(crate) struct BoundClients<T> {
+    cache: moka::sync::Cache<Url, T>,
+    build: Arc<dyn Fn(&Url) -> T + Send + Sync>,
+}
+
+impl<T: Clone + Send + Sync + 'static> BoundClients<T> {
+    pub(crate) fn new(build: impl Fn(&Url) -> T + Send + Sync + 'static) -> Self {
+        Self {
+            cache: moka::sync::Cache::new(BOUND_CLIENTS_CAPACITY),
+            build: Arc::new(build),
+        }
+    }
+
+    pub(crate) fn get(&self, proxy: &Url) -> T {
+        self.cache.get_with_by_ref(proxy, || (self.build)(proxy))
+    }
+}
+
+/// Distinct bound proxies kept warm per client set.
+const BOUND_CLIENTS_CAPACITY: u64 = 256;
+
+/// Unary and streaming upstream clients, one pair per egress proxy.
+///
+/// A credential bound to its own proxy always uses that proxy. Otherwise,
+/// with a `proxy_pool`, it is pinned to `pool[id % len]`, so an account keeps
+/// its egress IP as long as the pool itself is unchanged.
+#[derive(Clone)]
+pub(crate) struct EgressClients {
+    pairs: Arc<[(Client, Client)]>,
+    bound: Option<BoundClients<(Client, Client)>>,
+}
+
+impl EgressClients {
+    /// `pairs` holds `(unary, stream)` clients and must not be empty.
+    pub(crate) fn new(pairs: Vec<(Client, Client)>) -> Self {
+        assert!(!pairs.is_empty(), "egress client pool must not be empty");
+        Self {
+            pairs: pairs.into(),
+            bound: None,
+        }
+    }
+
+    pub(crate) fn single(client: Client, stream_client: Client) -> Self {
+        Self::new(vec![(client, stream_client)])
+    }
+
+    /// Honor per-credential proxy bindings, building their clients with
+    /// `bound`. Without it bindings are ignored.
+    #[must_use]
+    pub(crate) fn with_bound(mut self, bound: BoundClients<(Client, Client)>) -> Self {
+        self.bound = Some(bound);
+        self
+    }
+
+    /// Client used for credential `id`, bound to `proxy` when set.
+    pub(crate) fn select(&self, id: u64, proxy: Option<&Url>, stream: bool) -> Client {
+        let (client, stream_client) = if let (Some(proxy), Some(bound)) = (proxy, &self.bound) {
+            bound.get(proxy)
+        } else {
+            let len = self.pairs.len() as u64;
+            self.pairs[usize::try_from(id % len).unwrap_or(0)].clone()
+        };
+        if stream { stream_client } else { client }
+    }
+}
+
+/// Proxy schemes reqwest can dial: HTTP(S) and SOCKS5.
+pub(crate) fn is_supported_proxy(url: &Url) -> bool {
+    matches!(url.scheme(), "http" | "https" | "socks5" | "socks5h")
+}
+
+/// Proxy URL with any password masked, for logs and admin views.
+pub(crate) fn redact_proxy(url: &Url) -> String {
+    let mut shown = url.clone();
+    if shown.password().is_some() {
+        let _ = shown.set_password(Some("***"));
+    }
+    shown.to_string()
+}
@@ -1,11 +1,40 @@
 use serde::Serialize;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Compliance mode: request/response bodies never reach logs, only metadata.
+///
+/// Building with the `compliance` feature turns it on unconditionally;
+/// otherwise `basic.compliance_mode` enables it at startup.
+static COMPLIANCE_MODE: AtomicBool = AtomicBool::new(cfg!(feature = "compliance"));
+
+/// Turns on compliance mode for the rest of the process. It cannot be turned
+/// back off, and a `compliance` build ignores `false`.
+pub fn set_compliance_mode(enabled: bool) {
+    if enabled {
+        COMPLIANCE_MODE.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Whether payloads (bodies, token responses, upstream error text) may be logged.
+pub(crate) fn payload_logging_enabled() -> bool {
+    !COMPLIANCE_MODE.load(Ordering::Relaxed)
+}
+
+/// Truncated body text for logs, or just its size in compliance mode.
+pub(crate) fn body_preview(raw: &str, max_chars: usize) -> String {
+    if payload_logging_enabled() {
+        format!("{raw:.max_chars$}")
+    } else {
+        format!("<redacted {} bytes>", raw.len())
+    }
+}
 
 pub(crate) fn with_pretty_json_debug<T, F>(value: &T, log_action: F)
 where
     T: Serialize,
     F: FnOnce(&str),
 {
-    if !tracing::enabled!(tracing::Level::DEBUG) {
+    if !tracing::enabled!(tracing::Level::DEBUG) || !payload_logging_enabled() {
         return;
     }
 
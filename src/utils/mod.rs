@@ -1,2 +1,5 @@
+pub(crate) mod dns;
+pub(crate) mod http;
 pub(crate) mod jwt;
 pub(crate) mod logging;
+pub(crate) mod request_hash;
@@ -0,0 +1,50 @@
+//! Canonical request hashing.
+//!
+//! The hash covers the model and the client payload as parsed by Pollux, before
+//! any Pollux-side rewriting (thought signatures, experiments). Object keys are
+//! re-serialized in sorted order, so whitespace and key order on the wire do
+//! not change it.
+
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::fmt::Write as _;
+
+/// `sha256:<hex>` over `{"model": .., "request": ..}` with sorted keys.
+pub(crate) fn canonical_request_hash<T: Serialize>(model: &str, body: &T) -> Option<String> {
+    // `serde_json::Map` is a `BTreeMap` (no `preserve_order`), so this is canonical.
+    let value = serde_json::json!({
+        "model": model,
+        "request": serde_json::to_value(body).ok()?,
+    });
+    let bytes = serde_json::to_vec(&value).ok()?;
+    let digest = Sha256::digest(&bytes);
+
+    let mut out = String::with_capacity(7 + digest.len() * 2);
+    out.push_str("sha256:");
+    for byte in digest {
+        let _ = write!(out, "{byte:02x}");
+    }
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::canonical_request_hash;
+    use serde_json::Value;
+
+    #[test]
+    fn key_order_and_whitespace_do_not_change_the_hash() {
+        let a: Value = serde_json::from_str(r#"{"b":1,"a":{"y":true,"x":[1,2]}}"#).unwrap();
+        let b: Value =
+            serde_json::from_str("{ \"a\": { \"x\": [1, 2], \"y\": true },\n \"b\": 1 }").unwrap();
+
+        let ha = canonical_request_hash("m", &a).unwrap();
+        assert_eq!(ha, canonical_request_hash("m", &b).unwrap());
+        assert!(ha.starts_with("sha256:"));
+        assert_eq!(ha.len(), 7 + 64);
+
+        assert_ne!(ha, canonical_request_hash("other", &a).unwrap());
+        let c: Value = serde_json::from_str(r#"{"b":2,"a":{"y":true,"x":[1,2]}}"#).unwrap();
+        assert_ne!(ha, canonical_request_hash("m", &c).unwrap());
+    }
+}
@@ -0,0 +1,201 @@
+#![allow(clippy::too_many_lines)]
+use axum::{
+    Router,
+    body::{Body, to_bytes},
+    http::{Request, StatusCode},
+};
+use pollux::db::{CodexCreate, ProviderCreate};
+use serde_json::Value;
+use std::{
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+use tower::ServiceExt;
+
+const KEY: &str = "pwd";
+
+async fn send(app: &Router, method: &str, uri: &str, body: Option<&str>) -> (StatusCode, Value) {
+    let mut builder = Request::builder()
+        .method(method)
+        .uri(uri)
+        .header("x-goog-api-key", KEY);
+    if body.is_some() {
+        builder = builder.header("content-type", "application/json");
+    }
+    let req = builder
+        .body(body.map_or_else(Body::empty, |b| Body::from(b.to_string())))
+        .expect("failed to build request");
+    let resp = app.clone().oneshot(req).await.expect("request failed");
+    let status = resp.status();
+    let bytes = to_bytes(resp.into_body(), usize::MAX)
+        .await
+        .expect("read body");
+    let json = serde_json::from_slice(&bytes).unwrap_or(Value::Null);
+    (status, json)
+}
+
+#[tokio::test]
+async fn admin_credentials_list_disable_enable_delete() {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("system time before UNIX_EPOCH")
+        .as_nanos();
+    let mut temp_path = std::env::temp_dir();
+    temp_path.push(format!(
+        "pollux-admin-{}-{}.sqlite",
+        std::process::id(),
+        nanos
+    ));
+    let database_url = format!("sqlite:{}", temp_path.display());
+    let db = pollux::db::spawn(&database_url).await;
+
+    let id = db
+        .create(ProviderCreate::Codex(CodexCreate {
+            email: Some("admin@example.com".to_string()),
+            sub: "auth0|admin".to_string(),
+            account_id: "acct-admin".to_string(),
+            refresh_token: "rt-admin".to_string(),
+            access_token: "at-admin".to_string(),
+            expiry: chrono::Utc::now() + chrono::Duration::hours(1),
+            chatgpt_plan_type: Some("plus".to_string()),
+            labels: Vec::new(),
+            proxy_url: None,
+        }))
+        .await
+        .expect("create codex row");
+
+    let mut cfg = pollux::config::Config::default();
+    cfg.basic.pollux_key = KEY.to_string();
+    let providers = pollux::providers::Providers::spawn(db, &cfg).await;
+    let state = pollux::server::router::PolluxState::new(
+        providers,
+        Arc::from(KEY),
+        cfg.basic.insecure_cookie,
+    );
+    let app = pollux::server::router::pollux_router(state);
+
+    // Requires the key like every other API route.
+    let resp = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri("/admin/v1/credentials")
+                .body(Body::empty())
+                .expect("failed to build request"),
+        )
+        .await
+        .expect("request failed");
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+
+    let (status, body) = send(&app, "GET", "/admin/v1/credentials/codex", None).await;
+    assert_eq!(status, StatusCode::OK);
+    let creds = body["credentials"].as_array().expect("credentials array");
+    assert_eq!(creds.len(), 1);
+    assert_eq!(creds[0]["id"], id);
+    assert_eq!(creds[0]["provider"], "codex");
+    assert_eq!(creds[0]["state"], "active");
+    assert_eq!(creds[0]["account_id"], "acct-admin");
+    assert!(creds[0]["capability_mask"].is_string());
+
+    let uri = format!("/admin/v1/credentials/codex/{id}");
+    let (status, _) = send(&app, "PATCH", &uri, Some(r#"{"enabled":false}"#)).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let (_, body) = send(&app, "GET", "/admin/v1/credentials", None).await;
+    assert_eq!(body["credentials"][0]["state"], "disabled");
+    assert!(body["credentials"][0]["capability_mask"].is_null());
+
+    let (status, _) = send(&app, "PATCH", &uri, Some(r#"{"enabled":true}"#)).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let (_, body) = send(&app, "GET", "/admin/v1/credentials/codex", None).await;
+    assert_eq!(body["credentials"][0]["state"], "active");
+
+    // Per-model override: off drops the model from the live view, on restores it.
+    let model = body["credentials"][0]["models"][0]
+        .as_str()
+        .expect("model name")
+        .to_string();
+    let model_uri = format!("{uri}/models/{model}");
+    let (status, _) = send(&app, "PATCH", &model_uri, Some(r#"{"enabled":false}"#)).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let (_, body) = send(&app, "GET", "/admin/v1/credentials/codex", None).await;
+    assert!(
+        !body["credentials"][0]["models"]
+            .as_array()
+            .expect("models array")
+            .iter()
+            .any(|m| m == model.as_str())
+    );
+    let (status, _) = send(&app, "PATCH", &model_uri, Some(r#"{"enabled":true}"#)).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let (_, body) = send(&app, "GET", "/admin/v1/credentials/codex", None).await;
+    assert_eq!(body["credentials"][0]["models"][0], model.as_str());
+    let (status, _) = send(
+        &app,
+        "PATCH",
+        &format!("{uri}/models/no-such-model"),
+        Some(r#"{"enabled":true}"#),
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    // Manual cooldown: shows up in the live view until cleared.
+    let cooldown_uri = format!("/admin/v1/codex/credentials/{id}/cooldown");
+    let body = format!(r#"{{"model":"{model}","secs":600}}"#);
+    let (status, _) = send(&app, "POST", &cooldown_uri, Some(&body)).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let (_, body) = send(&app, "GET", "/admin/v1/credentials/codex", None).await;
+    let cooldowns = &body["credentials"][0]["cooldowns"];
+    assert_eq!(cooldowns[0]["model"], model.as_str(), "{cooldowns}");
+    assert!(
+        cooldowns[0]["remaining_secs"]
+            .as_u64()
+            .is_some_and(|s| s > 590)
+    );
+    let (status, _) = send(&app, "DELETE", &cooldown_uri, None).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let (_, body) = send(&app, "GET", "/admin/v1/credentials/codex", None).await;
+    assert_eq!(
+        body["credentials"][0]["cooldowns"],
+        Value::Array(Vec::new())
+    );
+    let (status, _) = send(&app, "POST", &cooldown_uri, Some(r#"{"secs":0}"#)).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+
+    // Capability mask: an empty set takes the credential off every model.
+    let caps_uri = format!("/admin/v1/codex/credentials/{id}/capabilities");
+    let (status, _) = send(&app, "PUT", &caps_uri, Some(r#"{"models":[]}"#)).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let (_, body) = send(&app, "GET", "/admin/v1/credentials/codex", None).await;
+    assert_eq!(body["credentials"][0]["models"], Value::Array(Vec::new()));
+    let body = format!(r#"{{"models":["{model}"]}}"#);
+    let (status, _) = send(&app, "PUT", &caps_uri, Some(&body)).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let (_, body) = send(&app, "GET", "/admin/v1/credentials/codex", None).await;
+    assert_eq!(body["credentials"][0]["models"][0], model.as_str());
+
+    // Dashboard snapshot: one entry per provider, codex has the loaded credential.
+    let (status, body) = send(&app, "GET", "/admin/v1/status", None).await;
+    assert_eq!(status, StatusCode::OK);
+    let codex = body["providers"]
+        .as_array()
+        .expect("providers array")
+        .iter()
+        .find(|p| p["provider"] == "codex")
+        .expect("codex status");
+    assert_eq!(codex["loaded"], 1);
+    assert_eq!(codex["credentials"]["active"], 1);
+    assert!(codex["models"].as_array().is_some_and(|m| !m.is_empty()));
+
+    let (status, _) = send(&app, "DELETE", &uri, None).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let (status, body) = send(&app, "DELETE", &uri, None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(body["error"]["code"], "NOT_FOUND");
+    let (status, _) = send(&app, "PATCH", &uri, Some(r#"{"enabled":true}"#)).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (status, _) = send(&app, "GET", "/admin/v1/credentials/nope", None).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let _ = std::fs::remove_file(temp_path);
+}
@@ -0,0 +1,76 @@
+use axum::{Json, Router, extract::State, routing::post};
+use pollux::config::{AlertFormat, AlertsConfig};
+use pollux::providers::manifest::ProviderKind;
+use pollux::server::alerts::{AlertEvent, alert};
+use serde_json::{Value, json};
+use std::time::Duration;
+use tokio::net::TcpListener;
+use tokio::sync::mpsc;
+use url::Url;
+
+async fn next_body(rx: &mut mpsc::UnboundedReceiver<Value>) -> Value {
+    tokio::time::timeout(Duration::from_secs(5), rx.recv())
+        .await
+        .expect("webhook called")
+        .expect("receiver open")
+}
+
+#[tokio::test]
+async fn pool_events_are_posted_to_the_webhook_once_per_interval() {
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let app = Router::new()
+        .route(
+            "/hook",
+            post(
+                |State(tx): State<mpsc::UnboundedSender<Value>>, Json(body): Json<Value>| async move {
+                    let _ = tx.send(body);
+                },
+            ),
+        )
+        .with_state(tx);
+    let listener = TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("bind listener");
+    let addr = listener.local_addr().expect("local addr");
+    tokio::spawn(async move {
+        axum::serve(listener, app).await.expect("server run");
+    });
+
+    // Before the actor runs, alerts go nowhere.
+    alert(AlertEvent::DatabaseError {
+        error: "dropped".to_string(),
+    });
+
+    let cfg = AlertsConfig {
+        webhook_url: Some(Url::parse(&format!("http://{addr}/hook")).unwrap()),
+        format: AlertFormat::Slack,
+        ..AlertsConfig::default()
+    };
+    pollux::server::alerts::spawn(&cfg).await;
+
+    let banned = AlertEvent::CredentialBanned {
+        provider: ProviderKind::Codex,
+        id: 3,
+        account: "ops@example.com".to_string(),
+    };
+    alert(banned.clone());
+    alert(banned);
+    alert(AlertEvent::PoolEmpty {
+        provider: ProviderKind::Codex,
+        model: "gpt-5".to_string(),
+    });
+
+    assert_eq!(
+        next_body(&mut rx).await,
+        json!({ "text": "[pollux] codex credential 3 (ops@example.com) banned" })
+    );
+    assert_eq!(
+        next_body(&mut rx).await,
+        json!({ "text": "[pollux] codex has no credential available for gpt-5" })
+    );
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    assert!(
+        rx.try_recv().is_err(),
+        "repeat and pre-start alerts are not sent"
+    );
+}
@@ -34,7 +34,7 @@ async fn antigravity_actor_get_credential_returns_none_with_empty_db() {
         pollux::model_catalog::mask("gemini-2.5-pro").expect("model present in registry");
     let lease = providers
         .antigravity
-        .get_credential(model_mask)
+        .get_credential(model_mask, None, None)
         .await
         .expect("GetCredential should not error");
 
@@ -163,5 +163,40 @@ async fn antigravity_oauth_routes_set_cookies_and_return_explicit_flow_errors()
     let body_str = std::str::from_utf8(&body).expect("response body was not utf-8");
     assert!(body_str.contains("\"code\":\"CSRF_MISMATCH\""));
 
+    // 4) The login alias starts the same flow.
+    let resp = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("GET")
+                .uri("/antigravity/oauth/login")
+                .body(Body::empty())
+                .expect("failed to build request"),
+        )
+        .await
+        .expect("request failed");
+    assert!(resp.status().is_redirection());
+
+    // 5) Declined consent in a browser gets a readable page instead of a 404.
+    let resp = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("GET")
+                .uri("/?error=access_denied&state=some_state")
+                .header(header::ACCEPT, "text/html")
+                .body(Body::empty())
+                .expect("failed to build request"),
+        )
+        .await
+        .expect("request failed");
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    let body = to_bytes(resp.into_body(), usize::MAX)
+        .await
+        .expect("failed to read response body");
+    let body_str = std::str::from_utf8(&body).expect("response body was not utf-8");
+    assert!(body_str.contains("<h1>Authorization was not completed</h1>"));
+    assert!(body_str.contains("/antigravity/oauth/login"));
+
     let _ = fs::remove_file(&temp_path);
 }
@@ -12,7 +12,9 @@ use axum::{
     routing::post,
 };
 use base64::Engine as _;
-use pollux::config::AntigravityResolvedConfig;
+use pollux::config::{
+    AntigravityResolvedConfig, HttpClientConfig, ModelAliases, SchedulingPolicy, ThoughtSigConfig,
+};
 use pollux::providers::antigravity::client::oauth::{
     endpoints::AntigravityOauthEndpoints, ops::AntigravityOauthOps,
 };
@@ -54,16 +56,40 @@ fn make_cfg(token_url: Url, api_url: Url) -> AntigravityResolvedConfig {
     AntigravityResolvedConfig {
         api_url,
         proxy: None,
+        proxy_pool: Vec::new(),
         oauth_tps: 5,
         model_list: vec!["gemini-2.5-pro".to_string()],
+        stream_only_models: Vec::new(),
+        model_aliases: ModelAliases::default(),
         enable_multiplexing: true,
+        http_client: HttpClientConfig::default(),
         retry_max_times: 3,
+        auto_disable: None,
+        health: None,
+        capability_probe: None,
+        system_instruction: None,
+        model_overrides: HashMap::new(),
+        tier_weights: HashMap::new(),
+        daily_quota: None,
+        min_token_validity_secs: 300,
+        stale_grace_secs: 0,
+        capability_restore_secs: 0,
+        max_concurrent_per_credential: None,
+        lease_wait_ms: 0,
+        scheduling_policy: SchedulingPolicy::RoundRobin,
+        priority_reserve: 0.0,
+        stream_resume_max_times: 0,
+        max_inline_data_bytes: 0,
+        response_cache: None,
         oauth_auth_url: Url::parse("http://oauth.test/authorize").unwrap(),
         oauth_token_url: token_url,
         oauth_redirect_url: Url::parse("http://localhost:8188").unwrap(),
         oauth_client_id: "client-id".to_string(),
         oauth_client_secret: "client-secret".to_string(),
         oauth_scopes: vec!["openid".to_string()],
+        stream_transformers: Vec::new(),
+        thoughtsig: ThoughtSigConfig::default(),
+        model_discovery: None,
     }
 }
 
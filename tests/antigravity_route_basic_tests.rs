@@ -98,6 +98,7 @@ async fn antigravity_route_requires_key_rejects_bad_json_and_maps_no_credentials
                 .uri(&uri)
                 .header("content-type", "application/json")
                 .header("x-goog-api-key", pollux_key.as_ref())
+                .header("x-request-id", "antigravity-no-credential")
                 .body(Body::from(valid_body))
                 .expect("failed to build request"),
         )
@@ -111,8 +112,54 @@ async fn antigravity_route_requires_key_rejects_bad_json_and_maps_no_credentials
     let body_str = std::str::from_utf8(&body).expect("response body was not utf-8");
     assert_eq!(
         body_str,
-        r#"{"error":{"code":503,"message":"No available credentials to process the request.","status":"UNAVAILABLE"}}"#
+        r#"{"error":{"code":503,"message":"No available credentials to process the request.","request_id":"antigravity-no-credential","status":"UNAVAILABLE"}}"#
     );
 
+    // 4) countTokens accepts the wrapped `generateContentRequest` form and
+    // leases a credential like a generate call.
+    let resp = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri(format!("/antigravity/v1beta/models/{}:countTokens", model))
+                .header("content-type", "application/json")
+                .header("x-goog-api-key", pollux_key.as_ref())
+                .body(Body::from(format!(
+                    r#"{{"generateContentRequest":{{"model":"models/{}","contents":[{{"role":"user","parts":[{{"text":"hi"}}]}}]}}}}"#,
+                    model
+                )))
+                .expect("failed to build request"),
+        )
+        .await
+        .expect("request failed");
+    assert_eq!(resp.status(), StatusCode::SERVICE_UNAVAILABLE);
+
+    // 5) Anthropic count_tokens (authenticated with `x-api-key`) falls back to
+    // a local estimate instead of failing when upstream cannot count.
+    let resp = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/antigravity/v1/messages/count_tokens")
+                .header("content-type", "application/json")
+                .header("x-api-key", pollux_key.as_ref())
+                .body(Body::from(format!(
+                    r#"{{"model":"{}","system":"Be brief.","messages":[{{"role":"user","content":"hello there"}}]}}"#,
+                    model
+                )))
+                .expect("failed to build request"),
+        )
+        .await
+        .expect("request failed");
+    assert_eq!(resp.status(), StatusCode::OK);
+    let body = to_bytes(resp.into_body(), usize::MAX)
+        .await
+        .expect("failed to read response body");
+    let body: serde_json::Value = serde_json::from_slice(&body).expect("json body");
+    // 20 characters at about four per token.
+    assert_eq!(body["input_tokens"], 5);
+
     let _ = fs::remove_file(&temp_path);
 }
@@ -0,0 +1,204 @@
+use axum::{
+    Router,
+    body::Body,
+    http::{Request, StatusCode},
+};
+use pollux::config::{ApiKeyConfig, RequestPolicyConfig};
+use std::{
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+use tower::ServiceExt;
+
+async fn get(app: &Router, uri: &str, key: &str) -> StatusCode {
+    app.clone()
+        .oneshot(
+            Request::builder()
+                .uri(uri)
+                .header("x-goog-api-key", key)
+                .body(Body::empty())
+                .expect("failed to build request"),
+        )
+        .await
+        .expect("request failed")
+        .status()
+}
+
+/// AiStudio-style auth: the key rides in `?key=` instead of a header.
+async fn get_with_query_key(app: &Router, uri: &str, key: &str) -> StatusCode {
+    app.clone()
+        .oneshot(
+            Request::builder()
+                .uri(format!("{uri}?key={key}"))
+                .body(Body::empty())
+                .expect("failed to build request"),
+        )
+        .await
+        .expect("request failed")
+        .status()
+}
+
+async fn send(app: &Router, method: &str, uri: &str, key: &str) -> StatusCode {
+    app.clone()
+        .oneshot(
+            Request::builder()
+                .method(method)
+                .uri(uri)
+                .header("x-goog-api-key", key)
+                .header("content-type", "application/json")
+                .body(Body::from("{}"))
+                .expect("failed to build request"),
+        )
+        .await
+        .expect("request failed")
+        .status()
+}
+
+async fn post(app: &Router, uri: &str, key: &str, body: &str) -> StatusCode {
+    app.clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri(uri)
+                .header("x-goog-api-key", key)
+                .header("content-type", "application/json")
+                .body(Body::from(body.to_string()))
+                .expect("failed to build request"),
+        )
+        .await
+        .expect("request failed")
+        .status()
+}
+
+#[tokio::test]
+async fn scoped_keys_are_limited_to_their_routes() {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("system time before UNIX_EPOCH")
+        .as_nanos();
+    let mut temp_path = std::env::temp_dir();
+    temp_path.push(format!(
+        "pollux-key-scope-{}-{}.sqlite",
+        std::process::id(),
+        nanos
+    ));
+    let database_url = format!("sqlite:{}", temp_path.display());
+    let db = pollux::db::spawn(&database_url).await;
+
+    let mut cfg = pollux::config::Config::default();
+    cfg.basic.pollux_key = "pwd".to_string();
+    let providers = pollux::providers::Providers::spawn(db, &cfg).await;
+    let state = pollux::server::router::PolluxState::new(
+        providers,
+        Arc::from("pwd"),
+        cfg.basic.insecure_cookie,
+    )
+    .with_api_keys(vec![
+        ApiKeyConfig {
+            name: "codex-only".to_string(),
+            key: "codex-key".to_string(),
+            routes: vec!["/codex/*".to_string()],
+            rate_limit: None,
+            policy: Some(RequestPolicyConfig {
+                forbid_store: true,
+                ..RequestPolicyConfig::default()
+            }),
+            priority: None,
+        },
+        ApiKeyConfig {
+            name: "gemini-models".to_string(),
+            key: "gemini-key".to_string(),
+            routes: vec!["/geminicli/v1beta/models".to_string()],
+            rate_limit: None,
+            policy: None,
+            priority: None,
+        },
+        ApiKeyConfig {
+            name: "everything".to_string(),
+            key: "wide-key".to_string(),
+            routes: vec!["*".to_string(), "/*".to_string()],
+            rate_limit: None,
+            policy: None,
+            priority: None,
+        },
+    ]);
+    let app = pollux::server::router::pollux_router(state);
+
+    // Master key keeps full access.
+    assert_eq!(get(&app, "/codex/v1/models", "pwd").await, StatusCode::OK);
+    assert_eq!(
+        get(&app, "/admin/v1/credentials", "pwd").await,
+        StatusCode::OK
+    );
+
+    // Scoped key: allowed inside its scope, forbidden elsewhere.
+    assert_eq!(
+        get(&app, "/codex/v1/models", "codex-key").await,
+        StatusCode::OK
+    );
+    assert_eq!(
+        get(&app, "/admin/v1/credentials", "codex-key").await,
+        StatusCode::FORBIDDEN
+    );
+    assert_eq!(
+        get(&app, "/geminicli/v1beta/models", "codex-key").await,
+        StatusCode::FORBIDDEN
+    );
+
+    // Its policy is enforced before any credential is needed.
+    assert_eq!(
+        post(
+            &app,
+            "/codex/v1/responses",
+            "codex-key",
+            r#"{"model":"gpt-4o-mini","input":"hi","store":true}"#
+        )
+        .await,
+        StatusCode::BAD_REQUEST
+    );
+
+    // Version aliases and `?key=` auth, checked against the `v1beta` scope.
+    for uri in [
+        "/geminicli/v1beta/models",
+        "/geminicli/v1alpha/models",
+        "/geminicli/v1/models",
+    ] {
+        assert_eq!(
+            get_with_query_key(&app, uri, "gemini-key").await,
+            StatusCode::OK,
+            "{uri}"
+        );
+    }
+    assert_eq!(
+        get_with_query_key(&app, "/codex/v1/models", "gemini-key").await,
+        StatusCode::FORBIDDEN
+    );
+
+    assert_eq!(
+        get(&app, "/codex/v1/models", "unknown").await,
+        StatusCode::UNAUTHORIZED
+    );
+
+    // Wildcards reach every provider route but never the admin API.
+    assert_eq!(
+        get(&app, "/codex/v1/models", "wide-key").await,
+        StatusCode::OK
+    );
+    for (method, uri) in [
+        ("GET", "/admin/v1/credentials"),
+        ("DELETE", "/admin/v1/credentials/codex/1"),
+        ("PUT", "/admin/v1/loglevel"),
+    ] {
+        assert_eq!(
+            send(&app, method, uri, "wide-key").await,
+            StatusCode::FORBIDDEN,
+            "{method} {uri}"
+        );
+    }
+    assert_eq!(
+        get(&app, "/admin/v1/credentials", "unknown").await,
+        StatusCode::UNAUTHORIZED
+    );
+
+    let _ = std::fs::remove_file(temp_path);
+}
@@ -0,0 +1,77 @@
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+};
+use std::{
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+use tower::ServiceExt;
+
+const KEY: &str = "pwd";
+
+#[tokio::test]
+async fn claude_requests_are_counted_and_streamed() {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("system time before UNIX_EPOCH")
+        .as_nanos();
+    let mut temp_path = std::env::temp_dir();
+    temp_path.push(format!(
+        "pollux-events-claude-{}-{}.sqlite",
+        std::process::id(),
+        nanos
+    ));
+    let database_url = format!("sqlite:{}", temp_path.display());
+    let db = pollux::db::spawn(&database_url).await;
+
+    let mut cfg = pollux::config::Config::default();
+    cfg.basic.pollux_key = KEY.to_string();
+    let model = pollux::config::CONFIG.claude().model_list[0].clone();
+    cfg.providers.claude.model_list = vec![model.clone()];
+    let providers = pollux::providers::Providers::spawn(db, &cfg).await;
+    let state = pollux::server::router::PolluxState::new(
+        providers,
+        Arc::from(KEY),
+        cfg.basic.insecure_cookie,
+    );
+    let counters = state.request_counters.clone();
+    let mut events = state.request_events.subscribe();
+    let app = pollux::server::router::pollux_router(state);
+
+    // Known model, no credentials: answered 503 by the Claude route.
+    let resp = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/claude/v1/messages")
+                .header("x-api-key", KEY)
+                .header("content-type", "application/json")
+                .body(Body::from(format!(
+                    r#"{{"model":"{model}","max_tokens":16,"messages":[{{"role":"user","content":"hi"}}]}}"#
+                )))
+                .expect("failed to build request"),
+        )
+        .await
+        .expect("request failed");
+    assert_eq!(resp.status(), StatusCode::SERVICE_UNAVAILABLE);
+
+    let event = events.try_recv().expect("event published");
+    assert_eq!(event.provider, Some("claude"));
+    assert_eq!(event.model.as_deref(), Some(model.as_str()));
+    let claude_only = pollux::server::request_events::RequestEventFilter {
+        provider: Some("claude".to_string()),
+        ..Default::default()
+    };
+    assert!(claude_only.matches(&event));
+
+    let rows = counters.snapshot();
+    assert_eq!(rows.len(), 1);
+    assert_eq!(
+        (rows[0].provider.as_str(), rows[0].model.as_str()),
+        ("claude", model.as_str())
+    );
+    assert_eq!((rows[0].requests, rows[0].errors), (1, 1));
+
+    let _ = std::fs::remove_file(temp_path);
+}
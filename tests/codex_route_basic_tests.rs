@@ -43,6 +43,7 @@ async fn codex_response_route_rejects_bad_requests_and_requires_key() {
         .cloned()
         .unwrap_or_else(|| "gpt-4o-mini".to_string());
     cfg.providers.codex.model_list = vec![model.clone()];
+    cfg.providers.codex.model_aliases = [("legacy-alias", model.as_str())].into_iter().collect();
 
     // No Codex keys inserted => valid requests should yield 503 (NO_CREDENTIAL).
     let providers = pollux::providers::Providers::spawn(db.clone(), &cfg).await;
@@ -110,6 +111,7 @@ async fn codex_response_route_rejects_bad_requests_and_requires_key() {
                 .uri("/codex/v1/responses")
                 .header("content-type", "application/json")
                 .header("x-goog-api-key", pollux_key.as_ref())
+                .header("x-request-id", "codex-no-credential")
                 .body(Body::from(format!(r#"{{ "model": "{model}", "foo": 1 }}"#)))
                 .expect("failed to build request"),
         )
@@ -123,9 +125,25 @@ async fn codex_response_route_rejects_bad_requests_and_requires_key() {
     let body_str = std::str::from_utf8(&body).expect("response body was not utf-8");
     assert_eq!(
         body_str,
-        r#"{"error":{"code":"NO_CREDENTIAL","message":"No available credentials to process the request.","type":"NO_CREDENTIAL"}}"#
+        r#"{"error":{"code":"NO_CREDENTIAL","message":"No available credentials to process the request.","request_id":"codex-no-credential","type":"NO_CREDENTIAL"}}"#
     );
 
+    // 4b) aliased model name resolves to the configured model -> 503, not 400
+    let resp = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/codex/v1/responses")
+                .header("content-type", "application/json")
+                .header("x-goog-api-key", pollux_key.as_ref())
+                .body(Body::from(r#"{"model":"legacy-alias"}"#))
+                .expect("failed to build request"),
+        )
+        .await
+        .expect("request failed");
+    assert_eq!(resp.status(), StatusCode::SERVICE_UNAVAILABLE);
+
     // 5) correct key + 30 MiB JSON body -> 503 (Codex endpoint limit is higher than 30 MiB)
     let oversized_input = "a".repeat(30 * 1024 * 1024 + 1024);
     let oversized_payload = format!(r#"{{"model":"{model}","input":"{oversized_input}"}}"#);
@@ -137,6 +155,7 @@ async fn codex_response_route_rejects_bad_requests_and_requires_key() {
                 .uri("/codex/v1/responses")
                 .header("content-type", "application/json")
                 .header("x-goog-api-key", pollux_key.as_ref())
+                .header("x-request-id", "codex-no-credential")
                 .body(Body::from(oversized_payload))
                 .expect("failed to build request"),
         )
@@ -150,7 +169,7 @@ async fn codex_response_route_rejects_bad_requests_and_requires_key() {
     let body_str = std::str::from_utf8(&body).expect("response body was not utf-8");
     assert_eq!(
         body_str,
-        r#"{"error":{"code":"NO_CREDENTIAL","message":"No available credentials to process the request.","type":"NO_CREDENTIAL"}}"#
+        r#"{"error":{"code":"NO_CREDENTIAL","message":"No available credentials to process the request.","request_id":"codex-no-credential","type":"NO_CREDENTIAL"}}"#
     );
 
     // 6) GET /codex/v1/models: no key -> 401
@@ -42,10 +42,13 @@ async fn test_antigravity_db_actor_baseline() {
     let create_data = AntigravityCreate {
         email: email.clone(),
         project_id: project_id.clone(),
+        tier: None,
         sub: Some(sub.clone()),
         refresh_token: refresh_token.clone(),
         access_token: access_token.clone(),
         expiry,
+        labels: Vec::new(),
+        proxy_url: None,
     };
     let provider_create = ProviderCreate::Antigravity(create_data);
 
@@ -5,7 +5,7 @@
     clippy::map_unwrap_or,
     clippy::manual_is_variant_and
 )]
-use pollux::db::{CodexCreate, CodexPatch, ProviderCreate, ProviderPatch};
+use pollux::db::{CodexCreate, CodexPatch, ProviderCreate, ProviderIdentity, ProviderPatch};
 use std::collections::hash_map::DefaultHasher;
 use std::hash::{Hash, Hasher};
 use std::time::SystemTime;
@@ -47,6 +47,8 @@ async fn test_codex_db_actor_baseline() {
         access_token: access_token.clone(),
         expiry,
         chatgpt_plan_type: chatgpt_plan_type.clone(),
+        labels: Vec::new(),
+        proxy_url: None,
     };
     let provider_create = ProviderCreate::Codex(create_data);
 
@@ -94,6 +96,24 @@ async fn test_codex_db_actor_baseline() {
         "Expected no active Codex keys after disabling"
     );
 
+    // 7. Assert find_by_identity() still finds the disabled row, and only for its account
+    let found = db_actor_handle
+        .find_by_identity(ProviderIdentity::Codex {
+            sub: sub.clone(),
+            account_id: account_id.clone(),
+        })
+        .await
+        .unwrap();
+    assert_eq!(found, Some(id));
+    let other = db_actor_handle
+        .find_by_identity(ProviderIdentity::Codex {
+            sub,
+            account_id: "acct-other-id".to_string(),
+        })
+        .await
+        .unwrap();
+    assert_eq!(other, None);
+
     // Clean up the temporary database file
     let wal_path = std::path::PathBuf::from(format!("{}-wal", db_path.to_string_lossy()));
     let shm_path = std::path::PathBuf::from(format!("{}-shm", db_path.to_string_lossy()));
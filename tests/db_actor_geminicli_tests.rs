@@ -42,10 +42,13 @@ async fn test_geminicli_db_actor_baseline() {
     let create_data = GeminiCliCreate {
         email: email.clone(),
         project_id: project_id.clone(),
+        tier: Some("free-tier".to_string()),
         sub: sub.clone(),
         refresh_token: refresh_token.clone(),
         access_token: access_token.clone(),
         expiry,
+        labels: vec!["team-a".to_string(), "batch".to_string()],
+        proxy_url: Some("socks5h://127.0.0.1:1080".parse().unwrap()),
     };
     let provider_create = ProviderCreate::GeminiCli(create_data);
 
@@ -68,12 +71,20 @@ async fn test_geminicli_db_actor_baseline() {
     assert_eq!(credential.email, email);
     assert_eq!(credential.access_token, access_token);
     assert_eq!(credential.expiry.timestamp(), expiry.timestamp()); // Compare timestamps for equality
+    assert_eq!(credential.labels, "team-a,batch");
+    assert_eq!(credential.tier.as_deref(), Some("free-tier"));
+    assert_eq!(
+        credential.proxy_url.as_deref(),
+        Some("socks5h://127.0.0.1:1080")
+    );
     assert!(credential.status);
 
-    // 4. Patch access_token while status remains active
+    // 4. Patch access_token, labels and tier while status remains active
     let new_token = "new_token".to_string();
     let patch = GeminiCliPatch {
         access_token: Some(new_token.clone()),
+        labels: Some(vec!["team-b".to_string()]),
+        tier: Some("standard-tier".to_string()),
         ..Default::default()
     };
     db_actor_handle
@@ -88,6 +99,8 @@ async fn test_geminicli_db_actor_baseline() {
     let active = db_actor_handle.list_active_geminicli().await.unwrap();
     assert_eq!(active.len(), 1);
     assert_eq!(active[0].access_token, Some(new_token));
+    assert_eq!(active[0].labels, "team-b");
+    assert_eq!(active[0].tier.as_deref(), Some("standard-tier"));
 
     // 5. Patch status=false
     let patch_inactive = GeminiCliPatch {
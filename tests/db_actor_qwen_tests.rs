@@ -0,0 +1,70 @@
+use chrono::{Duration, Utc};
+use pollux::db::{ProviderCreate, ProviderIdentity, QwenCreate};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::fs;
+
+#[tokio::test]
+async fn same_qwen_credential_submitted_twice_is_one_row() {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("system time before UNIX_EPOCH")
+        .as_nanos();
+    let db_path = std::env::temp_dir().join(format!(
+        "pollux-qwen-db-{}-{}.sqlite",
+        std::process::id(),
+        nanos
+    ));
+    let database_url = format!("sqlite:{}", db_path.display());
+    let db = pollux::db::spawn(&database_url).await;
+
+    let create = |access_token: &str, resource_url: Option<&str>| {
+        ProviderCreate::Qwen(QwenCreate {
+            refresh_token: "qwen-rt-1".to_string(),
+            access_token: access_token.to_string(),
+            resource_url: resource_url.map(ToString::to_string),
+            expiry: Utc::now() + Duration::hours(1),
+            labels: Vec::new(),
+            proxy_url: None,
+        })
+    };
+
+    let first = db
+        .create(create("at-1", Some("portal.qwen.ai")))
+        .await
+        .unwrap();
+    let second = db.create(create("at-2", None)).await.unwrap();
+    assert_eq!(first, second);
+
+    let rows = db.list_qwen().await.unwrap();
+    assert_eq!(rows.len(), 1);
+    assert_eq!(rows[0].access_token, "at-2");
+    assert_eq!(rows[0].resource_url.as_deref(), Some("portal.qwen.ai"));
+
+    let found = db
+        .find_by_identity(ProviderIdentity::Qwen {
+            sub: rows[0].sub.clone(),
+        })
+        .await
+        .unwrap();
+    assert_eq!(found, Some(first));
+
+    // A different refresh token is a different credential.
+    let other = db
+        .create(ProviderCreate::Qwen(QwenCreate {
+            refresh_token: "qwen-rt-2".to_string(),
+            access_token: "at-3".to_string(),
+            resource_url: None,
+            expiry: Utc::now() + Duration::hours(1),
+            labels: Vec::new(),
+            proxy_url: None,
+        }))
+        .await
+        .unwrap();
+    assert_ne!(other, first);
+    assert_eq!(db.list_qwen().await.unwrap().len(), 2);
+
+    for suffix in ["-wal", "-shm"] {
+        let _ = fs::remove_file(format!("{}{suffix}", db_path.display())).await;
+    }
+    let _ = fs::remove_file(&db_path).await;
+}
@@ -0,0 +1,67 @@
+use pollux::db::{DbPool, GeminiCliPatch, ProviderPatch, TokenCipher};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[tokio::test]
+async fn plaintext_tokens_are_encrypted_at_startup_and_read_back_transparently() {
+    // NOTE: `pollux::db::spawn*()` registers a singleton ractor actor by name
+    // within a process. Keep this test file to a single test.
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("system time before UNIX_EPOCH")
+        .as_nanos();
+    let mut temp_path = std::env::temp_dir();
+    temp_path.push(format!(
+        "pollux-db-encryption-{}-{}.sqlite",
+        std::process::id(),
+        nanos
+    ));
+    let database_url = format!("sqlite:{}", temp_path.display());
+
+    // A row written before encryption was configured.
+    let DbPool::Sqlite(raw) = DbPool::connect(&database_url).await.unwrap() else {
+        unreachable!("sqlite url");
+    };
+    let now = chrono::Utc::now();
+    sqlx::query(
+        "INSERT INTO gemini_cli (email, sub, project_id, refresh_token, access_token, expiry, status, created_at, updated_at)
+         VALUES (NULL, 'sub-1', 'proj-1', 'rt-plain', 'at-plain', $1, TRUE, $1, $1)",
+    )
+    .bind(now)
+    .execute(&raw)
+    .await
+    .unwrap();
+
+    let db = pollux::db::spawn_with_cipher(&database_url, Some(TokenCipher::new("at-rest-secret")))
+        .await;
+
+    let stored: (String, String) =
+        sqlx::query_as("SELECT refresh_token, access_token FROM gemini_cli")
+            .fetch_one(&raw)
+            .await
+            .unwrap();
+    assert!(TokenCipher::is_sealed(&stored.0), "{stored:?}");
+    assert!(TokenCipher::is_sealed(&stored.1), "{stored:?}");
+
+    let rows = db.list_geminicli().await.unwrap();
+    assert_eq!(rows[0].refresh_token, "rt-plain");
+    assert_eq!(rows[0].access_token.as_deref(), Some("at-plain"));
+
+    db.patch(ProviderPatch::GeminiCli {
+        id: rows[0].id.cast_unsigned(),
+        patch: GeminiCliPatch {
+            access_token: Some("at-refreshed".to_string()),
+            ..GeminiCliPatch::default()
+        },
+    })
+    .await
+    .unwrap();
+    let (access,): (String,) = sqlx::query_as("SELECT access_token FROM gemini_cli")
+        .fetch_one(&raw)
+        .await
+        .unwrap();
+    assert!(TokenCipher::is_sealed(&access));
+    let row = db.get_geminicli_by_id(rows[0].id).await.unwrap();
+    assert_eq!(row.access_token.as_deref(), Some("at-refreshed"));
+
+    let _ = tokio::fs::remove_file(&temp_path).await;
+}
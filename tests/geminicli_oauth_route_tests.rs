@@ -0,0 +1,87 @@
+use axum::{
+    body::{Body, to_bytes},
+    http::{Request, StatusCode, header},
+};
+use std::{
+    fs,
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+use tower::ServiceExt;
+
+async fn get(app: &axum::Router, uri: &str) -> axum::response::Response {
+    app.clone()
+        .oneshot(
+            Request::builder()
+                .method("GET")
+                .uri(uri)
+                .header(header::ACCEPT, "text/html,application/xhtml+xml")
+                .body(Body::empty())
+                .expect("failed to build request"),
+        )
+        .await
+        .expect("request failed")
+}
+
+async fn body_text(resp: axum::response::Response) -> String {
+    let body = to_bytes(resp.into_body(), usize::MAX)
+        .await
+        .expect("failed to read response body");
+    String::from_utf8(body.to_vec()).expect("response body was not utf-8")
+}
+
+#[tokio::test]
+async fn geminicli_login_redirects_and_callback_renders_result_pages() {
+    // `pollux::db::spawn()` registers a singleton actor; keep everything in one test.
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("system time before UNIX_EPOCH")
+        .as_nanos();
+    let mut temp_path = std::env::temp_dir();
+    temp_path.push(format!(
+        "pollux-geminicli-oauth-{}-{nanos}.sqlite",
+        std::process::id()
+    ));
+    let db = pollux::db::spawn(&format!("sqlite:{}", temp_path.display())).await;
+
+    let mut cfg = pollux::config::Config::default();
+    cfg.basic.pollux_key = "pwd".to_string();
+    let providers = pollux::providers::Providers::spawn(db.clone(), &cfg).await;
+    let pollux_key: Arc<str> = Arc::from(cfg.basic.pollux_key.clone());
+    let state =
+        pollux::server::router::PolluxState::new(providers, pollux_key, cfg.basic.insecure_cookie);
+    let app = pollux::server::router::pollux_router(state);
+
+    // 1) The login page sends the browser to Google and sets the flow cookies.
+    let resp = get(&app, "/geminicli/oauth/login").await;
+    assert!(resp.status().is_redirection());
+    let location = resp
+        .headers()
+        .get(header::LOCATION)
+        .and_then(|v| v.to_str().ok())
+        .expect("missing location header");
+    assert!(location.starts_with("https://accounts.google.com/"));
+    assert!(location.contains("access_type=offline"));
+    assert!(
+        resp.headers()
+            .get_all(header::SET_COOKIE)
+            .iter()
+            .any(|c| c.to_str().unwrap_or("").starts_with("oauth_csrf_token="))
+    );
+
+    // 2) Declined consent comes back without a code.
+    let resp = get(&app, "/oauth2callback?error=access_denied").await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    let page = body_text(resp).await;
+    assert!(page.contains("<h1>Authorization was not completed</h1>"));
+    assert!(page.contains("access_denied"));
+
+    // 3) A code without the flow cookies is rejected with a readable page.
+    let resp = get(&app, "/oauth2callback?code=fake&state=%3Cb%3Ex").await;
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+    let page = body_text(resp).await;
+    assert!(page.contains("<h1>Adding the account failed</h1>"));
+    assert!(page.contains("Missing OAuth session cookies"));
+
+    let _ = fs::remove_file(&temp_path);
+}
@@ -81,5 +81,47 @@ async fn geminicli_response_route_returns_413_for_oversized_body() {
     assert!(body_str.contains(r#""status":"PAYLOAD_TOO_LARGE""#));
     assert!(body_str.contains(r#""message":"request body too large""#));
 
+    // Generation models are not scheduled for the embedding RPCs.
+    let resp = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri(format!("/geminicli/v1beta/models/{model}:embedContent"))
+                .header("content-type", "application/json")
+                .header("x-goog-api-key", pollux_key.as_ref())
+                .body(Body::from(r#"{"content":{"parts":[{"text":"hi"}]}}"#))
+                .expect("failed to build request"),
+        )
+        .await
+        .expect("request failed");
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    let body = to_bytes(resp.into_body(), usize::MAX)
+        .await
+        .expect("failed to read response body");
+    let body_str = std::str::from_utf8(&body).expect("response body was not utf-8");
+    assert!(body_str.contains("unsupported model"));
+
+    // Nor for Imagen's predict RPC.
+    let resp = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri(format!("/geminicli/v1beta/models/{model}:predict"))
+                .header("content-type", "application/json")
+                .header("x-goog-api-key", pollux_key.as_ref())
+                .body(Body::from(r#"{"instances":[{"prompt":"a cat"}]}"#))
+                .expect("failed to build request"),
+        )
+        .await
+        .expect("request failed");
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    let body = to_bytes(resp.into_body(), usize::MAX)
+        .await
+        .expect("failed to read response body");
+    let body_str = std::str::from_utf8(&body).expect("response body was not utf-8");
+    assert!(body_str.contains("unsupported model"));
+
     let _ = fs::remove_file(&temp_path);
 }
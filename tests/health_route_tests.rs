@@ -0,0 +1,91 @@
+use axum::{
+    Router,
+    body::{Body, to_bytes},
+    http::{Request, StatusCode},
+};
+use pollux::db::{CodexCreate, ProviderCreate};
+use serde_json::Value;
+use std::{
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+use tower::ServiceExt;
+
+async fn get(app: &Router, uri: &str) -> (StatusCode, Vec<u8>) {
+    let req = Request::builder()
+        .uri(uri)
+        .body(Body::empty())
+        .expect("failed to build request");
+    let resp = app.clone().oneshot(req).await.expect("request failed");
+    let status = resp.status();
+    let bytes = to_bytes(resp.into_body(), usize::MAX)
+        .await
+        .expect("read body");
+    (status, bytes.to_vec())
+}
+
+#[tokio::test]
+async fn readyz_reports_providers_without_usable_credentials() {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("system time before UNIX_EPOCH")
+        .as_nanos();
+    let mut temp_path = std::env::temp_dir();
+    temp_path.push(format!(
+        "pollux-health-{}-{}.sqlite",
+        std::process::id(),
+        nanos
+    ));
+    let database_url = format!("sqlite:{}", temp_path.display());
+    let db = pollux::db::spawn(&database_url).await;
+    db.create(ProviderCreate::Codex(CodexCreate {
+        email: None,
+        sub: "auth0|health".to_string(),
+        account_id: "acct-health".to_string(),
+        refresh_token: "rt-health".to_string(),
+        access_token: "at-health".to_string(),
+        expiry: chrono::Utc::now() + chrono::Duration::hours(1),
+        chatgpt_plan_type: None,
+        labels: Vec::new(),
+        proxy_url: None,
+    }))
+    .await
+    .expect("create codex row");
+
+    let mut cfg = pollux::config::Config::default();
+    cfg.basic.pollux_key = "pwd".to_string();
+    let providers = pollux::providers::Providers::spawn(db, &cfg).await;
+    let state = pollux::server::router::PolluxState::new(
+        providers,
+        Arc::from("pwd"),
+        cfg.basic.insecure_cookie,
+    );
+    let app = pollux::server::router::pollux_router(state);
+
+    // Both probes are reachable without a key.
+    let (status, body) = get(&app, "/healthz").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body, b"ok");
+
+    let (status, body) = get(&app, "/readyz").await;
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+    let body: Value = serde_json::from_slice(&body).expect("json body");
+    assert_eq!(body["ready"], false);
+    let providers = body["providers"].as_array().expect("providers array");
+    assert_eq!(providers.len(), 5);
+    let codex = providers
+        .iter()
+        .find(|p| p["provider"] == "codex")
+        .expect("codex entry");
+    assert_eq!(codex["ready"], true);
+    assert_eq!(codex["active"], 1);
+    assert_eq!(codex["usable"], 1);
+    let geminicli = providers
+        .iter()
+        .find(|p| p["provider"] == "gemini_cli")
+        .expect("geminicli entry");
+    assert_eq!(geminicli["ready"], false);
+    assert_eq!(geminicli["usable"], 0);
+
+    let _ = std::fs::remove_file(&temp_path);
+}
@@ -0,0 +1,112 @@
+use axum::{
+    Router,
+    body::Body,
+    http::{Request, StatusCode, header::RETRY_AFTER},
+    response::Response,
+};
+use pollux::config::{ApiKeyConfig, RateLimitConfig};
+use pollux::server::guards::rate_limit::KeyRateLimits;
+use serde_json::Value;
+use std::{
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+use tower::ServiceExt;
+
+async fn get(app: &Router, uri: &str, key: &str) -> Response {
+    app.clone()
+        .oneshot(
+            Request::builder()
+                .uri(uri)
+                .header("x-goog-api-key", key)
+                .body(Body::empty())
+                .expect("failed to build request"),
+        )
+        .await
+        .expect("request failed")
+}
+
+async fn json_body(resp: Response) -> Value {
+    let bytes = axum::body::to_bytes(resp.into_body(), usize::MAX)
+        .await
+        .expect("failed to read body");
+    serde_json::from_slice(&bytes).expect("body is not JSON")
+}
+
+#[tokio::test]
+async fn over_limit_keys_get_provider_shaped_429s() {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("system time before UNIX_EPOCH")
+        .as_nanos();
+    let mut temp_path = std::env::temp_dir();
+    temp_path.push(format!(
+        "pollux-rate-limit-{}-{}.sqlite",
+        std::process::id(),
+        nanos
+    ));
+    let database_url = format!("sqlite:{}", temp_path.display());
+    let db = pollux::db::spawn(&database_url).await;
+
+    let mut cfg = pollux::config::Config::default();
+    cfg.basic.pollux_key = "pwd".to_string();
+    cfg.basic.rate_limit = Some(RateLimitConfig {
+        rps: Some(1),
+        tpm: None,
+    });
+    cfg.basic.api_keys = vec![ApiKeyConfig {
+        name: "unlimited".to_string(),
+        key: "free-key".to_string(),
+        routes: vec!["*".to_string()],
+        rate_limit: Some(RateLimitConfig::default()),
+        policy: None,
+        priority: None,
+    }];
+    let providers = pollux::providers::Providers::spawn(db, &cfg).await;
+    let state = pollux::server::router::PolluxState::new(
+        providers,
+        Arc::from("pwd"),
+        cfg.basic.insecure_cookie,
+    )
+    .with_api_keys(cfg.basic.api_keys.clone())
+    .with_rate_limits(KeyRateLimits::new(&cfg.basic));
+    let app = pollux::server::router::pollux_router(state);
+
+    assert_eq!(
+        get(&app, "/geminicli/v1beta/models", "pwd").await.status(),
+        StatusCode::OK
+    );
+
+    let gemini = get(&app, "/geminicli/v1beta/models", "pwd").await;
+    assert_eq!(gemini.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert!(gemini.headers().contains_key(RETRY_AFTER));
+    let request_id = gemini.headers()["x-pollux-request-id"]
+        .to_str()
+        .expect("request id is ASCII")
+        .to_string();
+    let body = json_body(gemini).await;
+    assert_eq!(body["error"]["status"], "RESOURCE_EXHAUSTED");
+    assert_eq!(body["error"]["code"], 429);
+    assert_eq!(body["error"]["request_id"], request_id.as_str());
+
+    let codex = get(&app, "/codex/v1/models", "pwd").await;
+    assert_eq!(codex.status(), StatusCode::TOO_MANY_REQUESTS);
+    let body = json_body(codex).await;
+    assert_eq!(body["error"]["type"], "rate_limit_error");
+    assert_eq!(body["error"]["code"], "rate_limit_exceeded");
+
+    // A key with an explicit empty limit is not throttled by the default.
+    for _ in 0..3 {
+        assert_eq!(
+            get(&app, "/codex/v1/models", "free-key").await.status(),
+            StatusCode::OK
+        );
+    }
+    // Admin routes are never limited.
+    assert_eq!(
+        get(&app, "/admin/v1/credentials", "pwd").await.status(),
+        StatusCode::OK
+    );
+
+    let _ = std::fs::remove_file(temp_path);
+}
@@ -0,0 +1,331 @@
+#![allow(clippy::too_many_lines)]
+//! Drives the full router against `pollux-mock-upstream`, covering onboarding
+//! and the retry/cooldown paths behind injected upstream errors.
+
+use axum::{
+    Router,
+    body::{Body, to_bytes},
+    http::{Request, StatusCode},
+};
+use chrono::{Duration, Utc};
+use pollux::config::{Config, ModelDiscoveryConfig};
+use pollux::db::{CodexCreate, DbActorHandle, GeminiCliCreate, ProviderCreate};
+use pollux_mock_upstream::{Endpoint, Fault, MockUpstream, PROJECT_ID, REPLY_TEXT};
+use serde_json::Value;
+use std::{
+    path::PathBuf,
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+use tower::ServiceExt;
+use url::Url;
+
+const KEY: &str = "pwd";
+
+async fn temp_db() -> (DbActorHandle, PathBuf) {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("system time before UNIX_EPOCH")
+        .as_nanos();
+    let mut path = std::env::temp_dir();
+    path.push(format!(
+        "pollux-mock-upstream-{}-{nanos}.sqlite",
+        std::process::id()
+    ));
+    let db = pollux::db::spawn(&format!("sqlite:{}", path.display())).await;
+    (db, path)
+}
+
+async fn app(db: DbActorHandle, cfg: &Config) -> Router {
+    let providers = pollux::providers::Providers::spawn(db, cfg).await;
+    let state = pollux::server::router::PolluxState::new(
+        providers,
+        Arc::from(KEY),
+        cfg.basic.insecure_cookie,
+    );
+    state.spawn_model_discovery();
+    pollux::server::router::pollux_router(state)
+}
+
+async fn get_json(app: &Router, uri: &str) -> Value {
+    let resp = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri(uri)
+                .header("x-goog-api-key", KEY)
+                .body(Body::empty())
+                .expect("failed to build request"),
+        )
+        .await
+        .expect("request failed");
+    assert_eq!(resp.status(), StatusCode::OK);
+    let body = to_bytes(resp.into_body(), usize::MAX)
+        .await
+        .expect("read body");
+    serde_json::from_slice(&body).expect("json body")
+}
+
+async fn post(app: &Router, uri: &str, body: String) -> (StatusCode, String) {
+    let resp = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri(uri)
+                .header("content-type", "application/json")
+                .header("x-goog-api-key", KEY)
+                .body(Body::from(body))
+                .expect("failed to build request"),
+        )
+        .await
+        .expect("request failed");
+    let status = resp.status();
+    let body = to_bytes(resp.into_body(), usize::MAX)
+        .await
+        .expect("read body");
+    (status, String::from_utf8_lossy(&body).into_owned())
+}
+
+fn bearers(mock: &MockUpstream, endpoint: Endpoint) -> Vec<String> {
+    mock.requests(endpoint)
+        .into_iter()
+        .map(|r| r.bearer.unwrap_or_default())
+        .collect()
+}
+
+const GEMINI_BODY: &str = r#"{"contents":[{"role":"user","parts":[{"text":"hi"}]}]}"#;
+
+fn first_model(list: &[String], fallback: &str) -> String {
+    list.first()
+        .cloned()
+        .unwrap_or_else(|| fallback.to_string())
+}
+
+fn geminicli_create(n: u32) -> ProviderCreate {
+    ProviderCreate::GeminiCli(GeminiCliCreate {
+        email: Some(format!("gemini-{n}@example.com")),
+        sub: format!("gemini-sub-{n}"),
+        project_id: format!("project-{n}"),
+        tier: None,
+        refresh_token: format!("gemini-rt-{n}"),
+        access_token: Some(format!("gemini-at-{n}")),
+        expiry: Utc::now() + Duration::hours(1),
+        labels: Vec::new(),
+        proxy_url: None,
+    })
+}
+
+fn codex_create(n: u32) -> ProviderCreate {
+    ProviderCreate::Codex(CodexCreate {
+        email: Some(format!("codex-{n}@example.com")),
+        sub: format!("codex-sub-{n}"),
+        account_id: format!("acct-{n}"),
+        refresh_token: format!("codex-rt-{n}"),
+        access_token: format!("codex-at-{n}"),
+        expiry: Utc::now() + Duration::hours(1),
+        chatgpt_plan_type: Some("plus".to_string()),
+        labels: Vec::new(),
+        proxy_url: None,
+    })
+}
+
+// Actors register under global names, so every scenario shares one router.
+#[tokio::test]
+async fn router_against_mock_upstream_onboards_streams_and_handles_injected_errors() {
+    let mock = MockUpstream::spawn().await;
+    let (db, path) = temp_db().await;
+    for n in 1..=2 {
+        db.create(geminicli_create(n))
+            .await
+            .expect("seed credential");
+        db.create(codex_create(n)).await.expect("seed credential");
+    }
+
+    // Keep test behavior stable regardless of the repo's runtime `config.toml`.
+    let antigravity_model = first_model(
+        &pollux::config::CONFIG.antigravity().model_list,
+        "gemini-2.5-pro",
+    );
+    let geminicli_model = first_model(
+        &pollux::config::CONFIG.geminicli().model_list,
+        "gemini-2.5-pro",
+    );
+    let codex_model = first_model(&pollux::config::CONFIG.codex().model_list, "gpt-5");
+
+    let mut cfg = Config::default();
+    cfg.basic.pollux_key = KEY.to_string();
+    let mock_url = Url::parse(&mock.url()).unwrap();
+    // `mock-retired` is configured but never offered by upstream.
+    cfg.providers.antigravity.model_list = vec![antigravity_model.clone(), "mock-retired".into()];
+    cfg.providers.antigravity.model_discovery = Some(ModelDiscoveryConfig { interval_secs: 1 });
+    mock.set_available_models(&[antigravity_model.as_str(), "mock-unconfigured"]);
+    cfg.providers.antigravity.api_url = mock_url.clone();
+    cfg.providers.antigravity.oauth_token_url = Url::parse(&mock.token_url()).unwrap();
+    cfg.providers.geminicli.model_list = vec![geminicli_model.clone()];
+    cfg.providers.geminicli.custom_api_url = mock_url.clone();
+    cfg.providers.geminicli.thoughtsig.report_outcomes = true;
+    cfg.providers.codex.model_list = vec![codex_model.clone()];
+    cfg.providers.codex.custom_api_url = mock_url;
+    let app = app(db, &cfg).await;
+
+    // 1) Antigravity seed: refresh grant, loadCodeAssist, then onboardUser.
+    let (status, body) = post(
+        &app,
+        "/antigravity/resource:add?validate=true",
+        r#"[{"refresh_token": "rt-1"}]"#.to_string(),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    let body: Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(body["results"][0]["status"], "ok", "{body}");
+    assert_eq!(mock.hits(Endpoint::Token), 1);
+    assert_eq!(mock.hits(Endpoint::OnboardUser), 1);
+    assert_eq!(bearers(&mock, Endpoint::LoadCodeAssist), ["mock-access-1"]);
+
+    // 2) The onboarded credential streams with the provisioned project.
+    let uri =
+        format!("/antigravity/v1beta/models/{antigravity_model}:streamGenerateContent?alt=sse");
+    let (status, body) = post(&app, &uri, GEMINI_BODY.to_string()).await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert!(body.contains("\"finishReason\":\"STOP\""), "{body}");
+    let sent: Value =
+        serde_json::from_str(&mock.requests(Endpoint::StreamGenerateContent)[0].body).unwrap();
+    assert_eq!(sent["project"], PROJECT_ID);
+
+    // 2b) Model discovery narrows the list to what the credential is offered.
+    let mut report = Value::Null;
+    for _ in 0..50 {
+        report = get_json(&app, "/admin/v1/antigravity/models").await;
+        if !report["refreshed_at"].is_null() {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    }
+    assert_eq!(report["credentials"], 1, "{report}");
+    let counts: Vec<(String, u64, bool)> = report["models"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|m| {
+            let name = m["model"].as_str().unwrap().to_string();
+            (
+                name,
+                m["credentials"].as_u64().unwrap(),
+                m["configured"] == true,
+            )
+        })
+        .collect();
+    assert_eq!(
+        counts,
+        [
+            (antigravity_model.clone(), 1, true),
+            ("mock-retired".to_string(), 0, true),
+            ("mock-unconfigured".to_string(), 1, false),
+        ]
+    );
+    let listed = get_json(&app, "/antigravity/v1beta/models").await;
+    let names: Vec<&str> = listed["models"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|m| m["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(names, [antigravity_model.as_str()]);
+
+    // 3) Gemini CLI: a 429 with RetryInfo is retried on the other credential.
+    let before = mock.hits(Endpoint::StreamGenerateContent);
+    mock.inject(
+        Endpoint::StreamGenerateContent,
+        Fault::RateLimited {
+            retry_after_secs: 600,
+        },
+    );
+    let uri = format!("/geminicli/v1beta/models/{geminicli_model}:streamGenerateContent?alt=sse");
+    let (status, body) = post(&app, &uri, GEMINI_BODY.to_string()).await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert!(body.contains("\"finishReason\":\"STOP\""), "{body}");
+    let first = bearers(&mock, Endpoint::StreamGenerateContent).split_off(before);
+    assert_eq!(first.len(), 2, "{first:?}");
+    assert_ne!(first[0], first[1]);
+
+    // 4) The rate-limited credential stays cooled; later requests skip it.
+    for _ in 0..3 {
+        let (status, _) = post(&app, &uri, GEMINI_BODY.to_string()).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+    let later = bearers(&mock, Endpoint::StreamGenerateContent).split_off(before + 2);
+    assert!(later.iter().all(|bearer| *bearer == first[1]), "{later:?}");
+
+    // 4b) AI Studio clients pinned to `v1alpha` reach the same handler; the
+    //     uncached thought in the history is reported as a miss.
+    let uri = format!("/geminicli/v1alpha/models/{geminicli_model}:generateContent");
+    let history = r#"{"contents":[
+        {"role":"user","parts":[{"text":"hi"}]},
+        {"role":"model","parts":[{"thought":true,"text":"never seen"},{"text":"hello"}]},
+        {"role":"user","parts":[{"text":"again"}]}
+    ]}"#;
+    let resp = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri(uri)
+                .header("content-type", "application/json")
+                .header("x-goog-api-key", KEY)
+                .body(Body::from(history))
+                .expect("failed to build request"),
+        )
+        .await
+        .expect("request failed");
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(
+        resp.headers()["x-pollux-thoughtsig"],
+        "hits=0; misses=1; dropped=0"
+    );
+    let body = to_bytes(resp.into_body(), usize::MAX)
+        .await
+        .expect("read body");
+    assert!(String::from_utf8_lossy(&body).contains(REPLY_TEXT));
+    assert_eq!(mock.hits(Endpoint::GenerateContent), 1);
+
+    // 5) Codex: a 403 is retried and the client never sees it.
+    let request = format!(r#"{{"model":"{codex_model}","input":"hi","stream":false}}"#);
+    mock.inject(Endpoint::CodexResponses, Fault::Forbidden);
+    let (status, body) = post(&app, "/codex/v1/responses", request.clone()).await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert!(body.contains(REPLY_TEXT), "{body}");
+    assert_eq!(mock.hits(Endpoint::CodexResponses), 2);
+
+    // 6) Once both credentials are rate limited the pool runs dry: 503 with
+    //    a Retry-After of the shortest cooldown.
+    for retry_after_secs in [120, 300] {
+        mock.inject(
+            Endpoint::CodexResponses,
+            Fault::RateLimited { retry_after_secs },
+        );
+    }
+    let resp = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/codex/v1/responses")
+                .header("content-type", "application/json")
+                .header("x-goog-api-key", KEY)
+                .body(Body::from(request))
+                .expect("failed to build request"),
+        )
+        .await
+        .expect("request failed");
+    assert_eq!(resp.status(), StatusCode::SERVICE_UNAVAILABLE);
+    let retry_after: u64 = resp.headers()["retry-after"]
+        .to_str()
+        .unwrap()
+        .parse()
+        .unwrap();
+    assert!((100..=121).contains(&retry_after), "{retry_after}");
+    assert_eq!(mock.hits(Endpoint::CodexResponses), 4);
+
+    let _ = std::fs::remove_file(&path);
+}
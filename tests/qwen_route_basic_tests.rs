@@ -0,0 +1,101 @@
+use axum::{
+    Router,
+    body::{Body, to_bytes},
+    http::{Request, StatusCode},
+};
+use serde_json::Value;
+use std::{
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+use tower::ServiceExt;
+
+async fn post(app: &Router, uri: &str, key: Option<&str>, body: &str) -> (StatusCode, Value) {
+    let mut req = Request::builder()
+        .method("POST")
+        .uri(uri)
+        .header("content-type", "application/json");
+    if let Some(key) = key {
+        req = req.header("authorization", format!("Bearer {key}"));
+    }
+    let resp = app
+        .clone()
+        .oneshot(
+            req.body(Body::from(body.to_string()))
+                .expect("failed to build request"),
+        )
+        .await
+        .expect("request failed");
+    let status = resp.status();
+    let bytes = to_bytes(resp.into_body(), usize::MAX)
+        .await
+        .expect("read body");
+    (
+        status,
+        serde_json::from_slice(&bytes).unwrap_or(Value::Null),
+    )
+}
+
+#[tokio::test]
+async fn qwen_chat_route_validates_model_and_answers_in_openai_shape() {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("system time before UNIX_EPOCH")
+        .as_nanos();
+    let mut temp_path = std::env::temp_dir();
+    temp_path.push(format!(
+        "pollux-qwen-basic-{}-{}.sqlite",
+        std::process::id(),
+        nanos
+    ));
+    let database_url = format!("sqlite:{}", temp_path.display());
+    let db = pollux::db::spawn(&database_url).await;
+
+    let mut cfg = pollux::config::Config::default();
+    cfg.basic.pollux_key = "pwd".to_string();
+    // Extractors validate against the global config, so reuse its models.
+    let model = pollux::config::CONFIG.qwen().model_list[0].clone();
+    cfg.providers.qwen.model_list = vec![model.clone()];
+
+    let providers = pollux::providers::Providers::spawn(db, &cfg).await;
+    let state = pollux::server::router::PolluxState::new(
+        providers,
+        Arc::from("pwd"),
+        cfg.basic.insecure_cookie,
+    );
+    let app = pollux::server::router::pollux_router(state);
+
+    let chat = |model: &str| {
+        format!(r#"{{"model":"{model}","messages":[{{"role":"user","content":"hi"}}]}}"#)
+    };
+
+    let (status, _) = post(&app, "/qwen/v1/chat/completions", None, &chat(&model)).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    let (status, body) = post(&app, "/qwen/v1/chat/completions", Some("pwd"), "{").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["error"]["code"], "INVALID_JSON");
+
+    let (status, body) = post(
+        &app,
+        "/qwen/v1/chat/completions",
+        Some("pwd"),
+        &chat("qwen-unknown"),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["error"]["code"], "UNSUPPORTED_MODEL");
+
+    // Known model, but no credentials loaded.
+    let (status, body) = post(
+        &app,
+        "/qwen/v1/chat/completions",
+        Some("pwd"),
+        &chat(&model),
+    )
+    .await;
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(body["error"]["code"], "NO_CREDENTIAL");
+
+    let _ = std::fs::remove_file(&temp_path);
+}
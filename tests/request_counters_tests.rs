@@ -0,0 +1,37 @@
+use axum::http::StatusCode;
+use pollux::server::request_counters::RequestCounters;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[tokio::test]
+async fn request_counters_round_trip_through_the_database() {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("system time before UNIX_EPOCH")
+        .as_nanos();
+    let mut temp_path = std::env::temp_dir();
+    temp_path.push(format!(
+        "pollux-counters-{}-{}.sqlite",
+        std::process::id(),
+        nanos
+    ));
+    let database_url = format!("sqlite:{}", temp_path.display());
+    let db = pollux::db::spawn(&database_url).await;
+
+    let counters = RequestCounters::restore(db.load_request_counters().await.expect("load"));
+    assert!(counters.snapshot().is_empty());
+    counters.record("codex", "gpt-5", StatusCode::OK);
+    counters.record("codex", "gpt-5", StatusCode::SERVICE_UNAVAILABLE);
+    counters.flush(&db).await.expect("flush");
+
+    // Simulated restart: totals resume from what was saved.
+    let restarted = RequestCounters::restore(db.load_request_counters().await.expect("load"));
+    restarted.record("codex", "gpt-5", StatusCode::OK);
+    restarted.flush(&db).await.expect("flush");
+
+    let rows = db.load_request_counters().await.expect("load");
+    assert_eq!(rows.len(), 1);
+    assert_eq!(rows[0].provider, "codex");
+    assert_eq!((rows[0].requests, rows[0].errors), (3, 1));
+
+    let _ = std::fs::remove_file(temp_path);
+}
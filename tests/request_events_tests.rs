@@ -0,0 +1,118 @@
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+};
+use std::{
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+use tower::ServiceExt;
+
+const KEY: &str = "pwd";
+
+#[tokio::test]
+async fn request_events_carry_provider_model_and_status() {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("system time before UNIX_EPOCH")
+        .as_nanos();
+    let mut temp_path = std::env::temp_dir();
+    temp_path.push(format!(
+        "pollux-events-{}-{}.sqlite",
+        std::process::id(),
+        nanos
+    ));
+    let database_url = format!("sqlite:{}", temp_path.display());
+    let db = pollux::db::spawn(&database_url).await;
+
+    let mut cfg = pollux::config::Config::default();
+    cfg.basic.pollux_key = KEY.to_string();
+    let model = pollux::config::CONFIG
+        .codex()
+        .model_list
+        .first()
+        .cloned()
+        .unwrap_or_else(|| "gpt-4o-mini".to_string());
+    cfg.providers.codex.model_list = vec![model.clone()];
+    let providers = pollux::providers::Providers::spawn(db, &cfg).await;
+    let state = pollux::server::router::PolluxState::new(
+        providers,
+        Arc::from(KEY),
+        cfg.basic.insecure_cookie,
+    );
+    let mut events = state.request_events.subscribe();
+    let app = pollux::server::router::pollux_router(state);
+
+    let resp = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/codex/v1/responses")
+                .header("x-goog-api-key", KEY)
+                .header("content-type", "application/json")
+                .body(Body::from(r#"{"model":"no-such-model","input":[]}"#))
+                .expect("failed to build request"),
+        )
+        .await
+        .expect("request failed");
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+    let event = events.try_recv().expect("event published");
+    assert_eq!(event.provider, Some("codex"));
+    assert_eq!(event.model.as_deref(), Some("no-such-model"));
+    assert_eq!(event.status, 400);
+    assert_eq!(event.path, "/codex/v1/responses");
+    assert_eq!(event.request_hash, None);
+
+    // Same payload with different key order/whitespace => same canonical hash.
+    let mut hashes = Vec::new();
+    for body in [
+        format!(r#"{{"model":"{model}","input":[{{"role":"user","content":"hi"}}]}}"#),
+        format!(
+            "{{ \"input\": [ {{ \"content\": \"hi\", \"role\": \"user\" }} ],\n  \"model\": \"{model}\" }}"
+        ),
+    ] {
+        let resp = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/codex/v1/responses/compact")
+                    .header("x-goog-api-key", KEY)
+                    .header("content-type", "application/json")
+                    .body(Body::from(body))
+                    .expect("failed to build request"),
+            )
+            .await
+            .expect("request failed");
+        let header = resp.headers()["x-pollux-request-hash"]
+            .to_str()
+            .unwrap()
+            .to_string();
+        let event = events.try_recv().expect("event published");
+        assert_eq!(event.request_hash.as_deref(), Some(header.as_str()));
+        hashes.push(header);
+    }
+    assert!(hashes[0].starts_with("sha256:"));
+    assert_eq!(hashes[0], hashes[1]);
+
+    let resp = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri("/admin/v1/logs/stream?min_status=400")
+                .header("x-goog-api-key", KEY)
+                .body(Body::empty())
+                .expect("failed to build request"),
+        )
+        .await
+        .expect("request failed");
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(
+        resp.headers()["content-type"].to_str().unwrap(),
+        "text/event-stream"
+    );
+
+    let _ = std::fs::remove_file(temp_path);
+}
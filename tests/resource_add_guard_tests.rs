@@ -0,0 +1,84 @@
+use axum::{
+    Router,
+    body::Body,
+    http::{Request, StatusCode},
+};
+use pollux::config::ResourceAddConfig;
+use std::{
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+use tower::ServiceExt;
+
+async fn upload(app: &Router, uri: &str, key: &str, body: &str) -> StatusCode {
+    app.clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri(uri)
+                .header("x-goog-api-key", key)
+                .header("content-type", "application/json")
+                .body(Body::from(body.to_string()))
+                .expect("failed to build request"),
+        )
+        .await
+        .expect("request failed")
+        .status()
+}
+
+#[tokio::test]
+async fn resource_add_uses_its_own_key_rate_and_batch_limits() {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("system time before UNIX_EPOCH")
+        .as_nanos();
+    let mut temp_path = std::env::temp_dir();
+    temp_path.push(format!(
+        "pollux-resource-add-{}-{}.sqlite",
+        std::process::id(),
+        nanos
+    ));
+    let database_url = format!("sqlite:{}", temp_path.display());
+    let db = pollux::db::spawn(&database_url).await;
+
+    let mut cfg = pollux::config::Config::default();
+    cfg.basic.pollux_key = "pwd".to_string();
+    let providers = pollux::providers::Providers::spawn(db, &cfg).await;
+    let state = pollux::server::router::PolluxState::new(
+        providers,
+        Arc::from("pwd"),
+        cfg.basic.insecure_cookie,
+    )
+    .with_resource_add(&ResourceAddConfig {
+        admin_key: Some("admin".to_string()),
+        requests_per_minute: 2,
+        max_batch: 2,
+    });
+    let app = pollux::server::router::pollux_router(state);
+
+    // The proxy key no longer uploads credentials, and the admin key does not proxy.
+    assert_eq!(
+        upload(&app, "/geminicli/resource:add", "pwd", "[{}]").await,
+        StatusCode::UNAUTHORIZED
+    );
+    assert_eq!(
+        upload(&app, "/codex/v1/responses", "admin", "{}").await,
+        StatusCode::UNAUTHORIZED
+    );
+
+    assert_eq!(
+        upload(&app, "/codex/resource:add", "admin", "[{}, {}, {}]").await,
+        StatusCode::PAYLOAD_TOO_LARGE
+    );
+    // The batch above used the first upload slot; the rate is shared by providers.
+    assert_eq!(
+        upload(&app, "/antigravity/resource:add", "admin", "[{}]").await,
+        StatusCode::ACCEPTED
+    );
+    assert_eq!(
+        upload(&app, "/geminicli/resource:add", "admin", "[{}]").await,
+        StatusCode::TOO_MANY_REQUESTS
+    );
+
+    let _ = std::fs::remove_file(temp_path);
+}
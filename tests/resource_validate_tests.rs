@@ -0,0 +1,94 @@
+use axum::{
+    Json, Router,
+    body::{Body, to_bytes},
+    http::{Request, StatusCode},
+    routing::post,
+};
+use serde_json::{Value, json};
+use std::{
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+use tokio::net::TcpListener;
+use tower::ServiceExt;
+use url::Url;
+
+#[tokio::test]
+async fn validate_mode_reports_each_seed() {
+    // Token endpoint that rejects every refresh token.
+    let token_server = Router::new().route(
+        "/token",
+        post(|| async {
+            (
+                StatusCode::BAD_REQUEST,
+                Json(json!({ "error": "invalid_grant", "error_description": "Bad Request" })),
+            )
+        }),
+    );
+    let listener = TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("bind listener");
+    let addr = listener.local_addr().expect("local addr");
+    tokio::spawn(async move {
+        axum::serve(listener, token_server)
+            .await
+            .expect("server run");
+    });
+
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("system time before UNIX_EPOCH")
+        .as_nanos();
+    let mut temp_path = std::env::temp_dir();
+    temp_path.push(format!(
+        "pollux-resource-validate-{}-{}.sqlite",
+        std::process::id(),
+        nanos
+    ));
+    let database_url = format!("sqlite:{}", temp_path.display());
+    let db = pollux::db::spawn(&database_url).await;
+
+    let mut cfg = pollux::config::Config::default();
+    cfg.basic.pollux_key = "pwd".to_string();
+    cfg.providers.antigravity.oauth_token_url =
+        Url::parse(&format!("http://{addr}/token")).expect("token url");
+    let providers = pollux::providers::Providers::spawn(db, &cfg).await;
+    let state = pollux::server::router::PolluxState::new(
+        providers,
+        Arc::from("pwd"),
+        cfg.basic.insecure_cookie,
+    );
+    let app = pollux::server::router::pollux_router(state);
+
+    let resp = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/antigravity/resource:add?validate=true")
+                .header("x-goog-api-key", "pwd")
+                .header("content-type", "application/json")
+                .body(Body::from(
+                    r#"[{}, {"refresh_token": "rt-1"}, {"refreshToken": " rt-1 "}]"#,
+                ))
+                .expect("failed to build request"),
+        )
+        .await
+        .expect("request failed");
+    assert_eq!(resp.status(), StatusCode::OK);
+    let body: Value = serde_json::from_slice(
+        &to_bytes(resp.into_body(), usize::MAX)
+            .await
+            .expect("read body"),
+    )
+    .expect("json body");
+    let statuses: Vec<&str> = body["results"]
+        .as_array()
+        .expect("results")
+        .iter()
+        .map(|r| r["status"].as_str().expect("status"))
+        .collect();
+    assert_eq!(statuses, ["invalid", "invalid", "duplicate"]);
+    assert!(body["results"][1]["error"].is_string());
+
+    let _ = std::fs::remove_file(&temp_path);
+}
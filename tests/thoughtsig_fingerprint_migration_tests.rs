@@ -0,0 +1,100 @@
+use pollux::config::{ThoughtSigConfig, ThoughtSigStorage};
+use pollux::db::ThoughtSignatureRow;
+use pollux::providers::manifest::ProviderKind;
+use pollux::providers::thoughtsig_store::PersistentSignatureStore;
+use pollux_thoughtsig_core::{
+    CacheKey, FingerprintVersion, Invalidation, SignatureCacheStore, ThoughtSignatureEngine,
+};
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[tokio::test]
+async fn persisted_signatures_migrate_to_a_new_fingerprint_version() {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("system time before UNIX_EPOCH")
+        .as_nanos();
+    let mut temp_path = std::env::temp_dir();
+    temp_path.push(format!(
+        "pollux-thoughtsig-migration-{}-{}.sqlite",
+        std::process::id(),
+        nanos
+    ));
+    let db = pollux::db::spawn(&format!("sqlite:{}", temp_path.display())).await;
+
+    let v1 = ThoughtSigConfig {
+        storage: ThoughtSigStorage::Persistent,
+        ..ThoughtSigConfig::default()
+    };
+    let store = PersistentSignatureStore::load(db.clone(), ProviderKind::GeminiCli, &v1).await;
+    let old = |hash| CacheKey::new(FingerprintVersion::V1, hash);
+    store.insert(old(5), Arc::from("sig_replayed"), Some("gemini-2.5-pro"));
+    store.insert(old(6), Arc::from("sig_abandoned"), None);
+    // Written by a newer build with a version this one does not know.
+    db.record_thought_signature(ThoughtSignatureRow {
+        provider: "geminicli".to_string(),
+        cache_key: 7,
+        key_version: 9,
+        signature: "sig_future".to_string(),
+        model: String::new(),
+        created_at: chrono::Utc::now().timestamp(),
+    });
+
+    // Upgrade: v2 keys, v1 still probed. The unknown version is compacted away.
+    let migrating = ThoughtSigConfig {
+        fingerprint: FingerprintVersion::V2,
+        migrate_from: Some(FingerprintVersion::V1),
+        ..v1
+    };
+    let store =
+        PersistentSignatureStore::load(db.clone(), ProviderKind::GeminiCli, &migrating).await;
+    let rows = db
+        .load_thought_signatures("geminicli", 0, 16)
+        .await
+        .expect("load");
+    assert!(rows.iter().all(|row| row.key_version == 1), "{rows:?}");
+
+    let new = CacheKey::new(FingerprintVersion::V2, 50);
+    assert_eq!(store.rekey(&old(5), new).as_deref(), Some("sig_replayed"));
+    assert!(store.get(&old(5)).is_none());
+    assert_eq!(store.get(&new).as_deref(), Some("sig_replayed"));
+
+    // Through the engine, a v1-keyed entry is found by the text it was keyed on.
+    let engine = ThoughtSignatureEngine::with_store(Arc::new(store.clone()))
+        .with_fingerprint(migrating.fingerprint, migrating.migrate_from);
+    let legacy = engine
+        .legacy_keys()
+        .unwrap()
+        .generate_text("thought")
+        .unwrap();
+    let current = engine.keys().generate_text("thought").unwrap();
+    engine.put_signature(legacy, Arc::from("sig_thought"));
+    assert_eq!(
+        engine.migrate_signature(&legacy, current).as_deref(),
+        Some("sig_thought")
+    );
+
+    // Migration done: v1 rows that were never replayed are dropped, moved
+    // rows keep their model tag.
+    let migrated = ThoughtSigConfig {
+        migrate_from: None,
+        ..migrating
+    };
+    let store =
+        PersistentSignatureStore::load(db.clone(), ProviderKind::GeminiCli, &migrated).await;
+    let mut rows = db
+        .load_thought_signatures("geminicli", 0, 16)
+        .await
+        .expect("load");
+    rows.sort_by_key(|row| row.signature.clone());
+    let kept: Vec<(&str, i16)> = rows
+        .iter()
+        .map(|row| (row.signature.as_str(), row.key_version))
+        .collect();
+    assert_eq!(kept, [("sig_replayed", 2), ("sig_thought", 2)]);
+    assert_eq!(store.get(&new).as_deref(), Some("sig_replayed"));
+    store.invalidate(&Invalidation::Model("gemini-2.5-pro".to_string()));
+    assert!(store.get(&new).is_none());
+
+    let _ = std::fs::remove_file(temp_path);
+}
@@ -0,0 +1,94 @@
+use pollux::config::{ThoughtSigConfig, ThoughtSigStorage};
+use pollux::db::ThoughtSignatureRow;
+use pollux::providers::manifest::ProviderKind;
+use pollux::providers::thoughtsig_store::PersistentSignatureStore;
+use pollux_thoughtsig_core::{CacheKey, FingerprintVersion, Invalidation, SignatureCacheStore};
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+fn key(hash: u64) -> CacheKey {
+    CacheKey::new(FingerprintVersion::V1, hash)
+}
+
+#[tokio::test]
+async fn persisted_signatures_survive_restart_and_expire() {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("system time before UNIX_EPOCH")
+        .as_nanos();
+    let mut temp_path = std::env::temp_dir();
+    temp_path.push(format!(
+        "pollux-thoughtsig-{}-{}.sqlite",
+        std::process::id(),
+        nanos
+    ));
+    let database_url = format!("sqlite:{}", temp_path.display());
+    let db = pollux::db::spawn(&database_url).await;
+
+    let cfg = ThoughtSigConfig {
+        storage: ThoughtSigStorage::Persistent,
+        ttl_secs: 3600,
+        max_capacity: 16,
+        ..ThoughtSigConfig::default()
+    };
+    let store = PersistentSignatureStore::load(db.clone(), ProviderKind::GeminiCli, &cfg).await;
+    store.insert(key(u64::MAX), Arc::from("sig_high_bit"), None);
+    store.insert(key(7), Arc::from("sig_007"), Some("gemini-2.5-pro"));
+
+    // Captured long before the TTL window; must not come back.
+    db.record_thought_signature(ThoughtSignatureRow {
+        provider: "geminicli".to_string(),
+        cache_key: 9,
+        key_version: 1,
+        signature: "sig_stale".to_string(),
+        model: String::new(),
+        created_at: 0,
+    });
+    // Another provider's signatures are kept apart.
+    db.record_thought_signature(ThoughtSignatureRow {
+        provider: "antigravity".to_string(),
+        cache_key: 11,
+        key_version: 1,
+        signature: "sig_other".to_string(),
+        model: String::new(),
+        created_at: chrono::Utc::now().timestamp(),
+    });
+
+    // Simulated restart: a fresh store sees only what was persisted.
+    let restarted = PersistentSignatureStore::load(db.clone(), ProviderKind::GeminiCli, &cfg).await;
+    assert_eq!(
+        restarted.get(&key(u64::MAX)).as_deref(),
+        Some("sig_high_bit")
+    );
+    assert_eq!(restarted.get(&key(7)).as_deref(), Some("sig_007"));
+    assert!(restarted.get(&key(9)).is_none());
+    assert!(restarted.get(&key(11)).is_none());
+
+    let stale = db
+        .load_thought_signatures("geminicli", 0, 16)
+        .await
+        .expect("load");
+    assert_eq!(stale.len(), 2, "expired row should have been pruned");
+
+    // Flushing a model also deletes its persisted rows.
+    let store = PersistentSignatureStore::load(db.clone(), ProviderKind::Antigravity, &cfg).await;
+    store.insert(key(1), Arc::from("sig_pro"), Some("gemini-2.5-pro"));
+    store.insert(key(2), Arc::from("sig_flash"), Some("gemini-2.5-flash"));
+    assert!(store.get(&key(1)).is_some());
+    assert!(store.get(&key(3)).is_none());
+
+    store.invalidate(&Invalidation::Model("gemini-2.5-pro".to_string()));
+    assert!(store.get(&key(1)).is_none());
+    let stats = store.stats();
+    // The restored `sig_other` row plus `sig_flash`.
+    assert_eq!((stats.entries, stats.inserts), (2, 2));
+    assert_eq!((stats.hits, stats.misses), (1, 2));
+
+    let restarted =
+        PersistentSignatureStore::load(db.clone(), ProviderKind::Antigravity, &cfg).await;
+    assert!(restarted.get(&key(1)).is_none());
+    assert_eq!(restarted.get(&key(2)).as_deref(), Some("sig_flash"));
+    assert_eq!(restarted.get(&key(11)).as_deref(), Some("sig_other"));
+
+    let _ = std::fs::remove_file(temp_path);
+}
@@ -0,0 +1,111 @@
+use axum::{
+    Router,
+    body::{Body, to_bytes},
+    http::{Request, StatusCode},
+};
+use serde_json::Value;
+use std::{
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+use tower::ServiceExt;
+
+async fn send(app: &Router, method: &str, uri: &str, body: &str) -> (StatusCode, Value) {
+    let req = Request::builder()
+        .method(method)
+        .uri(uri)
+        .header("content-type", "application/json")
+        .header("x-goog-api-key", "pwd")
+        .body(Body::from(body.to_string()))
+        .expect("failed to build request");
+    let resp = app.clone().oneshot(req).await.expect("request failed");
+    let status = resp.status();
+    let bytes = to_bytes(resp.into_body(), usize::MAX)
+        .await
+        .expect("read body");
+    (
+        status,
+        serde_json::from_slice(&bytes).unwrap_or(Value::Null),
+    )
+}
+
+#[tokio::test]
+async fn v1_routes_list_and_dispatch_by_model() {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("system time before UNIX_EPOCH")
+        .as_nanos();
+    let mut temp_path = std::env::temp_dir();
+    temp_path.push(format!(
+        "pollux-unified-{}-{}.sqlite",
+        std::process::id(),
+        nanos
+    ));
+    let database_url = format!("sqlite:{}", temp_path.display());
+    let db = pollux::db::spawn(&database_url).await;
+
+    let mut cfg = pollux::config::Config::default();
+    cfg.basic.pollux_key = "pwd".to_string();
+    // Extractors validate against the global config, so reuse its models.
+    let gemini = pollux::config::CONFIG.geminicli().model_list[0].clone();
+    let codex = pollux::config::CONFIG.codex().model_list[0].clone();
+    let claude = pollux::config::CONFIG.claude().model_list[0].clone();
+    let qwen = pollux::config::CONFIG.qwen().model_list[0].clone();
+    cfg.providers.geminicli.model_list = vec![gemini.clone()];
+    cfg.providers.codex.model_list = vec![codex.clone()];
+    cfg.providers.qwen.model_list = vec![qwen.clone()];
+    cfg.providers.antigravity.model_list = vec![gemini.clone(), "ag-only".to_string()];
+
+    let providers = pollux::providers::Providers::spawn(db, &cfg).await;
+    let state = pollux::server::router::PolluxState::new(
+        providers,
+        Arc::from("pwd"),
+        cfg.basic.insecure_cookie,
+    );
+    let app = pollux::server::router::pollux_router(state);
+
+    let (status, body) = send(&app, "GET", "/v1/models", "").await;
+    assert_eq!(status, StatusCode::OK);
+    let owners: Vec<(&str, &str)> = body["data"]
+        .as_array()
+        .expect("model list")
+        .iter()
+        .map(|m| (m["id"].as_str().unwrap(), m["owned_by"].as_str().unwrap()))
+        .collect();
+    assert_eq!(
+        owners,
+        [
+            (gemini.as_str(), "geminicli"),
+            (codex.as_str(), "codex"),
+            ("ag-only", "antigravity"),
+            (claude.as_str(), "claude"),
+            (qwen.as_str(), "qwen"),
+        ]
+    );
+
+    // Known models reach their provider, which has no credentials.
+    let chat = |model: &str| {
+        format!(r#"{{"model":"{model}","messages":[{{"role":"user","content":"hi"}}]}}"#)
+    };
+    let (status, _) = send(&app, "POST", "/v1/chat/completions", &chat(&gemini)).await;
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+    let (status, _) = send(&app, "POST", "/v1/chat/completions", &chat(&codex)).await;
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+    let (status, _) = send(&app, "POST", "/v1/chat/completions", &chat(&qwen)).await;
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+
+    // Models without a provider for the endpoint are rejected up front.
+    let (status, body) = send(&app, "POST", "/v1/chat/completions", &chat("ag-only")).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["error"]["code"], "UNSUPPORTED_MODEL");
+    let (status, _) = send(
+        &app,
+        "POST",
+        "/v1/responses",
+        &format!(r#"{{"model":"{gemini}","input":"hi"}}"#),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let _ = std::fs::remove_file(&temp_path);
+}
@@ -0,0 +1,154 @@
+#![allow(clippy::too_many_lines)]
+use axum::{
+    body::{Body, to_bytes},
+    http::{Request, StatusCode},
+};
+use pollux::db::{UsageQuery, UsageRecord};
+use serde_json::Value;
+use std::{
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+use tower::ServiceExt;
+
+const KEY: &str = "pwd";
+
+fn record(credential_id: Option<i64>, prompt: i64, output: i64, status: i32) -> UsageRecord {
+    UsageRecord {
+        created_at: chrono::Utc::now(),
+        provider: "geminicli".to_string(),
+        model: "gemini-2.5-pro".to_string(),
+        credential_id,
+        prompt_tokens: prompt,
+        output_tokens: output,
+        latency_ms: 100,
+        status,
+    }
+}
+
+#[tokio::test]
+async fn usage_rows_are_aggregated_per_credential_and_exposed_to_admin() {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("system time before UNIX_EPOCH")
+        .as_nanos();
+    let mut temp_path = std::env::temp_dir();
+    temp_path.push(format!(
+        "pollux-usage-{}-{}.sqlite",
+        std::process::id(),
+        nanos
+    ));
+    let database_url = format!("sqlite:{}", temp_path.display());
+    let db = pollux::db::spawn(&database_url).await;
+
+    db.record_usage(record(Some(1), 10, 20, 200));
+    db.record_usage(record(Some(1), 5, 30, 200));
+    db.record_usage(record(Some(2), 7, 0, 429));
+
+    let rows = db
+        .usage_summary(UsageQuery::default())
+        .await
+        .expect("usage summary");
+    assert_eq!(rows.len(), 2);
+    assert_eq!(rows[0].credential_id, Some(1));
+    assert_eq!(rows[0].requests, 2);
+    assert_eq!(rows[0].prompt_tokens, 15);
+    assert_eq!(rows[0].output_tokens, 50);
+    assert_eq!(rows[0].errors, 0);
+    assert_eq!(rows[1].credential_id, Some(2));
+    assert_eq!(rows[1].errors, 1);
+
+    let future = UsageQuery {
+        since: Some(chrono::Utc::now() + chrono::Duration::hours(1)),
+        provider: None,
+    };
+    assert!(db.usage_summary(future).await.expect("summary").is_empty());
+
+    let per_model = db
+        .model_usage_stats(chrono::Utc::now() - chrono::Duration::hours(1))
+        .await
+        .expect("model usage stats");
+    assert_eq!(per_model.len(), 1);
+    assert_eq!(per_model[0].requests, 3);
+    assert_eq!(per_model[0].rate_limited, 1);
+    assert_eq!(per_model[0].unavailable, 0);
+
+    let mut cfg = pollux::config::Config::default();
+    cfg.basic.pollux_key = KEY.to_string();
+    let model = pollux::config::CONFIG
+        .codex()
+        .model_list
+        .first()
+        .cloned()
+        .unwrap_or_else(|| "gpt-4o-mini".to_string());
+    cfg.providers.codex.model_list = vec![model.clone()];
+    let providers = pollux::providers::Providers::spawn(db, &cfg).await;
+    let state = pollux::server::router::PolluxState::new(
+        providers,
+        Arc::from(KEY),
+        cfg.basic.insecure_cookie,
+    );
+    let app = pollux::server::router::pollux_router(state);
+
+    // No Codex credentials => 503, still accounted without a credential id.
+    let resp = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/codex/v1/responses")
+                .header("x-goog-api-key", KEY)
+                .header("content-type", "application/json")
+                .body(Body::from(format!(r#"{{"model":"{model}","input":[]}}"#)))
+                .expect("failed to build request"),
+        )
+        .await
+        .expect("request failed");
+    assert_eq!(resp.status(), StatusCode::SERVICE_UNAVAILABLE);
+    drop(resp);
+
+    let resp = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri("/admin/v1/usage?provider=codex")
+                .header("x-goog-api-key", KEY)
+                .body(Body::empty())
+                .expect("failed to build request"),
+        )
+        .await
+        .expect("request failed");
+    assert_eq!(resp.status(), StatusCode::OK);
+    let bytes = to_bytes(resp.into_body(), usize::MAX)
+        .await
+        .expect("read body");
+    let body: Value = serde_json::from_slice(&bytes).expect("json body");
+    let usage = body["usage"].as_array().expect("usage array");
+    assert_eq!(usage.len(), 1);
+    assert_eq!(usage[0]["provider"], "codex");
+    assert_eq!(usage[0]["model"], model);
+    assert!(usage[0]["credential_id"].is_null());
+    assert_eq!(usage[0]["errors"], 1);
+
+    let resp = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri("/admin/v1/recommendations?window_hours=1")
+                .header("x-goog-api-key", KEY)
+                .body(Body::empty())
+                .expect("failed to build request"),
+        )
+        .await
+        .expect("request failed");
+    assert_eq!(resp.status(), StatusCode::OK);
+    let bytes = to_bytes(resp.into_body(), usize::MAX)
+        .await
+        .expect("read body");
+    let body: Value = serde_json::from_slice(&bytes).expect("json body");
+    assert_eq!(body["window_hours"], 1);
+    // Too few requests recorded to draw conclusions.
+    assert_eq!(body["recommendations"], serde_json::json!([]));
+
+    let _ = std::fs::remove_file(temp_path);
+}